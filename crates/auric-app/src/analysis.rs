@@ -0,0 +1,239 @@
+//! Audio analysis of library tracks: tempo, loudness and silence.
+//!
+//! Each runs as a background job that decodes the local tracks not analyzed
+//! yet, or changed since. Tempo comes from the onset autocorrelation in
+//! `auric-drift` and fills the BPM column; loudness is measured to EBU R128
+//! and evens out playback where files carry no ReplayGain tags; the silence
+//! at either end of a track is skipped on playback. Files that cannot be
+//! decoded, and Subsonic tracks, are recorded without a result so later runs
+//! skip them until the file changes.
+
+use crate::PlaybackConfig;
+use anyhow::Result;
+use auric_audio::player::PlaySpan;
+use auric_drift::analyzer::DriftAnalyzer;
+use auric_drift::loudness::{self, Loudness};
+use auric_drift::silence;
+use auric_library::db::{Database, TrackLoudness, TrackRow, TrackSilence};
+use std::path::Path;
+
+/// Work for the background thread; see [`crate::engine::BackgroundJob`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    Bpm,
+    Loudness,
+    /// Silence at `threshold_db`, which the job has to carry since it runs
+    /// without the config.
+    Silence {
+        threshold_db: i16,
+    },
+}
+
+impl Job {
+    /// Runs the job against `db`, returning the status line to show.
+    /// `progress` gets a line after each batch.
+    pub fn run(self, db: &mut Database, progress: &dyn Fn(String)) -> Result<String> {
+        match self {
+            Self::Bpm => analyze_bpm(db, progress),
+            Self::Loudness => analyze_loudness(db, progress),
+            Self::Silence { threshold_db } => analyze_silence(db, threshold_db, progress),
+        }
+    }
+}
+
+/// The `normalization` setting's values.
+pub const NORMALIZATION_MODES: &[&str] = &["off", "track", "album"];
+/// Tracks decoded in parallel before their results are written.
+const BATCH_SIZE: usize = 32;
+
+/// Estimates the tempo of every track that still needs one.
+pub fn analyze_bpm(db: &mut Database, progress: &dyn Fn(String)) -> Result<String> {
+    let analyzer = DriftAnalyzer::new();
+    let total = db.count_tracks_needing_analysis()?;
+    let (analyzed, failed) = run_batches(
+        db,
+        Database::tracks_needing_analysis,
+        |paths| analyzer.analyze_bpm_batch(paths),
+        |db, id, bpm| Ok(db.set_track_bpm(id, bpm)?),
+        |done| progress(format!("Analyzing track tempos... {done}/{total}")),
+    )?;
+    Ok(match (analyzed, failed) {
+        (0, 0) => "Every track already has a BPM".to_string(),
+        (n, 0) => format!("Analyzed the tempo of {n} tracks"),
+        (n, failed) => format!("Analyzed the tempo of {n} tracks ({failed} could not be analyzed)"),
+    })
+}
+
+/// Measures the loudness of every track that still needs it.
+pub fn analyze_loudness(db: &mut Database, progress: &dyn Fn(String)) -> Result<String> {
+    let total = db.count_tracks_needing_loudness()?;
+    let (measured, failed) = run_batches(
+        db,
+        Database::tracks_needing_loudness,
+        loudness::measure_batch,
+        |db, id, found: Option<loudness::FileLoudness>| {
+            let record = found.map(|found| TrackLoudness {
+                integrated_lufs: found.loudness.integrated_lufs,
+                true_peak_db: found.loudness.true_peak_db,
+                replaygain_track_db: found.replaygain_track_db,
+                replaygain_album_db: found.replaygain_album_db,
+            });
+            Ok(db.set_track_loudness(id, record.as_ref())?)
+        },
+        |done| progress(format!("Measuring loudness... {done}/{total}")),
+    )?;
+    Ok(match (measured, failed) {
+        (0, 0) => "Every track already has its loudness measured".to_string(),
+        (n, 0) => format!("Measured the loudness of {n} tracks"),
+        (n, failed) => {
+            format!("Measured the loudness of {n} tracks ({failed} could not be measured)")
+        }
+    })
+}
+
+/// Finds the silence at either end of every track not looked at with
+/// `threshold_db` yet.
+pub fn analyze_silence(
+    db: &mut Database,
+    threshold_db: i16,
+    progress: &dyn Fn(String),
+) -> Result<String> {
+    let total = db.count_tracks_needing_silence(threshold_db)?;
+    let (measured, failed) = run_batches(
+        db,
+        |db, limit| db.tracks_needing_silence(threshold_db, limit),
+        |paths| silence::measure_batch(paths, f32::from(threshold_db)),
+        |db, id, found: Option<silence::Silence>| {
+            let record = found.map(|found| TrackSilence {
+                leading_ms: found.leading_ms as i64,
+                trailing_ms: found.trailing_ms as i64,
+                duration_ms: found.duration_ms as i64,
+            });
+            Ok(db.set_track_silence(id, threshold_db, record.as_ref())?)
+        },
+        |done| progress(format!("Finding silence... {done}/{total}")),
+    )?;
+    Ok(match (measured, failed) {
+        (0, 0) => "Every track already has its silence found".to_string(),
+        (n, 0) => format!("Found the silence in {n} tracks"),
+        (n, failed) => format!("Found the silence in {n} tracks ({failed} could not be decoded)"),
+    })
+}
+
+/// Takes batches from `next` until none are left, measuring the local
+/// tracks with `measure` and storing every result, failures as `None`.
+/// Returns how many tracks were measured and how many failed.
+fn run_batches<T, E: std::fmt::Display>(
+    db: &Database,
+    next: impl Fn(&Database, usize) -> Result<Vec<TrackRow>, auric_library::db::DbError>,
+    measure: impl Fn(&[&Path]) -> Vec<Result<T, E>>,
+    store: impl Fn(&Database, &str, Option<T>) -> Result<()>,
+    progress: impl Fn(usize),
+) -> Result<(usize, usize)> {
+    let (mut measured, mut failed) = (0usize, 0usize);
+    loop {
+        let batch = next(db, BATCH_SIZE)?;
+        if batch.is_empty() {
+            break;
+        }
+        let is_local = |track: &TrackRow| !track.path.starts_with(crate::subsonic::PATH_PREFIX);
+        let paths = batch
+            .iter()
+            .filter(|track| is_local(track))
+            .map(|track| Path::new(&track.path))
+            .collect::<Vec<_>>();
+        let mut results = measure(&paths).into_iter();
+        for track in &batch {
+            let result = is_local(track)
+                .then(|| results.next())
+                .flatten()
+                .and_then(|result| {
+                    result
+                        .map_err(|err| {
+                            tracing::debug!(path = %track.path, error = %err, "track analysis failed");
+                        })
+                        .ok()
+                });
+            match result {
+                Some(_) => measured += 1,
+                None => failed += 1,
+            }
+            store(db, &track.id.0.to_string(), result)?;
+        }
+        progress(measured + failed);
+    }
+    Ok((measured, failed))
+}
+
+/// The gain in dB that normalization applies to `track_id`, or 0 when it is
+/// off or the track has not been measured. ReplayGain tags win over the
+/// measurement, and the gain never pushes the measured true peak past full
+/// scale.
+pub fn normalization_db(
+    db: &Database,
+    config: &PlaybackConfig,
+    track_id: &str,
+    album: Option<(&str, &str)>,
+) -> Result<f32> {
+    let mode = config.normalization.as_str();
+    if mode == "off" {
+        return Ok(0.0);
+    }
+    let Some(track) = db.track_loudness(track_id)? else {
+        return Ok(0.0);
+    };
+    let track_loudness = Loudness {
+        integrated_lufs: track.integrated_lufs,
+        true_peak_db: track.true_peak_db,
+    };
+    let measured = match (mode, album) {
+        ("album", Some((album, artist))) => {
+            let parts = db
+                .list_album_loudness(album, artist)?
+                .into_iter()
+                .map(|(part, duration_ms)| {
+                    let loudness = Loudness {
+                        integrated_lufs: part.integrated_lufs,
+                        true_peak_db: part.true_peak_db,
+                    };
+                    (loudness, duration_ms.unwrap_or(1).max(1) as f64)
+                })
+                .collect::<Vec<_>>();
+            loudness::combine(&parts).unwrap_or(track_loudness)
+        }
+        _ => track_loudness,
+    };
+    let tagged = match mode {
+        "album" => track.replaygain_album_db.or(track.replaygain_track_db),
+        _ => track.replaygain_track_db,
+    };
+    let target = f32::from(config.normalization_target_lufs);
+    let gain = tagged
+        .map(|gain| gain + target - loudness::REFERENCE_LUFS)
+        .unwrap_or(target - measured.integrated_lufs);
+    Ok(gain.min(-measured.true_peak_db))
+}
+
+/// The part of `track_id` to play with its silence trimmed: the whole track
+/// when trimming is off, the track has not been looked at with the current
+/// threshold, or its silence is shorter than `silence_min_ms`.
+pub fn trim_span(db: &Database, config: &PlaybackConfig, track_id: &str) -> Result<PlaySpan> {
+    if !config.trim_silence {
+        return Ok(PlaySpan::default());
+    }
+    let Some(found) = db.track_silence(track_id, config.silence_threshold_db)? else {
+        return Ok(PlaySpan::default());
+    };
+    let trimmed =
+        |silence_ms: i64| silence_ms > 0 && silence_ms >= i64::from(config.silence_min_ms);
+    let start_ms = if trimmed(found.leading_ms) {
+        found.leading_ms
+    } else {
+        0
+    };
+    let end_ms = trimmed(found.trailing_ms).then_some(found.duration_ms - found.trailing_ms);
+    Ok(PlaySpan {
+        start_ms: start_ms.max(0) as u64,
+        end_ms: end_ms.map(|end_ms| end_ms.max(start_ms) as u64),
+    })
+}
@@ -0,0 +1,237 @@
+//! `auric --daemon`: the library, watcher, queue and player without the
+//! terminal UI, driven only through the control socket, MPD and HTTP servers.
+//!
+//! Requests go through the same [`RemoteInbox`] the shell drains and are
+//! handled by [`engine::handle`] or the shell's palette commands, so a client
+//! cannot tell which front end it is talking to.
+
+use crate::engine::{self, persist_playback_state, spawn_background_scan, JobProgress, Request};
+use crate::{http_api, mpd, remote, watcher_from_config, BootstrappedApp, WatchOptionsOverrides};
+use anyhow::{bail, Result};
+use auric_audio::player::PlayerEvent;
+use auric_core::FeatureId;
+use auric_library::db::{Database, DatabaseOptions};
+use auric_library::watch::WatchRename;
+use auric_ui::remote_inbox::{RemoteInbox, RemoteResult};
+use auric_ui::shell::KeyAction;
+use auric_ui::terminal_guard;
+use std::env;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// How often the loop polls the player, the inbox and running scans.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Runs until SIGINT, SIGTERM or SIGHUP, then saves the session.
+pub fn run(app: &mut BootstrappedApp) -> Result<()> {
+    let inbox = RemoteInbox::new();
+    let db_options = app.config.database.to_options(&env::current_dir()?)?;
+    let (state_queries_tx, state_queries) = remote::query_channel();
+
+    let socket = remote::socket_path();
+    let remote_server = match remote::RemoteServer::start(&socket, inbox.clone()) {
+        Ok(server) => {
+            tracing::info!("control socket at {}", socket.display());
+            Some(server)
+        }
+        Err(err) => {
+            tracing::warn!("remote control disabled: {err:#}");
+            None
+        }
+    };
+    let mpd_server = if app.config.mpd.enabled {
+        mpd::MpdServer::start(&app.config.mpd, state_queries_tx.clone(), inbox.clone())
+            .map_err(|err| tracing::error!("MPD server disabled: {err:#}"))
+            .ok()
+    } else {
+        None
+    };
+    #[cfg(target_os = "linux")]
+    let _mpris_server = crate::mpris::MprisServer::start(state_queries_tx.clone(), inbox.clone())
+        .map_err(|err| tracing::info!("MPRIS disabled: {err:#}"))
+        .ok();
+    let http_server = if app.config.http.enabled {
+        http_api::HttpServer::start(
+            &app.config.http,
+            Some(db_options.clone()),
+            state_queries_tx,
+            inbox.clone(),
+        )
+        .map_err(|err| tracing::error!("HTTP API disabled: {err:#}"))
+        .ok()
+    } else {
+        None
+    };
+    if remote_server.is_none() && mpd_server.is_none() && http_server.is_none() {
+        bail!("no control interface could be started; see the log for details");
+    }
+
+    app.suspend.listen();
+
+    let mut scans = Vec::new();
+    let (rename_tx, renames) = mpsc::channel();
+    if app.feature_registry.is_enabled(FeatureId::WatchedFolders) {
+        spawn_watcher(app, db_options.clone(), rename_tx);
+    }
+    for (job, message) in engine::startup_jobs(app) {
+        engine::queue_job(app, job, message);
+    }
+    terminal_guard::install_signal_handlers()?;
+    println!("auric daemon running; stop it with Ctrl-C or SIGTERM.");
+
+    while !terminal_guard::shutdown_requested() {
+        let finished = engine::tick(app, Instant::now())
+            .iter()
+            .any(|event| matches!(event, PlayerEvent::TrackFinished));
+        if finished {
+            if let Err(err) = engine::handle(app, Request::Playback(engine::Action::Advance)) {
+                tracing::error!("failed to advance to the next track: {err:#}");
+            }
+        }
+        for rename in renames.try_iter() {
+            if let Err(err) = engine::apply_rename(app, &rename) {
+                tracing::warn!("failed to update the queue after a rename: {err:#}");
+            }
+        }
+        remote::answer_queries(&state_queries, &app.playback_state);
+        for request in inbox.drain() {
+            let result = handle_request(app, request.action);
+            let _ = request.reply.send(result);
+        }
+        start_jobs(app, &db_options, &mut scans);
+        scans.retain(poll_scan);
+        std::thread::sleep(TICK);
+    }
+
+    tracing::info!("daemon shutting down");
+    persist_playback_state(app)?;
+    engine::flush_writes(app)
+}
+
+/// Watches the saved roots on a thread with its own connection. The watcher
+/// has no stop signal, so the thread simply ends with the process. Renames are
+/// sent back so the queue can follow them.
+fn spawn_watcher(
+    app: &BootstrappedApp,
+    db_options: DatabaseOptions,
+    renames: mpsc::Sender<WatchRename>,
+) {
+    let service = watcher_from_config(
+        &app.config.library,
+        WatchOptionsOverrides {
+            prune_override: false,
+            watched_only: true,
+            scan_on_start: false,
+            run_for_ms: None,
+        },
+    )
+    .with_rename_sender(renames);
+    std::thread::spawn(move || {
+        let result = Database::open(&db_options)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut db| Ok(service.watch_saved_roots(&mut db)?));
+        if let Err(err) = result {
+            tracing::error!("folder watcher stopped: {err:#}");
+        }
+    });
+}
+
+/// Handles one request from the inbox the way the shell's run loop would.
+fn handle_request(app: &mut BootstrappedApp, action: KeyAction) -> RemoteResult {
+    let request = match action {
+        KeyAction::Continue => Request::Status,
+        KeyAction::Playback(action) => Request::Playback(action.into()),
+        KeyAction::CommandSubmitted(input) => {
+            return crate::execute_ui_palette_command(app, &input)
+                .map(|result| result.status_message)
+                .map_err(|err| format!("{err:#}"));
+        }
+        other => {
+            return Err(format!(
+                "{other:?} is not available without the terminal UI"
+            ))
+        }
+    };
+    let reply = engine::handle(app, request).map_err(|err| format!("{err:#}"))?;
+    Ok(reply.message().to_string())
+}
+
+/// Starts every queued background job. Without a screen to report on they
+/// run side by side rather than one at a time.
+fn start_jobs(
+    app: &mut BootstrappedApp,
+    db_options: &DatabaseOptions,
+    scans: &mut Vec<Receiver<JobProgress>>,
+) {
+    while let Some((job, message)) = engine::next_job(app) {
+        tracing::info!("{message}");
+        scans.push(spawn_background_scan(
+            app.config.library.clone(),
+            db_options.clone(),
+            app.config.hooks.clone(),
+            app.config.subsonic.clone(),
+            job,
+        ));
+    }
+}
+
+/// Logs whatever a background scan has reported; returns whether it is still
+/// running.
+fn poll_scan(scan: &Receiver<JobProgress>) -> bool {
+    loop {
+        match scan.try_recv() {
+            Ok(JobProgress::Progress { .. } | JobProgress::Status { .. }) => {}
+            Ok(JobProgress::Done { message }) => {
+                tracing::info!("{message}");
+                return false;
+            }
+            Ok(JobProgress::Error { message }) => {
+                tracing::error!("scan failed: {message}");
+                return false;
+            }
+            Err(TryRecvError::Empty) => return true,
+            Err(TryRecvError::Disconnected) => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bootstrap_from_config_path;
+    use auric_ui::PlaybackAction;
+
+    #[test]
+    fn requests_are_answered_without_a_shell() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("default.toml");
+        let db_path = dir.path().join("auric.db");
+        std::fs::write(
+            &config_path,
+            format!("[database]\npath = {:?}\n", db_path.display().to_string()),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&config_path).unwrap();
+        let db_options = app.config.database.to_options(dir.path()).unwrap();
+        let mut scans = Vec::new();
+
+        let status = handle_request(&mut app, KeyAction::Continue);
+        assert_eq!(status, Ok("stopped".to_string()));
+
+        let paused = handle_request(&mut app, KeyAction::Playback(PlaybackAction::Pause));
+        assert_eq!(paused, Ok("Not playing".to_string()));
+
+        let music = dir.path().join("music");
+        std::fs::create_dir(&music).unwrap();
+        let added = handle_request(
+            &mut app,
+            KeyAction::CommandSubmitted(format!("__add_root {}", music.display())),
+        );
+        assert!(added.is_ok(), "{added:?}");
+        start_jobs(&mut app, &db_options, &mut scans);
+        assert_eq!(scans.len(), 1);
+
+        let quit = handle_request(&mut app, KeyAction::Quit);
+        assert!(quit.is_err());
+    }
+}
@@ -0,0 +1,2611 @@
+//! The player and library core shared by every front end.
+//!
+//! The terminal shell, the daemon and the remote servers all drive playback
+//! through [`handle`] and advance it with [`tick`], so queue, session and
+//! hook behaviour is defined once here and can be tested without a terminal.
+
+use crate::{
+    analysis, feature_setting_key, format_clock, health, hooks, or_default_logged, podcast, remote,
+    scanner_from_config, subsonic, suspend::SleepSignal, BootstrappedApp, HooksConfig,
+    LibraryConfig, SubsonicConfig, PLAYBACK_STATE_SETTING_KEY,
+};
+use anyhow::{bail, Context, Result};
+use auric_audio::player::{PlaySpan, PlayerEvent};
+use auric_core::{
+    AppCommand, AppEvent, FeatureId, FeatureState, PlaybackQueueEntry, PlaybackState,
+    PlaybackStatus, RepeatMode, TrackId,
+};
+use auric_library::db::{Database, DatabaseOptions, OrphanCleanup, TrackResumeRow};
+use auric_library::scan::{root_looks_offline, DirectoryScanner, ReconcileSummary, ScanSummary};
+use auric_library::watch::WatchRename;
+use serde_json::json;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+/// A request to the core from any front end.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// The playback status line, without changing anything.
+    Status,
+    Playback(Action),
+}
+
+/// A playback or queue change asked for by a front end.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    PlayTrack {
+        track_index: usize,
+    },
+    TogglePause,
+    /// Pause only if playing; used by remote control.
+    Pause,
+    /// Resume or start the current track unless already playing.
+    Resume,
+    Stop,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    SetVolume {
+        volume: f32,
+    },
+    ToggleShuffle,
+    /// Queue a random album whenever the current one finishes.
+    ToggleRandomAlbum,
+    /// Keep the queue going with similar artists when it runs out.
+    ToggleRadio,
+    /// Take tracks off the queue once they have played.
+    ToggleConsume,
+    Seek {
+        position_ms: u64,
+    },
+    /// Add a library track to the end of the queue, starting it if nothing
+    /// plays. `requested_by` names the guest for party mode's cooldown.
+    Enqueue {
+        path: String,
+        requested_by: String,
+    },
+    /// Move on after the current track finished. Unlike `Next` it is not a
+    /// guest's choice, so party mode allows it.
+    Advance,
+}
+
+/// The core's answer to a request: a status line, and whether playback or
+/// the queue changed so views showing them should refresh.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    Changed(String),
+    Unchanged(String),
+}
+
+impl Reply {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Changed(message) | Self::Unchanged(message) => message,
+        }
+    }
+
+    pub fn changed(&self) -> bool {
+        matches!(self, Self::Changed(_))
+    }
+}
+
+/// What a background job reports on its channel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobProgress {
+    /// A scan has `discovered` tracks in the library so far.
+    Progress {
+        discovered: usize,
+        path: String,
+    },
+    /// A status line from a job that is not a scan.
+    Status {
+        message: String,
+    },
+    Done {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Handles one request. Background jobs it asks for wait in [`next_job`] for
+/// the caller to start with [`spawn_background_scan`].
+pub fn handle(app: &mut BootstrappedApp, request: Request) -> Result<Reply> {
+    match request {
+        Request::Status => Ok(Reply::Unchanged(remote::playback_summary(
+            &app.playback_state,
+        ))),
+        Request::Playback(action) => handle_playback_action(app, action),
+    }
+}
+
+/// One turn of the core's loop: autosaves the session if due, applies
+/// player events to it and returns them for the front end to display.
+/// Errors are logged rather than returned, and a finished track runs the
+/// `track_end` hook but does not advance; the caller decides what plays next.
+pub fn tick(app: &mut BootstrappedApp, now: Instant) -> Vec<PlayerEvent> {
+    if let Err(err) = autosave_if_due(app, now) {
+        tracing::warn!("autosave failed: {err:#}");
+    }
+    for signal in app.suspend.poll(now) {
+        match signal {
+            SleepSignal::Sleeping => {
+                pause_for_sleep(app);
+                if let Err(err) = flush_writes(app) {
+                    tracing::warn!("failed to save before sleep: {err:#}");
+                }
+                app.suspend.ready_to_sleep();
+            }
+            SleepSignal::Woke => reopen_after_sleep(app),
+        }
+    }
+    crate::radio::poll(app);
+    let mut events = app.player.poll_events();
+    events.retain(|event| match event {
+        PlayerEvent::Position { position_ms, .. } => {
+            app.playback_state.session.position_ms = *position_ms;
+            app.session_dirty = true;
+            if now.saturating_duration_since(app.last_position_save) >= POSITION_SAVE_INTERVAL {
+                save_position(app, *position_ms, false);
+                app.last_position_save = now;
+            }
+            true
+        }
+        PlayerEvent::TrackFinished => {
+            save_position(app, 0, true);
+            if let Some(entry) = app.playback_state.current_entry() {
+                // A no-op for podcast episodes, which are not library tracks.
+                let track_id = entry.track_id.0.to_string();
+                match &app.writer {
+                    Some(writer) => writer.record_track_play(&track_id),
+                    None => {
+                        if let Err(err) = app.db.record_track_play(&track_id) {
+                            tracing::warn!(error = %err, "failed to count the play");
+                        }
+                    }
+                }
+            }
+            hooks::run(
+                &app.config.hooks,
+                hooks::HookEvent::TrackEnd,
+                hooks::track_env(&app.playback_state),
+            );
+            // Nothing is left to save when the next track is picked.
+            app.playback_state.session.position_ms = 0;
+            true
+        }
+        PlayerEvent::Error { message } => {
+            tracing::error!("playback error: {message}");
+            false
+        }
+        PlayerEvent::DeviceLost { position_ms, .. } => {
+            app.playback_state.session.status = PlaybackStatus::Paused;
+            app.playback_state.session.position_ms = *position_ms;
+            app.session_dirty = true;
+            save_position(app, *position_ms, false);
+            true
+        }
+        PlayerEvent::DeviceRestored { .. } => {
+            app.playback_state.session.status = PlaybackStatus::Playing;
+            app.session_dirty = true;
+            true
+        }
+        _ => true,
+    });
+    events
+}
+
+/// Pauses before the system sleeps, so nothing plays on into a stream that
+/// will not survive it.
+fn pause_for_sleep(app: &mut BootstrappedApp) {
+    if app.playback_state.session.status != PlaybackStatus::Playing {
+        return;
+    }
+    tracing::info!("system is going to sleep; pausing");
+    let before = hooks::PlayerMark::of(&app.playback_state);
+    save_position(app, app.playback_state.session.position_ms, false);
+    app.player.pause();
+    app.playback_state.session.status = PlaybackStatus::Paused;
+    if let Some(event) = before.transition(&hooks::PlayerMark::of(&app.playback_state)) {
+        hooks::run(
+            &app.config.hooks,
+            event,
+            hooks::track_env(&app.playback_state),
+        );
+    }
+    if let Err(err) = persist_playback_state(app) {
+        tracing::warn!("failed to save the session before sleep: {err:#}");
+    }
+}
+
+/// After the system wakes, rebuilds the audio stream where the track
+/// stopped, paused. Sleep that went unannounced pauses here instead.
+fn reopen_after_sleep(app: &mut BootstrappedApp) {
+    if app.playback_state.current_entry().is_none()
+        || app.playback_state.session.status == PlaybackStatus::Stopped
+    {
+        return;
+    }
+    pause_for_sleep(app);
+    tracing::info!("system woke up; reopening the audio stream");
+    app.player.reopen();
+}
+
+/// How often the resume position of the playing track or episode is saved.
+const POSITION_SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Records where the current queue entry stopped: a podcast episode's
+/// progress, or a library track's resume position when it remembers one. A
+/// finished track starts from the beginning next time. Saved through the
+/// background writer; read positions back after [`flush_writes`].
+fn save_position(app: &BootstrappedApp, position_ms: u64, finished: bool) {
+    let Some(entry) = app.playback_state.current_entry() else {
+        return;
+    };
+    let id = entry.track_id.0.to_string();
+    let remember = remembers_position(app, entry);
+    match &app.writer {
+        Some(writer) => writer.save_playback_position(&id, position_ms as i64, finished, remember),
+        None => {
+            if let Err(err) =
+                app.db
+                    .save_playback_position(&id, position_ms as i64, finished, remember)
+            {
+                tracing::warn!(error = %err, "failed to save the playback position");
+            }
+        }
+    }
+}
+
+/// Waits for queued position saves before one is read back, in case it is
+/// the track or episode that was just left.
+fn flush_positions(app: &BootstrappedApp) {
+    if let Err(err) = flush_writes(app) {
+        tracing::warn!(error = %err, "failed to save the playback position");
+    }
+}
+
+/// Whether a queue entry saves its position and resumes there: set per
+/// track with `remember`, or automatic for tracks of at least
+/// `[playback] resume_min_minutes` and for tracks under `resume_folders`.
+pub fn remembers_position(app: &BootstrappedApp, entry: &PlaybackQueueEntry) -> bool {
+    match app.db.get_track_resume(&entry.track_id.0.to_string()) {
+        Ok(Some(TrackResumeRow {
+            remember: Some(remember),
+            ..
+        })) => return remember,
+        Ok(_) => {}
+        Err(err) => tracing::warn!(error = %err, "failed to read the track's resume setting"),
+    }
+    let min_ms = i64::from(app.config.playback.resume_min_minutes) * 60_000;
+    if min_ms > 0 && entry.duration_ms.is_some_and(|ms| ms >= min_ms) {
+        return true;
+    }
+    app.config
+        .playback
+        .resume_folders
+        .iter()
+        .any(|folder| !folder.is_empty() && Path::new(&entry.path).starts_with(folder))
+}
+
+/// Seeks the track just loaded to where it stopped last time, when it
+/// remembers its position, and returns that position.
+fn resume_saved_position(app: &mut BootstrappedApp) -> Option<u64> {
+    flush_positions(app);
+    let entry = app.playback_state.current_entry()?;
+    if !remembers_position(app, entry) {
+        return None;
+    }
+    let resume = app
+        .db
+        .get_track_resume(&entry.track_id.0.to_string())
+        .map_err(|err| tracing::warn!(error = %err, "failed to read the resume position"))
+        .ok()??;
+    let position_ms = u64::try_from(resume.position_ms)
+        .ok()
+        .filter(|ms| *ms > 0)?;
+    app.player.seek(position_ms);
+    app.playback_state.session.position_ms = position_ms;
+    Some(position_ms)
+}
+
+/// Loads a queue entry's path into the player. Songs synced from a Subsonic
+/// server are streamed from a URL made for this load, since its token is
+/// only good for a while. Local tracks skip the silence found at either end.
+fn load_into_player(app: &mut BootstrappedApp, path: &str) {
+    match subsonic::song_id(path) {
+        Some(_) if app.offline => {
+            tracing::warn!(path, "offline mode turns streaming off; not loading");
+            app.player.stop();
+        }
+        Some(id) => app
+            .player
+            .load(&subsonic::stream_url(&app.config.subsonic, id)),
+        None => app.player.load_span(path, trim_span(app, path)),
+    }
+}
+
+/// The part of the current track to play once its silence is trimmed, when
+/// it is the one at `path`.
+pub fn trim_span(app: &BootstrappedApp, path: &str) -> PlaySpan {
+    let Some(entry) = app
+        .playback_state
+        .current_entry()
+        .filter(|entry| entry.path == path)
+    else {
+        return PlaySpan::default();
+    };
+    analysis::trim_span(&app.db, &app.config.playback, &entry.track_id.0.to_string())
+        .unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "failed to read the track silence");
+            PlaySpan::default()
+        })
+}
+
+/// Sends the session volume to the player with the current track's and the
+/// queue's volume offsets applied.
+pub fn apply_volume(app: &BootstrappedApp) {
+    app.player.set_volume(output_volume(app));
+}
+
+/// Sends the output settings to the player, and brings the volume down to
+/// `max_volume` when it was lowered.
+pub fn apply_output_settings(app: &mut BootstrappedApp) {
+    let cap = max_volume(app);
+    let session = &mut app.playback_state.session;
+    session.volume = session.volume.min(cap);
+    app.player.set_crossfeed(app.config.playback.crossfeed);
+    app.player.set_limiter(app.config.playback.limiter);
+    apply_volume(app);
+}
+
+/// The `max_volume` setting as a session volume.
+fn max_volume(app: &BootstrappedApp) -> f32 {
+    f32::from(app.config.playback.max_volume) / 100.0
+}
+
+fn output_volume(app: &BootstrappedApp) -> f32 {
+    offset_volume(
+        app.playback_state.session.volume,
+        volume_offset_db(app) + normalization_db(app),
+    )
+}
+
+/// The loudness normalization gain for the current track in dB, 0 when it
+/// is off or the track has not been measured.
+pub fn normalization_db(app: &BootstrappedApp) -> f32 {
+    let Some(entry) = app.playback_state.current_entry() else {
+        return 0.0;
+    };
+    let album = entry
+        .album
+        .as_deref()
+        .map(|album| (album, entry.artist.as_deref().unwrap_or_default()));
+    analysis::normalization_db(
+        &app.db,
+        &app.config.playback,
+        &entry.track_id.0.to_string(),
+        album,
+    )
+    .unwrap_or_else(|err| {
+        tracing::warn!(error = %err, "failed to read the track loudness");
+        0.0
+    })
+}
+
+/// The current track's volume offset plus the queue's, in dB.
+pub fn volume_offset_db(app: &BootstrappedApp) -> f32 {
+    let track_db = app
+        .playback_state
+        .current_entry()
+        .map(|entry| {
+            app.db
+                .track_volume_offset(&entry.track_id.0.to_string())
+                .unwrap_or_else(|err| {
+                    tracing::warn!(error = %err, "failed to read the track volume offset");
+                    0.0
+                })
+        })
+        .unwrap_or(0.0);
+    track_db + app.playback_state.session.queue_volume_offset_db
+}
+
+/// `volume` raised or lowered by `offset_db` decibels.
+pub fn offset_volume(volume: f32, offset_db: f32) -> f32 {
+    volume * 10f32.powf(offset_db / 20.0)
+}
+
+/// `Playing: <title>`, with where playback resumed.
+fn playing_message(title: &str, resumed_ms: Option<u64>) -> String {
+    match resumed_ms {
+        Some(ms) => format!("Playing: {title} from {}", format_clock(ms as i64)),
+        None => format!("Playing: {title}"),
+    }
+}
+
+pub fn load_playback_state(db: &Database) -> Result<PlaybackState> {
+    let raw = db.get_setting_json(PLAYBACK_STATE_SETTING_KEY)?;
+    let mut state = match raw {
+        Some(value) => serde_json::from_value::<PlaybackState>(value).unwrap_or_else(|err| {
+            tracing::warn!("failed to deserialize playback state, resetting: {err}");
+            eprintln!("warning: failed to deserialize playback state, resetting: {err}");
+            PlaybackState::default()
+        }),
+        None => PlaybackState::default(),
+    };
+    normalize_playback_state(&mut state);
+    // Nothing is loaded into the player yet, so a session saved mid-track
+    // comes back stopped at its position; Space picks it up from there.
+    state.session.status = PlaybackStatus::Stopped;
+    save_playback_state(db, &state)?;
+    Ok(state)
+}
+
+fn save_playback_state(db: &Database, state: &PlaybackState) -> Result<()> {
+    db.set_setting_json(PLAYBACK_STATE_SETTING_KEY, &serde_json::to_value(state)?)?;
+    Ok(())
+}
+
+fn normalize_playback_state(state: &mut PlaybackState) {
+    if state.queue.is_empty() {
+        state.session.current_index = None;
+        state.session.position_ms = 0;
+        if !matches!(state.session.status, PlaybackStatus::Stopped) {
+            state.session.status = PlaybackStatus::Stopped;
+        }
+    } else if let Some(idx) = state.session.current_index {
+        if idx >= state.queue.len() {
+            state.session.current_index = Some(state.queue.len().saturating_sub(1));
+            state.session.position_ms = 0;
+        }
+    }
+
+    if !state.session.volume.is_finite() {
+        state.session.volume = 1.0;
+    }
+    state.session.volume = state.session.volume.clamp(0.0, 1.0);
+}
+
+/// Queues the playback session for saving. Call [`flush_writes`] when it
+/// must be on disk before continuing, as on exit.
+pub fn persist_playback_state(app: &mut BootstrappedApp) -> Result<()> {
+    normalize_playback_state(&mut app.playback_state);
+    save_setting(
+        app,
+        PLAYBACK_STATE_SETTING_KEY,
+        serde_json::to_value(&app.playback_state)?,
+    )?;
+    app.session_dirty = false;
+    app.last_autosave = Instant::now();
+    Ok(())
+}
+
+/// Writes a setting through the background writer, or directly when the
+/// library has no writer (an in-memory database).
+pub fn save_setting(app: &BootstrappedApp, key: &str, value: serde_json::Value) -> Result<()> {
+    match &app.writer {
+        Some(writer) => writer.set_setting_json(key, value),
+        None => app.db.set_setting_json(key, &value)?,
+    }
+    Ok(())
+}
+
+/// Blocks until every queued background write is on disk.
+pub fn flush_writes(app: &BootstrappedApp) -> Result<()> {
+    if let Some(writer) = &app.writer {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// How often frequently-changing session state (position, volume) is flushed
+/// while a front end is running.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Writes the playback session if it has unsaved changes and the last write
+/// was at least [`AUTOSAVE_INTERVAL`] ago. Returns whether it saved.
+pub fn autosave_if_due(app: &mut BootstrappedApp, now: Instant) -> Result<bool> {
+    if !app.session_dirty || now.saturating_duration_since(app.last_autosave) < AUTOSAVE_INTERVAL {
+        return Ok(false);
+    }
+    persist_playback_state(app)?;
+    Ok(true)
+}
+
+pub fn playback_queue_entry_from_track_row(row: auric_library::db::TrackRow) -> PlaybackQueueEntry {
+    PlaybackQueueEntry {
+        track_id: row.id,
+        path: row.path,
+        title: row.title,
+        artist: row.artist,
+        album: row.album,
+        duration_ms: row.duration_ms,
+        sample_rate: row.sample_rate,
+        channels: row.channels,
+        bit_depth: row.bit_depth,
+    }
+}
+
+fn emit_playback_state_changed(events: &mut Vec<AppEvent>, state: &PlaybackState) {
+    events.push(AppEvent::PlaybackStateChanged {
+        status: state.session.status,
+        current_index: state.session.current_index,
+        queue_len: state.queue.len(),
+    });
+}
+
+fn current_track_id(state: &PlaybackState) -> Option<TrackId> {
+    state.current_entry().map(|entry| entry.track_id)
+}
+
+pub fn dispatch_app_command(
+    app: &mut BootstrappedApp,
+    command: AppCommand,
+) -> Result<Vec<AppEvent>> {
+    let mut events = Vec::new();
+
+    match command {
+        AppCommand::ToggleFeature { feature, enabled } => {
+            if enabled && app.offline && feature.uses_network() {
+                bail!("{feature} needs the network, which offline mode turns off");
+            }
+            let transition = if enabled {
+                FeatureState::Starting
+            } else {
+                FeatureState::Stopping
+            };
+            app.feature_registry.set_state(feature, transition.clone());
+            events.push(AppEvent::FeatureStateChanged {
+                feature,
+                state: transition,
+            });
+
+            app.db
+                .set_setting_json(&feature_setting_key(feature), &json!(enabled))?;
+
+            let final_state = if enabled {
+                FeatureState::Enabled
+            } else {
+                FeatureState::Disabled
+            };
+            app.feature_registry.set_state(feature, final_state.clone());
+            events.push(AppEvent::FeatureStateChanged {
+                feature,
+                state: final_state,
+            });
+
+            app.report.feature_enabled_count = FeatureId::ALL
+                .into_iter()
+                .filter(|f| app.feature_registry.is_enabled(*f))
+                .count();
+        }
+        cmd @ (AppCommand::Play
+        | AppCommand::Pause
+        | AppCommand::Stop
+        | AppCommand::Next
+        | AppCommand::Previous
+        | AppCommand::SeekMillis(_)
+        | AppCommand::SetVolume(_)) => {
+            handle_playback_transport_command(app, cmd, &mut events)?;
+        }
+    }
+
+    Ok(events)
+}
+
+/// Runs a playback action from any front end and persists the session: queue and
+/// transport changes are saved right away, while volume and seek changes
+/// (which arrive in bursts while dragging) wait for the next autosave.
+pub fn handle_playback_action(app: &mut BootstrappedApp, action: Action) -> Result<Reply> {
+    if let Some(party) = app.party.as_mut() {
+        if !crate::party::allows(&action) {
+            bail!(crate::party::LOCKED);
+        }
+        if let Action::Enqueue { requested_by, .. } = &action {
+            let cooldown = Duration::from_secs(app.config.party.request_cooldown_secs);
+            if let Err(wait) = party.admit(requested_by, cooldown, Instant::now()) {
+                bail!("Party mode: next request in {}s", wait.as_secs().max(1));
+            }
+        }
+    }
+    let debounced = matches!(
+        action,
+        Action::VolumeUp | Action::VolumeDown | Action::SetVolume { .. } | Action::Seek { .. }
+    );
+    if !debounced && app.playback_state.session.status != PlaybackStatus::Stopped {
+        save_position(app, app.playback_state.session.position_ms, false);
+    }
+    let before = hooks::PlayerMark::of(&app.playback_state);
+    let result = apply_playback_action(app, action)?;
+    if let Some(event) = before.transition(&hooks::PlayerMark::of(&app.playback_state)) {
+        hooks::run(
+            &app.config.hooks,
+            event,
+            hooks::track_env(&app.playback_state),
+        );
+    }
+    if debounced {
+        app.session_dirty = true;
+    } else {
+        persist_playback_state(app)?;
+    }
+    Ok(result)
+}
+
+fn apply_playback_action(app: &mut BootstrappedApp, action: Action) -> Result<Reply> {
+    match action {
+        Action::PlayTrack { track_index } => {
+            let total = app.db.stats().map(|s| s.track_count).unwrap_or_else(|err| {
+                tracing::warn!(error = %err, "failed to count tracks for the play queue");
+                250
+            }) as usize;
+            let limit = total.min(5000);
+            let tracks = or_default_logged(
+                app.db.list_tracks(limit),
+                "failed to load tracks for the play queue",
+            );
+            let queue: Vec<PlaybackQueueEntry> = tracks
+                .into_iter()
+                .map(|t| PlaybackQueueEntry {
+                    track_id: t.id,
+                    path: t.path,
+                    title: t.title,
+                    artist: t.artist,
+                    album: t.album,
+                    duration_ms: t.duration_ms,
+                    sample_rate: t.sample_rate,
+                    channels: t.channels,
+                    bit_depth: t.bit_depth,
+                })
+                .collect();
+
+            if track_index >= queue.len() {
+                return Ok(Reply::Unchanged("No track at that index".to_string()));
+            }
+
+            app.playback_state.queue = queue;
+            app.playback_state.session.current_index = Some(track_index);
+            app.playback_state.session.status = PlaybackStatus::Playing;
+            app.playback_state.session.position_ms = 0;
+
+            let entry = &app.playback_state.queue[track_index];
+            let title = entry.title.clone().unwrap_or_default();
+            let path = entry.path.clone();
+            load_into_player(app, &path);
+            apply_volume(app);
+            let resumed = resume_saved_position(app);
+
+            Ok(Reply::Changed(playing_message(&title, resumed)))
+        }
+        Action::TogglePause => match app.playback_state.session.status {
+            PlaybackStatus::Playing => {
+                app.player.pause();
+                app.playback_state.session.status = PlaybackStatus::Paused;
+                Ok(Reply::Changed("Paused".to_string()))
+            }
+            PlaybackStatus::Paused => {
+                app.player.resume();
+                app.playback_state.session.status = PlaybackStatus::Playing;
+                Ok(Reply::Changed("Resumed".to_string()))
+            }
+            PlaybackStatus::Stopped => {
+                if let Some(idx) = app.playback_state.session.current_index {
+                    let entry_path = app.playback_state.queue.get(idx).map(|e| e.path.clone());
+                    let entry_title = app
+                        .playback_state
+                        .queue
+                        .get(idx)
+                        .and_then(|e| e.title.clone());
+                    if let Some(path) = entry_path {
+                        load_into_player(app, &path);
+                        apply_volume(app);
+                        let restored_ms = app.playback_state.session.position_ms;
+                        app.playback_state.session.status = PlaybackStatus::Playing;
+                        let resumed = resume_saved_position(app).or_else(|| {
+                            // Stop rewinds to the start, so a stopped position
+                            // is one carried over from the last session.
+                            (restored_ms > 0).then(|| {
+                                app.player.seek(restored_ms);
+                                restored_ms
+                            })
+                        });
+                        let title = entry_title.unwrap_or_default();
+                        return Ok(Reply::Changed(playing_message(&title, resumed)));
+                    }
+                }
+                Ok(Reply::Unchanged("No track to play".to_string()))
+            }
+        },
+        Action::Pause => {
+            if app.playback_state.session.status == PlaybackStatus::Playing {
+                apply_playback_action(app, Action::TogglePause)
+            } else {
+                Ok(Reply::Unchanged("Not playing".to_string()))
+            }
+        }
+        Action::Resume => {
+            if app.playback_state.session.status == PlaybackStatus::Playing {
+                Ok(Reply::Unchanged("Already playing".to_string()))
+            } else {
+                apply_playback_action(app, Action::TogglePause)
+            }
+        }
+        Action::Stop => {
+            app.player.stop();
+            app.playback_state.session.status = PlaybackStatus::Stopped;
+            Ok(Reply::Changed("Stopped".to_string()))
+        }
+        Action::Next | Action::Advance => {
+            let mut events = Vec::new();
+            handle_playback_transport_command(app, AppCommand::Next, &mut events)?;
+            let status = app.playback_state.session.status;
+            let entry_info = app
+                .playback_state
+                .current_entry()
+                .map(|e| (e.path.clone(), e.title.clone().unwrap_or_default()));
+            if status == PlaybackStatus::Playing || status == PlaybackStatus::Paused {
+                if let Some((path, title)) = entry_info {
+                    load_into_player(app, &path);
+                    apply_volume(app);
+                    app.playback_state.session.status = PlaybackStatus::Playing;
+                    let resumed = resume_saved_position(app);
+                    return Ok(Reply::Changed(playing_message(&title, resumed)));
+                }
+            }
+            app.player.stop();
+            Ok(Reply::Changed("End of queue".to_string()))
+        }
+        Action::Previous => {
+            let mut events = Vec::new();
+            handle_playback_transport_command(app, AppCommand::Previous, &mut events)?;
+            let status = app.playback_state.session.status;
+            let entry_info = app
+                .playback_state
+                .current_entry()
+                .map(|e| (e.path.clone(), e.title.clone().unwrap_or_default()));
+            if let Some((path, title)) = entry_info {
+                if status == PlaybackStatus::Playing {
+                    load_into_player(app, &path);
+                    apply_volume(app);
+                    resume_saved_position(app);
+                }
+                return Ok(Reply::Changed(format!("Track: {title}")));
+            }
+            Ok(Reply::Changed("Start of queue".to_string()))
+        }
+        Action::VolumeUp => {
+            let step = f32::from(app.config.playback.volume_step) / 100.0;
+            let new_vol = (app.playback_state.session.volume + step).min(max_volume(app));
+            app.playback_state.session.volume = new_vol;
+            apply_volume(app);
+            Ok(Reply::Changed(format!(
+                "Volume: {}%",
+                (new_vol * 100.0).round() as u32
+            )))
+        }
+        Action::VolumeDown => {
+            let step = f32::from(app.config.playback.volume_step) / 100.0;
+            let new_vol = (app.playback_state.session.volume - step).max(0.0);
+            app.playback_state.session.volume = new_vol;
+            apply_volume(app);
+            Ok(Reply::Changed(format!(
+                "Volume: {}%",
+                (new_vol * 100.0).round() as u32
+            )))
+        }
+        Action::SetVolume { volume } => {
+            let new_vol = volume.clamp(0.0, max_volume(app));
+            app.playback_state.session.volume = new_vol;
+            apply_volume(app);
+            // The shell already shows the new level; skip the refresh so
+            // dragging the slider stays responsive.
+            Ok(Reply::Unchanged(format!(
+                "Volume: {}%",
+                (new_vol * 100.0).round() as u32
+            )))
+        }
+        Action::ToggleShuffle => {
+            app.playback_state.session.shuffle = !app.playback_state.session.shuffle;
+            app.playback_state.session.shuffle_history.clear();
+            let label = if app.playback_state.session.shuffle {
+                "Shuffle: on"
+            } else {
+                "Shuffle: off"
+            };
+            Ok(Reply::Changed(label.to_string()))
+        }
+        Action::ToggleRandomAlbum => {
+            app.playback_state.session.random_album = !app.playback_state.session.random_album;
+            let label = if app.playback_state.session.random_album {
+                "Random album: on"
+            } else {
+                "Random album: off"
+            };
+            Ok(Reply::Changed(label.to_string()))
+        }
+        Action::Enqueue { path, .. } => {
+            let Some(track) = app.db.get_track_by_path(&path)? else {
+                bail!("not in the library: {path}");
+            };
+            let title = track.title.clone().unwrap_or_else(|| path.clone());
+            app.playback_state
+                .queue
+                .push(playback_queue_entry_from_track_row(track));
+            if app.playback_state.session.status != PlaybackStatus::Stopped {
+                return Ok(Reply::Changed(format!("Queued: {title}")));
+            }
+            // Nothing is playing, so the request starts right away.
+            app.playback_state.session.current_index = Some(app.playback_state.queue.len() - 1);
+            app.playback_state.session.status = PlaybackStatus::Playing;
+            app.playback_state.session.position_ms = 0;
+            load_into_player(app, &path);
+            apply_volume(app);
+            let resumed = resume_saved_position(app);
+            Ok(Reply::Changed(playing_message(&title, resumed)))
+        }
+        Action::ToggleRadio => {
+            app.playback_state.session.radio = !app.playback_state.session.radio;
+            let label = if app.playback_state.session.radio {
+                "Radio: on"
+            } else {
+                "Radio: off"
+            };
+            Ok(Reply::Changed(label.to_string()))
+        }
+        Action::ToggleConsume => {
+            app.playback_state.session.consume = !app.playback_state.session.consume;
+            let label = if app.playback_state.session.consume {
+                "Consume: on"
+            } else {
+                "Consume: off"
+            };
+            Ok(Reply::Changed(label.to_string()))
+        }
+        Action::Seek { position_ms } => {
+            app.playback_state.session.position_ms = position_ms;
+            if app.playback_state.session.status != PlaybackStatus::Stopped {
+                app.player.seek(position_ms);
+            }
+            let secs = position_ms / 1000;
+            Ok(Reply::Changed(format!(
+                "Seek: {:02}:{:02}",
+                secs / 60,
+                secs % 60
+            )))
+        }
+    }
+}
+
+fn handle_playback_transport_command(
+    app: &mut BootstrappedApp,
+    command: AppCommand,
+    events: &mut Vec<AppEvent>,
+) -> Result<()> {
+    let prev_track_id = current_track_id(&app.playback_state);
+    let mut track_changed = false;
+
+    match command {
+        AppCommand::Play => {
+            if app.playback_state.queue.is_empty() {
+                events.push(AppEvent::Warning("playback queue is empty".to_string()));
+                return Ok(());
+            }
+            if app.playback_state.session.current_index.is_none() {
+                app.playback_state.session.current_index = Some(0);
+                app.playback_state.session.position_ms = 0;
+                track_changed = true;
+            }
+            app.playback_state.session.status = PlaybackStatus::Playing;
+        }
+        AppCommand::Pause => {
+            app.playback_state.session.status = PlaybackStatus::Paused;
+        }
+        AppCommand::Stop => {
+            app.playback_state.session.status = PlaybackStatus::Stopped;
+            app.playback_state.session.position_ms = 0;
+        }
+        AppCommand::SeekMillis(position_ms) => {
+            let clamped = if let Some(entry) = app.playback_state.current_entry() {
+                match entry.duration_ms {
+                    Some(ms) if ms > 0 => position_ms.min(ms as u64),
+                    _ => position_ms,
+                }
+            } else {
+                position_ms
+            };
+            app.playback_state.session.position_ms = clamped;
+            if app.playback_state.session.status != PlaybackStatus::Stopped {
+                app.player.seek(clamped);
+            }
+            events.push(AppEvent::PlaybackPositionMillis(clamped));
+        }
+        AppCommand::SetVolume(volume) => {
+            let normalized = if volume.is_finite() { volume } else { 1.0 };
+            app.playback_state.session.volume = normalized.clamp(0.0, max_volume(app));
+            apply_volume(app);
+        }
+        AppCommand::Next => {
+            if app.playback_state.queue.is_empty() {
+                events.push(AppEvent::Warning("playback queue is empty".to_string()));
+                return Ok(());
+            }
+            let random_album = app.playback_state.session.random_album
+                && app.playback_state.session.repeat != RepeatMode::One
+                && queue_random_album(app);
+            let shuffle =
+                app.playback_state.session.shuffle && !app.playback_state.session.random_album;
+            let session = &app.playback_state.session;
+            if session.radio
+                && !random_album
+                && !shuffle
+                && session.repeat == RepeatMode::Off
+                && session
+                    .current_index
+                    .is_some_and(|i| i + 1 >= app.playback_state.queue.len())
+            {
+                crate::radio::refill(app);
+            }
+            let len = app.playback_state.queue.len();
+            let current = app.playback_state.session.current_index.unwrap_or(0);
+            // A new random album has already replaced the played one.
+            let consume = app.playback_state.session.consume
+                && !random_album
+                && app.playback_state.session.current_index.is_some();
+            let next_index = match app.playback_state.session.repeat {
+                _ if random_album => Some(0),
+                RepeatMode::One => Some(current.min(len.saturating_sub(1))),
+                _ if shuffle => crate::shuffle::pick_next(app),
+                RepeatMode::All => Some((current + 1) % len),
+                RepeatMode::Off => {
+                    if current + 1 < len {
+                        Some(current + 1)
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            match next_index {
+                Some(idx) => {
+                    if shuffle {
+                        crate::shuffle::remember_played(&mut app.playback_state, idx);
+                    }
+                    let idx = if consume && idx != current {
+                        app.playback_state.queue.remove(current);
+                        if idx > current {
+                            idx - 1
+                        } else {
+                            idx
+                        }
+                    } else {
+                        idx
+                    };
+                    let was_none = app.playback_state.session.current_index.is_none();
+                    app.playback_state.session.current_index = Some(idx);
+                    app.playback_state.session.position_ms = 0;
+                    if app.playback_state.session.status == PlaybackStatus::Stopped {
+                        app.playback_state.session.status = PlaybackStatus::Paused;
+                    }
+                    track_changed =
+                        was_none || prev_track_id != current_track_id(&app.playback_state);
+                }
+                None => {
+                    if consume {
+                        app.playback_state.queue.remove(current);
+                        adjust_playback_selection_after_queue_removal(
+                            &mut app.playback_state,
+                            current,
+                        );
+                    }
+                    app.playback_state.session.status = PlaybackStatus::Stopped;
+                    app.playback_state.session.position_ms = 0;
+                    events.push(AppEvent::Warning("end of queue".to_string()));
+                }
+            }
+        }
+        AppCommand::Previous => {
+            if app.playback_state.queue.is_empty() {
+                events.push(AppEvent::Warning("playback queue is empty".to_string()));
+                return Ok(());
+            }
+            if app.playback_state.session.position_ms > 3_000 {
+                app.playback_state.session.position_ms = 0;
+                events.push(AppEvent::PlaybackPositionMillis(0));
+            } else {
+                let len = app.playback_state.queue.len();
+                let current = app.playback_state.session.current_index.unwrap_or(0);
+                let shuffle =
+                    app.playback_state.session.shuffle && !app.playback_state.session.random_album;
+                let prev_index = match app.playback_state.session.repeat {
+                    RepeatMode::One => current.min(len.saturating_sub(1)),
+                    _ if shuffle => {
+                        crate::shuffle::step_back(&mut app.playback_state).unwrap_or(current)
+                    }
+                    RepeatMode::All => {
+                        if current == 0 {
+                            len.saturating_sub(1)
+                        } else {
+                            current - 1
+                        }
+                    }
+                    RepeatMode::Off => current.saturating_sub(1),
+                };
+                app.playback_state.session.current_index = Some(prev_index);
+                app.playback_state.session.position_ms = 0;
+                track_changed = prev_track_id != current_track_id(&app.playback_state);
+            }
+        }
+        AppCommand::ToggleFeature { .. } => unreachable!("handled above"),
+    }
+
+    let new_track_id = current_track_id(&app.playback_state);
+    if track_changed || prev_track_id != new_track_id {
+        events.push(AppEvent::TrackChanged {
+            track_id: new_track_id,
+        });
+    }
+    emit_playback_state_changed(events, &app.playback_state);
+    persist_playback_state(app)?;
+    Ok(())
+}
+
+fn album_key(entry: &PlaybackQueueEntry) -> Option<(&str, &str)> {
+    let album = entry.album.as_deref().filter(|album| !album.is_empty())?;
+    Some((album, entry.artist.as_deref().unwrap_or("")))
+}
+
+/// In random album mode, replaces the queue with a random other album once
+/// the current one has no tracks left after this one. Returns whether it
+/// did; the caller then starts the new queue from its first track.
+fn queue_random_album(app: &mut BootstrappedApp) -> bool {
+    let state = &app.playback_state;
+    let current = state.current_entry().and_then(album_key);
+    let next = state
+        .session
+        .current_index
+        .and_then(|index| state.queue.get(index + 1))
+        .and_then(album_key);
+    if current.is_some() && next == current {
+        return false;
+    }
+    let picked = match app.db.random_album(current) {
+        Ok(Some(picked)) => picked,
+        Ok(None) => return false,
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to pick a random album");
+            return false;
+        }
+    };
+    let tracks = or_default_logged(
+        app.db.list_album_tracks(&picked.0, &picked.1),
+        "failed to load the random album",
+    );
+    if tracks.is_empty() {
+        return false;
+    }
+    app.playback_state.queue = tracks
+        .into_iter()
+        .map(playback_queue_entry_from_track_row)
+        .collect();
+    true
+}
+
+/// Removes repeated tracks from the queue, keeping each track's first entry,
+/// or the playing entry when it is one of the copies. Returns how many
+/// entries went.
+pub fn dedupe_queue(state: &mut PlaybackState) -> usize {
+    let current = state.session.current_index;
+    let playing = state.current_entry().map(|entry| entry.track_id);
+    let mut seen = HashSet::new();
+    let keep = state
+        .queue
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            Some(index) == current
+                || (Some(entry.track_id) != playing && seen.insert(entry.track_id))
+        })
+        .collect::<Vec<_>>();
+    let mut removed = 0;
+    for index in (0..keep.len()).rev().filter(|&index| !keep[index]) {
+        state.queue.remove(index);
+        adjust_playback_selection_after_queue_removal(state, index);
+        removed += 1;
+    }
+    removed
+}
+
+pub fn adjust_playback_selection_after_queue_removal(
+    state: &mut PlaybackState,
+    removed_index: usize,
+) {
+    if state.queue.is_empty() {
+        state.session.current_index = None;
+        state.session.position_ms = 0;
+        state.session.status = PlaybackStatus::Stopped;
+        return;
+    }
+
+    match state.session.current_index {
+        Some(idx) if idx > removed_index => state.session.current_index = Some(idx - 1),
+        Some(idx) if idx == removed_index => {
+            let new_idx = removed_index.min(state.queue.len().saturating_sub(1));
+            state.session.current_index = Some(new_idx);
+            state.session.position_ms = 0;
+        }
+        _ => {}
+    }
+}
+
+/// Moves the queue entry at `from` to `to`, shifting the entries between
+/// them; the playing entry stays current wherever it ends up.
+pub fn move_queue_entry(state: &mut PlaybackState, from: usize, to: usize) -> Result<()> {
+    let len = state.queue.len();
+    if from >= len || to >= len {
+        bail!(
+            "queue index out of range: {}",
+            if from >= len { from } else { to }
+        );
+    }
+    let entry = state.queue.remove(from);
+    state.queue.insert(to, entry);
+    state.session.current_index = state.session.current_index.map(|current| match current {
+        current if current == from => to,
+        current if from < current && current <= to => current - 1,
+        current if to <= current && current < from => current + 1,
+        current => current,
+    });
+    Ok(())
+}
+
+/// Scans the folder (or the folder containing the file) without adding it as
+/// a library root, then starts playing the file or the folder's first track.
+pub fn open_path_and_play(app: &mut BootstrappedApp, path: &Path) -> Result<Reply> {
+    let target = path
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", path.display()))?;
+    let folder = if target.is_dir() {
+        target.clone()
+    } else {
+        target
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| anyhow::anyhow!("{} has no parent folder", target.display()))?
+    };
+    let scanner = scanner_from_config(&app.config.library, false);
+    scanner.scan_path(&mut app.db, &folder)?;
+
+    // The queue is the folder, however large the rest of the library is.
+    let folder_str = folder.to_string_lossy();
+    let count = app.db.count_tracks_by_prefix(&folder_str)?;
+    let tracks = app.db.list_tracks_by_prefix(&folder_str, count)?;
+    let index = if target.is_dir() {
+        (!tracks.is_empty()).then_some(0)
+    } else {
+        let target_str = target.to_string_lossy();
+        tracks.iter().position(|track| track.path == target_str)
+    }
+    .ok_or_else(|| anyhow::anyhow!("no playable tracks found in {}", path.display()))?;
+    let playing = replace_queue_and_play(app, tracks, index)?;
+    Ok(Reply::Changed(playing))
+}
+
+/// Appends tracks to the end of the queue. When nothing plays, the first of
+/// them starts, as a single enqueued track does.
+pub fn append_to_queue(
+    app: &mut BootstrappedApp,
+    name: &str,
+    tracks: Vec<auric_library::db::TrackRow>,
+) -> Result<Reply> {
+    if tracks.is_empty() {
+        return Ok(Reply::Unchanged(format!("{name} is empty")));
+    }
+    let count = tracks.len();
+    let first = app.playback_state.queue.len();
+    app.playback_state
+        .queue
+        .extend(tracks.into_iter().map(playback_queue_entry_from_track_row));
+    if app.playback_state.session.status != PlaybackStatus::Stopped {
+        persist_playback_state(app)?;
+        return Ok(Reply::Changed(format!("Queued {count} tracks ({name})")));
+    }
+    let before = hooks::PlayerMark::of(&app.playback_state);
+    let playing = start_queue_entry(app, first, before)?;
+    Ok(Reply::Changed(format!("{playing} ({name})")))
+}
+
+/// Puts a track right after the playing one and starts it, leaving the rest
+/// of the queue as it was.
+pub fn play_now(app: &mut BootstrappedApp, track: auric_library::db::TrackRow) -> Result<Reply> {
+    if app.playback_state.session.status != PlaybackStatus::Stopped {
+        save_position(app, app.playback_state.session.position_ms, false);
+    }
+    let before = hooks::PlayerMark::of(&app.playback_state);
+    let index = app
+        .playback_state
+        .session
+        .current_index
+        .map_or(0, |current| current + 1)
+        .min(app.playback_state.queue.len());
+    app.playback_state
+        .queue
+        .insert(index, playback_queue_entry_from_track_row(track));
+    let playing = start_queue_entry(app, index, before)?;
+    Ok(Reply::Changed(playing))
+}
+
+/// Plays the queue entry at `index` from the top, runs the hook for what
+/// changed since `before`, and returns the `Playing: ...` line.
+fn start_queue_entry(
+    app: &mut BootstrappedApp,
+    index: usize,
+    before: hooks::PlayerMark,
+) -> Result<String> {
+    app.playback_state.session.current_index = Some(index);
+    app.playback_state.session.status = PlaybackStatus::Playing;
+    app.playback_state.session.position_ms = 0;
+    let entry = &app.playback_state.queue[index];
+    let (path, title) = (entry.path.clone(), entry.title.clone().unwrap_or_default());
+    load_into_player(app, &path);
+    apply_volume(app);
+    let resumed = resume_saved_position(app);
+    if let Some(event) = before.transition(&hooks::PlayerMark::of(&app.playback_state)) {
+        hooks::run(
+            &app.config.hooks,
+            event,
+            hooks::track_env(&app.playback_state),
+        );
+    }
+    persist_playback_state(app)?;
+    Ok(playing_message(&title, resumed))
+}
+
+/// Replaces the queue with a playlist's tracks and plays the first, the
+/// way picking a track in the list queues the library.
+pub fn play_playlist(
+    app: &mut BootstrappedApp,
+    name: &str,
+    tracks: Vec<auric_library::db::TrackRow>,
+) -> Result<Reply> {
+    if tracks.is_empty() {
+        return Ok(Reply::Unchanged(format!("{name} is empty")));
+    }
+    let playing = replace_queue_and_play(app, tracks, 0)?;
+    Ok(Reply::Changed(format!("{playing} ({name})")))
+}
+
+/// Makes `tracks` the queue and plays the one at `index`, which must exist.
+fn replace_queue_and_play(
+    app: &mut BootstrappedApp,
+    tracks: Vec<auric_library::db::TrackRow>,
+    index: usize,
+) -> Result<String> {
+    if app.playback_state.session.status != PlaybackStatus::Stopped {
+        save_position(app, app.playback_state.session.position_ms, false);
+    }
+    let before = hooks::PlayerMark::of(&app.playback_state);
+    app.playback_state.queue = tracks
+        .into_iter()
+        .map(playback_queue_entry_from_track_row)
+        .collect();
+    start_queue_entry(app, index, before)
+}
+
+/// Plays a podcast episode on its own, from where it was left off. A
+/// downloaded episode plays from disk; otherwise it is streamed, which
+/// offline mode does not allow.
+pub fn play_episode(app: &mut BootstrappedApp, episode_id: &str) -> Result<Reply> {
+    flush_positions(app);
+    let episode = app
+        .db
+        .get_podcast_episode(episode_id)?
+        .ok_or_else(|| anyhow::anyhow!("unknown episode: {episode_id}"))?;
+    let source = match episode
+        .local_path
+        .as_deref()
+        .filter(|p| Path::new(p).is_file())
+    {
+        Some(path) => path.to_string(),
+        None if app.offline => {
+            bail!(
+                "{} is not downloaded, and offline mode turns streaming off",
+                episode.title
+            )
+        }
+        None => episode.audio_url.clone(),
+    };
+    let show = podcast::find(&app.db, &episode.podcast_id)
+        .map(|p| p.title)
+        .ok();
+    let saved_ms = if episode.played {
+        0
+    } else {
+        episode.position_ms.max(0) as u64
+    };
+    // A stream cannot seek, so only a downloaded episode resumes.
+    let streaming = source == episode.audio_url;
+    let resume_ms = if streaming { 0 } else { saved_ms };
+
+    let before = hooks::PlayerMark::of(&app.playback_state);
+    app.playback_state.queue = vec![PlaybackQueueEntry {
+        track_id: TrackId(uuid::Uuid::parse_str(&episode.id)?),
+        path: source,
+        title: Some(episode.title.clone()),
+        artist: show,
+        album: None,
+        duration_ms: episode.duration_ms,
+        sample_rate: None,
+        channels: None,
+        bit_depth: None,
+    }];
+    app.playback_state.session.current_index = Some(0);
+    app.playback_state.session.status = PlaybackStatus::Playing;
+    app.playback_state.session.position_ms = resume_ms;
+    let path = app.playback_state.queue[0].path.clone();
+    load_into_player(app, &path);
+    apply_volume(app);
+    if resume_ms > 0 {
+        app.player.seek(resume_ms);
+    }
+    if let Some(event) = before.transition(&hooks::PlayerMark::of(&app.playback_state)) {
+        hooks::run(
+            &app.config.hooks,
+            event,
+            hooks::track_env(&app.playback_state),
+        );
+    }
+    persist_playback_state(app)?;
+
+    let message = if streaming && saved_ms > 0 {
+        format!(
+            "Streaming: {} from the start; download it to resume at {}",
+            episode.title,
+            format_clock(saved_ms as i64)
+        )
+    } else {
+        playing_message(&episode.title, Some(resume_ms).filter(|ms| *ms > 0))
+    };
+    Ok(Reply::Changed(message))
+}
+
+/// Points queue entries at their new location after the watcher saw a file
+/// or folder renamed, so the queue keeps working without a reload.
+pub fn apply_rename(app: &mut BootstrappedApp, rename: &WatchRename) -> Result<()> {
+    let mut changed = false;
+    for entry in &mut app.playback_state.queue {
+        if let Ok(rest) = Path::new(&entry.path).strip_prefix(&rename.from) {
+            entry.path = Path::new(&rename.to)
+                .join(rest)
+                .to_string_lossy()
+                .to_string();
+            changed = true;
+        }
+    }
+    if changed {
+        persist_playback_state(app)?;
+    }
+    Ok(())
+}
+
+/// Moves a library file to the system trash and forgets the track: it leaves
+/// the database and the queue, stopping playback first if it is the current
+/// track. A file already gone from disk is only forgotten.
+pub fn trash_track(app: &mut BootstrappedApp, path: &str) -> Result<Reply> {
+    let track = app
+        .db
+        .get_track_by_path(path)?
+        .with_context(|| format!("not in the library: {path}"))?;
+    let on_disk = Path::new(path).exists();
+    if on_disk {
+        trash::delete(path).with_context(|| format!("failed to move {path} to the trash"))?;
+    }
+    app.db.delete_tracks_by_paths(&[path.to_string()])?;
+
+    // Decided before removing anything: removing an earlier copy of the
+    // track shifts the playing entry's index.
+    let playing_it = app
+        .playback_state
+        .current_entry()
+        .is_some_and(|entry| entry.track_id == track.id);
+    if playing_it && app.playback_state.session.status != PlaybackStatus::Stopped {
+        app.player.stop();
+        app.playback_state.session.status = PlaybackStatus::Stopped;
+    }
+    let mut index = 0;
+    let mut removed = false;
+    while index < app.playback_state.queue.len() {
+        if app.playback_state.queue[index].track_id != track.id {
+            index += 1;
+            continue;
+        }
+        app.playback_state.queue.remove(index);
+        adjust_playback_selection_after_queue_removal(&mut app.playback_state, index);
+        removed = true;
+    }
+    if removed {
+        persist_playback_state(app)?;
+    }
+
+    let title = track.title.unwrap_or_else(|| path.to_string());
+    let message = if on_disk {
+        format!("Moved to the trash: {title}")
+    } else {
+        format!("Already gone from disk, removed from the library: {title}")
+    };
+    tracing::info!(path, on_disk, "trashed track");
+    Ok(Reply::Changed(message))
+}
+
+/// Work that runs on a background thread with its own database connection;
+/// see [`spawn_background_scan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackgroundJob {
+    /// Scans one folder.
+    Scan(String),
+    /// Brings every watched folder in line with the disk; see
+    /// [`reconcile_watched_roots`].
+    ReconcileWatched,
+    Podcast(podcast::Job),
+    /// Copies the Subsonic library; `only_due` skips it after a recent sync.
+    SubsonicSync {
+        only_due: bool,
+    },
+    Analysis(analysis::Job),
+    Health(health::Job),
+}
+
+/// Queues `job` for the front end to start once the running one finishes.
+/// `message` is the status line shown while it runs.
+pub fn queue_job(app: &mut BootstrappedApp, job: BackgroundJob, message: impl Into<String>) {
+    app.background_jobs.push_back((job, message.into()));
+}
+
+/// The oldest queued job and its status line, taken off the queue.
+pub fn next_job(app: &mut BootstrappedApp) -> Option<(BackgroundJob, String)> {
+    app.background_jobs.pop_front()
+}
+
+/// The jobs every front end queues at startup: catching up on what changed in
+/// the watched folders while auric was not running, and refreshing podcasts
+/// and the Subsonic library when they are due.
+pub fn startup_jobs(app: &BootstrappedApp) -> Vec<(BackgroundJob, String)> {
+    let mut jobs = Vec::new();
+    if crate::has_watched_roots(app) {
+        jobs.push((
+            BackgroundJob::ReconcileWatched,
+            "Scanning watched folders...".to_string(),
+        ));
+    }
+    if crate::has_podcasts_to_refresh(app) {
+        jobs.push((
+            BackgroundJob::Podcast(podcast::Job::Refresh { only_due: true }),
+            "Refreshing podcasts...".to_string(),
+        ));
+    }
+    if subsonic::is_configured(&app.config.subsonic) && !app.offline {
+        jobs.push((
+            BackgroundJob::SubsonicSync { only_due: true },
+            format!("Syncing {}...", app.config.subsonic.name),
+        ));
+    }
+    jobs
+}
+
+/// Runs `job` on a background thread, reporting progress and the result on
+/// the returned channel, in whatever type the front end displays.
+pub fn spawn_background_scan<P: From<JobProgress> + Send + 'static>(
+    lib_config: LibraryConfig,
+    db_options: DatabaseOptions,
+    hooks_config: HooksConfig,
+    subsonic_config: SubsonicConfig,
+    job: BackgroundJob,
+) -> Receiver<P> {
+    let scan_path = match job {
+        BackgroundJob::Scan(path) => Some(path),
+        BackgroundJob::ReconcileWatched => None,
+        BackgroundJob::Podcast(job) => return spawn_job(db_options, move |db, _| job.run(db)),
+        BackgroundJob::SubsonicSync { only_due } => {
+            return spawn_job(db_options, move |db, _| {
+                subsonic::run_sync(db, &subsonic_config, only_due)
+            })
+        }
+        BackgroundJob::Analysis(job) => {
+            return spawn_job(db_options, move |db, progress| job.run(db, progress))
+        }
+        BackgroundJob::Health(job) => {
+            return spawn_job(db_options, move |db, progress| job.run(db, progress))
+        }
+    };
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        // Progress poller: check DB track count periodically
+        let progress_tx = tx.clone();
+        let progress_db_opts = db_options.clone();
+        let progress_path = scan_path
+            .clone()
+            .unwrap_or_else(|| "watched folders".to_string());
+        let progress_done = std::sync::Arc::clone(&done);
+        std::thread::spawn(move || {
+            let db = Database::open(&progress_db_opts)
+                .map_err(|err| {
+                    tracing::warn!(error = %err, "scan progress poller could not open the database");
+                })
+                .ok();
+            while !progress_done.load(std::sync::atomic::Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(750));
+                if let Some(ref db) = db {
+                    let count = db.stats().map(|s| s.track_count).unwrap_or(0);
+                    let progress = JobProgress::Progress {
+                        discovered: count as usize,
+                        path: progress_path.clone(),
+                    };
+                    let _ = progress_tx.send(P::from(progress));
+                }
+            }
+        });
+
+        let scan_result = (|| -> anyhow::Result<String> {
+            let mut db = Database::open(&db_options)?;
+            let scanner = scanner_from_config(&lib_config, false);
+            let Some(scan_path) = scan_path else {
+                let summaries = reconcile_watched_roots(&mut db, &scanner)?;
+                for summary in &summaries {
+                    hooks::run(
+                        &hooks_config,
+                        hooks::HookEvent::ScanComplete,
+                        hooks::reconcile_env(summary),
+                    );
+                }
+                return Ok(reconcile_message(&summaries));
+            };
+            let summary: ScanSummary = scanner.scan_path(&mut db, Path::new(&scan_path))?;
+            hooks::run(
+                &hooks_config,
+                hooks::HookEvent::ScanComplete,
+                hooks::scan_env(&summary),
+            );
+            Ok(format!(
+                "Scan complete: {} ({} tracks imported in {:.1}s)",
+                summary.root_path,
+                summary.imported_tracks,
+                summary.elapsed_ms as f64 / 1000.0,
+            ))
+        })();
+
+        done.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        let _ = tx.send(P::from(match scan_result {
+            Ok(message) => JobProgress::Done { message },
+            Err(err) => JobProgress::Error {
+                message: format!("{err:#}"),
+            },
+        }));
+    });
+    rx
+}
+
+/// Runs a job that is not a scan on a thread with its own connection. `job`
+/// gets a callback for status lines.
+fn spawn_job<P: From<JobProgress> + Send + 'static>(
+    db_options: DatabaseOptions,
+    job: impl FnOnce(&mut Database, &dyn Fn(String)) -> Result<String> + Send + 'static,
+) -> Receiver<P> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let progress = |message| {
+            let _ = tx.send(P::from(JobProgress::Status { message }));
+        };
+        let result = Database::open(&db_options)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut db| job(&mut db, &progress));
+        let _ = tx.send(P::from(match result {
+            Ok(message) => JobProgress::Done { message },
+            Err(err) => JobProgress::Error {
+                message: format!("{err:#}"),
+            },
+        }));
+    });
+    rx
+}
+
+/// Brings every watched folder in line with the disk, picking up what changed
+/// while auric was not running. Paused folders are left alone and folders
+/// that look unmounted are marked offline rather than emptied.
+pub fn reconcile_watched_roots(
+    db: &mut Database,
+    scanner: &DirectoryScanner,
+) -> Result<Vec<ReconcileSummary>> {
+    let mut summaries = Vec::new();
+    for root in db.list_library_roots()? {
+        if !root.watched || root.paused {
+            continue;
+        }
+        if root_looks_offline(Path::new(&root.path)) {
+            db.set_library_root_offline(&root.path, true)?;
+            tracing::warn!(root = %root.path, "watched folder is offline; keeping its tracks");
+            continue;
+        }
+        db.set_library_root_offline(&root.path, false)?;
+        let summary = scanner.reconcile_path(db, &root.path)?;
+        tracing::info!(
+            root = %summary.root_path,
+            added = summary.added_tracks,
+            updated = summary.updated_tracks,
+            removed = summary.removed_tracks,
+            "reconciled watched folder"
+        );
+        summaries.push(summary);
+    }
+    Ok(summaries)
+}
+
+/// One status line for a reconciliation run.
+pub fn reconcile_message(summaries: &[ReconcileSummary]) -> String {
+    let added = summaries.iter().map(|s| s.added_tracks).sum::<usize>();
+    let updated = summaries.iter().map(|s| s.updated_tracks).sum::<usize>();
+    let removed = summaries.iter().map(|s| s.removed_tracks).sum::<usize>();
+    let folders = match summaries.len() {
+        1 => "1 folder".to_string(),
+        n => format!("{n} folders"),
+    };
+    if added + updated + removed == 0 {
+        format!("Watched folders up to date ({folders})")
+    } else {
+        format!("Watched folders: {added} added, {updated} updated, {removed} removed ({folders})")
+    }
+}
+
+/// Outcome of [`run_maintenance`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceSummary {
+    pub size_before_bytes: i64,
+    pub size_after_bytes: i64,
+    /// `integrity_check` findings; empty when the database is healthy.
+    pub problems: Vec<String>,
+    pub orphans: OrphanCleanup,
+    /// Queue entries dropped because their track left the library.
+    pub queue_entries: usize,
+    pub vacuumed: bool,
+}
+
+/// Checks the database, removes rows pointing at deleted tracks (including
+/// queue entries) and, when `vacuum` is set, compacts the file. Cleanup is
+/// skipped if the integrity check finds problems, so a damaged file is left
+/// as it is for a backup or restore.
+pub fn run_maintenance(app: &mut BootstrappedApp, vacuum: bool) -> Result<MaintenanceSummary> {
+    flush_writes(app)?;
+    let mut summary = MaintenanceSummary {
+        size_before_bytes: app.db.stats()?.db_size_bytes,
+        problems: app.db.integrity_check()?,
+        ..MaintenanceSummary::default()
+    };
+    if summary.problems.is_empty() {
+        summary.orphans = app.db.remove_orphan_rows()?;
+        let mut index = 0;
+        while index < app.playback_state.queue.len() {
+            let track_id = app.playback_state.queue[index].track_id;
+            if app.db.get_track_by_id(track_id)?.is_some() {
+                index += 1;
+                continue;
+            }
+            app.playback_state.queue.remove(index);
+            adjust_playback_selection_after_queue_removal(&mut app.playback_state, index);
+            summary.queue_entries += 1;
+        }
+        if summary.queue_entries > 0 {
+            persist_playback_state(app)?;
+            flush_writes(app)?;
+        }
+        if vacuum {
+            app.db.vacuum()?;
+            summary.vacuumed = true;
+        }
+    }
+    summary.size_after_bytes = app.db.stats()?.db_size_bytes;
+    Ok(summary)
+}
+
+/// One status line for a maintenance run.
+pub fn maintenance_message(summary: &MaintenanceSummary) -> String {
+    if let Some(first) = summary.problems.first() {
+        return format!(
+            "Integrity check found {} problem(s), nothing changed: {first}",
+            summary.problems.len()
+        );
+    }
+    let size = |bytes: i64| {
+        let kib = bytes.max(0) as f64 / 1024.0;
+        if kib >= 1024.0 {
+            format!("{:.1} MiB", kib / 1024.0)
+        } else {
+            format!("{kib:.1} KiB")
+        }
+    };
+    format!(
+        "Database ok: removed {} orphaned playlist entries, {} artwork rows, {} queue entries; {} -> {}{}",
+        summary.orphans.playlist_entries,
+        summary.orphans.artwork_links + summary.orphans.artwork_assets,
+        summary.queue_entries,
+        size(summary.size_before_bytes),
+        size(summary.size_after_bytes),
+        if summary.vacuumed { " after VACUUM" } else { "" }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bootstrap_from_config_path, execute_ui_palette_command};
+    use auric_library::{LibraryRoot, TrackRecord};
+    use std::fs;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    #[test]
+    fn startup_jobs_catch_up_on_watched_folders_and_due_feeds() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert!(startup_jobs(&app).is_empty());
+
+        app.db
+            .upsert_library_root(&LibraryRoot {
+                path: "/music".to_string(),
+                watched: true,
+            })
+            .unwrap();
+        app.db
+            .subscribe_podcast("https://example.com/feed.xml", "Show")
+            .unwrap();
+        let jobs: Vec<_> = startup_jobs(&app).into_iter().map(|(job, _)| job).collect();
+        assert_eq!(
+            jobs,
+            [
+                BackgroundJob::ReconcileWatched,
+                BackgroundJob::Podcast(podcast::Job::Refresh { only_due: true }),
+            ]
+        );
+
+        for (job, message) in startup_jobs(&app) {
+            queue_job(&mut app, job, message);
+        }
+        assert_eq!(
+            next_job(&mut app),
+            Some((
+                BackgroundJob::ReconcileWatched,
+                "Scanning watched folders...".to_string()
+            ))
+        );
+        crate::go_offline(&mut app);
+        assert_eq!(
+            startup_jobs(&app)
+                .into_iter()
+                .map(|(job, _)| job)
+                .collect::<Vec<_>>(),
+            [BackgroundJob::ReconcileWatched]
+        );
+    }
+
+    #[test]
+    fn maintenance_drops_queue_entries_for_deleted_tracks() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for i in 0..2 {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: format!("/missing/t-{i}.flac"),
+                    title: Some(format!("T{i}")),
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        app.playback_state.queue = app
+            .db
+            .list_tracks_by_prefix("/missing", 10)
+            .unwrap()
+            .into_iter()
+            .map(playback_queue_entry_from_track_row)
+            .collect();
+        app.playback_state.session.current_index = Some(1);
+        app.db
+            .delete_tracks_by_paths(&["/missing/t-0.flac".to_string()])
+            .unwrap();
+
+        let summary = run_maintenance(&mut app, true).unwrap();
+        assert!(summary.problems.is_empty());
+        assert_eq!(summary.queue_entries, 1);
+        assert_eq!(app.playback_state.queue.len(), 1);
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+        let message = maintenance_message(&summary);
+        assert!(message.starts_with(
+            "Database ok: removed 0 orphaned playlist entries, 0 artwork rows, 1 queue entries;"
+        ));
+        assert!(message.ends_with("after VACUUM"));
+    }
+
+    #[test]
+    fn trashing_forgets_the_track_everywhere() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for i in 0..3 {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: format!("/missing/t-{i}.flac"),
+                    title: Some(format!("T{i}")),
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        app.playback_state.queue = app
+            .db
+            .list_tracks_by_prefix("/missing", 10)
+            .unwrap()
+            .into_iter()
+            .map(playback_queue_entry_from_track_row)
+            .collect();
+        app.playback_state.session.current_index = Some(2);
+
+        // Files that are already gone are only forgotten, so nothing here
+        // reaches the real trash.
+        let result = trash_track(&mut app, "/missing/t-1.flac").unwrap();
+        assert!(result.changed());
+        assert_eq!(
+            result.message(),
+            "Already gone from disk, removed from the library: T1"
+        );
+        assert!(app
+            .db
+            .get_track_by_path("/missing/t-1.flac")
+            .unwrap()
+            .is_none());
+        assert_eq!(app.playback_state.queue.len(), 2);
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+        assert_eq!(
+            app.playback_state.current_entry().unwrap().path,
+            "/missing/t-2.flac"
+        );
+        assert!(trash_track(&mut app, "/missing/t-1.flac").is_err());
+
+        // The playing track also queued ahead of where it plays.
+        let queue = &mut app.playback_state.queue;
+        queue.insert(0, queue[1].clone());
+        app.playback_state.session.current_index = Some(2);
+        app.playback_state.session.status = PlaybackStatus::Playing;
+        trash_track(&mut app, "/missing/t-2.flac").unwrap();
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Stopped);
+        let paths: Vec<_> = app
+            .playback_state
+            .queue
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect();
+        assert_eq!(paths, ["/missing/t-0.flac"]);
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+    }
+
+    #[test]
+    fn requests_drive_playback_without_a_terminal() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for i in 0..2 {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: format!("/missing/t-{i}.flac"),
+                    title: Some(format!("T{i}")),
+                    artist: Some("A".to_string()),
+                    album: None,
+                    duration_ms: Some(61_000),
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+
+        let played = handle(
+            &mut app,
+            Request::Playback(Action::PlayTrack { track_index: 1 }),
+        )
+        .unwrap();
+        assert_eq!(played.message(), "Playing: T1");
+        let status = handle(&mut app, Request::Status).unwrap();
+        assert_eq!(status.message(), "playing: A - T1 (00:00 / 01:01)");
+
+        handle(&mut app, Request::Playback(Action::Pause)).unwrap();
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Paused);
+        let events = tick(&mut app, Instant::now());
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, PlayerEvent::Error { .. })));
+        drop(app);
+
+        let app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+    }
+
+    #[test]
+    fn audiobooks_resume_where_they_stopped_and_keep_bookmarks() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n\n[playback]\nresume_folders = [\"/books\"]\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for (i, path) in ["/books/novel.m4b", "/music/song.flac"]
+            .into_iter()
+            .enumerate()
+        {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: path.to_string(),
+                    title: Some(format!("T{i}")),
+                    artist: None,
+                    album: None,
+                    duration_ms: Some(180_000),
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        let play = |app: &mut BootstrappedApp, track_index| {
+            handle(app, Request::Playback(Action::PlayTrack { track_index }))
+                .unwrap()
+                .message()
+                .to_string()
+        };
+        let command = |app: &mut BootstrappedApp, input: &str| {
+            execute_ui_palette_command(app, input)
+                .unwrap()
+                .status_message
+        };
+
+        assert_eq!(play(&mut app, 0), "Playing: T0");
+        app.playback_state.session.position_ms = 90_000;
+        assert_eq!(
+            command(&mut app, "bookmark add Chapter 2"),
+            "Bookmarked 01:30 in T0: Chapter 2"
+        );
+        assert_eq!(
+            command(&mut app, "remember"),
+            "Remember position: on (automatic)"
+        );
+        assert_eq!(play(&mut app, 1), "Playing: T1");
+        app.playback_state.session.position_ms = 30_000;
+        assert_eq!(play(&mut app, 0), "Playing: T0 from 01:30");
+        assert_eq!(app.playback_state.session.position_ms, 90_000);
+        assert_eq!(
+            command(&mut app, "bookmark"),
+            "Bookmarks: 1) 01:30 Chapter 2"
+        );
+        assert_eq!(play(&mut app, 1), "Playing: T1");
+
+        assert_eq!(command(&mut app, "remember on"), "Remember position: on");
+        app.playback_state.session.position_ms = 45_000;
+        assert_eq!(play(&mut app, 0), "Playing: T0 from 01:30");
+        assert_eq!(play(&mut app, 1), "Playing: T1 from 00:45");
+    }
+
+    #[test]
+    fn opening_a_path_scans_its_folder_and_plays_it() {
+        let dir = tempdir().unwrap();
+        let music = dir.path().join("album");
+        fs::create_dir(&music).unwrap();
+        fs::write(music.join("01.flac"), b"x").unwrap();
+        fs::write(music.join("02.flac"), b"x").unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        // Sorts before the album in the library, and stays out of its queue.
+        let other = dir.path().join("a-single");
+        fs::create_dir(&other).unwrap();
+        fs::write(other.join("01.flac"), b"x").unwrap();
+        open_path_and_play(&mut app, &other).unwrap();
+
+        open_path_and_play(&mut app, &music.join("02.flac")).unwrap();
+        let current = app.playback_state.current_entry().unwrap();
+        assert!(current.path.ends_with("02.flac"));
+        assert_eq!(app.playback_state.queue.len(), 2);
+        assert!(app.db.list_library_roots().unwrap().is_empty());
+
+        open_path_and_play(&mut app, &music).unwrap();
+        assert!(app
+            .playback_state
+            .current_entry()
+            .unwrap()
+            .path
+            .ends_with("01.flac"));
+
+        let empty = dir.path().join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert!(open_path_and_play(&mut app, &empty).is_err());
+    }
+
+    #[test]
+    fn max_volume_caps_every_way_of_raising_the_volume() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[playback]\nmax_volume = 60\n\n[database]\npath = \"{}\"\n",
+                dir.path().join("auric-test.db").display()
+            ),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(app.playback_state.session.volume, 0.6);
+        for _ in 0..5 {
+            handle_playback_action(&mut app, Action::VolumeUp).unwrap();
+        }
+        assert_eq!(app.playback_state.session.volume, 0.6);
+        handle_playback_action(&mut app, Action::SetVolume { volume: 1.0 }).unwrap();
+        assert_eq!(app.playback_state.session.volume, 0.6);
+        dispatch_app_command(&mut app, AppCommand::SetVolume(0.9)).unwrap();
+        assert_eq!(app.playback_state.session.volume, 0.6);
+
+        // Lowering the cap brings the volume down with it.
+        execute_ui_palette_command(&mut app, "__setting_set max_volume 40").unwrap();
+        assert_eq!(app.playback_state.session.volume, 0.4);
+        execute_ui_palette_command(&mut app, "__setting_set max_volume 100").unwrap();
+        handle_playback_action(&mut app, Action::VolumeUp).unwrap();
+        assert!((app.playback_state.session.volume - 0.45).abs() < 1e-6);
+        assert!(execute_ui_palette_command(&mut app, "__setting_set limiter maybe").is_err());
+    }
+
+    #[test]
+    fn session_changes_are_autosaved_after_the_interval() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        handle_playback_action(&mut app, Action::SetVolume { volume: 0.25 }).unwrap();
+        assert!(app.session_dirty);
+
+        let now = Instant::now();
+        app.last_autosave = now;
+        assert!(!autosave_if_due(&mut app, now + Duration::from_secs(1)).unwrap());
+        assert!(autosave_if_due(&mut app, now + AUTOSAVE_INTERVAL).unwrap());
+        assert!(!app.session_dirty);
+        assert!(!autosave_if_due(&mut app, now + AUTOSAVE_INTERVAL * 2).unwrap());
+        drop(app);
+
+        let app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert!((app.playback_state.session.volume - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn playback_transport_next_previous_updates_selection() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for i in 0..3 {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: format!("/tmp/t-{i}.flac"),
+                    title: Some(format!("T{i}")),
+                    artist: Some("A".to_string()),
+                    album: Some("B".to_string()),
+                    duration_ms: Some(1000),
+                    sample_rate: Some(44_100),
+                    channels: Some(2),
+                    bit_depth: Some(16),
+                    file_mtime_ms: Some(i as i64),
+                })
+                .unwrap();
+        }
+        let rows = app.db.list_tracks_by_prefix("/tmp", 10).unwrap();
+        app.playback_state.queue = rows
+            .into_iter()
+            .map(playback_queue_entry_from_track_row)
+            .collect();
+        app.playback_state.session.current_index = Some(0);
+        app.playback_state.session.status = PlaybackStatus::Playing;
+        persist_playback_state(&mut app).unwrap();
+
+        dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+        assert_eq!(app.playback_state.session.position_ms, 0);
+
+        app.playback_state.session.position_ms = 4_000;
+        persist_playback_state(&mut app).unwrap();
+        dispatch_app_command(&mut app, AppCommand::Previous).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+        assert_eq!(app.playback_state.session.position_ms, 0);
+
+        dispatch_app_command(&mut app, AppCommand::Previous).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+
+        dispatch_app_command(&mut app, AppCommand::Previous).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+    }
+
+    #[test]
+    fn shuffle_pass_and_queue_position_survive_a_restart() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        app.playback_state.queue = (0..5)
+            .map(|i| PlaybackQueueEntry {
+                track_id: TrackId(Uuid::new_v4()),
+                path: format!("/tmp/s-{i}.flac"),
+                title: None,
+                artist: None,
+                album: None,
+                duration_ms: None,
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+            })
+            .collect();
+        app.playback_state.session.current_index = Some(0);
+        app.playback_state.session.status = PlaybackStatus::Playing;
+        app.playback_state.session.shuffle = true;
+
+        let mut played = vec![0];
+        for _ in 0..4 {
+            dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+            played.push(app.playback_state.session.current_index.unwrap());
+        }
+        let mut distinct = played.clone();
+        distinct.sort_unstable();
+        assert_eq!(distinct, [0, 1, 2, 3, 4], "no repeats within a pass");
+
+        dispatch_app_command(&mut app, AppCommand::Previous).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(played[3]));
+
+        app.playback_state.session.position_ms = 42_000;
+        persist_playback_state(&mut app).unwrap();
+        flush_writes(&app).unwrap();
+        let restored = load_playback_state(&app.db).unwrap();
+        assert_eq!(restored.session.status, PlaybackStatus::Stopped);
+        assert_eq!(restored.session.current_index, Some(played[3]));
+        assert_eq!(restored.session.position_ms, 42_000);
+        assert_eq!(
+            restored.session.shuffle_history,
+            app.playback_state.session.shuffle_history
+        );
+        assert_eq!(restored.session.shuffle_history.len(), 3);
+    }
+
+    #[test]
+    fn consume_drops_played_tracks_and_dedupe_keeps_the_playing_copy() {
+        let mut state = PlaybackState::default();
+        let ids = [TrackId(Uuid::new_v4()), TrackId(Uuid::new_v4())];
+        for (i, id) in [ids[0], ids[1], ids[0], ids[1], ids[0]]
+            .into_iter()
+            .enumerate()
+        {
+            state.queue.push(PlaybackQueueEntry {
+                track_id: id,
+                path: format!("/tmp/d-{i}.flac"),
+                title: None,
+                artist: None,
+                album: None,
+                duration_ms: None,
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+            });
+        }
+        state.session.current_index = Some(2);
+        assert_eq!(dedupe_queue(&mut state), 3);
+        let paths: Vec<_> = state.queue.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/tmp/d-1.flac", "/tmp/d-2.flac"]);
+        assert_eq!(state.session.current_index, Some(1));
+        assert_eq!(dedupe_queue(&mut state), 0);
+
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        app.playback_state = state;
+        app.playback_state.session.current_index = Some(0);
+        handle_playback_action(&mut app, Action::ToggleConsume).unwrap();
+        dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+        assert_eq!(app.playback_state.queue.len(), 1);
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+        assert_eq!(app.playback_state.queue[0].path, "/tmp/d-2.flac");
+
+        dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+        assert!(app.playback_state.queue.is_empty());
+        assert_eq!(app.playback_state.session.current_index, None);
+        flush_writes(&app).unwrap();
+        assert!(load_playback_state(&app.db).unwrap().session.consume);
+    }
+
+    #[test]
+    fn renames_update_queue_paths() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let entry = |path: &str| PlaybackQueueEntry {
+            track_id: TrackId(Uuid::new_v4()),
+            path: path.to_string(),
+            title: None,
+            artist: None,
+            album: None,
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+        };
+        app.playback_state.queue = vec![entry("/music/old/01.flac"), entry("/music/older.flac")];
+
+        let rename = WatchRename {
+            from: "/music/old".to_string(),
+            to: "/music/new".to_string(),
+            moved_tracks: 1,
+        };
+        apply_rename(&mut app, &rename).unwrap();
+        let paths: Vec<_> = app
+            .playback_state
+            .queue
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect();
+        assert_eq!(paths, ["/music/new/01.flac", "/music/older.flac"]);
+    }
+
+    #[test]
+    fn watched_folders_are_reconciled_and_paused_ones_skipped() {
+        let dir = tempdir().unwrap();
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let add_root = |name: &str, watched: bool, files: &[&str]| {
+            let root = dir.path().join(name);
+            fs::create_dir(&root).unwrap();
+            for file in files {
+                fs::write(root.join(file), b"x").unwrap();
+            }
+            let path = root.canonicalize().unwrap().display().to_string();
+            db.upsert_library_root(&LibraryRoot {
+                path: path.clone(),
+                watched,
+            })
+            .unwrap();
+            path
+        };
+        let music = add_root("music", true, &["a.flac", "b.flac"]);
+        let paused = add_root("paused", true, &["c.flac"]);
+        add_root("manual", false, &["d.flac"]);
+        let unmounted = add_root("nas", true, &[]);
+        db.set_library_root_paused(&paused, true).unwrap();
+
+        let scanner = DirectoryScanner::new(Default::default());
+        let summaries = reconcile_watched_roots(&mut db, &scanner).unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].root_path, music);
+        assert_eq!(
+            reconcile_message(&summaries),
+            "Watched folders: 2 added, 0 updated, 0 removed (1 folder)"
+        );
+        assert_eq!(db.count_tracks().unwrap(), 2);
+        let nas = db.get_library_root_by_path(&unmounted).unwrap().unwrap();
+        assert!(nas.offline_since_ms.is_some());
+
+        let summaries = reconcile_watched_roots(&mut db, &scanner).unwrap();
+        assert_eq!(
+            reconcile_message(&summaries),
+            "Watched folders up to date (1 folder)"
+        );
+    }
+
+    #[test]
+    fn random_album_mode_queues_another_album_when_one_ends() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for (album, track) in [("A", 2), ("A", 1), ("B", 2), ("B", 1)] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: format!("/music/{album}/{track}.flac"),
+                    title: Some(format!("{album}{track}")),
+                    artist: Some("Band".to_string()),
+                    album: Some(album.to_string()),
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        app.playback_state.queue = app
+            .db
+            .list_album_tracks("A", "Band")
+            .unwrap()
+            .into_iter()
+            .map(playback_queue_entry_from_track_row)
+            .collect();
+        app.playback_state.session.current_index = Some(0);
+        app.playback_state.session.random_album = true;
+        let titles = |app: &BootstrappedApp| -> Vec<String> {
+            app.playback_state
+                .queue
+                .iter()
+                .filter_map(|entry| entry.title.clone())
+                .collect()
+        };
+        assert_eq!(titles(&app), ["A1", "A2"]);
+
+        dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+        let events = dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+        assert_eq!(titles(&app), ["B1", "B2"]);
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, AppEvent::TrackChanged { .. })));
+        assert!(app
+            .db
+            .random_album(Some(("B", "Band")))
+            .unwrap()
+            .is_some_and(|(album, _)| album == "A"));
+    }
+
+    #[test]
+    fn radio_appends_related_artists_when_the_queue_runs_out() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for (artist, album) in [
+            ("Seed", "Split"),
+            ("Friend", "Split"),
+            ("Stranger", "Alone"),
+        ] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: format!("/music/{artist}.flac"),
+                    title: Some(artist.to_string()),
+                    artist: Some(artist.to_string()),
+                    album: Some(album.to_string()),
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        app.playback_state.queue = app
+            .db
+            .list_tracks_by_artist("Seed")
+            .unwrap()
+            .into_iter()
+            .map(playback_queue_entry_from_track_row)
+            .collect();
+        app.playback_state.session.current_index = Some(0);
+
+        dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+        assert_eq!(app.playback_state.queue.len(), 1);
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Stopped);
+
+        app.playback_state.session.current_index = Some(0);
+        app.playback_state.session.radio = true;
+        dispatch_app_command(&mut app, AppCommand::Next).unwrap();
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+        assert_eq!(
+            app.playback_state
+                .current_entry()
+                .and_then(|e| e.artist.as_deref()),
+            Some("Friend")
+        );
+    }
+
+    #[test]
+    fn party_mode_only_lets_guests_enqueue() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n[party]\nrequest_cooldown_secs = 60\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for title in ["One", "Two"] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(Uuid::new_v4()),
+                    path: format!("/music/{title}.flac"),
+                    title: Some(title.to_string()),
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        app.party = Some(crate::party::PartyMode::new("disco"));
+
+        assert!(handle_playback_action(&mut app, Action::Next).is_err());
+        assert!(handle_playback_action(&mut app, Action::SetVolume { volume: 1.0 }).is_err());
+        let enqueue = |path: &str, by: &str| Action::Enqueue {
+            path: path.to_string(),
+            requested_by: by.to_string(),
+        };
+        handle_playback_action(&mut app, enqueue("/music/One.flac", "keyboard")).unwrap();
+        assert_eq!(app.playback_state.queue.len(), 1);
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+        let err =
+            handle_playback_action(&mut app, enqueue("/music/Two.flac", "keyboard")).unwrap_err();
+        assert!(err.to_string().contains("next request in"), "{err}");
+        handle_playback_action(&mut app, enqueue("/music/Two.flac", "10.0.0.7")).unwrap();
+        assert_eq!(app.playback_state.queue.len(), 2);
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+    }
+
+    #[test]
+    fn sleep_pauses_playback_and_waking_leaves_it_paused() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        reopen_after_sleep(&mut app);
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Stopped);
+
+        app.playback_state.queue = vec![PlaybackQueueEntry {
+            track_id: TrackId(Uuid::new_v4()),
+            path: "/music/missing.flac".to_string(),
+            title: None,
+            artist: None,
+            album: None,
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+        }];
+        app.playback_state.session.current_index = Some(0);
+        app.playback_state.session.status = PlaybackStatus::Playing;
+        app.playback_state.session.position_ms = 42_000;
+        pause_for_sleep(&mut app);
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Paused);
+
+        app.playback_state.session.status = PlaybackStatus::Playing;
+        reopen_after_sleep(&mut app);
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Paused);
+        assert_eq!(app.playback_state.session.position_ms, 42_000);
+    }
+}
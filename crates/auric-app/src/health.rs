@@ -0,0 +1,163 @@
+//! The library health report: tracks with missing tags or artwork, zero
+//! lengths, files that no longer read, and lossy files at low bitrates.
+//!
+//! Artwork and lengths come straight from the database. The rest needs a
+//! look at each file, which the check job does in the background and
+//! records per track until the file changes. The fix jobs write the tags the
+//! scanner would guess from file names, and link cover images found next to
+//! the music.
+
+use anyhow::Result;
+use auric_library::db::{Database, HealthIssue, TrackHealth};
+use auric_library::health::{fill_missing_tags, inspect_file};
+use auric_library::scan::{link_folder_covers, ScanOptions};
+use std::path::Path;
+
+/// Tracks read per page while checking.
+const PAGE_SIZE: usize = 256;
+
+/// Work for the background thread; see [`crate::engine::BackgroundJob`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    Check,
+    FixTags,
+    FixArtwork,
+}
+
+impl Job {
+    /// Runs the job against `db`, returning the status line to show.
+    pub fn run(self, db: &mut Database, progress: &dyn Fn(String)) -> Result<String> {
+        match self {
+            Self::Check => check_library(db, progress),
+            Self::FixTags => fix_missing_tags(db, progress),
+            Self::FixArtwork => fix_missing_artwork(db),
+        }
+    }
+}
+
+/// What the bulk fix for `issue` does and the palette command that runs it.
+pub fn fix(issue: HealthIssue) -> Option<(&'static str, String)> {
+    match issue {
+        HealthIssue::MissingTags => Some((
+            "Fill missing tags from file names",
+            "health fix tags".to_string(),
+        )),
+        HealthIssue::MissingArtwork => Some((
+            "Link folder covers to tracks without artwork",
+            "health fix artwork".to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// What the check found in a file, for showing next to the track, e.g.
+/// `no artist, album` or `96 kbps`.
+pub fn detail(found: &TrackHealth) -> Option<String> {
+    if !found.readable {
+        return Some("cannot be read".to_string());
+    }
+    let mut parts = Vec::new();
+    if !found.missing_tags.is_empty() {
+        parts.push(format!("no {}", found.missing_tags.join(", ")));
+    }
+    if let Some(kbps) = found.low_bitrate_kbps {
+        parts.push(format!("{kbps} kbps"));
+    }
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+/// Reads every local track's file and records what it found.
+pub fn check_library(db: &mut Database, progress: &dyn Fn(String)) -> Result<String> {
+    let total = db.count_tracks()?;
+    let (mut checked, mut after) = (0usize, String::new());
+    loop {
+        let page = db.track_paths_after(&after, PAGE_SIZE)?;
+        let Some((_, last)) = page.last() else { break };
+        after = last.clone();
+        for (id, path) in &page {
+            if path.starts_with(crate::subsonic::PATH_PREFIX) {
+                continue;
+            }
+            db.set_track_health(id, &inspect_file(Path::new(path)))?;
+            checked += 1;
+        }
+        progress(format!("Checking library health... {checked}/{total}"));
+    }
+    let issues = HealthIssue::ALL
+        .into_iter()
+        .map(|issue| Ok((issue, db.count_health_issue(issue)?)))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(issue, count)| format!("{count} {}", issue.label().to_lowercase()))
+        .collect::<Vec<_>>();
+    Ok(match issues.is_empty() {
+        true => format!("Checked {checked} tracks: no issues found"),
+        false => format!("Checked {checked} tracks: {}", issues.join(", ")),
+    })
+}
+
+/// Fills the empty tags of every track the last check found missing some.
+pub fn fix_missing_tags(db: &mut Database, progress: &dyn Fn(String)) -> Result<String> {
+    let tracks = db.tracks_with_health_issue(HealthIssue::MissingTags, usize::MAX)?;
+    let total = tracks.len();
+    let (mut fixed, mut failed) = (0usize, 0usize);
+    for (done, (track, _)) in tracks.iter().enumerate() {
+        match fill_missing_tags(db, Path::new(&track.path)) {
+            Ok(filled) if filled.is_empty() => {}
+            Ok(_) => fixed += 1,
+            Err(err) => {
+                tracing::warn!(path = %track.path, error = %err, "could not fill missing tags");
+                failed += 1;
+            }
+        }
+        progress(format!("Filling missing tags... {}/{total}", done + 1));
+    }
+    Ok(match (fixed, failed) {
+        (0, 0) => "No tags could be guessed from file names".to_string(),
+        (n, 0) => format!("Filled missing tags in {n} files"),
+        (n, failed) => format!("Filled missing tags in {n} files ({failed} could not be written)"),
+    })
+}
+
+/// Links the folder cover, where there is one, to every track without artwork.
+pub fn fix_missing_artwork(db: &mut Database) -> Result<String> {
+    let paths = db
+        .tracks_with_health_issue(HealthIssue::MissingArtwork, usize::MAX)?
+        .into_iter()
+        .map(|(track, _)| track.path)
+        .collect::<Vec<_>>();
+    let linked = link_folder_covers(
+        db,
+        &paths,
+        ScanOptions::default().max_embedded_artwork_bytes,
+    )?;
+    Ok(match linked {
+        0 => "No folder covers found for tracks without artwork".to_string(),
+        n => format!("Linked folder covers to {n} tracks"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detail_sums_up_what_the_check_found() {
+        let found = |readable, missing: &[&str], low_bitrate_kbps| TrackHealth {
+            readable,
+            missing_tags: missing.iter().map(|s| s.to_string()).collect(),
+            low_bitrate_kbps,
+        };
+        assert_eq!(detail(&found(true, &[], None)), None);
+        assert_eq!(
+            detail(&found(true, &["artist", "album"], Some(96))).as_deref(),
+            Some("no artist, album · 96 kbps")
+        );
+        assert_eq!(
+            detail(&found(false, &["title"], None)).as_deref(),
+            Some("cannot be read")
+        );
+        assert!(fix(HealthIssue::MissingTags).is_some());
+    }
+}
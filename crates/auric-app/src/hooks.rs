@@ -0,0 +1,241 @@
+//! User commands run on player events, configured under `[hooks]`.
+//!
+//! Each hook runs through the shell with details in `AURIC_*` environment
+//! variables and without blocking playback. Failures are logged.
+
+use crate::HooksConfig;
+use auric_core::{PlaybackQueueEntry, PlaybackState, PlaybackStatus};
+use auric_library::scan::{ReconcileSummary, ScanSummary};
+use std::process::{Command, Stdio};
+use std::thread::JoinHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    TrackStart,
+    TrackEnd,
+    Pause,
+    Resume,
+    ScanComplete,
+}
+
+impl HookEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TrackStart => "track_start",
+            Self::TrackEnd => "track_end",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::ScanComplete => "scan_complete",
+        }
+    }
+}
+
+impl HooksConfig {
+    fn command(&self, event: HookEvent) -> Option<&str> {
+        let command = match event {
+            HookEvent::TrackStart => &self.track_start,
+            HookEvent::TrackEnd => &self.track_end,
+            HookEvent::Pause => &self.pause,
+            HookEvent::Resume => &self.resume,
+            HookEvent::ScanComplete => &self.scan_complete,
+        };
+        Some(command.trim()).filter(|c| !c.is_empty())
+    }
+}
+
+/// What the player was doing, for telling which event an action caused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerMark {
+    status: PlaybackStatus,
+    path: Option<String>,
+}
+
+impl PlayerMark {
+    pub fn of(state: &PlaybackState) -> Self {
+        Self {
+            status: state.session.status,
+            path: state.current_entry().map(|entry| entry.path.clone()),
+        }
+    }
+
+    /// The event that moving from `self` to `after` represents, if any.
+    pub fn transition(&self, after: &Self) -> Option<HookEvent> {
+        match (self.status, after.status) {
+            (_, PlaybackStatus::Playing)
+                if self.status == PlaybackStatus::Stopped || self.path != after.path =>
+            {
+                Some(HookEvent::TrackStart)
+            }
+            (PlaybackStatus::Playing, PlaybackStatus::Paused) => Some(HookEvent::Pause),
+            (PlaybackStatus::Paused, PlaybackStatus::Playing) => Some(HookEvent::Resume),
+            _ => None,
+        }
+    }
+}
+
+/// `AURIC_TRACK_*` variables for the current track plus the position.
+pub fn track_env(state: &PlaybackState) -> Vec<(&'static str, String)> {
+    let mut env = vec![("AURIC_POSITION_MS", state.session.position_ms.to_string())];
+    if let (Some(index), Some(entry)) = (state.session.current_index, state.current_entry()) {
+        env.extend(entry_env(entry));
+        env.push(("AURIC_TRACK_INDEX", index.to_string()));
+    }
+    env
+}
+
+fn entry_env(entry: &PlaybackQueueEntry) -> Vec<(&'static str, String)> {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    vec![
+        ("AURIC_TRACK_PATH", entry.path.clone()),
+        ("AURIC_TRACK_TITLE", text(&entry.title)),
+        ("AURIC_TRACK_ARTIST", text(&entry.artist)),
+        ("AURIC_TRACK_ALBUM", text(&entry.album)),
+        (
+            "AURIC_TRACK_DURATION_MS",
+            entry
+                .duration_ms
+                .map(|ms| ms.to_string())
+                .unwrap_or_default(),
+        ),
+    ]
+}
+
+pub fn scan_env(summary: &ScanSummary) -> Vec<(&'static str, String)> {
+    vec![
+        ("AURIC_SCAN_PATH", summary.root_path.clone()),
+        ("AURIC_SCAN_IMPORTED", summary.imported_tracks.to_string()),
+        ("AURIC_SCAN_ELAPSED_MS", summary.elapsed_ms.to_string()),
+    ]
+}
+
+/// A startup reconciliation of one watched folder; `IMPORTED` counts both
+/// new and re-read tracks.
+pub fn reconcile_env(summary: &ReconcileSummary) -> Vec<(&'static str, String)> {
+    vec![
+        ("AURIC_SCAN_PATH", summary.root_path.clone()),
+        (
+            "AURIC_SCAN_IMPORTED",
+            (summary.added_tracks + summary.updated_tracks).to_string(),
+        ),
+        ("AURIC_SCAN_ADDED", summary.added_tracks.to_string()),
+        ("AURIC_SCAN_UPDATED", summary.updated_tracks.to_string()),
+        ("AURIC_SCAN_REMOVED", summary.removed_tracks.to_string()),
+        ("AURIC_SCAN_ELAPSED_MS", summary.elapsed_ms.to_string()),
+    ]
+}
+
+/// Starts the hook for `event`, if one is configured. The returned thread
+/// waits for the command and logs a failure; callers normally drop it.
+pub fn run(
+    config: &HooksConfig,
+    event: HookEvent,
+    env: Vec<(&'static str, String)>,
+) -> Option<JoinHandle<()>> {
+    let command = config.command(event)?;
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .env("AURIC_EVENT", event.name())
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    let mut child = match shell.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            tracing::warn!("{} hook failed to start: {err}", event.name());
+            return None;
+        }
+    };
+    Some(std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => {
+            tracing::warn!("{} hook exited with {status}", event.name());
+        }
+        Ok(_) => {}
+        Err(err) => tracing::warn!("{} hook could not be waited on: {err}", event.name()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mark(status: PlaybackStatus, path: &str) -> PlayerMark {
+        PlayerMark {
+            status,
+            path: Some(path.to_string()),
+        }
+    }
+
+    #[test]
+    fn transitions_map_to_events() {
+        use PlaybackStatus::*;
+        let cases = [
+            (
+                mark(Stopped, "a"),
+                mark(Playing, "a"),
+                Some(HookEvent::TrackStart),
+            ),
+            (
+                mark(Playing, "a"),
+                mark(Playing, "b"),
+                Some(HookEvent::TrackStart),
+            ),
+            (
+                mark(Playing, "a"),
+                mark(Paused, "a"),
+                Some(HookEvent::Pause),
+            ),
+            (
+                mark(Paused, "a"),
+                mark(Playing, "a"),
+                Some(HookEvent::Resume),
+            ),
+            (mark(Playing, "a"), mark(Playing, "a"), None),
+            (mark(Playing, "a"), mark(Stopped, "a"), None),
+        ];
+        for (before, after, expected) in cases {
+            assert_eq!(
+                before.transition(&after),
+                expected,
+                "{before:?} -> {after:?}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks_run_with_event_details_in_the_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("hook.txt");
+        let config = HooksConfig {
+            scan_complete: format!(
+                "printf '%s %s %s' \"$AURIC_EVENT\" \"$AURIC_SCAN_PATH\" \"$AURIC_SCAN_IMPORTED\" > '{}'",
+                out.display()
+            ),
+            ..HooksConfig::default()
+        };
+        assert!(run(&config, HookEvent::Pause, Vec::new()).is_none());
+
+        let env = vec![
+            ("AURIC_SCAN_PATH", "/music".to_string()),
+            ("AURIC_SCAN_IMPORTED", "12".to_string()),
+        ];
+        run(&config, HookEvent::ScanComplete, env)
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "scan_complete /music 12"
+        );
+    }
+}
@@ -0,0 +1,746 @@
+//! Optional HTTP API for browser remotes and stream overlays.
+//!
+//! `GET /` serves a small remote-control page. JSON endpoints live under
+//! `/api`; `GET /api/events` upgrades to a WebSocket that pushes the player
+//! state whenever it changes and the position once a second while playing.
+//! Like the MPD server, reads go through [`StateQuery`] and playback changes
+//! through the shell's [`RemoteInbox`].
+//!
+//! Every request must carry `[http] token`, and name the server by a loopback
+//! name or the configured bind address in its `Host`, which keeps out other
+//! machines and DNS-rebinding pages. Requests that change something are also
+//! refused from a page on another origin.
+
+use crate::remote::{self, StateQuery};
+use crate::HttpConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use auric_core::{PlaybackQueueEntry, PlaybackState};
+use auric_library::db::{Database, DatabaseOptions, TrackRow};
+use auric_ui::remote_inbox::RemoteInbox;
+use auric_ui::shell::KeyAction;
+use auric_ui::PlaybackAction;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+
+const REMOTE_PAGE: &str = include_str!("../assets/remote.html");
+/// How long a request waits for the shell to perform an action.
+const HANDLE_TIMEOUT: Duration = Duration::from_secs(3);
+/// How often the server and event streams wake up.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const POSITION_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+/// Event streams served at once; each holds a thread.
+const MAX_EVENT_CLIENTS: usize = 8;
+
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(400, message)
+    }
+
+    fn unavailable() -> Self {
+        Self::new(503, "player did not respond")
+    }
+}
+
+type ApiResult = Result<Value, ApiError>;
+
+/// The token requests must carry and the host names the server answers to.
+struct Access {
+    token: String,
+    hosts: Vec<String>,
+}
+
+impl Access {
+    fn new(config: &HttpConfig) -> Self {
+        let mut hosts: Vec<String> = ["localhost", "127.0.0.1", "::1"].map(String::from).into();
+        let bind = host_name(config.bind.trim()).to_ascii_lowercase();
+        if !bind.is_empty() && bind != "0.0.0.0" && bind != "::" && !hosts.contains(&bind) {
+            hosts.push(bind);
+        }
+        Self {
+            token: config.token.clone(),
+            hosts,
+        }
+    }
+
+    /// Checks the `Host` and the token, and for requests that change something
+    /// that an `Origin`, when sent, is this server.
+    fn check(
+        &self,
+        request: &Request,
+        params: &[(String, String)],
+        changes: bool,
+    ) -> Result<(), ApiError> {
+        let host = header_value(request, "Host").unwrap_or_default();
+        if !self
+            .hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(host_name(host)))
+        {
+            return Err(ApiError::new(403, format!("unexpected Host: {host}")));
+        }
+        let token = header_value(request, "Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| param(params, "token"));
+        if !token.is_some_and(|token| same_token(token.trim(), &self.token)) {
+            return Err(ApiError::new(401, "missing or wrong token"));
+        }
+        if changes {
+            if let Some(origin) = header_value(request, "Origin") {
+                let origin_host = origin
+                    .strip_prefix("http://")
+                    .or_else(|| origin.strip_prefix("https://"));
+                if !origin_host.is_some_and(|origin_host| origin_host.eq_ignore_ascii_case(host)) {
+                    return Err(ApiError::new(403, format!("foreign Origin: {origin}")));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `host` from `host:port` or `[v6]:port`.
+fn host_name(host: &str) -> &str {
+    if let Some(bracketed) = host.strip_prefix('[') {
+        return bracketed.split(']').next().unwrap_or(bracketed);
+    }
+    match host.split_once(':') {
+        Some((name, port)) if !port.contains(':') => name,
+        _ => host,
+    }
+}
+
+/// Compares in time independent of where the tokens differ.
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+struct Api {
+    queries: Sender<StateQuery>,
+    inbox: RemoteInbox,
+    /// Opened on the server thread; search answers 503 without it.
+    db: Option<Database>,
+}
+
+impl Api {
+    fn query<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&PlaybackState) -> T + Send + 'static,
+    ) -> Result<T, ApiError> {
+        remote::query_state(&self.queries, f).ok_or_else(ApiError::unavailable)
+    }
+
+    fn perform(&self, action: PlaybackAction) -> ApiResult {
+        match self
+            .inbox
+            .submit(KeyAction::Playback(action))
+            .recv_timeout(HANDLE_TIMEOUT)
+        {
+            Ok(Ok(message)) => Ok(json!({ "message": message })),
+            Ok(Err(message)) => Err(ApiError::new(500, message)),
+            Err(_) => Err(ApiError::unavailable()),
+        }
+    }
+
+    fn handle(
+        &self,
+        method: &Method,
+        path: &str,
+        params: &[(String, String)],
+        client: &str,
+    ) -> ApiResult {
+        match (method, path) {
+            (Method::Get, "/api/now-playing") => self.query(now_playing_json),
+            (Method::Get, "/api/queue") => {
+                let offset = optional_param(params, "offset")?.unwrap_or(0);
+                let limit = optional_param(params, "limit")?
+                    .unwrap_or(DEFAULT_LIMIT)
+                    .min(MAX_LIMIT);
+                self.query(move |state| {
+                    let items: Vec<Value> = state
+                        .queue
+                        .iter()
+                        .enumerate()
+                        .skip(offset)
+                        .take(limit)
+                        .map(|(index, entry)| entry_json(index, entry))
+                        .collect();
+                    json!({ "total": state.queue.len(), "offset": offset, "items": items })
+                })
+            }
+            (Method::Get, "/api/search") => {
+                let query = param(params, "q")
+                    .filter(|q| !q.trim().is_empty())
+                    .ok_or_else(|| ApiError::bad_request("missing query parameter q"))?;
+                let limit = optional_param(params, "limit")?
+                    .unwrap_or(DEFAULT_LIMIT)
+                    .min(MAX_LIMIT);
+                let db = self
+                    .db
+                    .as_ref()
+                    .ok_or_else(|| ApiError::new(503, "library database unavailable"))?;
+                let found = db
+                    .search_tracks(query.trim(), limit)
+                    .map_err(|err| ApiError::new(500, err.to_string()))?;
+                let items: Vec<Value> = found
+                    .iter()
+                    .map(|(index, row)| row_json(*index, row))
+                    .collect();
+                Ok(json!({ "items": items }))
+            }
+            (Method::Post, control) if control.starts_with("/api/control/") => {
+                let action = match &control["/api/control/".len()..] {
+                    "play" => match optional_param(params, "index")? {
+                        Some(track_index) => PlaybackAction::PlayTrack { track_index },
+                        None => PlaybackAction::Resume,
+                    },
+                    "pause" => PlaybackAction::Pause,
+                    "toggle" => PlaybackAction::TogglePause,
+                    "stop" => PlaybackAction::Stop,
+                    "next" => PlaybackAction::Next,
+                    "previous" => PlaybackAction::Previous,
+                    "shuffle" => PlaybackAction::ToggleShuffle,
+                    "random-album" => PlaybackAction::ToggleRandomAlbum,
+                    "radio" => PlaybackAction::ToggleRadio,
+                    "consume" => PlaybackAction::ToggleConsume,
+                    "enqueue" => PlaybackAction::Enqueue {
+                        path: param(params, "path")
+                            .ok_or_else(|| ApiError::bad_request("missing path"))?
+                            .to_string(),
+                        requested_by: client.to_string(),
+                    },
+                    "seek" => PlaybackAction::Seek {
+                        position_ms: optional_param(params, "position_ms")?
+                            .ok_or_else(|| ApiError::bad_request("missing position_ms"))?
+                            as u64,
+                    },
+                    "volume" => {
+                        let percent: usize = optional_param(params, "percent")?
+                            .ok_or_else(|| ApiError::bad_request("missing percent"))?;
+                        PlaybackAction::SetVolume {
+                            volume: percent.min(100) as f32 / 100.0,
+                        }
+                    }
+                    other => return Err(ApiError::new(404, format!("unknown control: {other}"))),
+                };
+                self.perform(action)
+            }
+            (_, path) if path.starts_with("/api/") => Err(ApiError::new(
+                404,
+                format!("no such endpoint: {method} {path}"),
+            )),
+            _ => Err(ApiError::new(404, "not found")),
+        }
+    }
+}
+
+fn entry_json(index: usize, entry: &PlaybackQueueEntry) -> Value {
+    json!({
+        "index": index,
+        "path": entry.path,
+        "title": entry.title,
+        "artist": entry.artist,
+        "album": entry.album,
+        "duration_ms": entry.duration_ms,
+    })
+}
+
+fn row_json(index: usize, row: &TrackRow) -> Value {
+    json!({
+        "index": index,
+        "path": row.path,
+        "title": row.title,
+        "artist": row.artist,
+        "album": row.album,
+        "duration_ms": row.duration_ms,
+    })
+}
+
+fn now_playing_json(state: &PlaybackState) -> Value {
+    let session = &state.session;
+    let track = match (session.current_index, state.current_entry()) {
+        (Some(index), Some(entry)) => entry_json(index, entry),
+        _ => Value::Null,
+    };
+    json!({
+        "status": remote::status_label(session.status),
+        "position_ms": session.position_ms,
+        "volume": (session.volume * 100.0).round() as u32,
+        "shuffle": session.shuffle,
+        "random_album": session.random_album,
+        "radio": session.radio,
+        "consume": session.consume,
+        "repeat": session.repeat,
+        "track": track,
+    })
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn optional_param(params: &[(String, String)], name: &str) -> Result<Option<usize>, ApiError> {
+    param(params, name)
+        .map(|raw| {
+            raw.parse()
+                .map_err(|_| ApiError::bad_request(format!("invalid {name}: {raw}")))
+        })
+        .transpose()
+}
+
+/// Splits `/path?a=1&b=x%20y` into the path and decoded query pairs.
+fn split_url(url: &str) -> (&str, Vec<(String, String)>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    (path, params)
+}
+
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (escaped, bytes[i]) {
+            (Some(byte), _) => {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+            (None, b'+') => out.push(b' '),
+            (None, byte) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("static header is valid")
+}
+
+fn respond_json(request: Request, status: u16, body: &Value) {
+    let response = Response::from_string(body.to_string())
+        .with_status_code(StatusCode(status))
+        .with_header(header("Content-Type", "application/json"));
+    if let Err(err) = request.respond(response) {
+        tracing::debug!("http response failed: {err}");
+    }
+}
+
+fn header_value<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str().trim())
+}
+
+fn websocket_key(request: &Request) -> Option<String> {
+    let upgrade = request
+        .headers()
+        .iter()
+        .any(|h| h.field.equiv("Upgrade") && h.value.as_str().eq_ignore_ascii_case("websocket"));
+    if !upgrade {
+        return None;
+    }
+    header_value(request, "Sec-WebSocket-Key").map(str::to_string)
+}
+
+/// Completes the WebSocket handshake and pushes player events until the
+/// client goes away or the server stops. Messages from the client are ignored.
+/// `clients` counts the streams running, this one included.
+fn stream_events(
+    request: Request,
+    key: &str,
+    queries: Sender<StateQuery>,
+    stop: Arc<AtomicBool>,
+    clients: Arc<AtomicUsize>,
+) {
+    use tungstenite::protocol::Role;
+    use tungstenite::{Message, WebSocket};
+
+    let accept = tungstenite::handshake::derive_accept_key(key.as_bytes());
+    let response = Response::empty(StatusCode(101))
+        .with_header(header("Upgrade", "websocket"))
+        .with_header(header("Connection", "Upgrade"))
+        .with_header(header("Sec-WebSocket-Accept", &accept));
+    let stream = request.upgrade("websocket", response);
+
+    std::thread::spawn(move || {
+        let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+        let mut last_state: Option<Value> = None;
+        let mut last_position = Instant::now();
+        while !stop.load(Ordering::Relaxed) {
+            let Some(mut now_playing) = remote::query_state(&queries, now_playing_json) else {
+                break;
+            };
+            let position = now_playing["position_ms"].take();
+            let message = if last_state.as_ref() != Some(&now_playing) {
+                last_state = Some(now_playing.clone());
+                last_position = Instant::now();
+                now_playing["position_ms"] = position;
+                Some(json!({ "type": "state", "state": now_playing }))
+            } else if now_playing["status"] == "playing"
+                && last_position.elapsed() >= POSITION_INTERVAL
+            {
+                last_position = Instant::now();
+                Some(json!({ "type": "position", "position_ms": position }))
+            } else {
+                None
+            };
+            if let Some(message) = message {
+                if socket.send(Message::Text(message.to_string())).is_err() {
+                    break;
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        let _ = socket.close(None);
+        clients.fetch_sub(1, Ordering::Relaxed);
+    });
+}
+
+/// Serves the HTTP API until dropped.
+pub struct HttpServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HttpServer {
+    pub fn start(
+        config: &HttpConfig,
+        db_options: Option<DatabaseOptions>,
+        queries: Sender<StateQuery>,
+        inbox: RemoteInbox,
+    ) -> Result<Self> {
+        if config.token.trim().is_empty() {
+            bail!("set a token under [http] to enable the HTTP API");
+        }
+        let access = Access::new(config);
+        let server = Server::http((config.bind.as_str(), config.port))
+            .map_err(|err| anyhow!("{err}"))
+            .with_context(|| format!("failed to listen on {}:{}", config.bind, config.port))?;
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| anyhow!("HTTP server is not bound to an IP address"))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                let db = db_options.and_then(|options| {
+                    Database::open(&options)
+                        .map_err(|err| tracing::warn!("HTTP API search disabled: {err}"))
+                        .ok()
+                });
+                let api = Api { queries, inbox, db };
+                let clients = Arc::new(AtomicUsize::new(0));
+                while !stop.load(Ordering::Relaxed) {
+                    let request = match server.recv_timeout(POLL_INTERVAL) {
+                        Ok(Some(request)) => request,
+                        Ok(None) => continue,
+                        Err(err) => {
+                            tracing::warn!("HTTP accept failed: {err}");
+                            continue;
+                        }
+                    };
+                    let (path, params) = split_url(request.url());
+                    let path = path.to_string();
+                    let changes = request.method() != &Method::Get || path == "/api/events";
+                    if let Err(err) = access.check(&request, &params, changes) {
+                        respond_json(request, err.status, &json!({ "error": err.message }));
+                        continue;
+                    }
+                    if request.method() == &Method::Get && path == "/" {
+                        let response = Response::from_string(REMOTE_PAGE)
+                            .with_header(header("Content-Type", "text/html; charset=utf-8"));
+                        let _ = request.respond(response);
+                    } else if request.method() == &Method::Get && path == "/api/events" {
+                        match websocket_key(&request) {
+                            Some(_) if clients.load(Ordering::Relaxed) >= MAX_EVENT_CLIENTS => {
+                                respond_json(
+                                    request,
+                                    503,
+                                    &json!({ "error": "too many event streams" }),
+                                )
+                            }
+                            Some(key) => {
+                                clients.fetch_add(1, Ordering::Relaxed);
+                                stream_events(
+                                    request,
+                                    &key,
+                                    api.queries.clone(),
+                                    Arc::clone(&stop),
+                                    Arc::clone(&clients),
+                                )
+                            }
+                            None => respond_json(
+                                request,
+                                426,
+                                &json!({ "error": "expected a WebSocket upgrade" }),
+                            ),
+                        }
+                    } else {
+                        let client = request
+                            .remote_addr()
+                            .map(|addr| addr.ip().to_string())
+                            .unwrap_or_default();
+                        match api.handle(request.method(), &path, &params, &client) {
+                            Ok(body) => respond_json(request, 200, &body),
+                            Err(err) => {
+                                respond_json(request, err.status, &json!({ "error": err.message }))
+                            }
+                        }
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auric_core::{PlaybackSession, PlaybackStatus, TrackId};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn playing_state() -> PlaybackState {
+        PlaybackState {
+            session: PlaybackSession {
+                status: PlaybackStatus::Playing,
+                current_index: Some(0),
+                position_ms: 4_000,
+                volume: 0.5,
+                ..PlaybackSession::default()
+            },
+            queue: vec![PlaybackQueueEntry {
+                track_id: TrackId(uuid::Uuid::new_v4()),
+                path: "/music/one.flac".to_string(),
+                title: Some("One".to_string()),
+                artist: None,
+                album: None,
+                duration_ms: Some(90_000),
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+            }],
+        }
+    }
+
+    const TOKEN: &str = "s3cret";
+
+    fn config() -> HttpConfig {
+        HttpConfig {
+            enabled: true,
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+            token: TOKEN.to_string(),
+        }
+    }
+
+    fn http(addr: SocketAddr, method: &str, target: &str) -> (u16, Value) {
+        let headers = format!("Host: {addr}\r\nAuthorization: Bearer {TOKEN}\r\n");
+        http_with(addr, method, target, &headers)
+    }
+
+    fn http_with(addr: SocketAddr, method: &str, target: &str, headers: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {target} HTTP/1.1\r\n{headers}Connection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).unwrap();
+        let status = raw[9..12].parse().unwrap();
+        let body = raw.split_once("\r\n\r\n").unwrap().1;
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn query_strings_are_decoded() {
+        let (path, params) = split_url("/api/search?q=caf%C3%A9+del%20mar&limit=5&flag");
+        assert_eq!(path, "/api/search");
+        assert_eq!(
+            params,
+            [
+                ("q".to_string(), "café del mar".to_string()),
+                ("limit".to_string(), "5".to_string()),
+                ("flag".to_string(), String::new()),
+            ]
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn endpoints_read_state_and_control_playback() {
+        let (queries_tx, queries_rx) = remote::query_channel();
+        let inbox = RemoteInbox::new();
+        let server = HttpServer::start(&config(), None, queries_tx, inbox.clone()).unwrap();
+        let addr = server.local_addr();
+
+        // Stands in for the shell: answers queries and performs actions.
+        let done = Arc::new(AtomicBool::new(false));
+        let shell = {
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                let state = playing_state();
+                let mut actions = Vec::new();
+                while !done.load(Ordering::Relaxed) {
+                    remote::answer_queries(&queries_rx, &state);
+                    for request in inbox.drain() {
+                        actions.push(request.action);
+                        request.reply.send(Ok("Playing: two".to_string())).unwrap();
+                    }
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                actions
+            })
+        };
+
+        let (status, body) = http(addr, "GET", "/api/now-playing");
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "playing");
+        assert_eq!(body["volume"], 50);
+        assert_eq!(body["track"]["title"], "One");
+
+        let (status, body) = http(addr, "GET", "/api/queue?offset=1");
+        assert_eq!((status, body["total"].clone()), (200, json!(1)));
+        assert_eq!(body["items"], json!([]));
+
+        let (status, body) = http(addr, "POST", "/api/control/next");
+        assert_eq!(
+            (status, body["message"].clone()),
+            (200, json!("Playing: two"))
+        );
+        let (status, _) = http(addr, "POST", "/api/control/volume?percent=30");
+        assert_eq!(status, 200);
+        let (status, body) = http(addr, "POST", "/api/control/volume?percent=loud");
+        assert_eq!(
+            (status, body["error"].clone()),
+            (400, json!("invalid percent: loud"))
+        );
+        assert_eq!(http(addr, "GET", "/api/search?q=one").0, 503);
+        assert_eq!(http(addr, "GET", "/api/nothing").0, 404);
+
+        let events = || {
+            let stream = TcpStream::connect(addr).unwrap();
+            tungstenite::client(format!("ws://{addr}/api/events?token={TOKEN}"), stream).ok()
+        };
+        let (mut socket, _) = events().unwrap();
+        let first: Value = serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+        assert_eq!(first["type"], "state");
+        assert_eq!(first["state"]["position_ms"], 4_000);
+        let others: Vec<_> = (1..MAX_EVENT_CLIENTS).map(|_| events().unwrap()).collect();
+        assert!(events().is_none());
+        drop(others);
+
+        done.store(true, Ordering::Relaxed);
+        let actions = shell.join().unwrap();
+        assert_eq!(
+            actions,
+            [
+                KeyAction::Playback(PlaybackAction::Next),
+                KeyAction::Playback(PlaybackAction::SetVolume { volume: 0.3 }),
+            ]
+        );
+        drop(socket);
+        drop(server);
+    }
+
+    #[test]
+    fn requests_need_the_token_a_local_host_and_the_same_origin() {
+        let (queries_tx, _queries_rx) = remote::query_channel();
+        let unset = HttpConfig {
+            token: " ".to_string(),
+            ..config()
+        };
+        assert!(HttpServer::start(&unset, None, queries_tx.clone(), RemoteInbox::new()).is_err());
+        let server = HttpServer::start(&config(), None, queries_tx, RemoteInbox::new()).unwrap();
+        let addr = server.local_addr();
+        let host = format!("Host: {addr}\r\n");
+        let auth = format!("Authorization: Bearer {TOKEN}\r\n");
+
+        assert_eq!(http_with(addr, "GET", "/api/nothing", &host).0, 401);
+        let wrong = format!("{host}Authorization: Bearer s3cres\r\n");
+        assert_eq!(http_with(addr, "GET", "/api/nothing", &wrong).0, 401);
+        let by_query = format!("/api/nothing?token={TOKEN}");
+        assert_eq!(http_with(addr, "GET", &by_query, &host).0, 404);
+        let localhost = format!("Host: localhost:{}\r\n{auth}", addr.port());
+        assert_eq!(http_with(addr, "GET", "/api/nothing", &localhost).0, 404);
+
+        let rebound = format!("Host: attacker.example:{}\r\n{auth}", addr.port());
+        assert_eq!(http_with(addr, "GET", "/api/nothing", &rebound).0, 403);
+        assert_eq!(http_with(addr, "GET", "/api/nothing", &auth).0, 403);
+
+        let foreign = format!("{host}{auth}Origin: http://attacker.example\r\n");
+        let (status, body) = http_with(addr, "POST", "/api/control/bogus", &foreign);
+        assert_eq!(
+            (status, body["error"].clone()),
+            (403, json!("foreign Origin: http://attacker.example"))
+        );
+        let same = format!("{host}{auth}Origin: http://{addr}\r\n");
+        assert_eq!(http_with(addr, "POST", "/api/control/bogus", &same).0, 404);
+    }
+}
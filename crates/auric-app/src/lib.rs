@@ -1,25 +1,64 @@
+pub mod analysis;
+pub mod daemon;
+pub mod engine;
+pub mod health;
+pub mod hooks;
+pub mod http_api;
+pub mod logging;
+pub mod mpd;
+#[cfg(target_os = "linux")]
+pub mod mpris;
+pub mod party;
+pub mod podcast;
+pub mod profile;
+pub mod radio;
+pub mod remote;
+pub mod shuffle;
+pub mod subsonic;
+pub mod suspend;
 pub mod update;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 use anyhow::{bail, Context, Result};
+use auric_audio::player::PlayerEvent;
 use auric_audio::AudioEngine;
 use auric_core::{
     AppCommand, AppEvent, FeatureId, FeatureRegistry, FeatureState, PlaybackQueueEntry,
     PlaybackState, PlaybackStatus, RepeatMode, TrackId,
 };
-use auric_library::db::{Database, DatabaseOptions, JournalMode, PragmaSnapshot, SynchronousMode};
+use auric_library::cue;
+use auric_library::db::{
+    Database, DatabaseOptions, HealthIssue, JournalMode, LibraryRootRow, PlayGroup, PlayTally,
+    PlaylistSummaryRow, PodcastEpisodeRow, PragmaSnapshot, SmartPlaylist, StatsPeriod,
+    SynchronousMode, TrackRow,
+};
+use auric_library::export::{self, ExportFormat, PlaylistExport};
+use auric_library::import;
+use auric_library::review::{self, Review, ReviewFormat, ReviewRange};
 use auric_library::scan::{DirectoryScanner, ScanOptions, ScanSummary};
 use auric_library::watch::{WatchOptions, WatchSessionSummary, WatchedFolderService, WatchedRoot};
-use auric_library::{LibraryRoot, TrackRecord};
+use auric_library::writer::DbWriter;
+use auric_library::{LibraryRoot, RootOptions, TrackRecord};
+use auric_ui::file_browser::BrowserPlaces;
+use auric_ui::scheduler::FrameScheduler;
 use auric_ui::ThemeStore;
 use auric_ui::{
-    render_once_to_text, run_interactive_full, FsThemeStore, IconMode, Palette,
-    PaletteCommandResult, PlaybackAction, PlayerEventUpdate, RunOptions, ScanProgress,
-    ShellListItem, ShellSnapshot, ShellState, ShellTrackItem,
+    render_once_to_text, run_interactive_full, ColorMode, FsThemeStore, IconMode, Palette,
+    PaletteCommandResult, PanelLayout, PlaybackAction, PlayerEventUpdate, RunOptions, ScanProgress,
+    ShellEpisode, ShellHealthIssue, ShellHealthReport, ShellHealthTrack, ShellListItem,
+    ShellListeningStats, ShellPodcast, ShellReview, ShellRootOptions, ShellSnapshot, ShellState,
+    ShellTrackItem, SortColumn,
+};
+use clap::Parser;
+use engine::{
+    adjust_playback_selection_after_queue_removal, dispatch_app_command, load_playback_state,
+    open_path_and_play, persist_playback_state, playback_queue_entry_from_track_row,
+    spawn_background_scan, BackgroundJob,
 };
 use serde::Deserialize;
 use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -31,8 +70,18 @@ use uuid::Uuid;
 pub struct AppConfig {
     pub features: FeaturesConfig,
     pub library: LibraryConfig,
+    pub playback: PlaybackConfig,
     pub ui: UiConfig,
     pub database: DatabaseConfig,
+    pub logging: LoggingConfig,
+    pub mpd: MpdConfig,
+    pub http: HttpConfig,
+    pub hooks: HooksConfig,
+    pub network: NetworkConfig,
+    pub subsonic: SubsonicConfig,
+    pub radio: RadioConfig,
+    pub party: PartyConfig,
+    pub profile: ProfileConfig,
 }
 
 impl AppConfig {
@@ -106,6 +155,8 @@ pub struct LibraryConfig {
     pub write_tags: bool,
     pub scan_batch_size: usize,
     pub prune_missing_on_scan: bool,
+    /// List each library folder as a read-only playlist in the sidebar.
+    pub folder_playlists: bool,
 }
 
 impl Default for LibraryConfig {
@@ -117,10 +168,212 @@ impl Default for LibraryConfig {
             write_tags: true,
             scan_batch_size: 2_000,
             prune_missing_on_scan: false,
+            folder_playlists: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PlaybackConfig {
+    /// Percentage points added or removed by the volume keys.
+    pub volume_step: u16,
+    /// Tracks at least this many minutes long remember where they stopped;
+    /// 0 turns the automatic rule off.
+    pub resume_min_minutes: u16,
+    /// Folders whose tracks always remember where they stopped, such as an
+    /// audiobook or DJ mix collection.
+    pub resume_folders: Vec<String>,
+    /// How shuffle picks the next track: random | weighted.
+    pub shuffle_strategy: String,
+    /// How strongly weighted shuffle favors higher-rated tracks, 0-10.
+    pub shuffle_rating_weight: u16,
+    /// How strongly weighted shuffle favors tracks not played lately, 0-10.
+    pub shuffle_recency_weight: u16,
+    /// Evens out loudness from ReplayGain tags or `analyze loudness`:
+    /// off | track | album.
+    pub normalization: String,
+    /// The level normalization aims for, in LUFS.
+    pub normalization_target_lufs: i16,
+    /// Skips the silence `analyze silence` found at either end of a track.
+    pub trim_silence: bool,
+    /// Audio quieter than this, in dBFS, counts as silence.
+    pub silence_threshold_db: i16,
+    /// Silence shorter than this many milliseconds is played as it is.
+    pub silence_min_ms: u16,
+    /// Headphone crossfeed: blends some of each channel into the other ear.
+    pub crossfeed: bool,
+    /// The highest the volume goes, in percent, however it is raised.
+    pub max_volume: u16,
+    /// Eases loud peaks under full scale instead of clipping them.
+    pub limiter: bool,
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            volume_step: 5,
+            resume_min_minutes: 20,
+            resume_folders: Vec::new(),
+            shuffle_strategy: "random".to_string(),
+            shuffle_rating_weight: 5,
+            shuffle_recency_weight: 5,
+            normalization: "track".to_string(),
+            normalization_target_lufs: -18,
+            trim_silence: true,
+            silence_threshold_db: -60,
+            silence_min_ms: 1_000,
+            crossfeed: false,
+            max_volume: 100,
+            limiter: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// off | error | warn | info | debug | trace; `--log-level` overrides it.
+    pub level: String,
+    /// Log directory, relative to the config file's directory.
+    pub directory: String,
+    /// Number of daily log files kept before the oldest is deleted.
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "warn".to_string(),
+            directory: "logs".to_string(),
+            max_files: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MpdConfig {
+    /// Serve a subset of the MPD protocol while the shell is running.
+    pub enabled: bool,
+    pub bind: String,
+    pub port: u16,
+}
+
+impl Default for MpdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1".to_string(),
+            port: 6600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Serve the HTTP/WebSocket remote API while the shell is running.
+    pub enabled: bool,
+    pub bind: String,
+    pub port: u16,
+    /// Required on every request, as `Authorization: Bearer <token>` or
+    /// `?token=`. The server does not start without one.
+    pub token: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1".to_string(),
+            port: 8765,
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Never make outgoing connections: no update check and no features
+    /// that fetch metadata or talk to peers. Same as `--offline` on every run.
+    pub offline: bool,
+}
+
+/// A Subsonic-compatible server (Navidrome, Airsonic, Gonic, ...) whose
+/// library is synced in next to the local folders; empty `url` means none.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SubsonicConfig {
+    pub url: String,
+    pub username: String,
+    /// `AURIC_SUBSONIC_PASSWORD` takes precedence, to keep it out of the file.
+    pub password: String,
+    /// Added to the names of the server's playlists, e.g. "Road Trip (Navidrome)".
+    pub name: String,
+}
+
+impl Default for SubsonicConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            name: "Subsonic".to_string(),
+        }
+    }
+}
+
+/// Radio mode, which keeps the queue going with similar artists.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RadioConfig {
+    /// Enables Last.fm similar artists; without it the library's own
+    /// albums and playlists decide. `AURIC_LASTFM_API_KEY` takes precedence.
+    pub lastfm_api_key: String,
+    /// Tracks appended each time the queue runs out.
+    pub batch: usize,
+}
+
+impl Default for RadioConfig {
+    fn default() -> Self {
+        Self {
+            lastfm_api_key: String::new(),
+            batch: 10,
         }
     }
 }
 
+/// Party mode; see [`party`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PartyConfig {
+    /// Ends party mode and allows quitting; `party on <passphrase>` can set
+    /// one for the session instead.
+    pub passphrase: String,
+    /// Seconds each guest waits between queue requests; 0 means no limit.
+    pub request_cooldown_secs: u64,
+}
+
+/// Shell commands run on player events; empty means no hook.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub track_start: String,
+    pub track_end: String,
+    pub pause: String,
+    pub resume: String,
+    pub scan_complete: String,
+}
+
+/// Set in the configs `auric profile create` writes; empty for the base config.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub name: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct UiConfig {
@@ -134,6 +387,19 @@ pub struct UiConfig {
     pub icon_fallback: String,
     pub preferred_terminal_font: String,
     pub use_theme_background: bool,
+    pub color_mode: String,
+    /// `auto`, `full`, `basic` (no braille) or `ascii`; see [`auric_ui::glyphs::UnicodeLevel`].
+    pub unicode_level: String,
+    /// `off`, `on` or `announce`; see [`auric_ui::shell::ScreenReaderMode`].
+    pub screen_reader: String,
+    pub show_artwork: bool,
+    /// Redraws per second while the spectrum plays; see
+    /// [`auric_ui::scheduler`].
+    pub refresh_hz: u16,
+    /// Footer line templates; see [`auric_ui::footer`].
+    pub footer_top: String,
+    pub footer_bottom: String,
+    pub layout: PanelLayout,
 }
 
 impl Default for UiConfig {
@@ -149,6 +415,14 @@ impl Default for UiConfig {
             icon_fallback: "ascii".to_string(),
             preferred_terminal_font: "FiraCode Nerd Font Mono".to_string(),
             use_theme_background: false,
+            color_mode: "auto".to_string(),
+            unicode_level: "auto".to_string(),
+            screen_reader: "off".to_string(),
+            show_artwork: true,
+            refresh_hz: auric_ui::scheduler::DEFAULT_ACTIVE_FPS,
+            footer_top: auric_ui::footer::DEFAULT_TOP.to_string(),
+            footer_bottom: auric_ui::footer::DEFAULT_BOTTOM.to_string(),
+            layout: PanelLayout::default(),
         }
     }
 }
@@ -156,6 +430,7 @@ impl Default for UiConfig {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct DatabaseConfig {
+    /// Empty means `auric.db` in [`default_data_dir`].
     pub path: String,
     pub journal_mode: String,
     pub synchronous: String,
@@ -168,7 +443,7 @@ pub struct DatabaseConfig {
 impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
-            path: "var/auric.db".to_string(),
+            path: String::new(),
             journal_mode: "wal".to_string(),
             synchronous: "normal".to_string(),
             busy_timeout_ms: 5_000,
@@ -182,8 +457,10 @@ impl Default for DatabaseConfig {
 impl DatabaseConfig {
     pub fn to_options(&self, cwd: &Path) -> Result<DatabaseOptions> {
         let db_path = {
-            let p = PathBuf::from(&self.path);
-            if p.is_absolute() {
+            let p = PathBuf::from(self.path.trim());
+            if p.as_os_str().is_empty() {
+                default_data_dir().join(DEFAULT_DB_FILE)
+            } else if p.is_absolute() {
                 p
             } else {
                 cwd.join(p)
@@ -202,9 +479,79 @@ impl DatabaseConfig {
     }
 }
 
+const DEFAULT_DB_FILE: &str = "auric.db";
+
+/// Where the library was kept before it moved to [`default_data_dir`],
+/// relative to the working directory.
+const LEGACY_DB_PATH: &str = "var/auric.db";
+
+/// Data that is not configuration: `$XDG_DATA_HOME/auric-tui`, else
+/// `~/.local/share/auric-tui`.
+pub fn default_data_dir() -> PathBuf {
+    let from_env = |name: &str| {
+        env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+    };
+    if let Some(dir) = from_env("XDG_DATA_HOME") {
+        return dir.join("auric-tui");
+    }
+    if let Some(home) = from_env("HOME") {
+        return home.join(".local/share/auric-tui");
+    }
+    match from_env("APPDATA") {
+        Some(dir) => dir.join("auric-tui"),
+        None => PathBuf::from("var"),
+    }
+}
+
+/// Moves a database from its old location to `target` the first time the
+/// default location is used, WAL contents included. Does nothing when
+/// `target` already exists, so a library is never overwritten. Returns
+/// whether anything moved.
+fn migrate_legacy_database(legacy: &Path, target: &Path) -> Result<bool> {
+    if target.exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    // The copy goes to a file next to the target and is renamed into place,
+    // so a failure partway leaves no target to be taken for the library.
+    let staging = PathBuf::from(format!("{}.migrating", target.display()));
+    let _ = fs::remove_file(&staging);
+    if let Err(err) = auric_library::db::copy_database(legacy, &staging) {
+        let _ = fs::remove_file(&staging);
+        return Err(err).with_context(|| {
+            format!(
+                "failed to copy {} to {}",
+                legacy.display(),
+                target.display()
+            )
+        });
+    }
+    fs::rename(&staging, target).with_context(|| {
+        format!(
+            "failed to move {} to {}",
+            staging.display(),
+            target.display()
+        )
+    })?;
+    for suffix in ["", "-wal", "-shm"] {
+        let old = PathBuf::from(format!("{}{suffix}", legacy.display()));
+        if old.exists() {
+            fs::remove_file(&old).with_context(|| format!("failed to remove {}", old.display()))?;
+        }
+    }
+    tracing::info!(from = %legacy.display(), to = %target.display(), "moved library database");
+    Ok(true)
+}
+
 #[derive(Debug)]
 pub struct BootstrapReport {
     pub config_path: PathBuf,
+    pub profile: String,
     pub db_path: Option<PathBuf>,
     pub schema_version: i64,
     pub pragmas: PragmaSnapshot,
@@ -223,7 +570,39 @@ pub struct BootstrappedApp {
     pub playback_state: PlaybackState,
     pub report: BootstrapReport,
     pub player: auric_audio::player::PlayerHandle,
+    /// Takes settings, session and position saves and play counts off the
+    /// render loop; see [`engine::flush_writes`].
+    writer: Option<DbWriter>,
     artwork_cache: std::cell::RefCell<(String, Option<Vec<u8>>)>,
+    /// The playing track's chapters, so its CUE sheet is read once.
+    chapter_cache: std::cell::RefCell<(String, Vec<(u64, String)>)>,
+    /// Playback session changes not yet written to the database.
+    session_dirty: bool,
+    last_autosave: Instant,
+    /// When the playing track's resume position was last saved.
+    last_position_save: Instant,
+    /// Set by `[network] offline` or `--offline`; see [`go_offline`].
+    offline: bool,
+    /// On while guests are in charge; see [`party`].
+    party: Option<party::PartyMode>,
+    /// Sleep and wake of the system; see [`suspend`].
+    suspend: suspend::SuspendWatch,
+    /// Radio mode's Last.fm lookup for the queue's last artist; see [`radio::poll`].
+    radio_lookup: Option<radio::Lookup>,
+    /// Jobs waiting for the front end to start them; see [`engine::next_job`].
+    background_jobs: std::collections::VecDeque<(BackgroundJob, String)>,
+    /// What the palette's `undo` puts back.
+    undo: Option<Undo>,
+    /// The playlist the split view shows beside the track list; `None`
+    /// shows the queue.
+    split_playlist: Option<String>,
+}
+
+/// A destructive palette command that `undo` can reverse. One level is kept;
+/// the next destructive command replaces it.
+#[derive(Debug, Clone)]
+enum Undo {
+    DeletedPlaylist(auric_library::db::DeletedPlaylist),
 }
 
 impl std::fmt::Debug for BootstrappedApp {
@@ -239,15 +618,30 @@ impl std::fmt::Debug for BootstrappedApp {
 
 pub fn bootstrap_from_config_path(config_path: &Path) -> Result<BootstrappedApp> {
     let config = AppConfig::load_from_path(config_path)?;
+    bootstrap_from_config(config_path, config)
+}
+
+fn bootstrap_from_config(config_path: &Path, mut config: AppConfig) -> Result<BootstrappedApp> {
     let cwd = env::current_dir().context("failed to resolve current working directory")?;
     let options = config.database.to_options(&cwd)?;
+    if config.database.path.trim().is_empty()
+        && migrate_legacy_database(&cwd.join(LEGACY_DB_PATH), &options.path)?
+    {
+        eprintln!("moved library database to {}", options.path.display());
+    }
 
     let db = Database::open(&options)?;
     seed_initial_settings(&db, &config)?;
+    config.ui.layout = load_panel_layout(&db, config.ui.layout)?;
+    apply_saved_user_settings(&db, &mut config)?;
     let feature_registry = load_feature_registry(&db, &config.features)?;
     let playback_state = load_playback_state(&db)?;
     db.quick_check().context("sqlite quick_check failed")?;
     db.optimize().context("sqlite optimize failed")?;
+    let writer = match db.path() {
+        Some(_) => Some(DbWriter::spawn(&options).context("failed to start database writer")?),
+        None => None,
+    };
 
     let feature_enabled_count = FeatureId::ALL
         .into_iter()
@@ -256,6 +650,7 @@ pub fn bootstrap_from_config_path(config_path: &Path) -> Result<BootstrappedApp>
 
     let report = BootstrapReport {
         config_path: config_path.to_path_buf(),
+        profile: config.profile.name.clone(),
         db_path: db.path().map(|p| p.to_path_buf()),
         schema_version: db.schema_version()?,
         pragmas: db.pragma_snapshot()?,
@@ -269,15 +664,139 @@ pub fn bootstrap_from_config_path(config_path: &Path) -> Result<BootstrappedApp>
 
     let player = auric_audio::player::PlayerHandle::spawn();
 
-    Ok(BootstrappedApp {
+    let mut app = BootstrappedApp {
         config,
         db,
         feature_registry,
         playback_state,
         report,
         player,
+        writer,
         artwork_cache: std::cell::RefCell::new((String::new(), None)),
-    })
+        chapter_cache: std::cell::RefCell::new((String::new(), Vec::new())),
+        session_dirty: false,
+        last_autosave: Instant::now(),
+        last_position_save: Instant::now(),
+        offline: false,
+        party: None,
+        suspend: suspend::SuspendWatch::default(),
+        radio_lookup: None,
+        background_jobs: std::collections::VecDeque::new(),
+        undo: None,
+        split_playlist: None,
+    };
+    engine::apply_output_settings(&mut app);
+    if app.config.network.offline {
+        go_offline(&mut app);
+    }
+    Ok(app)
+}
+
+/// The command line: startup options, then a command and its arguments or a
+/// file or folder to play. Each command parses its own arguments.
+#[derive(Debug, Parser)]
+#[command(name = "auric", version = VERSION, about = "A terminal music player")]
+struct Cli {
+    /// Log level for this run: off, error, warn, info, debug or trace
+    #[arg(long, value_name = "LEVEL")]
+    log_level: Option<String>,
+    #[command(flatten)]
+    startup: StartupOptions,
+    /// A file or folder to play, or a command and its arguments [default: ui]
+    #[arg(
+        value_name = "COMMAND | PATH",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    args: Vec<String>,
+}
+
+/// Options that adjust a single run. They override the config file and saved
+/// settings but are never written back.
+#[derive(Debug, Clone, Default, PartialEq, Eq, clap::Args)]
+pub struct StartupOptions {
+    /// Read default.toml from this directory
+    #[arg(long, value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
+    /// Use a library profile's config and database
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Use this database file instead of `[database] path`
+    #[arg(long = "db", value_name = "PATH")]
+    pub db_path: Option<String>,
+    /// Use this theme instead of `[ui] theme`
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+    /// Hide album art
+    #[arg(long)]
+    pub no_art: bool,
+    /// Make no outgoing connections: no update check or remote metadata
+    #[arg(long)]
+    pub offline: bool,
+    /// Start a shell even if one is already running, instead of handing the
+    /// command to it
+    #[arg(long)]
+    pub new_instance: bool,
+    /// Run without the terminal UI, controlled only through the control
+    /// socket, MPD and HTTP servers
+    #[arg(long)]
+    pub daemon: bool,
+}
+
+impl StartupOptions {
+    fn base_config_path(&self) -> PathBuf {
+        match &self.config_dir {
+            Some(dir) => dir.join("default.toml"),
+            None => resolve_config_path(),
+        }
+    }
+
+    fn config_path(&self) -> Result<PathBuf> {
+        let base = self.base_config_path();
+        match &self.profile {
+            Some(name) => Ok(profile::find(&base, name)?.config_path()),
+            None => Ok(base),
+        }
+    }
+}
+
+/// Like [`bootstrap_from_config_path`], with command-line overrides applied.
+pub fn bootstrap_with_options(
+    config_path: &Path,
+    startup: &StartupOptions,
+) -> Result<BootstrappedApp> {
+    let mut config = AppConfig::load_from_path(config_path)?;
+    if let Some(db_path) = &startup.db_path {
+        config.database.path = db_path.clone();
+    }
+    let mut app = bootstrap_from_config(config_path, config)?;
+    if let Some(theme) = &startup.theme {
+        app.config.ui.theme = theme.clone();
+        app.report.ui_theme = theme.clone();
+    }
+    if startup.no_art {
+        app.config.ui.show_artwork = false;
+    }
+    if startup.offline {
+        go_offline(&mut app);
+    }
+    Ok(app)
+}
+
+/// Turns off everything that makes outgoing connections for this session:
+/// the update check and network features, which also cannot be enabled until
+/// restart. Saved feature settings are left alone.
+fn go_offline(app: &mut BootstrappedApp) {
+    app.offline = true;
+    for feature in FeatureId::ALL {
+        if feature.uses_network() {
+            app.feature_registry.set_enabled(feature, false);
+        }
+    }
+    app.report.feature_enabled_count = FeatureId::ALL
+        .into_iter()
+        .filter(|feature| app.feature_registry.is_enabled(*feature))
+        .count();
 }
 
 fn seed_initial_settings(db: &Database, config: &AppConfig) -> Result<()> {
@@ -350,90 +869,251 @@ fn feature_setting_key(feature: FeatureId) -> String {
 }
 
 const PLAYBACK_STATE_SETTING_KEY: &str = "playback.state";
-
-fn load_playback_state(db: &Database) -> Result<PlaybackState> {
-    let raw = db.get_setting_json(PLAYBACK_STATE_SETTING_KEY)?;
-    let mut state = match raw {
-        Some(value) => serde_json::from_value::<PlaybackState>(value).unwrap_or_else(|err| {
-            eprintln!("warning: failed to deserialize playback state, resetting: {err}");
-            PlaybackState::default()
-        }),
-        None => PlaybackState::default(),
+const PANEL_LAYOUT_SETTING_KEY: &str = "ui.layout";
+/// Written directly rather than through the background writer: both the
+/// shell and `__add_root` read, change and write it back.
+const BROWSER_PLACES_SETTING_KEY: &str = "ui.browser_places";
+
+const USER_SETTINGS_KEY: &str = "settings.user";
+
+/// Applies one value chosen in the Settings dialog to the running config and
+/// returns the status line to show.
+fn apply_user_setting(config: &mut AppConfig, key: &str, value: &str) -> Result<String> {
+    let parse_bool = |raw: &str| {
+        raw.parse::<bool>()
+            .with_context(|| format!("{key} expects true or false, got {raw}"))
     };
-    normalize_playback_state(&mut state);
-    save_playback_state(db, &state)?;
-    Ok(state)
-}
-
-fn save_playback_state(db: &Database, state: &PlaybackState) -> Result<()> {
-    db.set_setting_json(PLAYBACK_STATE_SETTING_KEY, &serde_json::to_value(state)?)?;
-    Ok(())
-}
-
-fn normalize_playback_state(state: &mut PlaybackState) {
-    if state.queue.is_empty() {
-        state.session.current_index = None;
-        state.session.position_ms = 0;
-        if !matches!(state.session.status, PlaybackStatus::Stopped) {
-            state.session.status = PlaybackStatus::Stopped;
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let message = match key {
+        "theme" => {
+            config.ui.theme = value.to_string();
+            format!("Theme: {value}")
         }
-    } else if let Some(idx) = state.session.current_index {
-        if idx >= state.queue.len() {
-            state.session.current_index = Some(state.queue.len().saturating_sub(1));
-            state.session.position_ms = 0;
+        "color_scheme" => {
+            config.ui.color_scheme = value.to_string();
+            format!("Color scheme: {value}")
         }
-    }
-
-    if !state.session.volume.is_finite() {
-        state.session.volume = 1.0;
-    }
-    state.session.volume = state.session.volume.clamp(0.0, 1.0);
-}
-
-fn persist_playback_state(app: &mut BootstrappedApp) -> Result<()> {
-    normalize_playback_state(&mut app.playback_state);
-    save_playback_state(&app.db, &app.playback_state)
+        "color_mode" => {
+            config.ui.color_mode = value.to_string();
+            format!("Color mode: {value}")
+        }
+        "unicode_level" => {
+            config.ui.unicode_level = value.to_string();
+            format!("Glyphs: {value}")
+        }
+        "screen_reader" => {
+            config.ui.screen_reader = value.to_string();
+            format!("Screen reader mode: {value}")
+        }
+        "use_theme_background" => {
+            config.ui.use_theme_background = parse_bool(value)?;
+            format!(
+                "Theme background: {}",
+                on_off(config.ui.use_theme_background)
+            )
+        }
+        "icon_pack" => {
+            config.ui.icon_pack = value.to_string();
+            format!("Icons: {value}")
+        }
+        "preferred_terminal_font" => {
+            config.ui.preferred_terminal_font = value.to_string();
+            format!("Terminal font: {value}")
+        }
+        "pixel_art_artwork" => {
+            config.ui.pixel_art_artwork = parse_bool(value)?;
+            format!("Pixel art: {}", on_off(config.ui.pixel_art_artwork))
+        }
+        "pixel_art_cell_size" => {
+            config.ui.pixel_art_cell_size = parse_u16_arg(value, key)?.min(4);
+            format!("Pixel art cell: {}", config.ui.pixel_art_cell_size)
+        }
+        "volume_step" => {
+            config.playback.volume_step = parse_u16_arg(value, key)?.min(25);
+            format!("Volume step: {}%", config.playback.volume_step)
+        }
+        "resume_min_minutes" => {
+            config.playback.resume_min_minutes = parse_u16_arg(value, key)?.min(600);
+            match config.playback.resume_min_minutes {
+                0 => "Remember position: only tracks and folders set to".to_string(),
+                minutes => format!("Remember position: tracks of {minutes} min or longer"),
+            }
+        }
+        "shuffle_strategy" => {
+            if shuffle::ShuffleStrategy::parse(value).is_none() {
+                bail!(
+                    "{key} expects one of {}, got {value}",
+                    shuffle::ShuffleStrategy::NAMES.join(", ")
+                );
+            }
+            config.playback.shuffle_strategy = value.to_string();
+            format!("Shuffle: {value}")
+        }
+        "shuffle_rating_weight" => {
+            config.playback.shuffle_rating_weight = parse_u16_arg(value, key)?.min(10);
+            format!(
+                "Shuffle rating weight: {}",
+                config.playback.shuffle_rating_weight
+            )
+        }
+        "shuffle_recency_weight" => {
+            config.playback.shuffle_recency_weight = parse_u16_arg(value, key)?.min(10);
+            format!(
+                "Shuffle recency weight: {}",
+                config.playback.shuffle_recency_weight
+            )
+        }
+        "normalization" => {
+            if !analysis::NORMALIZATION_MODES.contains(&value) {
+                bail!(
+                    "{key} expects one of {}, got {value}",
+                    analysis::NORMALIZATION_MODES.join(", ")
+                );
+            }
+            config.playback.normalization = value.to_string();
+            format!("Loudness normalization: {value}")
+        }
+        "normalization_target_lufs" => {
+            let target = value
+                .parse::<i16>()
+                .with_context(|| format!("invalid value for {key}: {value}"))?;
+            config.playback.normalization_target_lufs = target.clamp(-30, -5);
+            format!(
+                "Normalization target: {} LUFS",
+                config.playback.normalization_target_lufs
+            )
+        }
+        "trim_silence" => {
+            config.playback.trim_silence = parse_bool(value)?;
+            format!("Trim silence: {}", on_off(config.playback.trim_silence))
+        }
+        "silence_threshold_db" => {
+            let threshold = value
+                .parse::<i16>()
+                .with_context(|| format!("invalid value for {key}: {value}"))?;
+            config.playback.silence_threshold_db = threshold.clamp(-90, -20);
+            format!(
+                "Silence threshold: {} dB",
+                config.playback.silence_threshold_db
+            )
+        }
+        "silence_min_ms" => {
+            config.playback.silence_min_ms = parse_u16_arg(value, key)?.min(10_000);
+            format!(
+                "Shortest trimmed silence: {} ms",
+                config.playback.silence_min_ms
+            )
+        }
+        "crossfeed" => {
+            config.playback.crossfeed = parse_bool(value)?;
+            format!("Headphone crossfeed: {}", on_off(config.playback.crossfeed))
+        }
+        "max_volume" => {
+            config.playback.max_volume = parse_u16_arg(value, key)?.clamp(10, 100);
+            format!("Max volume: {}%", config.playback.max_volume)
+        }
+        "limiter" => {
+            config.playback.limiter = parse_bool(value)?;
+            format!("Limiter: {}", on_off(config.playback.limiter))
+        }
+        "folder_playlists" => {
+            config.library.folder_playlists = parse_bool(value)?;
+            format!(
+                "Folder playlists: {}",
+                on_off(config.library.folder_playlists)
+            )
+        }
+        _ => bail!("unknown setting: {key}"),
+    };
+    Ok(message)
 }
 
-fn playback_queue_entry_from_track_row(row: auric_library::db::TrackRow) -> PlaybackQueueEntry {
-    PlaybackQueueEntry {
-        track_id: row.id,
-        path: row.path,
-        title: row.title,
-        artist: row.artist,
-        album: row.album,
-        duration_ms: row.duration_ms,
-        sample_rate: row.sample_rate,
-        channels: row.channels,
-        bit_depth: row.bit_depth,
+/// Re-applies values changed in the Settings dialog on earlier runs, which
+/// take precedence over the config file.
+fn apply_saved_user_settings(db: &Database, config: &mut AppConfig) -> Result<()> {
+    if let Some(JsonValue::Object(saved)) = db.get_setting_json(USER_SETTINGS_KEY)? {
+        for (key, value) in saved {
+            if let Some(value) = value.as_str() {
+                if let Err(err) = apply_user_setting(config, &key, value) {
+                    tracing::warn!("ignoring saved setting {key}: {err}");
+                    eprintln!("warning: ignoring saved setting {key}: {err}");
+                }
+            }
+        }
     }
+    Ok(())
 }
 
-fn emit_playback_state_changed(events: &mut Vec<AppEvent>, state: &PlaybackState) {
-    events.push(AppEvent::PlaybackStateChanged {
-        status: state.session.status,
-        current_index: state.session.current_index,
-        queue_len: state.queue.len(),
-    });
+fn save_user_setting(db: &Database, key: &str, value: &str) -> Result<()> {
+    let mut saved = match db.get_setting_json(USER_SETTINGS_KEY)? {
+        Some(JsonValue::Object(saved)) => saved,
+        _ => serde_json::Map::new(),
+    };
+    saved.insert(key.to_string(), json!(value));
+    db.set_setting_json(USER_SETTINGS_KEY, &JsonValue::Object(saved))?;
+    Ok(())
 }
 
-fn current_track_id(state: &PlaybackState) -> Option<TrackId> {
-    state.current_entry().map(|entry| entry.track_id)
+/// Returns the layout last saved from the shell, falling back to the config.
+fn load_panel_layout(db: &Database, defaults: PanelLayout) -> Result<PanelLayout> {
+    let layout = match db.get_setting_json(PANEL_LAYOUT_SETTING_KEY)? {
+        Some(value) => serde_json::from_value::<PanelLayout>(value).unwrap_or(defaults),
+        None => defaults,
+    };
+    Ok(layout.clamped())
 }
 
 pub fn run_cli() -> Result<()> {
-    let mut args = env::args().skip(1);
+    let Cli {
+        log_level,
+        startup,
+        args,
+    } = Cli::parse();
+    let mut args = args.into_iter();
     let command = args.next().unwrap_or_else(|| "ui".to_string());
-    let config_path = resolve_config_path();
+    let mut config_path = startup.config_path()?;
+    if startup.profile.is_none()
+        && command == "ui"
+        && matches!(
+            args.as_slice().first().map(String::as_str),
+            None | Some("preview")
+        )
+    {
+        if let Some(picked) = pick_profile_at_startup(&config_path)? {
+            config_path = picked.config_path();
+        }
+    }
+    let bootstrap = || bootstrap_with_options(&config_path, &startup);
+    if command != "version" {
+        init_logging(&config_path, log_level.as_deref())?;
+    }
+
+    if startup.daemon {
+        if !startup.new_instance && remote::instance_running() {
+            return report_running_instance();
+        }
+        let mut app = bootstrap()?;
+        match command.as_str() {
+            "ui" => {}
+            other if Path::new(other).exists() => {
+                open_path_and_play(&mut app, Path::new(other))?;
+            }
+            other => bail!("--daemon takes an optional file or folder to play, not {other}"),
+        }
+        return daemon::run(&mut app);
+    }
 
     match command.as_str() {
         "init" => {
-            let app = bootstrap_from_config_path(&config_path)?;
+            let app = bootstrap()?;
             print_bootstrap_report(&app.report);
         }
         "doctor" => {
-            let app = bootstrap_from_config_path(&config_path)?;
+            let app = bootstrap()?;
             print_bootstrap_report(&app.report);
+            for migration in app.db.applied_migrations()? {
+                println!("  migration: v{} {}", migration.version, migration.name);
+            }
             println!("doctor: quick_check=ok optimize=ok");
         }
         "db-stress" => {
@@ -443,63 +1123,153 @@ pub fn run_cli() -> Result<()> {
                     .with_context(|| format!("invalid track count: {raw}"))?,
                 None => 20_000,
             };
-            let mut app = bootstrap_from_config_path(&config_path)?;
+            let mut app = bootstrap()?;
             run_db_stress(&mut app.db, count)?;
         }
         "feature" => {
-            let mut app = bootstrap_from_config_path(&config_path)?;
+            let mut app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_feature_command(&mut app, &subargs)?;
         }
         "root" => {
-            let app = bootstrap_from_config_path(&config_path)?;
+            let app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_root_command(&app, &subargs)?;
         }
+        "profile" => {
+            let subargs: Vec<String> = args.collect();
+            handle_profile_command(&startup.base_config_path(), &subargs)?;
+        }
+        "maintenance" => {
+            let mut app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            let vacuum = match subargs.first().map(String::as_str) {
+                None => true,
+                Some("--no-vacuum") => false,
+                Some(other) => bail!("usage: auric maintenance [--no-vacuum] (got {other})"),
+            };
+            let summary = engine::run_maintenance(&mut app, vacuum)?;
+            for problem in &summary.problems {
+                println!("integrity: {problem}");
+            }
+            println!("{}", engine::maintenance_message(&summary));
+            if !summary.problems.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        "backup" => {
+            let mut app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            handle_backup_command(&mut app, &subargs)?;
+        }
+        "podcast" => {
+            let mut app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            handle_podcast_command(&mut app, &subargs)?;
+        }
+        "export" => {
+            let app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            handle_export_command(&app, subargs)?;
+        }
+        "import" => {
+            let mut app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            handle_import_command(&mut app, subargs)?;
+        }
+        "subsonic" => {
+            let mut app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            handle_subsonic_command(&mut app, &subargs)?;
+        }
+        "analyze" => {
+            let mut app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            let progress = |line: String| eprint!("\r{line}");
+            let message = match subargs.first().map(String::as_str) {
+                None | Some("bpm") => analysis::analyze_bpm(&mut app.db, &progress)?,
+                Some("loudness") => analysis::analyze_loudness(&mut app.db, &progress)?,
+                Some("silence") => {
+                    let threshold_db = app.config.playback.silence_threshold_db;
+                    analysis::analyze_silence(&mut app.db, threshold_db, &progress)?
+                }
+                Some(other) => {
+                    bail!("usage: auric analyze [bpm | loudness | silence] (got {other})")
+                }
+            };
+            eprint!("\r");
+            println!("{message}");
+        }
+        "review" => {
+            let app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            handle_review_command(&app, subargs)?;
+        }
+        "health" => {
+            let mut app = bootstrap()?;
+            let subargs: Vec<String> = args.collect();
+            handle_health_command(&mut app, &subargs)?;
+        }
         "playlist" => {
-            let app = bootstrap_from_config_path(&config_path)?;
+            let app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_playlist_command(&app, &subargs)?;
         }
         "scan" => {
-            let mut app = bootstrap_from_config_path(&config_path)?;
+            let mut app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_scan_command(&mut app, &subargs)?;
         }
         "watch" => {
-            let mut app = bootstrap_from_config_path(&config_path)?;
+            let mut app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_watch_command(&mut app, &subargs)?;
         }
         "artwork" => {
-            let app = bootstrap_from_config_path(&config_path)?;
+            let app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_artwork_command(&app, &subargs)?;
         }
         "track" => {
-            let app = bootstrap_from_config_path(&config_path)?;
+            let app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_track_command(&app, &subargs)?;
         }
         "audio" => {
-            let app = bootstrap_from_config_path(&config_path)?;
+            let app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_audio_command(&app, &subargs)?;
         }
         "playback" => {
-            let mut app = bootstrap_from_config_path(&config_path)?;
+            let mut app = bootstrap()?;
             let subargs: Vec<String> = args.collect();
             handle_playback_command(&mut app, &subargs)?;
         }
         "ui" => {
-            let mut app = bootstrap_from_config_path(&config_path)?;
             let subargs: Vec<String> = args.collect();
+            let interactive = matches!(subargs.first().map(String::as_str), None | Some("preview"));
+            if interactive && !startup.new_instance && remote::instance_running() {
+                return report_running_instance();
+            }
+            let mut app = bootstrap()?;
             handle_ui_command(&mut app, &subargs)?;
         }
-        "--version" | "-V" | "version" => {
+        name if remote::RemoteCommand::NAMES.contains(&name) => {
+            let subargs: Vec<String> = args.collect();
+            remote::run_client(name, &subargs)?;
+        }
+        "version" => {
             println!("auric {VERSION}");
         }
         "update" => {
+            let offline_in_config = AppConfig::load_from_path(&config_path)
+                .map(|config| config.network.offline)
+                .unwrap_or(false);
+            if startup.offline || offline_in_config {
+                bail!(
+                    "offline mode is on; turn off `[network] offline` or drop --offline to update"
+                );
+            }
             println!("Checking for updates...");
             match update::self_update(VERSION) {
                 Ok(msg) => println!("{msg}"),
@@ -509,9 +1279,17 @@ pub fn run_cli() -> Result<()> {
                 }
             }
         }
+        other if Path::new(other).exists() => {
+            if !startup.new_instance && remote::instance_running() {
+                return remote::run_client("open", &[other.to_string()]);
+            }
+            let mut app = bootstrap()?;
+            open_path_and_play(&mut app, Path::new(other))?;
+            handle_ui_command(&mut app, &[])?;
+        }
         other => {
             bail!(
-                "unknown command: {other}. expected one of: init, doctor, db-stress [count], feature, root, playlist, scan, watch, artwork, track, audio, playback, ui, update, version"
+                "unknown command: {other}. expected a file or folder to play, or one of: init, doctor, db-stress [count], feature, root, profile, backup, export, import, maintenance, podcast, subsonic, health, review, playlist, scan, watch, artwork, track, audio, playback, ui, update, version, or play|pause|next|prev|status|add <path> for a running instance"
             );
         }
     }
@@ -519,345 +1297,127 @@ pub fn run_cli() -> Result<()> {
     Ok(())
 }
 
-fn resolve_config_path() -> PathBuf {
-    if let Ok(path) = env::var("AURIC_CONFIG") {
-        let trimmed = path.trim();
-        if !trimmed.is_empty() {
-            return PathBuf::from(trimmed);
-        }
-    }
-    PathBuf::from("config/default.toml")
+/// A second shell would fight the first over the database and audio device,
+/// so point at the running one instead.
+fn report_running_instance() -> Result<()> {
+    let status = remote::send_to(&remote::socket_path(), &remote::RemoteCommand::Status)
+        .map(|reply| reply.message)
+        .unwrap_or_else(|err| format!("{err:#}"));
+    println!("auric is already running ({status}).");
+    println!("Control it with `auric play|pause|next|prev|status`, or pass --new-instance to start another.");
+    Ok(())
 }
 
-fn dispatch_app_command(app: &mut BootstrappedApp, command: AppCommand) -> Result<Vec<AppEvent>> {
-    let mut events = Vec::new();
+/// Offers the profile picker when profiles exist and both ends are a
+/// terminal; scripts and pipes keep getting the base config.
+fn pick_profile_at_startup(config_path: &Path) -> Result<Option<profile::Profile>> {
+    use std::io::IsTerminal;
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(None);
+    }
+    let profiles = profile::list(config_path)?;
+    if profiles.is_empty() {
+        return Ok(None);
+    }
+    profile::pick(
+        &profiles,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout(),
+    )
+}
 
-    match command {
-        AppCommand::ToggleFeature { feature, enabled } => {
-            let transition = if enabled {
-                FeatureState::Starting
-            } else {
-                FeatureState::Stopping
-            };
-            app.feature_registry.set_state(feature, transition.clone());
-            events.push(AppEvent::FeatureStateChanged {
-                feature,
-                state: transition,
-            });
-
-            app.db
-                .set_setting_json(&feature_setting_key(feature), &json!(enabled))?;
-
-            let final_state = if enabled {
-                FeatureState::Enabled
-            } else {
-                FeatureState::Disabled
-            };
-            app.feature_registry.set_state(feature, final_state.clone());
-            events.push(AppEvent::FeatureStateChanged {
-                feature,
-                state: final_state,
-            });
-
-            app.report.feature_enabled_count = FeatureId::ALL
-                .into_iter()
-                .filter(|f| app.feature_registry.is_enabled(*f))
-                .count();
+fn handle_profile_command(base_config: &Path, args: &[String]) -> Result<()> {
+    let sub = args.first().map(String::as_str).unwrap_or("list");
+    match sub {
+        "list" => {
+            let profiles = profile::list(base_config)?;
+            if profiles.is_empty() {
+                println!("no profiles (create one with `auric profile create <name>`)");
+            }
+            for profile in profiles {
+                println!("{} | {}", profile.name, profile.dir.display());
+            }
         }
-        cmd @ (AppCommand::Play
-        | AppCommand::Pause
-        | AppCommand::Stop
-        | AppCommand::Next
-        | AppCommand::Previous
-        | AppCommand::SeekMillis(_)
-        | AppCommand::SetVolume(_)) => {
-            handle_playback_transport_command(app, cmd, &mut events)?;
+        "create" => {
+            let name = join_args(args, 1)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric profile create <name>"))?;
+            let created = profile::create(base_config, &default_data_dir(), &name)?;
+            println!(
+                "created profile {} at {}",
+                created.name,
+                created.dir.display()
+            );
+            println!("open it with `auric --profile {:?}`", created.name);
         }
+        other => bail!("unknown profile subcommand: {other} (expected list|create)"),
     }
-
-    Ok(events)
+    Ok(())
 }
 
-fn handle_tui_playback_action(
-    app: &mut BootstrappedApp,
-    action: PlaybackAction,
-) -> Result<PaletteCommandResult> {
-    match action {
-        PlaybackAction::PlayTrack { track_index } => {
-            let total = app.db.stats().map(|s| s.track_count).unwrap_or(250) as usize;
-            let limit = total.min(5000);
-            let tracks = app.db.list_tracks(limit).unwrap_or_default();
-            let queue: Vec<PlaybackQueueEntry> = tracks
-                .into_iter()
-                .map(|t| PlaybackQueueEntry {
-                    track_id: t.id,
-                    path: t.path,
-                    title: t.title,
-                    artist: t.artist,
-                    album: t.album,
-                    duration_ms: t.duration_ms,
-                    sample_rate: t.sample_rate,
-                    channels: t.channels,
-                    bit_depth: t.bit_depth,
-                })
-                .collect();
-
-            if track_index >= queue.len() {
-                return Ok(PaletteCommandResult::new("No track at that index", false));
-            }
-
-            app.playback_state.queue = queue;
-            app.playback_state.session.current_index = Some(track_index);
-            app.playback_state.session.status = PlaybackStatus::Playing;
-            app.playback_state.session.position_ms = 0;
+/// Removes `flag <value>` or `flag=<value>` from `args`, wherever it appears.
+fn take_option(args: &mut Vec<String>, flag: &str) -> Result<Option<String>> {
+    let Some(i) = args
+        .iter()
+        .position(|arg| arg == flag || arg.starts_with(&format!("{flag}=")))
+    else {
+        return Ok(None);
+    };
+    let arg = args.remove(i);
+    if let Some(value) = arg.strip_prefix(&format!("{flag}=")) {
+        return Ok(Some(value.to_string()));
+    }
+    if i >= args.len() {
+        bail!("missing value for {flag}");
+    }
+    Ok(Some(args.remove(i)))
+}
 
-            let entry = &app.playback_state.queue[track_index];
-            app.player.load(&entry.path);
-            app.player.set_volume(app.playback_state.session.volume);
+/// Starts file logging. A config that fails to load is reported by bootstrap
+/// instead, so logging is simply skipped here; a bad `--log-level` is an error.
+fn init_logging(config_path: &Path, level_override: Option<&str>) -> Result<()> {
+    if let Some(level) = level_override {
+        logging::parse_level(level)?;
+    }
+    let Ok(config) = AppConfig::load_from_path(config_path) else {
+        return Ok(());
+    };
+    if let Err(err) = logging::init(&config.logging, config_path, level_override) {
+        eprintln!("warning: file logging disabled: {err:#}");
+    }
+    Ok(())
+}
 
-            let title = entry.title.clone().unwrap_or_default();
-            Ok(PaletteCommandResult::new(
-                format!("Playing: {title}"),
-                true,
-            ))
-        }
-        PlaybackAction::TogglePause => match app.playback_state.session.status {
-            PlaybackStatus::Playing => {
-                app.player.pause();
-                app.playback_state.session.status = PlaybackStatus::Paused;
-                Ok(PaletteCommandResult::new("Paused", true))
-            }
-            PlaybackStatus::Paused => {
-                app.player.resume();
-                app.playback_state.session.status = PlaybackStatus::Playing;
-                Ok(PaletteCommandResult::new("Resumed", true))
-            }
-            PlaybackStatus::Stopped => {
-                if let Some(idx) = app.playback_state.session.current_index {
-                    let entry_path = app.playback_state.queue.get(idx).map(|e| e.path.clone());
-                    let entry_title = app
-                        .playback_state
-                        .queue
-                        .get(idx)
-                        .and_then(|e| e.title.clone());
-                    if let Some(path) = entry_path {
-                        app.player.load(&path);
-                        app.player.set_volume(app.playback_state.session.volume);
-                        app.playback_state.session.status = PlaybackStatus::Playing;
-                        let title = entry_title.unwrap_or_default();
-                        return Ok(PaletteCommandResult::new(
-                            format!("Playing: {title}"),
-                            true,
-                        ));
-                    }
-                }
-                Ok(PaletteCommandResult::new("No track to play", false))
-            }
-        },
-        PlaybackAction::Stop => {
-            app.player.stop();
-            app.playback_state.session.status = PlaybackStatus::Stopped;
-            Ok(PaletteCommandResult::new("Stopped", true))
-        }
-        PlaybackAction::Next => {
-            let mut events = Vec::new();
-            handle_playback_transport_command(app, AppCommand::Next, &mut events)?;
-            let status = app.playback_state.session.status;
-            let entry_info = app.playback_state.current_entry().map(|e| {
-                (e.path.clone(), e.title.clone().unwrap_or_default())
-            });
-            if status == PlaybackStatus::Playing || status == PlaybackStatus::Paused {
-                if let Some((path, title)) = entry_info {
-                    app.player.load(&path);
-                    app.player.set_volume(app.playback_state.session.volume);
-                    app.playback_state.session.status = PlaybackStatus::Playing;
-                    return Ok(PaletteCommandResult::new(
-                        format!("Playing: {title}"),
-                        true,
-                    ));
-                }
-            }
-            app.player.stop();
-            Ok(PaletteCommandResult::new("End of queue", true))
-        }
-        PlaybackAction::Previous => {
-            let mut events = Vec::new();
-            handle_playback_transport_command(app, AppCommand::Previous, &mut events)?;
-            let status = app.playback_state.session.status;
-            let entry_info = app.playback_state.current_entry().map(|e| {
-                (e.path.clone(), e.title.clone().unwrap_or_default())
-            });
-            if let Some((path, title)) = entry_info {
-                if status == PlaybackStatus::Playing {
-                    app.player.load(&path);
-                    app.player.set_volume(app.playback_state.session.volume);
-                }
-                return Ok(PaletteCommandResult::new(
-                    format!("Track: {title}"),
-                    true,
-                ));
-            }
-            Ok(PaletteCommandResult::new("Start of queue", true))
-        }
-        PlaybackAction::VolumeUp => {
-            let new_vol = (app.playback_state.session.volume + 0.05).min(1.0);
-            app.playback_state.session.volume = new_vol;
-            app.player.set_volume(new_vol);
-            Ok(PaletteCommandResult::new(
-                format!("Volume: {}%", (new_vol * 100.0).round() as u32),
-                true,
-            ))
-        }
-        PlaybackAction::VolumeDown => {
-            let new_vol = (app.playback_state.session.volume - 0.05).max(0.0);
-            app.playback_state.session.volume = new_vol;
-            app.player.set_volume(new_vol);
-            Ok(PaletteCommandResult::new(
-                format!("Volume: {}%", (new_vol * 100.0).round() as u32),
-                true,
-            ))
-        }
-        PlaybackAction::ToggleShuffle => {
-            app.playback_state.session.shuffle = !app.playback_state.session.shuffle;
-            let label = if app.playback_state.session.shuffle {
-                "Shuffle: on"
-            } else {
-                "Shuffle: off"
-            };
-            Ok(PaletteCommandResult::new(label, true))
-        }
-        PlaybackAction::Seek { position_ms } => {
-            app.playback_state.session.position_ms = position_ms;
-            let secs = position_ms / 1000;
-            Ok(PaletteCommandResult::new(
-                format!("Seek: {:02}:{:02}", secs / 60, secs % 60),
-                true,
-            ))
+/// Falls back to the default value when a non-essential lookup fails,
+/// logging the error rather than dropping it.
+fn load_browser_places(db: &Database) -> BrowserPlaces {
+    match db.get_setting_json(BROWSER_PLACES_SETTING_KEY) {
+        Ok(Some(value)) => serde_json::from_value(value).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "ignoring unreadable file browser places");
+            BrowserPlaces::default()
+        }),
+        Ok(None) => BrowserPlaces::default(),
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to load file browser places");
+            BrowserPlaces::default()
         }
     }
 }
 
-fn handle_playback_transport_command(
-    app: &mut BootstrappedApp,
-    command: AppCommand,
-    events: &mut Vec<AppEvent>,
-) -> Result<()> {
-    let prev_track_id = current_track_id(&app.playback_state);
-    let mut track_changed = false;
-
-    match command {
-        AppCommand::Play => {
-            if app.playback_state.queue.is_empty() {
-                events.push(AppEvent::Warning("playback queue is empty".to_string()));
-                return Ok(());
-            }
-            if app.playback_state.session.current_index.is_none() {
-                app.playback_state.session.current_index = Some(0);
-                app.playback_state.session.position_ms = 0;
-                track_changed = true;
-            }
-            app.playback_state.session.status = PlaybackStatus::Playing;
-        }
-        AppCommand::Pause => {
-            app.playback_state.session.status = PlaybackStatus::Paused;
-        }
-        AppCommand::Stop => {
-            app.playback_state.session.status = PlaybackStatus::Stopped;
-            app.playback_state.session.position_ms = 0;
-        }
-        AppCommand::SeekMillis(position_ms) => {
-            let clamped = if let Some(entry) = app.playback_state.current_entry() {
-                match entry.duration_ms {
-                    Some(ms) if ms > 0 => position_ms.min(ms as u64),
-                    _ => position_ms,
-                }
-            } else {
-                position_ms
-            };
-            app.playback_state.session.position_ms = clamped;
-            events.push(AppEvent::PlaybackPositionMillis(clamped));
-        }
-        AppCommand::SetVolume(volume) => {
-            let normalized = if volume.is_finite() { volume } else { 1.0 };
-            app.playback_state.session.volume = normalized.clamp(0.0, 1.0);
-            app.player.set_volume(app.playback_state.session.volume);
-        }
-        AppCommand::Next => {
-            if app.playback_state.queue.is_empty() {
-                events.push(AppEvent::Warning("playback queue is empty".to_string()));
-                return Ok(());
-            }
-            let len = app.playback_state.queue.len();
-            let current = app.playback_state.session.current_index.unwrap_or(0);
-            let next_index = match app.playback_state.session.repeat {
-                RepeatMode::One => Some(current.min(len.saturating_sub(1))),
-                RepeatMode::All => Some((current + 1) % len),
-                RepeatMode::Off => {
-                    if current + 1 < len {
-                        Some(current + 1)
-                    } else {
-                        None
-                    }
-                }
-            };
+fn or_default_logged<T: Default, E: std::fmt::Display>(result: Result<T, E>, context: &str) -> T {
+    result.unwrap_or_else(|err| {
+        tracing::warn!(error = %err, "{context}");
+        T::default()
+    })
+}
 
-            match next_index {
-                Some(idx) => {
-                    let was_none = app.playback_state.session.current_index.is_none();
-                    app.playback_state.session.current_index = Some(idx);
-                    app.playback_state.session.position_ms = 0;
-                    if app.playback_state.session.status == PlaybackStatus::Stopped {
-                        app.playback_state.session.status = PlaybackStatus::Paused;
-                    }
-                    track_changed =
-                        was_none || prev_track_id != current_track_id(&app.playback_state);
-                }
-                None => {
-                    app.playback_state.session.status = PlaybackStatus::Stopped;
-                    app.playback_state.session.position_ms = 0;
-                    events.push(AppEvent::Warning("end of queue".to_string()));
-                }
-            }
-        }
-        AppCommand::Previous => {
-            if app.playback_state.queue.is_empty() {
-                events.push(AppEvent::Warning("playback queue is empty".to_string()));
-                return Ok(());
-            }
-            if app.playback_state.session.position_ms > 3_000 {
-                app.playback_state.session.position_ms = 0;
-                events.push(AppEvent::PlaybackPositionMillis(0));
-            } else {
-                let len = app.playback_state.queue.len();
-                let current = app.playback_state.session.current_index.unwrap_or(0);
-                let prev_index = match app.playback_state.session.repeat {
-                    RepeatMode::One => current.min(len.saturating_sub(1)),
-                    RepeatMode::All => {
-                        if current == 0 {
-                            len.saturating_sub(1)
-                        } else {
-                            current - 1
-                        }
-                    }
-                    RepeatMode::Off => current.saturating_sub(1),
-                };
-                app.playback_state.session.current_index = Some(prev_index);
-                app.playback_state.session.position_ms = 0;
-                track_changed = prev_track_id != current_track_id(&app.playback_state);
-            }
+fn resolve_config_path() -> PathBuf {
+    if let Ok(path) = env::var("AURIC_CONFIG") {
+        let trimmed = path.trim();
+        if !trimmed.is_empty() {
+            return PathBuf::from(trimmed);
         }
-        AppCommand::ToggleFeature { .. } => unreachable!("handled above"),
-    }
-
-    let new_track_id = current_track_id(&app.playback_state);
-    if track_changed || prev_track_id != new_track_id {
-        events.push(AppEvent::TrackChanged {
-            track_id: new_track_id,
-        });
     }
-    emit_playback_state_changed(events, &app.playback_state);
-    persist_playback_state(app)?;
-    Ok(())
+    PathBuf::from("config/default.toml")
 }
 
 fn handle_feature_command(app: &mut BootstrappedApp, args: &[String]) -> Result<()> {
@@ -928,7 +1488,14 @@ fn handle_root_command(app: &BootstrappedApp, args: &[String]) -> Result<()> {
                 println!("no library roots");
             } else {
                 for row in rows {
-                    println!("{} | watched={} | {}", row.id, row.watched, row.path);
+                    println!(
+                        "{} | watched={} paused={} offline={} | {}",
+                        row.id,
+                        row.watched,
+                        row.paused,
+                        row.offline_since_ms.is_some(),
+                        row.path
+                    );
                 }
             }
         }
@@ -957,7 +1524,20 @@ fn handle_root_command(app: &BootstrappedApp, args: &[String]) -> Result<()> {
                 row.id, row.watched, row.path
             );
         }
-        _ => bail!("usage: auric root [list|add <path> [--watched]]"),
+        "pause" | "resume" => {
+            let path = args
+                .get(1)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric root {sub} <path>"))?;
+            if app.db.get_library_root_by_path(path)?.is_none() {
+                bail!("not a library root: {path}");
+            }
+            let row = app.db.set_library_root_paused(path, sub == "pause")?;
+            println!(
+                "root saved: {} | paused={} | {}",
+                row.id, row.paused, row.path
+            );
+        }
+        _ => bail!("usage: auric root [list|add <path> [--watched]|pause <path>|resume <path>]"),
     }
     Ok(())
 }
@@ -1088,6 +1668,22 @@ fn handle_playlist_command(app: &BootstrappedApp, args: &[String]) -> Result<()>
             app.db.remove_playlist_track_at(playlist_id, position)?;
             println!("playlist track removed: {} @ {}", playlist_id, position);
         }
+        "move-track" => {
+            let usage = "usage: auric playlist move-track <playlist-id> <from> <to>";
+            let playlist_id = args
+                .get(1)
+                .map(String::as_str)
+                .ok_or_else(|| anyhow::anyhow!(usage))?;
+            let parse_position = |index: usize| -> Result<i64> {
+                let raw = args.get(index).ok_or_else(|| anyhow::anyhow!(usage))?;
+                raw.parse::<i64>()
+                    .with_context(|| format!("invalid playlist position: {raw}"))
+            };
+            let from = parse_position(2)?;
+            let to = parse_position(3)?;
+            app.db.move_playlist_track(playlist_id, from, to)?;
+            println!("playlist track moved: {} @ {} -> {}", playlist_id, from, to);
+        }
         "clear-tracks" => {
             let playlist_id = args.get(1).map(String::as_str).ok_or_else(|| {
                 anyhow::anyhow!("usage: auric playlist clear-tracks <playlist-id>")
@@ -1095,11 +1691,443 @@ fn handle_playlist_command(app: &BootstrappedApp, args: &[String]) -> Result<()>
             let removed = app.db.clear_playlist_tracks(playlist_id)?;
             println!("playlist tracks cleared: {} (removed {})", playlist_id, removed);
         }
-        _ => bail!("usage: auric playlist [list|create <name>|rename <id> <name>|delete <id>|list-tracks <id> [--limit N]|add-track <playlist-id> <track-path>|add-track <playlist-id> --track-id <track-id>|remove-track <playlist-id> <position>|clear-tracks <playlist-id>]"),
+        _ => bail!("usage: auric playlist [list|create <name>|rename <id> <name>|delete <id>|list-tracks <id> [--limit N]|add-track <playlist-id> <track-path>|add-track <playlist-id> --track-id <track-id>|remove-track <playlist-id> <position>|move-track <playlist-id> <from> <to>|clear-tracks <playlist-id>]"),
+    }
+    Ok(())
+}
+
+fn handle_backup_command(app: &mut BootstrappedApp, args: &[String]) -> Result<()> {
+    let mut args = args.to_vec();
+    let map = take_option(&mut args, "--map")?;
+    let sub = args.first().map(String::as_str).unwrap_or_default();
+    let path = join_args(&args, 1);
+    match (sub, path) {
+        ("export", Some(path)) => {
+            app.db.write_library_backup(Path::new(&path))?;
+            println!("library exported to {path}");
+        }
+        ("import", Some(path)) => {
+            println!("{}", import_library_backup(app, &path, map.as_deref())?);
+        }
+        _ => bail!("usage: auric backup export <file.db|file.json> | auric backup import <file> [--map FROM=TO]"),
+    }
+    Ok(())
+}
+
+fn handle_podcast_command(app: &mut BootstrappedApp, args: &[String]) -> Result<()> {
+    let sub = args.first().map(String::as_str).unwrap_or("list");
+    let arg = join_args(args, 1);
+    if matches!(sub, "subscribe" | "refresh" | "download") && app.offline {
+        bail!("offline mode is on; podcasts cannot be fetched");
+    }
+    match (sub, arg) {
+        ("list", _) => {
+            let podcasts = app.db.list_podcasts()?;
+            if podcasts.is_empty() {
+                println!("no podcasts (subscribe with `auric podcast subscribe <feed-url>`)");
+            }
+            for podcast in podcasts {
+                println!(
+                    "{} | {} | episodes={} unplayed={} | {}",
+                    podcast.id, podcast.title, podcast.episode_count, podcast.unplayed_count, podcast.feed_url
+                );
+            }
+        }
+        ("subscribe", Some(url)) => {
+            let (podcast, added) = podcast::subscribe(&mut app.db, &url)?;
+            println!("subscribed to {} ({added} episodes)", podcast.title);
+        }
+        ("refresh", _) => println!("{}", podcast::refresh_all(&mut app.db, false)?),
+        ("episodes", Some(name)) => {
+            let podcast = podcast::find(&app.db, &name)?;
+            for episode in app.db.list_podcast_episodes(&podcast.id, 500)? {
+                println!(
+                    "{} | {} | position={}s played={} downloaded={}",
+                    episode.id,
+                    episode.title,
+                    episode.position_ms / 1000,
+                    episode.played,
+                    episode.local_path.is_some()
+                );
+            }
+        }
+        ("download", Some(id)) => {
+            let episode = app
+                .db
+                .get_podcast_episode(&id)?
+                .ok_or_else(|| anyhow::anyhow!("unknown episode: {id}"))?;
+            println!("downloaded to {}", podcast::download(&app.db, &episode)?.display());
+        }
+        ("remove", Some(name)) => {
+            let podcast = podcast::find(&app.db, &name)?;
+            podcast::remove(&app.db, &podcast)?;
+            println!("unsubscribed from {}", podcast.title);
+        }
+        _ => bail!("usage: auric podcast [list | subscribe <feed-url> | refresh | episodes <podcast> | download <episode-id> | remove <podcast>]"),
+    }
+    Ok(())
+}
+
+/// `auric export <tracks|playlists> <file|-> [--folder DIR] [--format csv|json]`.
+fn handle_export_command(app: &BootstrappedApp, mut args: Vec<String>) -> Result<()> {
+    const USAGE: &str = "usage: auric export <tracks|playlists> <file.csv|file.json|-> [--folder DIR] [--format csv|json]";
+    let folder = take_option(&mut args, "--folder")?;
+    let format = match take_option(&mut args, "--format")? {
+        Some(name) => Some(
+            ExportFormat::parse(&name)
+                .ok_or_else(|| anyhow::anyhow!("unknown export format: {name}"))?,
+        ),
+        None => None,
+    };
+    let (Some(what), Some(dest)) = (args.first(), args.get(1)) else {
+        bail!(USAGE);
+    };
+    let message = export_table(app, what, dest, format, folder.as_deref())?;
+    if dest != "-" {
+        println!("{message}");
+    }
+    Ok(())
+}
+
+/// Writes the track table (`what` = "tracks") or the playlists to `dest`,
+/// or to stdout for `-`, limited to tracks under `folder` when given. The
+/// format defaults to the file's extension, or CSV on stdout.
+fn export_table(
+    app: &BootstrappedApp,
+    what: &str,
+    dest: &str,
+    format: Option<ExportFormat>,
+    folder: Option<&str>,
+) -> Result<String> {
+    let format = match format {
+        Some(format) => format,
+        None if dest == "-" => ExportFormat::Csv,
+        None => ExportFormat::from_path(Path::new(dest)).ok_or_else(|| {
+            anyhow::anyhow!(
+                "cannot tell the format of {dest}; name it .csv or .json, or pass --format"
+            )
+        })?,
+    };
+    let folder = folder.map(|f| f.trim_end_matches(['/', '\\']));
+    use std::io::Write as _;
+    let mut out: Box<dyn std::io::Write> = if dest == "-" {
+        Box::new(std::io::stdout().lock())
+    } else {
+        let file = fs::File::create(dest).with_context(|| format!("failed to create {dest}"))?;
+        Box::new(std::io::BufWriter::new(file))
+    };
+    let message = match what {
+        "tracks" => {
+            let tracks = app.db.list_tracks_with_stats(folder)?;
+            export::write_tracks(&mut out, format, &tracks)?;
+            format!("Exported {} tracks to {dest}", tracks.len())
+        }
+        "playlists" => {
+            let mut playlists = Vec::new();
+            for playlist in app.db.list_playlists()? {
+                let mut entries = app
+                    .db
+                    .list_playlist_tracks(&playlist.id, i64::MAX as usize)?;
+                if let Some(folder) = folder {
+                    entries.retain(|entry| Path::new(&entry.track.path).starts_with(folder));
+                }
+                playlists.push(PlaylistExport {
+                    name: playlist.name,
+                    entries,
+                });
+            }
+            export::write_playlists(&mut out, format, &playlists)?;
+            format!("Exported {} playlists to {dest}", playlists.len())
+        }
+        other => bail!("cannot export {other}; expected tracks or playlists"),
+    };
+    out.flush()
+        .with_context(|| format!("failed to write {dest}"))?;
+    Ok(message)
+}
+
+/// `auric review [RANGE] [FILE|-] [--format text|json]`: the year in
+/// review, printed as text unless a file or format says otherwise.
+fn handle_review_command(app: &BootstrappedApp, mut args: Vec<String>) -> Result<()> {
+    const USAGE: &str = "usage: auric review [YEAR | YYYY-MM | FROM..TO] [file.txt|file.json|-] [--format text|json]";
+    let format = match take_option(&mut args, "--format")? {
+        Some(name) => Some(
+            ReviewFormat::parse(&name)
+                .ok_or_else(|| anyhow::anyhow!("unknown review format: {name}"))?,
+        ),
+        None => None,
+    };
+    let range = args
+        .first()
+        .is_some_and(|arg| ReviewRange::parse(arg).is_some())
+        .then(|| args.remove(0));
+    if args.len() > 1 {
+        bail!(USAGE);
+    }
+    let dest = args.first().map_or("-", String::as_str);
+    let message = export_review(app, &review_range(app, range.as_deref())?, dest, format)?;
+    if dest != "-" {
+        println!("{message}");
+    }
+    Ok(())
+}
+
+/// Most played entries kept in a year in review.
+const REVIEW_TOP: usize = 10;
+
+/// The range `text` gives, or this year.
+fn review_range(app: &BootstrappedApp, text: Option<&str>) -> Result<ReviewRange> {
+    let this_year = app.db.local_today()?.chars().take(4).collect::<String>();
+    let text = text.unwrap_or(&this_year);
+    ReviewRange::parse(text).ok_or_else(|| {
+        anyhow::anyhow!("not a range: {text}; give a year, a YYYY-MM month or FROM..TO")
+    })
+}
+
+/// Writes the year in review for `range` to `dest`, or to stdout for `-`.
+/// The format defaults to the file's extension, or text.
+fn export_review(
+    app: &BootstrappedApp,
+    range: &ReviewRange,
+    dest: &str,
+    format: Option<ReviewFormat>,
+) -> Result<String> {
+    let format = match format {
+        Some(format) => format,
+        None if dest == "-" => ReviewFormat::Text,
+        None => ReviewFormat::from_path(Path::new(dest)).ok_or_else(|| {
+            anyhow::anyhow!(
+                "cannot tell the format of {dest}; name it .txt or .json, or pass --format"
+            )
+        })?,
+    };
+    let review = Review::build(&app.db, range, REVIEW_TOP)?;
+    use std::io::Write as _;
+    let mut out: Box<dyn std::io::Write> = if dest == "-" {
+        Box::new(std::io::stdout().lock())
+    } else {
+        let file = fs::File::create(dest).with_context(|| format!("failed to create {dest}"))?;
+        Box::new(std::io::BufWriter::new(file))
+    };
+    review::write_review(&mut out, format, &review)?;
+    out.flush()
+        .with_context(|| format!("failed to write {dest}"))?;
+    Ok(format!("Wrote your {} in review to {dest}", review.range))
+}
+
+fn shell_review(app: &BootstrappedApp, range: &ReviewRange) -> Result<ShellReview> {
+    let review = Review::build(&app.db, range, REVIEW_TOP)?;
+    let ranked = |entries: &[review::Ranked]| {
+        entries
+            .iter()
+            .map(|entry| (entry.display(), entry.plays.max(0) as u64))
+            .collect()
+    };
+    let ms = |ms: i64| ms.max(0) as u64;
+    Ok(ShellReview {
+        range: range.label.clone(),
+        title: review.range.clone(),
+        previous: range.step(-1).map(|r| r.label),
+        next: range.step(1).map(|r| r.label),
+        plays: ms(review.plays),
+        listened_ms: ms(review.listened_ms),
+        tracks: ms(review.tracks),
+        artists: ms(review.artists),
+        top_month: review
+            .top_month()
+            .map(|top| (review::month_name(&top.month), ms(top.listened_ms))),
+        top_artists: ranked(&review.top_artists),
+        top_albums: ranked(&review.top_albums),
+        top_tracks: ranked(&review.top_tracks),
+        months: review
+            .months
+            .iter()
+            .map(|month| (month.month.clone(), ms(month.listened_ms)))
+            .collect(),
+    })
+}
+
+/// `auric import <mpd|itunes|beets> ...`: ratings, play counts and
+/// playlists from another player, matched to scanned tracks by path.
+fn handle_import_command(app: &mut BootstrappedApp, mut args: Vec<String>) -> Result<()> {
+    const USAGE: &str = "usage: auric import mpd <sticker.sql> --music-dir DIR [--playlists DIR] [--map FROM=TO] | auric import itunes <Library.xml> [--map FROM=TO] | auric import beets <library.db> [--map FROM=TO]";
+    let map = take_option(&mut args, "--map")?;
+    let music_dir = take_option(&mut args, "--music-dir")?;
+    let playlist_dir = take_option(&mut args, "--playlists")?;
+    let (Some(source), Some(path)) = (args.first(), args.get(1)) else {
+        bail!(USAGE);
+    };
+    let path = Path::new(path);
+    let mut library = match source.as_str() {
+        "mpd" => {
+            let music_dir = music_dir.ok_or_else(|| {
+                anyhow::anyhow!("MPD stickers need --music-dir, MPD's music_directory")
+            })?;
+            let mut library = import::read_mpd_stickers(path, Path::new(&music_dir))?;
+            if let Some(dir) = playlist_dir {
+                library.playlists =
+                    import::read_mpd_playlists(Path::new(&dir), Path::new(&music_dir))?;
+            }
+            library
+        }
+        "itunes" => import::read_itunes_xml(path)?,
+        "beets" => import::read_beets(path)?,
+        _ => bail!(USAGE),
+    };
+    if let Some(map) = map {
+        let (from, to) = map
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("--map expects FROM=TO, got {map}"))?;
+        library.remap_paths(from.trim(), to.trim());
+    }
+    let summary = app.db.import_track_stats(&library)?;
+    println!(
+        "imported stats for {} tracks and {} playlists ({} entries)",
+        summary.tracks, summary.playlists, summary.playlist_entries
+    );
+    if summary.unmatched_tracks + summary.unmatched_entries > 0 {
+        println!(
+            "skipped {} tracks and {} playlist entries not in the library (scan the folder first, or use --map)",
+            summary.unmatched_tracks, summary.unmatched_entries
+        );
+    }
+    if summary.skipped_playlists > 0 {
+        println!(
+            "skipped {} playlists whose name is already taken",
+            summary.skipped_playlists
+        );
+    }
+    Ok(())
+}
+
+fn handle_subsonic_command(app: &mut BootstrappedApp, args: &[String]) -> Result<()> {
+    let sub = args.first().map(String::as_str).unwrap_or("status");
+    if matches!(sub, "ping" | "sync") && app.offline {
+        bail!("offline mode is on; the Subsonic server cannot be reached");
+    }
+    let config = &app.config.subsonic;
+    match sub {
+        "status" => println!("{}", subsonic_status(app)?),
+        "ping" => {
+            subsonic::ping(config)?;
+            println!("{} at {} answered", config.name, config.url);
+        }
+        "sync" => {
+            let summary = subsonic::sync(&mut app.db, config)?;
+            println!("{}", subsonic::sync_message(config, &summary));
+        }
+        _ => bail!("usage: auric subsonic [status | ping | sync]"),
     }
     Ok(())
 }
 
+fn handle_health_command(app: &mut BootstrappedApp, args: &[String]) -> Result<()> {
+    let progress = |line: String| eprint!("\r{line}");
+    let message = match (
+        args.first().map(String::as_str),
+        args.get(1).map(String::as_str),
+    ) {
+        (None, _) => {
+            for issue in HealthIssue::ALL {
+                println!(
+                    "{:<18} {}",
+                    issue.label(),
+                    app.db.count_health_issue(issue)?
+                );
+            }
+            match app.db.last_health_check_ms()? {
+                Some(ms) => format!(
+                    "Files last checked {}",
+                    auric_library::podcast::format_date(ms)
+                ),
+                None => "Files never checked; run `auric health check`".to_string(),
+            }
+        }
+        (Some("check"), None) => health::check_library(&mut app.db, &progress)?,
+        (Some("list"), Some(key)) => {
+            let issue = HealthIssue::from_key(key).ok_or_else(|| {
+                let keys = HealthIssue::ALL.map(HealthIssue::as_key);
+                anyhow::anyhow!("unknown issue {key}; expected one of: {}", keys.join(", "))
+            })?;
+            let tracks = app.db.tracks_with_health_issue(issue, usize::MAX)?;
+            for (track, found) in &tracks {
+                match found.as_ref().and_then(health::detail) {
+                    Some(detail) => println!("{}\t{detail}", track.path),
+                    None => println!("{}", track.path),
+                }
+            }
+            format!("{} tracks", tracks.len())
+        }
+        (Some("fix"), Some("tags")) => health::fix_missing_tags(&mut app.db, &progress)?,
+        (Some("fix"), Some("artwork")) => health::fix_missing_artwork(&mut app.db)?,
+        _ => bail!("usage: auric health [check | list <issue> | fix tags | fix artwork]"),
+    };
+    eprint!("\r");
+    println!("{message}");
+    Ok(())
+}
+
+/// The configured server, how many of its songs are in the library and
+/// when they were last synced.
+fn subsonic_status(app: &BootstrappedApp) -> Result<String> {
+    let config = &app.config.subsonic;
+    if !subsonic::is_configured(config) {
+        return Ok(
+            "No Subsonic server configured; set url and username under [subsonic]".to_string(),
+        );
+    }
+    let tracks = app
+        .db
+        .list_track_paths_under_prefix(subsonic::PATH_PREFIX.trim_end_matches('/'))?
+        .len();
+    let synced = match subsonic::synced_at_ms(&app.db) {
+        Some(ms) => format!("last synced {}", auric_library::podcast::format_date(ms)),
+        None => "never synced".to_string(),
+    };
+    Ok(format!(
+        "{} at {}: {tracks} tracks, {synced}",
+        config.name, config.url
+    ))
+}
+
+/// Restores a backup into the open library; `map` rewrites paths under
+/// `FROM` to `TO` for music mounted elsewhere on this machine.
+fn import_library_backup(
+    app: &mut BootstrappedApp,
+    path: &str,
+    map: Option<&str>,
+) -> Result<String> {
+    let mut export = auric_library::db::read_library_backup(Path::new(path))
+        .with_context(|| format!("failed to read backup {path}"))?;
+    if let Some(map) = map {
+        let (from, to) = map
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("--map expects FROM=TO, got {map}"))?;
+        export.remap_paths(from.trim(), to.trim());
+    }
+    let summary = app.db.import_library(&export)?;
+    let mut message = format!(
+        "Imported {} folders, {} tracks, {} playlists ({} entries), {} settings, \
+         stats for {} tracks, {} plays, {} resume positions, {} bookmarks",
+        summary.roots,
+        summary.tracks,
+        summary.playlists,
+        summary.playlist_entries,
+        summary.settings,
+        summary.track_stats,
+        summary.plays,
+        summary.resume_positions,
+        summary.bookmarks
+    );
+    if summary.skipped_entries > 0 {
+        message.push_str(&format!(
+            "; skipped {} playlist entries with no track",
+            summary.skipped_entries
+        ));
+    }
+    Ok(message)
+}
+
 fn join_args(args: &[String], start: usize) -> Option<String> {
     if args.len() <= start {
         return None;
@@ -1152,10 +2180,26 @@ fn handle_scan_command(app: &mut BootstrappedApp, args: &[String]) -> Result<()>
             let summary = scanner.scan_path(&mut app.db, Path::new(path))?;
             print_scan_summary(&summary);
         }
-        _ => bail!("usage: auric scan [roots [--prune] | path <dir> [--prune]]"),
-    }
-    Ok(())
-}
+        "watched" => {
+            let scanner = scanner_from_config(&app.config.library, false);
+            let summaries = engine::reconcile_watched_roots(&mut app.db, &scanner)?;
+            for summary in &summaries {
+                println!(
+                    "{} | added={} updated={} removed={} unchanged={} | {}ms",
+                    summary.root_path,
+                    summary.added_tracks,
+                    summary.updated_tracks,
+                    summary.removed_tracks,
+                    summary.unchanged_tracks,
+                    summary.elapsed_ms
+                );
+            }
+            println!("{}", engine::reconcile_message(&summaries));
+        }
+        _ => bail!("usage: auric scan [roots [--prune] | path <dir> [--prune] | watched]"),
+    }
+    Ok(())
+}
 
 fn scanner_from_config(cfg: &LibraryConfig, prune_override: bool) -> DirectoryScanner {
     DirectoryScanner::new(ScanOptions {
@@ -1254,6 +2298,7 @@ fn watcher_from_config(
         max_embedded_artwork_bytes: 8 * 1024 * 1024,
         scan_on_start: overrides.scan_on_start,
         max_runtime: overrides.run_for_ms.map(Duration::from_millis),
+        root_check_interval_ms: 2_000,
     })
 }
 
@@ -1306,10 +2351,18 @@ fn print_scan_summary(summary: &ScanSummary) {
         "  embedded_artwork_skipped_oversize: {}",
         summary.embedded_artwork_skipped_oversize
     );
+    println!(
+        "  folder_artwork_candidates: {}",
+        summary.folder_artwork_candidates
+    );
     println!(
         "  skipped_non_audio_files: {}",
         summary.skipped_non_audio_files
     );
+    println!(
+        "  skipped_filtered_files: {}",
+        summary.skipped_filtered_files
+    );
     println!(
         "  skipped_unreadable_entries: {}",
         summary.skipped_unreadable_entries
@@ -1346,6 +2399,16 @@ fn print_watch_summary(summary: &WatchSessionSummary) {
     if summary.rescans.len() > 8 {
         println!("    ... {} additional rescans", summary.rescans.len() - 8);
     }
+    println!("  renames: {}", summary.renames.len());
+    for rename in summary.renames.iter().take(8) {
+        println!(
+            "    from={} to={} tracks={}",
+            rename.from, rename.to, rename.moved_tracks
+        );
+    }
+    if summary.renames.len() > 8 {
+        println!("    ... {} additional renames", summary.renames.len() - 8);
+    }
     println!("  elapsed_ms: {}", summary.elapsed_ms);
 }
 
@@ -1541,6 +2604,7 @@ fn handle_playback_command(app: &mut BootstrappedApp, args: &[String]) -> Result
                 .ok_or_else(|| anyhow::anyhow!("usage: auric playback shuffle <on|off>"))?;
             app.playback_state.session.shuffle = parse_bool_toggle(raw)
                 .ok_or_else(|| anyhow::anyhow!("usage: auric playback shuffle <on|off>"))?;
+            app.playback_state.session.shuffle_history.clear();
             persist_playback_state(app)?;
             println!(
                 "shuffle => {}",
@@ -1552,11 +2616,57 @@ fn handle_playback_command(app: &mut BootstrappedApp, args: &[String]) -> Result
             );
             print_playback_status(app);
         }
+        "random-album" => {
+            let raw = args
+                .get(1)
+                .map(String::as_str)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric playback random-album <on|off>"))?;
+            app.playback_state.session.random_album = parse_bool_toggle(raw)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric playback random-album <on|off>"))?;
+            persist_playback_state(app)?;
+            println!(
+                "random_album => {}",
+                if app.playback_state.session.random_album {
+                    "on"
+                } else {
+                    "off"
+                }
+            );
+            print_playback_status(app);
+        }
+        "radio" => {
+            let raw = args
+                .get(1)
+                .map(String::as_str)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric playback radio <on|off>"))?;
+            app.playback_state.session.radio = parse_bool_toggle(raw)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric playback radio <on|off>"))?;
+            persist_playback_state(app)?;
+            println!(
+                "radio => {}",
+                if app.playback_state.session.radio { "on" } else { "off" }
+            );
+            print_playback_status(app);
+        }
+        "consume" => {
+            let raw = args
+                .get(1)
+                .map(String::as_str)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric playback consume <on|off>"))?;
+            app.playback_state.session.consume = parse_bool_toggle(raw)
+                .ok_or_else(|| anyhow::anyhow!("usage: auric playback consume <on|off>"))?;
+            persist_playback_state(app)?;
+            println!(
+                "consume => {}",
+                if app.playback_state.session.consume { "on" } else { "off" }
+            );
+            print_playback_status(app);
+        }
         "queue" => {
             handle_playback_queue_command(app, args)?;
         }
         _ => bail!(
-            "usage: auric playback [status|play|pause|stop|next|previous|seek <ms>|volume <0..1>|repeat <off|one|all>|shuffle <on|off>|queue ...]"
+            "usage: auric playback [status|play|pause|stop|next|previous|seek <ms>|volume <0..1>|repeat <off|one|all>|shuffle <on|off>|random-album <on|off>|radio <on|off>|consume <on|off>|queue ...]"
         ),
     }
     Ok(())
@@ -1746,6 +2856,12 @@ fn handle_playback_queue_command(app: &mut BootstrappedApp, args: &[String]) ->
             println!("removed queue item: {index}");
             print_playback_status(app);
         }
+        "dedupe" => {
+            let removed = engine::dedupe_queue(&mut app.playback_state);
+            persist_playback_state(app)?;
+            println!("removed duplicate queue items: {removed}");
+            print_playback_status(app);
+        }
         "select" | "play" => {
             let raw = args.get(2).ok_or_else(|| {
                 anyhow::anyhow!("usage: auric playback queue {sub} <index>")
@@ -1773,31 +2889,12 @@ fn handle_playback_queue_command(app: &mut BootstrappedApp, args: &[String]) ->
             print_playback_status(app);
         }
         _ => bail!(
-            "usage: auric playback queue [list [--limit N] | clear | add-path <track-path> | add-id <track-id> | add-prefix <path-prefix> [--limit N] | add-playlist <playlist-id> [--limit N] | load-playlist <playlist-id> [--limit N] | remove <index> | select <index> | play <index>]"
+            "usage: auric playback queue [list [--limit N] | clear | add-path <track-path> | add-id <track-id> | add-prefix <path-prefix> [--limit N] | add-playlist <playlist-id> [--limit N] | load-playlist <playlist-id> [--limit N] | remove <index> | dedupe | select <index> | play <index>]"
         ),
     }
     Ok(())
 }
 
-fn adjust_playback_selection_after_queue_removal(state: &mut PlaybackState, removed_index: usize) {
-    if state.queue.is_empty() {
-        state.session.current_index = None;
-        state.session.position_ms = 0;
-        state.session.status = PlaybackStatus::Stopped;
-        return;
-    }
-
-    match state.session.current_index {
-        Some(idx) if idx > removed_index => state.session.current_index = Some(idx - 1),
-        Some(idx) if idx == removed_index => {
-            let new_idx = removed_index.min(state.queue.len().saturating_sub(1));
-            state.session.current_index = Some(new_idx);
-            state.session.position_ms = 0;
-        }
-        _ => {}
-    }
-}
-
 fn print_playback_events(events: Vec<AppEvent>) {
     for event in events {
         println!("event: {event:?}");
@@ -1819,6 +2916,12 @@ fn print_playback_status(app: &BootstrappedApp) {
     println!("  position_ms: {}", session.position_ms);
     println!("  volume: {:.3}", session.volume);
     println!("  shuffle: {}", if session.shuffle { "on" } else { "off" });
+    println!(
+        "  random_album: {}",
+        if session.random_album { "on" } else { "off" }
+    );
+    println!("  radio: {}", if session.radio { "on" } else { "off" });
+    println!("  consume: {}", if session.consume { "on" } else { "off" });
     println!("  repeat: {}", format_repeat_mode(session.repeat));
     if let Some(entry) = app.playback_state.current_entry() {
         println!(
@@ -2019,165 +3122,194 @@ fn handle_ui_command(app: &mut BootstrappedApp, args: &[String]) -> Result<()> {
             let mouse = !has_flag(args, "--no-mouse");
             let (palette, snapshot) = load_ui_palette_and_snapshot(app);
             let mut state = ShellState::new(snapshot);
+            state.event_log = logging::event_log().clone();
+            for (job, message) in engine::startup_jobs(app) {
+                engine::queue_job(app, job, message);
+            }
+            app.suspend.listen();
+            // Removed again when dropped at the end of the session.
+            let _remote_server = match remote::RemoteServer::start(
+                &remote::socket_path(),
+                state.remote_inbox.clone(),
+            ) {
+                Ok(server) => Some(server),
+                Err(err) => {
+                    tracing::warn!("remote control disabled: {err:#}");
+                    None
+                }
+            };
+            let (state_queries_tx, state_queries) = remote::query_channel();
+            let _mpd_server = if app.config.mpd.enabled {
+                match mpd::MpdServer::start(
+                    &app.config.mpd,
+                    state_queries_tx.clone(),
+                    state.remote_inbox.clone(),
+                ) {
+                    Ok(server) => Some(server),
+                    Err(err) => {
+                        tracing::error!("MPD server disabled: {err:#}");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            #[cfg(target_os = "linux")]
+            let _mpris_server = mpris::MprisServer::start(
+                state_queries_tx.clone(),
+                state.remote_inbox.clone(),
+            )
+            .map_err(|err| tracing::info!("MPRIS disabled: {err:#}"))
+            .ok();
+            let http_config = app.config.http.clone();
             let mut update_checker = update::UpdateChecker::new();
-            let update_handle = update_checker.maybe_check();
+            let update_handle = if app.offline {
+                None
+            } else {
+                update_checker.maybe_check()
+            };
             let app_cell = std::cell::RefCell::new(app);
             let lib_config = {
                 let app_ref = app_cell.borrow();
                 app_ref.config.library.clone()
             };
+            let frames = FrameScheduler::new(app_cell.borrow().config.ui.refresh_hz);
             let db_options = {
                 let app_ref = app_cell.borrow();
                 let cwd = env::current_dir().unwrap_or_default();
-                app_ref.config.database.to_options(&cwd).unwrap_or_default()
+                or_default_logged(
+                    app_ref.config.database.to_options(&cwd),
+                    "invalid database config for background scans",
+                )
             };
-            run_interactive_full(
-                &mut state,
-                &palette,
-                RunOptions {
-                    mouse,
-                    ..RunOptions::default()
-                },
-                || {
-                    let app_ref = app_cell.borrow();
-                    Ok(build_shell_snapshot(&app_ref))
-                },
-                |input| {
-                    let mut app_ref = app_cell.borrow_mut();
-                    execute_ui_palette_command(&mut app_ref, input).map_err(|e| {
-                        auric_ui::UiError::Terminal(format!("palette command failed: {e}"))
-                    })
-                },
-                {
-                    let lib_config = lib_config.clone();
-                    let db_options = db_options.clone();
-                    move |scan_path: String| {
-                        let (tx, rx) = std::sync::mpsc::channel();
+            let _http_server = if http_config.enabled {
+                match http_api::HttpServer::start(
+                    &http_config,
+                    Some(db_options.clone()),
+                    state_queries_tx.clone(),
+                    state.remote_inbox.clone(),
+                ) {
+                    Ok(server) => Some(server),
+                    Err(err) => {
+                        tracing::error!("HTTP API disabled: {err:#}");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            // Catch a panic long enough to save the session; the panic hook has
+            // already restored the terminal by the time we get here.
+            let run_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                run_interactive_full(
+                    &mut state,
+                    &palette,
+                    RunOptions {
+                        frames,
+                        mouse,
+                    },
+                    || {
+                        let app_ref = app_cell.borrow();
+                        Ok(build_shell_snapshot(&app_ref))
+                    },
+                    |input| {
+                        let mut app_ref = app_cell.borrow_mut();
+                        execute_ui_palette_command(&mut app_ref, input).map_err(|e| {
+                            auric_ui::UiError::Terminal(format!("palette command failed: {e}"))
+                        })
+                    },
+                    {
                         let lib_config = lib_config.clone();
                         let db_options = db_options.clone();
-                        std::thread::spawn(move || {
-                            let done = std::sync::Arc::new(
-                                std::sync::atomic::AtomicBool::new(false),
+                        let hooks_config = app_cell.borrow().config.hooks.clone();
+                        let subsonic_config = app_cell.borrow().config.subsonic.clone();
+                        let app_cell = &app_cell;
+                        move || {
+                            let (job, message) = engine::next_job(&mut app_cell.borrow_mut())?;
+                            let progress = spawn_background_scan(
+                                lib_config.clone(),
+                                db_options.clone(),
+                                hooks_config.clone(),
+                                subsonic_config.clone(),
+                                job,
                             );
-
-                            // Progress poller: check DB track count periodically
-                            let progress_tx = tx.clone();
-                            let progress_db_opts = db_options.clone();
-                            let progress_path = scan_path.clone();
-                            let progress_done = std::sync::Arc::clone(&done);
-                            std::thread::spawn(move || {
-                                let db = Database::open(&progress_db_opts).ok();
-                                while !progress_done.load(
-                                    std::sync::atomic::Ordering::Relaxed,
-                                ) {
-                                    std::thread::sleep(Duration::from_millis(750));
-                                    if let Some(ref db) = db {
-                                        let count = db.stats().map(|s| s.track_count).unwrap_or(0);
-                                        let _ = progress_tx.send(ScanProgress::Progress {
-                                            discovered: count as usize,
-                                            path: progress_path.clone(),
-                                        });
+                            Some((message, progress))
+                        }
+                    },
+                    |action: PlaybackAction| {
+                        let mut app_ref = app_cell.borrow_mut();
+                        engine::handle(&mut app_ref, engine::Request::Playback(action.into()))
+                            .map(PaletteCommandResult::from)
+                            .map_err(|e| {
+                                auric_ui::UiError::Terminal(format!("playback error: {e}"))
+                            })
+                    },
+                    |analyze_spectrum| {
+                        let mut app_ref = app_cell.borrow_mut();
+                        let events = engine::tick(&mut app_ref, Instant::now());
+                        remote::answer_queries(&state_queries, &app_ref.playback_state);
+                        events
+                            .into_iter()
+                            .filter_map(|evt| {
+                                let (position_ms, duration_ms, status) = match evt {
+                                    PlayerEvent::Position {
+                                        position_ms,
+                                        duration_ms,
+                                    } => (position_ms, duration_ms, "playing"),
+                                    PlayerEvent::TrackFinished | PlayerEvent::Stopped => {
+                                        (0, 0, "stopped")
                                     }
-                                }
-                            });
-
-                            let scan_result = (|| -> anyhow::Result<ScanSummary> {
-                                let mut db = Database::open(&db_options)?;
-                                let scanner = scanner_from_config(&lib_config, false);
-                                let summary = scanner.scan_path(
-                                    &mut db,
-                                    std::path::Path::new(&scan_path),
-                                )?;
-                                Ok(summary)
-                            })();
-
-                            done.store(true, std::sync::atomic::Ordering::Relaxed);
-
-                            match scan_result {
-                                Ok(summary) => {
-                                    let _ = tx.send(ScanProgress::Done {
-                                        message: format!(
-                                            "Scan complete: {} ({} tracks imported in {:.1}s)",
-                                            summary.root_path,
-                                            summary.imported_tracks,
-                                            summary.elapsed_ms as f64 / 1000.0,
-                                        ),
-                                    });
-                                }
-                                Err(err) => {
-                                    let _ = tx.send(ScanProgress::Error {
-                                        message: format!("{err:#}"),
-                                    });
-                                }
-                            }
-                        });
-                        rx
-                    }
-                },
-                |action: PlaybackAction| {
-                    let mut app_ref = app_cell.borrow_mut();
-                    handle_tui_playback_action(&mut app_ref, action).map_err(|e| {
-                        auric_ui::UiError::Terminal(format!("playback error: {e}"))
-                    })
-                },
-                || {
-                    let app_ref = app_cell.borrow();
-                    let events = app_ref.player.poll_events();
-                    events
-                        .into_iter()
-                        .filter_map(|evt| match evt {
-                            auric_audio::player::PlayerEvent::Position {
-                                position_ms,
-                                duration_ms,
-                            } => {
-                                let samples =
-                                    app_ref.player.peek_visualization_samples(1024);
-                                let bands =
-                                    auric_ui::visualizer::analyze_spectrum(&samples, 32);
+                                    PlayerEvent::Paused => (0, 0, "paused"),
+                                    PlayerEvent::DeviceLost { .. } => (0, 0, "paused"),
+                                    PlayerEvent::DeviceRestored { .. } => (0, 0, "playing"),
+                                    _ => return None,
+                                };
+                                let message = match &evt {
+                                    PlayerEvent::DeviceLost { device, .. } => Some(format!(
+                                        "{device} disconnected; paused until it is back"
+                                    )),
+                                    PlayerEvent::DeviceRestored { device } => {
+                                        Some(format!("{device} is back; playing"))
+                                    }
+                                    _ => None,
+                                };
+                                let (spectrum_bands, raw_samples) = if status == "playing" && analyze_spectrum {
+                                    let samples =
+                                        app_ref.player.peek_visualization_samples(1024);
+                                    let bands =
+                                        auric_ui::visualizer::analyze_spectrum(&samples, 32);
+                                    (bands, samples)
+                                } else {
+                                    (Vec::new(), Vec::new())
+                                };
                                 Some(PlayerEventUpdate {
                                     position_ms,
                                     duration_ms,
-                                    status: "playing".to_string(),
-                                    track_finished: false,
-                                    spectrum_bands: bands,
-                                    raw_samples: samples,
-                                })
-                            }
-                            auric_audio::player::PlayerEvent::TrackFinished => {
-                                Some(PlayerEventUpdate {
-                                    position_ms: 0,
-                                    duration_ms: 0,
-                                    status: "stopped".to_string(),
-                                    track_finished: true,
-                                    spectrum_bands: Vec::new(),
-                                    raw_samples: Vec::new(),
-                                })
-                            }
-                            auric_audio::player::PlayerEvent::Paused => {
-                                Some(PlayerEventUpdate {
-                                    position_ms: 0,
-                                    duration_ms: 0,
-                                    status: "paused".to_string(),
-                                    track_finished: false,
-                                    spectrum_bands: Vec::new(),
-                                    raw_samples: Vec::new(),
+                                    status: status.to_string(),
+                                    track_finished: matches!(evt, PlayerEvent::TrackFinished),
+                                    message,
+                                    spectrum_bands,
+                                    raw_samples,
                                 })
-                            }
-                            auric_audio::player::PlayerEvent::Stopped => {
-                                Some(PlayerEventUpdate {
-                                    position_ms: 0,
-                                    duration_ms: 0,
-                                    status: "stopped".to_string(),
-                                    track_finished: false,
-                                    spectrum_bands: Vec::new(),
-                                    raw_samples: Vec::new(),
-                                })
-                            }
-                            _ => None,
-                        })
-                        .collect()
-                },
-            )?;
+                            })
+                            .collect()
+                    },
+                )
+            }));
+            let flushed = persist_playback_state(&mut app_cell.borrow_mut())
+                .and_then(|()| engine::flush_writes(&app_cell.borrow()));
+            let run_result = match run_result {
+                Ok(result) => result,
+                Err(panic) => {
+                    if let Err(err) = flushed {
+                        tracing::error!("failed to save session after panic: {err:#}");
+                        eprintln!("warning: failed to save session: {err:#}");
+                    }
+                    std::panic::resume_unwind(panic);
+                }
+            };
+            flushed?;
+            run_result?;
             if let Some(handle) = update_handle {
                 if let Ok(version) = handle.join() {
                     update_checker.finish_check(version);
@@ -2213,6 +3345,7 @@ fn load_ui_palette_and_snapshot(app: &BootstrappedApp) -> (Palette, ShellSnapsho
         }
     };
     palette.use_terminal_bg = !app.config.ui.use_theme_background;
+    let palette = palette.with_color_mode(ColorMode::from_config(&app.config.ui.color_mode));
     let snapshot = build_shell_snapshot(app);
     (palette, snapshot)
 }
@@ -2223,6 +3356,52 @@ fn default_theme_dir() -> PathBuf {
         .join("themes")
 }
 
+impl From<PlaybackAction> for engine::Action {
+    fn from(action: PlaybackAction) -> Self {
+        match action {
+            PlaybackAction::PlayTrack { track_index } => Self::PlayTrack { track_index },
+            PlaybackAction::TogglePause => Self::TogglePause,
+            PlaybackAction::Pause => Self::Pause,
+            PlaybackAction::Resume => Self::Resume,
+            PlaybackAction::Stop => Self::Stop,
+            PlaybackAction::Next => Self::Next,
+            PlaybackAction::Previous => Self::Previous,
+            PlaybackAction::VolumeUp => Self::VolumeUp,
+            PlaybackAction::VolumeDown => Self::VolumeDown,
+            PlaybackAction::SetVolume { volume } => Self::SetVolume { volume },
+            PlaybackAction::ToggleShuffle => Self::ToggleShuffle,
+            PlaybackAction::ToggleRandomAlbum => Self::ToggleRandomAlbum,
+            PlaybackAction::ToggleRadio => Self::ToggleRadio,
+            PlaybackAction::ToggleConsume => Self::ToggleConsume,
+            PlaybackAction::Seek { position_ms } => Self::Seek { position_ms },
+            PlaybackAction::Enqueue { path, requested_by } => Self::Enqueue { path, requested_by },
+            PlaybackAction::Advance => Self::Advance,
+        }
+    }
+}
+
+impl From<engine::Reply> for PaletteCommandResult {
+    fn from(reply: engine::Reply) -> Self {
+        match reply {
+            engine::Reply::Changed(message) => Self::new(message, true),
+            engine::Reply::Unchanged(message) => Self::new(message, false),
+        }
+    }
+}
+
+impl From<engine::JobProgress> for ScanProgress {
+    fn from(progress: engine::JobProgress) -> Self {
+        match progress {
+            engine::JobProgress::Progress { discovered, path } => {
+                Self::Progress { discovered, path }
+            }
+            engine::JobProgress::Status { message } => Self::Status { message },
+            engine::JobProgress::Done { message } => Self::Done { message },
+            engine::JobProgress::Error { message } => Self::Error { message },
+        }
+    }
+}
+
 fn execute_ui_palette_command(
     app: &mut BootstrappedApp,
     input: &str,
@@ -2235,9 +3414,13 @@ fn execute_ui_palette_command(
     let words = command.split_whitespace().collect::<Vec<_>>();
     let head = words.first().copied().unwrap_or_default();
 
+    if app.party.is_some() && !party::allows_command(head) {
+        bail!("{} (`party off <passphrase>` ends it)", party::LOCKED);
+    }
+
     match head {
         "help" | "?" => Ok(PaletteCommandResult::new(
-            "Palette commands: help, refresh, feature [list|enable|disable], scan [roots|path], root [list|add], playlist [list|create|rename|delete]",
+            "Palette commands: help, refresh, feature [list|enable|disable], scan [roots|path|watched], root [list|add|pause|resume], playlist [list|play|create|rename|delete], backup [export|import], maintenance [--no-vacuum], export [tracks|playlists], podcast [list|subscribe|refresh|download|play|remove], subsonic [status|sync], analyze [bpm|loudness|silence], health [check|fix tags|fix artwork], review [range|export], bookmark [list|add|go|delete], remember [on|off|auto], gain [track|queue] <dB>, queue [dedupe|consume], party [on|off], undo, trash <path>",
             false,
         )),
         "refresh" | "reload" => Ok(PaletteCommandResult::new(
@@ -2248,6 +3431,140 @@ fn execute_ui_palette_command(
         "scan" => execute_palette_scan_command(app, command, &words),
         "root" => execute_palette_root_command(app, command, &words),
         "playlist" => execute_palette_playlist_command(app, command, &words),
+        "podcast" => execute_palette_podcast_command(app, command, &words),
+        "subsonic" => execute_palette_subsonic_command(app, &words),
+        "analyze" => match words.get(1).copied().unwrap_or("bpm") {
+            "bpm" => Ok(run_in_background(
+                app,
+                BackgroundJob::Analysis(analysis::Job::Bpm),
+                "Analyzing track tempos...",
+            )),
+            "loudness" => Ok(run_in_background(
+                app,
+                BackgroundJob::Analysis(analysis::Job::Loudness),
+                "Measuring loudness...",
+            )),
+            "silence" => {
+                let threshold_db = app.config.playback.silence_threshold_db;
+                Ok(run_in_background(
+                    app,
+                    BackgroundJob::Analysis(analysis::Job::Silence { threshold_db }),
+                    "Finding silence...",
+                ))
+            }
+            _ => Ok(PaletteCommandResult::new("usage: analyze [bpm | loudness | silence]", false)),
+        },
+        "review" => match words.get(1).copied() {
+            Some("export") => {
+                let range = words.get(2).copied().filter(|word| ReviewRange::parse(word).is_some());
+                let path = strip_n_words(command, if range.is_some() { 3 } else { 2 })
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty() && s != "-");
+                match path {
+                    Some(path) => {
+                        let range = review_range(app, range)?;
+                        Ok(PaletteCommandResult::new(export_review(app, &range, &path, None)?, false))
+                    }
+                    None => Ok(PaletteCommandResult::new(
+                        "usage: review export [range] <file.txt|file.json>",
+                        false,
+                    )),
+                }
+            }
+            range => Ok(PaletteCommandResult::with_review(shell_review(app, &review_range(app, range)?)?)),
+        },
+        "health" => match (words.get(1).copied().unwrap_or("check"), words.get(2).copied()) {
+            ("check", None) => Ok(run_in_background(
+                app,
+                BackgroundJob::Health(health::Job::Check),
+                "Checking library health...",
+            )),
+            ("fix", Some("tags")) => Ok(run_in_background(
+                app,
+                BackgroundJob::Health(health::Job::FixTags),
+                "Filling missing tags...",
+            )),
+            ("fix", Some("artwork")) => Ok(run_in_background(
+                app,
+                BackgroundJob::Health(health::Job::FixArtwork),
+                "Linking folder covers...",
+            )),
+            _ => Ok(PaletteCommandResult::new("usage: health [check | fix tags | fix artwork]", false)),
+        },
+        "bookmark" => execute_palette_bookmark_command(app, command, &words),
+        "remember" => execute_palette_remember_command(app, &words),
+        "gain" => execute_palette_gain_command(app, &words),
+        "party" => execute_palette_party_command(app, command, &words),
+        "undo" => execute_palette_undo_command(app),
+        "queue" => match words.get(1).copied() {
+            Some("dedupe") => {
+                let removed = engine::dedupe_queue(&mut app.playback_state);
+                persist_playback_state(app)?;
+                let message = match removed {
+                    0 => "No duplicates in the queue".to_string(),
+                    1 => "Removed 1 duplicate from the queue".to_string(),
+                    n => format!("Removed {n} duplicates from the queue"),
+                };
+                Ok(PaletteCommandResult::new(message, true))
+            }
+            Some("consume") => {
+                engine::handle_playback_action(app, engine::Action::ToggleConsume).map(Into::into)
+            }
+            _ => Ok(PaletteCommandResult::new("usage: queue [dedupe|consume]", false)),
+        },
+        "trash" => match strip_n_words(command, 1)
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+        {
+            Some(path) => engine::trash_track(app, &path).map(Into::into),
+            None => Ok(PaletteCommandResult::new("usage: trash <track path>", false)),
+        },
+        "__party_quit" => {
+            let passphrase = strip_n_words(command, 1).unwrap_or_default();
+            match &app.party {
+                Some(party) if !party.unlocks(passphrase.trim()) => bail!("wrong passphrase"),
+                _ => Ok(PaletteCommandResult::quit("Goodbye")),
+            }
+        }
+        "maintenance" => {
+            let vacuum = !words.contains(&"--no-vacuum");
+            let summary = engine::run_maintenance(app, vacuum)?;
+            Ok(PaletteCommandResult::new(engine::maintenance_message(&summary), true))
+        }
+        "backup" => {
+            let path = strip_n_words(command, 2)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty());
+            match (words.get(1).copied(), path) {
+                (Some("export"), Some(path)) => {
+                    app.db.write_library_backup(Path::new(&path))?;
+                    Ok(PaletteCommandResult::new(format!("Library exported to {path}"), false))
+                }
+                (Some("import"), Some(path)) => Ok(PaletteCommandResult::new(
+                    import_library_backup(app, &path, None)?,
+                    true,
+                )),
+                _ => Ok(PaletteCommandResult::new(
+                    "usage: backup [export <file.db|file.json> | import <file>]",
+                    false,
+                )),
+            }
+        }
+        "export" => {
+            let path = strip_n_words(command, 2)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty() && s != "-");
+            match (words.get(1).copied(), path) {
+                (Some(what @ ("tracks" | "playlists")), Some(path)) => Ok(PaletteCommandResult::new(
+                    export_table(app, what, &path, None, None)?,
+                    false,
+                )),
+                _ => Ok(PaletteCommandResult::new(
+                    "usage: export <tracks|playlists> <file.csv|file.json>",
+                    false,
+                )),
+            }
+        }
         "watch" => Ok(PaletteCommandResult::new(
             "watch commands are not supported in the interactive shell (run from CLI)",
             false,
@@ -2261,102 +3578,169 @@ fn execute_ui_palette_command(
                 path: path.clone(),
                 watched: true,
             })?;
-            Ok(PaletteCommandResult::with_background_scan(
-                format!("Added {path}, scanning..."),
-                path,
-            ))
+            let mut places = load_browser_places(&app.db);
+            places.remember(&path);
+            app.db.set_setting_json(BROWSER_PLACES_SETTING_KEY, &serde_json::to_value(&places)?)?;
+            let status = format!("Added {path}, scanning...");
+            Ok(scan_in_background(app, path, status))
+        }
+        "__open" => {
+            let path = strip_n_words(command, 1)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("internal error: __open with no path"))?;
+            open_path_and_play(app, Path::new(&path)).map(Into::into)
         }
         "__fetch_artwork" => {
             let path = strip_n_words(command, 1)
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
                 .unwrap_or_default();
-            let data = if path.is_empty() {
+            let data = if path.is_empty() || !app.config.ui.show_artwork {
                 None
             } else {
-                app.db.get_artwork_data_for_track(&path).ok().flatten()
+                or_default_logged(app.db.get_artwork_data_for_track(&path), "failed to load artwork")
             };
             Ok(PaletteCommandResult::with_artwork("", data))
         }
-        "__setting_toggle" => {
-            let key = words.get(1).copied().unwrap_or("");
-            match key {
-                "use_theme_background" => {
-                    app.config.ui.use_theme_background = !app.config.ui.use_theme_background;
-                    Ok(PaletteCommandResult::new(
-                        format!(
-                            "Theme background: {}",
-                            if app.config.ui.use_theme_background { "on" } else { "off" }
-                        ),
-                        true,
-                    ))
-                }
-                "pixel_art_artwork" => {
-                    app.config.ui.pixel_art_artwork = !app.config.ui.pixel_art_artwork;
-                    Ok(PaletteCommandResult::new(
-                        format!(
-                            "Pixel art: {}",
-                            if app.config.ui.pixel_art_artwork { "on" } else { "off" }
-                        ),
-                        true,
-                    ))
-                }
-                _ => Ok(PaletteCommandResult::new(format!("Unknown setting: {key}"), false)),
+        "__album_art" => {
+            let raw = strip_n_words(command, 1).unwrap_or_default();
+            let albums = serde_json::from_str::<Vec<String>>(&raw).context("invalid album list")?;
+            let album_art = albums
+                .into_iter()
+                .map(|album| {
+                    let data = if app.config.ui.show_artwork {
+                        or_default_logged(app.db.get_artwork_data_for_album(&album), "failed to load album art")
+                    } else {
+                        None
+                    };
+                    (album, data)
+                })
+                .collect();
+            Ok(PaletteCommandResult::with_album_art(album_art))
+        }
+        "__layout_save" => {
+            let raw = strip_n_words(command, 1).unwrap_or_default();
+            let layout = serde_json::from_str::<PanelLayout>(&raw)
+                .context("invalid layout payload")?
+                .clamped();
+            engine::save_setting(app, PANEL_LAYOUT_SETTING_KEY, serde_json::to_value(layout)?)?;
+            app.config.ui.layout = layout;
+            Ok(PaletteCommandResult::new(layout.describe(), false))
+        }
+        "__places_save" => {
+            let raw = strip_n_words(command, 1).unwrap_or_default();
+            let places = serde_json::from_str::<BrowserPlaces>(&raw).context("invalid places payload")?;
+            let before = load_browser_places(&app.db);
+            app.db.set_setting_json(BROWSER_PLACES_SETTING_KEY, &serde_json::to_value(&places)?)?;
+            let added = places.bookmarks.iter().position(|dir| !before.bookmarks.contains(dir));
+            let removed = before.bookmarks.iter().find(|dir| !places.bookmarks.contains(dir));
+            let message = match (added, removed) {
+                (Some(slot), _) => format!("Bookmarked as {}: {}", slot + 1, places.bookmarks[slot]),
+                (None, Some(dir)) => format!("Bookmark removed: {dir}"),
+                (None, None) => "Folder places saved".to_string(),
+            };
+            Ok(PaletteCommandResult::new(message, false))
+        }
+        "__split_source" | "__split_add" | "__split_remove" | "__split_move" | "__queue_view" => {
+            execute_split_command(app, command, &words)
+        }
+        "__whole" => execute_whole_command(app, command, &words),
+        "__play_now" => {
+            let path = strip_n_words(command, 1)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("internal error: __play_now with no path"))?;
+            let track = app
+                .db
+                .get_track_by_path(&path)?
+                .ok_or_else(|| anyhow::anyhow!("not in the library: {path}"))?;
+            engine::play_now(app, track).map(Into::into)
+        }
+        "__queue_folder" => {
+            let root_id = words
+                .get(1)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("internal error: __queue_folder with no root"))?;
+            let column = words.get(2).and_then(|label| SortColumn::from_label(label));
+            let descending = words.get(3) == Some(&"desc");
+            let Some((name, mut tracks)) =
+                library_playlist(app, &format!("{FOLDER_PLAYLIST_PREFIX}{root_id}"))?
+            else {
+                bail!("library folder not found: {root_id}");
+            };
+            if let Some(column) = column {
+                // The order the track list shows it in; the sort is stable, so
+                // ties stay in path order.
+                let bpms = rounded_bpms(app)?;
+                let mut keyed = tracks
+                    .into_iter()
+                    .map(|row| (with_bpm(shell_track_item(row.clone()), &bpms), row))
+                    .collect::<Vec<_>>();
+                keyed.sort_by(|(a, _), (b, _)| {
+                    let order = column.compare(a, b);
+                    if descending { order.reverse() } else { order }
+                });
+                tracks = keyed.into_iter().map(|(_, row)| row).collect();
             }
+            engine::append_to_queue(app, &name, tracks).map(Into::into)
         }
-        "__setting_cycle" => {
+        "__setting_set" => {
             let key = words.get(1).copied().unwrap_or("");
+            let value = strip_n_words(command, 2).unwrap_or_default();
+            let value = value.trim();
+            if let Some(name) = key.strip_prefix("feature.") {
+                let feature = FeatureId::from_key(name)
+                    .ok_or_else(|| anyhow::anyhow!("unknown feature: {name}"))?;
+                let enabled = value
+                    .parse::<bool>()
+                    .with_context(|| format!("{key} expects true or false, got {value}"))?;
+                dispatch_app_command(app, AppCommand::ToggleFeature { feature, enabled })?;
+                return Ok(PaletteCommandResult::new(
+                    format!("{name}: {}", if enabled { "on" } else { "off" }),
+                    true,
+                ));
+            }
+            let message = apply_user_setting(&mut app.config, key, value)?;
+            save_user_setting(&app.db, key, value)?;
+            engine::apply_output_settings(app);
+            Ok(PaletteCommandResult::new(message, true))
+        }
+        "__root_set" => {
+            // `__root_set <key> <value> <path>`; the value may be empty.
+            let usage = || anyhow::anyhow!("usage: __root_set <key> <value> <path>");
+            let args = strip_n_words(command, 1).unwrap_or_default();
+            let (key, rest) = args.split_once(' ').ok_or_else(usage)?;
+            let (value, path) = rest.split_once(' ').ok_or_else(usage)?;
+            let root = app
+                .db
+                .get_library_root_by_path(path)?
+                .ok_or_else(|| anyhow::anyhow!("not a library root: {path}"))?;
+            let mut options = root.options;
+            let parse_bool = |value: &str| {
+                value
+                    .parse::<bool>()
+                    .with_context(|| format!("{key} expects true or false, got {value}"))
+            };
             match key {
-                "theme" => {
-                    let store = FsThemeStore::new(default_theme_dir());
-                    let themes = store.list().unwrap_or_default();
-                    if !themes.is_empty() {
-                        let current_idx = themes
-                            .iter()
-                            .position(|t| t == &app.config.ui.theme)
-                            .unwrap_or(0);
-                        let next_idx = (current_idx + 1) % themes.len();
-                        app.config.ui.theme = themes[next_idx].clone();
-                    }
-                    Ok(PaletteCommandResult::new(
-                        format!("Theme: {}", app.config.ui.theme),
-                        true,
-                    ))
-                }
-                "icon_pack" => {
-                    app.config.ui.icon_pack = match app.config.ui.icon_pack.as_str() {
-                        "nerd-font" => "ascii".to_string(),
-                        _ => "nerd-font".to_string(),
-                    };
-                    Ok(PaletteCommandResult::new(
-                        format!("Icons: {}", app.config.ui.icon_pack),
-                        true,
-                    ))
-                }
-                "pixel_art_cell_size" => {
-                    app.config.ui.pixel_art_cell_size = match app.config.ui.pixel_art_cell_size {
-                        1 => 2,
-                        2 => 3,
-                        3 => 4,
-                        _ => 1,
-                    };
-                    Ok(PaletteCommandResult::new(
-                        format!("Pixel art cell: {}", app.config.ui.pixel_art_cell_size),
-                        true,
-                    ))
+                "recursive" => options.recursive = parse_bool(value)?,
+                "include_extensions" => {
+                    options.include_extensions = RootOptions::parse_extensions(value)
                 }
-                "color_scheme" => {
-                    app.config.ui.color_scheme = match app.config.ui.color_scheme.as_str() {
-                        "dark" => "light".to_string(),
-                        _ => "dark".to_string(),
-                    };
-                    Ok(PaletteCommandResult::new(
-                        format!("Color scheme: {}", app.config.ui.color_scheme),
-                        true,
-                    ))
+                "exclude_extensions" => {
+                    options.exclude_extensions = RootOptions::parse_extensions(value)
                 }
-                _ => Ok(PaletteCommandResult::new(format!("Unknown setting: {key}"), false)),
+                "auto_artwork" => options.auto_artwork = parse_bool(value)?,
+                "paused" => return set_root_paused(app, path, parse_bool(value)?),
+                other => bail!("unknown folder option: {other}"),
             }
+            app.db.set_library_root_options(path, &options)?;
+            // Rescan so newly admitted files and folder artwork show up.
+            Ok(scan_in_background(
+                app,
+                path.to_string(),
+                format!("{path}: {key} = {value}"),
+            ))
         }
         other => Ok(PaletteCommandResult::new(
             format!("Unknown command: {other} (use 'help')"),
@@ -2365,56 +3749,312 @@ fn execute_ui_palette_command(
     }
 }
 
-fn execute_palette_feature_command(
+/// `__whole <album|artist> <play|queue> <path>`: plays, or appends to the
+/// queue, every track of the album or artist of the track at `path`.
+fn execute_whole_command(
     app: &mut BootstrappedApp,
+    command: &str,
     words: &[&str],
 ) -> Result<PaletteCommandResult> {
-    let sub = words.get(1).copied().unwrap_or("list");
-    match sub {
-        "list" => {
-            let enabled = FeatureId::ALL
-                .into_iter()
-                .filter(|f| app.feature_registry.is_enabled(*f))
-                .count();
-            Ok(PaletteCommandResult::new(
-                format!(
-                    "Features enabled: {enabled}/{} (use 'feature enable <name>' or 'feature disable <name>')",
-                    FeatureId::ALL.len()
-                ),
-                true,
-            ))
+    let path = strip_n_words(command, 3)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("internal error: __whole with no path"))?;
+    let track = app
+        .db
+        .get_track_by_path(&path)?
+        .ok_or_else(|| anyhow::anyhow!("not in the library: {path}"))?;
+    let title = track.title.clone().unwrap_or_else(|| path.clone());
+    let artist = track.artist.filter(|artist| !artist.is_empty());
+    let (name, tracks) = match words.get(1).copied() {
+        Some("album") => {
+            let Some(album) = track.album.filter(|album| !album.is_empty()) else {
+                return Ok(PaletteCommandResult::new(
+                    format!("{title} has no album"),
+                    false,
+                ));
+            };
+            let tracks = app
+                .db
+                .list_album_tracks(&album, artist.as_deref().unwrap_or(""))?;
+            (album, tracks)
         }
-        "enable" | "disable" => {
-            let raw = words
-                .get(2)
-                .copied()
-                .ok_or_else(|| anyhow::anyhow!("usage: feature {sub} <feature-name>"))?;
-            let feature = FeatureId::from_key(raw).ok_or_else(|| {
-                anyhow::anyhow!(
-                    "unknown feature: {raw} (valid: {})",
-                    FeatureId::ALL
-                        .into_iter()
-                        .map(|f| f.as_key())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
-            })?;
-            let enabled = sub == "enable";
-            let _events =
-                dispatch_app_command(app, AppCommand::ToggleFeature { feature, enabled })?;
-            Ok(PaletteCommandResult::new(
-                format!(
-                    "feature {} {}",
-                    feature,
-                    if enabled { "enabled" } else { "disabled" }
-                ),
-                true,
-            ))
+        Some("artist") => {
+            let Some(artist) = artist else {
+                return Ok(PaletteCommandResult::new(
+                    format!("{title} has no artist"),
+                    false,
+                ));
+            };
+            let tracks = app.db.list_tracks_by_artist(&artist)?;
+            (artist, tracks)
         }
-        _ => Ok(PaletteCommandResult::new(
-            "usage: feature [list|enable <name>|disable <name>]",
-            false,
-        )),
+        _ => bail!("internal error: __whole expects album or artist"),
+    };
+    match words.get(2).copied() {
+        Some("play") => engine::play_playlist(app, &name, tracks).map(Into::into),
+        Some("queue") => engine::append_to_queue(app, &name, tracks).map(Into::into),
+        _ => bail!("internal error: __whole expects play or queue"),
+    }
+}
+
+/// The split view's commands: choosing what it shows, and moving tracks into
+/// and out of it. `__queue_view` also opens the split, for the queue line in
+/// Now Playing.
+fn execute_split_command(
+    app: &mut BootstrappedApp,
+    command: &str,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    let arg = strip_n_words(command, 1).unwrap_or_default();
+    let playlist = app.split_playlist.clone();
+    match (words[0], playlist) {
+        ("__queue_view", _) => {
+            app.split_playlist = None;
+            let layout = PanelLayout {
+                show_split: true,
+                ..app.config.ui.layout
+            };
+            engine::save_setting(app, PANEL_LAYOUT_SETTING_KEY, serde_json::to_value(layout)?)?;
+            app.config.ui.layout = layout;
+            Ok(PaletteCommandResult::new("Split view: queue", true))
+        }
+        ("__split_source", _) => {
+            let name = if arg == "queue" || arg.is_empty() {
+                app.split_playlist = None;
+                "queue".to_string()
+            } else {
+                let name = match library_playlist(app, &arg)? {
+                    Some((name, _)) => name,
+                    None => app
+                        .db
+                        .list_playlists()?
+                        .into_iter()
+                        .find(|p| p.id == arg)
+                        .map(|p| p.name)
+                        .ok_or_else(|| anyhow::anyhow!("playlist not found: {arg}"))?,
+                };
+                app.split_playlist = Some(arg);
+                name
+            };
+            Ok(PaletteCommandResult::new(
+                format!("Split view: {name}"),
+                true,
+            ))
+        }
+        ("__split_add", None) => {
+            let row = app
+                .db
+                .get_track_by_path(&arg)?
+                .ok_or_else(|| anyhow::anyhow!("track not found by path: {arg}"))?;
+            let title = row.title.clone().unwrap_or_else(|| row.path.clone());
+            app.playback_state
+                .queue
+                .push(playback_queue_entry_from_track_row(row));
+            persist_playback_state(app)?;
+            Ok(PaletteCommandResult::new(format!("Queued: {title}"), true))
+        }
+        ("__split_move", None) => {
+            let (from, to) = parse_split_move(&arg)?;
+            engine::move_queue_entry(&mut app.playback_state, from, to)?;
+            persist_playback_state(app)?;
+            let entry = &app.playback_state.queue[to];
+            let title = entry.title.clone().unwrap_or_else(|| entry.path.clone());
+            Ok(PaletteCommandResult::new(
+                format!("Moved in queue: {title}"),
+                true,
+            ))
+        }
+        (_, None) => {
+            let index = arg
+                .parse::<usize>()
+                .with_context(|| format!("invalid queue index: {arg}"))?;
+            if index >= app.playback_state.queue.len() {
+                bail!("queue index out of range: {index}");
+            }
+            if app.playback_state.session.current_index == Some(index) {
+                bail!("the playing track cannot be removed from the queue");
+            }
+            let entry = app.playback_state.queue.remove(index);
+            adjust_playback_selection_after_queue_removal(&mut app.playback_state, index);
+            persist_playback_state(app)?;
+            let title = entry.title.unwrap_or(entry.path);
+            Ok(PaletteCommandResult::new(
+                format!("Removed from queue: {title}"),
+                true,
+            ))
+        }
+        (_, Some(id)) if SmartPlaylist::from_id(&id).is_some() => {
+            bail!("built-in playlists cannot be edited")
+        }
+        (_, Some(id)) if id.starts_with(FOLDER_PLAYLIST_PREFIX) => {
+            bail!("folder playlists follow the folder; copy one with c to edit it")
+        }
+        ("__split_add", Some(id)) => {
+            let row = app
+                .db
+                .get_track_by_path(&arg)?
+                .ok_or_else(|| anyhow::anyhow!("track not found by path: {arg}"))?;
+            app.db.append_track_to_playlist(&id, row.id)?;
+            let title = row.title.unwrap_or(row.path);
+            Ok(PaletteCommandResult::new(
+                format!("Added to playlist: {title}"),
+                true,
+            ))
+        }
+        ("__split_move", Some(id)) => {
+            let (from, to) = parse_split_move(&arg)?;
+            let entries = app.db.list_playlist_tracks(&id, PLAYLIST_PLAY_LIMIT)?;
+            let entry = |index: usize| {
+                entries
+                    .get(index)
+                    .ok_or_else(|| anyhow::anyhow!("playlist index out of range: {index}"))
+            };
+            let (moved, target) = (entry(from)?, entry(to)?);
+            app.db
+                .move_playlist_track(&id, moved.position, target.position)?;
+            let title = moved
+                .track
+                .title
+                .clone()
+                .unwrap_or_else(|| moved.track.path.clone());
+            Ok(PaletteCommandResult::new(
+                format!("Moved in playlist: {title}"),
+                true,
+            ))
+        }
+        (_, Some(id)) => {
+            let index = arg
+                .parse::<usize>()
+                .with_context(|| format!("invalid playlist index: {arg}"))?;
+            let entry = app
+                .db
+                .list_playlist_tracks(&id, PLAYLIST_PLAY_LIMIT)?
+                .into_iter()
+                .nth(index)
+                .ok_or_else(|| anyhow::anyhow!("playlist index out of range: {index}"))?;
+            app.db.remove_playlist_track_at(&id, entry.position)?;
+            let title = entry.track.title.unwrap_or(entry.track.path);
+            Ok(PaletteCommandResult::new(
+                format!("Removed from playlist: {title}"),
+                true,
+            ))
+        }
+    }
+}
+
+/// The `from to` row indices of a `__split_move` command.
+fn parse_split_move(arg: &str) -> Result<(usize, usize)> {
+    let mut indices = arg.split_whitespace().map(|raw| {
+        raw.parse::<usize>()
+            .with_context(|| format!("invalid split index: {raw}"))
+    });
+    match (indices.next(), indices.next()) {
+        (Some(from), Some(to)) => Ok((from?, to?)),
+        _ => bail!("usage: __split_move <from> <to>"),
+    }
+}
+
+fn execute_palette_party_command(
+    app: &mut BootstrappedApp,
+    command: &str,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    let passphrase = strip_n_words(command, 2)
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default();
+    match words.get(1).copied().unwrap_or("status") {
+        "status" => Ok(PaletteCommandResult::new(
+            if app.party.is_some() {
+                "Party mode is on; `party off <passphrase>` ends it"
+            } else {
+                "Party mode is off; `party on [passphrase]` starts it"
+            },
+            false,
+        )),
+        "on" => {
+            if app.party.is_some() {
+                return Ok(PaletteCommandResult::new("Party mode is already on", false));
+            }
+            let passphrase = if passphrase.is_empty() {
+                app.config.party.passphrase.clone()
+            } else {
+                passphrase
+            };
+            if passphrase.is_empty() {
+                bail!("party mode needs a passphrase: `party on <passphrase>` or set [party] passphrase");
+            }
+            app.party = Some(party::PartyMode::new(passphrase));
+            Ok(PaletteCommandResult::new(
+                "Party mode on: guests can add tracks to the queue; `party off <passphrase>` ends it",
+                true,
+            ))
+        }
+        "off" => match &app.party {
+            None => Ok(PaletteCommandResult::new("Party mode is off", false)),
+            Some(party) if !party.unlocks(&passphrase) => bail!("wrong passphrase"),
+            Some(_) => {
+                app.party = None;
+                Ok(PaletteCommandResult::new("Party mode off", true))
+            }
+        },
+        _ => Ok(PaletteCommandResult::new(
+            "usage: party [status | on [passphrase] | off <passphrase>]",
+            false,
+        )),
+    }
+}
+
+fn execute_palette_feature_command(
+    app: &mut BootstrappedApp,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    let sub = words.get(1).copied().unwrap_or("list");
+    match sub {
+        "list" => {
+            let enabled = FeatureId::ALL
+                .into_iter()
+                .filter(|f| app.feature_registry.is_enabled(*f))
+                .count();
+            Ok(PaletteCommandResult::new(
+                format!(
+                    "Features enabled: {enabled}/{} (use 'feature enable <name>' or 'feature disable <name>')",
+                    FeatureId::ALL.len()
+                ),
+                true,
+            ))
+        }
+        "enable" | "disable" => {
+            let raw = words
+                .get(2)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("usage: feature {sub} <feature-name>"))?;
+            let feature = FeatureId::from_key(raw).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "unknown feature: {raw} (valid: {})",
+                    FeatureId::ALL
+                        .into_iter()
+                        .map(|f| f.as_key())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+            let enabled = sub == "enable";
+            let _events =
+                dispatch_app_command(app, AppCommand::ToggleFeature { feature, enabled })?;
+            Ok(PaletteCommandResult::new(
+                format!(
+                    "feature {} {}",
+                    feature,
+                    if enabled { "enabled" } else { "disabled" }
+                ),
+                true,
+            ))
+        }
+        _ => Ok(PaletteCommandResult::new(
+            "usage: feature [list|enable <name>|disable <name>]",
+            false,
+        )),
     }
 }
 
@@ -2457,112 +4097,640 @@ fn execute_palette_scan_command(
                 true,
             ))
         }
+        "watched" => Ok(run_in_background(
+            app,
+            BackgroundJob::ReconcileWatched,
+            "Scanning watched folders...",
+        )),
         _ => Ok(PaletteCommandResult::new(
-            "usage: scan [roots [--prune] | path <dir> [--prune]]",
+            "usage: scan [roots [--prune] | path <dir> [--prune] | watched]",
             false,
         )),
     }
 }
 
-fn execute_palette_root_command(
+fn execute_palette_subsonic_command(
+    app: &mut BootstrappedApp,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    match words.get(1).copied().unwrap_or("status") {
+        "status" => Ok(PaletteCommandResult::new(subsonic_status(app)?, false)),
+        "sync" => {
+            if app.offline {
+                bail!("offline mode is on; the Subsonic server cannot be reached");
+            }
+            if !subsonic::is_configured(&app.config.subsonic) {
+                bail!("no Subsonic server configured; set url and username under [subsonic]");
+            }
+            let only_due = words.get(2) == Some(&"--due");
+            let message = format!("Syncing {}...", app.config.subsonic.name);
+            Ok(run_in_background(
+                app,
+                BackgroundJob::SubsonicSync { only_due },
+                message,
+            ))
+        }
+        _ => Ok(PaletteCommandResult::new(
+            "usage: subsonic [status | sync]",
+            false,
+        )),
+    }
+}
+
+fn execute_palette_podcast_command(
     app: &mut BootstrappedApp,
     command: &str,
     words: &[&str],
 ) -> Result<PaletteCommandResult> {
+    let arg = strip_n_words(command, 2)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
     let sub = words.get(1).copied().unwrap_or("list");
-    match sub {
-        "list" => {
-            let count = app.db.list_library_roots()?.len();
+    if matches!(sub, "subscribe" | "refresh" | "download") && app.offline {
+        bail!("offline mode is on; podcasts cannot be fetched");
+    }
+    match (sub, arg) {
+        ("list", _) => {
+            let podcasts = app.db.list_podcasts()?;
+            if podcasts.is_empty() {
+                return Ok(PaletteCommandResult::new(
+                    "No podcasts; subscribe with `podcast subscribe <feed-url>`",
+                    false,
+                ));
+            }
+            let names: Vec<String> = podcasts
+                .iter()
+                .map(|p| format!("{} ({} unplayed)", p.title, p.unplayed_count))
+                .collect();
             Ok(PaletteCommandResult::new(
-                format!("Library roots: {count}"),
-                true,
+                format!("Podcasts: {}", names.join(", ")),
+                false,
             ))
         }
-        "add" => {
-            let watched = words.contains(&"--watched") || words.contains(&"watched");
-            let path = strip_n_words(command, 2)
-                .map(|s| s.replace("--watched", "").replace(" watched", ""))
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .ok_or_else(|| anyhow::anyhow!("usage: root add <path> [--watched]"))?;
-            let row = app.db.upsert_library_root(&LibraryRoot { path, watched })?;
+        ("subscribe", Some(url)) => {
+            let message = format!("Subscribing to {url}...");
+            Ok(run_in_background(
+                app,
+                BackgroundJob::Podcast(podcast::Job::Subscribe { url }),
+                message,
+            ))
+        }
+        ("refresh", arg) => Ok(run_in_background(
+            app,
+            BackgroundJob::Podcast(podcast::Job::Refresh {
+                only_due: arg.as_deref() == Some("--due"),
+            }),
+            "Refreshing podcasts...",
+        )),
+        ("download", Some(id)) => {
+            let episode = app
+                .db
+                .get_podcast_episode(&id)?
+                .ok_or_else(|| anyhow::anyhow!("unknown episode: {id}"))?;
+            if episode.local_path.as_deref().is_some_and(|p| Path::new(p).is_file()) {
+                return Ok(PaletteCommandResult::new(
+                    format!("{} is already downloaded", episode.title),
+                    false,
+                ));
+            }
+            Ok(run_in_background(
+                app,
+                BackgroundJob::Podcast(podcast::Job::Download { episode_id: id }),
+                format!("Downloading {}...", episode.title),
+            ))
+        }
+        ("play", Some(id)) => engine::play_episode(app, &id).map(Into::into),
+        ("remove", Some(name)) => {
+            let podcast = podcast::find(&app.db, &name)?;
+            podcast::remove(&app.db, &podcast)?;
             Ok(PaletteCommandResult::new(
-                format!("Root saved: {} (watched={})", row.path, row.watched),
+                format!("Unsubscribed from {}", podcast.title),
                 true,
             ))
         }
         _ => Ok(PaletteCommandResult::new(
-            "usage: root [list | add <path> [--watched]]",
+            "usage: podcast [list | subscribe <feed-url> | refresh | download <episode> | play <episode> | remove <podcast>]",
             false,
         )),
     }
 }
 
-fn execute_palette_playlist_command(
+/// Bookmarks in the playing track, numbered from 1 in playback order.
+fn execute_palette_bookmark_command(
     app: &mut BootstrappedApp,
     command: &str,
     words: &[&str],
 ) -> Result<PaletteCommandResult> {
-    let sub = words.get(1).copied().unwrap_or("list");
-    match sub {
+    let Some(entry) = app.playback_state.current_entry() else {
+        return Ok(PaletteCommandResult::new("Nothing is playing", false));
+    };
+    let track_id = entry.track_id.0.to_string();
+    let title = entry.title.clone().unwrap_or_else(|| entry.path.clone());
+    let position_ms = app.playback_state.session.position_ms;
+    let bookmarks = app.db.list_track_bookmarks(&track_id)?;
+    let numbered = |arg: Option<&&str>| {
+        arg.and_then(|n| n.parse::<usize>().ok())
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| bookmarks.get(i))
+    };
+    match words.get(1).copied().unwrap_or("list") {
+        "list" if bookmarks.is_empty() => Ok(PaletteCommandResult::new(
+            format!("No bookmarks in {title}; add one with b or `bookmark add [label]`"),
+            false,
+        )),
         "list" => {
-            let count = app.db.list_playlists()?.len();
+            let listed: Vec<String> = bookmarks
+                .iter()
+                .enumerate()
+                .map(|(i, b)| format!("{}) {} {}", i + 1, format_clock(b.position_ms), b.label))
+                .collect();
             Ok(PaletteCommandResult::new(
-                format!("Playlists: {count}"),
-                true,
+                format!("Bookmarks: {}", listed.join(", ")),
+                false,
             ))
         }
-        "create" => {
-            let name = strip_n_words(command, 2)
-                .filter(|s| !s.trim().is_empty())
-                .ok_or_else(|| anyhow::anyhow!("usage: playlist create <name>"))?;
-            let id = app.db.create_playlist(name.trim())?;
+        "add" => {
+            let label = strip_n_words(command, 2)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| format!("Bookmark {}", bookmarks.len() + 1));
+            app.db
+                .add_track_bookmark(&track_id, position_ms as i64, &label)?;
             Ok(PaletteCommandResult::new(
-                format!("Playlist created: {} | {}", id, name.trim()),
-                true,
+                format!(
+                    "Bookmarked {} in {title}: {label}",
+                    format_clock(position_ms as i64)
+                ),
+                false,
             ))
         }
-        "rename" => {
-            let id = words
-                .get(2)
-                .copied()
-                .ok_or_else(|| anyhow::anyhow!("usage: playlist rename <id> <name>"))?;
-            let name = strip_n_words(command, 3)
-                .filter(|s| !s.trim().is_empty())
-                .ok_or_else(|| anyhow::anyhow!("usage: playlist rename <id> <name>"))?;
-            app.db.rename_playlist(id, name.trim())?;
+        "go" => {
+            let Some(bookmark) = numbered(words.get(2)).cloned() else {
+                bail!("usage: bookmark go <number>; `bookmark list` shows the numbers");
+            };
+            if app.playback_state.session.status == PlaybackStatus::Stopped {
+                return Ok(PaletteCommandResult::new(
+                    "Start the track to jump to a bookmark",
+                    false,
+                ));
+            }
+            engine::handle_playback_action(
+                app,
+                engine::Action::Seek {
+                    position_ms: bookmark.position_ms.max(0) as u64,
+                },
+            )?;
             Ok(PaletteCommandResult::new(
-                format!("Playlist renamed: {} | {}", id, name.trim()),
-                true,
+                format!(
+                    "Jumped to {} ({})",
+                    bookmark.label,
+                    format_clock(bookmark.position_ms)
+                ),
+                false,
             ))
         }
         "delete" => {
-            let id = words
-                .get(2)
-                .copied()
-                .ok_or_else(|| anyhow::anyhow!("usage: playlist delete <id>"))?;
-            app.db.delete_playlist(id)?;
+            let Some(bookmark) = numbered(words.get(2)) else {
+                bail!("usage: bookmark delete <number>; `bookmark list` shows the numbers");
+            };
+            app.db.delete_track_bookmark(&bookmark.id)?;
             Ok(PaletteCommandResult::new(
-                format!("Playlist deleted: {id}"),
-                true,
+                format!("Deleted bookmark {}", bookmark.label),
+                false,
             ))
         }
         _ => Ok(PaletteCommandResult::new(
-            "usage: playlist [list|create <name>|rename <id> <name>|delete <id>]",
+            "usage: bookmark [list | add [label] | go <number> | delete <number>]",
             false,
         )),
     }
 }
 
-fn strip_n_words(input: &str, n: usize) -> Option<String> {
-    let mut in_word = false;
-    let mut words_seen = 0usize;
-    for (idx, ch) in input.char_indices() {
-        if ch.is_whitespace() {
-            in_word = false;
-            continue;
+/// Shows or sets whether the playing track resumes where it stopped; `auto`
+/// goes back to the `[playback]` length and folder rules.
+fn execute_palette_remember_command(
+    app: &mut BootstrappedApp,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    let Some(entry) = app.playback_state.current_entry() else {
+        return Ok(PaletteCommandResult::new("Nothing is playing", false));
+    };
+    let track_id = entry.track_id.0.to_string();
+    let remember = match words.get(1).copied() {
+        None => {
+            let set = app.db.get_track_resume(&track_id)?.and_then(|r| r.remember);
+            let state = if engine::remembers_position(app, entry) {
+                "on"
+            } else {
+                "off"
+            };
+            let how = if set.is_some() {
+                "set for this track"
+            } else {
+                "automatic"
+            };
+            return Ok(PaletteCommandResult::new(
+                format!("Remember position: {state} ({how})"),
+                false,
+            ));
         }
-        if !in_word {
+        Some("on") => Some(true),
+        Some("off") => Some(false),
+        Some("auto") => None,
+        Some(_) => bail!("usage: remember [on | off | auto]"),
+    };
+    // A position save still queued would land after this one.
+    engine::flush_writes(app)?;
+    app.db.set_track_remember(&track_id, remember)?;
+    let position_ms = app.playback_state.session.position_ms as i64;
+    let on = engine::remembers_position(app, entry);
+    if on {
+        app.db.set_track_resume_position(&track_id, position_ms)?;
+    }
+    Ok(PaletteCommandResult::new(
+        format!("Remember position: {}", if on { "on" } else { "off" }),
+        true,
+    ))
+}
+
+/// Largest volume offset `gain` accepts either way, in dB.
+const MAX_VOLUME_OFFSET_DB: f32 = 12.0;
+
+/// `gain [track | queue] <dB | reset>`: a volume offset for the playing
+/// track, kept in the library, or for everything in the queue.
+fn execute_palette_gain_command(
+    app: &mut BootstrappedApp,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    const USAGE: &str = "usage: gain [track | queue] <dB | reset>";
+    let (scope, value) = match (words.get(1).copied(), words.get(2).copied()) {
+        (None, _) => {
+            let track_db =
+                engine::volume_offset_db(app) - app.playback_state.session.queue_volume_offset_db;
+            return Ok(PaletteCommandResult::new(
+                format!(
+                    "Volume offset: track {track_db:+.1} dB, queue {:+.1} dB",
+                    app.playback_state.session.queue_volume_offset_db
+                ),
+                false,
+            ));
+        }
+        (Some(scope @ ("track" | "queue")), Some(value)) => (scope, value),
+        (Some(value), None) => ("track", value),
+        _ => bail!(USAGE),
+    };
+    let offset_db = match value {
+        "reset" | "0" => 0.0,
+        _ => value
+            .trim_end_matches("dB")
+            .trim_end_matches("db")
+            .parse::<f32>()
+            .ok()
+            .filter(|db| db.is_finite())
+            .with_context(|| USAGE.to_string())?,
+    };
+    if offset_db.abs() > MAX_VOLUME_OFFSET_DB {
+        bail!("volume offsets go from -{MAX_VOLUME_OFFSET_DB} to +{MAX_VOLUME_OFFSET_DB} dB");
+    }
+    if scope == "queue" {
+        app.playback_state.session.queue_volume_offset_db = offset_db;
+        persist_playback_state(app)?;
+    } else {
+        let Some(entry) = app.playback_state.current_entry() else {
+            return Ok(PaletteCommandResult::new("Nothing is playing", false));
+        };
+        app.db
+            .set_track_volume_offset(&entry.track_id.0.to_string(), offset_db)?;
+    }
+    engine::apply_volume(app);
+    Ok(PaletteCommandResult::new(
+        format!("Volume offset for the {scope}: {offset_db:+.1} dB"),
+        true,
+    ))
+}
+
+/// `mm:ss`, with minutes going past 59 for long files.
+fn format_clock(ms: i64) -> String {
+    let secs = ms.max(0) / 1000;
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+fn execute_palette_root_command(
+    app: &mut BootstrappedApp,
+    command: &str,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    let sub = words.get(1).copied().unwrap_or("list");
+    match sub {
+        "list" => {
+            let count = app.db.list_library_roots()?.len();
+            Ok(PaletteCommandResult::new(
+                format!("Library roots: {count}"),
+                true,
+            ))
+        }
+        "add" => {
+            let watched = words.contains(&"--watched") || words.contains(&"watched");
+            let path = strip_n_words(command, 2)
+                .map(|s| s.replace("--watched", "").replace(" watched", ""))
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("usage: root add <path> [--watched]"))?;
+            let row = app.db.upsert_library_root(&LibraryRoot { path, watched })?;
+            Ok(PaletteCommandResult::new(
+                format!("Root saved: {} (watched={})", row.path, row.watched),
+                true,
+            ))
+        }
+        "pause" | "resume" => {
+            let path = strip_n_words(command, 2)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("usage: root {sub} <path>"))?;
+            set_root_paused(app, &path, sub == "pause")
+        }
+        _ => Ok(PaletteCommandResult::new(
+            "usage: root [list | add <path> [--watched] | pause <path> | resume <path>]",
+            false,
+        )),
+    }
+}
+
+/// Pausing only stops the watcher picking up changes; resuming rescans the
+/// folder so anything that changed in the meantime is imported.
+fn set_root_paused(
+    app: &mut BootstrappedApp,
+    path: &str,
+    paused: bool,
+) -> Result<PaletteCommandResult> {
+    if app.db.get_library_root_by_path(path)?.is_none() {
+        bail!("not a library root: {path}");
+    }
+    app.db.set_library_root_paused(path, paused)?;
+    if paused {
+        Ok(PaletteCommandResult::new(
+            format!("Paused watching {path}"),
+            true,
+        ))
+    } else {
+        Ok(scan_in_background(
+            app,
+            path.to_string(),
+            format!("Resumed watching {path}"),
+        ))
+    }
+}
+
+/// Queues `job`, showing `message` now and while it runs.
+fn run_in_background(
+    app: &mut BootstrappedApp,
+    job: BackgroundJob,
+    message: impl Into<String>,
+) -> PaletteCommandResult {
+    let message = message.into();
+    engine::queue_job(app, job, message.clone());
+    PaletteCommandResult::new(message, false)
+}
+
+/// Queues a scan of `path`, showing `status` until it starts.
+fn scan_in_background(
+    app: &mut BootstrappedApp,
+    path: String,
+    status: impl Into<String>,
+) -> PaletteCommandResult {
+    let message = format!("Scanning {path}...");
+    engine::queue_job(app, BackgroundJob::Scan(path), message);
+    PaletteCommandResult::new(status, false)
+}
+
+fn execute_palette_playlist_command(
+    app: &mut BootstrappedApp,
+    command: &str,
+    words: &[&str],
+) -> Result<PaletteCommandResult> {
+    let sub = words.get(1).copied().unwrap_or("list");
+    match sub {
+        "list" => {
+            let count = app.db.list_playlists()?.len();
+            Ok(PaletteCommandResult::new(
+                format!("Playlists: {count}"),
+                true,
+            ))
+        }
+        "create" => {
+            let name = strip_n_words(command, 2)
+                .filter(|s| !s.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("usage: playlist create <name>"))?;
+            let id = app.db.create_playlist(name.trim())?;
+            Ok(PaletteCommandResult::new(
+                format!("Playlist created: {} | {}", id, name.trim()),
+                true,
+            ))
+        }
+        "play" => {
+            let id = words
+                .get(2)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("usage: playlist play <id>"))?;
+            let (name, tracks) = playlist_tracks(app, id)?;
+            engine::play_playlist(app, &name, tracks).map(Into::into)
+        }
+        "copy" => {
+            let id = words
+                .get(2)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("usage: playlist copy <id>"))?;
+            let (name, tracks) = playlist_tracks(app, id)?;
+            let taken = app
+                .db
+                .list_playlists()?
+                .into_iter()
+                .map(|p| p.name.to_lowercase())
+                .collect::<HashSet<_>>();
+            let name = (1..)
+                .map(|n| {
+                    if n == 1 {
+                        name.clone()
+                    } else {
+                        format!("{name} ({n})")
+                    }
+                })
+                .find(|candidate| !taken.contains(&candidate.to_lowercase()))
+                .unwrap_or(name);
+            let new_id = app.db.create_playlist(&name)?;
+            for track in &tracks {
+                app.db.append_track_to_playlist(&new_id, track.id)?;
+            }
+            Ok(PaletteCommandResult::new(
+                format!("Playlist created: {name} ({} tracks)", tracks.len()),
+                true,
+            ))
+        }
+        "rename" | "delete"
+            if words
+                .get(2)
+                .is_some_and(|id| SmartPlaylist::from_id(id).is_some()) =>
+        {
+            bail!("built-in playlists cannot be renamed or deleted")
+        }
+        "rename" | "delete"
+            if words
+                .get(2)
+                .is_some_and(|id| id.starts_with(FOLDER_PLAYLIST_PREFIX)) =>
+        {
+            bail!("folder playlists cannot be renamed or deleted")
+        }
+        "rename" => {
+            let id = words
+                .get(2)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("usage: playlist rename <id> <name>"))?;
+            let name = strip_n_words(command, 3)
+                .filter(|s| !s.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("usage: playlist rename <id> <name>"))?;
+            app.db.rename_playlist(id, name.trim())?;
+            Ok(PaletteCommandResult::new(
+                format!("Playlist renamed: {} | {}", id, name.trim()),
+                true,
+            ))
+        }
+        "delete" => {
+            let id = words
+                .get(2)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("usage: playlist delete <id>"))?;
+            let deleted = app.db.take_playlist(id)?;
+            let message = format!("Playlist deleted: {} (u to undo)", deleted.playlist.name);
+            app.undo = Some(Undo::DeletedPlaylist(deleted));
+            Ok(PaletteCommandResult::new(message, true))
+        }
+        _ => Ok(PaletteCommandResult::new(
+            "usage: playlist [list|play <id>|create <name>|rename <id> <name>|delete <id>]",
+            false,
+        )),
+    }
+}
+
+fn execute_palette_undo_command(app: &mut BootstrappedApp) -> Result<PaletteCommandResult> {
+    let Some(undo) = app.undo.take() else {
+        return Ok(PaletteCommandResult::new("Nothing to undo", false));
+    };
+    match &undo {
+        Undo::DeletedPlaylist(deleted) => match app.db.restore_playlist(deleted) {
+            Ok(restored) => Ok(PaletteCommandResult::new(
+                format!(
+                    "Playlist restored: {} ({restored} of {} tracks)",
+                    deleted.playlist.name,
+                    deleted.entries.len()
+                ),
+                true,
+            )),
+            Err(err) => {
+                let name = deleted.playlist.name.clone();
+                app.undo = Some(undo);
+                Err(err).with_context(|| format!("failed to restore playlist {name}"))
+            }
+        },
+    }
+}
+
+/// The most tracks `playlist play` queues, matching the track list.
+const PLAYLIST_PLAY_LIMIT: usize = 5000;
+
+/// Folder playlists stand in for a library root: `folder:<root id>`.
+const FOLDER_PLAYLIST_PREFIX: &str = "folder:";
+
+/// How many tracks the browse pane's Recent list shows.
+const RECENT_TRACKS: usize = 20;
+
+/// The name and tracks of any playlist: one the user made, a built-in one,
+/// or a library folder.
+fn playlist_tracks(app: &BootstrappedApp, id: &str) -> Result<(String, Vec<TrackRow>)> {
+    if let Some(found) = library_playlist(app, id)? {
+        return Ok(found);
+    }
+    let playlist = app
+        .db
+        .list_playlists()?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| anyhow::anyhow!("unknown playlist: {id}"))?;
+    let tracks = app
+        .db
+        .list_playlist_tracks(id, PLAYLIST_PLAY_LIMIT)?
+        .into_iter()
+        .map(|entry| entry.track)
+        .collect();
+    Ok((playlist.name, tracks))
+}
+
+/// The name and tracks of a playlist the library keeps up to date: a
+/// built-in one or a folder's. `None` for playlists the user made.
+fn library_playlist(app: &BootstrappedApp, id: &str) -> Result<Option<(String, Vec<TrackRow>)>> {
+    if let Some(smart) = SmartPlaylist::from_id(id) {
+        let tracks = app
+            .db
+            .list_smart_playlist_tracks(smart, PLAYLIST_PLAY_LIMIT)?;
+        return Ok(Some((smart.name().to_string(), tracks)));
+    }
+    let Some(root_id) = id.strip_prefix(FOLDER_PLAYLIST_PREFIX) else {
+        return Ok(None);
+    };
+    let root = app
+        .db
+        .list_library_roots()?
+        .into_iter()
+        .find(|root| root.id == root_id)
+        .ok_or_else(|| anyhow::anyhow!("library folder not found: {root_id}"))?;
+    let tracks = app
+        .db
+        .list_tracks_by_prefix(&root.path, PLAYLIST_PLAY_LIMIT)?;
+    Ok(Some((folder_playlist_name(&root.path), tracks)))
+}
+
+/// A folder playlist is named after the folder, not its whole path.
+fn folder_playlist_name(path: &str) -> String {
+    Path::new(path).file_name().map_or_else(
+        || path.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Whether startup should reconcile any watched folders.
+fn has_watched_roots(app: &BootstrappedApp) -> bool {
+    app.feature_registry.is_enabled(FeatureId::WatchedFolders)
+        && or_default_logged(app.db.list_library_roots(), "failed to list library roots")
+            .iter()
+            .any(|root| root.watched && !root.paused)
+}
+
+/// Feeds are refreshed at startup unless offline mode keeps the network off.
+fn has_podcasts_to_refresh(app: &BootstrappedApp) -> bool {
+    !app.offline && !or_default_logged(app.db.list_podcasts(), "failed to list podcasts").is_empty()
+}
+
+/// "watched" or "manual", plus whether watching is paused or the folder is
+/// offline.
+fn root_state_label(row: &LibraryRootRow) -> String {
+    let mut label = if row.watched { "watched" } else { "manual" }.to_string();
+    if row.offline_since_ms.is_some() {
+        label.push_str(", offline");
+    }
+    if row.paused {
+        label.push_str(", paused");
+    }
+    label
+}
+
+fn strip_n_words(input: &str, n: usize) -> Option<String> {
+    let mut in_word = false;
+    let mut words_seen = 0usize;
+    for (idx, ch) in input.char_indices() {
+        if ch.is_whitespace() {
+            in_word = false;
+            continue;
+        }
+        if !in_word {
             in_word = true;
             if words_seen == n {
                 return Some(input[idx..].to_string());
@@ -2573,77 +4741,413 @@ fn strip_n_words(input: &str, n: usize) -> Option<String> {
     None
 }
 
-fn build_shell_snapshot(app: &BootstrappedApp) -> ShellSnapshot {
-    let stats = app.db.stats().unwrap_or_else(|err| {
-        eprintln!("warning: failed to load database stats: {err}");
-        app.report.stats.clone()
-    });
+/// Runs one snapshot query and records how long it took for the debug overlay.
+fn timed<T>(timings: &mut Vec<(String, Duration)>, query: &str, run: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = run();
+    timings.push((query.to_string(), started.elapsed()));
+    result
+}
 
-    let roots = app
-        .db
-        .list_library_roots()
-        .unwrap_or_default()
-        .into_iter()
-        .map(|row| ShellListItem {
-            id: row.id,
-            label: row.path,
-            detail: Some(if row.watched {
-                "watched".to_string()
-            } else {
-                "manual".to_string()
-            }),
-        })
-        .collect::<Vec<_>>();
+/// Track count, length and when the playlist last changed, e.g.
+/// `12 · 48m · Oct 3`. Dates from other years show the year instead of the
+/// day.
+fn playlist_detail(summary: &PlaylistSummaryRow, now_ms: i64) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let minutes = summary.duration_ms / 60_000;
+    let length = match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h {}m", minutes % 60),
+    };
+    let (year, month, day) = auric_library::podcast::civil_from_ms(summary.playlist.updated_at_ms);
+    let month = MONTHS[(month - 1).clamp(0, 11) as usize];
+    let date = if year == auric_library::podcast::civil_from_ms(now_ms).0 {
+        format!("{month} {day}")
+    } else {
+        format!("{month} {year}")
+    };
+    format!("{} · {length} · {date}", summary.track_count)
+}
 
-    let playlists = app
-        .db
-        .list_playlists()
-        .unwrap_or_default()
-        .into_iter()
-        .map(|row| ShellListItem {
-            id: row.id,
-            label: row.name,
-            detail: None,
-        })
-        .collect::<Vec<_>>();
+/// Publish date and length, e.g. `2026-10-01  1:02:03`.
+fn episode_detail(episode: &PodcastEpisodeRow) -> String {
+    let date = episode
+        .published_at_ms
+        .map(auric_library::podcast::format_date)
+        .unwrap_or_default();
+    let Some(duration_ms) = episode.duration_ms else {
+        return date;
+    };
+    let secs = duration_ms.max(0) / 1000;
+    let length = if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    };
+    format!("{date}  {length}").trim().to_string()
+}
 
-    let track_limit = 5000;
-    let tracks = app
+fn shell_track_item(row: TrackRow) -> ShellTrackItem {
+    ShellTrackItem {
+        id: row.id.0.to_string(),
+        title: row.title.unwrap_or_else(|| "-".to_string()),
+        artist: row.artist.unwrap_or_else(|| "-".to_string()),
+        album: row.album.unwrap_or_else(|| "-".to_string()),
+        path: row.path,
+        duration_ms: row.duration_ms,
+        sample_rate: row.sample_rate,
+        channels: row.channels,
+        bit_depth: row.bit_depth,
+        bpm: None,
+    }
+}
+
+/// Every analyzed track's tempo, rounded for display, by track id.
+fn rounded_bpms(app: &BootstrappedApp) -> Result<HashMap<String, u16>> {
+    Ok(app
         .db
-        .list_tracks(track_limit)
-        .unwrap_or_default()
+        .track_bpms()?
         .into_iter()
-        .map(|row| ShellTrackItem {
-            id: row.id.0.to_string(),
-            title: row.title.unwrap_or_else(|| "-".to_string()),
-            artist: row.artist.unwrap_or_else(|| "-".to_string()),
-            album: row.album.unwrap_or_else(|| "-".to_string()),
-            path: row.path,
-            duration_ms: row.duration_ms,
-            sample_rate: row.sample_rate,
-            channels: row.channels,
-            bit_depth: row.bit_depth,
-        })
-        .collect::<Vec<_>>();
+        .map(|(id, bpm)| (id, bpm.round() as u16))
+        .collect())
+}
 
-    let feature_summary = FeatureId::ALL
-        .into_iter()
-        .map(|feature| {
-            (
-                feature.as_key().to_string(),
-                app.feature_registry.is_enabled(feature),
-            )
-        })
-        .collect::<Vec<_>>();
+/// Days and weeks the stats dashboard charts listening time over.
+const STATS_DAYS: usize = 14;
+const STATS_WEEKS: usize = 12;
+/// Formats beyond the most common ones are counted together as `Other`.
+const STATS_FORMATS: usize = 6;
+
+fn listening_stats(app: &BootstrappedApp) -> Result<ShellListeningStats> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64);
+    let ms = |periods: Vec<(String, i64)>| {
+        periods
+            .into_iter()
+            .map(|(start, ms)| (start, ms.max(0) as u64))
+            .collect()
+    };
+    let top = |group| -> Result<Vec<(String, u64)>> {
+        Ok(app
+            .db
+            .top_played(group, None, auric_ui::stats::TOP_COUNT)?
+            .into_iter()
+            .map(|tally: PlayTally| {
+                let name = match tally.detail.filter(|artist| !artist.is_empty()) {
+                    Some(artist) => format!("{} — {artist}", tally.name),
+                    None => tally.name,
+                };
+                (name, tally.plays.max(0) as u64)
+            })
+            .collect())
+    };
+    let (plays, listened_ms) = app.db.play_totals(None)?;
+    Ok(ShellListeningStats {
+        days: ms(app.db.listening_time(StatsPeriod::Day, STATS_DAYS, now)?),
+        weeks: ms(app.db.listening_time(StatsPeriod::Week, STATS_WEEKS, now)?),
+        top_artists: top(PlayGroup::Artist)?,
+        top_albums: top(PlayGroup::Album)?,
+        top_tracks: top(PlayGroup::Track)?,
+        plays: plays.max(0) as u64,
+        listened_ms: listened_ms.max(0) as u64,
+        formats: format_breakdown(app.db.track_formats()?),
+        added_by_month: app
+            .db
+            .tracks_added_by_month()?
+            .into_iter()
+            .map(|(month, added)| (month, added.max(0) as u64))
+            .collect(),
+    })
+}
 
-    let mouse_enabled =
-        app.feature_registry.is_enabled(FeatureId::Mouse) && app.config.features.mouse;
-    let feature_enabled_count = FeatureId::ALL
-        .into_iter()
-        .filter(|feature| app.feature_registry.is_enabled(*feature))
-        .count();
+/// Tracks listed under each issue of the health report.
+const HEALTH_TRACKS: usize = 200;
 
-    ShellSnapshot {
+fn health_report(app: &BootstrappedApp) -> Result<ShellHealthReport> {
+    let issues = HealthIssue::ALL
+        .into_iter()
+        .map(|issue| {
+            let tracks = app
+                .db
+                .tracks_with_health_issue(issue, HEALTH_TRACKS)?
+                .into_iter()
+                .map(|(track, found)| ShellHealthTrack {
+                    title: match (track.artist.as_deref(), track.title.as_deref()) {
+                        (Some(artist), Some(title)) if !artist.is_empty() => {
+                            format!("{artist} — {title}")
+                        }
+                        (_, Some(title)) if !title.is_empty() => title.to_string(),
+                        _ => Path::new(&track.path).file_name().map_or_else(
+                            || track.path.clone(),
+                            |name| name.to_string_lossy().into_owned(),
+                        ),
+                    },
+                    detail: found.as_ref().and_then(health::detail).unwrap_or_default(),
+                    path: track.path,
+                })
+                .collect();
+            Ok(ShellHealthIssue {
+                label: issue.label().to_string(),
+                count: app.db.count_health_issue(issue)?,
+                needs_check: issue.needs_check(),
+                fix: health::fix(issue).map(|(what, command)| (what.to_string(), command)),
+                tracks,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ShellHealthReport {
+        issues,
+        checked: app
+            .db
+            .last_health_check_ms()?
+            .map(auric_library::podcast::format_date)
+            .unwrap_or_default(),
+    })
+}
+
+/// Track counts by extension as format names, most common first: `flac`
+/// becomes `FLAC`, and the rare ones, streams and odd extensions are folded
+/// into `Other`.
+fn format_breakdown(by_extension: Vec<(String, i64)>) -> Vec<(String, u64)> {
+    let mut formats: Vec<(String, u64)> = Vec::new();
+    let mut other = 0;
+    for (extension, tracks) in by_extension {
+        let tracks = tracks.max(0) as u64;
+        let known = !extension.is_empty()
+            && extension.len() <= 5
+            && extension.chars().all(|c| c.is_ascii_alphanumeric());
+        if known && formats.len() < STATS_FORMATS {
+            formats.push((extension.to_ascii_uppercase(), tracks));
+        } else {
+            other += tracks;
+        }
+    }
+    if other > 0 {
+        formats.push(("Other".to_string(), other));
+    }
+    formats
+}
+
+fn with_bpm(mut item: ShellTrackItem, bpms: &HashMap<String, u16>) -> ShellTrackItem {
+    item.bpm = bpms.get(&item.id).copied();
+    item
+}
+
+fn shell_queue_item(entry: &PlaybackQueueEntry) -> ShellTrackItem {
+    ShellTrackItem {
+        id: entry.track_id.0.to_string(),
+        title: entry.title.clone().unwrap_or_else(|| "-".to_string()),
+        artist: entry.artist.clone().unwrap_or_else(|| "-".to_string()),
+        album: entry.album.clone().unwrap_or_else(|| "-".to_string()),
+        path: entry.path.clone(),
+        duration_ms: entry.duration_ms,
+        sample_rate: entry.sample_rate,
+        channels: entry.channels,
+        bit_depth: entry.bit_depth,
+        bpm: None,
+    }
+}
+
+/// The next two queue entries in the order they will play. Empty while
+/// shuffle or random album picks as it goes, or at the end of the queue.
+fn up_next(state: &PlaybackState) -> Vec<ShellTrackItem> {
+    let session = &state.session;
+    let len = state.queue.len();
+    let Some(current) = session
+        .current_index
+        .filter(|_| !session.shuffle && !session.random_album)
+    else {
+        return Vec::new();
+    };
+    let indices: Vec<usize> = match session.repeat {
+        RepeatMode::One => vec![current],
+        RepeatMode::All => (1..=2).map(|step| (current + step) % len).collect(),
+        RepeatMode::Off => (current + 1..len).take(2).collect(),
+    };
+    indices
+        .into_iter()
+        .filter_map(|index| state.queue.get(index))
+        .map(shell_queue_item)
+        .collect()
+}
+
+/// The title and tracks of the split view: the queue, or the chosen
+/// playlist while it still exists.
+fn split_view(app: &BootstrappedApp, playlists: &[ShellListItem]) -> (String, Vec<ShellTrackItem>) {
+    let playlist = app
+        .split_playlist
+        .as_deref()
+        .and_then(|id| playlists.iter().find(|p| p.id == id));
+    let Some(playlist) = playlist else {
+        let tracks = app
+            .playback_state
+            .queue
+            .iter()
+            .map(shell_queue_item)
+            .collect();
+        return ("Queue".to_string(), tracks);
+    };
+    let rows = playlist_tracks(app, &playlist.id).map(|(_, tracks)| tracks);
+    let tracks = or_default_logged(rows, "failed to list playlist tracks")
+        .into_iter()
+        .map(shell_track_item)
+        .collect();
+    (playlist.label.clone(), tracks)
+}
+
+fn build_shell_snapshot(app: &BootstrappedApp) -> ShellSnapshot {
+    let mut db_timings = Vec::new();
+    let stats = timed(&mut db_timings, "stats", || app.db.stats()).unwrap_or_else(|err| {
+        tracing::warn!(error = %err, "failed to load database stats");
+        app.report.stats.clone()
+    });
+
+    let root_rows = or_default_logged(
+        timed(&mut db_timings, "roots", || app.db.list_library_roots()),
+        "failed to list library roots",
+    );
+    let root_options = root_rows
+        .iter()
+        .map(|row| ShellRootOptions {
+            recursive: row.options.recursive,
+            include_extensions: row.options.include_extensions.join(","),
+            exclude_extensions: row.options.exclude_extensions.join(","),
+            auto_artwork: row.options.auto_artwork,
+            paused: row.paused,
+        })
+        .collect::<Vec<_>>();
+    let roots = root_rows
+        .into_iter()
+        .map(|row| ShellListItem {
+            detail: Some(root_state_label(&row)),
+            id: row.id,
+            label: row.path,
+        })
+        .collect::<Vec<_>>();
+
+    let mut playlists = timed(&mut db_timings, "smart playlists", || {
+        SmartPlaylist::ALL
+            .into_iter()
+            .map(|smart| {
+                let count = or_default_logged(
+                    app.db
+                        .count_smart_playlist_tracks(smart, PLAYLIST_PLAY_LIMIT),
+                    "failed to count a built-in playlist",
+                );
+                ShellListItem {
+                    id: smart.id().to_string(),
+                    label: smart.name().to_string(),
+                    detail: Some(count.to_string()),
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+    if app.config.library.folder_playlists {
+        playlists.extend(roots.iter().map(|root| {
+            let count = or_default_logged(
+                app.db.count_tracks_by_prefix(&root.label),
+                "failed to count a folder playlist",
+            );
+            ShellListItem {
+                id: format!("{FOLDER_PLAYLIST_PREFIX}{}", root.id),
+                label: folder_playlist_name(&root.label),
+                detail: Some(format!("{count} · folder")),
+            }
+        }));
+    }
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64);
+    playlists.extend(
+        or_default_logged(
+            timed(&mut db_timings, "playlists", || {
+                app.db.list_playlist_summaries()
+            }),
+            "failed to list playlists",
+        )
+        .into_iter()
+        .map(|row| ShellListItem {
+            detail: Some(playlist_detail(&row, now_ms)),
+            id: row.playlist.id,
+            label: row.playlist.name,
+        }),
+    );
+
+    let track_limit = 5000;
+    let bpms = or_default_logged(
+        timed(&mut db_timings, "bpm", || rounded_bpms(app)),
+        "failed to load track tempos",
+    );
+    let tracks = or_default_logged(
+        timed(&mut db_timings, "tracks", || {
+            app.db.list_tracks(track_limit)
+        }),
+        "failed to list tracks",
+    )
+    .into_iter()
+    .map(|row| with_bpm(shell_track_item(row), &bpms))
+    .collect::<Vec<_>>();
+    let (split_title, split_tracks) =
+        timed(&mut db_timings, "split", || split_view(app, &playlists));
+
+    let podcasts = or_default_logged(
+        timed(&mut db_timings, "podcasts", || app.db.list_podcasts()),
+        "failed to list podcasts",
+    )
+    .into_iter()
+    .map(|row| ShellPodcast {
+        detail: format!(
+            "{} episodes, {} unplayed",
+            row.episode_count, row.unplayed_count
+        ),
+        episodes: or_default_logged(
+            app.db.list_podcast_episodes(&row.id, 100),
+            "failed to list podcast episodes",
+        )
+        .into_iter()
+        .map(|episode| ShellEpisode {
+            detail: episode_detail(&episode),
+            downloaded: episode.local_path.is_some(),
+            id: episode.id,
+            title: episode.title,
+            position_ms: episode.position_ms.max(0) as u64,
+            played: episode.played,
+        })
+        .collect(),
+        id: row.id,
+        title: row.title,
+    })
+    .collect::<Vec<_>>();
+    let listening_stats = or_default_logged(
+        timed(&mut db_timings, "listening stats", || listening_stats(app)),
+        "failed to read listening stats",
+    );
+    let health = or_default_logged(
+        timed(&mut db_timings, "health report", || health_report(app)),
+        "failed to read the library health report",
+    );
+
+    let feature_summary = FeatureId::ALL
+        .into_iter()
+        .map(|feature| {
+            (
+                feature.as_key().to_string(),
+                app.feature_registry.is_enabled(feature),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mouse_enabled =
+        app.feature_registry.is_enabled(FeatureId::Mouse) && app.config.features.mouse;
+    let feature_enabled_count = FeatureId::ALL
+        .into_iter()
+        .filter(|feature| app.feature_registry.is_enabled(*feature))
+        .count();
+
+    ShellSnapshot {
         app_title: "auric".to_string(),
         theme_name: app.config.ui.theme.clone(),
         color_scheme: app.config.ui.color_scheme.clone(),
@@ -2656,11 +5160,16 @@ fn build_shell_snapshot(app: &BootstrappedApp) -> ShellSnapshot {
         } else {
             app.config.ui.artwork_display_filter.clone()
         },
+        show_artwork: app.config.ui.show_artwork,
         pixel_art_enabled: app.config.ui.pixel_art_artwork,
         pixel_art_cell_size: app.config.ui.pixel_art_cell_size,
         roots,
+        root_options,
         playlists,
         tracks,
+        podcasts,
+        listening_stats,
+        health,
         feature_summary,
         status_lines: vec![
             format!(
@@ -2712,12 +5221,16 @@ fn build_shell_snapshot(app: &BootstrappedApp) -> ShellSnapshot {
         now_playing_artwork: app
             .playback_state
             .current_entry()
+            .filter(|_| app.config.ui.show_artwork)
             .and_then(|e| {
                 let mut cache = app.artwork_cache.borrow_mut();
                 if cache.0 == e.path {
                     return cache.1.clone();
                 }
-                let data = app.db.get_artwork_data_for_track(&e.path).ok().flatten();
+                let data = or_default_logged(
+                    app.db.get_artwork_data_for_track(&e.path),
+                    "failed to load artwork",
+                );
                 *cache = (e.path.clone(), data.clone());
                 data
             }),
@@ -2727,18 +5240,65 @@ fn build_shell_snapshot(app: &BootstrappedApp) -> ShellSnapshot {
             .and_then(|e| e.duration_ms)
             .unwrap_or(0) as u64,
         now_playing_position_ms: app.playback_state.session.position_ms,
+        now_playing_remembers_position: app
+            .playback_state
+            .current_entry()
+            .is_some_and(|entry| engine::remembers_position(app, entry)),
         volume: app.playback_state.session.volume,
+        volume_offset_db: engine::volume_offset_db(app) + engine::normalization_db(app),
         shuffle: app.playback_state.session.shuffle,
+        random_album: app.playback_state.session.random_album,
+        radio: app.playback_state.session.radio,
+        consume: app.playback_state.session.consume,
+        party_mode: app.party.is_some(),
         repeat_mode: match app.playback_state.session.repeat {
             RepeatMode::Off => "off",
             RepeatMode::One => "one",
             RepeatMode::All => "all",
         }
         .to_string(),
-        artists: app.db.distinct_artists().unwrap_or_default(),
-        albums: app.db.distinct_albums().unwrap_or_default(),
+        artists: or_default_logged(
+            timed(&mut db_timings, "artists", || app.db.distinct_artists()),
+            "failed to list artists",
+        ),
+        albums: or_default_logged(
+            timed(&mut db_timings, "albums", || app.db.distinct_albums()),
+            "failed to list albums",
+        ),
+        recent_tracks: or_default_logged(
+            timed(&mut db_timings, "recent", || {
+                app.db.list_recently_played(RECENT_TRACKS)
+            }),
+            "failed to list recently played tracks",
+        )
+        .into_iter()
+        .map(shell_track_item)
+        .collect(),
         total_track_count: stats.track_count as usize,
+        total_duration_ms: stats.track_duration_ms.max(0) as u64,
+        split_source: app
+            .split_playlist
+            .clone()
+            .unwrap_or_else(|| "queue".to_string()),
+        split_title,
+        split_tracks,
         queue_length: app.playback_state.queue.len(),
+        up_next: up_next(&app.playback_state),
+        now_playing_chapters: app
+            .playback_state
+            .current_entry()
+            .map(|e| {
+                let mut cache = app.chapter_cache.borrow_mut();
+                if cache.0 != e.path {
+                    let chapters = cue::chapters_for(Path::new(&e.path))
+                        .into_iter()
+                        .map(|c| (c.start_ms, c.title.unwrap_or_default()))
+                        .collect();
+                    *cache = (e.path.clone(), chapters);
+                }
+                cache.1.clone()
+            })
+            .unwrap_or_default(),
         queue_position: app
             .playback_state
             .session
@@ -2750,10 +5310,38 @@ fn build_shell_snapshot(app: &BootstrappedApp) -> ShellSnapshot {
         setting_pixel_art: app.config.ui.pixel_art_artwork,
         setting_pixel_art_cell_size: app.config.ui.pixel_art_cell_size,
         setting_color_scheme: app.config.ui.color_scheme.clone(),
+        setting_color_mode: app.config.ui.color_mode.clone(),
+        setting_unicode_level: app.config.ui.unicode_level.clone(),
+        setting_screen_reader: app.config.ui.screen_reader.clone(),
+        setting_volume_step: app.config.playback.volume_step,
+        setting_folder_playlists: app.config.library.folder_playlists,
+        setting_resume_min_minutes: app.config.playback.resume_min_minutes,
+        setting_shuffle_strategy: app.config.playback.shuffle_strategy.clone(),
+        setting_shuffle_rating_weight: app.config.playback.shuffle_rating_weight,
+        setting_shuffle_recency_weight: app.config.playback.shuffle_recency_weight,
+        setting_normalization: app.config.playback.normalization.clone(),
+        setting_normalization_target_lufs: app.config.playback.normalization_target_lufs,
+        setting_trim_silence: app.config.playback.trim_silence,
+        setting_silence_threshold_db: app.config.playback.silence_threshold_db,
+        setting_silence_min_ms: app.config.playback.silence_min_ms,
+        setting_crossfeed: app.config.playback.crossfeed,
+        setting_max_volume: app.config.playback.max_volume,
+        setting_limiter: app.config.playback.limiter,
+        setting_footer_top: app.config.ui.footer_top.clone(),
+        setting_footer_bottom: app.config.ui.footer_bottom.clone(),
+        utc_offset_minutes: or_default_logged(
+            timed(&mut db_timings, "utc_offset", || {
+                app.db.local_utc_offset_minutes()
+            }),
+            "failed to read the local time zone",
+        ),
         available_themes: {
             let store = FsThemeStore::new(default_theme_dir());
             store.list().unwrap_or_default()
         },
+        panel_layout: app.config.ui.layout,
+        browser_places: load_browser_places(&app.db),
+        db_timings,
     }
 }
 
@@ -2770,6 +5358,9 @@ fn parse_u16_arg(raw: &str, flag: &str) -> Result<u16> {
 fn print_bootstrap_report(report: &BootstrapReport) {
     println!("auric bootstrap ready");
     println!("  config: {}", report.config_path.display());
+    if !report.profile.is_empty() {
+        println!("  profile: {}", report.profile);
+    }
     if let Some(path) = &report.db_path {
         println!("  db: {}", path.display());
     }
@@ -2875,9 +5466,15 @@ fn parse_synchronous_mode(raw: &str) -> Result<SynchronousMode> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use auric_audio::player::PlaySpan;
     use serde_json::json;
     use tempfile::tempdir;
 
+    /// The oldest job a command queued, without its status line.
+    fn queued_job(app: &mut BootstrappedApp) -> Option<BackgroundJob> {
+        engine::next_job(app).map(|(job, _)| job)
+    }
+
     #[test]
     fn parses_default_config() {
         let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../config/default.toml");
@@ -2888,381 +5485,1750 @@ mod tests {
         assert!(!cfg.ui.pixel_art_artwork);
         assert_eq!(cfg.ui.pixel_art_cell_size, 2);
         assert_eq!(cfg.ui.icon_pack, "nerd-font");
+        assert_eq!(cfg.ui.color_mode, "auto");
+        assert_eq!(cfg.ui.unicode_level, "auto");
+        assert_eq!(cfg.ui.screen_reader, "off");
+        assert_eq!(cfg.ui.refresh_hz, 30);
+        assert_eq!(cfg.ui.footer_top, auric_ui::footer::DEFAULT_TOP);
+        assert_eq!(cfg.ui.footer_bottom, "{status}");
         assert!(cfg.features.metadata);
         assert!(!cfg.features.visualizer);
         assert_eq!(cfg.database.journal_mode, "wal");
     }
 
     #[test]
-    fn bootstrap_creates_database_and_seeds_settings() {
+    fn bootstrap_creates_database_and_seeds_settings() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!(
+                "[ui]\ntheme = \"auric-light\"\ncolor_scheme = \"light\"\nicon_pack = \"ascii\"\nicon_fallback = \"ascii\"\npreferred_terminal_font = \"FiraCode Nerd Font Mono\"\n\n[database]\npath = \"{}\"\njournal_mode = \"wal\"\nsynchronous = \"normal\"\n",
+                db_path.display()
+            ),
+        )
+        .unwrap();
+
+        let app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert!(db_path.exists());
+        assert_eq!(app.report.schema_version, 14);
+        assert_eq!(
+            app.db.get_setting_json("ui.theme").unwrap(),
+            Some(json!("auric-light"))
+        );
+        assert_eq!(
+            app.db.get_setting_json("ui.icon_pack").unwrap(),
+            Some(json!("ascii"))
+        );
+        assert_eq!(
+            app.db.get_setting_json("ui.pixel_art_artwork").unwrap(),
+            Some(json!(false))
+        );
+        assert_eq!(
+            app.db
+                .get_setting_json("feature.visualizer.enabled")
+                .unwrap(),
+            Some(json!(false))
+        );
+        assert!(!app.feature_registry.is_enabled(FeatureId::Visualizer));
+    }
+
+    #[test]
+    fn feature_toggle_dispatch_persists_state() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let events = dispatch_app_command(
+            &mut app,
+            AppCommand::ToggleFeature {
+                feature: FeatureId::Visualizer,
+                enabled: true,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert!(app.feature_registry.is_enabled(FeatureId::Visualizer));
+        assert_eq!(
+            app.db
+                .get_setting_json("feature.visualizer.enabled")
+                .unwrap(),
+            Some(json!(true))
+        );
+    }
+
+    #[test]
+    fn layout_save_persists_across_bootstrap() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!(
+                "[ui.layout]\nsidebar_percent = 30\n\n[database]\npath = \"{}\"\n",
+                db_path.display()
+            ),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(app.config.ui.layout.sidebar_percent, 30);
+        execute_ui_palette_command(
+            &mut app,
+            r#"__layout_save {"sidebar_percent":90,"now_playing_height":6,"show_sidebar":true,"show_now_playing":true,"show_spectrum":false}"#,
+        )
+        .unwrap();
+        drop(app);
+
+        let app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(app.config.ui.layout.sidebar_percent, 50);
+        assert_eq!(app.config.ui.layout.now_playing_height, 6);
+        assert!(!app.config.ui.layout.show_spectrum);
+    }
+
+    #[test]
+    fn settings_dialog_changes_persist_immediately() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!(
+                "[ui]\nicon_pack = \"nerd-font\"\n\n[database]\npath = \"{}\"\n",
+                db_path.display()
+            ),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(app.config.playback.volume_step, 5);
+        for command in [
+            "__setting_set volume_step 10",
+            "__setting_set icon_pack ascii",
+            "__setting_set preferred_terminal_font JetBrains Mono",
+            "__setting_set feature.visualizer true",
+            "__setting_set shuffle_strategy weighted",
+            "__setting_set crossfeed true",
+        ] {
+            let result = execute_ui_palette_command(&mut app, command).unwrap();
+            assert!(result.refresh_requested, "{command}");
+        }
+        assert!(execute_ui_palette_command(&mut app, "__setting_set volume_step lots").is_err());
+        assert!(execute_ui_palette_command(&mut app, "__setting_set bogus 1").is_err());
+        assert!(
+            execute_ui_palette_command(&mut app, "__setting_set shuffle_strategy smart").is_err()
+        );
+
+        let before = app.playback_state.session.volume;
+        app.playback_state.session.volume = 0.5;
+        engine::handle_playback_action(&mut app, engine::Action::VolumeUp).unwrap();
+        assert!((app.playback_state.session.volume - 0.6).abs() < 1e-6);
+        app.playback_state.session.volume = before;
+        drop(app);
+
+        let app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(app.config.playback.volume_step, 10);
+        assert_eq!(app.config.ui.icon_pack, "ascii");
+        assert_eq!(app.config.ui.preferred_terminal_font, "JetBrains Mono");
+        assert_eq!(app.config.playback.shuffle_strategy, "weighted");
+        assert!(app.config.playback.crossfeed);
+        assert!(app.feature_registry.is_enabled(FeatureId::Visualizer));
+    }
+
+    #[test]
+    fn backups_restore_into_another_library() {
+        let dir = tempdir().unwrap();
+        let open = |name: &str| {
+            let cfg_path = dir.path().join(format!("{name}.toml"));
+            let db_path = dir.path().join(format!("{name}.db"));
+            fs::write(
+                &cfg_path,
+                format!("[database]\npath = \"{}\"\n", db_path.display()),
+            )
+            .unwrap();
+            bootstrap_from_config_path(&cfg_path).unwrap()
+        };
+        let mut home = open("home");
+        home.db
+            .upsert_library_root(&LibraryRoot {
+                path: "/srv/music".to_string(),
+                watched: true,
+            })
+            .unwrap();
+        home.db
+            .upsert_track(&TrackRecord {
+                id: TrackId(Uuid::new_v4()),
+                path: "/srv/music/a.flac".to_string(),
+                title: Some("A".to_string()),
+                artist: None,
+                album: None,
+                duration_ms: None,
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+                file_mtime_ms: None,
+            })
+            .unwrap();
+        let playlist = home.db.create_playlist("Favourites").unwrap();
+        let track = home
+            .db
+            .get_track_by_path("/srv/music/a.flac")
+            .unwrap()
+            .unwrap();
+        home.db
+            .append_track_to_playlist(&playlist, track.id)
+            .unwrap();
+
+        let backup = dir.path().join("library.json");
+        let command = format!("backup export {}", backup.display());
+        execute_ui_palette_command(&mut home, &command).unwrap();
+
+        let mut laptop = open("laptop");
+        let message = import_library_backup(
+            &mut laptop,
+            &backup.display().to_string(),
+            Some("/srv/music=/home/me/Music"),
+        )
+        .unwrap();
+        assert!(message.starts_with("Imported 1 folders, 1 tracks, 1 playlists (1 entries)"));
+        let restored = laptop
+            .db
+            .get_track_by_path("/home/me/Music/a.flac")
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.id, track.id);
+        assert_eq!(laptop.db.list_playlists().unwrap()[0].id, playlist);
+        assert!(import_library_backup(&mut laptop, "missing.json", None).is_err());
+    }
+
+    #[test]
+    fn folder_properties_are_saved_per_root() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let music = dir.path().join("my music");
+        fs::create_dir(&music).unwrap();
+        let path = music.display().to_string();
+        app.db
+            .upsert_library_root(&LibraryRoot {
+                path: path.clone(),
+                watched: true,
+            })
+            .unwrap();
+
+        for command in [
+            format!("__root_set recursive false {path}"),
+            format!("__root_set exclude_extensions mp3,ogg {path}"),
+            format!("__root_set include_extensions flac {path}"),
+            format!("__root_set include_extensions  {path}"),
+            format!("__root_set auto_artwork true {path}"),
+        ] {
+            execute_ui_palette_command(&mut app, &command).unwrap();
+            assert_eq!(
+                queued_job(&mut app),
+                Some(BackgroundJob::Scan(path.clone()))
+            );
+        }
+        execute_ui_palette_command(&mut app, &format!("__root_set paused true {path}")).unwrap();
+        assert_eq!(queued_job(&mut app), None);
+        let snapshot = build_shell_snapshot(&app);
+        assert_eq!(snapshot.roots[0].detail.as_deref(), Some("watched, paused"));
+        execute_ui_palette_command(&mut app, &format!("root resume {path}")).unwrap();
+        assert_eq!(
+            queued_job(&mut app),
+            Some(BackgroundJob::Scan(path.clone()))
+        );
+        assert!(
+            execute_ui_palette_command(&mut app, &format!("__root_set bogus 1 {path}")).is_err()
+        );
+        assert!(
+            execute_ui_palette_command(&mut app, "__root_set recursive true /nowhere").is_err()
+        );
+
+        let snapshot = build_shell_snapshot(&app);
+        assert_eq!(
+            snapshot.root_options,
+            [ShellRootOptions {
+                recursive: false,
+                include_extensions: String::new(),
+                exclude_extensions: "mp3,ogg".to_string(),
+                auto_artwork: true,
+                paused: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn startup_options_override_config_for_one_run() {
+        let cli = Cli::try_parse_from([
+            "auric",
+            "--theme",
+            "nord",
+            "--no-art",
+            "--db=alt.db",
+            "--offline",
+            "--config-dir",
+            "conf",
+            "--log-level",
+            "debug",
+            "maintenance",
+            "--no-vacuum",
+            "--offline",
+        ])
+        .unwrap();
+        // Everything after the command is the command's own.
+        assert_eq!(cli.args, ["maintenance", "--no-vacuum", "--offline"]);
+        assert_eq!(cli.log_level.as_deref(), Some("debug"));
+        let startup = cli.startup;
+        assert!(Cli::try_parse_from(["auric", "--db"]).is_err());
+        assert_eq!(
+            startup.config_path().unwrap(),
+            PathBuf::from("conf/default.toml")
+        );
+        assert_eq!(startup.db_path.as_deref(), Some("alt.db"));
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("override.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(&cfg_path, "[features]\nremote_metadata = true\n").unwrap();
+        let startup = StartupOptions {
+            db_path: Some(db_path.display().to_string()),
+            ..startup
+        };
+        let app = bootstrap_with_options(&cfg_path, &startup).unwrap();
+        assert_eq!(app.report.db_path.as_deref(), Some(db_path.as_path()));
+        assert_eq!(app.config.ui.theme, "nord");
+        assert!(!app.config.ui.show_artwork);
+        assert!(app.offline);
+        assert!(!app.feature_registry.is_enabled(FeatureId::RemoteMetadata));
+    }
+
+    #[test]
+    fn offline_config_keeps_network_features_off() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[network]\noffline = true\n[features]\np2p_sync = true\nremote_metadata = true\n[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert!(app.offline);
+        assert!(!app.feature_registry.is_enabled(FeatureId::P2PSync));
+        assert!(!app.feature_registry.is_enabled(FeatureId::RemoteMetadata));
+        assert!(app.feature_registry.is_enabled(FeatureId::Artwork));
+
+        let err = dispatch_app_command(
+            &mut app,
+            AppCommand::ToggleFeature {
+                feature: FeatureId::RemoteMetadata,
+                enabled: true,
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("offline mode"));
+        dispatch_app_command(
+            &mut app,
+            AppCommand::ToggleFeature {
+                feature: FeatureId::Visualizer,
+                enabled: true,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn profile_option_opens_the_profile_database() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("default.toml"), "[ui]\ntheme = \"nord\"\n").unwrap();
+        let created = profile::create(
+            &dir.path().join("default.toml"),
+            &dir.path().join("data"),
+            "Laptop",
+        )
+        .unwrap();
+
+        let config_dir = dir.path().display().to_string();
+        let cli = Cli::try_parse_from([
+            "auric",
+            "--config-dir",
+            &config_dir,
+            "--profile",
+            "laptop",
+            "doctor",
+        ])
+        .unwrap();
+        assert_eq!(cli.args, ["doctor"]);
+        let startup = cli.startup;
+        let config_path = startup.config_path().unwrap();
+        assert_eq!(config_path, created.config_path());
+
+        let app = bootstrap_with_options(&config_path, &startup).unwrap();
+        assert_eq!(app.report.profile, "Laptop");
+        assert_eq!(
+            app.report.db_path.as_deref(),
+            Some(dir.path().join("data/profiles/laptop/auric.db").as_path())
+        );
+        assert_eq!(app.config.ui.theme, "nord");
+
+        let unknown = StartupOptions {
+            profile: Some("Office".to_string()),
+            ..startup
+        };
+        assert!(unknown.config_path().is_err());
+    }
+
+    #[test]
+    fn legacy_database_moves_once_to_the_data_dir() {
+        let dir = tempdir().unwrap();
+        let legacy = dir.path().join("var/auric.db");
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        let open = |path: &Path| {
+            Database::open(&DatabaseOptions {
+                path: path.to_path_buf(),
+                ..DatabaseOptions::default()
+            })
+            .unwrap()
+        };
+        open(&legacy)
+            .set_setting_json("ui.theme", &json!("nord"))
+            .unwrap();
+        let target = dir.path().join("share/auric-tui/auric.db");
+
+        assert!(migrate_legacy_database(&legacy, &target).unwrap());
+        assert!(!legacy.exists());
+        assert!(!dir.path().join("var/auric.db-wal").exists());
+        assert_eq!(
+            open(&target).get_setting_json("ui.theme").unwrap(),
+            Some(json!("nord"))
+        );
+
+        fs::write(&legacy, b"stale").unwrap();
+        assert!(!migrate_legacy_database(&legacy, &target).unwrap());
+        assert_eq!(
+            open(&target).get_setting_json("ui.theme").unwrap(),
+            Some(json!("nord"))
+        );
+
+        let config = DatabaseConfig::default();
+        let options = config.to_options(dir.path()).unwrap();
+        assert_eq!(options.path, default_data_dir().join("auric.db"));
+    }
+
+    #[test]
+    fn a_failed_legacy_move_leaves_no_target() {
+        let dir = tempdir().unwrap();
+        let legacy = dir.path().join("auric.db");
+        fs::write(&legacy, b"not a database").unwrap();
+        let target = dir.path().join("data/auric.db");
+
+        assert!(migrate_legacy_database(&legacy, &target).is_err());
+        assert!(!target.exists());
+        assert!(!dir.path().join("data/auric.db.migrating").exists());
+        assert_eq!(fs::read(&legacy).unwrap(), b"not a database");
+    }
+
+    #[test]
+    fn log_level_option_is_removed_from_args() {
+        let mut args: Vec<String> = ["ui", "--log-level", "debug", "preview"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            take_option(&mut args, "--log-level").unwrap().as_deref(),
+            Some("debug")
+        );
+        assert_eq!(args, ["ui", "preview"]);
+
+        let mut args = vec!["--log-level=off".to_string(), "scan".to_string()];
+        assert_eq!(
+            take_option(&mut args, "--log-level").unwrap().as_deref(),
+            Some("off")
+        );
+        assert_eq!(args, ["scan"]);
+
+        let mut args = vec!["scan".to_string(), "--log-level".to_string()];
+        assert!(take_option(&mut args, "--log-level").is_err());
+        assert_eq!(take_option(&mut vec![], "--log-level").unwrap(), None);
+    }
+
+    #[test]
+    fn root_and_playlist_cli_commands_use_db() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+
+        let app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let music_dir = dir.path().join("music");
+        fs::create_dir(&music_dir).unwrap();
+        handle_root_command(
+            &app,
+            &[
+                String::from("add"),
+                music_dir.to_string_lossy().to_string(),
+                String::from("--watched"),
+            ],
+        )
+        .unwrap();
+        handle_playlist_command(
+            &app,
+            &[
+                String::from("create"),
+                String::from("Road"),
+                String::from("Trip"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(app.db.list_library_roots().unwrap().len(), 1);
+        let playlists = app.db.list_playlists().unwrap();
+        assert_eq!(playlists.len(), 1);
+        assert_eq!(playlists[0].name, "Road Trip");
+    }
+
+    #[test]
+    fn playback_queue_and_session_persist_across_bootstrap() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let track_a = TrackRecord {
+            id: TrackId(Uuid::new_v4()),
+            path: "/tmp/test-a.flac".to_string(),
+            title: Some("Track A".to_string()),
+            artist: Some("Artist".to_string()),
+            album: Some("Album".to_string()),
+            duration_ms: Some(120_000),
+            sample_rate: Some(96_000),
+            channels: Some(2),
+            bit_depth: Some(24),
+            file_mtime_ms: Some(1),
+        };
+        let track_b = TrackRecord {
+            id: TrackId(Uuid::new_v4()),
+            path: "/tmp/test-b.flac".to_string(),
+            title: Some("Track B".to_string()),
+            artist: Some("Artist".to_string()),
+            album: Some("Album".to_string()),
+            duration_ms: Some(150_000),
+            sample_rate: Some(44_100),
+            channels: Some(2),
+            bit_depth: Some(16),
+            file_mtime_ms: Some(2),
+        };
+        app.db.upsert_track(&track_a).unwrap();
+        app.db.upsert_track(&track_b).unwrap();
+
+        handle_playback_command(
+            &mut app,
+            &[
+                String::from("queue"),
+                String::from("add-path"),
+                track_a.path.clone(),
+            ],
+        )
+        .unwrap();
+        handle_playback_command(
+            &mut app,
+            &[
+                String::from("queue"),
+                String::from("add-path"),
+                track_b.path.clone(),
+            ],
+        )
+        .unwrap();
+        handle_playback_command(
+            &mut app,
+            &[
+                String::from("queue"),
+                String::from("play"),
+                String::from("1"),
+            ],
+        )
+        .unwrap();
+        let _ = dispatch_app_command(&mut app, AppCommand::SeekMillis(42_000)).unwrap();
+        let _ = dispatch_app_command(&mut app, AppCommand::SetVolume(0.65)).unwrap();
+        app.playback_state.session.repeat = RepeatMode::All;
+        persist_playback_state(&mut app).unwrap();
+        engine::flush_writes(&app).unwrap();
+
+        let app2 = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(app2.playback_state.queue.len(), 2);
+        assert_eq!(app2.playback_state.session.current_index, Some(1));
+        // Nothing is loaded yet, so it waits at the saved position for Space.
+        assert_eq!(app2.playback_state.session.status, PlaybackStatus::Stopped);
+        assert_eq!(app2.playback_state.session.position_ms, 42_000);
+        assert!((app2.playback_state.session.volume - 0.65).abs() < 0.0001);
+        assert_eq!(app2.playback_state.session.repeat, RepeatMode::All);
+        assert_eq!(
+            app2.playback_state
+                .current_entry()
+                .and_then(|e| e.title.as_deref()),
+            Some("Track B")
+        );
+    }
+
+    #[test]
+    fn playlist_tracks_can_be_edited_and_loaded_into_playback_queue() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let playlist_id = app.db.create_playlist("Queue Source").unwrap();
+        let tracks = [
+            TrackRecord {
+                id: TrackId(Uuid::new_v4()),
+                path: "/tmp/pl-a.flac".to_string(),
+                title: Some("PLA".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                duration_ms: Some(1000),
+                sample_rate: Some(44_100),
+                channels: Some(2),
+                bit_depth: Some(16),
+                file_mtime_ms: Some(1),
+            },
+            TrackRecord {
+                id: TrackId(Uuid::new_v4()),
+                path: "/tmp/pl-b.flac".to_string(),
+                title: Some("PLB".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                duration_ms: Some(2000),
+                sample_rate: Some(48_000),
+                channels: Some(2),
+                bit_depth: Some(24),
+                file_mtime_ms: Some(2),
+            },
+        ];
+        for track in &tracks {
+            app.db.upsert_track(track).unwrap();
+            handle_playlist_command(
+                &app,
+                &[
+                    String::from("add-track"),
+                    playlist_id.clone(),
+                    track.path.clone(),
+                ],
+            )
+            .unwrap();
+        }
+
+        let playlist_rows = app.db.list_playlist_tracks(&playlist_id, 10).unwrap();
+        assert_eq!(playlist_rows.len(), 2);
+        assert_eq!(playlist_rows[0].position, 0);
+        assert_eq!(playlist_rows[1].position, 1);
+
+        handle_playlist_command(
+            &app,
+            &[
+                String::from("move-track"),
+                playlist_id.clone(),
+                String::from("1"),
+                String::from("0"),
+            ],
+        )
+        .unwrap();
+        let playlist_rows = app.db.list_playlist_tracks(&playlist_id, 10).unwrap();
+        assert_eq!(playlist_rows[0].track.title.as_deref(), Some("PLB"));
+
+        handle_playlist_command(
+            &app,
+            &[
+                String::from("remove-track"),
+                playlist_id.clone(),
+                String::from("1"),
+            ],
+        )
+        .unwrap();
+        let playlist_rows = app.db.list_playlist_tracks(&playlist_id, 10).unwrap();
+        assert_eq!(playlist_rows.len(), 1);
+        assert_eq!(playlist_rows[0].position, 0);
+        assert_eq!(playlist_rows[0].track.title.as_deref(), Some("PLB"));
+
+        handle_playback_command(
+            &mut app,
+            &[
+                String::from("queue"),
+                String::from("load-playlist"),
+                playlist_id.clone(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(app.playback_state.queue.len(), 1);
+        assert_eq!(app.playback_state.queue[0].title.as_deref(), Some("PLB"));
+        assert_eq!(app.playback_state.session.current_index, None);
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Stopped);
+    }
+
+    #[test]
+    fn ui_palette_commands_mutate_state_and_return_refresh_hints() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("auric-test.db");
+        let cfg_path = dir.path().join("auric-test.toml");
+
+        fs::write(
+            &cfg_path,
+            format!("[database]\npath = \"{}\"\n", db_path.display()),
+        )
+        .unwrap();
+
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+
+        let result = execute_ui_palette_command(&mut app, "feature enable visualizer").unwrap();
+        assert!(result.refresh_requested);
+        assert!(app.feature_registry.is_enabled(FeatureId::Visualizer));
+
+        let result =
+            execute_ui_palette_command(&mut app, "root add /tmp/auric-palette --watched").unwrap();
+        assert!(result.refresh_requested);
+        assert_eq!(app.db.list_library_roots().unwrap().len(), 1);
+        assert!(result.status_message.contains("Root saved"));
+
+        let result =
+            execute_ui_palette_command(&mut app, "playlist create Late Night Mix").unwrap();
+        assert!(result.refresh_requested);
+        let playlists = app.db.list_playlists().unwrap();
+        assert_eq!(playlists.len(), 1);
+        assert_eq!(playlists[0].name, "Late Night Mix");
+
+        let command = format!("playlist delete {}", playlists[0].id);
+        let result = execute_ui_palette_command(&mut app, &command).unwrap();
+        assert_eq!(
+            result.status_message,
+            "Playlist deleted: Late Night Mix (u to undo)"
+        );
+        assert!(app.db.list_playlists().unwrap().is_empty());
+        let result = execute_ui_palette_command(&mut app, "undo").unwrap();
+        assert!(result.refresh_requested);
+        assert_eq!(app.db.list_playlists().unwrap(), playlists);
+        let result = execute_ui_palette_command(&mut app, "undo").unwrap();
+        assert_eq!(result.status_message, "Nothing to undo");
+
+        execute_ui_palette_command(&mut app, "__add_root /tmp/auric-places").unwrap();
+        let result = execute_ui_palette_command(
+            &mut app,
+            r#"__places_save {"bookmarks":["/mnt/nas"],"recent":["/tmp/auric-places"]}"#,
+        )
+        .unwrap();
+        assert_eq!(result.status_message, "Bookmarked as 1: /mnt/nas");
+        let places = build_shell_snapshot(&app).browser_places;
+        assert_eq!(places.recent, ["/tmp/auric-places"]);
+        let result =
+            execute_ui_palette_command(&mut app, r#"__places_save {"recent":[]}"#).unwrap();
+        assert_eq!(result.status_message, "Bookmark removed: /mnt/nas");
+    }
+
+    #[test]
+    fn split_view_moves_tracks_into_and_out_of_the_queue_and_playlists() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for (path, title) in [("/music/a.flac", "A"), ("/music/b.flac", "B")] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path: path.to_string(),
+                    title: Some(title.to_string()),
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+
+        let result = execute_ui_palette_command(&mut app, "__split_add /music/b.flac").unwrap();
+        assert_eq!(result.status_message, "Queued: B");
+        let snapshot = build_shell_snapshot(&app);
+        assert_eq!(
+            (
+                snapshot.split_source.as_str(),
+                snapshot.split_title.as_str()
+            ),
+            ("queue", "Queue")
+        );
+        assert_eq!(snapshot.split_tracks[0].path, "/music/b.flac");
+        execute_ui_palette_command(&mut app, "__split_add /music/a.flac").unwrap();
+        app.playback_state.session.current_index = Some(0);
+        let result = execute_ui_palette_command(&mut app, "__split_move 1 0").unwrap();
+        assert_eq!(result.status_message, "Moved in queue: A");
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+        let titles = |app: &BootstrappedApp| {
+            build_shell_snapshot(app)
+                .split_tracks
+                .into_iter()
+                .map(|t| t.title)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(&app), ["A", "B"]);
+        assert!(execute_ui_palette_command(&mut app, "__split_move 0 2").is_err());
+        execute_ui_palette_command(&mut app, "__split_remove 0").unwrap();
+        app.playback_state.session.current_index = None;
+        execute_ui_palette_command(&mut app, "__split_remove 0").unwrap();
+        assert!(app.playback_state.queue.is_empty());
+
+        let id = app.db.create_playlist("Mix").unwrap();
+        let result = execute_ui_palette_command(&mut app, &format!("__split_source {id}")).unwrap();
+        assert_eq!(result.status_message, "Split view: Mix");
+        execute_ui_palette_command(&mut app, "__split_add /music/a.flac").unwrap();
+        execute_ui_palette_command(&mut app, "__split_add /music/b.flac").unwrap();
+        execute_ui_palette_command(&mut app, "__split_move 0 1").unwrap();
+        assert_eq!(titles(&app), ["B", "A"]);
+        execute_ui_palette_command(&mut app, "__split_remove 0").unwrap();
+        let snapshot = build_shell_snapshot(&app);
+        assert_eq!(snapshot.split_title, "Mix");
+        assert_eq!(snapshot.split_tracks.len(), 1);
+        assert_eq!(snapshot.split_tracks[0].title, "A");
+
+        let smart = SmartPlaylist::AddedThisWeek.id();
+        execute_ui_palette_command(&mut app, &format!("__split_source {smart}")).unwrap();
+        assert_eq!(build_shell_snapshot(&app).split_tracks.len(), 2);
+        assert!(execute_ui_palette_command(&mut app, "__split_add /music/a.flac").is_err());
+    }
+
+    #[test]
+    fn folders_show_as_read_only_playlists_that_copy_into_real_ones() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("auric-test.db");
         let cfg_path = dir.path().join("auric-test.toml");
-
         fs::write(
             &cfg_path,
             format!(
-                "[ui]\ntheme = \"auric-light\"\ncolor_scheme = \"light\"\nicon_pack = \"ascii\"\nicon_fallback = \"ascii\"\npreferred_terminal_font = \"FiraCode Nerd Font Mono\"\n\n[database]\npath = \"{}\"\njournal_mode = \"wal\"\nsynchronous = \"normal\"\n",
-                db_path.display()
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
             ),
         )
         .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let root = app
+            .db
+            .upsert_library_root(&LibraryRoot {
+                path: "/music/Jazz".to_string(),
+                watched: false,
+            })
+            .unwrap();
+        for path in [
+            "/music/Jazz/a.flac",
+            "/music/Jazz/b.flac",
+            "/music/Rock/c.flac",
+        ] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path: path.to_string(),
+                    title: None,
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        let folder_id = format!("folder:{}", root.id);
+        let listed = |app: &BootstrappedApp| {
+            build_shell_snapshot(app)
+                .playlists
+                .into_iter()
+                .find(|p| p.id == folder_id)
+        };
+        assert!(listed(&app).is_none());
 
-        let app = bootstrap_from_config_path(&cfg_path).unwrap();
-        assert!(db_path.exists());
-        assert_eq!(app.report.schema_version, 2);
-        assert_eq!(
-            app.db.get_setting_json("ui.theme").unwrap(),
-            Some(json!("auric-light"))
-        );
-        assert_eq!(
-            app.db.get_setting_json("ui.icon_pack").unwrap(),
-            Some(json!("ascii"))
-        );
+        let result =
+            execute_ui_palette_command(&mut app, "__setting_set folder_playlists true").unwrap();
+        assert_eq!(result.status_message, "Folder playlists: on");
+        let folder = listed(&app).expect("folder playlist listed");
         assert_eq!(
-            app.db.get_setting_json("ui.pixel_art_artwork").unwrap(),
-            Some(json!(false))
+            (folder.label.as_str(), folder.detail.as_deref()),
+            ("Jazz", Some("2 · folder"))
         );
-        assert_eq!(
-            app.db
-                .get_setting_json("feature.visualizer.enabled")
-                .unwrap(),
-            Some(json!(false))
+        execute_ui_palette_command(&mut app, &format!("__split_source {folder_id}")).unwrap();
+        assert_eq!(build_shell_snapshot(&app).split_tracks.len(), 2);
+        assert!(execute_ui_palette_command(&mut app, "__split_add /music/Rock/c.flac").is_err());
+        assert!(
+            execute_ui_palette_command(&mut app, &format!("playlist delete {folder_id}")).is_err()
         );
-        assert!(!app.feature_registry.is_enabled(FeatureId::Visualizer));
+
+        for expected in [
+            "Playlist created: Jazz (2 tracks)",
+            "Playlist created: Jazz (2) (2 tracks)",
+        ] {
+            let result =
+                execute_ui_palette_command(&mut app, &format!("playlist copy {folder_id}"))
+                    .unwrap();
+            assert_eq!(result.status_message, expected);
+        }
+        let copy = app
+            .db
+            .list_playlists()
+            .unwrap()
+            .into_iter()
+            .find(|p| p.name == "Jazz")
+            .unwrap();
+        assert_eq!(app.db.playlist_track_count(&copy.id).unwrap(), 2);
     }
 
     #[test]
-    fn feature_toggle_dispatch_persists_state() {
+    fn whole_album_and_artist_play_or_queue_in_order() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("auric-test.db");
         let cfg_path = dir.path().join("auric-test.toml");
-
         fs::write(
             &cfg_path,
-            format!("[database]\npath = \"{}\"\n", db_path.display()),
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-
         let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
-        let events = dispatch_app_command(
+        let tracks = [
+            ("/music/Band/Later/CD2/01.flac", "Band", "Later"),
+            ("/music/Band/Later/CD1/02.flac", "Band", "Later"),
+            ("/music/Band/Later/CD1/01.flac", "Band", "Later"),
+            ("/music/Band/Early/01.flac", "Band", "Early"),
+            ("/music/Other/Later/01.flac", "Other", "Later"),
+        ];
+        for (path, artist, album) in tracks {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path: path.to_string(),
+                    title: Some(path.to_string()),
+                    artist: Some(artist.to_string()),
+                    album: Some(album.to_string()),
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        let queued = |app: &BootstrappedApp| {
+            app.playback_state
+                .queue
+                .iter()
+                .map(|e| e.path.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let result = execute_ui_palette_command(
             &mut app,
-            AppCommand::ToggleFeature {
-                feature: FeatureId::Visualizer,
-                enabled: true,
-            },
+            "__whole album play /music/Band/Later/CD2/01.flac",
         )
         .unwrap();
-
-        assert_eq!(events.len(), 2);
-        assert!(app.feature_registry.is_enabled(FeatureId::Visualizer));
+        assert!(
+            result.status_message.ends_with("(Later)"),
+            "{}",
+            result.status_message
+        );
         assert_eq!(
-            app.db
-                .get_setting_json("feature.visualizer.enabled")
-                .unwrap(),
-            Some(json!(true))
+            queued(&app),
+            [
+                "/music/Band/Later/CD1/01.flac",
+                "/music/Band/Later/CD1/02.flac",
+                "/music/Band/Later/CD2/01.flac"
+            ]
         );
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+
+        let result =
+            execute_ui_palette_command(&mut app, "__whole artist queue /music/Band/Early/01.flac")
+                .unwrap();
+        assert_eq!(result.status_message, "Queued 4 tracks (Band)");
+        assert_eq!(queued(&app).len(), 7);
+        assert_eq!(queued(&app)[3], "/music/Band/Early/01.flac");
+        assert_eq!(app.playback_state.session.current_index, Some(0));
     }
 
     #[test]
-    fn root_and_playlist_cli_commands_use_db() {
+    fn recently_played_tracks_replay_next_without_clearing_the_queue() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("auric-test.db");
         let cfg_path = dir.path().join("auric-test.toml");
-
         fs::write(
             &cfg_path,
-            format!("[database]\npath = \"{}\"\n", db_path.display()),
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let mut ids = Vec::new();
+        for path in ["/music/a.flac", "/music/b.flac", "/music/c.flac"] {
+            let id = TrackId(uuid::Uuid::new_v4());
+            app.db
+                .upsert_track(&TrackRecord {
+                    id,
+                    path: path.to_string(),
+                    title: None,
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+            ids.push(id);
+        }
+        app.db.record_track_play(&ids[2].0.to_string()).unwrap();
+        let recent = build_shell_snapshot(&app).recent_tracks;
+        assert_eq!(
+            recent.iter().map(|t| t.path.as_str()).collect::<Vec<_>>(),
+            ["/music/c.flac"]
+        );
 
-        let app = bootstrap_from_config_path(&cfg_path).unwrap();
-        let music_dir = dir.path().join("music");
-        fs::create_dir(&music_dir).unwrap();
-        handle_root_command(
-            &app,
-            &[
-                String::from("add"),
-                music_dir.to_string_lossy().to_string(),
-                String::from("--watched"),
-            ],
-        )
-        .unwrap();
-        handle_playlist_command(
-            &app,
-            &[
-                String::from("create"),
-                String::from("Road"),
-                String::from("Trip"),
-            ],
+        app.playback_state.queue = ["/music/a.flac", "/music/b.flac"]
+            .into_iter()
+            .map(|path| {
+                playback_queue_entry_from_track_row(
+                    app.db.get_track_by_path(path).unwrap().unwrap(),
+                )
+            })
+            .collect();
+        app.playback_state.session.current_index = Some(0);
+        execute_ui_palette_command(&mut app, "__play_now /music/c.flac").unwrap();
+        let queued = app
+            .playback_state
+            .queue
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(queued, ["/music/a.flac", "/music/c.flac", "/music/b.flac"]);
+        assert_eq!(app.playback_state.session.current_index, Some(1));
+        assert_eq!(app.playback_state.session.status, PlaybackStatus::Playing);
+    }
+
+    #[test]
+    fn bpm_analysis_runs_once_per_track_and_fills_the_column() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let broken = dir.path().join("broken.flac");
+        fs::write(&broken, b"not audio").unwrap();
+        for path in [broken.display().to_string(), "subsonic://42".to_string()] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path,
+                    title: None,
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
 
-        assert_eq!(app.db.list_library_roots().unwrap().len(), 1);
-        let playlists = app.db.list_playlists().unwrap();
-        assert_eq!(playlists.len(), 1);
-        assert_eq!(playlists[0].name, "Road Trip");
+        execute_ui_palette_command(&mut app, "analyze bpm").unwrap();
+        assert_eq!(
+            queued_job(&mut app),
+            Some(BackgroundJob::Analysis(analysis::Job::Bpm))
+        );
+        let message = analysis::Job::Bpm.run(&mut app.db, &|_| {}).unwrap();
+        assert!(message.contains("2 could not be analyzed"), "{message}");
+        let message = analysis::Job::Bpm.run(&mut app.db, &|_| {}).unwrap();
+        assert_eq!(message, "Every track already has a BPM");
+
+        let track = app
+            .db
+            .get_track_by_path(&broken.display().to_string())
+            .unwrap()
+            .unwrap();
+        app.db
+            .set_track_bpm(&track.id.0.to_string(), Some(127.6))
+            .unwrap();
+        let bpms = build_shell_snapshot(&app)
+            .tracks
+            .iter()
+            .map(|t| t.bpm)
+            .collect::<Vec<_>>();
+        assert_eq!(bpms, [Some(128), None]);
     }
 
     #[test]
-    fn playback_queue_and_session_persist_across_bootstrap() {
+    fn loudness_normalization_prefers_tags_and_stays_under_full_scale() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("auric-test.db");
         let cfg_path = dir.path().join("auric-test.toml");
-
         fs::write(
             &cfg_path,
-            format!("[database]\npath = \"{}\"\n", db_path.display()),
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-
         let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
-        let track_a = TrackRecord {
-            id: TrackId(Uuid::new_v4()),
-            path: "/tmp/test-a.flac".to_string(),
-            title: Some("Track A".to_string()),
-            artist: Some("Artist".to_string()),
-            album: Some("Album".to_string()),
-            duration_ms: Some(120_000),
-            sample_rate: Some(96_000),
-            channels: Some(2),
-            bit_depth: Some(24),
-            file_mtime_ms: Some(1),
+        for (path, duration_ms) in [
+            ("/music/loud.flac", 100_000),
+            ("/music/quiet.flac", 300_000),
+        ] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path: path.to_string(),
+                    title: None,
+                    artist: Some("Artist".to_string()),
+                    album: Some("Album".to_string()),
+                    duration_ms: Some(duration_ms),
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        let message = analysis::Job::Loudness.run(&mut app.db, &|_| {}).unwrap();
+        assert!(message.contains("2 could not be measured"), "{message}");
+
+        let loud = app
+            .db
+            .get_track_by_path("/music/loud.flac")
+            .unwrap()
+            .unwrap();
+        let quiet = app
+            .db
+            .get_track_by_path("/music/quiet.flac")
+            .unwrap()
+            .unwrap();
+        use auric_library::db::TrackLoudness;
+        let measured = |integrated_lufs, true_peak_db| TrackLoudness {
+            integrated_lufs,
+            true_peak_db,
+            replaygain_track_db: None,
+            replaygain_album_db: None,
         };
-        let track_b = TrackRecord {
-            id: TrackId(Uuid::new_v4()),
-            path: "/tmp/test-b.flac".to_string(),
-            title: Some("Track B".to_string()),
-            artist: Some("Artist".to_string()),
-            album: Some("Album".to_string()),
-            duration_ms: Some(150_000),
-            sample_rate: Some(44_100),
-            channels: Some(2),
-            bit_depth: Some(16),
-            file_mtime_ms: Some(2),
+        app.db
+            .set_track_loudness(&loud.id.0.to_string(), Some(&measured(-8.0, -0.2)))
+            .unwrap();
+        app.db
+            .set_track_loudness(&quiet.id.0.to_string(), Some(&measured(-28.0, -12.0)))
+            .unwrap();
+        app.playback_state.queue = vec![
+            playback_queue_entry_from_track_row(loud.clone()),
+            playback_queue_entry_from_track_row(quiet.clone()),
+        ];
+
+        app.playback_state.session.current_index = Some(0);
+        assert_eq!(engine::normalization_db(&app), -10.0);
+        app.playback_state.session.current_index = Some(1);
+        assert_eq!(engine::normalization_db(&app), 10.0);
+        assert_eq!(build_shell_snapshot(&app).volume_offset_db, 10.0);
+
+        // +14 dB would push the -12 dBTP peak past full scale.
+        execute_ui_palette_command(&mut app, "__setting_set normalization_target_lufs -14")
+            .unwrap();
+        assert_eq!(engine::normalization_db(&app), 12.0);
+
+        execute_ui_palette_command(&mut app, "__setting_set normalization album").unwrap();
+        let album_gain = engine::normalization_db(&app);
+        assert!((album_gain + 0.11).abs() < 0.01, "{album_gain}");
+
+        let tagged = TrackLoudness {
+            replaygain_track_db: Some(-3.0),
+            replaygain_album_db: Some(-5.0),
+            ..measured(-28.0, -12.0)
         };
-        app.db.upsert_track(&track_a).unwrap();
-        app.db.upsert_track(&track_b).unwrap();
+        app.db
+            .set_track_loudness(&quiet.id.0.to_string(), Some(&tagged))
+            .unwrap();
+        assert_eq!(engine::normalization_db(&app), -5.0 + 4.0);
+        execute_ui_palette_command(&mut app, "__setting_set normalization track").unwrap();
+        assert_eq!(engine::normalization_db(&app), -3.0 + 4.0);
+
+        execute_ui_palette_command(&mut app, "__setting_set normalization off").unwrap();
+        assert_eq!(engine::normalization_db(&app), 0.0);
+        assert!(execute_ui_palette_command(&mut app, "__setting_set normalization loud").is_err());
+        execute_ui_palette_command(&mut app, "analyze loudness").unwrap();
+        assert_eq!(
+            queued_job(&mut app),
+            Some(BackgroundJob::Analysis(analysis::Job::Loudness))
+        );
+    }
 
-        handle_playback_command(
-            &mut app,
-            &[
-                String::from("queue"),
-                String::from("add-path"),
-                track_a.path.clone(),
-            ],
+    #[test]
+    fn health_check_reports_unreadable_files_and_links_folder_covers() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-        handle_playback_command(
-            &mut app,
-            &[
-                String::from("queue"),
-                String::from("add-path"),
-                track_b.path.clone(),
-            ],
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let album = dir.path().join("Band").join("Record");
+        fs::create_dir_all(&album).unwrap();
+        fs::write(album.join("cover.jpg"), b"jpeg bytes").unwrap();
+        let local = [album.join("01.flac"), dir.path().join("loose.flac")];
+        for path in &local {
+            fs::write(path, b"not audio").unwrap();
+        }
+        let paths = local
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .chain([format!("{}song-1", subsonic::PATH_PREFIX)]);
+        for (i, path) in paths.enumerate() {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path,
+                    title: Some("Song".to_string()),
+                    artist: Some("Band".to_string()),
+                    album: Some("Record".to_string()),
+                    duration_ms: (i > 0).then_some(240_000),
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+
+        let report = build_shell_snapshot(&app).health;
+        assert!(report.checked.is_empty());
+        execute_ui_palette_command(&mut app, "health").unwrap();
+        assert_eq!(
+            queued_job(&mut app),
+            Some(BackgroundJob::Health(health::Job::Check))
+        );
+        let message = health::Job::Check.run(&mut app.db, &|_| {}).unwrap();
+        assert!(message.starts_with("Checked 2 tracks: "), "{message}");
+        assert!(message.contains("2 unreadable files"), "{message}");
+
+        let report = build_shell_snapshot(&app).health;
+        assert!(!report.checked.is_empty());
+        let counts = report
+            .issues
+            .iter()
+            .map(|i| (i.label.as_str(), i.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            [
+                ("Missing tags", 0),
+                ("Missing artwork", 2),
+                ("Zero duration", 1),
+                ("Unreadable files", 2),
+                ("Low bitrate", 0),
+            ]
+        );
+        let unreadable = &report.issues[3].tracks[0];
+        assert_eq!(
+            (unreadable.title.as_str(), unreadable.detail.as_str()),
+            ("Band — Song", "cannot be read")
+        );
+        assert_eq!(
+            report.issues[1].fix.as_ref().unwrap().1,
+            "health fix artwork"
+        );
+
+        execute_ui_palette_command(&mut app, "health fix artwork").unwrap();
+        assert_eq!(
+            queued_job(&mut app),
+            Some(BackgroundJob::Health(health::Job::FixArtwork))
+        );
+        let message = health::Job::FixArtwork.run(&mut app.db, &|_| {}).unwrap();
+        assert_eq!(message, "Linked folder covers to 1 tracks");
+        assert_eq!(
+            app.db
+                .count_health_issue(HealthIssue::MissingArtwork)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn year_in_review_is_shown_and_exported_for_a_range() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-        handle_playback_command(
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let mut ids = Vec::new();
+        for (path, title) in [("/music/a.flac", "Teardrop"), ("/music/b.flac", "Angel")] {
+            let id = TrackId(uuid::Uuid::new_v4());
+            app.db
+                .upsert_track(&TrackRecord {
+                    id,
+                    path: path.to_string(),
+                    title: Some(title.to_string()),
+                    artist: Some("Massive Attack".to_string()),
+                    album: Some("Mezzanine".to_string()),
+                    duration_ms: Some(300_000),
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+            ids.push(id);
+        }
+        for id in [&ids[0], &ids[0], &ids[1]] {
+            app.db.record_track_play(&id.0.to_string()).unwrap();
+        }
+        let today = app.db.local_today().unwrap();
+        let year: i64 = today[..4].parse().unwrap();
+
+        let shown = execute_ui_palette_command(&mut app, "review")
+            .unwrap()
+            .review
+            .unwrap();
+        assert_eq!(shown.range, year.to_string());
+        assert_eq!(
+            (shown.plays, shown.listened_ms, shown.tracks, shown.artists),
+            (3, 900_000, 2, 1)
+        );
+        assert_eq!(
+            shown.top_tracks[0],
+            ("Teardrop — Massive Attack".to_string(), 2)
+        );
+        assert_eq!(shown.top_month.as_ref().unwrap().1, 900_000);
+        assert_eq!(shown.previous, Some((year - 1).to_string()));
+        assert_eq!(shown.next, Some((year + 1).to_string()));
+        let month =
+            execute_ui_palette_command(&mut app, &format!("review {}", &today[..7])).unwrap();
+        assert_eq!(month.review.unwrap().plays, 3);
+        let earlier = execute_ui_palette_command(&mut app, "review 1999")
+            .unwrap()
+            .review
+            .unwrap();
+        assert_eq!((earlier.plays, earlier.top_month), (0, None));
+        assert!(execute_ui_palette_command(&mut app, "review 99").is_err());
+
+        let out = dir.path().join("my review.json");
+        let result = execute_ui_palette_command(
             &mut app,
-            &[
-                String::from("queue"),
-                String::from("play"),
-                String::from("1"),
-            ],
+            &format!("review export {} {}", year, out.display()),
         )
         .unwrap();
-        let _ = dispatch_app_command(&mut app, AppCommand::SeekMillis(42_000)).unwrap();
-        let _ = dispatch_app_command(&mut app, AppCommand::SetVolume(0.65)).unwrap();
-        app.playback_state.session.repeat = RepeatMode::All;
-        persist_playback_state(&mut app).unwrap();
-
-        let app2 = bootstrap_from_config_path(&cfg_path).unwrap();
-        assert_eq!(app2.playback_state.queue.len(), 2);
-        assert_eq!(app2.playback_state.session.current_index, Some(1));
-        assert_eq!(app2.playback_state.session.status, PlaybackStatus::Playing);
-        assert_eq!(app2.playback_state.session.position_ms, 42_000);
-        assert!((app2.playback_state.session.volume - 0.65).abs() < 0.0001);
-        assert_eq!(app2.playback_state.session.repeat, RepeatMode::All);
         assert_eq!(
-            app2.playback_state
-                .current_entry()
-                .and_then(|e| e.title.as_deref()),
-            Some("Track B")
+            result.status_message,
+            format!("Wrote your {year} in review to {}", out.display())
         );
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(value["plays"], 3);
+        assert_eq!(value["top_artists"][0]["name"], "Massive Attack");
+        let out = dir.path().join("review.txt");
+        execute_ui_palette_command(&mut app, &format!("review export {}", out.display())).unwrap();
+        let text = fs::read_to_string(&out).unwrap();
+        assert!(text.starts_with(&format!("Your {year} in music")), "{text}");
+        assert!(text.contains("3 plays, 15m listened"), "{text}");
     }
 
     #[test]
-    fn playback_transport_next_previous_updates_selection() {
+    fn listening_stats_are_built_from_plays_and_tracks() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("auric-test.db");
         let cfg_path = dir.path().join("auric-test.toml");
-
         fs::write(
             &cfg_path,
-            format!("[database]\npath = \"{}\"\n", db_path.display()),
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-
-        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
-        for i in 0..3 {
+        let app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let mut ids = Vec::new();
+        for path in [
+            "/music/a.flac",
+            "/music/b.flac",
+            "/music/c.mp3",
+            "/music/d.opus",
+            "/music/e.m4a",
+            "/music/f.ogg",
+            "/music/g.wav",
+            "/music/h.aiff",
+            "/music/i.some-tag",
+        ] {
+            let id = TrackId(uuid::Uuid::new_v4());
             app.db
                 .upsert_track(&TrackRecord {
-                    id: TrackId(Uuid::new_v4()),
-                    path: format!("/tmp/t-{i}.flac"),
-                    title: Some(format!("T{i}")),
-                    artist: Some("A".to_string()),
-                    album: Some("B".to_string()),
-                    duration_ms: Some(1000),
-                    sample_rate: Some(44_100),
-                    channels: Some(2),
-                    bit_depth: Some(16),
-                    file_mtime_ms: Some(i as i64),
+                    id,
+                    path: path.to_string(),
+                    title: Some("Song".to_string()),
+                    artist: Some("Band".to_string()),
+                    album: Some("Record".to_string()),
+                    duration_ms: Some(240_000),
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
                 })
                 .unwrap();
+            ids.push(id);
+        }
+        for id in [&ids[0], &ids[0], &ids[1]] {
+            app.db.record_track_play(&id.0.to_string()).unwrap();
         }
-        let rows = app.db.list_tracks_by_prefix("/tmp", 10).unwrap();
-        app.playback_state.queue = rows
-            .into_iter()
-            .map(playback_queue_entry_from_track_row)
-            .collect();
-        app.playback_state.session.current_index = Some(0);
-        app.playback_state.session.status = PlaybackStatus::Playing;
-        persist_playback_state(&mut app).unwrap();
 
-        dispatch_app_command(&mut app, AppCommand::Next).unwrap();
-        assert_eq!(app.playback_state.session.current_index, Some(1));
-        assert_eq!(app.playback_state.session.position_ms, 0);
+        let stats = build_shell_snapshot(&app).listening_stats;
+        assert_eq!((stats.plays, stats.listened_ms), (3, 720_000));
+        assert_eq!(stats.days.len(), STATS_DAYS);
+        assert_eq!(stats.days.last().unwrap().1, 720_000);
+        assert_eq!(stats.weeks.len(), STATS_WEEKS);
+        assert_eq!(stats.top_artists, [("Band".to_string(), 3)]);
+        assert_eq!(stats.top_albums, [("Record — Band".to_string(), 3)]);
+        assert_eq!(
+            stats.top_tracks,
+            [
+                ("Song — Band".to_string(), 2),
+                ("Song — Band".to_string(), 1)
+            ]
+        );
+        assert_eq!(stats.formats.len(), STATS_FORMATS + 1);
+        assert_eq!(stats.formats[0], ("FLAC".to_string(), 2));
+        assert_eq!(stats.formats.last().unwrap(), &("Other".to_string(), 2));
+        assert_eq!(
+            stats
+                .added_by_month
+                .iter()
+                .map(|(_, added)| added)
+                .sum::<u64>(),
+            9
+        );
+    }
 
-        app.playback_state.session.position_ms = 4_000;
-        persist_playback_state(&mut app).unwrap();
-        dispatch_app_command(&mut app, AppCommand::Previous).unwrap();
-        assert_eq!(app.playback_state.session.current_index, Some(1));
-        assert_eq!(app.playback_state.session.position_ms, 0);
+    #[test]
+    fn silence_trimming_skips_long_silence_found_at_the_current_threshold() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        let path = "/music/padded.flac";
+        app.db
+            .upsert_track(&TrackRecord {
+                id: TrackId(uuid::Uuid::new_v4()),
+                path: path.to_string(),
+                title: None,
+                artist: None,
+                album: None,
+                duration_ms: Some(200_000),
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+                file_mtime_ms: None,
+            })
+            .unwrap();
+        execute_ui_palette_command(&mut app, "analyze silence").unwrap();
+        let job = analysis::Job::Silence { threshold_db: -60 };
+        assert_eq!(queued_job(&mut app), Some(BackgroundJob::Analysis(job)));
+        let message = job.run(&mut app.db, &|_| {}).unwrap();
+        assert!(message.contains("1 could not be decoded"), "{message}");
+
+        let track = app.db.get_track_by_path(path).unwrap().unwrap();
+        let silence = auric_library::db::TrackSilence {
+            leading_ms: 2_500,
+            trailing_ms: 800,
+            duration_ms: 200_000,
+        };
+        app.db
+            .set_track_silence(&track.id.0.to_string(), -60, Some(&silence))
+            .unwrap();
+        app.playback_state.queue = vec![playback_queue_entry_from_track_row(track)];
+        app.playback_state.session.current_index = Some(0);
+        assert_eq!(
+            engine::trim_span(&app, "/music/other.flac"),
+            PlaySpan::default()
+        );
 
-        dispatch_app_command(&mut app, AppCommand::Previous).unwrap();
-        assert_eq!(app.playback_state.session.current_index, Some(0));
+        // The 800 ms tail is under the default one second.
+        let span = |start_ms, end_ms| PlaySpan { start_ms, end_ms };
+        assert_eq!(engine::trim_span(&app, path), span(2_500, None));
+        execute_ui_palette_command(&mut app, "__setting_set silence_min_ms 500").unwrap();
+        assert_eq!(engine::trim_span(&app, path), span(2_500, Some(199_200)));
 
-        dispatch_app_command(&mut app, AppCommand::Previous).unwrap();
-        assert_eq!(app.playback_state.session.current_index, Some(0));
+        // Silence found at another threshold does not count.
+        execute_ui_palette_command(&mut app, "__setting_set silence_threshold_db -50").unwrap();
+        assert_eq!(engine::trim_span(&app, path), PlaySpan::default());
+        execute_ui_palette_command(&mut app, "analyze silence").unwrap();
+        assert_eq!(
+            queued_job(&mut app),
+            Some(BackgroundJob::Analysis(analysis::Job::Silence {
+                threshold_db: -50
+            }))
+        );
+        execute_ui_palette_command(&mut app, "__setting_set silence_threshold_db -60").unwrap();
+
+        execute_ui_palette_command(&mut app, "__setting_set trim_silence false").unwrap();
+        assert_eq!(engine::trim_span(&app, path), PlaySpan::default());
     }
 
     #[test]
-    fn playlist_tracks_can_be_edited_and_loaded_into_playback_queue() {
+    fn queue_folder_appends_in_the_track_list_order() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("auric-test.db");
         let cfg_path = dir.path().join("auric-test.toml");
-
         fs::write(
             &cfg_path,
-            format!("[database]\npath = \"{}\"\n", db_path.display()),
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-
         let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
-        let playlist_id = app.db.create_playlist("Queue Source").unwrap();
-        let tracks = [
-            TrackRecord {
-                id: TrackId(Uuid::new_v4()),
-                path: "/tmp/pl-a.flac".to_string(),
-                title: Some("PLA".to_string()),
-                artist: Some("Artist".to_string()),
-                album: Some("Album".to_string()),
-                duration_ms: Some(1000),
-                sample_rate: Some(44_100),
-                channels: Some(2),
-                bit_depth: Some(16),
-                file_mtime_ms: Some(1),
-            },
-            TrackRecord {
-                id: TrackId(Uuid::new_v4()),
-                path: "/tmp/pl-b.flac".to_string(),
-                title: Some("PLB".to_string()),
-                artist: Some("Artist".to_string()),
-                album: Some("Album".to_string()),
-                duration_ms: Some(2000),
-                sample_rate: Some(48_000),
-                channels: Some(2),
-                bit_depth: Some(24),
-                file_mtime_ms: Some(2),
-            },
-        ];
-        for track in &tracks {
-            app.db.upsert_track(track).unwrap();
-            handle_playlist_command(
-                &app,
-                &[
-                    String::from("add-track"),
-                    playlist_id.clone(),
-                    track.path.clone(),
-                ],
-            )
+        let root = app
+            .db
+            .upsert_library_root(&LibraryRoot {
+                path: "/music/Jazz".to_string(),
+                watched: true,
+            })
             .unwrap();
+        for (path, title) in [
+            ("/music/Jazz/1.flac", "Blue"),
+            ("/music/Jazz/2.flac", "so What"),
+            ("/music/Rock/3.flac", "Zed"),
+        ] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path: path.to_string(),
+                    title: Some(title.to_string()),
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
         }
+        let titles = |app: &BootstrappedApp| {
+            app.playback_state
+                .queue
+                .iter()
+                .filter_map(|e| e.title.clone())
+                .collect::<Vec<_>>()
+        };
 
-        let playlist_rows = app.db.list_playlist_tracks(&playlist_id, 10).unwrap();
-        assert_eq!(playlist_rows.len(), 2);
-        assert_eq!(playlist_rows[0].position, 0);
-        assert_eq!(playlist_rows[1].position, 1);
+        execute_ui_palette_command(&mut app, &format!("__queue_folder {} Title desc", root.id))
+            .unwrap();
+        assert_eq!(titles(&app), ["so What", "Blue"]);
+        assert_eq!(app.playback_state.session.current_index, Some(0));
+        let result =
+            execute_ui_palette_command(&mut app, &format!("__queue_folder {} Title asc", root.id))
+                .unwrap();
+        assert_eq!(result.status_message, "Queued 2 tracks (Jazz)");
+        assert_eq!(titles(&app), ["so What", "Blue", "Blue", "so What"]);
+    }
 
-        handle_playlist_command(
-            &app,
-            &[
-                String::from("remove-track"),
-                playlist_id.clone(),
-                String::from("0"),
-            ],
-        )
-        .unwrap();
-        let playlist_rows = app.db.list_playlist_tracks(&playlist_id, 10).unwrap();
-        assert_eq!(playlist_rows.len(), 1);
-        assert_eq!(playlist_rows[0].position, 0);
-        assert_eq!(playlist_rows[0].track.title.as_deref(), Some("PLB"));
+    #[test]
+    fn playlist_detail_abbreviates_length_and_date() {
+        let day_ms = 86_400_000;
+        // 2026-10-03 and 2025-03-01, UTC.
+        let (this_year, last_year) = (20_729 * day_ms, 20_148 * day_ms);
+        let summary = |track_count, duration_ms, updated_at_ms| PlaylistSummaryRow {
+            playlist: auric_library::db::PlaylistRow {
+                id: "p".into(),
+                name: "Mix".into(),
+                created_at_ms: 0,
+                updated_at_ms,
+            },
+            track_count,
+            duration_ms,
+        };
+        let now = this_year + 5 * day_ms;
+        assert_eq!(
+            playlist_detail(&summary(12, 2_880_000, this_year), now),
+            "12 · 48m · Oct 3"
+        );
+        assert_eq!(
+            playlist_detail(&summary(40, 11_520_000, last_year), now),
+            "40 · 3h 12m · Mar 2025"
+        );
+        assert_eq!(
+            playlist_detail(&summary(0, 0, this_year), now),
+            "0 · 0m · Oct 3"
+        );
+    }
 
-        handle_playback_command(
-            &mut app,
-            &[
-                String::from("queue"),
-                String::from("load-playlist"),
-                playlist_id.clone(),
-            ],
+    #[test]
+    fn up_next_follows_play_order_and_repeat() {
+        let mut state = PlaybackState::default();
+        for title in ["a", "b", "c", "d"] {
+            state.queue.push(PlaybackQueueEntry {
+                track_id: TrackId(uuid::Uuid::new_v4()),
+                path: format!("/music/{title}.flac"),
+                title: Some(title.to_string()),
+                artist: None,
+                album: None,
+                duration_ms: None,
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+            });
+        }
+        let titles = |state: &PlaybackState| {
+            up_next(state)
+                .into_iter()
+                .map(|t| t.title)
+                .collect::<Vec<_>>()
+        };
+        assert!(titles(&state).is_empty());
+        state.session.current_index = Some(2);
+        assert_eq!(titles(&state), ["d"]);
+        state.session.repeat = RepeatMode::All;
+        assert_eq!(titles(&state), ["d", "a"]);
+        state.session.repeat = RepeatMode::One;
+        assert_eq!(titles(&state), ["c"]);
+        state.session.shuffle = true;
+        assert!(titles(&state).is_empty());
+
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-        assert_eq!(app.playback_state.queue.len(), 1);
-        assert_eq!(app.playback_state.queue[0].title.as_deref(), Some("PLB"));
-        assert_eq!(app.playback_state.session.current_index, None);
-        assert_eq!(app.playback_state.session.status, PlaybackStatus::Stopped);
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        app.split_playlist = Some(SmartPlaylist::MostPlayed.id().to_string());
+        execute_ui_palette_command(&mut app, "__queue_view").unwrap();
+        assert!(app.split_playlist.is_none());
+        assert!(app.config.ui.layout.show_split);
     }
 
     #[test]
-    fn ui_palette_commands_mutate_state_and_return_refresh_hints() {
+    fn exports_can_be_limited_to_a_folder() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("auric-test.db");
         let cfg_path = dir.path().join("auric-test.toml");
-
         fs::write(
             &cfg_path,
-            format!("[database]\npath = \"{}\"\n", db_path.display()),
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("auric.db").display()
+            ),
         )
         .unwrap();
-
         let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        for path in [
+            "/music/rock/a.flac",
+            "/music/rocksteady/b.flac",
+            "/music/rock/sub/c.flac",
+        ] {
+            app.db
+                .upsert_track(&TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path: path.to_string(),
+                    title: None,
+                    artist: None,
+                    album: None,
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
 
-        let result = execute_ui_palette_command(&mut app, "feature enable visualizer").unwrap();
-        assert!(result.refresh_requested);
-        assert!(app.feature_registry.is_enabled(FeatureId::Visualizer));
-
-        let result =
-            execute_ui_palette_command(&mut app, "root add /tmp/auric-palette --watched").unwrap();
-        assert!(result.refresh_requested);
-        assert_eq!(app.db.list_library_roots().unwrap().len(), 1);
-        assert!(result.status_message.contains("Root saved"));
-
-        let result =
-            execute_ui_palette_command(&mut app, "playlist create Late Night Mix").unwrap();
-        assert!(result.refresh_requested);
-        let playlists = app.db.list_playlists().unwrap();
-        assert_eq!(playlists.len(), 1);
-        assert_eq!(playlists[0].name, "Late Night Mix");
+        let out = dir.path().join("rock.json");
+        let message = export_table(
+            &app,
+            "tracks",
+            out.to_str().unwrap(),
+            None,
+            Some("/music/rock/"),
+        )
+        .unwrap();
+        assert!(message.starts_with("Exported 2 tracks"));
+        let exported: serde_json::Value = serde_json::from_slice(&fs::read(&out).unwrap()).unwrap();
+        assert_eq!(exported[1]["path"], "/music/rock/sub/c.flac");
+
+        let csv = dir.path().join("all.csv");
+        let command = format!("export playlists {}", csv.display());
+        execute_ui_palette_command(&mut app, &command).unwrap();
+        assert!(fs::read_to_string(&csv)
+            .unwrap()
+            .starts_with("playlist,position,path"));
+        assert!(export_table(&app, "tracks", "out.txt", None, None).is_err());
     }
 
     #[test]
@@ -3273,4 +7239,87 @@ mod tests {
         );
         assert_eq!(strip_n_words("feature list", 2), None);
     }
+
+    #[test]
+    fn party_mode_needs_the_passphrase_to_end_or_quit() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert!(execute_ui_palette_command(&mut app, "party on").is_err());
+        execute_ui_palette_command(&mut app, "party on disco").unwrap();
+        assert!(build_shell_snapshot(&app).party_mode);
+
+        let err = execute_ui_palette_command(&mut app, "root add /music").unwrap_err();
+        assert!(err.to_string().contains("Party mode"), "{err}");
+        assert!(execute_ui_palette_command(&mut app, "__party_quit nope").is_err());
+        assert!(execute_ui_palette_command(&mut app, "party off nope").is_err());
+        assert!(
+            execute_ui_palette_command(&mut app, "__party_quit disco")
+                .unwrap()
+                .quit_requested
+        );
+
+        execute_ui_palette_command(&mut app, "party off disco").unwrap();
+        assert!(app.party.is_none());
+        execute_ui_palette_command(&mut app, "root list").unwrap();
+    }
+
+    #[test]
+    fn gain_sets_track_and_queue_volume_offsets() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = bootstrap_from_config_path(&cfg_path).unwrap();
+        assert_eq!(
+            execute_ui_palette_command(&mut app, "gain 3")
+                .unwrap()
+                .status_message,
+            "Nothing is playing"
+        );
+        app.db
+            .upsert_track(&TrackRecord {
+                id: TrackId(Uuid::new_v4()),
+                path: "/music/live.flac".to_string(),
+                title: Some("Live".to_string()),
+                artist: None,
+                album: None,
+                duration_ms: None,
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+                file_mtime_ms: None,
+            })
+            .unwrap();
+        let track = app
+            .db
+            .get_track_by_path("/music/live.flac")
+            .unwrap()
+            .unwrap();
+        app.playback_state.queue = vec![engine::playback_queue_entry_from_track_row(track)];
+        app.playback_state.session.current_index = Some(0);
+
+        execute_ui_palette_command(&mut app, "gain 6").unwrap();
+        execute_ui_palette_command(&mut app, "gain queue -2dB").unwrap();
+        assert_eq!(build_shell_snapshot(&app).volume_offset_db, 4.0);
+        assert!(execute_ui_palette_command(&mut app, "gain track 20").is_err());
+        assert!(execute_ui_palette_command(&mut app, "gain track loud").is_err());
+        execute_ui_palette_command(&mut app, "gain track reset").unwrap();
+        assert_eq!(engine::volume_offset_db(&app), -2.0);
+        assert!((engine::offset_volume(0.5, 20.0) - 5.0).abs() < 1e-4);
+    }
 }
@@ -0,0 +1,184 @@
+use crate::LoggingConfig;
+use anyhow::{bail, Context, Result};
+use auric_ui::event_log::{EventLog, LogLevel};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::layer::{Context as LayerContext, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+const LOG_FILE_PREFIX: &str = "auric";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Parses a level name as accepted by `[logging] level` and `--log-level`.
+pub fn parse_level(raw: &str) -> Result<LevelFilter> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "off" => Ok(LevelFilter::OFF),
+        "error" => Ok(LevelFilter::ERROR),
+        "warn" | "warning" => Ok(LevelFilter::WARN),
+        "info" => Ok(LevelFilter::INFO),
+        "debug" => Ok(LevelFilter::DEBUG),
+        "trace" => Ok(LevelFilter::TRACE),
+        other => bail!("unsupported log level: {other} (expected off|error|warn|info|debug|trace)"),
+    }
+}
+
+/// Directory the log files go to: `[logging] directory`, resolved against the
+/// config file's directory when relative.
+pub fn log_directory(config: &LoggingConfig, config_path: &Path) -> PathBuf {
+    let config_dir = config_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let dir = PathBuf::from(&config.directory);
+    if dir.is_absolute() {
+        dir
+    } else {
+        config_dir.join(dir)
+    }
+}
+
+/// Warnings and errors from every crate, kept for the shell's log dialog.
+pub fn event_log() -> &'static EventLog {
+    static EVENT_LOG: OnceLock<EventLog> = OnceLock::new();
+    EVENT_LOG.get_or_init(EventLog::new)
+}
+
+/// Copies warning and error events into [`event_log`], independent of the
+/// file log level.
+struct EventLogLayer;
+
+impl<S: Subscriber> Layer<S> for EventLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let level = match *event.metadata().level() {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            _ => return,
+        };
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        event_log().push(level, message.0);
+    }
+}
+
+/// Formats an event as its message followed by ` key=value` fields.
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let fields = std::mem::take(&mut self.0);
+            let _ = write!(self.0, "{value:?}{fields}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.record_debug(field, &format_args!("{value}"));
+        } else {
+            let _ = write!(self.0, " {}={value}", field.name());
+        }
+    }
+}
+
+/// Installs the global subscriber: a daily-rotated log file at the level
+/// from `level_override` (the `--log-level` flag) or the config, plus the
+/// in-memory [`event_log`]. Returns the log directory, or `None` when file
+/// logging is off. If the log file cannot be opened the event log is still
+/// installed and the error returned.
+pub fn init(
+    config: &LoggingConfig,
+    config_path: &Path,
+    level_override: Option<&str>,
+) -> Result<Option<PathBuf>> {
+    let level = parse_level(level_override.unwrap_or(&config.level))?;
+    let dir = log_directory(config, config_path);
+    let appender = if level == LevelFilter::OFF {
+        Ok(None)
+    } else {
+        Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(config.max_files.max(1))
+            .build(&dir)
+            .map(Some)
+            .with_context(|| format!("failed to open log directory {}", dir.display()))
+    };
+    let (file_layer, result) = match appender {
+        Ok(Some(appender)) => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(appender)
+                    .with_filter(level),
+            ),
+            Ok(Some(dir)),
+        ),
+        Ok(None) => (None, Ok(None)),
+        Err(err) => (None, Err(err)),
+    };
+
+    // Another subscriber may already be installed (tests); keep it.
+    let _ = tracing_subscriber::registry()
+        .with(file_layer)
+        .with(EventLogLayer)
+        .try_init();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_parse_case_insensitively() {
+        assert_eq!(parse_level("WARN").unwrap(), LevelFilter::WARN);
+        assert_eq!(parse_level(" debug ").unwrap(), LevelFilter::DEBUG);
+        assert_eq!(parse_level("off").unwrap(), LevelFilter::OFF);
+        assert!(parse_level("loud").is_err());
+    }
+
+    #[test]
+    fn event_messages_include_fields() {
+        let subscriber = tracing_subscriber::registry().with(EventLogLayer);
+        let before = event_log().pushed();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("not captured");
+            tracing::warn!(path = "a.flac", "failed to read tags");
+        });
+        assert_eq!(event_log().pushed(), before + 1);
+        let last = event_log().entries().pop().unwrap();
+        assert_eq!(last.level, LogLevel::Warn);
+        assert_eq!(last.message, "failed to read tags path=a.flac");
+    }
+
+    #[test]
+    fn relative_log_directory_is_under_the_config_dir() {
+        let config = LoggingConfig::default();
+        assert_eq!(
+            log_directory(&config, Path::new("/etc/auric/config.toml")),
+            PathBuf::from("/etc/auric/logs")
+        );
+        assert_eq!(
+            log_directory(&config, Path::new("config.toml")),
+            PathBuf::from("./logs")
+        );
+
+        let absolute = LoggingConfig {
+            directory: "/var/log/auric".to_string(),
+            ..LoggingConfig::default()
+        };
+        assert_eq!(
+            log_directory(&absolute, Path::new("config.toml")),
+            PathBuf::from("/var/log/auric")
+        );
+    }
+}
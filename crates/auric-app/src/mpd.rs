@@ -0,0 +1,781 @@
+//! A subset of the MPD protocol, so MPD clients can drive the shell.
+//!
+//! Song positions in the queue double as song ids. Reads are answered from
+//! the app's [`PlaybackState`] through [`StateQuery`] closures the shell's
+//! poll loop runs; playback changes go through the same [`RemoteInbox`] as
+//! the remote-control socket so the shell stays in sync.
+
+use crate::remote::{self, StateQuery};
+use crate::MpdConfig;
+use anyhow::{Context, Result};
+use auric_core::{PlaybackQueueEntry, PlaybackState, PlaybackStatus, RepeatMode};
+use auric_ui::remote_inbox::RemoteInbox;
+use auric_ui::shell::KeyAction;
+use auric_ui::PlaybackAction;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const GREETING: &str = "OK MPD 0.23.0\n";
+/// How long a connection waits for the shell to perform an action.
+const HANDLE_TIMEOUT: Duration = Duration::from_secs(3);
+/// How often blocked reads and `idle` wake up to check for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Longest request line accepted.
+const MAX_LINE: usize = 64 * 1024;
+
+const ACK_ERROR_ARG: u32 = 2;
+const ACK_ERROR_UNKNOWN: u32 = 5;
+const ACK_ERROR_NO_EXIST: u32 = 50;
+const ACK_ERROR_SYSTEM: u32 = 52;
+
+const COMMANDS: [&str; 28] = [
+    "close",
+    "command_list_begin",
+    "command_list_end",
+    "command_list_ok_begin",
+    "commands",
+    "consume",
+    "currentsong",
+    "idle",
+    "next",
+    "noidle",
+    "notcommands",
+    "outputs",
+    "pause",
+    "ping",
+    "play",
+    "playid",
+    "playlistid",
+    "playlistinfo",
+    "plchanges",
+    "previous",
+    "random",
+    "seek",
+    "seekcur",
+    "seekid",
+    "setvol",
+    "stats",
+    "status",
+    "stop",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Ack {
+    code: u32,
+    message: String,
+}
+
+impl Ack {
+    fn new(code: u32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// What a connection needs to answer commands.
+#[derive(Clone)]
+struct Handler {
+    queries: Sender<StateQuery>,
+    inbox: RemoteInbox,
+}
+
+impl Handler {
+    fn query<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&PlaybackState) -> T + Send + 'static,
+    ) -> Result<T, Ack> {
+        remote::query_state(&self.queries, f)
+            .ok_or_else(|| Ack::new(ACK_ERROR_SYSTEM, "player did not respond"))
+    }
+
+    fn perform(&self, action: PlaybackAction) -> Result<String, Ack> {
+        match self
+            .inbox
+            .submit(KeyAction::Playback(action))
+            .recv_timeout(HANDLE_TIMEOUT)
+        {
+            Ok(Ok(_)) => Ok(String::new()),
+            Ok(Err(message)) => Err(Ack::new(ACK_ERROR_SYSTEM, message)),
+            Err(_) => Err(Ack::new(ACK_ERROR_SYSTEM, "player did not respond")),
+        }
+    }
+
+    /// Runs one command and returns its response body, without the final `OK`.
+    fn execute(&self, name: &str, args: &[String]) -> Result<String, Ack> {
+        match name {
+            "ping" => Ok(String::new()),
+            "commands" => Ok(COMMANDS.iter().map(|c| format!("command: {c}\n")).collect()),
+            "notcommands" => Ok(String::new()),
+            "outputs" => Ok("outputid: 0\noutputname: auric\noutputenabled: 1\n".to_string()),
+            "status" => self.query(format_status),
+            "stats" => self.query(|state| format!("songs: {}\n", state.queue.len())),
+            "currentsong" => {
+                self.query(
+                    |state| match (state.session.current_index, state.current_entry()) {
+                        (Some(pos), Some(entry)) => format_song(pos, entry),
+                        _ => String::new(),
+                    },
+                )
+            }
+            "playlistinfo" | "playlistid" => {
+                let only = args.first().map(|raw| parse_index(raw)).transpose()?;
+                self.query(move |state| match only {
+                    Some(pos) => state
+                        .queue
+                        .get(pos)
+                        .map(|entry| format_song(pos, entry))
+                        .ok_or_else(|| Ack::new(ACK_ERROR_NO_EXIST, "No such song")),
+                    None => Ok(format_queue(&state.queue)),
+                })?
+            }
+            // The whole queue is resent; clients only need it to be complete.
+            "plchanges" => self.query(|state| format_queue(&state.queue)),
+            "play" | "playid" => match args.first() {
+                Some(raw) => self.perform(PlaybackAction::PlayTrack {
+                    track_index: parse_index(raw)?,
+                }),
+                None => self.perform(PlaybackAction::Resume),
+            },
+            "pause" => match args.first().map(String::as_str) {
+                Some("1") => self.perform(PlaybackAction::Pause),
+                Some("0") => self.perform(PlaybackAction::Resume),
+                Some(other) => Err(Ack::new(
+                    ACK_ERROR_ARG,
+                    format!("Boolean (0/1) expected: {other}"),
+                )),
+                None => self.perform(PlaybackAction::TogglePause),
+            },
+            "stop" => self.perform(PlaybackAction::Stop),
+            "next" => self.perform(PlaybackAction::Next),
+            "previous" => self.perform(PlaybackAction::Previous),
+            "seekcur" => {
+                let raw = args
+                    .first()
+                    .ok_or_else(|| Ack::new(ACK_ERROR_ARG, "missing argument"))?;
+                let relative = raw.starts_with('+') || raw.starts_with('-');
+                let offset_ms = parse_millis(raw.trim_start_matches('+'))?;
+                let position_ms = if relative {
+                    let current = self.query(|state| state.session.position_ms as i64)?;
+                    (current + offset_ms).max(0) as u64
+                } else {
+                    offset_ms.max(0) as u64
+                };
+                self.perform(PlaybackAction::Seek { position_ms })
+            }
+            "seek" | "seekid" => {
+                let (Some(raw_pos), Some(raw_time)) = (args.first(), args.get(1)) else {
+                    return Err(Ack::new(ACK_ERROR_ARG, "missing argument"));
+                };
+                let pos = parse_index(raw_pos)?;
+                let position_ms = parse_millis(raw_time)?.max(0) as u64;
+                if self.query(|state| state.session.current_index)? != Some(pos) {
+                    self.perform(PlaybackAction::PlayTrack { track_index: pos })?;
+                }
+                self.perform(PlaybackAction::Seek { position_ms })
+            }
+            "setvol" => {
+                let raw = args
+                    .first()
+                    .ok_or_else(|| Ack::new(ACK_ERROR_ARG, "missing argument"))?;
+                let volume = raw
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|v| *v <= 100)
+                    .ok_or_else(|| Ack::new(ACK_ERROR_ARG, format!("Invalid volume: {raw}")))?;
+                self.perform(PlaybackAction::SetVolume {
+                    volume: f32::from(volume) / 100.0,
+                })
+            }
+            "random" => {
+                let wanted = match args.first().map(String::as_str) {
+                    Some("1") => true,
+                    Some("0") => false,
+                    _ => return Err(Ack::new(ACK_ERROR_ARG, "Boolean (0/1) expected")),
+                };
+                if self.query(|state| state.session.shuffle)? != wanted {
+                    self.perform(PlaybackAction::ToggleShuffle)?;
+                }
+                Ok(String::new())
+            }
+            "consume" => {
+                let wanted = match args.first().map(String::as_str) {
+                    Some("1") => true,
+                    Some("0") => false,
+                    _ => return Err(Ack::new(ACK_ERROR_ARG, "Boolean (0/1) expected")),
+                };
+                if self.query(|state| state.session.consume)? != wanted {
+                    self.perform(PlaybackAction::ToggleConsume)?;
+                }
+                Ok(String::new())
+            }
+            other => Err(Ack::new(
+                ACK_ERROR_UNKNOWN,
+                format!("unknown command \"{other}\""),
+            )),
+        }
+    }
+}
+
+fn parse_index(raw: &str) -> Result<usize, Ack> {
+    raw.parse()
+        .map_err(|_| Ack::new(ACK_ERROR_ARG, format!("Integer expected: {raw}")))
+}
+
+/// Seconds, possibly fractional, as milliseconds.
+fn parse_millis(raw: &str) -> Result<i64, Ack> {
+    raw.parse::<f64>()
+        .ok()
+        .filter(|s| s.is_finite())
+        .map(|s| (s * 1000.0) as i64)
+        .ok_or_else(|| Ack::new(ACK_ERROR_ARG, format!("Number expected: {raw}")))
+}
+
+/// Splits a request line into words, honouring double quotes and `\` escapes.
+fn tokenize(line: &str) -> Result<Vec<String>, Ack> {
+    let mut words = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut word = String::new();
+        if c == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => word.extend(chars.next()),
+                    Some(c) => word.push(c),
+                    None => return Err(Ack::new(ACK_ERROR_ARG, "Missing closing '\"'")),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+        }
+        words.push(word);
+    }
+    Ok(words)
+}
+
+fn playlist_version(queue: &[PlaybackQueueEntry]) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    queue.len().hash(&mut hasher);
+    for entry in queue {
+        entry.track_id.hash(&mut hasher);
+    }
+    // Clients treat 0 as "no playlist yet".
+    (hasher.finish() as u32).max(1)
+}
+
+fn format_status(state: &PlaybackState) -> String {
+    let session = &state.session;
+    let mut out = String::new();
+    let _ = writeln!(out, "volume: {}", (session.volume * 100.0).round() as u32);
+    let _ = writeln!(
+        out,
+        "repeat: {}",
+        u8::from(session.repeat != RepeatMode::Off)
+    );
+    let _ = writeln!(out, "random: {}", u8::from(session.shuffle));
+    let _ = writeln!(
+        out,
+        "single: {}",
+        u8::from(session.repeat == RepeatMode::One)
+    );
+    let _ = writeln!(out, "consume: {}", u8::from(session.consume));
+    let _ = writeln!(out, "playlist: {}", playlist_version(&state.queue));
+    let _ = writeln!(out, "playlistlength: {}", state.queue.len());
+    let label = match session.status {
+        PlaybackStatus::Playing => "play",
+        PlaybackStatus::Paused => "pause",
+        PlaybackStatus::Stopped => "stop",
+    };
+    let _ = writeln!(out, "state: {label}");
+    if let (Some(pos), Some(entry)) = (session.current_index, state.current_entry()) {
+        let _ = writeln!(out, "song: {pos}\nsongid: {pos}");
+        if session.status != PlaybackStatus::Stopped {
+            let elapsed = session.position_ms as f64 / 1000.0;
+            let duration = entry.duration_ms.unwrap_or(0).max(0) as f64 / 1000.0;
+            let _ = writeln!(out, "time: {}:{}", elapsed as u64, duration.round() as u64);
+            let _ = writeln!(out, "elapsed: {elapsed:.3}\nduration: {duration:.3}");
+        }
+        if pos + 1 < state.queue.len() {
+            let _ = writeln!(out, "nextsong: {0}\nnextsongid: {0}", pos + 1);
+        }
+    }
+    out
+}
+
+fn format_song(pos: usize, entry: &PlaybackQueueEntry) -> String {
+    let mut out = format!("file: {}\n", entry.path);
+    for (key, value) in [
+        ("Title", &entry.title),
+        ("Artist", &entry.artist),
+        ("Album", &entry.album),
+    ] {
+        if let Some(value) = value {
+            let _ = writeln!(out, "{key}: {value}");
+        }
+    }
+    if let Some(ms) = entry.duration_ms {
+        let _ = writeln!(out, "Time: {}", (ms.max(0) as f64 / 1000.0).round() as u64);
+        let _ = writeln!(out, "duration: {:.3}", ms.max(0) as f64 / 1000.0);
+    }
+    let _ = writeln!(out, "Pos: {pos}\nId: {pos}");
+    out
+}
+
+fn format_queue(queue: &[PlaybackQueueEntry]) -> String {
+    queue
+        .iter()
+        .enumerate()
+        .map(|(pos, entry)| format_song(pos, entry))
+        .collect()
+}
+
+/// The parts of the state each `idle` subsystem reports changes for.
+#[derive(Debug, Clone, PartialEq)]
+struct IdleFingerprint {
+    player: (PlaybackStatus, Option<usize>),
+    mixer: u32,
+    options: (bool, RepeatMode),
+    playlist: u32,
+}
+
+impl IdleFingerprint {
+    fn of(state: &PlaybackState) -> Self {
+        let session = &state.session;
+        Self {
+            player: (session.status, session.current_index),
+            mixer: (session.volume * 100.0).round() as u32,
+            options: (session.shuffle, session.repeat),
+            playlist: playlist_version(&state.queue),
+        }
+    }
+
+    fn changes(&self, newer: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.player != newer.player {
+            changed.push("player");
+        }
+        if self.mixer != newer.mixer {
+            changed.push("mixer");
+        }
+        if self.options != newer.options {
+            changed.push("options");
+        }
+        if self.playlist != newer.playlist {
+            changed.push("playlist");
+        }
+        changed
+    }
+}
+
+/// Reads newline-terminated requests, waking up every [`POLL_INTERVAL`].
+struct LineReader {
+    stream: TcpStream,
+    buf: Vec<u8>,
+}
+
+impl LineReader {
+    /// The next line, or `None` if nothing complete arrived before the timeout.
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        loop {
+            if let Some(end) = self.buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buf.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                return Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()));
+            }
+            if self.buf.len() > MAX_LINE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "request too long",
+                ));
+            }
+            let mut chunk = [0u8; 4096];
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+fn serve_connection(stream: TcpStream, handler: &Handler, stop: &AtomicBool) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = LineReader {
+        stream,
+        buf: Vec::new(),
+    };
+    writer.write_all(GREETING.as_bytes())?;
+
+    // Commands buffered between command_list_begin and command_list_end, and
+    // whether each should be followed by `list_OK`.
+    let mut command_list: Option<(Vec<String>, bool)> = None;
+    while !stop.load(Ordering::Relaxed) {
+        let Some(line) = reader.next_line()? else {
+            continue;
+        };
+        if let Some((lines, ok_each)) = command_list.as_mut() {
+            if line.trim() == "command_list_end" {
+                let (lines, ok_each) = (std::mem::take(lines), *ok_each);
+                command_list = None;
+                let mut response = String::new();
+                let mut failed = false;
+                for (index, line) in lines.iter().enumerate() {
+                    match run_line(handler, line) {
+                        Ok(Some(body)) => {
+                            response.push_str(&body);
+                            if ok_each {
+                                response.push_str("list_OK\n");
+                            }
+                        }
+                        Ok(None) => return Ok(()),
+                        Err((name, ack)) => {
+                            response.push_str(&format_ack(index, &name, &ack));
+                            failed = true;
+                            break;
+                        }
+                    }
+                }
+                if !failed {
+                    response.push_str("OK\n");
+                }
+                writer.write_all(response.as_bytes())?;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+        match line.trim() {
+            "command_list_begin" => command_list = Some((Vec::new(), false)),
+            "command_list_ok_begin" => command_list = Some((Vec::new(), true)),
+            // Only meaningful while idling; MPD ignores it otherwise.
+            "noidle" => {}
+            "idle" => writer.write_all(idle(handler, &mut reader, &[], stop)?.as_bytes())?,
+            trimmed if trimmed.starts_with("idle ") => {
+                let filter = tokenize(trimmed).unwrap_or_default();
+                writer.write_all(idle(handler, &mut reader, &filter[1..], stop)?.as_bytes())?;
+            }
+            _ => match run_line(handler, &line) {
+                Ok(Some(body)) => writer.write_all(format!("{body}OK\n").as_bytes())?,
+                Ok(None) => return Ok(()),
+                Err((name, ack)) => writer.write_all(format_ack(0, &name, &ack).as_bytes())?,
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Runs one request line. `Ok(None)` means the client asked to close.
+fn run_line(handler: &Handler, line: &str) -> Result<Option<String>, (String, Ack)> {
+    let words = tokenize(line).map_err(|ack| (String::new(), ack))?;
+    let Some((name, args)) = words.split_first() else {
+        return Err((
+            String::new(),
+            Ack::new(ACK_ERROR_UNKNOWN, "No command given"),
+        ));
+    };
+    if name == "close" {
+        return Ok(None);
+    }
+    handler
+        .execute(name, args)
+        .map(Some)
+        .map_err(|ack| (name.clone(), ack))
+}
+
+fn format_ack(index: usize, command: &str, ack: &Ack) -> String {
+    format!("ACK [{}@{index}] {{{command}}} {}\n", ack.code, ack.message)
+}
+
+/// Blocks until one of the `subsystems` (all when empty) changes or the client
+/// sends `noidle`, then returns the response.
+fn idle(
+    handler: &Handler,
+    reader: &mut LineReader,
+    subsystems: &[String],
+    stop: &AtomicBool,
+) -> io::Result<String> {
+    let fingerprint = |handler: &Handler| handler.query(IdleFingerprint::of).ok();
+    let mut last = fingerprint(handler);
+    while !stop.load(Ordering::Relaxed) {
+        if let Some(line) = reader.next_line()? {
+            if line.trim() == "noidle" {
+                return Ok("OK\n".to_string());
+            }
+            // Anything but noidle during idle is a protocol error.
+            return Ok(format_ack(
+                0,
+                "idle",
+                &Ack::new(ACK_ERROR_ARG, "only noidle is allowed"),
+            ));
+        }
+        let Some(current) = fingerprint(handler) else {
+            continue;
+        };
+        let changed: Vec<_> = match &last {
+            Some(previous) => previous
+                .changes(&current)
+                .into_iter()
+                .filter(|name| subsystems.is_empty() || subsystems.iter().any(|s| s == name))
+                .collect(),
+            None => Vec::new(),
+        };
+        if !changed.is_empty() {
+            let mut out: String = changed
+                .iter()
+                .map(|name| format!("changed: {name}\n"))
+                .collect();
+            out.push_str("OK\n");
+            return Ok(out);
+        }
+        last = Some(current);
+    }
+    Ok(String::new())
+}
+
+/// Accepts MPD clients until dropped.
+pub struct MpdServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MpdServer {
+    pub fn start(
+        config: &MpdConfig,
+        queries: Sender<StateQuery>,
+        inbox: RemoteInbox,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind((config.bind.as_str(), config.port))
+            .with_context(|| format!("failed to listen on {}:{}", config.bind, config.port))?;
+        let addr = listener.local_addr()?;
+        // Polled so that dropping the server can stop the thread.
+        listener.set_nonblocking(true)?;
+
+        let handler = Handler { queries, inbox };
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, peer)) => {
+                            let handler = handler.clone();
+                            let stop = Arc::clone(&stop);
+                            std::thread::spawn(move || {
+                                if let Err(err) = serve_connection(stream, &handler, &stop) {
+                                    if err.kind() != io::ErrorKind::UnexpectedEof {
+                                        tracing::warn!("mpd client {peer}: {err}");
+                                    }
+                                }
+                            });
+                        }
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(Duration::from_millis(50));
+                        }
+                        Err(err) => {
+                            tracing::warn!("mpd accept failed: {err}");
+                            std::thread::sleep(Duration::from_millis(50));
+                        }
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for MpdServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auric_core::{PlaybackSession, TrackId};
+    use std::io::{BufRead, BufReader};
+
+    fn entry(title: &str, duration_ms: i64) -> PlaybackQueueEntry {
+        PlaybackQueueEntry {
+            track_id: TrackId(uuid::Uuid::new_v4()),
+            path: format!("/music/{title}.flac"),
+            title: Some(title.to_string()),
+            artist: Some("Artist".to_string()),
+            album: None,
+            duration_ms: Some(duration_ms),
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+        }
+    }
+
+    fn playing_state() -> PlaybackState {
+        PlaybackState {
+            session: PlaybackSession {
+                status: PlaybackStatus::Playing,
+                current_index: Some(0),
+                position_ms: 12_500,
+                volume: 0.8,
+                ..PlaybackSession::default()
+            },
+            queue: vec![entry("One", 200_000), entry("Two", 180_000)],
+        }
+    }
+
+    #[test]
+    fn requests_are_split_on_whitespace_and_quotes() {
+        assert_eq!(tokenize("seek 0 12.5").unwrap(), ["seek", "0", "12.5"]);
+        assert_eq!(
+            tokenize(r#"find "artist" "AC\"DC""#).unwrap(),
+            ["find", "artist", "AC\"DC"]
+        );
+        assert!(tokenize(r#"play "1"#).is_err());
+    }
+
+    #[test]
+    fn status_and_current_song_follow_mpd_field_names() {
+        let state = playing_state();
+        let status = format_status(&state);
+        assert!(status.contains("volume: 80\n"));
+        assert!(status.contains("state: play\n"));
+        assert!(status.contains("song: 0\nsongid: 0\n"));
+        assert!(status.contains("time: 12:200\nelapsed: 12.500\n"));
+        assert!(status.contains("nextsong: 1\n"));
+
+        let song = format_song(1, &state.queue[1]);
+        assert_eq!(
+            song,
+            "file: /music/Two.flac\nTitle: Two\nArtist: Artist\nTime: 180\nduration: 180.000\nPos: 1\nId: 1\n"
+        );
+    }
+
+    #[test]
+    fn idle_reports_the_changed_subsystems() {
+        let mut state = playing_state();
+        let before = IdleFingerprint::of(&state);
+        state.session.status = PlaybackStatus::Paused;
+        state.session.volume = 0.5;
+        assert_eq!(
+            before.changes(&IdleFingerprint::of(&state)),
+            ["player", "mixer"]
+        );
+    }
+
+    #[test]
+    fn clients_query_state_and_control_playback_over_tcp() {
+        let (queries_tx, queries_rx) = remote::query_channel();
+        let inbox = RemoteInbox::new();
+        let config = MpdConfig {
+            enabled: true,
+            bind: "127.0.0.1".to_string(),
+            port: 0,
+        };
+        let server = MpdServer::start(&config, queries_tx, inbox.clone()).unwrap();
+
+        // Stands in for the shell: answers queries and performs actions.
+        let done = Arc::new(AtomicBool::new(false));
+        let shell = {
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                let mut state = playing_state();
+                let mut actions = Vec::new();
+                while !done.load(Ordering::Relaxed) {
+                    remote::answer_queries(&queries_rx, &state);
+                    for request in inbox.drain() {
+                        if let KeyAction::Playback(PlaybackAction::Pause) = request.action {
+                            state.session.status = PlaybackStatus::Paused;
+                        }
+                        actions.push(request.action);
+                        request.reply.send(Ok(String::new())).unwrap();
+                    }
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                actions
+            })
+        };
+
+        let stream = TcpStream::connect(server.local_addr()).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut response = |request: &str| {
+            writer.write_all(request.as_bytes()).unwrap();
+            let mut out = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                out.push_str(&line);
+                if line == "OK\n" || line.starts_with("ACK ") || line.starts_with("OK MPD") {
+                    return out;
+                }
+            }
+        };
+
+        assert_eq!(response(""), GREETING);
+        assert!(response("currentsong\n").starts_with("file: /music/One.flac\n"));
+        assert_eq!(response("pause 1\n"), "OK\n");
+        assert!(response("status\n").contains("state: pause\n"));
+        assert_eq!(
+            response("command_list_ok_begin\nnext\nsetvol 40\ncommand_list_end\n"),
+            "list_OK\nlist_OK\nOK\n"
+        );
+        assert_eq!(
+            response("play x\n"),
+            "ACK [2@0] {play} Integer expected: x\n"
+        );
+        assert!(response("bogus\n").starts_with("ACK [5@0] {bogus}"));
+
+        done.store(true, Ordering::Relaxed);
+        let actions = shell.join().unwrap();
+        assert_eq!(
+            actions,
+            [
+                KeyAction::Playback(PlaybackAction::Pause),
+                KeyAction::Playback(PlaybackAction::Next),
+                KeyAction::Playback(PlaybackAction::SetVolume { volume: 0.4 }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,395 @@
+//! MPRIS on the D-Bus session bus, so desktop media keys, `playerctl` and
+//! panel applets control auric while another window has focus.
+//!
+//! Serves `org.mpris.MediaPlayer2` and its `Player` interface at the standard
+//! object path. Like the MPD and HTTP servers, reads go through
+//! [`StateQuery`] and playback changes through the shell's [`RemoteInbox`].
+//! Property changes are not signalled; clients read them when they ask.
+
+use crate::remote::{self, StateQuery};
+use anyhow::{Context, Result};
+use auric_core::{PlaybackState, PlaybackStatus};
+use auric_ui::remote_inbox::RemoteInbox;
+use auric_ui::shell::KeyAction;
+use auric_ui::PlaybackAction;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+use zbus::blocking::connection::Builder;
+use zbus::blocking::Connection;
+use zbus::fdo;
+use zbus::zvariant::{ObjectPath, Value};
+
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.auric";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+/// How long a method call waits for the shell to perform an action.
+const HANDLE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Owns the bus name until dropped.
+pub struct MprisServer {
+    connection: Connection,
+    name: String,
+}
+
+impl MprisServer {
+    /// Connects to the session bus and takes `org.mpris.MediaPlayer2.auric`,
+    /// or a per-process name when another instance already has it.
+    pub fn start(queries: Sender<StateQuery>, inbox: RemoteInbox) -> Result<Self> {
+        let builder = Builder::session().context("no D-Bus session bus")?;
+        Self::start_on(builder, queries, inbox)
+    }
+
+    fn start_on(builder: Builder, queries: Sender<StateQuery>, inbox: RemoteInbox) -> Result<Self> {
+        let connection = builder
+            .serve_at(OBJECT_PATH, Root)?
+            .serve_at(OBJECT_PATH, Player { queries, inbox })?
+            .build()
+            .context("failed to connect to the session bus")?;
+        let mut name = BUS_NAME.to_string();
+        if connection.request_name(name.as_str()).is_err() {
+            name = format!("{BUS_NAME}.instance{}", std::process::id());
+            connection
+                .request_name(name.as_str())
+                .with_context(|| format!("failed to own {name}"))?;
+        }
+        Ok(Self { connection, name })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for MprisServer {
+    fn drop(&mut self) {
+        let _ = self.connection.release_name(self.name.as_str());
+    }
+}
+
+struct Root;
+
+#[zbus::interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        "auric".to_string()
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+struct Player {
+    queries: Sender<StateQuery>,
+    inbox: RemoteInbox,
+}
+
+impl Player {
+    fn query<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&PlaybackState) -> T + Send + 'static,
+    ) -> fdo::Result<T> {
+        remote::query_state(&self.queries, f)
+            .ok_or_else(|| fdo::Error::Failed("player did not respond".to_string()))
+    }
+
+    fn perform(&self, action: PlaybackAction) -> fdo::Result<()> {
+        match self
+            .inbox
+            .submit(KeyAction::Playback(action))
+            .recv_timeout(HANDLE_TIMEOUT)
+        {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(message)) => Err(fdo::Error::Failed(message)),
+            Err(_) => Err(fdo::Error::Failed("player did not respond".to_string())),
+        }
+    }
+}
+
+#[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    fn play_pause(&self) -> fdo::Result<()> {
+        self.perform(PlaybackAction::TogglePause)
+    }
+
+    fn play(&self) -> fdo::Result<()> {
+        self.perform(PlaybackAction::Resume)
+    }
+
+    fn pause(&self) -> fdo::Result<()> {
+        self.perform(PlaybackAction::Pause)
+    }
+
+    fn stop(&self) -> fdo::Result<()> {
+        self.perform(PlaybackAction::Stop)
+    }
+
+    fn next(&self) -> fdo::Result<()> {
+        self.perform(PlaybackAction::Next)
+    }
+
+    fn previous(&self) -> fdo::Result<()> {
+        self.perform(PlaybackAction::Previous)
+    }
+
+    /// Moves by `offset` microseconds from the current position.
+    fn seek(&self, offset: i64) -> fdo::Result<()> {
+        let position_ms = self.query(|state| state.session.position_ms)?;
+        let target = (position_ms as i64).saturating_add(offset / 1000).max(0);
+        self.perform(PlaybackAction::Seek {
+            position_ms: target as u64,
+        })
+    }
+
+    /// Ignored unless `track_id` is still the playing track, as the spec asks.
+    fn set_position(&self, track_id: ObjectPath<'_>, position: i64) -> fdo::Result<()> {
+        let current = self.query(|state| track_path(state.session.current_index))?;
+        if track_id.as_str() != current || position < 0 {
+            return Ok(());
+        }
+        self.perform(PlaybackAction::Seek {
+            position_ms: (position / 1000) as u64,
+        })
+    }
+
+    fn open_uri(&self, _uri: String) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported(
+            "use `auric open` to play a file".to_string(),
+        ))
+    }
+
+    #[zbus(property)]
+    fn playback_status(&self) -> fdo::Result<String> {
+        let status = self.query(|state| state.session.status)?;
+        Ok(match status {
+            PlaybackStatus::Playing => "Playing",
+            PlaybackStatus::Paused => "Paused",
+            PlaybackStatus::Stopped => "Stopped",
+        }
+        .to_string())
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> fdo::Result<HashMap<String, Value<'static>>> {
+        self.query(metadata)
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> fdo::Result<f64> {
+        self.query(|state| f64::from(state.session.volume))
+    }
+
+    #[zbus(property)]
+    fn set_volume(&self, volume: f64) -> zbus::Result<()> {
+        self.perform(PlaybackAction::SetVolume {
+            volume: volume.clamp(0.0, 1.0) as f32,
+        })
+        .map_err(zbus::Error::from)
+    }
+
+    /// In microseconds, like every MPRIS time.
+    #[zbus(property)]
+    fn position(&self) -> fdo::Result<i64> {
+        self.query(|state| state.session.position_ms as i64 * 1000)
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+/// The queue position names the track, which is all `SetPosition` needs.
+fn track_path(index: Option<usize>) -> String {
+    match index {
+        Some(index) => format!("{OBJECT_PATH}/Track/{index}"),
+        None => NO_TRACK.to_string(),
+    }
+}
+
+fn metadata(state: &PlaybackState) -> HashMap<String, Value<'static>> {
+    let mut fields = HashMap::new();
+    let path = track_path(state.session.current_index);
+    if let Ok(path) = ObjectPath::try_from(path) {
+        fields.insert("mpris:trackid".to_string(), Value::from(path));
+    }
+    let Some(entry) = state.current_entry() else {
+        return fields;
+    };
+    if let Some(title) = &entry.title {
+        fields.insert("xesam:title".to_string(), Value::from(title.clone()));
+    }
+    if let Some(artist) = &entry.artist {
+        fields.insert(
+            "xesam:artist".to_string(),
+            Value::from(vec![artist.clone()]),
+        );
+    }
+    if let Some(album) = &entry.album {
+        fields.insert("xesam:album".to_string(), Value::from(album.clone()));
+    }
+    if let Some(duration_ms) = entry.duration_ms {
+        fields.insert("mpris:length".to_string(), Value::from(duration_ms * 1000));
+    }
+    fields.insert(
+        "xesam:url".to_string(),
+        Value::from(format!("file://{}", entry.path)),
+    );
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn media_key_calls_reach_the_inbox() {
+        // A private bus, so the test neither needs nor touches the desktop's.
+        let Ok(mut bus) = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            eprintln!("dbus-daemon not installed; skipping");
+            return;
+        };
+        let mut address = String::new();
+        BufReader::new(bus.stdout.take().unwrap())
+            .read_line(&mut address)
+            .unwrap();
+
+        let (queries, _answers) = remote::query_channel();
+        let inbox = RemoteInbox::new();
+        let server = MprisServer::start_on(
+            Builder::address(address.trim()).unwrap(),
+            queries,
+            inbox.clone(),
+        )
+        .unwrap();
+        assert_eq!(server.name(), BUS_NAME);
+
+        let shell = {
+            let inbox = inbox.clone();
+            std::thread::spawn(move || loop {
+                if let Some(request) = inbox.drain().into_iter().next() {
+                    let _ = request.reply.send(Ok(String::new()));
+                    return request.action;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            })
+        };
+        let client = Builder::address(address.trim()).unwrap().build().unwrap();
+        client
+            .call_method(
+                Some(BUS_NAME),
+                OBJECT_PATH,
+                Some("org.mpris.MediaPlayer2.Player"),
+                "PlayPause",
+                &(),
+            )
+            .unwrap();
+        assert_eq!(
+            shell.join().unwrap(),
+            KeyAction::Playback(PlaybackAction::TogglePause)
+        );
+
+        drop(server);
+        let _ = bus.kill();
+        let _ = bus.wait();
+    }
+
+    #[test]
+    fn metadata_names_the_playing_track() {
+        let mut state = PlaybackState::default();
+        assert_eq!(
+            metadata(&state).get("mpris:trackid"),
+            Some(&Value::from(ObjectPath::try_from(NO_TRACK).unwrap()))
+        );
+        state.queue.push(auric_core::PlaybackQueueEntry {
+            track_id: auric_core::TrackId(uuid::Uuid::new_v4()),
+            path: "/music/a.flac".to_string(),
+            title: Some("A".to_string()),
+            artist: Some("B".to_string()),
+            album: None,
+            duration_ms: Some(2_000),
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+        });
+        state.session.current_index = Some(0);
+        let fields = metadata(&state);
+        assert_eq!(fields.get("xesam:title"), Some(&Value::from("A")));
+        assert_eq!(fields.get("mpris:length"), Some(&Value::from(2_000_000i64)));
+        assert!(!fields.contains_key("xesam:album"));
+    }
+}
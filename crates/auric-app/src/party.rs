@@ -0,0 +1,94 @@
+//! Party mode, for when the laptop drives the music at a gathering.
+//!
+//! While it is on, guests at the keyboard or on a remote can only add tracks
+//! to the queue; playback controls, settings, library changes and quitting
+//! need the passphrase. `[party] request_cooldown_secs` makes each guest wait
+//! between requests, counted per remote address (the keyboard is one guest).
+
+use crate::engine::Action;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct PartyMode {
+    passphrase: String,
+    last_request: HashMap<String, Instant>,
+}
+
+impl PartyMode {
+    pub fn new(passphrase: impl Into<String>) -> Self {
+        Self {
+            passphrase: passphrase.into(),
+            last_request: HashMap::new(),
+        }
+    }
+
+    pub fn unlocks(&self, attempt: &str) -> bool {
+        attempt == self.passphrase
+    }
+
+    /// Records a request from `user`, or returns how long they still have
+    /// to wait.
+    pub fn admit(&mut self, user: &str, cooldown: Duration, now: Instant) -> Result<(), Duration> {
+        if let Some(last) = self.last_request.get(user) {
+            let waited = now.saturating_duration_since(*last);
+            if waited < cooldown {
+                return Err(cooldown - waited);
+            }
+        }
+        self.last_request.insert(user.to_string(), now);
+        Ok(())
+    }
+}
+
+/// Playback actions guests may use: adding to the queue, and moving on when
+/// a track ends.
+pub fn allows(action: &Action) -> bool {
+    matches!(action, Action::Enqueue { .. } | Action::Advance)
+}
+
+/// Palette commands that keep working in party mode.
+pub fn allows_command(head: &str) -> bool {
+    matches!(
+        head,
+        "help"
+            | "?"
+            | "refresh"
+            | "reload"
+            | "party"
+            | "__party_quit"
+            | "__fetch_artwork"
+            | "__album_art"
+            | "__layout_save"
+    )
+}
+
+pub const LOCKED: &str = "Party mode: guests can only add tracks to the queue";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_guest_waits_out_their_own_cooldown() {
+        let mut party = PartyMode::new("disco");
+        let cooldown = Duration::from_secs(60);
+        let start = Instant::now();
+        assert!(party.admit("10.0.0.2", cooldown, start).is_ok());
+        assert!(party.admit("10.0.0.3", cooldown, start).is_ok());
+        assert_eq!(
+            party.admit("10.0.0.2", cooldown, start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+        assert!(party.admit("10.0.0.2", cooldown, start + cooldown).is_ok());
+        assert!(party
+            .admit("10.0.0.2", Duration::ZERO, start + cooldown)
+            .is_ok());
+
+        assert!(party.unlocks("disco"));
+        assert!(!party.unlocks("Disco"));
+        assert!(allows(&Action::Advance));
+        assert!(!allows(&Action::Next));
+        assert!(allows_command("party") && !allows_command("root"));
+    }
+}
@@ -0,0 +1,312 @@
+//! Podcast subscriptions. Feeds and episodes are fetched with `curl`, like
+//! the update check, and every network step runs as a background job on
+//! [`crate::engine::spawn_background_scan`]'s thread so the shell never
+//! waits on a slow server.
+
+use anyhow::{bail, Context, Result};
+use auric_library::db::{Database, PodcastEpisodeRow, PodcastRow};
+use auric_library::podcast::{parse_feed, ParsedFeed};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// Feeds refreshed longer ago than this are fetched again at startup.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Work for the background thread; see [`crate::engine::BackgroundJob`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Job {
+    /// Fetch every feed, or only those due for a refresh.
+    Refresh {
+        only_due: bool,
+    },
+    Subscribe {
+        url: String,
+    },
+    Download {
+        episode_id: String,
+    },
+}
+
+impl Job {
+    /// Runs the job against `db`, returning the status line to show.
+    pub fn run(&self, db: &mut Database) -> Result<String> {
+        match self {
+            Self::Refresh { only_due } => refresh_all(db, *only_due),
+            Self::Subscribe { url } => {
+                let (podcast, added) = subscribe(db, url)?;
+                Ok(format!(
+                    "Subscribed to {} ({added} episodes)",
+                    podcast.title
+                ))
+            }
+            Self::Download { episode_id } => {
+                let episode = db
+                    .get_podcast_episode(episode_id)?
+                    .ok_or_else(|| anyhow::anyhow!("unknown episode: {episode_id}"))?;
+                download(db, &episode)?;
+                Ok(format!("Downloaded {}", episode.title))
+            }
+        }
+    }
+}
+
+/// Where downloaded episodes go: `podcasts/<podcast id>/` next to the
+/// database, so each profile keeps its own.
+pub fn downloads_dir(db: &Database) -> Result<PathBuf> {
+    let db_path = db
+        .path()
+        .ok_or_else(|| anyhow::anyhow!("an in-memory library cannot store downloads"))?;
+    Ok(db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("podcasts"))
+}
+
+/// Feed and episode URLs come from whoever publishes the feed, so curl may
+/// only speak HTTP, redirects included.
+const CURL_HTTP_ONLY: [&str; 4] = ["--proto", "=http,https", "--proto-redir", "=http,https"];
+
+fn check_http_url(url: &str) -> Result<()> {
+    let scheme = url
+        .split_once("://")
+        .map(|(scheme, _)| scheme.to_ascii_lowercase());
+    if !matches!(scheme.as_deref(), Some("http" | "https")) {
+        bail!("not an http or https URL: {url}");
+    }
+    Ok(())
+}
+
+/// Fetches an `http(s)` URL and returns the body.
+fn fetch(url: &str) -> Result<Vec<u8>> {
+    check_http_url(url)?;
+    let output = Command::new("curl")
+        .args(["-sSLf", "--max-time", "30"])
+        .args(CURL_HTTP_ONLY)
+        .args(["--", url])
+        .output()
+        .context("failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "failed to fetch {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+pub fn fetch_feed(url: &str) -> Result<ParsedFeed> {
+    let body = fetch(url)?;
+    parse_feed(&String::from_utf8_lossy(&body)).with_context(|| format!("failed to read {url}"))
+}
+
+/// Fetches the feed first, so a bad URL is reported without subscribing.
+/// Returns the podcast and how many episodes it brought in.
+pub fn subscribe(db: &mut Database, url: &str) -> Result<(PodcastRow, usize)> {
+    let url = url.trim();
+    let feed = fetch_feed(url)?;
+    let title = if feed.title.is_empty() {
+        url
+    } else {
+        &feed.title
+    };
+    let podcast = db.subscribe_podcast(url, title)?;
+    let added = db.update_podcast_feed(&podcast.id, title, &feed.episodes)?;
+    let podcast = find(db, &podcast.id)?;
+    Ok((podcast, added))
+}
+
+/// Fetches one feed and stores its episodes; returns how many are new.
+pub fn refresh(db: &mut Database, podcast: &PodcastRow) -> Result<usize> {
+    let feed = fetch_feed(&podcast.feed_url)?;
+    let title = if feed.title.is_empty() {
+        &podcast.title
+    } else {
+        &feed.title
+    };
+    Ok(db.update_podcast_feed(&podcast.id, title, &feed.episodes)?)
+}
+
+/// Refreshes every feed, or only those not fetched within
+/// [`REFRESH_INTERVAL`]. A feed that fails is logged and skipped.
+pub fn refresh_all(db: &mut Database, only_due: bool) -> Result<String> {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let due = |podcast: &PodcastRow| match podcast.refreshed_at_ms {
+        Some(at) => now_ms.saturating_sub(at) >= REFRESH_INTERVAL.as_millis() as i64,
+        None => true,
+    };
+    let mut added = 0;
+    let mut refreshed = 0;
+    let mut failed = 0;
+    for podcast in db.list_podcasts()? {
+        if only_due && !due(&podcast) {
+            continue;
+        }
+        match refresh(db, &podcast) {
+            Ok(count) => {
+                added += count;
+                refreshed += 1;
+            }
+            Err(err) => {
+                tracing::warn!(feed = %podcast.feed_url, "podcast refresh failed: {err:#}");
+                failed += 1;
+            }
+        }
+    }
+    let mut message = format!("Podcasts: {added} new episodes from {refreshed} feeds");
+    if failed > 0 {
+        message.push_str(&format!(", {failed} failed (see log)"));
+    }
+    Ok(message)
+}
+
+/// Downloads an episode next to the database and records where it went.
+/// The file is written under a temporary name first, so an interrupted
+/// download never looks finished.
+pub fn download(db: &Database, episode: &PodcastEpisodeRow) -> Result<PathBuf> {
+    let dir = downloads_dir(db)?.join(&episode.podcast_id);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let dest = dir.join(format!(
+        "{}.{}",
+        episode.id,
+        audio_extension(&episode.audio_url)
+    ));
+    let partial = dest.with_extension("part");
+    check_http_url(&episode.audio_url)?;
+    let status = Command::new("curl")
+        .args(CURL_HTTP_ONLY)
+        .args(["-sSLf", "-o"])
+        .arg(&partial)
+        .args(["--", &episode.audio_url])
+        .status()
+        .context("failed to run curl")?;
+    if !status.success() {
+        let _ = fs::remove_file(&partial);
+        bail!("failed to download {}", episode.audio_url);
+    }
+    fs::rename(&partial, &dest)
+        .with_context(|| format!("failed to move download to {}", dest.display()))?;
+    db.set_podcast_episode_local_path(&episode.id, Some(&dest.to_string_lossy()))?;
+    Ok(dest)
+}
+
+/// Unsubscribes and deletes the podcast's downloads.
+pub fn remove(db: &Database, podcast: &PodcastRow) -> Result<()> {
+    db.delete_podcast(&podcast.id)?;
+    if let Ok(dir) = downloads_dir(db) {
+        let dir = dir.join(&podcast.id);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("failed to remove {}", dir.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Looks a podcast up by id or by title, case-insensitively.
+pub fn find(db: &Database, name: &str) -> Result<PodcastRow> {
+    let name = name.trim();
+    db.list_podcasts()?
+        .into_iter()
+        .find(|p| p.id == name || p.title.eq_ignore_ascii_case(name))
+        .ok_or_else(|| anyhow::anyhow!("unknown podcast: {name}"))
+}
+
+/// The extension of the URL's last path segment, ignoring any query string,
+/// or `mp3` when it has none.
+fn audio_extension(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((_, ext))
+            if !ext.is_empty()
+                && ext.len() <= 5
+                && ext.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            ext.to_ascii_lowercase()
+        }
+        _ => "mp3".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auric_library::db::DatabaseOptions;
+
+    #[test]
+    fn episode_extensions_come_from_the_url() {
+        assert_eq!(
+            audio_extension("https://cdn.example.com/ep1.M4A?token=x"),
+            "m4a"
+        );
+        assert_eq!(audio_extension("https://example.com/stream"), "mp3");
+    }
+
+    /// Serves `feed.xml` and `ep1.ogg` on a local port until the test ends.
+    fn serve_show() -> String {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let feed = format!(
+            "<rss><channel><title>Local Show</title><item><title>One</title>\
+             <enclosure url=\"{base}/ep1.ogg\" type=\"audio/ogg\"/></item></channel></rss>"
+        );
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let body = match request.url() {
+                    "/feed.xml" => feed.clone().into_bytes(),
+                    "/ep1.ogg" => b"not really audio".to_vec(),
+                    _ => {
+                        let _ = request.respond(tiny_http::Response::empty(404));
+                        continue;
+                    }
+                };
+                let _ = request.respond(tiny_http::Response::from_data(body));
+            }
+        });
+        base
+    }
+
+    #[test]
+    fn subscribing_and_downloading_work_over_http_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = serve_show();
+        let mut db = Database::open(&DatabaseOptions {
+            path: dir.path().join("lib").join("auric.db"),
+            ..DatabaseOptions::default()
+        })
+        .unwrap();
+
+        let local = dir.path().join("feed.xml");
+        fs::write(
+            &local,
+            "<rss><channel><title>Sneaky</title></channel></rss>",
+        )
+        .unwrap();
+        let file_url = format!("file://{}", local.display());
+        assert!(Job::Subscribe { url: file_url }.run(&mut db).is_err());
+        assert!(db.list_podcasts().unwrap().is_empty());
+
+        let url = format!("{base}/feed.xml");
+        let message = Job::Subscribe { url: url.clone() }.run(&mut db).unwrap();
+        assert_eq!(message, "Subscribed to Local Show (1 episodes)");
+        let podcast = find(&db, "local show").unwrap();
+        assert_eq!(podcast.episode_count, 1);
+        assert_eq!(refresh(&mut db, &podcast).unwrap(), 0);
+
+        let episode = db.list_podcast_episodes(&podcast.id, 10).unwrap().remove(0);
+        let path = download(&db, &episode).unwrap();
+        assert!(path.starts_with(dir.path().join("lib").join("podcasts")));
+        assert_eq!(fs::read(&path).unwrap(), b"not really audio");
+        let stored = db.get_podcast_episode(&episode.id).unwrap().unwrap();
+        assert_eq!(stored.local_path.as_deref(), Some(path.to_str().unwrap()));
+
+        remove(&db, &podcast).unwrap();
+        assert!(!path.exists());
+        assert!(db.list_podcasts().unwrap().is_empty());
+    }
+}
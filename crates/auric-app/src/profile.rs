@@ -0,0 +1,262 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+const PROFILE_CONFIG: &str = "default.toml";
+const PROFILE_DB: &str = "auric.db";
+
+/// A named library with its own `default.toml` in a directory under
+/// `profiles/` next to the base config, and its own database under
+/// `profiles/` in the data directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+impl Profile {
+    pub fn config_path(&self) -> PathBuf {
+        self.dir.join(PROFILE_CONFIG)
+    }
+}
+
+/// Directory holding the profiles for the config at `base_config`.
+pub fn profiles_dir(base_config: &Path) -> PathBuf {
+    base_config
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .join("profiles")
+}
+
+/// Directory name for a profile: lowercase ASCII letters and digits, with
+/// every other run of characters collapsed to one `-`.
+pub fn slug(name: &str) -> String {
+    let mut out = String::new();
+    for ch in name.trim().chars() {
+        if ch.is_ascii_alphanumeric() {
+            out.push(ch.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+/// Profiles under [`profiles_dir`], sorted by name. A missing directory just
+/// means there are none.
+pub fn list(base_config: &Path) -> Result<Vec<Profile>> {
+    let dir = profiles_dir(base_config);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", dir.display())),
+    };
+    let mut profiles = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let config_path = path.join(PROFILE_CONFIG);
+        if !config_path.is_file() {
+            continue;
+        }
+        let fallback = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name = read_name(&config_path).unwrap_or(fallback);
+        profiles.push(Profile { name, dir: path });
+    }
+    profiles.sort_by_key(|p| p.name.to_lowercase());
+    Ok(profiles)
+}
+
+/// Looks a profile up by its name or directory name, case-insensitively.
+pub fn find(base_config: &Path, name: &str) -> Result<Profile> {
+    let wanted = slug(name);
+    let profiles = list(base_config)?;
+    if let Some(profile) = profiles.iter().find(|p| {
+        p.name.eq_ignore_ascii_case(name.trim())
+            || p.dir
+                .file_name()
+                .map(|n| n.to_string_lossy() == wanted)
+                .unwrap_or(false)
+    }) {
+        return Ok(profile.clone());
+    }
+    if profiles.is_empty() {
+        bail!("unknown profile: {name}. create it with `auric profile create {name:?}`");
+    }
+    let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+    bail!(
+        "unknown profile: {name}. known profiles: {}",
+        names.join(", ")
+    )
+}
+
+/// Creates a profile from the base config, with `[profile] name` set and
+/// `[database] path` pointing at `profiles/<slug>/auric.db` under `data_dir`.
+pub fn create(base_config: &Path, data_dir: &Path, name: &str) -> Result<Profile> {
+    let slug = slug(name);
+    if slug.is_empty() {
+        bail!("profile name needs at least one letter or digit");
+    }
+    let dir = profiles_dir(base_config).join(&slug);
+    if dir.join(PROFILE_CONFIG).exists() {
+        bail!("profile already exists: {}", dir.display());
+    }
+
+    let mut table: toml::Table = match fs::read_to_string(base_config) {
+        Ok(raw) => toml::from_str(&raw)
+            .with_context(|| format!("failed to parse TOML config {}", base_config.display()))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("failed to read config {}", base_config.display()))
+        }
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let dir = dir.canonicalize().unwrap_or(dir);
+    let db_path = data_dir.join("profiles").join(&slug).join(PROFILE_DB);
+    set_key(&mut table, "profile", "name", name.trim());
+    set_key(&mut table, "database", "path", &db_path.to_string_lossy());
+
+    let config_path = dir.join(PROFILE_CONFIG);
+    let raw = format!(
+        "# Auric profile: {}\n\n{}",
+        name.trim(),
+        toml::to_string(&table)?
+    );
+    fs::write(&config_path, raw)
+        .with_context(|| format!("failed to write {}", config_path.display()))?;
+    Ok(Profile {
+        name: name.trim().to_string(),
+        dir,
+    })
+}
+
+/// Asks which profile to open, with 0 for the base config. An empty answer
+/// also picks the base config.
+pub fn pick(
+    profiles: &[Profile],
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<Option<Profile>> {
+    writeln!(output, "Choose a library profile:")?;
+    writeln!(output, "  0) default")?;
+    for (i, profile) in profiles.iter().enumerate() {
+        writeln!(output, "  {}) {}", i + 1, profile.name)?;
+    }
+    loop {
+        write!(output, "profile [0]: ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let answer = line.trim();
+        if answer.is_empty() || answer == "0" {
+            return Ok(None);
+        }
+        if let Some(profile) = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| profiles.get(i))
+        {
+            return Ok(Some(profile.clone()));
+        }
+        if let Some(profile) = profiles
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(answer))
+        {
+            return Ok(Some(profile.clone()));
+        }
+        writeln!(output, "no profile {answer:?}")?;
+    }
+}
+
+fn read_name(config_path: &Path) -> Option<String> {
+    let raw = fs::read_to_string(config_path).ok()?;
+    let table: toml::Table = toml::from_str(&raw).ok()?;
+    let name = table.get("profile")?.get("name")?.as_str()?.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn set_key(table: &mut toml::Table, section: &str, key: &str, value: &str) {
+    let entry = table
+        .entry(section.to_string())
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if !entry.is_table() {
+        *entry = toml::Value::Table(toml::Table::new());
+    }
+    if let Some(section) = entry.as_table_mut() {
+        section.insert(key.to_string(), toml::Value::String(value.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn slugs_are_lowercase_and_dashed() {
+        assert_eq!(slug("Home NAS"), "home-nas");
+        assert_eq!(slug("  Work / Laptop!  "), "work-laptop");
+        assert_eq!(slug("***"), "");
+    }
+
+    #[test]
+    fn created_profiles_get_their_own_database() {
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("default.toml");
+        fs::write(
+            &base,
+            "[database]\npath = \"var/auric.db\"\n\n[ui]\ntheme = \"nord\"\n",
+        )
+        .unwrap();
+
+        let data = dir.path().join("data");
+        let created = create(&base, &data, "Home NAS").unwrap();
+        assert!(created.dir.ends_with("profiles/home-nas"));
+        let config = crate::AppConfig::load_from_path(&created.config_path()).unwrap();
+        assert_eq!(config.profile.name, "Home NAS");
+        assert_eq!(config.ui.theme, "nord");
+        assert_eq!(
+            PathBuf::from(&config.database.path),
+            data.join("profiles/home-nas").join(PROFILE_DB)
+        );
+        assert!(create(&base, &data, "home nas").is_err());
+
+        create(&base, &data, "Laptop").unwrap();
+        let names: Vec<String> = list(&base).unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["Home NAS", "Laptop"]);
+        assert_eq!(find(&base, "home-nas").unwrap(), created);
+        assert_eq!(find(&base, "HOME NAS").unwrap(), created);
+        assert!(find(&base, "Office").is_err());
+    }
+
+    #[test]
+    fn picker_accepts_numbers_and_names() {
+        let profiles = vec![
+            Profile {
+                name: "Home NAS".to_string(),
+                dir: PathBuf::from("a"),
+            },
+            Profile {
+                name: "Laptop".to_string(),
+                dir: PathBuf::from("b"),
+            },
+        ];
+        let mut out = Vec::new();
+        let picked = pick(&profiles, &mut "2\n".as_bytes(), &mut out).unwrap();
+        assert_eq!(picked.unwrap().name, "Laptop");
+        let picked = pick(&profiles, &mut "9\nhome nas\n".as_bytes(), &mut out).unwrap();
+        assert_eq!(picked.unwrap().name, "Home NAS");
+        assert!(pick(&profiles, &mut "\n".as_bytes(), &mut out)
+            .unwrap()
+            .is_none());
+        assert!(String::from_utf8(out).unwrap().contains("no profile \"9\""));
+    }
+}
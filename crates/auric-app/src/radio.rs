@@ -0,0 +1,313 @@
+//! Radio mode: when the queue runs out, tracks by artists similar to the
+//! last one played are appended so music keeps going.
+//!
+//! Similar artists come from Last.fm when `[radio] lastfm_api_key` is set
+//! and offline mode is off, and otherwise from the library itself: artists
+//! sharing albums or playlists with the last one. Only library tracks are
+//! queued; when nothing related is found, random tracks fill in.
+//!
+//! Last.fm is asked on a background thread once the queue's last entry
+//! starts, and its picks are appended when the answer arrives, so the render
+//! loop never waits on the network. Skipping past the last entry before then
+//! refills from the library.
+
+use crate::engine::playback_queue_entry_from_track_row;
+use crate::BootstrappedApp;
+use anyhow::{bail, Context, Result};
+use auric_core::{PlaybackStatus, RepeatMode, TrackId};
+use auric_library::db::TrackRow;
+use rand::seq::SliceRandom;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+const SIMILAR_ARTISTS: usize = 30;
+
+/// A Last.fm lookup for the artist of the queue's last entry. It is kept
+/// after answering so the same artist is not asked again.
+#[derive(Debug)]
+pub struct Lookup {
+    seed: String,
+    answer: Option<Receiver<Result<Vec<String>>>>,
+}
+
+/// Starts a Last.fm lookup while the queue's last entry plays and appends
+/// tracks by the similar artists once it answers. Called every
+/// [`crate::engine::tick`].
+pub fn poll(app: &mut BootstrappedApp) {
+    let key = api_key(&app.config.radio);
+    let Some(seed) = lookahead_seed(app).filter(|_| !key.is_empty() && !app.offline) else {
+        app.radio_lookup = None;
+        return;
+    };
+    let lookup = match &mut app.radio_lookup {
+        Some(lookup) if lookup.seed == seed => lookup,
+        slot => {
+            let (tx, rx) = mpsc::channel();
+            let artist = seed.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(lastfm_similar(&key, &artist));
+            });
+            slot.insert(Lookup {
+                seed,
+                answer: Some(rx),
+            })
+        }
+    };
+    let answer = match lookup.answer.as_ref().map(Receiver::try_recv) {
+        Some(Ok(answer)) => answer,
+        Some(Err(TryRecvError::Disconnected)) => Err(anyhow::anyhow!("the lookup stopped")),
+        Some(Err(TryRecvError::Empty)) | None => return,
+    };
+    lookup.answer = None;
+    let seed = lookup.seed.clone();
+    let similar = match answer {
+        Ok(names) => in_library(app, &names),
+        Err(err) => {
+            tracing::warn!(error = %err, "Last.fm similar artists failed; using the library");
+            Vec::new()
+        }
+    };
+    let similar = if similar.is_empty() {
+        related_in_library(app, &seed)
+    } else {
+        similar
+    };
+    append(app, Some(&seed), similar);
+}
+
+/// The artist of the playing entry when it is the last one and radio mode
+/// will refill after it.
+fn lookahead_seed(app: &BootstrappedApp) -> Option<String> {
+    let session = &app.playback_state.session;
+    let refills = session.radio
+        && !session.shuffle
+        && !session.random_album
+        && session.repeat == RepeatMode::Off
+        && session.status == PlaybackStatus::Playing
+        && session.current_index? + 1 == app.playback_state.queue.len();
+    if !refills {
+        return None;
+    }
+    app.playback_state
+        .current_entry()
+        .and_then(|entry| entry.artist.clone())
+        .filter(|artist| !artist.is_empty())
+}
+
+/// Appends up to `[radio] batch` tracks by artists the library relates to the
+/// last queue entry's, and returns how many were added.
+pub fn refill(app: &mut BootstrappedApp) -> usize {
+    let seed = app
+        .playback_state
+        .queue
+        .last()
+        .and_then(|entry| entry.artist.clone())
+        .filter(|artist| !artist.is_empty());
+    let similar = seed
+        .as_deref()
+        .map(|seed| related_in_library(app, seed))
+        .unwrap_or_default();
+    append(app, seed.as_deref(), similar)
+}
+
+/// Appends up to `[radio] batch` tracks by `similar` artists, or random ones
+/// when they have none left to queue, and returns how many were added.
+fn append(app: &mut BootstrappedApp, seed: Option<&str>, similar: Vec<String>) -> usize {
+    let batch = app.config.radio.batch.max(1);
+    let queued: HashSet<TrackId> = app
+        .playback_state
+        .queue
+        .iter()
+        .map(|entry| entry.track_id)
+        .collect();
+
+    let mut picked: Vec<TrackRow> = Vec::new();
+    for artist in similar {
+        match app.db.list_tracks_by_artist(&artist) {
+            Ok(tracks) => picked.extend(tracks),
+            Err(err) => tracing::warn!(error = %err, "failed to list tracks for radio"),
+        }
+    }
+    picked.retain(|track| !queued.contains(&track.id));
+    picked.shuffle(&mut rand::rng());
+    picked.truncate(batch);
+    if picked.is_empty() {
+        match app.db.random_tracks(batch * 2) {
+            Ok(tracks) => {
+                picked = tracks
+                    .into_iter()
+                    .filter(|track| !queued.contains(&track.id))
+                    .take(batch)
+                    .collect()
+            }
+            Err(err) => tracing::warn!(error = %err, "failed to pick random tracks for radio"),
+        }
+    }
+
+    let added = picked.len();
+    app.playback_state
+        .queue
+        .extend(picked.into_iter().map(playback_queue_entry_from_track_row));
+    tracing::info!(
+        added,
+        seed = seed.unwrap_or("-"),
+        "radio refilled the queue"
+    );
+    added
+}
+
+/// Artists sharing albums or playlists with `artist`, most related first.
+fn related_in_library(app: &BootstrappedApp, artist: &str) -> Vec<String> {
+    app.db
+        .related_artists(artist, SIMILAR_ARTISTS)
+        .unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "failed to find related artists");
+            Vec::new()
+        })
+}
+
+/// The library's spelling of each name it has, in the given order.
+fn in_library(app: &BootstrappedApp, names: &[String]) -> Vec<String> {
+    let artists = app.db.distinct_artists().unwrap_or_default();
+    names
+        .iter()
+        .filter_map(|name| {
+            artists
+                .iter()
+                .find(|artist| artist.eq_ignore_ascii_case(name))
+                .cloned()
+        })
+        .collect()
+}
+
+fn api_key(config: &crate::RadioConfig) -> String {
+    std::env::var("AURIC_LASTFM_API_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .unwrap_or_else(|| config.lastfm_api_key.clone())
+}
+
+fn lastfm_similar(key: &str, artist: &str) -> Result<Vec<String>> {
+    let url = format!(
+        "https://ws.audioscrobbler.com/2.0/?method=artist.getsimilar&format=json&autocorrect=1&limit={SIMILAR_ARTISTS}&artist={}&api_key={}",
+        crate::subsonic::encode(artist),
+        crate::subsonic::encode(key),
+    );
+    let output = Command::new("curl")
+        .args(["-sSLf", "--max-time", "5", "--"])
+        .arg(&url)
+        .output()
+        .context("failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "failed to reach Last.fm: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let body: Value =
+        serde_json::from_slice(&output.stdout).context("Last.fm sent something other than JSON")?;
+    similar_names(&body)
+}
+
+fn similar_names(body: &Value) -> Result<Vec<String>> {
+    if let Some(message) = body.get("message").and_then(Value::as_str) {
+        bail!("Last.fm: {message}");
+    }
+    let artists = body
+        .pointer("/similarartists/artist")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(artists
+        .iter()
+        .filter_map(|artist| artist.get("name").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn lastfm_answers_are_read_in_order() {
+        let body = json!({
+            "similarartists": {
+                "artist": [{ "name": "Boards of Canada", "match": "1" }, { "name": "Aphex Twin" }],
+                "@attr": { "artist": "Autechre" }
+            }
+        });
+        assert_eq!(
+            similar_names(&body).unwrap(),
+            ["Boards of Canada", "Aphex Twin"]
+        );
+        assert!(similar_names(&json!({ "error": 10, "message": "Invalid API key" })).is_err());
+        assert!(similar_names(&json!({})).unwrap().is_empty());
+    }
+
+    #[test]
+    fn lastfm_picks_are_appended_when_the_answer_arrives() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg_path = dir.path().join("auric-test.toml");
+        std::fs::write(
+            &cfg_path,
+            format!(
+                "[database]\npath = \"{}\"\n[radio]\nlastfm_api_key = \"key\"\n",
+                dir.path().join("t.db").display()
+            ),
+        )
+        .unwrap();
+        let mut app = crate::bootstrap_from_config_path(&cfg_path).unwrap();
+        for (artist, album) in [("Seed", "Split"), ("Friend", "Split"), ("Far", "Away")] {
+            app.db
+                .upsert_track(&auric_library::TrackRecord {
+                    id: TrackId(uuid::Uuid::new_v4()),
+                    path: format!("/music/{artist}.flac"),
+                    title: Some(artist.to_string()),
+                    artist: Some(artist.to_string()),
+                    album: Some(album.to_string()),
+                    duration_ms: None,
+                    sample_rate: None,
+                    channels: None,
+                    bit_depth: None,
+                    file_mtime_ms: None,
+                })
+                .unwrap();
+        }
+        app.playback_state.queue = app
+            .db
+            .list_tracks_by_artist("Seed")
+            .unwrap()
+            .into_iter()
+            .map(playback_queue_entry_from_track_row)
+            .collect();
+        app.playback_state.session.current_index = Some(0);
+        app.playback_state.session.status = PlaybackStatus::Playing;
+        app.playback_state.session.radio = true;
+
+        let (tx, rx) = mpsc::channel();
+        app.radio_lookup = Some(Lookup {
+            seed: "Seed".to_string(),
+            answer: Some(rx),
+        });
+        poll(&mut app);
+        assert_eq!(app.playback_state.queue.len(), 1);
+
+        tx.send(Ok(vec!["Nobody".to_string(), "far".to_string()]))
+            .unwrap();
+        poll(&mut app);
+        let artists: Vec<_> = app
+            .playback_state
+            .queue
+            .iter()
+            .map(|entry| entry.artist.as_deref().unwrap_or_default())
+            .collect();
+        assert_eq!(artists, ["Seed", "Far"]);
+        // The playing entry is no longer the last, so nothing is asked.
+        poll(&mut app);
+        assert!(app.radio_lookup.is_none());
+    }
+}
@@ -0,0 +1,563 @@
+//! Remote control of a running `auric` shell over a Unix socket.
+//!
+//! The protocol is one request line per connection, answered with one JSON
+//! line: `{"ok":true,"message":"..."}`. Requests are the command name
+//! optionally followed by an argument, e.g. `next` or `add /music/new`.
+//!
+//! The running shell accepts connections on a background thread and hands
+//! each command to its run loop through a [`RemoteInbox`]. Anyone who can
+//! open the socket file can control the player; the file is created
+//! owner-only.
+
+use anyhow::{bail, Context, Result};
+use auric_core::{PlaybackState, PlaybackStatus};
+use auric_ui::remote_inbox::RemoteInbox;
+use auric_ui::shell::KeyAction;
+use auric_ui::PlaybackAction;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+/// How long the client waits for a running instance to answer.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the server waits for the run loop to handle a command.
+const HANDLE_TIMEOUT: Duration = Duration::from_secs(3);
+/// `open` scans the folder before answering.
+const OPEN_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest request line the server reads; a path argument fits easily.
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+
+/// Runs on the app thread with the current playback state, so network
+/// servers can read it without sharing the app.
+pub type StateQuery = Box<dyn FnOnce(&PlaybackState) + Send>;
+
+/// Sender half for [`StateQuery`]s and the receiver the poll loop drains.
+pub fn query_channel() -> (Sender<StateQuery>, Receiver<StateQuery>) {
+    mpsc::channel()
+}
+
+/// Answers every pending query. Called from the shell's poll loop.
+pub fn answer_queries(queries: &Receiver<StateQuery>, state: &PlaybackState) {
+    for query in queries.try_iter() {
+        query(state);
+    }
+}
+
+/// Computes a value from the playback state on the app thread. `None` when
+/// the app is gone or does not answer within a few seconds.
+pub fn query_state<T: Send + 'static>(
+    queries: &Sender<StateQuery>,
+    f: impl FnOnce(&PlaybackState) -> T + Send + 'static,
+) -> Option<T> {
+    let (tx, rx) = mpsc::channel();
+    let query: StateQuery = Box::new(move |state| {
+        let _ = tx.send(f(state));
+    });
+    queries.send(query).ok()?;
+    rx.recv_timeout(HANDLE_TIMEOUT).ok()
+}
+
+pub fn status_label(status: PlaybackStatus) -> &'static str {
+    match status {
+        PlaybackStatus::Playing => "playing",
+        PlaybackStatus::Paused => "paused",
+        PlaybackStatus::Stopped => "stopped",
+    }
+}
+
+/// The `status` reply without a shell, e.g. `playing: Artist - Title (01:23 / 04:56)`.
+pub fn playback_summary(state: &PlaybackState) -> String {
+    let status = status_label(state.session.status);
+    let Some(entry) = state.current_entry() else {
+        return status.to_string();
+    };
+    let title = entry.title.as_deref().unwrap_or(&entry.path);
+    let track = match entry.artist.as_deref() {
+        Some(artist) if !artist.is_empty() => format!("{artist} - {title}"),
+        _ => title.to_string(),
+    };
+    let clock = |ms: u64| format!("{:02}:{:02}", ms / 60_000, ms / 1000 % 60);
+    format!(
+        "{status}: {track} ({} / {})",
+        clock(state.session.position_ms),
+        clock(entry.duration_ms.unwrap_or(0).max(0) as u64)
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteCommand {
+    Play,
+    Pause,
+    /// Play or pause, for binding a desktop-wide media key.
+    Toggle,
+    Stop,
+    Next,
+    Previous,
+    Status,
+    /// Add a folder as a library root and scan it.
+    Add(String),
+    /// Scan a file's or folder's tracks and play them, as `auric <path>` does.
+    Open(String),
+}
+
+impl RemoteCommand {
+    /// Names accepted on the command line and on the socket.
+    pub const NAMES: [&'static str; 9] = [
+        "play", "pause", "toggle", "stop", "next", "prev", "status", "add", "open",
+    ];
+
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = line.trim();
+        let (name, arg) = match line.split_once(' ') {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (line, ""),
+        };
+        let command = match name {
+            "play" => Self::Play,
+            "pause" => Self::Pause,
+            "toggle" => Self::Toggle,
+            "stop" => Self::Stop,
+            "next" => Self::Next,
+            "prev" | "previous" => Self::Previous,
+            "status" => Self::Status,
+            "add" | "open" if arg.is_empty() => bail!("usage: {name} <path>"),
+            "add" => return Ok(Self::Add(arg.to_string())),
+            "open" => return Ok(Self::Open(arg.to_string())),
+            other => bail!("unknown remote command: {other}"),
+        };
+        if !arg.is_empty() {
+            bail!("{name} takes no arguments");
+        }
+        Ok(command)
+    }
+
+    pub fn to_line(&self) -> String {
+        match self {
+            Self::Play => "play".to_string(),
+            Self::Pause => "pause".to_string(),
+            Self::Toggle => "toggle".to_string(),
+            Self::Stop => "stop".to_string(),
+            Self::Next => "next".to_string(),
+            Self::Previous => "prev".to_string(),
+            Self::Status => "status".to_string(),
+            Self::Add(path) => format!("add {path}"),
+            Self::Open(path) => format!("open {path}"),
+        }
+    }
+
+    /// How long the run loop may take to answer.
+    pub fn timeout(&self) -> Duration {
+        match self {
+            Self::Open(_) => OPEN_TIMEOUT,
+            _ => HANDLE_TIMEOUT,
+        }
+    }
+
+    /// The shell action the run loop performs for this command.
+    pub fn action(&self) -> KeyAction {
+        match self {
+            Self::Play => KeyAction::Playback(PlaybackAction::Resume),
+            Self::Pause => KeyAction::Playback(PlaybackAction::Pause),
+            Self::Toggle => KeyAction::Playback(PlaybackAction::TogglePause),
+            Self::Stop => KeyAction::Playback(PlaybackAction::Stop),
+            Self::Next => KeyAction::Playback(PlaybackAction::Next),
+            Self::Previous => KeyAction::Playback(PlaybackAction::Previous),
+            Self::Status => KeyAction::Continue,
+            Self::Add(path) => KeyAction::CommandSubmitted(format!("__add_root {path}")),
+            Self::Open(path) => KeyAction::CommandSubmitted(format!("__open {path}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteReply {
+    pub ok: bool,
+    pub message: String,
+}
+
+impl RemoteReply {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+        }
+    }
+}
+
+/// `$AURIC_SOCKET`, else `auric.sock` in `$XDG_RUNTIME_DIR`, else a per-user
+/// name in the temp directory. Access is limited by the file's permissions.
+pub fn socket_path() -> PathBuf {
+    if let Ok(path) = env::var("AURIC_SOCKET") {
+        let trimmed = path.trim();
+        if !trimmed.is_empty() {
+            return PathBuf::from(trimmed);
+        }
+    }
+    if let Ok(dir) = env::var("XDG_RUNTIME_DIR") {
+        if !dir.trim().is_empty() {
+            return PathBuf::from(dir).join("auric.sock");
+        }
+    }
+    let user = env::var("USER").unwrap_or_else(|_| "user".to_string());
+    env::temp_dir().join(format!("auric-{user}.sock"))
+}
+
+/// Parses one request line, queues its action on `inbox` and waits for the
+/// run loop's answer.
+pub fn handle_line(line: &str, inbox: &RemoteInbox) -> RemoteReply {
+    let command = match RemoteCommand::parse(line) {
+        Ok(command) => command,
+        Err(err) => return RemoteReply::error(format!("{err:#}")),
+    };
+    match &command {
+        RemoteCommand::Add(path) if !Path::new(path).is_dir() => {
+            return RemoteReply::error(format!("not a directory: {path}"));
+        }
+        RemoteCommand::Open(path) if !Path::new(path).exists() => {
+            return RemoteReply::error(format!("no such file or directory: {path}"));
+        }
+        _ => {}
+    }
+    match inbox
+        .submit(command.action())
+        .recv_timeout(command.timeout())
+    {
+        Ok(Ok(message)) => RemoteReply::ok(message),
+        Ok(Err(message)) => RemoteReply::error(message),
+        Err(_) => RemoteReply::error("the instance did not respond in time"),
+    }
+}
+
+/// Listens on the remote-control socket until dropped, then removes it.
+#[cfg(unix)]
+pub struct RemoteServer {
+    path: PathBuf,
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(unix)]
+impl RemoteServer {
+    /// Binds `path`, replacing a stale socket left by an instance that did
+    /// not exit cleanly. Fails if another instance is still listening there.
+    pub fn start(path: &Path, inbox: RemoteInbox) -> Result<Self> {
+        use std::os::unix::net::UnixStream;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                bail!("another auric instance is listening on {}", path.display());
+            }
+            std::fs::remove_file(path)
+                .with_context(|| format!("failed to remove stale socket {}", path.display()))?;
+        }
+        let listener = bind_owner_only(path)
+            .with_context(|| format!("failed to listen on {}", path.display()))?;
+        // Polled so that dropping the server can stop the thread.
+        listener.set_nonblocking(true)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let inbox = inbox.clone();
+                            std::thread::spawn(move || serve_connection(stream, &inbox));
+                        }
+                        Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(Duration::from_millis(50));
+                        }
+                        Err(err) => {
+                            tracing::warn!("remote control accept failed: {err}");
+                            std::thread::sleep(Duration::from_millis(50));
+                        }
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            tracing::warn!("failed to remove {}: {err}", self.path.display());
+        }
+    }
+}
+
+/// Binds the socket in a directory only the owner can enter, makes it
+/// owner-only and then moves it to `path`, so that it never shows up there
+/// with the default permissions.
+#[cfg(unix)]
+fn bind_owner_only(path: &Path) -> Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::os::unix::net::UnixListener;
+
+    let name = path.file_name().context("socket path has no file name")?;
+    let staging = path.with_file_name(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    // Left behind by a crash of an earlier process with the same id.
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .with_context(|| format!("failed to create {}", staging.display()))?;
+    let staged = staging.join("socket");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    Ok(bound?)
+}
+
+/// Reads one request line of at most [`MAX_REQUEST_BYTES`]. A longer line
+/// is an error rather than buffered to the end.
+fn read_request(stream: impl std::io::Read) -> std::result::Result<String, String> {
+    use std::io::{BufRead, BufReader};
+
+    let mut line = Vec::new();
+    BufReader::new(stream.take(MAX_REQUEST_BYTES))
+        .read_until(b'\n', &mut line)
+        .map_err(|err| format!("read failed: {err}"))?;
+    if line.len() as u64 >= MAX_REQUEST_BYTES && !line.ends_with(b"\n") {
+        return Err(format!("request is longer than {MAX_REQUEST_BYTES} bytes"));
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+#[cfg(unix)]
+fn serve_connection(stream: std::os::unix::net::UnixStream, inbox: &RemoteInbox) {
+    use std::io::Write;
+
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(CLIENT_TIMEOUT));
+    let reply = match read_request(&stream) {
+        Ok(line) => handle_line(&line, inbox),
+        Err(err) => {
+            tracing::warn!("remote control {err}");
+            RemoteReply::error(err)
+        }
+    };
+    let encoded = serde_json::to_string(&reply).unwrap_or_default();
+    if let Err(err) = writeln!(&stream, "{encoded}") {
+        tracing::warn!("remote control reply failed: {err}");
+    }
+}
+
+#[cfg(not(unix))]
+pub struct RemoteServer;
+
+#[cfg(not(unix))]
+impl RemoteServer {
+    pub fn start(_path: &Path, _inbox: RemoteInbox) -> Result<Self> {
+        bail!("remote control needs Unix domain sockets, which this platform lacks")
+    }
+}
+
+/// Sends one command to the instance listening on `path` and returns its reply.
+#[cfg(unix)]
+pub fn send_to(path: &Path, command: &RemoteCommand) -> Result<RemoteReply> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path).with_context(|| {
+        format!(
+            "no running auric instance (could not connect to {})",
+            path.display()
+        )
+    })?;
+    stream.set_read_timeout(Some(command.timeout() + CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    writeln!(stream, "{}", command.to_line())?;
+
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .context("no reply from the running instance")?;
+    serde_json::from_str(line.trim()).context("invalid reply from the running instance")
+}
+
+/// Whether a shell is listening on [`socket_path`].
+#[cfg(unix)]
+pub fn instance_running() -> bool {
+    std::os::unix::net::UnixStream::connect(socket_path()).is_ok()
+}
+
+#[cfg(not(unix))]
+pub fn instance_running() -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+pub fn send_to(_path: &Path, _command: &RemoteCommand) -> Result<RemoteReply> {
+    bail!("remote control needs Unix domain sockets, which this platform lacks")
+}
+
+/// Runs `auric <command> [arg]` against the running instance and prints the
+/// reply. Fails when the instance reports an error.
+pub fn run_client(name: &str, args: &[String]) -> Result<()> {
+    let mut line = name.to_string();
+    if name == "add" || name == "open" {
+        let path = args
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("usage: auric {name} <path>"))?;
+        // The instance may run in another directory.
+        let absolute = Path::new(path)
+            .canonicalize()
+            .with_context(|| format!("failed to resolve {path}"))?;
+        line = format!("{name} {}", absolute.display());
+    } else if let Some(extra) = args.first() {
+        bail!("auric {name} takes no arguments (got {extra})");
+    }
+    let command = RemoteCommand::parse(&line)?;
+    let reply = send_to(&socket_path(), &command)?;
+    if !reply.ok {
+        bail!("{}", reply.message);
+    }
+    println!("{}", reply.message);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_round_trip_through_their_line_form() {
+        for command in [
+            RemoteCommand::Play,
+            RemoteCommand::Pause,
+            RemoteCommand::Toggle,
+            RemoteCommand::Stop,
+            RemoteCommand::Next,
+            RemoteCommand::Previous,
+            RemoteCommand::Status,
+            RemoteCommand::Add("/music/with space".to_string()),
+            RemoteCommand::Open("/music/a b.flac".to_string()),
+        ] {
+            assert_eq!(RemoteCommand::parse(&command.to_line()).unwrap(), command);
+        }
+        assert_eq!(
+            RemoteCommand::parse("previous\n").unwrap(),
+            RemoteCommand::Previous
+        );
+        assert!(RemoteCommand::parse("add").is_err());
+        assert!(RemoteCommand::parse("open ").is_err());
+        assert!(RemoteCommand::Open(String::new()).timeout() > RemoteCommand::Next.timeout());
+        assert!(RemoteCommand::parse("next 2").is_err());
+        assert!(RemoteCommand::parse("shutdown").is_err());
+    }
+
+    #[test]
+    fn requests_longer_than_the_limit_are_refused() {
+        assert_eq!(
+            read_request(&b"add /music\nrest"[..]).unwrap(),
+            "add /music\n"
+        );
+        let path = "a".repeat(MAX_REQUEST_BYTES as usize - 6);
+        assert!(read_request(format!("add {path}\n").as_bytes()).is_ok());
+        let err = read_request(format!("add {path}aa\n").as_bytes()).unwrap_err();
+        assert!(err.contains("longer than"));
+        assert!(read_request(std::io::repeat(b'x')).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn client_sends_a_line_and_reads_the_reply() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("auric.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let reply = RemoteReply::ok(format!("got {}", line.trim()));
+            writeln!(&stream, "{}", serde_json::to_string(&reply).unwrap()).unwrap();
+        });
+
+        let reply = send_to(&path, &RemoteCommand::Next).unwrap();
+        assert_eq!(reply, RemoteReply::ok("got next"));
+        server.join().unwrap();
+
+        let missing = dir.path().join("missing.sock");
+        let err = send_to(&missing, &RemoteCommand::Status).unwrap_err();
+        assert!(format!("{err:#}").contains("no running auric instance"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn server_hands_commands_to_the_run_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("auric.sock");
+        // A stale socket file from a crashed instance is replaced.
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let inbox = RemoteInbox::new();
+        let server = RemoteServer::start(&path, inbox.clone()).unwrap();
+        assert!(RemoteServer::start(&path, RemoteInbox::new()).is_err());
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            // The staging directory it was bound in is gone.
+            assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        }
+
+        // Stands in for the shell's run loop.
+        let run_loop = std::thread::spawn(move || loop {
+            if let Some(request) = inbox.drain().into_iter().next() {
+                let outcome = match request.action {
+                    KeyAction::Playback(PlaybackAction::Next) => Ok("Playing: two".to_string()),
+                    other => Err(format!("unexpected {other:?}")),
+                };
+                request.reply.send(outcome).unwrap();
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        });
+
+        let reply = send_to(&path, &RemoteCommand::Next).unwrap();
+        assert_eq!(reply, RemoteReply::ok("Playing: two"));
+        run_loop.join().unwrap();
+
+        let reply = send_to(&path, &RemoteCommand::Add("/no/such/dir".to_string())).unwrap();
+        assert!(!reply.ok);
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
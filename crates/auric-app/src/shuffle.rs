@@ -0,0 +1,198 @@
+//! Picking the next track while shuffle is on, configured under
+//! `[playback]`.
+//!
+//! `random` gives every other queue entry the same chance. `weighted` favors
+//! higher-rated tracks and tracks not played for a while, by as much as
+//! `shuffle_rating_weight` and `shuffle_recency_weight` ask for.
+//!
+//! Either way, tracks already played this pass are left out until the whole
+//! queue has had its turn. That history is saved with the session, so a
+//! restart carries on the same pass.
+
+use crate::{BootstrappedApp, PlaybackConfig};
+use auric_core::PlaybackState;
+use auric_library::db::TrackStatsRow;
+use rand::Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tracks unplayed for this long count as fully rested.
+const RESTED_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShuffleStrategy {
+    Random,
+    Weighted,
+}
+
+impl ShuffleStrategy {
+    pub const NAMES: [&'static str; 2] = ["random", "weighted"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(Self::Random),
+            "weighted" => Some(Self::Weighted),
+            _ => None,
+        }
+    }
+}
+
+/// The chance of picking a track relative to others. Unrated tracks sit
+/// halfway between one and five stars; never-played tracks are fully rested.
+pub fn track_weight(config: &PlaybackConfig, stats: Option<&TrackStatsRow>, now_ms: i64) -> f64 {
+    let rating = match stats.and_then(|s| s.rating) {
+        Some(stars) => f64::from(stars.clamp(1, 5) - 1) / 4.0,
+        None => 0.5,
+    };
+    let rested = match stats.and_then(|s| s.last_played_at_ms) {
+        Some(played) => (now_ms - played).clamp(0, RESTED_MS) as f64 / RESTED_MS as f64,
+        None => 1.0,
+    };
+    1.0 + f64::from(config.shuffle_rating_weight) * rating
+        + f64::from(config.shuffle_recency_weight) * rested
+}
+
+/// A random queue index other than the current one, preferring tracks not
+/// yet played this pass, or `None` when nothing else is queued.
+pub fn pick_next(app: &BootstrappedApp) -> Option<usize> {
+    let queue = &app.playback_state.queue;
+    let current = app.playback_state.session.current_index;
+    let history = &app.playback_state.session.shuffle_history;
+    let others: Vec<usize> = (0..queue.len()).filter(|&i| Some(i) != current).collect();
+    if others.is_empty() {
+        return None;
+    }
+    let unplayed: Vec<usize> = others
+        .iter()
+        .copied()
+        .filter(|&i| !history.contains(&queue[i].track_id))
+        .collect();
+    let candidates = if unplayed.is_empty() {
+        others
+    } else {
+        unplayed
+    };
+    let config = &app.config.playback;
+    let weights: Vec<f64> = match ShuffleStrategy::parse(&config.shuffle_strategy) {
+        Some(ShuffleStrategy::Weighted) => {
+            let now = now_ms();
+            candidates
+                .iter()
+                .map(|&i| {
+                    let stats = app
+                        .db
+                        .get_track_stats(&queue[i].track_id.0.to_string())
+                        .unwrap_or_else(|err| {
+                            tracing::warn!(error = %err, "failed to read track stats for shuffle");
+                            None
+                        });
+                    track_weight(config, stats.as_ref(), now)
+                })
+                .collect()
+        }
+        _ => vec![1.0; candidates.len()],
+    };
+    Some(candidates[weighted_index(&weights, &mut rand::rng())])
+}
+
+/// Adds the current track to the history before moving on to `next`. Moving
+/// to a track already in the history starts a new pass.
+pub fn remember_played(state: &mut PlaybackState, next: usize) {
+    let current = state.current_entry().map(|entry| entry.track_id);
+    let queue = &state.queue;
+    let history = &mut state.session.shuffle_history;
+    history.retain(|id| queue.iter().any(|entry| entry.track_id == *id));
+    if queue
+        .get(next)
+        .is_some_and(|entry| history.contains(&entry.track_id))
+    {
+        history.clear();
+    }
+    history.extend(current);
+}
+
+/// Takes the most recently played track still queued off the history and
+/// returns its queue index.
+pub fn step_back(state: &mut PlaybackState) -> Option<usize> {
+    while let Some(id) = state.session.shuffle_history.pop() {
+        if let Some(index) = state.queue.iter().position(|entry| entry.track_id == id) {
+            return Some(index);
+        }
+    }
+    None
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn weighted_index(weights: &[f64], rng: &mut impl Rng) -> usize {
+    let total: f64 = weights.iter().sum();
+    let mut roll = rng.random_range(0.0..total);
+    for (i, weight) in weights.iter().enumerate() {
+        if roll < *weight {
+            return i;
+        }
+        roll -= weight;
+    }
+    weights.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(rating: Option<u8>, last_played_at_ms: Option<i64>) -> TrackStatsRow {
+        TrackStatsRow {
+            track_id: "t".to_string(),
+            rating,
+            play_count: 1,
+            last_played_at_ms,
+        }
+    }
+
+    #[test]
+    fn weights_favor_rated_and_rested_tracks() {
+        let config = PlaybackConfig {
+            shuffle_rating_weight: 4,
+            shuffle_recency_weight: 2,
+            ..PlaybackConfig::default()
+        };
+        let now = RESTED_MS * 2;
+        assert_eq!(track_weight(&config, None, now), 1.0 + 2.0 + 2.0);
+        assert_eq!(
+            track_weight(&config, Some(&stats(Some(5), Some(now))), now),
+            1.0 + 4.0
+        );
+        assert_eq!(
+            track_weight(&config, Some(&stats(Some(1), Some(0))), now),
+            1.0 + 2.0
+        );
+        assert_eq!(
+            track_weight(&config, Some(&stats(None, Some(now - RESTED_MS / 2))), now),
+            1.0 + 2.0 + 1.0
+        );
+
+        let flat = PlaybackConfig {
+            shuffle_rating_weight: 0,
+            shuffle_recency_weight: 0,
+            ..PlaybackConfig::default()
+        };
+        assert_eq!(track_weight(&flat, Some(&stats(Some(5), None)), now), 1.0);
+    }
+
+    #[test]
+    fn weighted_index_never_picks_a_zero_weight() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            assert_eq!(weighted_index(&[0.0, 3.0, 0.0], &mut rng), 1);
+        }
+        assert_eq!(
+            ShuffleStrategy::parse("weighted"),
+            Some(ShuffleStrategy::Weighted)
+        );
+        assert_eq!(ShuffleStrategy::parse("smart"), None);
+    }
+}
@@ -0,0 +1,432 @@
+//! A Subsonic-compatible server (Navidrome, Airsonic, Gonic, ...) as a
+//! remote library source.
+//!
+//! Syncing copies the server's songs into the `tracks` table under
+//! `subsonic://<song id>` paths, and its playlists into `playlists`, so they
+//! browse, search and queue like local files. When a song starts, the engine
+//! swaps its path for a stream URL with fresh credentials, which the player
+//! reads through `curl`. Requests use `curl` too, like the update check.
+
+use crate::SubsonicConfig;
+use anyhow::{bail, Context, Result};
+use auric_audio::player::curl_url_config;
+use auric_core::TrackId;
+use auric_library::db::{Database, RemotePlaylist};
+use auric_library::TrackRecord;
+use md5::{Digest, Md5};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const PATH_PREFIX: &str = "subsonic://";
+/// Source name the server's playlists are recorded under.
+const SOURCE: &str = "subsonic";
+const API_VERSION: &str = "1.16.1";
+const CLIENT_NAME: &str = "auric";
+/// Albums requested per `getAlbumList2` page.
+const PAGE_SIZE: usize = 500;
+/// The startup sync is skipped when the last one is more recent than this.
+const SYNC_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const SYNCED_AT_SETTING_KEY: &str = "subsonic.synced_at_ms";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub tracks: usize,
+    pub removed: usize,
+    pub playlists: usize,
+}
+
+pub fn is_configured(config: &SubsonicConfig) -> bool {
+    !config.url.trim().is_empty() && !config.username.trim().is_empty()
+}
+
+/// The server's id for a synced song's path.
+pub fn song_id(path: &str) -> Option<&str> {
+    path.strip_prefix(PATH_PREFIX).filter(|id| !id.is_empty())
+}
+
+/// A URL the player can stream the song from.
+pub fn stream_url(config: &SubsonicConfig, song_id: &str) -> String {
+    endpoint_url(config, "stream", &[("id", song_id)])
+}
+
+/// When the library was last synced, in Unix milliseconds.
+pub fn synced_at_ms(db: &Database) -> Option<i64> {
+    db.get_setting_json(SYNCED_AT_SETTING_KEY)
+        .ok()
+        .flatten()
+        .and_then(|value| value.as_i64())
+}
+
+/// The background sync job, returning the status line to show. `only_due`
+/// skips it when the last sync is recent.
+pub fn run_sync(db: &mut Database, config: &SubsonicConfig, only_due: bool) -> Result<String> {
+    if only_due {
+        let due = synced_at_ms(db)
+            .map(|at| now_ms().saturating_sub(at) >= SYNC_INTERVAL.as_millis() as i64)
+            .unwrap_or(true);
+        if !due {
+            return Ok(format!("{} library is up to date", config.name));
+        }
+    }
+    sync(db, config).map(|summary| sync_message(config, &summary))
+}
+
+pub fn sync_message(config: &SubsonicConfig, summary: &SyncSummary) -> String {
+    let mut message = format!(
+        "{}: {} tracks, {} playlists",
+        config.name, summary.tracks, summary.playlists
+    );
+    if summary.removed > 0 {
+        message.push_str(&format!(", {} removed", summary.removed));
+    }
+    message
+}
+
+/// Checks the address and credentials.
+pub fn ping(config: &SubsonicConfig) -> Result<()> {
+    ensure_configured(config)?;
+    call(config, "ping", &[]).map(|_| ())
+}
+
+/// Copies the server's songs and playlists into the library and records
+/// when, so the startup sync can be skipped for a while.
+pub fn sync(db: &mut Database, config: &SubsonicConfig) -> Result<SyncSummary> {
+    ensure_configured(config)?;
+    let summary = sync_with(db, &config.name, |endpoint, params| {
+        call(config, endpoint, params)
+    })?;
+    db.set_setting_json(SYNCED_AT_SETTING_KEY, &json!(now_ms()))?;
+    Ok(summary)
+}
+
+fn ensure_configured(config: &SubsonicConfig) -> Result<()> {
+    if !is_configured(config) {
+        bail!("no Subsonic server configured; set url and username under [subsonic]");
+    }
+    Ok(())
+}
+
+/// The sync itself, with `fetch` answering API calls so tests can stand in
+/// for a server. Songs the server no longer has are removed.
+fn sync_with(
+    db: &mut Database,
+    label: &str,
+    mut fetch: impl FnMut(&str, &[(&str, &str)]) -> Result<Value>,
+) -> Result<SyncSummary> {
+    let mut songs = Vec::new();
+    let size = PAGE_SIZE.to_string();
+    let mut offset = 0;
+    loop {
+        let page_offset = offset.to_string();
+        let page = fetch(
+            "getAlbumList2",
+            &[
+                ("type", "alphabeticalByName"),
+                ("size", &size),
+                ("offset", &page_offset),
+            ],
+        )?;
+        let albums = items(&page["albumList2"]["album"]);
+        for album_id in albums.iter().filter_map(|album| id(album)) {
+            let album = fetch("getAlbum", &[("id", &album_id)])?;
+            songs.extend(
+                items(&album["album"]["song"])
+                    .into_iter()
+                    .filter_map(track_record),
+            );
+        }
+        if albums.len() < PAGE_SIZE {
+            break;
+        }
+        offset += PAGE_SIZE;
+    }
+
+    let seen: HashSet<&str> = songs.iter().map(|song| song.path.as_str()).collect();
+    // Matches `subsonic://...` and nothing on disk.
+    let stale: Vec<String> = db
+        .list_track_paths_under_prefix(PATH_PREFIX.trim_end_matches('/'))?
+        .into_iter()
+        .filter(|path| !seen.contains(path.as_str()))
+        .collect();
+    let removed = db.delete_tracks_by_paths(&stale)?;
+    db.upsert_tracks_batch(&songs)?;
+
+    let mut playlists = Vec::new();
+    let listed = fetch("getPlaylists", &[])?;
+    for playlist in items(&listed["playlists"]["playlist"]) {
+        let Some(remote_id) = id(playlist) else {
+            continue;
+        };
+        let detail = fetch("getPlaylist", &[("id", &remote_id)])?;
+        let mut track_ids = Vec::new();
+        for song_id in items(&detail["playlist"]["entry"])
+            .into_iter()
+            .filter_map(id)
+        {
+            if let Some(track) = db.get_track_by_path(&format!("{PATH_PREFIX}{song_id}"))? {
+                track_ids.push(track.id);
+            }
+        }
+        let name = playlist["name"].as_str().unwrap_or(&remote_id);
+        playlists.push(RemotePlaylist {
+            name: format!("{name} ({label})"),
+            remote_id,
+            track_ids,
+        });
+    }
+    let playlists = db.sync_remote_playlists(SOURCE, &playlists)?;
+    Ok(SyncSummary {
+        tracks: songs.len(),
+        removed,
+        playlists,
+    })
+}
+
+fn track_record(song: &Value) -> Option<TrackRecord> {
+    if song["isVideo"].as_bool() == Some(true) {
+        return None;
+    }
+    let text = |key: &str| song[key].as_str().map(str::to_string);
+    Some(TrackRecord {
+        id: TrackId(uuid::Uuid::new_v4()),
+        path: format!("{PATH_PREFIX}{}", id(song)?),
+        title: text("title"),
+        artist: text("artist"),
+        album: text("album"),
+        duration_ms: song["duration"].as_i64().map(|secs| secs * 1000),
+        sample_rate: song["samplingRate"].as_i64(),
+        channels: song["channelCount"].as_i64(),
+        bit_depth: song["bitDepth"].as_i64(),
+        file_mtime_ms: None,
+    })
+}
+
+/// Ids are strings on most servers and numbers on a few.
+fn id(item: &Value) -> Option<String> {
+    match &item["id"] {
+        Value::String(id) if !id.is_empty() => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Older servers send a single item as an object rather than a one-item
+/// array, and leave the field out when there are none.
+fn items(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Null => Vec::new(),
+        item => vec![item],
+    }
+}
+
+fn password(config: &SubsonicConfig) -> String {
+    std::env::var("AURIC_SUBSONIC_PASSWORD")
+        .ok()
+        .filter(|password| !password.is_empty())
+        .unwrap_or_else(|| config.password.clone())
+}
+
+/// `rest/<endpoint>` with the user, a salted token in place of the
+/// password, and `params`.
+fn endpoint_url(config: &SubsonicConfig, endpoint: &str, params: &[(&str, &str)]) -> String {
+    let salt = uuid::Uuid::new_v4().simple().to_string();
+    let token = Md5::digest(format!("{}{salt}", password(config)));
+    let mut url = format!(
+        "{}/rest/{endpoint}?u={}&t={token:x}&s={salt}&v={API_VERSION}&c={CLIENT_NAME}&f=json",
+        config.url.trim().trim_end_matches('/'),
+        encode(config.username.trim()),
+    );
+    for (key, value) in params {
+        url.push_str(&format!("&{key}={}", encode(value)));
+    }
+    url
+}
+
+/// The URL carries the token and salt, so it reaches curl on stdin rather
+/// than on a command line any local user can read.
+fn call(config: &SubsonicConfig, endpoint: &str, params: &[(&str, &str)]) -> Result<Value> {
+    let mut child = Command::new("curl")
+        .args(["-sSLf", "--max-time", "30"])
+        .args(["--proto", "=http,https", "--proto-redir", "=http,https"])
+        .args(["-K", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run curl")?;
+    let config_file = curl_url_config(&endpoint_url(config, endpoint, params));
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(config_file.as_bytes())
+            .context("failed to run curl")?;
+    }
+    let output = child.wait_with_output().context("failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "failed to reach {}: {}",
+            config.url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let body: Value = serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("{} did not answer like a Subsonic server", config.url))?;
+    response(body)
+}
+
+/// The `subsonic-response` object, or the server's error as an error.
+fn response(mut body: Value) -> Result<Value> {
+    let response = body["subsonic-response"].take();
+    if response.is_null() {
+        bail!("not a Subsonic server response");
+    }
+    if response["status"] != "ok" {
+        let message = response["error"]["message"]
+            .as_str()
+            .unwrap_or("unknown error");
+        bail!("the server refused the request: {message}");
+    }
+    Ok(response)
+}
+
+/// Percent-encodes a query value.
+pub fn encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A two-album server; `albums` limits which it still has.
+    fn server(endpoint: &str, params: &[(&str, &str)], albums: &[&str]) -> Result<Value> {
+        let param = |key: &str| params.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+        Ok(match endpoint {
+            "getAlbumList2" => json!({ "albumList2": { "album": albums
+                .iter()
+                .map(|id| json!({ "id": id }))
+                .collect::<Vec<_>>() } }),
+            "getAlbum" if param("id") == Some("al-1") => json!({ "album": { "song": [
+                { "id": "s1", "title": "One", "artist": "A", "album": "First", "duration": 61 },
+                { "id": "v1", "title": "Clip", "isVideo": true }
+            ] } }),
+            "getAlbum" => {
+                json!({ "album": { "song": { "id": 2, "title": "Two", "samplingRate": 44100 } } })
+            }
+            "getPlaylists" => {
+                json!({ "playlists": { "playlist": [{ "id": "p1", "name": "Road Trip" }] } })
+            }
+            "getPlaylist" => {
+                json!({ "playlist": { "entry": [{ "id": 2 }, { "id": "s1" }, { "id": "gone" }] } })
+            }
+            other => bail!("unexpected call {other}"),
+        })
+    }
+
+    #[test]
+    fn sync_copies_songs_and_playlists_and_drops_what_the_server_lost() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let summary =
+            sync_with(&mut db, "Navidrome", |e, p| server(e, p, &["al-1", "al-2"])).unwrap();
+        assert_eq!(
+            summary,
+            SyncSummary {
+                tracks: 2,
+                removed: 0,
+                playlists: 1
+            }
+        );
+        let one = db.get_track_by_path("subsonic://s1").unwrap().unwrap();
+        assert_eq!(one.duration_ms, Some(61_000));
+        let two = db.get_track_by_path("subsonic://2").unwrap().unwrap();
+        assert_eq!(two.sample_rate, Some(44_100));
+        let playlist = db.list_playlists().unwrap().remove(0);
+        assert_eq!(playlist.name, "Road Trip (Navidrome)");
+        assert_eq!(db.playlist_track_count(&playlist.id).unwrap(), 2);
+
+        let summary = sync_with(&mut db, "Navidrome", |e, p| server(e, p, &["al-1"])).unwrap();
+        assert_eq!(summary.removed, 1);
+        assert_eq!(
+            db.get_track_by_path("subsonic://s1").unwrap().unwrap().id,
+            one.id
+        );
+        assert!(db.get_track_by_path("subsonic://2").unwrap().is_none());
+        assert_eq!(db.playlist_track_count(&playlist.id).unwrap(), 1);
+    }
+
+    #[test]
+    fn requests_carry_a_salted_token_and_errors_are_reported() {
+        let config = SubsonicConfig {
+            url: "https://music.example.com/".to_string(),
+            username: "me & you".to_string(),
+            password: "sesame".to_string(),
+            name: "Subsonic".to_string(),
+        };
+        let url = stream_url(&config, "a/b");
+        assert!(url.starts_with("https://music.example.com/rest/stream?u=me%20%26%20you&t="));
+        assert!(url.ends_with("&id=a%2Fb"));
+        assert!(!url.contains("sesame"));
+        assert_eq!(song_id("subsonic://abc"), Some("abc"));
+        assert_eq!(song_id("/music/a.flac"), None);
+
+        let refused = json!({ "subsonic-response": {
+            "status": "failed", "error": { "code": 40, "message": "Wrong username or password" }
+        } });
+        let err = response(refused).unwrap_err().to_string();
+        assert!(err.contains("Wrong username or password"));
+        assert!(response(json!({ "html": true })).is_err());
+    }
+
+    #[test]
+    fn requests_reach_the_server_with_the_url_passed_on_stdin() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let config = SubsonicConfig {
+            url: format!("http://{}", server.server_addr().to_ip().unwrap()),
+            username: "me \"quoted\"".to_string(),
+            password: "sesame".to_string(),
+            name: "Subsonic".to_string(),
+        };
+        let requested = std::thread::spawn(move || {
+            let request = server.recv().unwrap();
+            let url = request.url().to_string();
+            let body = json!({ "subsonic-response": { "status": "ok" } }).to_string();
+            request
+                .respond(tiny_http::Response::from_string(body))
+                .unwrap();
+            url
+        });
+        ping(&config).unwrap();
+        let url = requested.join().unwrap();
+        assert!(url.starts_with("/rest/ping?u=me%20%22quoted%22&t="));
+        assert!(url.contains("&s="));
+
+        assert!(call(
+            &SubsonicConfig {
+                url: "file:///etc".to_string(),
+                ..config
+            },
+            "ping",
+            &[]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! Noticing system sleep, so playback can pause before it and the audio
+//! stream can be rebuilt after it; streams often come back silent or out of
+//! step otherwise.
+//!
+//! On Linux, logind's `PrepareForSleep` signal is followed through
+//! `dbus-monitor` when it is installed, and a delay lock taken through
+//! `systemd-inhibit` holds the sleep back until playback is paused and the
+//! session saved. Everywhere, a wall clock that jumped
+//! ahead of the monotonic clock (which stops while the system sleeps) counts
+//! as waking up, which also covers sleep that was not announced.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Wall-clock time passing unseen by the monotonic clock beyond this counts
+/// as sleep; smaller gaps are clock adjustments.
+const SLEEP_GAP: Duration = Duration::from_secs(30);
+/// A wake reported again within this long (by the signal and the clock
+/// check both) is the same one.
+const WAKE_REPEAT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepSignal {
+    Sleeping,
+    Woke,
+}
+
+pub struct SuspendWatch {
+    monitor: Option<Child>,
+    /// `systemd-inhibit` holding the delay lock until its stdin closes.
+    delay_lock: Option<Child>,
+    signals: Option<Receiver<SleepSignal>>,
+    last_tick: (Instant, SystemTime),
+    last_wake: Option<Instant>,
+}
+
+impl Default for SuspendWatch {
+    fn default() -> Self {
+        Self {
+            monitor: None,
+            delay_lock: None,
+            signals: None,
+            last_tick: (Instant::now(), SystemTime::now()),
+            last_wake: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for SuspendWatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuspendWatch")
+            .field("listening", &self.monitor.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SuspendWatch {
+    /// Starts following logind's sleep announcements, where there are any.
+    /// Without them only the clock check runs.
+    pub fn listen(&mut self) {
+        if !cfg!(target_os = "linux") || self.monitor.is_some() {
+            return;
+        }
+        let spawned = Command::new("dbus-monitor")
+            .args([
+                "--system",
+                "type='signal',interface='org.freedesktop.login1.Manager',member='PrepareForSleep'",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(err) => {
+                tracing::debug!(error = %err, "dbus-monitor unavailable; noticing sleep by the clock only");
+                return;
+            }
+        };
+        let Some(stdout) = child.stdout.take() else {
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("auric-suspend".into())
+            .spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if let Some(signal) = parse_monitor_line(&line) {
+                        if tx.send(signal).is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+        if let Err(err) = spawned {
+            tracing::warn!(error = %err, "failed to follow sleep signals");
+            let _ = child.kill();
+            let _ = child.wait();
+            return;
+        }
+        self.monitor = Some(child);
+        self.signals = Some(rx);
+        self.delay_lock = take_delay_lock();
+    }
+
+    /// Lets the announced sleep go ahead; call it once playback is paused and
+    /// saved. The lock is taken again after waking.
+    pub fn ready_to_sleep(&mut self) {
+        if let Some(lock) = self.delay_lock.take() {
+            release_delay_lock(lock);
+        }
+    }
+
+    /// Sleep and wake since the last call, in order.
+    pub fn poll(&mut self, now: Instant) -> Vec<SleepSignal> {
+        let mut signals: Vec<SleepSignal> = self
+            .signals
+            .as_ref()
+            .map(|rx| rx.try_iter().collect())
+            .unwrap_or_default();
+        let wall = SystemTime::now();
+        if slept(self.last_tick, (now, wall)) {
+            signals.push(SleepSignal::Woke);
+        }
+        self.last_tick = (now, wall);
+        signals.retain(|signal| {
+            if *signal != SleepSignal::Woke {
+                return true;
+            }
+            let repeat = self
+                .last_wake
+                .is_some_and(|last| now.saturating_duration_since(last) < WAKE_REPEAT);
+            self.last_wake = Some(now);
+            !repeat
+        });
+        if self.monitor.is_some()
+            && self.delay_lock.is_none()
+            && signals.contains(&SleepSignal::Woke)
+        {
+            self.delay_lock = take_delay_lock();
+        }
+        signals
+    }
+}
+
+impl Drop for SuspendWatch {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.monitor.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.ready_to_sleep();
+    }
+}
+
+/// Asks logind to hold sleep back, for at most its `InhibitDelayMaxSec`,
+/// until the lock is released. The command `systemd-inhibit` runs is `cat`,
+/// which ends when its stdin closes, so nothing outlives auric.
+fn take_delay_lock() -> Option<Child> {
+    Command::new("systemd-inhibit")
+        .args([
+            "--what=sleep",
+            "--who=auric",
+            "--why=Pause playback",
+            "--mode=delay",
+            "cat",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| {
+            tracing::debug!(error = %err, "systemd-inhibit unavailable; sleep will not wait for a pause");
+        })
+        .ok()
+}
+
+fn release_delay_lock(mut lock: Child) {
+    drop(lock.stdin.take());
+    let _ = lock.wait();
+}
+
+/// Whether the wall clock moved on by more than the monotonic clock did,
+/// which only happens while the system sleeps (or the clock is set).
+pub fn slept(before: (Instant, SystemTime), after: (Instant, SystemTime)) -> bool {
+    let awake = after.0.saturating_duration_since(before.0);
+    let passed = after.1.duration_since(before.1).unwrap_or_default();
+    passed.saturating_sub(awake) >= SLEEP_GAP
+}
+
+/// `dbus-monitor` prints a signal's header and then its argument on a line
+/// of its own; `PrepareForSleep` carries true going to sleep and false on
+/// waking.
+fn parse_monitor_line(line: &str) -> Option<SleepSignal> {
+    match line.trim() {
+        "boolean true" => Some(SleepSignal::Sleeping),
+        "boolean false" => Some(SleepSignal::Woke),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_shows_as_wall_time_the_monotonic_clock_missed() {
+        let mono = Instant::now();
+        let wall = SystemTime::now();
+        let tick = Duration::from_millis(100);
+        assert!(!slept((mono, wall), (mono + tick, wall + tick)));
+        assert!(!slept(
+            (mono, wall),
+            (mono + tick, wall + Duration::from_secs(5))
+        ));
+        assert!(slept(
+            (mono, wall),
+            (mono + tick, wall + Duration::from_secs(3600))
+        ));
+        // The clock being set back is not sleep.
+        assert!(!slept(
+            (mono, wall),
+            (mono + tick, wall - Duration::from_secs(3600))
+        ));
+
+        assert_eq!(
+            parse_monitor_line("   boolean true"),
+            Some(SleepSignal::Sleeping)
+        );
+        assert_eq!(
+            parse_monitor_line("   boolean false"),
+            Some(SleepSignal::Woke)
+        );
+        assert_eq!(
+            parse_monitor_line("signal time=1 sender=:1.3 -> destination=(null destination) member=PrepareForSleep"),
+            None
+        );
+    }
+}
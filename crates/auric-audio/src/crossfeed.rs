@@ -0,0 +1,161 @@
+//! Bauer stereophonic-to-binaural crossfeed for headphone listening.
+//!
+//! On speakers each ear hears both channels, the far one later and with less
+//! treble; on headphones it hears only its own, which makes hard-panned
+//! recordings tiring. The filter feeds each channel's low end into the other
+//! ear and lowers its own low end to match, the way the `bs2b` library does,
+//! so the overall tonal balance stays the same.
+
+use crate::DspNode;
+
+/// Where the fed-across low end rolls off.
+pub const CUTOFF_HZ: f64 = 700.0;
+/// How much quieter the fed-across signal is than the direct one at low
+/// frequencies.
+pub const FEED_DB: f64 = 4.5;
+
+/// A crossfeed stage for interleaved stereo. Other channel counts pass
+/// through untouched.
+pub struct Crossfeed {
+    enabled: bool,
+    stereo: bool,
+    a0_lo: f32,
+    b1_lo: f32,
+    a0_hi: f32,
+    a1_hi: f32,
+    b1_hi: f32,
+    /// Brings a centred low note back to the level it came in at.
+    makeup: f32,
+    lo: [f32; 2],
+    hi: [f32; 2],
+    previous: [f32; 2],
+}
+
+impl Crossfeed {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let fs = f64::from(sample_rate.max(1));
+        let gain_lo_db = FEED_DB * -5.0 / 6.0 - 3.0;
+        let gain_hi_db = FEED_DB / 6.0 - 3.0;
+        let gain_lo = 10f64.powf(gain_lo_db / 20.0);
+        let gain_hi = 1.0 - 10f64.powf(gain_hi_db / 20.0);
+        let cutoff_hi = CUTOFF_HZ * 2f64.powf((gain_lo_db - 20.0 * gain_hi.log10()) / 12.0);
+
+        let x_lo = (-2.0 * std::f64::consts::PI * CUTOFF_HZ / fs).exp();
+        let x_hi = (-2.0 * std::f64::consts::PI * cutoff_hi / fs).exp();
+        Self {
+            enabled: false,
+            stereo: channels == 2,
+            a0_lo: (gain_lo * (1.0 - x_lo)) as f32,
+            b1_lo: x_lo as f32,
+            a0_hi: (1.0 - gain_hi * (1.0 - x_hi)) as f32,
+            a1_hi: -x_hi as f32,
+            b1_hi: x_hi as f32,
+            makeup: (1.0 / (gain_lo + 1.0 - gain_hi)) as f32,
+            lo: [0.0; 2],
+            hi: [0.0; 2],
+            previous: [0.0; 2],
+        }
+    }
+
+    /// Turns the filter on or off, starting it from silence when it comes
+    /// on so stale state does not click.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.enabled {
+            self.lo = [0.0; 2];
+            self.hi = [0.0; 2];
+            self.previous = [0.0; 2];
+        }
+        self.enabled = enabled;
+    }
+
+    /// Filters `interleaved` in place. Allocation-free, so it can run in the
+    /// output callback.
+    pub fn process(&mut self, interleaved: &mut [f32]) {
+        if !self.enabled || !self.stereo {
+            return;
+        }
+        for frame in interleaved.chunks_exact_mut(2) {
+            for (c, &input) in frame.iter().enumerate() {
+                self.lo[c] = self.a0_lo * input + self.b1_lo * self.lo[c];
+                self.hi[c] =
+                    self.a0_hi * input + self.a1_hi * self.previous[c] + self.b1_hi * self.hi[c];
+                self.previous[c] = input;
+            }
+            frame[0] = (self.hi[0] + self.lo[1]) * self.makeup;
+            frame[1] = (self.hi[1] + self.lo[0]) * self.makeup;
+        }
+    }
+}
+
+impl DspNode for Crossfeed {
+    fn id(&self) -> &'static str {
+        "crossfeed"
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The peak of each output channel once a sine in `left` and `right`
+    /// has settled.
+    fn peaks(freq: f32, left: f32, right: f32) -> [f32; 2] {
+        let mut crossfeed = Crossfeed::new(48_000, 2);
+        crossfeed.set_enabled(true);
+        let mut samples = (0..48_000)
+            .flat_map(|i| {
+                let s = (2.0 * std::f32::consts::PI * freq * i as f32 / 48_000.0).sin();
+                [s * left, s * right]
+            })
+            .collect::<Vec<_>>();
+        crossfeed.process(&mut samples);
+        let settled = &samples[samples.len() / 2..];
+        let peak = |c: usize| {
+            settled
+                .iter()
+                .skip(c)
+                .step_by(2)
+                .fold(0.0f32, |m, s| m.max(s.abs()))
+        };
+        [peak(0), peak(1)]
+    }
+
+    #[test]
+    fn feeds_the_low_end_across_and_keeps_the_treble_apart() {
+        let [direct, across] = peaks(100.0, 1.0, 0.0);
+        let feed_db = 20.0 * (direct / across).log10();
+        assert!((feed_db - FEED_DB as f32).abs() < 0.5, "{feed_db}");
+
+        let [direct, across] = peaks(8_000.0, 1.0, 0.0);
+        assert!(across < direct * 0.1, "{direct} {across}");
+    }
+
+    #[test]
+    fn centred_sound_keeps_its_level() {
+        let [left, right] = peaks(100.0, 0.5, 0.5);
+        assert!(
+            (left - 0.5).abs() < 0.02 && (right - 0.5).abs() < 0.02,
+            "{left} {right}"
+        );
+    }
+
+    #[test]
+    fn passes_audio_through_when_off_or_not_stereo() {
+        let original = vec![0.5, -0.25, 0.1, 0.0];
+        let mut off = Crossfeed::new(44_100, 2);
+        let mut samples = original.clone();
+        off.process(&mut samples);
+        assert_eq!(samples, original);
+        assert!(!off.enabled());
+
+        let mut mono = Crossfeed::new(44_100, 1);
+        mono.set_enabled(true);
+        mono.process(&mut samples);
+        assert_eq!(samples, original);
+        assert_eq!(mono.id(), "crossfeed");
+    }
+}
@@ -1,3 +1,5 @@
+pub mod crossfeed;
+pub mod limiter;
 pub mod player;
 
 use cpal::traits::{DeviceTrait, HostTrait};
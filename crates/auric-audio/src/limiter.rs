@@ -0,0 +1,154 @@
+//! A soft-knee peak limiter, the last stage before the output.
+//!
+//! Peaks are eased towards the ceiling from a few dB below it and never pass
+//! it, and the gain comes back over [`RELEASE_MS`] once they are gone. A big
+//! volume offset or a loud master is turned down rather than clipped, while
+//! audio that stays under the knee passes through untouched.
+
+use crate::DspNode;
+
+/// The highest peak let through, in dBFS.
+pub const CEILING_DB: f32 = -1.0;
+/// How far below the ceiling limiting starts, in dB.
+pub const KNEE_DB: f32 = 6.0;
+/// How long the gain takes to mostly recover after a peak.
+pub const RELEASE_MS: f32 = 150.0;
+
+pub struct Limiter {
+    enabled: bool,
+    channels: usize,
+    ceiling: f32,
+    knee: f32,
+    /// How much of the gain reduction is left after each frame.
+    release: f32,
+    gain: f32,
+}
+
+impl Limiter {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let ceiling = 10f32.powf(CEILING_DB / 20.0);
+        let release_frames = RELEASE_MS / 1000.0 * sample_rate.max(1) as f32;
+        Self {
+            enabled: false,
+            channels: usize::from(channels.max(1)),
+            ceiling,
+            knee: ceiling * 10f32.powf(-KNEE_DB / 20.0),
+            release: (-1.0 / release_frames).exp(),
+            gain: 1.0,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.enabled {
+            self.gain = 1.0;
+        }
+        self.enabled = enabled;
+    }
+
+    /// The gain that brings a peak of `peak` under the curve: unity below
+    /// the knee, then easing into the ceiling.
+    fn wanted_gain(&self, peak: f32) -> f32 {
+        if peak <= self.knee {
+            return 1.0;
+        }
+        let range = self.ceiling - self.knee;
+        let limited = self.knee + range * ((peak - self.knee) / range).tanh();
+        limited / peak
+    }
+
+    /// Limits `interleaved` in place. Allocation-free, so it can run in the
+    /// output callback.
+    pub fn process(&mut self, interleaved: &mut [f32]) {
+        if !self.enabled {
+            return;
+        }
+        for frame in interleaved.chunks_exact_mut(self.channels) {
+            let peak = frame
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+            let wanted = self.wanted_gain(peak);
+            self.gain = if wanted < self.gain {
+                wanted
+            } else {
+                wanted + (self.gain - wanted) * self.release
+            };
+            for sample in frame {
+                *sample *= self.gain;
+            }
+        }
+    }
+}
+
+impl DspNode for Limiter {
+    fn id(&self) -> &'static str {
+        "limiter"
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let s =
+                    amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 48_000.0).sin();
+                [s, s]
+            })
+            .collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn keeps_loud_audio_under_the_ceiling() {
+        let mut limiter = Limiter::new(48_000, 2);
+        limiter.set_enabled(true);
+        let mut samples = sine(4.0, 48_000);
+        limiter.process(&mut samples);
+        let ceiling = 10f32.powf(CEILING_DB / 20.0);
+        assert!(peak(&samples) <= ceiling + 1e-6, "{}", peak(&samples));
+        assert!(peak(&samples) > ceiling * 0.95, "{}", peak(&samples));
+    }
+
+    #[test]
+    fn leaves_quiet_audio_alone_and_recovers_after_a_peak() {
+        let mut limiter = Limiter::new(48_000, 2);
+        limiter.set_enabled(true);
+        let quiet = sine(0.25, 4_800);
+        let mut samples = quiet.clone();
+        limiter.process(&mut samples);
+        assert_eq!(samples, quiet);
+
+        let mut burst = sine(2.0, 4_800);
+        limiter.process(&mut burst);
+        // A second after the burst the gain is all but back.
+        let mut after = sine(0.25, 48_000);
+        limiter.process(&mut after);
+        assert!(
+            peak(&after[after.len() - 4_800..]) > 0.249,
+            "{}",
+            peak(&after)
+        );
+    }
+
+    #[test]
+    fn passes_audio_through_when_off() {
+        let mut limiter = Limiter::new(44_100, 1);
+        let original = vec![1.5, -2.0, 0.5];
+        let mut samples = original.clone();
+        limiter.process(&mut samples);
+        assert_eq!(samples, original);
+        assert!(!limiter.enabled());
+        assert_eq!(limiter.id(), "limiter");
+    }
+}
@@ -1,5 +1,7 @@
 use std::fs::File;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::io::{Read, Write};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -8,30 +10,79 @@ use std::time::{Duration, Instant};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
-use symphonia::core::formats::FormatOptions;
-use symphonia::core::io::MediaSourceStream;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::{MediaSource, MediaSourceStream, ReadOnlySource};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
+use symphonia::core::units::Time;
+
+use crate::crossfeed::Crossfeed;
+use crate::limiter::Limiter;
 
 #[derive(Debug, Clone)]
 pub enum PlayerCommand {
-    Load { path: String },
+    Load {
+        path: String,
+        span: PlaySpan,
+    },
     Pause,
     Resume,
     Stop,
-    SetVolume { volume: f32 },
+    /// Jumps within the current track; ignored when nothing is loaded.
+    Seek {
+        position_ms: u64,
+    },
+    SetVolume {
+        volume: f32,
+    },
+    /// Turns the headphone crossfeed filter on or off.
+    SetCrossfeed {
+        enabled: bool,
+    },
+    /// Turns the output limiter on or off.
+    SetLimiter {
+        enabled: bool,
+    },
+    /// Rebuilds the output stream at the current position and leaves it
+    /// paused, for after system sleep; ignored when nothing is loaded.
+    Reopen,
     Shutdown,
 }
 
+/// The part of a track to play: from `start_ms` until `end_ms`, or the end
+/// of the file. Reaching `end_ms` finishes the track as the end would.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlaySpan {
+    pub start_ms: u64,
+    pub end_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone)]
 pub enum PlayerEvent {
-    Playing { path: String },
+    Playing {
+        path: String,
+    },
     Paused,
     Resumed,
     Stopped,
-    Position { position_ms: u64, duration_ms: u64 },
+    Position {
+        position_ms: u64,
+        duration_ms: u64,
+    },
     TrackFinished,
-    Error { message: String },
+    Error {
+        message: String,
+    },
+    /// The output device went away mid-track. Playback is paused at
+    /// `position_ms` until it returns or playback is resumed elsewhere.
+    DeviceLost {
+        device: String,
+        position_ms: u64,
+    },
+    /// The lost device is back and the track carries on where it paused.
+    DeviceRestored {
+        device: String,
+    },
 }
 
 impl std::fmt::Debug for PlayerHandle {
@@ -69,8 +120,14 @@ impl PlayerHandle {
     }
 
     pub fn load(&self, path: &str) {
+        self.load_span(path, PlaySpan::default());
+    }
+
+    /// Loads `path` to play only `span` of it.
+    pub fn load_span(&self, path: &str, span: PlaySpan) {
         let _ = self.cmd_tx.send(PlayerCommand::Load {
             path: path.to_string(),
+            span,
         });
     }
 
@@ -86,10 +143,26 @@ impl PlayerHandle {
         let _ = self.cmd_tx.send(PlayerCommand::Stop);
     }
 
+    pub fn seek(&self, position_ms: u64) {
+        let _ = self.cmd_tx.send(PlayerCommand::Seek { position_ms });
+    }
+
+    pub fn reopen(&self) {
+        let _ = self.cmd_tx.send(PlayerCommand::Reopen);
+    }
+
     pub fn set_volume(&self, volume: f32) {
         let _ = self.cmd_tx.send(PlayerCommand::SetVolume { volume });
     }
 
+    pub fn set_crossfeed(&self, enabled: bool) {
+        let _ = self.cmd_tx.send(PlayerCommand::SetCrossfeed { enabled });
+    }
+
+    pub fn set_limiter(&self, enabled: bool) {
+        let _ = self.cmd_tx.send(PlayerCommand::SetLimiter { enabled });
+    }
+
     pub fn poll_events(&self) -> Vec<PlayerEvent> {
         let rx = self.event_rx.lock().expect("event_rx lock poisoned");
         let mut events = Vec::new();
@@ -124,7 +197,11 @@ fn player_thread(
     event_tx: mpsc::Sender<PlayerEvent>,
     viz_buf: Arc<Mutex<Vec<f32>>>,
 ) {
-    let volume = Arc::new(AtomicU32::new(f32::to_bits(1.0)));
+    let controls = OutputControls {
+        volume: Arc::new(AtomicU32::new(f32::to_bits(1.0))),
+        crossfeed: Arc::new(AtomicBool::new(false)),
+        limiter: Arc::new(AtomicBool::new(false)),
+    };
 
     loop {
         let cmd = match cmd_rx.recv() {
@@ -133,28 +210,66 @@ fn player_thread(
         };
 
         match cmd {
-            PlayerCommand::Load { path } => {
-                let result = play_track(&path, &cmd_rx, &event_tx, &volume, &viz_buf);
-                match result {
-                    PlayResult::Finished | PlayResult::Stopped | PlayResult::Error => {}
-                    PlayResult::LoadNew(new_path) => {
-                        let mut current_path = new_path;
-                        while let PlayResult::LoadNew(next) = play_track(
-                            &current_path,
-                            &cmd_rx,
-                            &event_tx,
-                            &volume,
-                            &viz_buf,
-                        ) {
-                            current_path = next;
+            PlayerCommand::Load { path, span } => {
+                let (mut current_path, mut current_span) = (path, span);
+                let mut result = play_track(
+                    &current_path,
+                    span,
+                    false,
+                    &cmd_rx,
+                    &event_tx,
+                    &controls,
+                    &viz_buf,
+                );
+                loop {
+                    result = match result {
+                        PlayResult::Finished | PlayResult::Stopped | PlayResult::Error => break,
+                        PlayResult::LoadNew(next, span) => {
+                            (current_path, current_span) = (next, span);
+                            play_track(
+                                &current_path,
+                                span,
+                                false,
+                                &cmd_rx,
+                                &event_tx,
+                                &controls,
+                                &viz_buf,
+                            )
                         }
-                    }
-                    PlayResult::Shutdown => return,
-                    PlayResult::Disconnected => return,
+                        PlayResult::Reopen {
+                            position_ms,
+                            restored,
+                            paused,
+                        } => {
+                            if let Some(device) = restored {
+                                let _ = event_tx.send(PlayerEvent::DeviceRestored { device });
+                            }
+                            let span = PlaySpan {
+                                start_ms: position_ms,
+                                ..current_span
+                            };
+                            play_track(
+                                &current_path,
+                                span,
+                                paused,
+                                &cmd_rx,
+                                &event_tx,
+                                &controls,
+                                &viz_buf,
+                            )
+                        }
+                        PlayResult::Shutdown | PlayResult::Disconnected => return,
+                    };
                 }
             }
             PlayerCommand::SetVolume { volume: v } => {
-                volume.store(v.to_bits(), Ordering::Relaxed);
+                controls.volume.store(v.to_bits(), Ordering::Relaxed);
+            }
+            PlayerCommand::SetCrossfeed { enabled } => {
+                controls.crossfeed.store(enabled, Ordering::Relaxed);
+            }
+            PlayerCommand::SetLimiter { enabled } => {
+                controls.limiter.store(enabled, Ordering::Relaxed);
             }
             PlayerCommand::Shutdown => return,
             _ => {}
@@ -162,15 +277,88 @@ fn player_thread(
     }
 }
 
+/// Output settings that carry over from one track's stream to the next,
+/// read by the output callback.
+struct OutputControls {
+    volume: Arc<AtomicU32>,
+    crossfeed: Arc<AtomicBool>,
+    limiter: Arc<AtomicBool>,
+}
+
 enum PlayResult {
     Finished,
     Stopped,
     Error,
-    LoadNew(String),
+    LoadNew(String, PlaySpan),
+    /// Open the same track again from `position_ms` on the current default
+    /// device, after the last one went away or the system slept. `restored`
+    /// names the device when it was the lost one coming back.
+    Reopen {
+        position_ms: u64,
+        restored: Option<String>,
+        paused: bool,
+    },
     Shutdown,
     Disconnected,
 }
 
+/// How long the output callback may go uncalled while playing before the
+/// device counts as gone; some backends report nothing when it disappears.
+const DEVICE_STALL: Duration = Duration::from_secs(3);
+/// How often the default device is checked while waiting for a lost one.
+const DEVICE_POLL: Duration = Duration::from_secs(1);
+
+/// Notices an output device that stopped taking audio: cpal reported it
+/// gone, or the stream callback stopped running.
+struct DeviceWatch {
+    lost: Arc<AtomicBool>,
+    beats: Arc<AtomicU64>,
+    last_beat: u64,
+    last_beat_at: Instant,
+}
+
+impl DeviceWatch {
+    fn new() -> Self {
+        Self {
+            lost: Arc::new(AtomicBool::new(false)),
+            beats: Arc::new(AtomicU64::new(0)),
+            last_beat: 0,
+            last_beat_at: Instant::now(),
+        }
+    }
+
+    /// Starts the stall timer over, for when the stream starts playing.
+    fn restart(&mut self) {
+        self.last_beat_at = Instant::now();
+    }
+
+    fn gone(&mut self) -> bool {
+        let beat = self.beats.load(Ordering::Relaxed);
+        if beat != self.last_beat {
+            self.last_beat = beat;
+            self.last_beat_at = Instant::now();
+        }
+        self.lost.load(Ordering::Acquire) || self.last_beat_at.elapsed() >= DEVICE_STALL
+    }
+}
+
+/// Where the listener is in the track: what was decoded, less what still
+/// waits in the ring buffer unplayed.
+fn heard_position_ms(
+    decoded_ms: u64,
+    buffered_samples: usize,
+    device_rate: u32,
+    device_channels: u16,
+) -> u64 {
+    let samples_per_sec = u64::from(device_rate) * u64::from(device_channels);
+    decoded_ms.saturating_sub(buffered_samples as u64 * 1000 / samples_per_sec.max(1))
+}
+
+/// A stable identifier for `device`, to recognise it when it comes back.
+fn device_key(device: &cpal::Device) -> Option<String> {
+    device.id().ok().map(|id| id.to_string())
+}
+
 /// Linear interpolation resampling for sample rate conversion.
 /// Operates on interleaved multi-channel audio.
 fn resample_linear(samples: &[f32], channels: u16, ratio: f64) -> Vec<f32> {
@@ -210,15 +398,115 @@ fn downmix_stereo_to_mono(samples: &[f32]) -> Vec<f32> {
         .collect()
 }
 
+/// Seeks to the nearest point the format can reach quickly and returns the
+/// position there in frames, or `None` when the file cannot seek.
+fn seek_track(
+    format: &mut dyn FormatReader,
+    track: &symphonia::core::formats::Track,
+    position_ms: u64,
+) -> Option<u64> {
+    let seeked = format
+        .seek(
+            SeekMode::Coarse,
+            SeekTo::Time {
+                time: Time::from(position_ms as f64 / 1000.0),
+                track_id: Some(track.id),
+            },
+        )
+        .map_err(|err| tracing::warn!(error = %err, "seek failed"))
+        .ok()?;
+    let sample_rate = track.codec_params.sample_rate? as f64;
+    let time = match track.codec_params.time_base {
+        Some(base) => base.calc_time(seeked.actual_ts),
+        None => return Some(seeked.actual_ts),
+    };
+    Some(((time.seconds as f64 + time.frac) * sample_rate) as u64)
+}
+
+/// Gives the output callback a moment to drop the audio queued before a
+/// seek, so the new position is heard straight away.
+fn wait_for_flush(flush: &AtomicBool) {
+    let deadline = Instant::now() + Duration::from_millis(250);
+    while flush.load(Ordering::Acquire) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(2));
+    }
+}
+
+/// A curl config file (`curl -K -`) that fetches `url`.
+pub fn curl_url_config(url: &str) -> String {
+    format!(
+        "url = \"{}\"\n",
+        url.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Opens a local file, or streams an `http(s)` URL through `curl` so podcast
+/// episodes can play before they are downloaded. Streams cannot seek.
+///
+/// The URL goes to curl on stdin rather than its command line, where other
+/// local users could read the credentials a Subsonic stream URL carries.
+fn open_source(path: &str) -> std::io::Result<Box<dyn MediaSource>> {
+    if !is_url(path) {
+        return Ok(Box::new(File::open(path)?));
+    }
+    let mut child = Command::new("curl")
+        .args([
+            "-sSLf",
+            "--proto",
+            "=http,https",
+            "--proto-redir",
+            "=http,https",
+        ])
+        .args(["-K", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(curl_url_config(path).as_bytes())?;
+    }
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| std::io::Error::other("curl has no output"))?;
+    Ok(Box::new(ReadOnlySource::new(HttpStream { child, stdout })))
+}
+
+/// The body of a `curl` download, read as it arrives. Dropping it stops the
+/// download.
+struct HttpStream {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl Read for HttpStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl Drop for HttpStream {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
 fn play_track(
     path: &str,
+    span: PlaySpan,
+    start_paused: bool,
     cmd_rx: &mpsc::Receiver<PlayerCommand>,
     event_tx: &mpsc::Sender<PlayerEvent>,
-    volume: &Arc<AtomicU32>,
+    controls: &OutputControls,
     viz_buf: &Arc<Mutex<Vec<f32>>>,
 ) -> PlayResult {
-    let file = match File::open(path) {
-        Ok(f) => f,
+    let source = match open_source(path) {
+        Ok(source) => source,
         Err(e) => {
             let _ = event_tx.send(PlayerEvent::Error {
                 message: format!("failed to open file: {e}"),
@@ -227,9 +515,13 @@ fn play_track(
         }
     };
 
-    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mss = MediaSourceStream::new(source, Default::default());
     let mut hint = Hint::new();
-    if let Some(ext) = std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
+    let name = path.split(['?', '#']).next().unwrap_or(path);
+    if let Some(ext) = std::path::Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+    {
         hint.with_extension(ext);
     }
 
@@ -249,10 +541,7 @@ fn play_track(
     };
 
     let mut format = probed.format;
-    let track = match format
-        .default_track()
-        .or_else(|| format.tracks().first())
-    {
+    let track = match format.default_track().or_else(|| format.tracks().first()) {
         Some(t) => t.clone(),
         None => {
             let _ = event_tx.send(PlayerEvent::Error {
@@ -291,10 +580,9 @@ fn play_track(
         .map(|frames| frames * 1000 / file_sample_rate as u64)
         .unwrap_or(0);
 
-    let mut decoder = match symphonia::default::get_codecs().make(
-        &track.codec_params,
-        &DecoderOptions::default(),
-    ) {
+    let mut decoder = match symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+    {
         Ok(d) => d,
         Err(e) => {
             let _ = event_tx.send(PlayerEvent::Error {
@@ -305,6 +593,13 @@ fn play_track(
     };
 
     let track_id = track.id;
+    let mut decoded_samples: u64 = 0;
+    if span.start_ms > 0 {
+        if let Some(frames) = seek_track(&mut *format, &track, span.start_ms) {
+            decoder.reset();
+            decoded_samples = frames;
+        }
+    }
 
     // Query device for its preferred output configuration
     let host = cpal::default_host();
@@ -328,6 +623,13 @@ fn play_track(
         }
     };
 
+    let device_id = device_key(&device);
+    let device_name = device
+        .description()
+        .map(|desc| desc.name().to_string())
+        .unwrap_or_else(|_| "the output device".to_string());
+    let mut watch = DeviceWatch::new();
+
     let device_sample_rate = default_config.sample_rate();
     let device_channels = default_config.channels();
 
@@ -347,7 +649,16 @@ fn play_track(
         buffer_size: cpal::BufferSize::Default,
     };
 
-    let vol_ref = Arc::clone(volume);
+    let vol_ref = Arc::clone(&controls.volume);
+    let crossfeed_ref = Arc::clone(&controls.crossfeed);
+    let mut crossfeed = Crossfeed::new(device_sample_rate, device_channels);
+    let limiter_ref = Arc::clone(&controls.limiter);
+    let mut limiter = Limiter::new(device_sample_rate, device_channels);
+    // Set after a seek so the callback drops audio from before it.
+    let flush = Arc::new(AtomicBool::new(false));
+    let flush_ref = Arc::clone(&flush);
+    let beats_ref = Arc::clone(&watch.beats);
+    let lost_ref = Arc::clone(&watch.lost);
 
     // Consumer lives in the cpal callback: lock-free, allocation-free
     let mut consumer = Some(consumer);
@@ -356,14 +667,35 @@ fn play_track(
         {
             let mut consumer = consumer.take().expect("consumer already taken");
             move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                beats_ref.fetch_add(1, Ordering::Relaxed);
+                if flush_ref.swap(false, Ordering::AcqRel) {
+                    while consumer.pop().is_ok() {}
+                }
+                for sample in data.iter_mut() {
+                    *sample = consumer.pop().unwrap_or(0.0);
+                }
+                crossfeed.set_enabled(crossfeed_ref.load(Ordering::Relaxed));
+                crossfeed.process(data);
                 let vol = f32::from_bits(vol_ref.load(Ordering::Relaxed));
                 for sample in data.iter_mut() {
-                    *sample = consumer.pop().unwrap_or(0.0) * vol;
+                    *sample *= vol;
+                }
+                limiter.set_enabled(limiter_ref.load(Ordering::Relaxed));
+                limiter.process(data);
+                for sample in data.iter_mut() {
+                    // Volume offsets can boost past unity; clip rather than wrap.
+                    *sample = sample.clamp(-1.0, 1.0);
                 }
             }
         },
-        |err| {
-            eprintln!("cpal stream error: {err}");
+        move |err| {
+            tracing::error!(error = %err, "output stream error");
+            if matches!(
+                err,
+                cpal::StreamError::DeviceNotAvailable | cpal::StreamError::StreamInvalidated
+            ) {
+                lost_ref.store(true, Ordering::Release);
+            }
         },
         None,
     ) {
@@ -376,42 +708,128 @@ fn play_track(
         }
     };
 
-    if let Err(e) = stream.play() {
-        let _ = event_tx.send(PlayerEvent::Error {
-            message: format!("failed to start playback: {e}"),
+    if start_paused {
+        // Some backends start a stream as soon as it is built.
+        if let Err(err) = stream.pause() {
+            tracing::warn!(error = %err, "failed to pause the reopened output stream");
+        }
+        let _ = event_tx.send(PlayerEvent::Paused);
+    } else {
+        if let Err(e) = stream.play() {
+            let _ = event_tx.send(PlayerEvent::Error {
+                message: format!("failed to start playback: {e}"),
+            });
+            return PlayResult::Error;
+        }
+        let _ = event_tx.send(PlayerEvent::Playing {
+            path: path.to_string(),
         });
-        return PlayResult::Error;
     }
 
-    let _ = event_tx.send(PlayerEvent::Playing {
-        path: path.to_string(),
-    });
-
     // Throttle threshold: 1 second of device-rate audio in the ring buffer
     let one_sec_samples = device_sample_rate as usize * device_channels as usize;
-    let mut paused = false;
+    let mut paused = start_paused;
     let mut last_position_report = Instant::now();
-    let mut decoded_samples: u64 = 0;
     let mut sample_buf: Option<SampleBuffer<f32>> = None;
+    // Set while the device is gone: when it was last looked for, and where
+    // playback picks up again.
+    let mut device_wait: Option<(Instant, u64)> = None;
+    watch.restart();
 
     loop {
         // Check commands
         if paused {
+            if let Some((checked, position_ms)) = device_wait {
+                if checked.elapsed() >= DEVICE_POLL {
+                    let back = cpal::default_host()
+                        .default_output_device()
+                        .and_then(|device| device_key(&device));
+                    if back.is_some() && back == device_id {
+                        tracing::info!(device = %device_name, "output device is back; resuming");
+                        return PlayResult::Reopen {
+                            position_ms,
+                            restored: Some(device_name),
+                            paused: false,
+                        };
+                    }
+                    device_wait = Some((Instant::now(), position_ms));
+                }
+            }
             match cmd_rx.recv_timeout(Duration::from_millis(50)) {
                 Ok(PlayerCommand::Resume) => {
+                    if let Some((_, position_ms)) = device_wait {
+                        // Carry on with whatever device is the default now.
+                        if cpal::default_host().default_output_device().is_none() {
+                            let _ = event_tx.send(PlayerEvent::Error {
+                                message: "no output device available".into(),
+                            });
+                            continue;
+                        }
+                        return PlayResult::Reopen {
+                            position_ms,
+                            restored: None,
+                            paused: false,
+                        };
+                    }
                     paused = false;
-                    let _ = stream.play();
+                    watch.restart();
+                    if let Err(err) = stream.play() {
+                        tracing::warn!(error = %err, "failed to resume output stream");
+                    }
+                    wait_for_flush(&flush);
                     let _ = event_tx.send(PlayerEvent::Resumed);
                 }
                 Ok(PlayerCommand::Stop) => {
                     let _ = event_tx.send(PlayerEvent::Stopped);
                     return PlayResult::Stopped;
                 }
-                Ok(PlayerCommand::Load { path: new_path }) => {
-                    return PlayResult::LoadNew(new_path);
+                Ok(PlayerCommand::Load {
+                    path: new_path,
+                    span,
+                }) => {
+                    return PlayResult::LoadNew(new_path, span);
                 }
                 Ok(PlayerCommand::SetVolume { volume: v }) => {
-                    volume.store(v.to_bits(), Ordering::Relaxed);
+                    controls.volume.store(v.to_bits(), Ordering::Relaxed);
+                }
+                Ok(PlayerCommand::SetCrossfeed { enabled }) => {
+                    controls.crossfeed.store(enabled, Ordering::Relaxed);
+                }
+                Ok(PlayerCommand::SetLimiter { enabled }) => {
+                    controls.limiter.store(enabled, Ordering::Relaxed);
+                }
+                Ok(PlayerCommand::Seek { position_ms }) => {
+                    if let Some((checked, _)) = device_wait {
+                        device_wait = Some((checked, position_ms));
+                        continue;
+                    }
+                    // The stream is stopped, so the flush waits for resume.
+                    if let Some(frames) = seek_track(&mut *format, &track, position_ms) {
+                        decoder.reset();
+                        decoded_samples = frames;
+                        flush.store(true, Ordering::Release);
+                    }
+                }
+                Ok(PlayerCommand::Reopen) => {
+                    let position_ms = match device_wait {
+                        Some((_, position_ms)) => position_ms,
+                        None => heard_position_ms(
+                            decoded_samples * 1000 / file_sample_rate as u64,
+                            // A pending flush drops what is buffered.
+                            if flush.load(Ordering::Acquire) {
+                                0
+                            } else {
+                                ring_capacity - producer.slots()
+                            },
+                            device_sample_rate,
+                            device_channels,
+                        ),
+                    };
+                    return PlayResult::Reopen {
+                        position_ms,
+                        restored: None,
+                        paused: true,
+                    };
                 }
                 Ok(PlayerCommand::Shutdown) => return PlayResult::Shutdown,
                 Ok(PlayerCommand::Pause) => {}
@@ -425,7 +843,9 @@ fn play_track(
         match cmd_rx.try_recv() {
             Ok(PlayerCommand::Pause) => {
                 paused = true;
-                let _ = stream.pause();
+                if let Err(err) = stream.pause() {
+                    tracing::warn!(error = %err, "failed to pause output stream");
+                }
                 let _ = event_tx.send(PlayerEvent::Paused);
                 continue;
             }
@@ -433,11 +853,40 @@ fn play_track(
                 let _ = event_tx.send(PlayerEvent::Stopped);
                 return PlayResult::Stopped;
             }
-            Ok(PlayerCommand::Load { path: new_path }) => {
-                return PlayResult::LoadNew(new_path);
+            Ok(PlayerCommand::Load {
+                path: new_path,
+                span,
+            }) => {
+                return PlayResult::LoadNew(new_path, span);
             }
             Ok(PlayerCommand::SetVolume { volume: v }) => {
-                volume.store(v.to_bits(), Ordering::Relaxed);
+                controls.volume.store(v.to_bits(), Ordering::Relaxed);
+            }
+            Ok(PlayerCommand::SetCrossfeed { enabled }) => {
+                controls.crossfeed.store(enabled, Ordering::Relaxed);
+            }
+            Ok(PlayerCommand::SetLimiter { enabled }) => {
+                controls.limiter.store(enabled, Ordering::Relaxed);
+            }
+            Ok(PlayerCommand::Seek { position_ms }) => {
+                if let Some(frames) = seek_track(&mut *format, &track, position_ms) {
+                    decoder.reset();
+                    decoded_samples = frames;
+                    flush.store(true, Ordering::Release);
+                    wait_for_flush(&flush);
+                }
+            }
+            Ok(PlayerCommand::Reopen) => {
+                return PlayResult::Reopen {
+                    position_ms: heard_position_ms(
+                        decoded_samples * 1000 / file_sample_rate as u64,
+                        ring_capacity - producer.slots(),
+                        device_sample_rate,
+                        device_channels,
+                    ),
+                    restored: None,
+                    paused: true,
+                };
             }
             Ok(PlayerCommand::Shutdown) => return PlayResult::Shutdown,
             Ok(PlayerCommand::Resume) => {}
@@ -445,6 +894,28 @@ fn play_track(
             Err(mpsc::TryRecvError::Disconnected) => return PlayResult::Disconnected,
         }
 
+        if watch.gone() {
+            // Pause where the listener stopped hearing, not where decoding
+            // got to: the ring buffer still holds what never played.
+            let position_ms = heard_position_ms(
+                decoded_samples * 1000 / file_sample_rate as u64,
+                ring_capacity - producer.slots(),
+                device_sample_rate,
+                device_channels,
+            );
+            if let Err(err) = stream.pause() {
+                tracing::debug!(error = %err, "failed to pause the lost output stream");
+            }
+            paused = true;
+            device_wait = Some((Instant::now(), position_ms));
+            tracing::warn!(device = %device_name, position_ms, "output device lost; pausing");
+            let _ = event_tx.send(PlayerEvent::DeviceLost {
+                device: device_name.clone(),
+                position_ms,
+            });
+            continue;
+        }
+
         // Throttle if ring buffer has more than 1 second of audio
         let available = ring_capacity - producer.slots();
         if available > one_sec_samples {
@@ -452,8 +923,18 @@ fn play_track(
             continue;
         }
 
-        // Decode next packet
-        let packet = match format.next_packet() {
+        // Decode next packet; the end of the span counts as the end of the file
+        let past_end = span
+            .end_ms
+            .is_some_and(|end_ms| decoded_samples * 1000 / file_sample_rate as u64 >= end_ms);
+        let next_packet = if past_end {
+            Err(symphonia::core::errors::Error::IoError(
+                std::io::ErrorKind::UnexpectedEof.into(),
+            ))
+        } else {
+            format.next_packet()
+        };
+        let packet = match next_packet {
             Ok(p) => p,
             Err(symphonia::core::errors::Error::IoError(ref e))
                 if e.kind() == std::io::ErrorKind::UnexpectedEof =>
@@ -461,7 +942,7 @@ fn play_track(
                 // EOF: wait for ring buffer to drain, then signal track finished
                 loop {
                     let buffered = ring_capacity - producer.slots();
-                    if buffered == 0 {
+                    if buffered == 0 || watch.gone() {
                         break;
                     }
                     thread::sleep(Duration::from_millis(20));
@@ -484,7 +965,7 @@ fn play_track(
         let decoded = match decoder.decode(&packet) {
             Ok(d) => d,
             Err(symphonia::core::errors::Error::DecodeError(msg)) => {
-                eprintln!("decode error (skipping): {msg}");
+                tracing::warn!(path, "decode error (skipping packet): {msg}");
                 continue;
             }
             Err(e) => {
@@ -536,9 +1017,13 @@ fn play_track(
             after_resample
         };
 
-        // Push processed samples into the lock-free ring buffer
+        // Push processed samples into the lock-free ring buffer; a lost
+        // device stops draining it, and the check above then pauses.
         for &sample in final_samples {
             while producer.push(sample).is_err() {
+                if watch.gone() {
+                    break;
+                }
                 std::thread::sleep(Duration::from_millis(1));
             }
         }
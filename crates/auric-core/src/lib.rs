@@ -49,6 +49,14 @@ impl FeatureId {
         }
     }
 
+    /// Features that reach out over the network; offline mode keeps them off.
+    pub fn uses_network(self) -> bool {
+        matches!(
+            self,
+            FeatureId::RemoteMetadata | FeatureId::P2PSync | FeatureId::P2PStream
+        )
+    }
+
     pub fn from_key(raw: &str) -> Option<Self> {
         match raw.trim().to_ascii_lowercase().as_str() {
             "metadata" => Some(FeatureId::Metadata),
@@ -144,6 +152,34 @@ pub enum ImageProtocol {
     ITerm2Inline,
 }
 
+/// Whether the scanner picks `path` up, going by its extension.
+pub fn is_supported_audio_file(path: &std::path::Path) -> bool {
+    let ext = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => ext,
+        None => return false,
+    };
+
+    matches!(
+        ext.to_ascii_lowercase().as_str(),
+        "flac"
+            | "wav"
+            | "wave"
+            | "aiff"
+            | "aif"
+            | "mp3"
+            | "m4a"
+            | "aac"
+            | "alac"
+            | "ogg"
+            | "opus"
+            | "wma"
+            | "ape"
+            | "wv"
+            | "dsf"
+            | "dff"
+    )
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TrackId(pub Uuid);
 
@@ -171,6 +207,23 @@ pub struct PlaybackSession {
     pub volume: f32,
     pub shuffle: bool,
     pub repeat: RepeatMode,
+    /// When the current album ends, queue a random other album in order.
+    #[serde(default)]
+    pub random_album: bool,
+    /// When the queue runs out, append tracks by similar artists.
+    #[serde(default)]
+    pub radio: bool,
+    /// Take each track off the queue once playback moves past it.
+    #[serde(default)]
+    pub consume: bool,
+    /// Added to every track's volume, in dB, on top of its own offset.
+    #[serde(default)]
+    pub queue_volume_offset_db: f32,
+    /// Tracks already played in this shuffle pass, oldest first. Shuffle
+    /// skips them until every track has had its turn, and Previous walks
+    /// back through them.
+    #[serde(default)]
+    pub shuffle_history: Vec<TrackId>,
 }
 
 impl Default for PlaybackSession {
@@ -182,6 +235,11 @@ impl Default for PlaybackSession {
             volume: 1.0,
             shuffle: false,
             repeat: RepeatMode::Off,
+            random_album: false,
+            radio: false,
+            consume: false,
+            queue_volume_offset_db: 0.0,
+            shuffle_history: Vec::new(),
         }
     }
 }
@@ -255,6 +313,8 @@ mod tests {
             assert_eq!(FeatureId::from_key(key), Some(feature));
         }
         assert_eq!(FeatureId::from_key("unknown"), None);
+        assert!(FeatureId::RemoteMetadata.uses_network());
+        assert!(!FeatureId::Artwork.uses_network());
     }
 
     #[test]
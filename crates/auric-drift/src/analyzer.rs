@@ -46,6 +46,25 @@ impl DriftAnalyzer {
         })
     }
 
+    /// Estimates only the tempo of the file at `path`, skipping the other
+    /// features.
+    pub fn analyze_bpm(&self, path: &Path) -> Result<f32, AnalyzerError> {
+        let sample_rate = detect_sample_rate(path)?;
+        let samples = load_mono_samples(path)?;
+        if samples.is_empty() {
+            return Err(AnalyzerError::EmptyAudio);
+        }
+        Ok(detect_bpm(&samples, sample_rate))
+    }
+
+    /// [`Self::analyze_bpm`] for each of `paths` in parallel, in order.
+    pub fn analyze_bpm_batch(&self, paths: &[&Path]) -> Vec<Result<f32, AnalyzerError>> {
+        paths
+            .par_iter()
+            .map(|path| self.analyze_bpm(path))
+            .collect()
+    }
+
     pub fn analyze_batch(
         &self,
         paths: &[&Path],
@@ -65,7 +84,10 @@ impl DriftAnalyzer {
                 let result = self.analyze_file(path);
                 let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
                 if let Some(cb) = &progress_callback {
-                    cb(AnalysisProgress { completed: done, total });
+                    cb(AnalysisProgress {
+                        completed: done,
+                        total,
+                    });
                 }
                 (idx, result)
             })
@@ -74,7 +96,9 @@ impl DriftAnalyzer {
 }
 
 impl Default for DriftAnalyzer {
-    fn default() -> Self { Self::new() }
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 fn detect_sample_rate(path: &Path) -> Result<f32, AnalyzerError> {
@@ -86,14 +110,24 @@ fn detect_sample_rate(path: &Path) -> Result<f32, AnalyzerError> {
     }
 
     let probed = symphonia::default::get_probe()
-        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
         .map_err(|e| AnalyzerError::UnsupportedFormat(e.to_string()))?;
 
-    let track = probed.format.tracks().iter()
+    let track = probed
+        .format
+        .tracks()
+        .iter()
         .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
         .ok_or_else(|| AnalyzerError::UnsupportedFormat("no audio track found".to_string()))?;
 
-    track.codec_params.sample_rate
+    track
+        .codec_params
+        .sample_rate
         .map(|sr| sr as f32)
         .ok_or_else(|| AnalyzerError::Decode("no sample rate in codec params".to_string()))
 }
@@ -107,11 +141,18 @@ fn load_mono_samples(path: &Path) -> Result<Vec<f32>, AnalyzerError> {
     }
 
     let probed = symphonia::default::get_probe()
-        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
         .map_err(|e| AnalyzerError::UnsupportedFormat(e.to_string()))?;
 
     let mut format = probed.format;
-    let track = format.tracks().iter()
+    let track = format
+        .tracks()
+        .iter()
         .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
         .ok_or_else(|| AnalyzerError::UnsupportedFormat("no audio track".to_string()))?;
 
@@ -130,11 +171,16 @@ fn load_mono_samples(path: &Path) -> Result<Vec<f32>, AnalyzerError> {
         let packet = match format.next_packet() {
             Ok(p) => p,
             Err(symphonia::core::errors::Error::IoError(ref e))
-                if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break
+            }
             Err(_) => break,
         };
 
-        if packet.track_id() != track_id { continue; }
+        if packet.track_id() != track_id {
+            continue;
+        }
 
         let decoded = match decoder.decode(&packet) {
             Ok(d) => d,
@@ -150,7 +196,9 @@ fn load_mono_samples(path: &Path) -> Result<Vec<f32>, AnalyzerError> {
         let ch = channels.max(1);
         for frame_idx in 0..n_frames {
             let offset = frame_idx * ch;
-            if offset >= interleaved.len() { break; }
+            if offset >= interleaved.len() {
+                break;
+            }
             let mut sum = 0.0f32;
             for c in 0..ch.min(interleaved.len() - offset) {
                 sum += interleaved[offset + c];
@@ -183,17 +231,23 @@ fn compute_dynamic_range(samples: &[f32]) -> f32 {
         let segment = &samples[offset..offset + segment_size];
         let sum_sq: f64 = segment.iter().map(|s| (*s as f64) * (*s as f64)).sum();
         let rms = (sum_sq / segment_size as f64).sqrt() as f32;
-        if rms > 1e-8 { segment_rms.push(rms); }
+        if rms > 1e-8 {
+            segment_rms.push(rms);
+        }
         offset += segment_size;
     }
 
-    if segment_rms.len() < 5 { return 0.5; }
+    if segment_rms.len() < 5 {
+        return 0.5;
+    }
 
     segment_rms.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
     let low = segment_rms[segment_rms.len() / 10];
     let high = segment_rms[segment_rms.len() * 9 / 10];
 
-    if low <= 1e-10 { return 1.0; }
+    if low <= 1e-10 {
+        return 1.0;
+    }
 
     let range_db = 20.0 * (high / low).log10();
     clamp_normalize(range_db, 0.0, 40.0)
@@ -224,7 +278,9 @@ fn compute_spectral_centroid(samples: &[f32], sample_rate: f32) -> f32 {
         offset += HOP_SIZE * 4;
     }
 
-    if frame_count == 0 { return 0.5; }
+    if frame_count == 0 {
+        return 0.5;
+    }
     let avg = (centroid_sum / frame_count as f64) as f32;
     let nyquist = sample_rate / 2.0;
     clamp_normalize(avg, 200.0, nyquist * 0.6)
@@ -239,25 +295,33 @@ fn detect_bpm(samples: &[f32], sample_rate: f32) -> f32 {
     let mut offset = 0;
     while offset + FFT_SIZE <= samples.len() {
         let mut magnitudes = compute_fft_magnitudes(&samples[offset..offset + FFT_SIZE], &window);
-        for m in &mut magnitudes { *m = m.sqrt(); }
+        for m in &mut magnitudes {
+            *m = m.sqrt();
+        }
 
         let mut flux = 0.0f32;
         for bin in 0..half_fft {
             let diff = magnitudes[bin] - prev_magnitudes[bin];
-            if diff > 0.0 { flux += diff; }
+            if diff > 0.0 {
+                flux += diff;
+            }
         }
         onset_strength.push(flux);
         prev_magnitudes = magnitudes;
         offset += HOP_SIZE;
     }
 
-    if onset_strength.len() < 16 { return 120.0; }
+    if onset_strength.len() < 16 {
+        return 120.0;
+    }
 
     let onset_rate = sample_rate / HOP_SIZE as f32;
     let min_lag = (onset_rate * 60.0 / 200.0) as usize;
     let max_lag = ((onset_rate * 60.0 / 60.0) as usize).min(onset_strength.len() / 2);
 
-    if min_lag >= max_lag { return 120.0; }
+    if min_lag >= max_lag {
+        return 120.0;
+    }
 
     let mut best_lag = min_lag;
     let mut best_corr = f64::NEG_INFINITY;
@@ -266,7 +330,8 @@ fn detect_bpm(samples: &[f32], sample_rate: f32) -> f32 {
         let n = onset_strength.len() - lag;
         let corr: f64 = (0..n)
             .map(|i| onset_strength[i] as f64 * onset_strength[i + lag] as f64)
-            .sum::<f64>() / n as f64;
+            .sum::<f64>()
+            / n as f64;
         if corr > best_corr {
             best_corr = corr;
             best_lag = lag;
@@ -274,9 +339,13 @@ fn detect_bpm(samples: &[f32], sample_rate: f32) -> f32 {
     }
 
     let bpm = onset_rate * 60.0 / best_lag as f32;
-    if bpm > 160.0 { bpm / 2.0 }
-    else if bpm < 70.0 { bpm * 2.0 }
-    else { bpm }
+    if bpm > 160.0 {
+        bpm / 2.0
+    } else if bpm < 70.0 {
+        bpm * 2.0
+    } else {
+        bpm
+    }
 }
 
 fn detect_key(samples: &[f32], sample_rate: f32) -> i32 {
@@ -291,7 +360,9 @@ fn detect_key(samples: &[f32], sample_rate: f32) -> i32 {
         let magnitudes = compute_fft_magnitudes(&samples[offset..offset + FFT_SIZE], &window);
         for (bin, &mag) in magnitudes[1..half_fft].iter().enumerate() {
             let freq = (bin + 1) as f32 * bin_width;
-            if !(65.0..=2000.0).contains(&freq) { continue; }
+            if !(65.0..=2000.0).contains(&freq) {
+                continue;
+            }
             let note_num = 12.0 * (freq / 440.0).log2() + 69.0;
             let pitch_class = (note_num.round() as i32).rem_euclid(12) as usize;
             chromagram[pitch_class] += mag as f64;
@@ -300,27 +371,43 @@ fn detect_key(samples: &[f32], sample_rate: f32) -> i32 {
         offset += HOP_SIZE * 4;
     }
 
-    if frame_count == 0 { return 0; }
-    for c in &mut chromagram { *c /= frame_count as f64; }
+    if frame_count == 0 {
+        return 0;
+    }
+    for c in &mut chromagram {
+        *c /= frame_count as f64;
+    }
     match_key_profile(&chromagram)
 }
 
 fn match_key_profile(chromagram: &[f64; 12]) -> i32 {
-    let major: [f64; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
-    let minor: [f64; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+    let major: [f64; 12] = [
+        6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+    ];
+    let minor: [f64; 12] = [
+        6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+    ];
 
     let mut best_key = 0i32;
     let mut best_corr = f64::NEG_INFINITY;
 
     for root in 0..12 {
         let mut rotated = [0.0f64; 12];
-        for i in 0..12 { rotated[i] = chromagram[(i + root) % 12]; }
+        for i in 0..12 {
+            rotated[i] = chromagram[(i + root) % 12];
+        }
 
         let major_corr = pearson(&rotated, &major);
         let minor_corr = pearson(&rotated, &minor);
 
-        if major_corr > best_corr { best_corr = major_corr; best_key = root as i32; }
-        if minor_corr > best_corr { best_corr = minor_corr; best_key = root as i32 + 12; }
+        if major_corr > best_corr {
+            best_corr = major_corr;
+            best_key = root as i32;
+        }
+        if minor_corr > best_corr {
+            best_corr = minor_corr;
+            best_key = root as i32 + 12;
+        }
     }
     best_key
 }
@@ -335,7 +422,9 @@ fn pearson(x: &[f64; 12], y: &[f64; 12]) -> f64 {
 
     let num = n * sum_xy - sum_x * sum_y;
     let den = ((n * sum_x2 - sum_x * sum_x) * (n * sum_y2 - sum_y * sum_y)).sqrt();
-    if den < 1e-10 { return 0.0; }
+    if den < 1e-10 {
+        return 0.0;
+    }
     num / den
 }
 
@@ -343,7 +432,9 @@ fn compute_fft_magnitudes(frame: &[f32], window: &[f32]) -> Vec<f32> {
     let n = frame.len();
     let half = n / 2;
 
-    let mut windowed: Vec<Complex<f32>> = frame.iter().zip(window)
+    let mut windowed: Vec<Complex<f32>> = frame
+        .iter()
+        .zip(window)
         .map(|(s, w)| Complex::new(s * w, 0.0))
         .collect();
 
@@ -355,10 +446,12 @@ fn compute_fft_magnitudes(frame: &[f32], window: &[f32]) -> Vec<f32> {
 }
 
 fn hann_window(size: usize) -> Vec<f32> {
-    (0..size).map(|i| {
-        let t = std::f32::consts::PI * 2.0 * i as f32 / size as f32;
-        0.5 * (1.0 - t.cos())
-    }).collect()
+    (0..size)
+        .map(|i| {
+            let t = std::f32::consts::PI * 2.0 * i as f32 / size as f32;
+            0.5 * (1.0 - t.cos())
+        })
+        .collect()
 }
 
 pub fn clamp_normalize(value: f32, min: f32, max: f32) -> f32 {
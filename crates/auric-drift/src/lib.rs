@@ -1,16 +1,18 @@
-pub mod types;
+pub mod analyzer;
 pub mod camelot;
-pub mod genre;
 pub mod engine;
-pub mod analyzer;
+pub mod genre;
+pub mod loudness;
+pub mod silence;
+pub mod types;
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
 
+pub use analyzer::DriftAnalyzer;
+pub use camelot::CamelotWheel;
+pub use engine::DriftEngine;
 pub use types::{
     AnalysisProgress, AnalyzerError, DriftConfig, DriftFeatures, DriftHistory, ShuffleMode,
     TrackSnapshot,
 };
-pub use engine::DriftEngine;
-pub use analyzer::DriftAnalyzer;
-pub use camelot::CamelotWheel;
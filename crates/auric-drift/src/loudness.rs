@@ -0,0 +1,342 @@
+//! EBU R128 loudness: integrated loudness (ITU-R BS.1770) and true peak.
+//!
+//! The meter K-weights each channel, measures 400 ms blocks every 100 ms and
+//! gates them first at -70 LUFS and then 10 LU below the level of what is
+//! left. True peak comes from 4x oversampling, which catches the overs that
+//! land between samples.
+
+use crate::types::AnalyzerError;
+use rayon::prelude::*;
+use std::f64::consts::PI;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+
+/// The ReplayGain 2.0 reference level.
+pub const REFERENCE_LUFS: f32 = -18.0;
+
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+const OVERSAMPLE: usize = 4;
+const TAPS_PER_PHASE: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+    pub integrated_lufs: f32,
+    /// In dB relative to full scale (dBTP).
+    pub true_peak_db: f32,
+}
+
+/// What [`measure_file`] found: the measured loudness, and any ReplayGain
+/// gains already written to the file's tags.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileLoudness {
+    pub loudness: Loudness,
+    pub replaygain_track_db: Option<f32>,
+    pub replaygain_album_db: Option<f32>,
+}
+
+/// Decodes the whole file at `path` and measures it.
+pub fn measure_file(path: &Path) -> Result<FileLoudness, AnalyzerError> {
+    let file = std::fs::File::open(path)?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| AnalyzerError::UnsupportedFormat(e.to_string()))?;
+
+    let (mut replaygain_track_db, mut replaygain_album_db) = (None, None);
+    let mut read_tags = |revision: Option<&MetadataRevision>| {
+        for tag in revision.map(|r| r.tags()).unwrap_or_default() {
+            match tag.std_key {
+                Some(StandardTagKey::ReplayGainTrackGain) => {
+                    replaygain_track_db =
+                        replaygain_track_db.or(parse_gain(&tag.value.to_string()));
+                }
+                Some(StandardTagKey::ReplayGainAlbumGain) => {
+                    replaygain_album_db =
+                        replaygain_album_db.or(parse_gain(&tag.value.to_string()));
+                }
+                _ => {}
+            }
+        }
+    };
+    if let Some(metadata) = probed.metadata.get() {
+        read_tags(metadata.current());
+    }
+    let mut format = probed.format;
+    read_tags(format.metadata().current());
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| AnalyzerError::UnsupportedFormat("no audio track".to_string()))?;
+    let track_id = track.id;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| AnalyzerError::Decode("no sample rate in codec params".to_string()))?;
+    let channels = track.codec_params.channels.map(|c| c.count()).unwrap_or(2);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| AnalyzerError::Decode(e.to_string()))?;
+    let mut meter = LoudnessMeter::new(sample_rate, channels);
+
+    while let Ok(packet) = format.next_packet() {
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let Ok(decoded) = decoder.decode(&packet) else {
+            continue;
+        };
+        let mut sample_buf = SampleBuffer::<f32>::new(decoded.frames() as u64, *decoded.spec());
+        sample_buf.copy_interleaved_ref(decoded);
+        meter.push_interleaved(sample_buf.samples());
+    }
+
+    Ok(FileLoudness {
+        loudness: meter.finish().ok_or(AnalyzerError::EmptyAudio)?,
+        replaygain_track_db,
+        replaygain_album_db,
+    })
+}
+
+/// [`measure_file`] for each of `paths` in parallel, in order.
+pub fn measure_batch(paths: &[&Path]) -> Vec<Result<FileLoudness, AnalyzerError>> {
+    paths.par_iter().map(|path| measure_file(path)).collect()
+}
+
+/// `-6.54 dB` as written in ReplayGain tags.
+fn parse_gain(value: &str) -> Option<f32> {
+    value
+        .split_whitespace()
+        .next()?
+        .parse::<f32>()
+        .ok()
+        .filter(|gain| gain.is_finite())
+}
+
+/// Measures interleaved audio fed to it in any number of pieces.
+pub struct LoudnessMeter {
+    channels: usize,
+    weights: Vec<f64>,
+    filters: Vec<[Biquad; 2]>,
+    peaks: Vec<TruePeak>,
+    /// Frames per 100 ms step; four steps make a gating block.
+    step_frames: usize,
+    step_sum: f64,
+    step_filled: usize,
+    /// Weighted mean square of each complete 100 ms step.
+    steps: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let fs = f64::from(sample_rate.max(1));
+        Self {
+            channels,
+            weights: (0..channels).map(|c| channel_weight(c, channels)).collect(),
+            filters: (0..channels).map(|_| k_weighting(fs)).collect(),
+            peaks: (0..channels).map(|_| TruePeak::new()).collect(),
+            step_frames: ((fs / 10.0).round() as usize).max(1),
+            step_sum: 0.0,
+            step_filled: 0,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn push_interleaved(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            let mut energy = 0.0;
+            for (c, &sample) in frame.iter().enumerate() {
+                self.peaks[c].push(sample);
+                if self.weights[c] == 0.0 {
+                    continue;
+                }
+                let [shelf, high_pass] = &mut self.filters[c];
+                let weighted = high_pass.process(shelf.process(f64::from(sample)));
+                energy += self.weights[c] * weighted * weighted;
+            }
+            self.step_sum += energy;
+            self.step_filled += 1;
+            if self.step_filled == self.step_frames {
+                self.steps.push(self.step_sum / self.step_frames as f64);
+                self.step_sum = 0.0;
+                self.step_filled = 0;
+            }
+        }
+    }
+
+    /// The measurement, or `None` when everything was gated out as silence
+    /// or the audio was shorter than one block.
+    pub fn finish(&self) -> Option<Loudness> {
+        let blocks = self
+            .steps
+            .windows(4)
+            .map(|w| w.iter().sum::<f64>() / 4.0)
+            .filter(|&z| block_loudness(z) > ABSOLUTE_GATE_LUFS)
+            .collect::<Vec<_>>();
+        if blocks.is_empty() {
+            return None;
+        }
+        let relative_gate = block_loudness(mean(&blocks)) + RELATIVE_GATE_LU;
+        let gated = blocks
+            .into_iter()
+            .filter(|&z| block_loudness(z) > relative_gate)
+            .collect::<Vec<_>>();
+        let peak = self.peaks.iter().map(|p| p.max).fold(0.0f32, f32::max);
+        Some(Loudness {
+            integrated_lufs: block_loudness(mean(&gated)) as f32,
+            true_peak_db: 20.0 * peak.max(f32::MIN_POSITIVE).log10(),
+        })
+    }
+}
+
+/// The loudness of several measured pieces played back to back, such as an
+/// album's tracks, each weighted by its length. Gating is approximated by
+/// each piece's own.
+pub fn combine(parts: &[(Loudness, f64)]) -> Option<Loudness> {
+    let total: f64 = parts.iter().map(|(_, weight)| weight).sum();
+    if parts.is_empty() || total <= 0.0 {
+        return None;
+    }
+    let energy = parts
+        .iter()
+        .map(|(l, weight)| weight * 10f64.powf((f64::from(l.integrated_lufs) + 0.691) / 10.0))
+        .sum::<f64>()
+        / total;
+    Some(Loudness {
+        integrated_lufs: block_loudness(energy) as f32,
+        true_peak_db: parts
+            .iter()
+            .map(|(l, _)| l.true_peak_db)
+            .fold(f32::NEG_INFINITY, f32::max),
+    })
+}
+
+fn block_loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.max(f64::MIN_POSITIVE).log10()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// BS.1770 channel weights: surround channels count 1.41 and the LFE of a
+/// 5.1 layout (L R C LFE Ls Rs) is left out.
+fn channel_weight(channel: usize, channels: usize) -> f64 {
+    match (channels, channel) {
+        (6, 3) => 0.0,
+        (5 | 6, 4..) | (5, 3) => 1.41,
+        _ => 1.0,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// The two K-weighting stages, a high shelf and a high pass, designed for
+/// `fs` so rates other than 48 kHz measure the same.
+fn k_weighting(fs: f64) -> [Biquad; 2] {
+    let (f0, gain_db, q) = (
+        1_681.974_450_955_533,
+        3.999_843_853_973_347,
+        0.707_175_236_955_419_6,
+    );
+    let k = (PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+    let (f0, q) = (38.135_470_876_024_44, 0.500_327_037_323_877_3);
+    let k = (PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+    [shelf, high_pass]
+}
+
+/// Peak of one channel upsampled 4x through a windowed-sinc interpolator.
+struct TruePeak {
+    taps: Vec<f32>,
+    history: [f32; TAPS_PER_PHASE],
+    max: f32,
+}
+
+impl TruePeak {
+    fn new() -> Self {
+        Self {
+            taps: (0..OVERSAMPLE * TAPS_PER_PHASE)
+                .map(interpolation_tap)
+                .collect(),
+            history: [0.0; TAPS_PER_PHASE],
+            max: 0.0,
+        }
+    }
+
+    fn push(&mut self, sample: f32) {
+        self.history.rotate_right(1);
+        self.history[0] = sample;
+        for phase in 0..OVERSAMPLE {
+            let y: f32 = (0..TAPS_PER_PHASE)
+                .map(|k| self.taps[phase + OVERSAMPLE * k] * self.history[k])
+                .sum();
+            self.max = self.max.max(y.abs());
+        }
+        self.max = self.max.max(sample.abs());
+    }
+}
+
+/// Coefficient `m` of the Hann-windowed sinc low pass at the original
+/// Nyquist frequency.
+fn interpolation_tap(m: usize) -> f32 {
+    let len = (OVERSAMPLE * TAPS_PER_PHASE) as f64;
+    let t = (m as f64 - (len - 1.0) / 2.0) / OVERSAMPLE as f64;
+    let sinc = if t == 0.0 {
+        1.0
+    } else {
+        (PI * t).sin() / (PI * t)
+    };
+    let window = 0.5 - 0.5 * (2.0 * PI * (m as f64 + 0.5) / len).cos();
+    (sinc * window) as f32
+}
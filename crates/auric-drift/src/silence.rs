@@ -0,0 +1,136 @@
+//! Leading and trailing silence: how long a track takes to start, and how
+//! long it runs on after its last sound.
+//!
+//! A frame counts as sound when any channel's sample peaks above the
+//! threshold; everything before the first such frame and after the last is
+//! silence. Quiet passages in between are left alone.
+
+use crate::types::AnalyzerError;
+use rayon::prelude::*;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Silence {
+    pub leading_ms: u64,
+    pub trailing_ms: u64,
+    pub duration_ms: u64,
+}
+
+/// Decodes the whole file at `path` and finds the silence at either end,
+/// counting samples below `threshold_db` (dBFS) as silent.
+pub fn measure_file(path: &Path, threshold_db: f32) -> Result<Silence, AnalyzerError> {
+    let file = std::fs::File::open(path)?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| AnalyzerError::UnsupportedFormat(e.to_string()))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| AnalyzerError::UnsupportedFormat("no audio track".to_string()))?;
+    let track_id = track.id;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| AnalyzerError::Decode("no sample rate in codec params".to_string()))?;
+    let channels = track.codec_params.channels.map(|c| c.count()).unwrap_or(2);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| AnalyzerError::Decode(e.to_string()))?;
+    let mut detector = SilenceDetector::new(sample_rate, channels, threshold_db);
+
+    while let Ok(packet) = format.next_packet() {
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let Ok(decoded) = decoder.decode(&packet) else {
+            continue;
+        };
+        let mut sample_buf = SampleBuffer::<f32>::new(decoded.frames() as u64, *decoded.spec());
+        sample_buf.copy_interleaved_ref(decoded);
+        detector.push_interleaved(sample_buf.samples());
+    }
+
+    detector.finish().ok_or(AnalyzerError::EmptyAudio)
+}
+
+/// [`measure_file`] for each of `paths` in parallel, in order.
+pub fn measure_batch(paths: &[&Path], threshold_db: f32) -> Vec<Result<Silence, AnalyzerError>> {
+    paths
+        .par_iter()
+        .map(|path| measure_file(path, threshold_db))
+        .collect()
+}
+
+/// Finds the silence at either end of interleaved audio fed to it in any
+/// number of pieces.
+pub struct SilenceDetector {
+    sample_rate: u32,
+    channels: usize,
+    threshold: f32,
+    frames: u64,
+    /// The first and last frames with sound in them.
+    first_sound: Option<u64>,
+    last_sound: u64,
+}
+
+impl SilenceDetector {
+    pub fn new(sample_rate: u32, channels: usize, threshold_db: f32) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1),
+            channels: channels.max(1),
+            threshold: 10f32.powf(threshold_db / 20.0),
+            frames: 0,
+            first_sound: None,
+            last_sound: 0,
+        }
+    }
+
+    pub fn push_interleaved(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            if frame.iter().any(|sample| sample.abs() > self.threshold) {
+                self.first_sound.get_or_insert(self.frames);
+                self.last_sound = self.frames;
+            }
+            self.frames += 1;
+        }
+    }
+
+    /// The silence found, or `None` when no audio was pushed. Audio that is
+    /// silent throughout has no sound to trim towards, so reports none.
+    pub fn finish(&self) -> Option<Silence> {
+        if self.frames == 0 {
+            return None;
+        }
+        let ms = |frames: u64| frames * 1000 / u64::from(self.sample_rate);
+        let (leading, trailing) = match self.first_sound {
+            Some(first) => (first, self.frames - 1 - self.last_sound),
+            None => (0, 0),
+        };
+        Some(Silence {
+            leading_ms: ms(leading),
+            trailing_ms: ms(trailing),
+            duration_ms: ms(self.frames),
+        })
+    }
+}
@@ -10,9 +10,18 @@ fn rejects_nonexistent_file() {
 
 #[test]
 fn normalize_returns_clamped_values() {
-    assert_eq!(auric_drift::analyzer::clamp_normalize(50.0, 0.0, 100.0), 0.5);
-    assert_eq!(auric_drift::analyzer::clamp_normalize(-10.0, 0.0, 100.0), 0.0);
-    assert_eq!(auric_drift::analyzer::clamp_normalize(200.0, 0.0, 100.0), 1.0);
+    assert_eq!(
+        auric_drift::analyzer::clamp_normalize(50.0, 0.0, 100.0),
+        0.5
+    );
+    assert_eq!(
+        auric_drift::analyzer::clamp_normalize(-10.0, 0.0, 100.0),
+        0.0
+    );
+    assert_eq!(
+        auric_drift::analyzer::clamp_normalize(200.0, 0.0, 100.0),
+        1.0
+    );
 }
 
 #[test]
@@ -21,3 +30,52 @@ fn batch_analyze_empty_returns_empty() {
     let results = analyzer.analyze_batch(&[], None);
     assert!(results.is_empty());
 }
+
+/// A 16-bit mono WAV of short clicks every `60 / bpm` seconds.
+fn write_click_track(path: &std::path::Path, bpm: f32, seconds: u32) {
+    let rate = 44_100u32;
+    let total = (rate * seconds) as usize;
+    let spacing = (rate as f32 * 60.0 / bpm) as usize;
+    let mut samples = vec![0i16; total];
+    for start in (0..total).step_by(spacing) {
+        for (i, sample) in samples[start..(start + 441).min(total)]
+            .iter_mut()
+            .enumerate()
+        {
+            *sample = if i % 2 == 0 { 20_000 } else { -20_000 };
+        }
+    }
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&rate.to_le_bytes());
+    bytes.extend_from_slice(&(rate * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    std::fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn detects_the_tempo_of_a_click_track() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("clicks.wav");
+    write_click_track(&path, 100.0, 20);
+    let analyzer = DriftAnalyzer::new();
+    let bpm = analyzer.analyze_bpm(&path).unwrap();
+    assert!((bpm - 100.0).abs() < 4.0, "detected {bpm}");
+
+    let missing = PathBuf::from("/definitely/not/a/file.flac");
+    let batch = analyzer.analyze_bpm_batch(&[path.as_path(), missing.as_path()]);
+    assert_eq!(batch.len(), 2);
+    assert!(batch[0].is_ok() && batch[1].is_err());
+}
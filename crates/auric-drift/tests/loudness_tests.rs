@@ -0,0 +1,124 @@
+use auric_drift::loudness::{combine, measure_file, Loudness, LoudnessMeter};
+
+fn sine(rate: u32, channels: usize, freq: f32, amplitude: f32, seconds: f32) -> Vec<f32> {
+    let frames = (rate as f32 * seconds) as usize;
+    (0..frames)
+        .flat_map(|i| {
+            let value =
+                amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin();
+            std::iter::repeat_n(value, channels)
+        })
+        .collect()
+}
+
+fn measure(rate: u32, channels: usize, samples: &[f32]) -> Option<Loudness> {
+    let mut meter = LoudnessMeter::new(rate, channels);
+    for chunk in samples.chunks(4096 * channels) {
+        meter.push_interleaved(chunk);
+    }
+    meter.finish()
+}
+
+#[test]
+fn a_full_scale_stereo_sine_measures_zero_lufs() {
+    for rate in [44_100, 48_000] {
+        let loudness = measure(rate, 2, &sine(rate, 2, 997.0, 1.0, 5.0)).unwrap();
+        assert!(loudness.integrated_lufs.abs() < 0.1, "{rate}: {loudness:?}");
+    }
+    let quiet = measure(48_000, 2, &sine(48_000, 2, 997.0, 0.1, 5.0)).unwrap();
+    assert!((quiet.integrated_lufs + 20.0).abs() < 0.1, "{quiet:?}");
+    let mono = measure(48_000, 1, &sine(48_000, 1, 997.0, 0.1, 5.0)).unwrap();
+    assert!((mono.integrated_lufs + 23.01).abs() < 0.1, "{mono:?}");
+}
+
+#[test]
+fn silence_is_gated_out() {
+    assert!(measure(48_000, 2, &vec![0.0; 48_000 * 2 * 3]).is_none());
+    assert!(measure(48_000, 2, &sine(48_000, 2, 997.0, 0.5, 0.2)).is_none());
+
+    // A quiet passage well below the rest barely drags the level down; only
+    // the blocks straddling the change count.
+    let mut samples = sine(48_000, 2, 997.0, 0.1, 5.0);
+    samples.extend(sine(48_000, 2, 997.0, 0.001, 5.0));
+    let loudness = measure(48_000, 2, &samples).unwrap();
+    assert!(
+        (loudness.integrated_lufs + 20.0).abs() < 0.2,
+        "{loudness:?}"
+    );
+}
+
+#[test]
+fn true_peak_catches_overs_between_samples() {
+    let plain = measure(48_000, 1, &sine(48_000, 1, 1000.0, 0.5, 1.0)).unwrap();
+    assert!((plain.true_peak_db + 6.02).abs() < 0.1, "{plain:?}");
+
+    // At a quarter of the sample rate, phased so every sample misses the crest.
+    let samples = (0..48_000)
+        .map(|i| (std::f32::consts::PI / 2.0 * (i % 4) as f32 + std::f32::consts::PI / 4.0).sin())
+        .collect::<Vec<_>>();
+    let intersample = measure(48_000, 1, &samples).unwrap();
+    assert!(samples.iter().all(|s| s.abs() < 0.71));
+    assert!(intersample.true_peak_db > -0.5, "{intersample:?}");
+}
+
+/// A 16-bit WAV of `samples`, interleaved.
+fn write_wav(path: &std::path::Path, rate: u32, channels: u16, samples: &[f32]) {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&channels.to_le_bytes());
+    bytes.extend_from_slice(&rate.to_le_bytes());
+    bytes.extend_from_slice(&(rate * u32::from(channels) * 2).to_le_bytes());
+    bytes.extend_from_slice(&(channels * 2).to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&((sample * 32_767.0) as i16).to_le_bytes());
+    }
+    std::fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn measures_a_decoded_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tone.wav");
+    write_wav(&path, 44_100, 2, &sine(44_100, 2, 997.0, 0.25, 3.0));
+    let found = measure_file(&path).unwrap();
+    assert!(
+        (found.loudness.integrated_lufs + 12.04).abs() < 0.1,
+        "{found:?}"
+    );
+    assert!(
+        (found.loudness.true_peak_db + 12.04).abs() < 0.1,
+        "{found:?}"
+    );
+    assert_eq!(found.replaygain_track_db, None);
+
+    let silent = dir.path().join("silence.wav");
+    write_wav(&silent, 44_100, 2, &vec![0.0; 44_100 * 2]);
+    assert!(measure_file(&silent).is_err());
+    assert!(measure_file(&dir.path().join("missing.wav")).is_err());
+}
+
+#[test]
+fn combined_loudness_weights_each_part_by_length() {
+    let loud = Loudness {
+        integrated_lufs: -10.0,
+        true_peak_db: -0.5,
+    };
+    let quiet = Loudness {
+        integrated_lufs: -20.0,
+        true_peak_db: -3.0,
+    };
+    let same = combine(&[(loud, 1.0), (loud, 3.0)]).unwrap();
+    assert!((same.integrated_lufs + 10.0).abs() < 0.01);
+    let mixed = combine(&[(loud, 1.0), (quiet, 1.0)]).unwrap();
+    assert!((mixed.integrated_lufs + 12.6).abs() < 0.05, "{mixed:?}");
+    assert_eq!(mixed.true_peak_db, -0.5);
+    assert!(combine(&[]).is_none());
+}
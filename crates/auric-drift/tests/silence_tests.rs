@@ -0,0 +1,79 @@
+use auric_drift::silence::{measure_file, Silence, SilenceDetector};
+
+/// `seconds` of silence, a tone at `amplitude`, then `tail` seconds of
+/// silence, as 48 kHz stereo.
+fn padded_tone(lead: f32, amplitude: f32, tail: f32) -> Vec<f32> {
+    let frames = |seconds: f32| (48_000.0 * seconds) as usize * 2;
+    let tone = (0..frames(2.0) / 2).flat_map(|i| {
+        let value = amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 48_000.0).sin();
+        [value, value]
+    });
+    std::iter::repeat_n(0.0, frames(lead))
+        .chain(tone)
+        .chain(std::iter::repeat_n(0.0, frames(tail)))
+        .collect()
+}
+
+fn detect(samples: &[f32], threshold_db: f32) -> Option<Silence> {
+    let mut detector = SilenceDetector::new(48_000, 2, threshold_db);
+    for chunk in samples.chunks(4096 * 2) {
+        detector.push_interleaved(chunk);
+    }
+    detector.finish()
+}
+
+#[test]
+fn finds_the_silence_at_either_end() {
+    let found = detect(&padded_tone(1.5, 0.5, 3.0), -60.0).unwrap();
+    assert_eq!(found.leading_ms, 1_500);
+    assert!((2_999..=3_000).contains(&found.trailing_ms), "{found:?}");
+    assert_eq!(found.duration_ms, 6_500);
+
+    let tight = detect(&padded_tone(0.0, 0.5, 0.0), -60.0).unwrap();
+    assert!(tight.leading_ms == 0 && tight.trailing_ms <= 1, "{tight:?}");
+}
+
+#[test]
+fn the_threshold_decides_what_counts_as_silence() {
+    // A -40 dBFS hum is sound at -60 but silence at -30.
+    let hum = padded_tone(1.0, 0.01, 1.0);
+    assert_eq!(detect(&hum, -60.0).unwrap().leading_ms, 1_000);
+    let quiet = detect(&hum, -30.0).unwrap();
+    assert_eq!((quiet.leading_ms, quiet.trailing_ms), (0, 0));
+    assert_eq!(quiet.duration_ms, 4_000);
+
+    assert!(detect(&[], -60.0).is_none());
+}
+
+/// A 16-bit WAV of `samples`, interleaved.
+fn write_wav(path: &std::path::Path, rate: u32, channels: u16, samples: &[f32]) {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&channels.to_le_bytes());
+    bytes.extend_from_slice(&rate.to_le_bytes());
+    bytes.extend_from_slice(&(rate * u32::from(channels) * 2).to_le_bytes());
+    bytes.extend_from_slice(&(channels * 2).to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&((sample * 32_767.0) as i16).to_le_bytes());
+    }
+    std::fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn measures_a_decoded_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("padded.wav");
+    write_wav(&path, 48_000, 2, &padded_tone(0.5, 0.5, 2.0));
+    let found = measure_file(&path, -60.0).unwrap();
+    assert_eq!((found.leading_ms, found.duration_ms), (500, 4_500));
+    assert!((1_999..=2_000).contains(&found.trailing_ms), "{found:?}");
+    assert!(measure_file(&dir.path().join("missing.wav"), -60.0).is_err());
+}
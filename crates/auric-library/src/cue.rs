@@ -0,0 +1,153 @@
+//! Chapter marks from CUE sheets.
+//!
+//! Single-file album rips and long recordings often come with a sheet
+//! beside them (`album.cue` or `album.flac.cue`) that lists where each track
+//! starts. Those starts become the chapters shown on the seek bar.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub start_ms: u64,
+    pub title: Option<String>,
+}
+
+/// The chapters of the audio file at `path`, from the first CUE sheet found
+/// beside it; empty when there is none.
+pub fn chapters_for(path: &Path) -> Vec<Chapter> {
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+        return Vec::new();
+    };
+    sheet_candidates(path)
+        .into_iter()
+        .find_map(|sheet| fs::read(sheet).ok())
+        // Older rippers write sheets in Latin-1; the times parse either way.
+        .map(|bytes| parse(&String::from_utf8_lossy(&bytes), file_name))
+        .unwrap_or_default()
+}
+
+fn sheet_candidates(path: &Path) -> Vec<PathBuf> {
+    let mut with_suffix = path.as_os_str().to_owned();
+    with_suffix.push(".cue");
+    vec![path.with_extension("cue"), PathBuf::from(with_suffix)]
+}
+
+/// Chapters for `file_name` in the sheet `text`, in order. A sheet naming
+/// several files only contributes the tracks under the matching `FILE`; a
+/// sheet with one `FILE` is taken to describe this audio whatever its name,
+/// as sheets often still name the image they were ripped to.
+pub fn parse(text: &str, file_name: &str) -> Vec<Chapter> {
+    let files = text
+        .lines()
+        .filter(|line| keyword(line) == Some("FILE"))
+        .count();
+    let mut chapters = Vec::new();
+    let mut in_file = files <= 1;
+    let mut title = None;
+    for line in text.lines() {
+        let line = line.trim();
+        match keyword(line) {
+            Some("FILE") if files > 1 => {
+                in_file = quoted(line).is_some_and(|name| {
+                    Path::new(&name.replace('\\', "/"))
+                        .file_name()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(file_name))
+                });
+            }
+            Some("TRACK") => title = None,
+            Some("TITLE") => title = quoted(line),
+            Some("INDEX") if in_file => {
+                let mut parts = line.split_whitespace().skip(1);
+                if parts.next() != Some("01") {
+                    continue;
+                }
+                if let Some(start_ms) = parts.next().and_then(parse_time) {
+                    chapters.push(Chapter {
+                        start_ms,
+                        title: title.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    chapters.sort_by_key(|chapter| chapter.start_ms);
+    chapters
+}
+
+fn keyword(line: &str) -> Option<&str> {
+    line.split_whitespace().next()
+}
+
+fn quoted(line: &str) -> Option<String> {
+    let start = line.find('"')? + 1;
+    let end = line[start..].find('"')? + start;
+    Some(line[start..end].to_string())
+}
+
+/// `mm:ss:ff`, where a frame is 1/75 of a second.
+fn parse_time(raw: &str) -> Option<u64> {
+    let mut parts = raw.split(':').map(|p| p.parse::<u64>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    Some((minutes * 60 + seconds) * 1000 + frames * 1000 / 75)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHEET: &str = r#"PERFORMER "Orchestra"
+TITLE "Symphonies"
+FILE "disc.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "I. Allegro"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "II. Adagio"
+    INDEX 00 12:29:70
+    INDEX 01 12:30:37
+"#;
+
+    #[test]
+    fn reads_track_starts_and_titles() {
+        let chapters = parse(SHEET, "other-name.flac");
+        assert_eq!(
+            chapters,
+            [
+                Chapter {
+                    start_ms: 0,
+                    title: Some("I. Allegro".into())
+                },
+                Chapter {
+                    start_ms: 750_493,
+                    title: Some("II. Adagio".into())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_file_sheets_only_give_the_matching_file() {
+        let sheet =
+            format!("{SHEET}FILE \"disc2.flac\" WAVE\n  TRACK 03 AUDIO\n    INDEX 01 01:00:00\n");
+        let second = parse(&sheet, "DISC2.flac");
+        assert_eq!(
+            second,
+            [Chapter {
+                start_ms: 60_000,
+                title: None
+            }]
+        );
+        assert_eq!(parse(&sheet, "disc.flac").len(), 2);
+    }
+
+    #[test]
+    fn finds_the_sheet_beside_the_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio = dir.path().join("disc.flac");
+        assert!(chapters_for(&audio).is_empty());
+        fs::write(dir.path().join("disc.flac.cue"), SHEET).unwrap();
+        assert_eq!(chapters_for(&audio).len(), 2);
+    }
+}
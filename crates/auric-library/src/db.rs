@@ -1,6 +1,8 @@
-use crate::{LibraryRoot, TrackRecord};
+use crate::import::ImportedLibrary;
+use crate::{LibraryRoot, RootOptions, TrackRecord};
 use auric_core::TrackId;
-use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Row, TransactionBehavior};
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
 use std::fs;
@@ -8,7 +10,11 @@ use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
-const SCHEMA_VERSION: i64 = 2;
+const SCHEMA_VERSION: i64 = 14;
+
+/// Identifies a JSON library export; bumped when its layout changes.
+const EXPORT_FORMAT: &str = "auric-library";
+const EXPORT_VERSION: u32 = 2;
 
 const SCHEMA_SQL: &str = r#"
 CREATE TABLE IF NOT EXISTS app_settings (
@@ -21,6 +27,12 @@ CREATE TABLE IF NOT EXISTS library_roots (
     id TEXT PRIMARY KEY,
     path TEXT NOT NULL UNIQUE,
     watched INTEGER NOT NULL CHECK (watched IN (0, 1)),
+    recursive INTEGER NOT NULL DEFAULT 1 CHECK (recursive IN (0, 1)),
+    include_extensions TEXT NOT NULL DEFAULT '',
+    exclude_extensions TEXT NOT NULL DEFAULT '',
+    auto_artwork INTEGER NOT NULL DEFAULT 0 CHECK (auto_artwork IN (0, 1)),
+    paused INTEGER NOT NULL DEFAULT 0 CHECK (paused IN (0, 1)),
+    offline_since_ms INTEGER,
     created_at_ms INTEGER NOT NULL,
     updated_at_ms INTEGER NOT NULL
 );
@@ -84,6 +96,115 @@ CREATE TABLE IF NOT EXISTS track_artwork (
 );
 
 CREATE INDEX IF NOT EXISTS idx_track_artwork_artwork_id ON track_artwork(artwork_id);
+
+CREATE TABLE IF NOT EXISTS podcasts (
+    id TEXT PRIMARY KEY,
+    feed_url TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    refreshed_at_ms INTEGER,
+    created_at_ms INTEGER NOT NULL,
+    updated_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS podcast_episodes (
+    id TEXT PRIMARY KEY,
+    podcast_id TEXT NOT NULL REFERENCES podcasts(id) ON DELETE CASCADE,
+    guid TEXT NOT NULL,
+    title TEXT NOT NULL,
+    audio_url TEXT NOT NULL,
+    published_at_ms INTEGER,
+    duration_ms INTEGER,
+    local_path TEXT,
+    position_ms INTEGER NOT NULL DEFAULT 0,
+    played INTEGER NOT NULL DEFAULT 0 CHECK (played IN (0, 1)),
+    added_at_ms INTEGER NOT NULL,
+    UNIQUE (podcast_id, guid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_podcast_episodes_published ON podcast_episodes(podcast_id, published_at_ms);
+
+CREATE TABLE IF NOT EXISTS track_resume (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    remember INTEGER CHECK (remember IN (0, 1)),
+    position_ms INTEGER NOT NULL DEFAULT 0,
+    updated_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_bookmarks (
+    id TEXT PRIMARY KEY,
+    track_id TEXT NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    position_ms INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    created_at_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_track_bookmarks_track ON track_bookmarks(track_id, position_ms);
+
+CREATE TABLE IF NOT EXISTS remote_playlists (
+    playlist_id TEXT PRIMARY KEY REFERENCES playlists(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    remote_id TEXT NOT NULL,
+    UNIQUE (source, remote_id)
+);
+
+CREATE TABLE IF NOT EXISTS track_stats (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    rating INTEGER CHECK (rating BETWEEN 1 AND 5),
+    play_count INTEGER NOT NULL DEFAULT 0,
+    last_played_at_ms INTEGER,
+    updated_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS play_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    track_id TEXT NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    played_at_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_play_history_played_at ON play_history(played_at_ms);
+CREATE INDEX IF NOT EXISTS idx_play_history_track ON play_history(track_id);
+
+CREATE TABLE IF NOT EXISTS track_volume (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    offset_db REAL NOT NULL,
+    updated_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_analysis (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    bpm REAL,
+    file_mtime_ms INTEGER,
+    analyzed_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_loudness (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    integrated_lufs REAL,
+    true_peak_db REAL,
+    replaygain_track_db REAL,
+    replaygain_album_db REAL,
+    file_mtime_ms INTEGER,
+    analyzed_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_silence (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    leading_ms INTEGER,
+    trailing_ms INTEGER,
+    duration_ms INTEGER,
+    threshold_db INTEGER NOT NULL,
+    file_mtime_ms INTEGER,
+    analyzed_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_health (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    readable INTEGER NOT NULL CHECK (readable IN (0, 1)),
+    missing_tags TEXT NOT NULL DEFAULT '',
+    low_bitrate_kbps INTEGER,
+    file_mtime_ms INTEGER,
+    checked_at_ms INTEGER NOT NULL
+);
 "#;
 
 const MIGRATION_V1_TO_V2_SQL: &str = r#"
@@ -111,6 +232,242 @@ CREATE TABLE IF NOT EXISTS track_artwork (
 CREATE INDEX IF NOT EXISTS idx_track_artwork_artwork_id ON track_artwork(artwork_id);
 "#;
 
+const MIGRATION_V2_TO_V3_SQL: &str = r#"
+ALTER TABLE library_roots ADD COLUMN recursive INTEGER NOT NULL DEFAULT 1 CHECK (recursive IN (0, 1));
+ALTER TABLE library_roots ADD COLUMN include_extensions TEXT NOT NULL DEFAULT '';
+ALTER TABLE library_roots ADD COLUMN exclude_extensions TEXT NOT NULL DEFAULT '';
+ALTER TABLE library_roots ADD COLUMN auto_artwork INTEGER NOT NULL DEFAULT 0 CHECK (auto_artwork IN (0, 1));
+"#;
+
+const MIGRATION_V3_TO_V4_SQL: &str = r#"
+ALTER TABLE library_roots ADD COLUMN paused INTEGER NOT NULL DEFAULT 0 CHECK (paused IN (0, 1));
+ALTER TABLE library_roots ADD COLUMN offline_since_ms INTEGER;
+"#;
+
+const MIGRATION_V4_TO_V5_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS podcasts (
+    id TEXT PRIMARY KEY,
+    feed_url TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    refreshed_at_ms INTEGER,
+    created_at_ms INTEGER NOT NULL,
+    updated_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS podcast_episodes (
+    id TEXT PRIMARY KEY,
+    podcast_id TEXT NOT NULL REFERENCES podcasts(id) ON DELETE CASCADE,
+    guid TEXT NOT NULL,
+    title TEXT NOT NULL,
+    audio_url TEXT NOT NULL,
+    published_at_ms INTEGER,
+    duration_ms INTEGER,
+    local_path TEXT,
+    position_ms INTEGER NOT NULL DEFAULT 0,
+    played INTEGER NOT NULL DEFAULT 0 CHECK (played IN (0, 1)),
+    added_at_ms INTEGER NOT NULL,
+    UNIQUE (podcast_id, guid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_podcast_episodes_published ON podcast_episodes(podcast_id, published_at_ms);
+"#;
+
+const MIGRATION_V5_TO_V6_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS track_resume (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    remember INTEGER CHECK (remember IN (0, 1)),
+    position_ms INTEGER NOT NULL DEFAULT 0,
+    updated_at_ms INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_bookmarks (
+    id TEXT PRIMARY KEY,
+    track_id TEXT NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    position_ms INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    created_at_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_track_bookmarks_track ON track_bookmarks(track_id, position_ms);
+"#;
+
+const MIGRATION_V6_TO_V7_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS remote_playlists (
+    playlist_id TEXT PRIMARY KEY REFERENCES playlists(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    remote_id TEXT NOT NULL,
+    UNIQUE (source, remote_id)
+);
+"#;
+
+const MIGRATION_V7_TO_V8_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS track_stats (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    rating INTEGER CHECK (rating BETWEEN 1 AND 5),
+    play_count INTEGER NOT NULL DEFAULT 0,
+    last_played_at_ms INTEGER,
+    updated_at_ms INTEGER NOT NULL
+);
+"#;
+
+const MIGRATION_V8_TO_V9_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS play_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    track_id TEXT NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    played_at_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_play_history_played_at ON play_history(played_at_ms);
+CREATE INDEX IF NOT EXISTS idx_play_history_track ON play_history(track_id);
+"#;
+
+const MIGRATION_V9_TO_V10_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS track_volume (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    offset_db REAL NOT NULL,
+    updated_at_ms INTEGER NOT NULL
+);
+"#;
+
+const MIGRATION_V10_TO_V11_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS track_analysis (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    bpm REAL,
+    file_mtime_ms INTEGER,
+    analyzed_at_ms INTEGER NOT NULL
+);
+"#;
+
+const MIGRATION_V11_TO_V12_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS track_loudness (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    integrated_lufs REAL,
+    true_peak_db REAL,
+    replaygain_track_db REAL,
+    replaygain_album_db REAL,
+    file_mtime_ms INTEGER,
+    analyzed_at_ms INTEGER NOT NULL
+);
+"#;
+
+const MIGRATION_V12_TO_V13_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS track_silence (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    leading_ms INTEGER,
+    trailing_ms INTEGER,
+    duration_ms INTEGER,
+    threshold_db INTEGER NOT NULL,
+    file_mtime_ms INTEGER,
+    analyzed_at_ms INTEGER NOT NULL
+);
+"#;
+
+const MIGRATION_V13_TO_V14_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS track_health (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    readable INTEGER NOT NULL CHECK (readable IN (0, 1)),
+    missing_tags TEXT NOT NULL DEFAULT '',
+    low_bitrate_kbps INTEGER,
+    file_mtime_ms INTEGER,
+    checked_at_ms INTEGER NOT NULL
+);
+"#;
+
+/// History of applied migrations. `PRAGMA user_version` stays the source of
+/// truth for the current version; this table records how it got there.
+const SCHEMA_VERSION_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    applied_at_ms INTEGER NOT NULL
+);
+"#;
+
+/// A forward migration taking the schema from `version - 1` to `version`.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
+
+/// Every migration in order. A new one is appended here with
+/// [`SCHEMA_VERSION`] bumped to its version and `SCHEMA_SQL` updated to
+/// match, so fresh and upgraded databases end up identical.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 2,
+        name: "artwork assets",
+        sql: MIGRATION_V1_TO_V2_SQL,
+    },
+    Migration {
+        version: 3,
+        name: "per-folder scan options",
+        sql: MIGRATION_V2_TO_V3_SQL,
+    },
+    Migration {
+        version: 4,
+        name: "paused and offline folders",
+        sql: MIGRATION_V3_TO_V4_SQL,
+    },
+    Migration {
+        version: 5,
+        name: "podcasts",
+        sql: MIGRATION_V4_TO_V5_SQL,
+    },
+    Migration {
+        version: 6,
+        name: "track resume positions and bookmarks",
+        sql: MIGRATION_V5_TO_V6_SQL,
+    },
+    Migration {
+        version: 7,
+        name: "remote playlists",
+        sql: MIGRATION_V6_TO_V7_SQL,
+    },
+    Migration {
+        version: 8,
+        name: "ratings and play counts",
+        sql: MIGRATION_V7_TO_V8_SQL,
+    },
+    Migration {
+        version: 9,
+        name: "play history",
+        sql: MIGRATION_V8_TO_V9_SQL,
+    },
+    Migration {
+        version: 10,
+        name: "track volume offsets",
+        sql: MIGRATION_V9_TO_V10_SQL,
+    },
+    Migration {
+        version: 11,
+        name: "track analysis",
+        sql: MIGRATION_V10_TO_V11_SQL,
+    },
+    Migration {
+        version: 12,
+        name: "track loudness",
+        sql: MIGRATION_V11_TO_V12_SQL,
+    },
+    Migration {
+        version: 13,
+        name: "track silence",
+        sql: MIGRATION_V12_TO_V13_SQL,
+    },
+    Migration {
+        version: 14,
+        name: "track health",
+        sql: MIGRATION_V13_TO_V14_SQL,
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub applied_at_ms: i64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JournalMode {
     Wal,
@@ -181,6 +538,12 @@ pub struct LibraryRootRow {
     pub id: String,
     pub path: String,
     pub watched: bool,
+    pub options: RootOptions,
+    /// Watching is suspended until resumed by the user.
+    pub paused: bool,
+    /// Set by the watcher while the folder is unreachable (an unmounted
+    /// drive or share); its tracks are kept until it comes back.
+    pub offline_since_ms: Option<i64>,
     pub created_at_ms: i64,
     pub updated_at_ms: i64,
 }
@@ -193,6 +556,26 @@ pub struct PlaylistRow {
     pub updated_at_ms: i64,
 }
 
+/// A deleted playlist with everything needed to put it back; see
+/// [`Database::take_playlist`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedPlaylist {
+    pub playlist: PlaylistRow,
+    /// `(track_id, added_at_ms)` in playlist order.
+    pub entries: Vec<(String, i64)>,
+    /// `(source, remote_id)` when the playlist was copied from a server.
+    pub remote: Option<(String, String)>,
+}
+
+/// A playlist with the number and total length of its tracks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistSummaryRow {
+    pub playlist: PlaylistRow,
+    pub track_count: usize,
+    /// Tracks with an unknown length count as zero.
+    pub duration_ms: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PlaylistTrackRow {
     pub playlist_id: String,
@@ -222,6 +605,8 @@ pub struct DatabaseStats {
     pub settings_count: i64,
     pub library_root_count: i64,
     pub track_count: i64,
+    /// Summed length of every track with a known duration.
+    pub track_duration_ms: i64,
     pub artwork_asset_count: i64,
     pub track_artwork_count: i64,
     pub playlist_count: i64,
@@ -277,120 +662,605 @@ pub struct ArtworkBatchUpsertSummary {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct PragmaSnapshot {
-    pub journal_mode: String,
-    pub synchronous: i64,
-    pub foreign_keys: bool,
-    pub cache_size: i64,
-    pub mmap_size: i64,
+pub struct PodcastRow {
+    pub id: String,
+    pub feed_url: String,
+    pub title: String,
+    /// When the feed was last fetched successfully.
+    pub refreshed_at_ms: Option<i64>,
+    pub episode_count: i64,
+    pub unplayed_count: i64,
+    pub created_at_ms: i64,
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum DbError {
-    #[error("io error: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("sqlite error: {0}")]
-    Sql(#[from] rusqlite::Error),
-    #[error("json error: {0}")]
-    Json(#[from] serde_json::Error),
-    #[error("uuid parse error: {0}")]
-    Uuid(#[from] uuid::Error),
-    #[error("unsupported schema version {found}; max supported {max_supported}")]
-    UnsupportedSchemaVersion { found: i64, max_supported: i64 },
-    #[error("not found: {0}")]
-    NotFound(String),
-    #[error("integrity check failed: {0}")]
-    IntegrityCheck(String),
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodcastEpisodeRow {
+    pub id: String,
+    pub podcast_id: String,
+    pub guid: String,
+    pub title: String,
+    pub audio_url: String,
+    pub published_at_ms: Option<i64>,
+    pub duration_ms: Option<i64>,
+    /// The downloaded file, once there is one.
+    pub local_path: Option<String>,
+    /// Where playback stopped last time, for resuming.
+    pub position_ms: i64,
+    pub played: bool,
 }
 
-impl Database {
-    pub fn open(options: &DatabaseOptions) -> Result<Self, DbError> {
-        if let Some(parent) = options.path.parent() {
-            if !parent.as_os_str().is_empty() {
-                fs::create_dir_all(parent)?;
-            }
-        }
+/// A track's saved place. `remember` is the per-track choice; `None` leaves
+/// it to the length and folder rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackResumeRow {
+    pub track_id: String,
+    pub remember: Option<bool>,
+    pub position_ms: i64,
+}
 
-        let conn = Connection::open(&options.path)?;
-        Self::from_connection(conn, options, Some(options.path.clone()))
-    }
+/// A track's rating and listening counts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackStatsRow {
+    pub track_id: String,
+    /// 1–5 stars; `None` when unrated.
+    pub rating: Option<u8>,
+    pub play_count: i64,
+    pub last_played_at_ms: Option<i64>,
+}
 
-    pub fn open_in_memory_for_tests() -> Result<Self, DbError> {
-        let options = DatabaseOptions {
-            journal_mode: JournalMode::Memory,
-            synchronous: SynchronousMode::Off,
-            path: PathBuf::from(":memory:"),
-            ..DatabaseOptions::default()
-        };
-        let conn = Connection::open_in_memory()?;
-        Self::from_connection(conn, &options, None)
-    }
+/// The periods listening time is totalled over, in local time. Weeks start
+/// on Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsPeriod {
+    Day,
+    Week,
+}
 
-    fn from_connection(
-        conn: Connection,
-        options: &DatabaseOptions,
-        path: Option<PathBuf>,
-    ) -> Result<Self, DbError> {
-        let mut db = Self { conn, path };
-        db.configure_connection(options)?;
-        db.migrate()?;
-        Ok(db)
+/// Local dates, `YYYY-MM-DD`, that plays are counted between, both ends
+/// included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: String,
+    pub to: String,
+}
+
+/// What the most played lists are made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayGroup {
+    Artist,
+    Album,
+    Track,
+}
+
+/// An artist, album or track with how often it was played. `detail` is the
+/// artist of an album or track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayTally {
+    pub name: String,
+    pub detail: Option<String>,
+    pub plays: i64,
+}
+
+/// A built-in playlist computed from when tracks were added and played,
+/// shown above the user's playlists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartPlaylist {
+    /// Tracks added in the last seven days, newest first.
+    AddedThisWeek,
+    /// Tracks played since local midnight, most recent first.
+    PlayedToday,
+    /// The 25 tracks with the most plays.
+    MostPlayed,
+}
+
+impl SmartPlaylist {
+    pub const ALL: [Self; 3] = [Self::AddedThisWeek, Self::PlayedToday, Self::MostPlayed];
+
+    /// Stands in for a playlist id; never a UUID, so it cannot collide.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::AddedThisWeek => "smart:added-this-week",
+            Self::PlayedToday => "smart:played-today",
+            Self::MostPlayed => "smart:most-played",
+        }
     }
 
-    pub fn path(&self) -> Option<&Path> {
-        self.path.as_deref()
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::AddedThisWeek => "Added this week",
+            Self::PlayedToday => "Played today",
+            Self::MostPlayed => "Top 25 Most Played",
+        }
     }
 
-    fn configure_connection(&mut self, options: &DatabaseOptions) -> Result<(), DbError> {
-        self.conn
-            .busy_timeout(Duration::from_millis(options.busy_timeout_ms))?;
-        self.conn.execute_batch(&format!(
-            "PRAGMA foreign_keys = ON;\nPRAGMA journal_mode = {};\nPRAGMA synchronous = {};\nPRAGMA temp_store = MEMORY;\nPRAGMA cache_size = -{};\nPRAGMA mmap_size = {};\nPRAGMA wal_autocheckpoint = {};\n",
-            options.journal_mode.as_sql(),
-            options.synchronous.as_sql(),
-            options.cache_size_kib,
-            options.mmap_size_bytes,
-            options.wal_autocheckpoint_pages
-        ))?;
-        Ok(())
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|smart| smart.id() == id)
     }
+}
 
-    fn migrate(&mut self) -> Result<(), DbError> {
-        let current = self.schema_version()?;
-        if current > SCHEMA_VERSION {
-            return Err(DbError::UnsupportedSchemaVersion {
-                found: current,
-                max_supported: SCHEMA_VERSION,
-            });
-        }
+/// A track's measured loudness, with the ReplayGain gains its tags carry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackLoudness {
+    pub integrated_lufs: f32,
+    /// True peak in dB relative to full scale.
+    pub true_peak_db: f32,
+    pub replaygain_track_db: Option<f32>,
+    pub replaygain_album_db: Option<f32>,
+}
+
+/// How long a track is silent at either end, and how long it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackSilence {
+    pub leading_ms: i64,
+    pub trailing_ms: i64,
+    pub duration_ms: i64,
+}
+
+/// What the library health check found in a track's file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackHealth {
+    pub readable: bool,
+    /// Which of `title`, `artist` and `album` the file's tags leave empty.
+    pub missing_tags: Vec<String>,
+    /// The bitrate of a lossy file encoded at a suspiciously low one.
+    pub low_bitrate_kbps: Option<u32>,
+}
+
+/// The problems the library health report lists tracks under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthIssue {
+    MissingTags,
+    MissingArtwork,
+    ZeroDuration,
+    Unreadable,
+    LowBitrate,
+}
 
-        if current == 0 {
-            let tx = self
-                .conn
-                .transaction_with_behavior(TransactionBehavior::Immediate)?;
-            tx.execute_batch(SCHEMA_SQL)?;
-            tx.execute_batch(&format!("PRAGMA user_version = {};", SCHEMA_VERSION))?;
-            tx.commit()?;
-        } else if current == 1 {
-            let tx = self
-                .conn
-                .transaction_with_behavior(TransactionBehavior::Immediate)?;
-            tx.execute_batch(MIGRATION_V1_TO_V2_SQL)?;
-            tx.execute_batch(&format!("PRAGMA user_version = {};", SCHEMA_VERSION))?;
-            tx.commit()?;
+impl HealthIssue {
+    pub const ALL: [Self; 5] = [
+        Self::MissingTags,
+        Self::MissingArtwork,
+        Self::ZeroDuration,
+        Self::Unreadable,
+        Self::LowBitrate,
+    ];
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            Self::MissingTags => "tags",
+            Self::MissingArtwork => "artwork",
+            Self::ZeroDuration => "duration",
+            Self::Unreadable => "unreadable",
+            Self::LowBitrate => "bitrate",
         }
+    }
 
-        Ok(())
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|issue| issue.as_key() == key)
     }
 
-    pub fn schema_version(&self) -> Result<i64, DbError> {
-        Ok(self
-            .conn
-            .query_row("PRAGMA user_version;", [], |row| row.get(0))?)
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MissingTags => "Missing tags",
+            Self::MissingArtwork => "Missing artwork",
+            Self::ZeroDuration => "Zero duration",
+            Self::Unreadable => "Unreadable files",
+            Self::LowBitrate => "Low bitrate",
+        }
     }
 
-    pub fn quick_check(&self) -> Result<(), DbError> {
-        let result: String = self
+    /// Whether finding it takes a health check of the files, rather than
+    /// only what the scanner stored.
+    pub fn needs_check(self) -> bool {
+        matches!(
+            self,
+            Self::MissingTags | Self::Unreadable | Self::LowBitrate
+        )
+    }
+
+    /// Picks the tracks with this issue from `tracks t` joined to a current
+    /// `track_health h`.
+    fn condition(self) -> &'static str {
+        match self {
+            Self::MissingTags => "h.missing_tags <> ''",
+            // Streamed tracks have URL paths and no artwork of their own.
+            Self::MissingArtwork => {
+                "instr(t.path, '://') = 0
+                 AND NOT EXISTS (SELECT 1 FROM track_artwork a WHERE a.track_id = t.id)"
+            }
+            Self::ZeroDuration => "COALESCE(t.duration_ms, 0) <= 0",
+            Self::Unreadable => "h.readable = 0",
+            Self::LowBitrate => "h.low_bitrate_kbps IS NOT NULL",
+        }
+    }
+}
+
+/// A track with its rating and listening counts, as exported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackWithStats {
+    pub track: TrackRow,
+    pub rating: Option<u8>,
+    pub play_count: i64,
+    pub last_played_at_ms: Option<i64>,
+}
+
+/// What [`Database::import_track_stats`] matched and skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsImportSummary {
+    pub tracks: usize,
+    /// Entries whose path is not a track in the library.
+    pub unmatched_tracks: usize,
+    pub playlists: usize,
+    pub playlist_entries: usize,
+    /// Playlists left alone because one with the same name exists.
+    pub skipped_playlists: usize,
+    pub unmatched_entries: usize,
+}
+
+/// A playlist on a remote library server, with its entries already mapped
+/// to local track ids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemotePlaylist {
+    pub remote_id: String,
+    pub name: String,
+    pub track_ids: Vec<TrackId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackBookmarkRow {
+    pub id: String,
+    pub track_id: String,
+    pub position_ms: i64,
+    pub label: String,
+    pub created_at_ms: i64,
+}
+
+/// An episode as read from a feed. Refreshing updates these fields and
+/// keeps the download and listening progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodcastEpisodeUpsert {
+    pub guid: String,
+    pub title: String,
+    pub audio_url: String,
+    pub published_at_ms: Option<i64>,
+    pub duration_ms: Option<i64>,
+}
+
+/// Rows removed by [`Database::remove_orphan_rows`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrphanCleanup {
+    pub playlist_entries: usize,
+    pub artwork_links: usize,
+    pub artwork_assets: usize,
+}
+
+impl OrphanCleanup {
+    pub fn total(&self) -> usize {
+        self.playlist_entries + self.artwork_links + self.artwork_assets
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PragmaSnapshot {
+    pub journal_mode: String,
+    pub synchronous: i64,
+    pub foreign_keys: bool,
+    pub cache_size: i64,
+    pub mmap_size: i64,
+}
+
+/// Portable copy of the library state. Artwork is left out and re-extracted
+/// by the next scan; ids are kept so playlists and settings that refer to
+/// tracks stay valid after a restore.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryExport {
+    pub format: String,
+    pub version: u32,
+    pub schema_version: i64,
+    pub exported_at_ms: i64,
+    pub settings: Vec<ExportedSetting>,
+    pub roots: Vec<ExportedRoot>,
+    pub tracks: Vec<ExportedTrack>,
+    pub playlists: Vec<ExportedPlaylist>,
+    /// Ratings and play counts; absent from version 1 exports.
+    #[serde(default)]
+    pub track_stats: Vec<ExportedTrackStats>,
+    /// Every play to the end, oldest first; absent from version 1 exports.
+    #[serde(default)]
+    pub play_history: Vec<ExportedPlay>,
+    /// Saved positions and remember choices; absent from version 1 exports.
+    #[serde(default)]
+    pub resume: Vec<ExportedResume>,
+    #[serde(default)]
+    pub bookmarks: Vec<ExportedBookmark>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedSetting {
+    pub key: String,
+    pub value: JsonValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedRoot {
+    pub id: String,
+    pub path: String,
+    pub watched: bool,
+    pub recursive: bool,
+    pub include_extensions: String,
+    pub exclude_extensions: String,
+    pub auto_artwork: bool,
+    pub paused: bool,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedTrack {
+    pub id: String,
+    pub path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub sample_rate: Option<i64>,
+    pub channels: Option<i64>,
+    pub bit_depth: Option<i64>,
+    pub file_mtime_ms: Option<i64>,
+    pub added_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedPlaylist {
+    pub id: String,
+    pub name: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+    /// Entries in play order.
+    pub entries: Vec<ExportedPlaylistEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedPlaylistEntry {
+    pub track_id: String,
+    pub added_at_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedTrackStats {
+    pub track_id: String,
+    pub rating: Option<u8>,
+    pub play_count: i64,
+    pub last_played_at_ms: Option<i64>,
+    pub updated_at_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedPlay {
+    pub track_id: String,
+    pub played_at_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedResume {
+    pub track_id: String,
+    pub remember: Option<bool>,
+    pub position_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedBookmark {
+    pub id: String,
+    pub track_id: String,
+    pub position_ms: i64,
+    pub label: String,
+    pub created_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryImportSummary {
+    pub settings: usize,
+    pub roots: usize,
+    pub tracks: usize,
+    pub playlists: usize,
+    pub playlist_entries: usize,
+    /// Entries whose track was neither in the backup nor the library.
+    pub skipped_entries: usize,
+    pub track_stats: usize,
+    pub plays: usize,
+    pub resume_positions: usize,
+    pub bookmarks: usize,
+}
+
+impl LibraryExport {
+    /// Rewrites root and track paths under `from` to live under `to`, for
+    /// restoring on a machine where the music is mounted elsewhere. Returns
+    /// how many paths changed.
+    pub fn remap_paths(&mut self, from: &str, to: &str) -> usize {
+        let from = Path::new(from);
+        let to = Path::new(to);
+        let remap = |path: &mut String| match Path::new(path.as_str()).strip_prefix(from) {
+            Ok(rest) => {
+                *path = if rest.as_os_str().is_empty() {
+                    to.to_string_lossy().into_owned()
+                } else {
+                    to.join(rest).to_string_lossy().into_owned()
+                };
+                true
+            }
+            Err(_) => false,
+        };
+        let mut changed = 0;
+        for root in &mut self.roots {
+            changed += usize::from(remap(&mut root.path));
+        }
+        for track in &mut self.tracks {
+            changed += usize::from(remap(&mut track.path));
+        }
+        changed
+    }
+}
+
+/// Reads a backup written by [`Database::write_library_backup`], in either
+/// format. SQLite backups are read from a temporary copy so an older backup
+/// is migrated without touching the original.
+pub fn read_library_backup(path: &Path) -> Result<LibraryExport, DbError> {
+    let mut header = [0u8; 16];
+    let is_sqlite = {
+        use std::io::Read;
+        let mut file = fs::File::open(path)?;
+        file.read(&mut header)? == header.len() && &header == b"SQLite format 3\0"
+    };
+    if !is_sqlite {
+        let export: LibraryExport = serde_json::from_slice(&fs::read(path)?)?;
+        if export.format != EXPORT_FORMAT {
+            return Err(DbError::InvalidBackup(format!(
+                "{} is not an auric library export",
+                path.display()
+            )));
+        }
+        if export.version > EXPORT_VERSION {
+            return Err(DbError::InvalidBackup(format!(
+                "export version {} is newer than supported version {EXPORT_VERSION}",
+                export.version
+            )));
+        }
+        return Ok(export);
+    }
+
+    let copy = std::env::temp_dir().join(format!("auric-restore-{}.db", Uuid::new_v4()));
+    fs::copy(path, &copy)?;
+    let result = Database::open(&DatabaseOptions {
+        path: copy.clone(),
+        journal_mode: JournalMode::Delete,
+        ..DatabaseOptions::default()
+    })
+    .and_then(|db| {
+        db.quick_check()?;
+        db.export_library()
+    });
+    let _ = fs::remove_file(&copy);
+    result
+}
+
+/// Writes a consistent copy of the database at `from`, including whatever
+/// is still in its WAL, to `to`, without migrating it. `to` must not exist.
+pub fn copy_database(from: &Path, to: &Path) -> Result<(), DbError> {
+    let conn = Connection::open_with_flags(from, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    conn.execute("VACUUM INTO ?1", params![to.to_string_lossy()])?;
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("sqlite error: {0}")]
+    Sql(#[from] rusqlite::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("uuid parse error: {0}")]
+    Uuid(#[from] uuid::Error),
+    #[error("unsupported schema version {found}; max supported {max_supported}")]
+    UnsupportedSchemaVersion { found: i64, max_supported: i64 },
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("integrity check failed: {0}")]
+    IntegrityCheck(String),
+    #[error("background write failed: {0}")]
+    Writer(String),
+    #[error("invalid backup: {0}")]
+    InvalidBackup(String),
+    #[error("migration to schema v{version} ({name}) failed: {source}")]
+    Migration {
+        version: i64,
+        name: &'static str,
+        source: rusqlite::Error,
+    },
+}
+
+impl Database {
+    pub fn open(options: &DatabaseOptions) -> Result<Self, DbError> {
+        if let Some(parent) = options.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        let conn = Connection::open(&options.path)?;
+        Self::from_connection(conn, options, Some(options.path.clone()))
+    }
+
+    pub fn open_in_memory_for_tests() -> Result<Self, DbError> {
+        let options = DatabaseOptions {
+            journal_mode: JournalMode::Memory,
+            synchronous: SynchronousMode::Off,
+            path: PathBuf::from(":memory:"),
+            ..DatabaseOptions::default()
+        };
+        let conn = Connection::open_in_memory()?;
+        Self::from_connection(conn, &options, None)
+    }
+
+    fn from_connection(
+        conn: Connection,
+        options: &DatabaseOptions,
+        path: Option<PathBuf>,
+    ) -> Result<Self, DbError> {
+        let mut db = Self { conn, path };
+        db.configure_connection(options)?;
+        db.migrate()?;
+        Ok(db)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn configure_connection(&mut self, options: &DatabaseOptions) -> Result<(), DbError> {
+        self.conn
+            .busy_timeout(Duration::from_millis(options.busy_timeout_ms))?;
+        self.conn.execute_batch(&format!(
+            "PRAGMA foreign_keys = ON;\nPRAGMA journal_mode = {};\nPRAGMA synchronous = {};\nPRAGMA temp_store = MEMORY;\nPRAGMA cache_size = -{};\nPRAGMA mmap_size = {};\nPRAGMA wal_autocheckpoint = {};\n",
+            options.journal_mode.as_sql(),
+            options.synchronous.as_sql(),
+            options.cache_size_kib,
+            options.mmap_size_bytes,
+            options.wal_autocheckpoint_pages
+        ))?;
+        Ok(())
+    }
+
+    fn migrate(&mut self) -> Result<(), DbError> {
+        run_migrations(&mut self.conn, MIGRATIONS, SCHEMA_VERSION)
+    }
+
+    pub fn schema_version(&self) -> Result<i64, DbError> {
+        Ok(self
+            .conn
+            .query_row("PRAGMA user_version;", [], |row| row.get(0))?)
+    }
+
+    /// Applied migrations, oldest first. Databases created before the history
+    /// was kept start with a single `baseline` entry for their version then.
+    pub fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT version, name, applied_at_ms FROM schema_version ORDER BY version ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(AppliedMigration {
+                version: row.get(0)?,
+                name: row.get(1)?,
+                applied_at_ms: row.get(2)?,
+            })
+        })?;
+        collect_rows(rows)
+    }
+
+    pub fn quick_check(&self) -> Result<(), DbError> {
+        let result: String = self
             .conn
             .query_row("PRAGMA quick_check;", [], |row| row.get(0))?;
         if result.eq_ignore_ascii_case("ok") {
@@ -400,6 +1270,73 @@ impl Database {
         }
     }
 
+    /// Full `PRAGMA integrity_check`; slower than [`Self::quick_check`] as it
+    /// also verifies indexes. Returns the problems found, empty when healthy.
+    pub fn integrity_check(&self) -> Result<Vec<String>, DbError> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check;")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut problems = collect_rows(rows)?;
+        problems.retain(|line| !line.eq_ignore_ascii_case("ok"));
+        Ok(problems)
+    }
+
+    /// Deletes playlist entries and artwork links whose track is gone, and
+    /// artwork nothing links to. Foreign keys normally prevent these, but a
+    /// database edited with them off can still have some. Playlist positions
+    /// are renumbered to stay contiguous.
+    pub fn remove_orphan_rows(&mut self) -> Result<OrphanCleanup, DbError> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let playlist_entries = tx.execute(
+            "DELETE FROM playlist_entries
+             WHERE track_id NOT IN (SELECT id FROM tracks)
+                OR playlist_id NOT IN (SELECT id FROM playlists)",
+            [],
+        )?;
+        if playlist_entries > 0 {
+            tx.execute_batch(
+                "CREATE TEMP TABLE renumbered AS
+                    SELECT playlist_id, position AS old_position,
+                           ROW_NUMBER() OVER (PARTITION BY playlist_id ORDER BY position) - 1 AS new_position
+                    FROM playlist_entries;
+                 UPDATE playlist_entries SET position = -1 - (
+                    SELECT new_position FROM renumbered r
+                    WHERE r.playlist_id = playlist_entries.playlist_id AND r.old_position = playlist_entries.position
+                 );
+                 UPDATE playlist_entries SET position = -1 - position;
+                 DROP TABLE renumbered;",
+            )?;
+        }
+        let artwork_links = tx.execute(
+            "DELETE FROM track_artwork
+             WHERE track_id NOT IN (SELECT id FROM tracks)
+                OR artwork_id NOT IN (SELECT id FROM artwork_assets)",
+            [],
+        )?;
+        let artwork_assets = tx.execute(
+            "DELETE FROM artwork_assets
+             WHERE id NOT IN (SELECT DISTINCT artwork_id FROM track_artwork)",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(OrphanCleanup {
+            playlist_entries,
+            artwork_links,
+            artwork_assets,
+        })
+    }
+
+    /// Rebuilds the file to reclaim free pages, then truncates the WAL so the
+    /// space is returned to the filesystem.
+    pub fn vacuum(&self) -> Result<(), DbError> {
+        self.conn.execute_batch("VACUUM;")?;
+        self.conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE);", [], |_| Ok(()))
+            .optional()?;
+        Ok(())
+    }
+
     pub fn optimize(&self) -> Result<(), DbError> {
         self.conn.execute_batch("PRAGMA optimize;")?;
         Ok(())
@@ -479,7 +1416,7 @@ impl Database {
     pub fn get_library_root_by_path(&self, path: &str) -> Result<Option<LibraryRootRow>, DbError> {
         self.conn
             .query_row(
-                "SELECT id, path, watched, created_at_ms, updated_at_ms FROM library_roots WHERE path = ?1",
+                "SELECT id, path, watched, created_at_ms, updated_at_ms, recursive, include_extensions, exclude_extensions, auto_artwork, paused, offline_since_ms FROM library_roots WHERE path = ?1",
                 params![path],
                 read_library_root,
             )
@@ -487,9 +1424,66 @@ impl Database {
             .map_err(DbError::from)
     }
 
+    pub fn set_library_root_options(
+        &self,
+        path: &str,
+        options: &RootOptions,
+    ) -> Result<LibraryRootRow, DbError> {
+        let updated = self.conn.execute(
+            "UPDATE library_roots SET recursive = ?2, include_extensions = ?3,
+             exclude_extensions = ?4, auto_artwork = ?5, updated_at_ms = ?6 WHERE path = ?1",
+            params![
+                path,
+                bool_to_i64(options.recursive),
+                options.include_extensions.join(","),
+                options.exclude_extensions.join(","),
+                bool_to_i64(options.auto_artwork),
+                now_ms()
+            ],
+        )?;
+        if updated == 0 {
+            return Err(DbError::NotFound(format!("library root {path}")));
+        }
+        self.get_library_root_by_path(path)?
+            .ok_or_else(|| DbError::NotFound(format!("library root {path}")))
+    }
+
+    pub fn set_library_root_paused(
+        &self,
+        path: &str,
+        paused: bool,
+    ) -> Result<LibraryRootRow, DbError> {
+        self.conn.execute(
+            "UPDATE library_roots SET paused = ?2, updated_at_ms = ?3 WHERE path = ?1",
+            params![path, bool_to_i64(paused), now_ms()],
+        )?;
+        self.get_library_root_by_path(path)?
+            .ok_or_else(|| DbError::NotFound(format!("library root {path}")))
+    }
+
+    /// Marks a root unreachable (keeping the time it was first seen offline)
+    /// or reachable again. Returns whether the row changed; paths that are not
+    /// saved roots are ignored.
+    pub fn set_library_root_offline(&self, path: &str, offline: bool) -> Result<bool, DbError> {
+        let updated = if offline {
+            self.conn.execute(
+                "UPDATE library_roots SET offline_since_ms = ?2
+                 WHERE path = ?1 AND offline_since_ms IS NULL",
+                params![path, now_ms()],
+            )?
+        } else {
+            self.conn.execute(
+                "UPDATE library_roots SET offline_since_ms = NULL
+                 WHERE path = ?1 AND offline_since_ms IS NOT NULL",
+                params![path],
+            )?
+        };
+        Ok(updated > 0)
+    }
+
     pub fn list_library_roots(&self) -> Result<Vec<LibraryRootRow>, DbError> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, path, watched, created_at_ms, updated_at_ms FROM library_roots ORDER BY path ASC",
+            "SELECT id, path, watched, created_at_ms, updated_at_ms, recursive, include_extensions, exclude_extensions, auto_artwork, paused, offline_since_ms FROM library_roots ORDER BY path ASC",
         )?;
         let rows = stmt.query_map([], read_library_root)?;
         collect_rows(rows)
@@ -520,6 +1514,33 @@ impl Database {
         collect_rows(rows)
     }
 
+    /// Like [`Self::list_playlists`], with each playlist's track count and
+    /// length.
+    pub fn list_playlist_summaries(&self) -> Result<Vec<PlaylistSummaryRow>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.id, p.name, p.created_at_ms, p.updated_at_ms,
+                    COUNT(pe.track_id), COALESCE(SUM(t.duration_ms), 0)
+             FROM playlists p
+             LEFT JOIN playlist_entries pe ON pe.playlist_id = p.id
+             LEFT JOIN tracks t ON t.id = pe.track_id
+             GROUP BY p.id
+             ORDER BY lower(p.name), p.name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PlaylistSummaryRow {
+                playlist: PlaylistRow {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    created_at_ms: row.get(2)?,
+                    updated_at_ms: row.get(3)?,
+                },
+                track_count: row.get::<_, i64>(4)?.max(0) as usize,
+                duration_ms: row.get::<_, i64>(5)?.max(0) as u64,
+            })
+        })?;
+        collect_rows(rows)
+    }
+
     pub fn rename_playlist(&self, playlist_id: &str, name: &str) -> Result<(), DbError> {
         let changed = self.conn.execute(
             "UPDATE playlists SET name = ?2, updated_at_ms = ?3 WHERE id = ?1",
@@ -541,13 +1562,88 @@ impl Database {
         Ok(())
     }
 
-    pub fn upsert_track(&self, track: &TrackRecord) -> Result<(), DbError> {
-        let now = now_ms();
-        let mut stmt = self.conn.prepare_cached(
-            "INSERT INTO tracks (
-                id, path, title, artist, album,
-                duration_ms, sample_rate, channels, bit_depth, file_mtime_ms,
-                added_at_ms, updated_at_ms
+    /// Deletes a playlist and returns what [`Self::restore_playlist`] needs
+    /// to bring it back.
+    pub fn take_playlist(&self, playlist_id: &str) -> Result<DeletedPlaylist, DbError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let playlist = tx
+            .query_row(
+                "SELECT id, name, created_at_ms, updated_at_ms FROM playlists WHERE id = ?1",
+                params![playlist_id],
+                |row| {
+                    Ok(PlaylistRow {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        created_at_ms: row.get(2)?,
+                        updated_at_ms: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?
+            .ok_or_else(|| DbError::NotFound(format!("playlist {playlist_id}")))?;
+        let entries = {
+            let mut stmt = tx.prepare(
+                "SELECT track_id, added_at_ms FROM playlist_entries
+                 WHERE playlist_id = ?1 ORDER BY position ASC",
+            )?;
+            let rows =
+                stmt.query_map(params![playlist_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            collect_rows(rows)?
+        };
+        let remote = tx
+            .query_row(
+                "SELECT source, remote_id FROM remote_playlists WHERE playlist_id = ?1",
+                params![playlist_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        tx.execute("DELETE FROM playlists WHERE id = ?1", params![playlist_id])?;
+        tx.commit()?;
+        Ok(DeletedPlaylist {
+            playlist,
+            entries,
+            remote,
+        })
+    }
+
+    /// Recreates a playlist removed by [`Self::take_playlist`] under its old
+    /// id. Tracks that have left the library since are skipped. Returns how
+    /// many entries came back.
+    pub fn restore_playlist(&self, deleted: &DeletedPlaylist) -> Result<usize, DbError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let playlist = &deleted.playlist;
+        tx.execute(
+            "INSERT INTO playlists (id, name, created_at_ms, updated_at_ms) VALUES (?1, ?2, ?3, ?4)",
+            params![playlist.id, playlist.name, playlist.created_at_ms, playlist.updated_at_ms],
+        )?;
+        let mut restored = 0usize;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO playlist_entries (playlist_id, track_id, position, added_at_ms)
+                 SELECT ?1, id, ?3, ?4 FROM tracks WHERE id = ?2",
+            )?;
+            for (track_id, added_at_ms) in &deleted.entries {
+                restored +=
+                    insert.execute(params![playlist.id, track_id, restored as i64, added_at_ms])?;
+            }
+        }
+        if let Some((source, remote_id)) = &deleted.remote {
+            tx.execute(
+                "INSERT OR IGNORE INTO remote_playlists (playlist_id, source, remote_id) VALUES (?1, ?2, ?3)",
+                params![playlist.id, source, remote_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(restored)
+    }
+
+    pub fn upsert_track(&self, track: &TrackRecord) -> Result<(), DbError> {
+        let now = now_ms();
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO tracks (
+                id, path, title, artist, album,
+                duration_ms, sample_rate, channels, bit_depth, file_mtime_ms,
+                added_at_ms, updated_at_ms
              ) VALUES (
                 ?1, ?2, ?3, ?4, ?5,
                 ?6, ?7, ?8, ?9, ?10,
@@ -690,6 +1786,7 @@ impl Database {
         Ok(Vec::new())
     }
 
+    /// Every track by `artist`, album by album, each album in play order.
     pub fn list_tracks_by_artist(&self, artist: &str) -> Result<Vec<TrackRow>, DbError> {
         let mut stmt = self.conn.prepare(
             "SELECT id, path, title, artist, album, duration_ms, sample_rate, channels, bit_depth, file_mtime_ms, added_at_ms, updated_at_ms
@@ -708,6 +1805,95 @@ impl Database {
         collect_rows(rows)
     }
 
+    /// Tracks of one album in play order. Albums are told apart by their
+    /// artist too, the same grouping as [`Self::distinct_albums`].
+    pub fn list_album_tracks(&self, album: &str, artist: &str) -> Result<Vec<TrackRow>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, title, artist, album, duration_ms, sample_rate, channels, bit_depth, file_mtime_ms, added_at_ms, updated_at_ms
+             FROM tracks WHERE album = ?1 AND COALESCE(artist, '') = ?2 ORDER BY path ASC",
+        )?;
+        let rows = stmt.query_map(params![album, artist], read_track_row)?;
+        collect_rows(rows)
+    }
+
+    /// A random `(album, artist)` other than `except`, or `None` when the
+    /// library has no other album.
+    pub fn random_album(
+        &self,
+        except: Option<(&str, &str)>,
+    ) -> Result<Option<(String, String)>, DbError> {
+        let (album, artist) = except.unwrap_or(("", ""));
+        self.conn
+            .query_row(
+                "SELECT album, artist FROM (
+                     SELECT DISTINCT album, COALESCE(artist, '') AS artist FROM tracks
+                     WHERE album IS NOT NULL AND album != ''
+                 )
+                 WHERE NOT (album = ?1 AND artist = ?2)
+                 ORDER BY RANDOM() LIMIT 1",
+                params![album, artist],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(DbError::from)
+    }
+
+    /// Artists that share albums or playlists with `artist`, those sharing
+    /// the most first.
+    pub fn related_artists(&self, artist: &str, limit: usize) -> Result<Vec<String>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "WITH seed AS (SELECT id, album FROM tracks WHERE artist = ?1),
+             shared AS (
+                 SELECT t.artist FROM tracks t JOIN seed s ON t.album = s.album
+                 WHERE s.album IS NOT NULL AND s.album != ''
+                 UNION ALL
+                 SELECT t.artist FROM seed s
+                 JOIN playlist_entries ps ON ps.track_id = s.id
+                 JOIN playlist_entries pe ON pe.playlist_id = ps.playlist_id
+                 JOIN tracks t ON t.id = pe.track_id
+             )
+             SELECT artist FROM shared
+             WHERE artist IS NOT NULL AND artist != '' AND artist != ?1
+             GROUP BY artist ORDER BY COUNT(*) DESC, artist ASC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![artist, limit as i64], |row| row.get::<_, String>(0))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Up to `limit` tracks in random order.
+    pub fn random_tracks(&self, limit: usize) -> Result<Vec<TrackRow>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, title, artist, album, duration_ms, sample_rate, channels, bit_depth, file_mtime_ms, added_at_ms, updated_at_ms
+             FROM tracks ORDER BY RANDOM() LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], read_track_row)?;
+        collect_rows(rows)
+    }
+
+    /// Tracks whose title, artist or album contains `query` (case-insensitive
+    /// for ASCII), each with its position in [`Self::list_tracks`] order.
+    pub fn search_tracks(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<(usize, TrackRow)>, DbError> {
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("%{escaped}%");
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, title, artist, album, duration_ms, sample_rate, channels, bit_depth, file_mtime_ms, added_at_ms, updated_at_ms, position
+             FROM (SELECT *, ROW_NUMBER() OVER (ORDER BY path ASC) - 1 AS position FROM tracks)
+             WHERE title LIKE ?1 ESCAPE '\\' OR artist LIKE ?1 ESCAPE '\\' OR album LIKE ?1 ESCAPE '\\'
+             ORDER BY position LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![pattern, limit as i64], |row| {
+            Ok((row.get::<_, i64>(12)? as usize, read_track_row(row)?))
+        })?;
+        collect_rows(rows)
+    }
+
     pub fn get_artwork_data_for_track(&self, track_path: &str) -> Result<Option<Vec<u8>>, DbError> {
         self.conn
             .query_row(
@@ -724,6 +1910,24 @@ impl Database {
             .map_err(DbError::from)
     }
 
+    /// Artwork of the first track in `album`, by path, that has any.
+    pub fn get_artwork_data_for_album(&self, album: &str) -> Result<Option<Vec<u8>>, DbError> {
+        self.conn
+            .query_row(
+                "SELECT aa.data
+                 FROM tracks t
+                 JOIN track_artwork ta ON ta.track_id = t.id
+                 JOIN artwork_assets aa ON aa.id = ta.artwork_id
+                 WHERE t.album = ?1
+                 ORDER BY t.path ASC
+                 LIMIT 1",
+                params![album],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()
+            .map_err(DbError::from)
+    }
+
     pub fn get_track_by_id(&self, track_id: TrackId) -> Result<Option<TrackRow>, DbError> {
         self.conn
             .query_row(
@@ -771,6 +1975,270 @@ impl Database {
         collect_rows(rows)
     }
 
+    /// How many tracks [`Self::list_tracks_by_prefix`] would find without a
+    /// limit.
+    pub fn count_tracks_by_prefix(&self, path_prefix: &str) -> Result<usize, DbError> {
+        let escaped = escape_sql_like(path_prefix);
+        let slash_pattern = format!("{escaped}/%");
+        let backslash_pattern = format!("{escaped}\\\\%");
+        let count: i64 = self
+            .conn
+            .prepare_cached(
+                "SELECT COUNT(*) FROM tracks
+             WHERE path = ?1
+                OR path LIKE ?2 ESCAPE '\\'
+                OR path LIKE ?3 ESCAPE '\\'",
+            )?
+            .query_row(
+                params![path_prefix, slash_pattern, backslash_pattern],
+                |row| row.get(0),
+            )?;
+        Ok(count.max(0) as usize)
+    }
+
+    /// The tracks in a built-in playlist, at most `limit`.
+    pub fn list_smart_playlist_tracks(
+        &self,
+        smart: SmartPlaylist,
+        limit: usize,
+    ) -> Result<Vec<TrackRow>, DbError> {
+        let (sql, since) = smart_playlist_query(smart);
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let rows = stmt.query_map(params![since, limit as i64], read_track_row)?;
+        collect_rows(rows)
+    }
+
+    /// The last `limit` different tracks played, most recent first.
+    pub fn list_recently_played(&self, limit: usize) -> Result<Vec<TrackRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT t.id, t.path, t.title, t.artist, t.album, t.duration_ms, t.sample_rate,
+                    t.channels, t.bit_depth, t.file_mtime_ms, t.added_at_ms, t.updated_at_ms
+             FROM tracks t
+             JOIN (SELECT track_id, MAX(id) AS last_play FROM play_history GROUP BY track_id) h
+               ON h.track_id = t.id
+             ORDER BY h.last_play DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], read_track_row)?;
+        collect_rows(rows)
+    }
+
+    /// How long was listened to in each of the `count` periods up to the one
+    /// holding `now_ms`, oldest first, by the local date each starts on.
+    /// Every play counts its track's full length, since only plays to the
+    /// end are recorded.
+    pub fn listening_time(
+        &self,
+        period: StatsPeriod,
+        count: usize,
+        now_ms: i64,
+    ) -> Result<Vec<(String, i64)>, DbError> {
+        let (align, step, period_ms) = match period {
+            StatsPeriod::Day => ("'start of day'", "'-1 day'", 24 * 60 * 60 * 1000),
+            StatsPeriod::Week => (
+                "'weekday 0', '-6 days'",
+                "'-7 days'",
+                7 * 24 * 60 * 60 * 1000,
+            ),
+        };
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "WITH RECURSIVE periods(start, n) AS (
+                 SELECT date(?1 / 1000, 'unixepoch', 'localtime', {align}), 1
+                 UNION ALL SELECT date(start, {step}), n + 1 FROM periods WHERE n < ?2
+             ),
+             played AS (
+                 SELECT date(h.played_at_ms / 1000, 'unixepoch', 'localtime', {align}) AS start,
+                        SUM(COALESCE(t.duration_ms, 0)) AS listened_ms
+                 FROM play_history h JOIN tracks t ON t.id = h.track_id
+                 WHERE h.played_at_ms >= ?3
+                 GROUP BY 1
+             )
+             SELECT p.start, COALESCE(played.listened_ms, 0) FROM periods p
+             LEFT JOIN played ON played.start = p.start
+             ORDER BY p.start ASC"
+        ))?;
+        // A period early, to allow for the local timezone and daylight saving.
+        let since = now_ms - (count as i64 + 1) * period_ms;
+        let rows = stmt.query_map(params![now_ms, count.max(1) as i64, since], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// The `limit` most played artists, albums or tracks, counted from the
+    /// play history, or the plays `during` a range, most played first.
+    pub fn top_played(
+        &self,
+        group: PlayGroup,
+        during: Option<&DateRange>,
+        limit: usize,
+    ) -> Result<Vec<PlayTally>, DbError> {
+        let (name, detail, key) = match group {
+            PlayGroup::Artist => (
+                "COALESCE(NULLIF(t.artist, ''), 'Unknown Artist')",
+                "NULL",
+                "1",
+            ),
+            PlayGroup::Album => ("t.album", "t.artist", "t.album, t.artist"),
+            PlayGroup::Track => ("COALESCE(NULLIF(t.title, ''), t.path)", "t.artist", "t.id"),
+        };
+        let filter = match group {
+            PlayGroup::Album => "AND COALESCE(t.album, '') <> ''",
+            _ => "",
+        };
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {name}, {detail}, COUNT(*) AS plays
+             FROM play_history h JOIN tracks t ON t.id = h.track_id
+             WHERE {PLAYED_DURING_SQL} {filter}
+             GROUP BY {key}
+             ORDER BY plays DESC, MAX(h.played_at_ms) DESC LIMIT ?1"
+        ))?;
+        let (from, to) = date_range_params(during);
+        let rows = stmt.query_map(params![limit as i64, from, to], |row| {
+            Ok(PlayTally {
+                name: row.get(0)?,
+                detail: row.get(1)?,
+                plays: row.get(2)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Plays in the history, or `during` a range, and how long they add up to.
+    pub fn play_totals(&self, during: Option<&DateRange>) -> Result<(i64, i64), DbError> {
+        let (from, to) = date_range_params(during);
+        Ok(self
+            .conn
+            .prepare_cached(&format!(
+                "SELECT COUNT(*), COALESCE(SUM(t.duration_ms), 0)
+                 FROM play_history h JOIN tracks t ON t.id = h.track_id
+                 WHERE {PLAYED_DURING_SQL}"
+            ))?
+            .query_row(params![0, from, to], |row| Ok((row.get(0)?, row.get(1)?)))?)
+    }
+
+    /// Today's date in local time, `YYYY-MM-DD`, as the play history
+    /// queries see it.
+    pub fn local_today(&self) -> Result<String, DbError> {
+        Ok(self
+            .conn
+            .prepare_cached("SELECT date('now', 'localtime')")?
+            .query_row([], |row| row.get(0))?)
+    }
+
+    /// How far local time is ahead of UTC right now, in minutes.
+    pub fn local_utc_offset_minutes(&self) -> Result<i32, DbError> {
+        Ok(self
+            .conn
+            .prepare_cached(
+                "SELECT CAST(round((julianday('now', 'localtime') - julianday('now')) * 1440) AS INTEGER)",
+            )?
+            .query_row([], |row| row.get(0))?)
+    }
+
+    /// How many different tracks and artists were played `during` a range.
+    pub fn play_variety(&self, during: &DateRange) -> Result<(i64, i64), DbError> {
+        Ok(self
+            .conn
+            .prepare_cached(&format!(
+                "SELECT COUNT(DISTINCT t.id), COUNT(DISTINCT COALESCE(NULLIF(t.artist, ''), 'Unknown Artist'))
+                 FROM play_history h JOIN tracks t ON t.id = h.track_id
+                 WHERE {PLAYED_DURING_SQL}"
+            ))?
+            .query_row(params![0, during.from, during.to], |row| Ok((row.get(0)?, row.get(1)?)))?)
+    }
+
+    /// How long was listened to in each local month `during` a range that
+    /// had any plays, as `YYYY-MM`, oldest first.
+    pub fn listening_by_month(&self, during: &DateRange) -> Result<Vec<(String, i64)>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT strftime('%Y-%m', h.played_at_ms / 1000, 'unixepoch', 'localtime') AS month,
+                    SUM(COALESCE(t.duration_ms, 0))
+             FROM play_history h JOIN tracks t ON t.id = h.track_id
+             WHERE {PLAYED_DURING_SQL}
+             GROUP BY month ORDER BY month ASC"
+        ))?;
+        let rows = stmt.query_map(params![0, during.from, during.to], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// How many tracks there are of each file extension, lowercased, most
+    /// common first. Paths without one, such as streamed songs, count as
+    /// `""`.
+    pub fn track_formats(&self) -> Result<Vec<(String, i64)>, DbError> {
+        // The extension is what is left once everything up to the last dot
+        // is trimmed; a slash in it means the dot was in a folder name.
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT CASE WHEN instr(ext, '/') > 0 OR instr(path, '.') = 0 THEN '' ELSE ext END AS format,
+                    COUNT(*) AS tracks
+             FROM (SELECT path, lower(replace(path, rtrim(path, replace(path, '.', '')), '')) AS ext
+                   FROM tracks)
+             GROUP BY format ORDER BY tracks DESC, format ASC",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// How many tracks were added in each local month that had any, as
+    /// `YYYY-MM`, oldest first.
+    pub fn tracks_added_by_month(&self) -> Result<Vec<(String, i64)>, DbError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT strftime('%Y-%m', added_at_ms / 1000, 'unixepoch', 'localtime') AS month, COUNT(*)
+             FROM tracks GROUP BY month ORDER BY month ASC",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// How many tracks a built-in playlist holds, up to `limit`.
+    pub fn count_smart_playlist_tracks(
+        &self,
+        smart: SmartPlaylist,
+        limit: usize,
+    ) -> Result<usize, DbError> {
+        let (sql, since) = smart_playlist_query(smart);
+        let count: i64 = self
+            .conn
+            .prepare_cached(&format!("SELECT COUNT(*) FROM ({sql})"))?
+            .query_row(params![since, limit as i64], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Every track, or those under `path_prefix`, with its stats, by path.
+    pub fn list_tracks_with_stats(
+        &self,
+        path_prefix: Option<&str>,
+    ) -> Result<Vec<TrackWithStats>, DbError> {
+        let escaped = path_prefix.map(escape_sql_like);
+        let slash_pattern = escaped.as_ref().map(|e| format!("{e}/%"));
+        let backslash_pattern = escaped.as_ref().map(|e| format!("{e}\\\\%"));
+        let mut stmt = self.conn.prepare(
+            "SELECT t.id, t.path, t.title, t.artist, t.album, t.duration_ms, t.sample_rate, t.channels,
+                    t.bit_depth, t.file_mtime_ms, t.added_at_ms, t.updated_at_ms,
+                    s.rating, COALESCE(s.play_count, 0), s.last_played_at_ms
+             FROM tracks t
+             LEFT JOIN track_stats s ON s.track_id = t.id
+             WHERE ?1 IS NULL
+                OR t.path = ?1
+                OR t.path LIKE ?2 ESCAPE '\\'
+                OR t.path LIKE ?3 ESCAPE '\\'
+             ORDER BY t.path ASC",
+        )?;
+        let rows = stmt.query_map(
+            params![path_prefix, slash_pattern, backslash_pattern],
+            |row| {
+                Ok(TrackWithStats {
+                    track: read_track_row(row)?,
+                    rating: row.get(12)?,
+                    play_count: row.get(13)?,
+                    last_played_at_ms: row.get(14)?,
+                })
+            },
+        )?;
+        collect_rows(rows)
+    }
+
     pub fn list_track_paths_under_prefix(&self, root_path: &str) -> Result<Vec<String>, DbError> {
         let escaped = escape_sql_like(root_path);
         let slash_pattern = format!("{escaped}/%");
@@ -790,6 +2258,30 @@ impl Database {
         collect_rows(rows)
     }
 
+    /// Paths under `root_path` with the file mtime recorded when each was
+    /// last imported, so a rescan can skip files that have not changed.
+    pub fn list_track_mtimes_under_prefix(
+        &self,
+        root_path: &str,
+    ) -> Result<Vec<(String, Option<i64>)>, DbError> {
+        let escaped = escape_sql_like(root_path);
+        let slash_pattern = format!("{escaped}/%");
+        let backslash_pattern = format!("{escaped}\\\\%");
+
+        let mut stmt = self.conn.prepare(
+            "SELECT path, file_mtime_ms FROM tracks
+             WHERE path = ?1
+                OR path LIKE ?2 ESCAPE '\\'
+                OR path LIKE ?3 ESCAPE '\\'
+             ORDER BY path ASC",
+        )?;
+        let rows = stmt.query_map(
+            params![root_path, slash_pattern, backslash_pattern],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?)),
+        )?;
+        collect_rows(rows)
+    }
+
     pub fn delete_tracks_by_paths(&mut self, paths: &[String]) -> Result<usize, DbError> {
         if paths.is_empty() {
             return Ok(0);
@@ -809,6 +2301,35 @@ impl Database {
         Ok(deleted)
     }
 
+    /// Moves the track at `from`, or every track under it when it is a folder,
+    /// to the same place under `to`. Ids stay the same, so playlist entries
+    /// and artwork follow the file. A track already recorded at a destination
+    /// path is replaced. Returns how many tracks moved.
+    pub fn rename_track_paths(&mut self, from: &str, to: &str) -> Result<usize, DbError> {
+        let moving = self.list_track_paths_under_prefix(from)?;
+        if moving.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let now = now_ms();
+        {
+            let mut clear = tx.prepare_cached("DELETE FROM tracks WHERE path = ?1")?;
+            let mut rename = tx.prepare_cached(
+                "UPDATE tracks SET path = ?2, updated_at_ms = ?3 WHERE path = ?1",
+            )?;
+            for old_path in &moving {
+                let new_path = format!("{to}{}", &old_path[from.len()..]);
+                clear.execute(params![new_path])?;
+                rename.execute(params![old_path, new_path, now])?;
+            }
+        }
+        tx.commit()?;
+        Ok(moving.len())
+    }
+
     pub fn upsert_track_artwork_batch(
         &mut self,
         items: &[TrackArtworkUpsert],
@@ -974,6 +2495,7 @@ impl Database {
              VALUES (?1, ?2, ?3, ?4)",
             params![playlist_id, track_id.0.to_string(), next_position, now_ms()],
         )?;
+        touch_playlist(&self.conn, playlist_id)?;
         Ok(next_position)
     }
 
@@ -1027,10 +2549,12 @@ impl Database {
     }
 
     pub fn clear_playlist_tracks(&self, playlist_id: &str) -> Result<usize, DbError> {
-        Ok(self.conn.execute(
+        let removed = self.conn.execute(
             "DELETE FROM playlist_entries WHERE playlist_id = ?1",
             params![playlist_id],
-        )?)
+        )?;
+        touch_playlist(&self.conn, playlist_id)?;
+        Ok(removed)
     }
 
     pub fn remove_playlist_track_at(
@@ -1053,49 +2577,1292 @@ impl Database {
              WHERE playlist_id = ?1 AND position > ?2",
             params![playlist_id, position],
         )?;
+        touch_playlist(&self.conn, playlist_id)?;
         Ok(())
     }
 
-    pub fn stats(&self) -> Result<DatabaseStats, DbError> {
-        let settings_count = count_table(&self.conn, StatsTable::AppSettings)?;
-        let library_root_count = count_table(&self.conn, StatsTable::LibraryRoots)?;
-        let track_count = count_table(&self.conn, StatsTable::Tracks)?;
-        let artwork_asset_count = count_table(&self.conn, StatsTable::ArtworkAssets)?;
-        let track_artwork_count = count_table(&self.conn, StatsTable::TrackArtwork)?;
-        let playlist_count = count_table(&self.conn, StatsTable::Playlists)?;
-        let playlist_entry_count = count_table(&self.conn, StatsTable::PlaylistEntries)?;
-        let page_count: i64 = self
-            .conn
-            .query_row("PRAGMA page_count;", [], |row| row.get(0))?;
-        let page_size: i64 = self
-            .conn
-            .query_row("PRAGMA page_size;", [], |row| row.get(0))?;
+    /// Moves the entry at `from` to `to`, shifting the entries in between.
+    pub fn move_playlist_track(
+        &self,
+        playlist_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<(), DbError> {
+        let count = self.playlist_track_count(playlist_id)?;
+        if !(0..count).contains(&from) || !(0..count).contains(&to) {
+            return Err(DbError::NotFound(format!(
+                "playlist entry {playlist_id}@{}",
+                if (0..count).contains(&from) { to } else { from }
+            )));
+        }
+        if from == to {
+            return Ok(());
+        }
+        let (lo, hi, shift) = if from < to {
+            (from + 1, to, -1)
+        } else {
+            (to, from - 1, 1)
+        };
+        // (playlist_id, position) is the primary key, so park rows on
+        // negative positions while shifting to avoid transient collisions.
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE playlist_entries SET position = -1 WHERE playlist_id = ?1 AND position = ?2",
+            params![playlist_id, from],
+        )?;
+        tx.execute(
+            "UPDATE playlist_entries SET position = -(position + ?4) - 2
+             WHERE playlist_id = ?1 AND position BETWEEN ?2 AND ?3",
+            params![playlist_id, lo, hi, shift],
+        )?;
+        tx.execute(
+            "UPDATE playlist_entries SET position = -(position + 2)
+             WHERE playlist_id = ?1 AND position <= -2",
+            params![playlist_id],
+        )?;
+        tx.execute(
+            "UPDATE playlist_entries SET position = ?2 WHERE playlist_id = ?1 AND position = -1",
+            params![playlist_id, to],
+        )?;
+        touch_playlist(&tx, playlist_id)?;
+        tx.commit()?;
+        Ok(())
+    }
 
-        Ok(DatabaseStats {
-            settings_count,
-            library_root_count,
-            track_count,
-            artwork_asset_count,
-            track_artwork_count,
-            playlist_count,
-            playlist_entry_count,
-            page_count,
-            page_size,
-            db_size_bytes: page_count.saturating_mul(page_size),
-        })
+    /// Adds a feed, or returns the existing subscription for the same URL.
+    pub fn subscribe_podcast(&self, feed_url: &str, title: &str) -> Result<PodcastRow, DbError> {
+        let now = now_ms();
+        self.conn.execute(
+            "INSERT INTO podcasts (id, feed_url, title, created_at_ms, updated_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT(feed_url) DO NOTHING",
+            params![Uuid::new_v4().to_string(), feed_url, title, now],
+        )?;
+        self.list_podcasts()?
+            .into_iter()
+            .find(|p| p.feed_url == feed_url)
+            .ok_or_else(|| DbError::NotFound(format!("podcast {feed_url}")))
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-enum StatsTable {
-    AppSettings,
-    LibraryRoots,
-    Tracks,
-    ArtworkAssets,
-    TrackArtwork,
-    Playlists,
-    PlaylistEntries,
-}
+    pub fn list_podcasts(&self) -> Result<Vec<PodcastRow>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.id, p.feed_url, p.title, p.refreshed_at_ms, p.created_at_ms,
+                    COUNT(e.id), COALESCE(SUM(CASE WHEN e.played = 0 THEN 1 ELSE 0 END), 0)
+             FROM podcasts p LEFT JOIN podcast_episodes e ON e.podcast_id = p.id
+             GROUP BY p.id
+             ORDER BY lower(p.title), p.title",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PodcastRow {
+                id: row.get(0)?,
+                feed_url: row.get(1)?,
+                title: row.get(2)?,
+                refreshed_at_ms: row.get(3)?,
+                created_at_ms: row.get(4)?,
+                episode_count: row.get(5)?,
+                unplayed_count: row.get(6)?,
+            })
+        })?;
+        collect_rows(rows)
+    }
+
+    /// Drops the subscription and its episodes. Downloaded files are left
+    /// for the caller to remove.
+    pub fn delete_podcast(&self, podcast_id: &str) -> Result<(), DbError> {
+        let changed = self
+            .conn
+            .execute("DELETE FROM podcasts WHERE id = ?1", params![podcast_id])?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("podcast {podcast_id}")));
+        }
+        Ok(())
+    }
+
+    /// Stores a fetched feed: the title, the refresh time and every episode,
+    /// matched on guid. Returns how many episodes were new.
+    pub fn update_podcast_feed(
+        &mut self,
+        podcast_id: &str,
+        title: &str,
+        episodes: &[PodcastEpisodeUpsert],
+    ) -> Result<usize, DbError> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let now = now_ms();
+        let changed = tx.execute(
+            "UPDATE podcasts SET title = ?2, refreshed_at_ms = ?3, updated_at_ms = ?3 WHERE id = ?1",
+            params![podcast_id, title, now],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("podcast {podcast_id}")));
+        }
+        let mut added = 0;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO podcast_episodes (
+                    id, podcast_id, guid, title, audio_url, published_at_ms, duration_ms, added_at_ms
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT(podcast_id, guid) DO NOTHING",
+            )?;
+            let mut update = tx.prepare_cached(
+                "UPDATE podcast_episodes
+                 SET title = ?3, audio_url = ?4, published_at_ms = ?5, duration_ms = ?6
+                 WHERE podcast_id = ?1 AND guid = ?2",
+            )?;
+            for episode in episodes {
+                let inserted = insert.execute(params![
+                    Uuid::new_v4().to_string(),
+                    podcast_id,
+                    episode.guid,
+                    episode.title,
+                    episode.audio_url,
+                    episode.published_at_ms,
+                    episode.duration_ms,
+                    now
+                ])?;
+                if inserted > 0 {
+                    added += 1;
+                } else {
+                    update.execute(params![
+                        podcast_id,
+                        episode.guid,
+                        episode.title,
+                        episode.audio_url,
+                        episode.published_at_ms,
+                        episode.duration_ms
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// A podcast's episodes, newest first.
+    pub fn list_podcast_episodes(
+        &self,
+        podcast_id: &str,
+        limit: usize,
+    ) -> Result<Vec<PodcastEpisodeRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {PODCAST_EPISODE_COLUMNS} FROM podcast_episodes WHERE podcast_id = ?1
+             ORDER BY published_at_ms IS NULL, published_at_ms DESC, added_at_ms DESC
+             LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![podcast_id, limit as i64], read_podcast_episode_row)?;
+        collect_rows(rows)
+    }
+
+    pub fn get_podcast_episode(
+        &self,
+        episode_id: &str,
+    ) -> Result<Option<PodcastEpisodeRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {PODCAST_EPISODE_COLUMNS} FROM podcast_episodes WHERE id = ?1"
+        ))?;
+        Ok(stmt
+            .query_row(params![episode_id], read_podcast_episode_row)
+            .optional()?)
+    }
+
+    pub fn set_podcast_episode_local_path(
+        &self,
+        episode_id: &str,
+        local_path: Option<&str>,
+    ) -> Result<(), DbError> {
+        let changed = self.conn.execute(
+            "UPDATE podcast_episodes SET local_path = ?2 WHERE id = ?1",
+            params![episode_id, local_path],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("episode {episode_id}")));
+        }
+        Ok(())
+    }
+
+    /// Records the resume position. Returns false when `episode_id` is not
+    /// an episode, so callers can pass any queue entry's id.
+    pub fn set_podcast_episode_progress(
+        &self,
+        episode_id: &str,
+        position_ms: i64,
+        played: bool,
+    ) -> Result<bool, DbError> {
+        let changed = self.conn.execute(
+            "UPDATE podcast_episodes SET position_ms = ?2, played = ?3 WHERE id = ?1",
+            params![episode_id, position_ms, bool_to_i64(played)],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Makes the playlists copied from `source` match `playlists`: each is
+    /// created or renamed and refilled, and ones the server no longer has
+    /// are deleted. Local playlists are never touched. Returns how many
+    /// playlists `source` has now.
+    pub fn sync_remote_playlists(
+        &mut self,
+        source: &str,
+        playlists: &[RemotePlaylist],
+    ) -> Result<usize, DbError> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let now = now_ms();
+        let mut kept = Vec::with_capacity(playlists.len());
+        for playlist in playlists {
+            let existing: Option<String> = tx
+                .query_row(
+                    "SELECT playlist_id FROM remote_playlists WHERE source = ?1 AND remote_id = ?2",
+                    params![source, playlist.remote_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let playlist_id = match existing {
+                Some(id) => {
+                    tx.execute(
+                        "UPDATE playlists SET name = ?2, updated_at_ms = ?3 WHERE id = ?1",
+                        params![id, playlist.name, now],
+                    )?;
+                    tx.execute(
+                        "DELETE FROM playlist_entries WHERE playlist_id = ?1",
+                        params![id],
+                    )?;
+                    id
+                }
+                None => {
+                    let id = Uuid::new_v4().to_string();
+                    tx.execute(
+                        "INSERT INTO playlists (id, name, created_at_ms, updated_at_ms) VALUES (?1, ?2, ?3, ?3)",
+                        params![id, playlist.name, now],
+                    )?;
+                    tx.execute(
+                        "INSERT INTO remote_playlists (playlist_id, source, remote_id) VALUES (?1, ?2, ?3)",
+                        params![id, source, playlist.remote_id],
+                    )?;
+                    id
+                }
+            };
+            {
+                let mut insert = tx.prepare_cached(
+                    "INSERT INTO playlist_entries (playlist_id, track_id, position, added_at_ms)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for (position, track_id) in playlist.track_ids.iter().enumerate() {
+                    insert.execute(params![
+                        playlist_id,
+                        track_id.0.to_string(),
+                        position as i64,
+                        now
+                    ])?;
+                }
+            }
+            kept.push(playlist_id);
+        }
+        let stale: Vec<String> = {
+            let mut stmt =
+                tx.prepare("SELECT playlist_id FROM remote_playlists WHERE source = ?1")?;
+            let rows = stmt.query_map(params![source], |row| row.get::<_, String>(0))?;
+            collect_rows(rows)?
+                .into_iter()
+                .filter(|id| !kept.contains(id))
+                .collect()
+        };
+        for id in &stale {
+            tx.execute("DELETE FROM playlists WHERE id = ?1", params![id])?;
+        }
+        tx.commit()?;
+        Ok(kept.len())
+    }
+
+    pub fn get_track_resume(&self, track_id: &str) -> Result<Option<TrackResumeRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT track_id, remember, position_ms FROM track_resume WHERE track_id = ?1",
+        )?;
+        Ok(stmt
+            .query_row(params![track_id], |row| {
+                Ok(TrackResumeRow {
+                    track_id: row.get(0)?,
+                    remember: row.get::<_, Option<i64>>(1)?.map(|v| v != 0),
+                    position_ms: row.get(2)?,
+                })
+            })
+            .optional()?)
+    }
+
+    /// Sets whether a track remembers its position; `None` goes back to the
+    /// length and folder rules. The saved position is kept either way.
+    pub fn set_track_remember(
+        &self,
+        track_id: &str,
+        remember: Option<bool>,
+    ) -> Result<(), DbError> {
+        let changed = self.conn.execute(
+            "INSERT INTO track_resume (track_id, remember, updated_at_ms)
+             SELECT id, ?2, ?3 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET remember = excluded.remember,
+                                                 updated_at_ms = excluded.updated_at_ms",
+            params![track_id, remember.map(bool_to_i64), now_ms()],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("track {track_id}")));
+        }
+        Ok(())
+    }
+
+    /// The volume offset set for a track in dB, 0 when none is set.
+    pub fn track_volume_offset(&self, track_id: &str) -> Result<f32, DbError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT offset_db FROM track_volume WHERE track_id = ?1")?;
+        let offset: Option<f64> = stmt
+            .query_row(params![track_id], |row| row.get(0))
+            .optional()?;
+        Ok(offset.unwrap_or(0.0) as f32)
+    }
+
+    /// Sets a track's volume offset in dB; 0 removes it.
+    pub fn set_track_volume_offset(&self, track_id: &str, offset_db: f32) -> Result<(), DbError> {
+        if offset_db == 0.0 {
+            self.conn.execute(
+                "DELETE FROM track_volume WHERE track_id = ?1",
+                params![track_id],
+            )?;
+            return Ok(());
+        }
+        let changed = self.conn.execute(
+            "INSERT INTO track_volume (track_id, offset_db, updated_at_ms)
+             SELECT id, ?2, ?3 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET offset_db = excluded.offset_db,
+                                                 updated_at_ms = excluded.updated_at_ms",
+            params![track_id, f64::from(offset_db), now_ms()],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("track {track_id}")));
+        }
+        Ok(())
+    }
+
+    /// Local tracks with no tempo estimate yet, or whose file changed since
+    /// it was made, by path.
+    pub fn tracks_needing_analysis(&self, limit: usize) -> Result<Vec<TrackRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT t.id, t.path, t.title, t.artist, t.album, t.duration_ms, t.sample_rate,
+                    t.channels, t.bit_depth, t.file_mtime_ms, t.added_at_ms, t.updated_at_ms
+             {NEEDS_ANALYSIS_SQL} ORDER BY t.path ASC LIMIT ?1"
+        ))?;
+        let rows = stmt.query_map(params![limit as i64], read_track_row)?;
+        collect_rows(rows)
+    }
+
+    pub fn count_tracks_needing_analysis(&self) -> Result<usize, DbError> {
+        let count: i64 = self
+            .conn
+            .prepare_cached(&format!("SELECT COUNT(*) {NEEDS_ANALYSIS_SQL}"))?
+            .query_row([], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Records a track's estimated tempo. `None` marks one that could not be
+    /// analyzed, so it is not tried again until its file changes.
+    pub fn set_track_bpm(&self, track_id: &str, bpm: Option<f32>) -> Result<(), DbError> {
+        let changed = self.conn.execute(
+            "INSERT INTO track_analysis (track_id, bpm, file_mtime_ms, analyzed_at_ms)
+             SELECT id, ?2, file_mtime_ms, ?3 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET bpm = excluded.bpm,
+                                                 file_mtime_ms = excluded.file_mtime_ms,
+                                                 analyzed_at_ms = excluded.analyzed_at_ms",
+            params![track_id, bpm.map(f64::from), now_ms()],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("track {track_id}")));
+        }
+        Ok(())
+    }
+
+    /// Local tracks not measured for loudness yet, or whose file changed
+    /// since, by path.
+    pub fn tracks_needing_loudness(&self, limit: usize) -> Result<Vec<TrackRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT t.id, t.path, t.title, t.artist, t.album, t.duration_ms, t.sample_rate,
+                    t.channels, t.bit_depth, t.file_mtime_ms, t.added_at_ms, t.updated_at_ms
+             {NEEDS_LOUDNESS_SQL} ORDER BY t.path ASC LIMIT ?1"
+        ))?;
+        let rows = stmt.query_map(params![limit as i64], read_track_row)?;
+        collect_rows(rows)
+    }
+
+    pub fn count_tracks_needing_loudness(&self) -> Result<usize, DbError> {
+        let count: i64 = self
+            .conn
+            .prepare_cached(&format!("SELECT COUNT(*) {NEEDS_LOUDNESS_SQL}"))?
+            .query_row([], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Records a track's loudness. `None` marks one that could not be
+    /// measured, so it is not tried again until its file changes.
+    pub fn set_track_loudness(
+        &self,
+        track_id: &str,
+        loudness: Option<&TrackLoudness>,
+    ) -> Result<(), DbError> {
+        let changed = self.conn.execute(
+            "INSERT INTO track_loudness (track_id, integrated_lufs, true_peak_db, replaygain_track_db,
+                                         replaygain_album_db, file_mtime_ms, analyzed_at_ms)
+             SELECT id, ?2, ?3, ?4, ?5, file_mtime_ms, ?6 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET integrated_lufs = excluded.integrated_lufs,
+                                                 true_peak_db = excluded.true_peak_db,
+                                                 replaygain_track_db = excluded.replaygain_track_db,
+                                                 replaygain_album_db = excluded.replaygain_album_db,
+                                                 file_mtime_ms = excluded.file_mtime_ms,
+                                                 analyzed_at_ms = excluded.analyzed_at_ms",
+            params![
+                track_id,
+                loudness.map(|l| f64::from(l.integrated_lufs)),
+                loudness.map(|l| f64::from(l.true_peak_db)),
+                loudness.and_then(|l| l.replaygain_track_db).map(f64::from),
+                loudness.and_then(|l| l.replaygain_album_db).map(f64::from),
+                now_ms()
+            ],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("track {track_id}")));
+        }
+        Ok(())
+    }
+
+    /// A track's loudness, when it has been measured.
+    pub fn track_loudness(&self, track_id: &str) -> Result<Option<TrackLoudness>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {LOUDNESS_COLUMNS} FROM track_loudness
+             WHERE track_id = ?1 AND integrated_lufs IS NOT NULL"
+        ))?;
+        stmt.query_row(params![track_id], read_track_loudness)
+            .optional()
+            .map_err(DbError::from)
+    }
+
+    /// The measured loudness of each track on an album, with its length.
+    pub fn list_album_loudness(
+        &self,
+        album: &str,
+        artist: &str,
+    ) -> Result<Vec<(TrackLoudness, Option<i64>)>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {LOUDNESS_COLUMNS}, t.duration_ms FROM track_loudness
+             JOIN tracks t ON t.id = track_loudness.track_id
+             WHERE t.album = ?1 AND COALESCE(t.artist, '') = ?2 AND integrated_lufs IS NOT NULL"
+        ))?;
+        let rows = stmt.query_map(params![album, artist], |row| {
+            Ok((read_track_loudness(row)?, row.get(4)?))
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Local tracks with no silence found at `threshold_db` yet, or whose
+    /// file changed since, by path.
+    pub fn tracks_needing_silence(
+        &self,
+        threshold_db: i16,
+        limit: usize,
+    ) -> Result<Vec<TrackRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT t.id, t.path, t.title, t.artist, t.album, t.duration_ms, t.sample_rate,
+                    t.channels, t.bit_depth, t.file_mtime_ms, t.added_at_ms, t.updated_at_ms
+             {NEEDS_SILENCE_SQL} ORDER BY t.path ASC LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![threshold_db, limit as i64], read_track_row)?;
+        collect_rows(rows)
+    }
+
+    pub fn count_tracks_needing_silence(&self, threshold_db: i16) -> Result<usize, DbError> {
+        let count: i64 = self
+            .conn
+            .prepare_cached(&format!("SELECT COUNT(*) {NEEDS_SILENCE_SQL}"))?
+            .query_row(params![threshold_db], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Records the silence found in a track at `threshold_db`. `None` marks
+    /// one that could not be decoded, so it is not tried again until its
+    /// file changes.
+    pub fn set_track_silence(
+        &self,
+        track_id: &str,
+        threshold_db: i16,
+        silence: Option<&TrackSilence>,
+    ) -> Result<(), DbError> {
+        let changed = self.conn.execute(
+            "INSERT INTO track_silence (track_id, leading_ms, trailing_ms, duration_ms, threshold_db,
+                                        file_mtime_ms, analyzed_at_ms)
+             SELECT id, ?2, ?3, ?4, ?5, file_mtime_ms, ?6 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET leading_ms = excluded.leading_ms,
+                                                 trailing_ms = excluded.trailing_ms,
+                                                 duration_ms = excluded.duration_ms,
+                                                 threshold_db = excluded.threshold_db,
+                                                 file_mtime_ms = excluded.file_mtime_ms,
+                                                 analyzed_at_ms = excluded.analyzed_at_ms",
+            params![
+                track_id,
+                silence.map(|s| s.leading_ms),
+                silence.map(|s| s.trailing_ms),
+                silence.map(|s| s.duration_ms),
+                threshold_db,
+                now_ms()
+            ],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("track {track_id}")));
+        }
+        Ok(())
+    }
+
+    /// The silence found in a track at `threshold_db`, when it has been
+    /// looked for at that threshold.
+    pub fn track_silence(
+        &self,
+        track_id: &str,
+        threshold_db: i16,
+    ) -> Result<Option<TrackSilence>, DbError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT leading_ms, trailing_ms, duration_ms FROM track_silence
+             WHERE track_id = ?1 AND threshold_db = ?2 AND leading_ms IS NOT NULL",
+        )?;
+        stmt.query_row(params![track_id, threshold_db], |row| {
+            Ok(TrackSilence {
+                leading_ms: row.get(0)?,
+                trailing_ms: row.get(1)?,
+                duration_ms: row.get(2)?,
+            })
+        })
+        .optional()
+        .map_err(DbError::from)
+    }
+
+    /// Records what the health check found in a track's file, replacing
+    /// any earlier result.
+    pub fn set_track_health(&self, track_id: &str, health: &TrackHealth) -> Result<(), DbError> {
+        let changed = self.conn.execute(
+            "INSERT INTO track_health (track_id, readable, missing_tags, low_bitrate_kbps,
+                                       file_mtime_ms, checked_at_ms)
+             SELECT id, ?2, ?3, ?4, file_mtime_ms, ?5 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET readable = excluded.readable,
+                                                 missing_tags = excluded.missing_tags,
+                                                 low_bitrate_kbps = excluded.low_bitrate_kbps,
+                                                 file_mtime_ms = excluded.file_mtime_ms,
+                                                 checked_at_ms = excluded.checked_at_ms",
+            params![
+                track_id,
+                health.readable,
+                health.missing_tags.join(","),
+                health.low_bitrate_kbps,
+                now_ms()
+            ],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("track {track_id}")));
+        }
+        Ok(())
+    }
+
+    /// When the health check last looked at any file, if it ever has.
+    pub fn last_health_check_ms(&self) -> Result<Option<i64>, DbError> {
+        Ok(self
+            .conn
+            .prepare_cached("SELECT MAX(checked_at_ms) FROM track_health")?
+            .query_row([], |row| row.get(0))?)
+    }
+
+    /// How many tracks have `issue`.
+    pub fn count_health_issue(&self, issue: HealthIssue) -> Result<usize, DbError> {
+        let count: i64 = self
+            .conn
+            .prepare_cached(&format!(
+                "SELECT COUNT(*) {HEALTH_SQL} WHERE {}",
+                issue.condition()
+            ))?
+            .query_row([], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Up to `limit` tracks with `issue`, by path, each with what the health
+    /// check found in its file when that is still current.
+    pub fn tracks_with_health_issue(
+        &self,
+        issue: HealthIssue,
+        limit: usize,
+    ) -> Result<Vec<(TrackRow, Option<TrackHealth>)>, DbError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT t.id, t.path, t.title, t.artist, t.album, t.duration_ms, t.sample_rate,
+                    t.channels, t.bit_depth, t.file_mtime_ms, t.added_at_ms, t.updated_at_ms,
+                    h.readable, h.missing_tags, h.low_bitrate_kbps
+             {HEALTH_SQL} WHERE {} ORDER BY t.path ASC LIMIT ?1",
+            issue.condition()
+        ))?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            let health = match row.get::<_, Option<bool>>(12)? {
+                Some(readable) => Some(TrackHealth {
+                    readable,
+                    missing_tags: row
+                        .get::<_, String>(13)?
+                        .split(',')
+                        .filter(|field| !field.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    low_bitrate_kbps: row.get(14)?,
+                }),
+                None => None,
+            };
+            Ok((read_track_row(row)?, health))
+        })?;
+        collect_rows(rows)
+    }
+
+    /// Track ids and paths after `after_path`, by path, for going through
+    /// the whole library in pages.
+    pub fn track_paths_after(
+        &self,
+        after_path: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, String)>, DbError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, path FROM tracks WHERE path > ?1 ORDER BY path ASC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![after_path, limit as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Sets whichever of a track's title, artist and album are empty to the
+    /// values given; ones already set are kept.
+    pub fn fill_track_tags(
+        &self,
+        path: &str,
+        title: Option<String>,
+        artist: Option<String>,
+        album: Option<String>,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE tracks SET title = COALESCE(NULLIF(title, ''), ?2),
+                               artist = COALESCE(NULLIF(artist, ''), ?3),
+                               album = COALESCE(NULLIF(album, ''), ?4),
+                               updated_at_ms = ?5
+             WHERE path = ?1",
+            params![path, title, artist, album, now_ms()],
+        )?;
+        Ok(())
+    }
+
+    /// Every estimated tempo, by track id.
+    pub fn track_bpms(&self) -> Result<Vec<(String, f32)>, DbError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT track_id, bpm FROM track_analysis WHERE bpm IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)? as f32))
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Saves where playback of a track stopped. Returns false when
+    /// `track_id` is not a library track, such as a podcast episode.
+    /// Records where playback of `id` stopped: a podcast episode's progress,
+    /// or else a track's resume position when `remember` is set. A finished
+    /// track starts from the beginning next time.
+    pub fn save_playback_position(
+        &self,
+        id: &str,
+        position_ms: i64,
+        finished: bool,
+        remember: bool,
+    ) -> Result<(), DbError> {
+        if !self.set_podcast_episode_progress(id, position_ms, finished)? && remember {
+            self.set_track_resume_position(id, if finished { 0 } else { position_ms })?;
+        }
+        Ok(())
+    }
+
+    pub fn set_track_resume_position(
+        &self,
+        track_id: &str,
+        position_ms: i64,
+    ) -> Result<bool, DbError> {
+        let changed = self.conn.execute(
+            "INSERT INTO track_resume (track_id, position_ms, updated_at_ms)
+             SELECT id, ?2, ?3 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET position_ms = excluded.position_ms,
+                                                 updated_at_ms = excluded.updated_at_ms",
+            params![track_id, position_ms, now_ms()],
+        )?;
+        Ok(changed > 0)
+    }
+
+    pub fn get_track_stats(&self, track_id: &str) -> Result<Option<TrackStatsRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT track_id, rating, play_count, last_played_at_ms FROM track_stats WHERE track_id = ?1",
+        )?;
+        Ok(stmt
+            .query_row(params![track_id], |row| {
+                Ok(TrackStatsRow {
+                    track_id: row.get(0)?,
+                    rating: row.get(1)?,
+                    play_count: row.get(2)?,
+                    last_played_at_ms: row.get(3)?,
+                })
+            })
+            .optional()?)
+    }
+
+    /// Counts a play of the track to the end and adds it to the play
+    /// history. Returns false when `track_id` is not a library track.
+    pub fn record_track_play(&self, track_id: &str) -> Result<bool, DbError> {
+        let now = now_ms();
+        self.conn.execute(
+            "INSERT INTO play_history (track_id, played_at_ms) SELECT id, ?2 FROM tracks WHERE id = ?1",
+            params![track_id, now],
+        )?;
+        let changed = self.conn.execute(
+            "INSERT INTO track_stats (track_id, play_count, last_played_at_ms, updated_at_ms)
+             SELECT id, 1, ?2, ?2 FROM tracks WHERE id = ?1
+             ON CONFLICT(track_id) DO UPDATE SET play_count = play_count + 1,
+                                                 last_played_at_ms = excluded.last_played_at_ms,
+                                                 updated_at_ms = excluded.updated_at_ms",
+            params![track_id, now],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Brings in ratings, play counts and playlists read from another
+    /// player, matching entries to tracks by path. Importing twice changes
+    /// nothing: play counts and last-played times only ever go up, a rating
+    /// already set in auric is kept, and playlists whose name is taken are
+    /// skipped.
+    pub fn import_track_stats(
+        &mut self,
+        library: &ImportedLibrary,
+    ) -> Result<StatsImportSummary, DbError> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let now = now_ms();
+        let mut summary = StatsImportSummary::default();
+        {
+            let mut find = tx.prepare_cached("SELECT id FROM tracks WHERE path = ?1")?;
+            let mut track_id = |path: &str| -> Result<Option<String>, DbError> {
+                Ok(find.query_row(params![path], |row| row.get(0)).optional()?)
+            };
+            let mut upsert = tx.prepare_cached(
+                "INSERT INTO track_stats (track_id, rating, play_count, last_played_at_ms, updated_at_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(track_id) DO UPDATE SET
+                    rating = COALESCE(track_stats.rating, excluded.rating),
+                    play_count = MAX(track_stats.play_count, excluded.play_count),
+                    last_played_at_ms = NULLIF(MAX(COALESCE(track_stats.last_played_at_ms, 0),
+                                                   COALESCE(excluded.last_played_at_ms, 0)), 0),
+                    updated_at_ms = excluded.updated_at_ms",
+            )?;
+            for track in &library.tracks {
+                let Some(id) = track_id(&track.path)? else {
+                    summary.unmatched_tracks += 1;
+                    continue;
+                };
+                upsert.execute(params![
+                    id,
+                    track.rating,
+                    track.play_count.unwrap_or(0).max(0),
+                    track.last_played_at_ms,
+                    now
+                ])?;
+                summary.tracks += 1;
+            }
+
+            let mut name_taken =
+                tx.prepare_cached("SELECT 1 FROM playlists WHERE name = ?1 COLLATE NOCASE")?;
+            let mut create = tx.prepare_cached(
+                "INSERT INTO playlists (id, name, created_at_ms, updated_at_ms) VALUES (?1, ?2, ?3, ?3)",
+            )?;
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO playlist_entries (playlist_id, track_id, position, added_at_ms)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for playlist in &library.playlists {
+                if name_taken.exists(params![playlist.name])? {
+                    summary.skipped_playlists += 1;
+                    continue;
+                }
+                let playlist_id = Uuid::new_v4().to_string();
+                create.execute(params![playlist_id, playlist.name, now])?;
+                let mut position = 0i64;
+                for path in &playlist.paths {
+                    let Some(id) = track_id(path)? else {
+                        summary.unmatched_entries += 1;
+                        continue;
+                    };
+                    insert.execute(params![playlist_id, id, position, now])?;
+                    position += 1;
+                }
+                summary.playlists += 1;
+                summary.playlist_entries += position as usize;
+            }
+        }
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    pub fn add_track_bookmark(
+        &self,
+        track_id: &str,
+        position_ms: i64,
+        label: &str,
+    ) -> Result<TrackBookmarkRow, DbError> {
+        let bookmark = TrackBookmarkRow {
+            id: Uuid::new_v4().to_string(),
+            track_id: track_id.to_string(),
+            position_ms,
+            label: label.to_string(),
+            created_at_ms: now_ms(),
+        };
+        let changed = self.conn.execute(
+            "INSERT INTO track_bookmarks (id, track_id, position_ms, label, created_at_ms)
+             SELECT ?1, id, ?3, ?4, ?5 FROM tracks WHERE id = ?2",
+            params![
+                bookmark.id,
+                bookmark.track_id,
+                bookmark.position_ms,
+                bookmark.label,
+                bookmark.created_at_ms
+            ],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("track {track_id}")));
+        }
+        Ok(bookmark)
+    }
+
+    /// A track's bookmarks in playback order.
+    pub fn list_track_bookmarks(&self, track_id: &str) -> Result<Vec<TrackBookmarkRow>, DbError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, track_id, position_ms, label, created_at_ms FROM track_bookmarks
+             WHERE track_id = ?1 ORDER BY position_ms ASC, created_at_ms ASC",
+        )?;
+        let rows = stmt.query_map(params![track_id], |row| {
+            Ok(TrackBookmarkRow {
+                id: row.get(0)?,
+                track_id: row.get(1)?,
+                position_ms: row.get(2)?,
+                label: row.get(3)?,
+                created_at_ms: row.get(4)?,
+            })
+        })?;
+        collect_rows(rows)
+    }
+
+    pub fn delete_track_bookmark(&self, bookmark_id: &str) -> Result<(), DbError> {
+        let changed = self.conn.execute(
+            "DELETE FROM track_bookmarks WHERE id = ?1",
+            params![bookmark_id],
+        )?;
+        if changed == 0 {
+            return Err(DbError::NotFound(format!("bookmark {bookmark_id}")));
+        }
+        Ok(())
+    }
+
+    /// Writes a backup to `dest`: a JSON export when the name ends in
+    /// `.json`, otherwise a compacted copy of the whole database, artwork
+    /// included. An existing file is never overwritten.
+    pub fn write_library_backup(&self, dest: &Path) -> Result<(), DbError> {
+        if dest.exists() {
+            return Err(DbError::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", dest.display()),
+            )));
+        }
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let is_json = dest
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+        if is_json {
+            let export = self.export_library()?;
+            fs::write(dest, serde_json::to_vec_pretty(&export)?)?;
+        } else {
+            self.conn
+                .execute("VACUUM INTO ?1", params![dest.to_string_lossy()])?;
+        }
+        Ok(())
+    }
+
+    pub fn export_library(&self) -> Result<LibraryExport, DbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value_json FROM app_settings ORDER BY key ASC")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut settings = Vec::new();
+        for row in collect_rows(rows)? {
+            settings.push(ExportedSetting {
+                key: row.0,
+                value: serde_json::from_str(&row.1)?,
+            });
+        }
+
+        let roots = self
+            .list_library_roots()?
+            .into_iter()
+            .map(|row| ExportedRoot {
+                id: row.id,
+                path: row.path,
+                watched: row.watched,
+                recursive: row.options.recursive,
+                include_extensions: row.options.include_extensions.join(","),
+                exclude_extensions: row.options.exclude_extensions.join(","),
+                auto_artwork: row.options.auto_artwork,
+                paused: row.paused,
+                created_at_ms: row.created_at_ms,
+                updated_at_ms: row.updated_at_ms,
+            })
+            .collect();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, title, artist, album, duration_ms, sample_rate, channels, bit_depth, file_mtime_ms, added_at_ms, updated_at_ms
+             FROM tracks ORDER BY path ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ExportedTrack {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                title: row.get(2)?,
+                artist: row.get(3)?,
+                album: row.get(4)?,
+                duration_ms: row.get(5)?,
+                sample_rate: row.get(6)?,
+                channels: row.get(7)?,
+                bit_depth: row.get(8)?,
+                file_mtime_ms: row.get(9)?,
+                added_at_ms: row.get(10)?,
+                updated_at_ms: row.get(11)?,
+            })
+        })?;
+        let tracks = collect_rows(rows)?;
+
+        let mut entries_stmt = self.conn.prepare(
+            "SELECT track_id, added_at_ms FROM playlist_entries WHERE playlist_id = ?1 ORDER BY position ASC",
+        )?;
+        let mut playlists = Vec::new();
+        for row in self.list_playlists()? {
+            let rows = entries_stmt.query_map(params![row.id], |entry| {
+                Ok(ExportedPlaylistEntry {
+                    track_id: entry.get(0)?,
+                    added_at_ms: entry.get(1)?,
+                })
+            })?;
+            playlists.push(ExportedPlaylist {
+                entries: collect_rows(rows)?,
+                id: row.id,
+                name: row.name,
+                created_at_ms: row.created_at_ms,
+                updated_at_ms: row.updated_at_ms,
+            });
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT track_id, rating, play_count, last_played_at_ms, updated_at_ms
+             FROM track_stats ORDER BY track_id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ExportedTrackStats {
+                track_id: row.get(0)?,
+                rating: row.get(1)?,
+                play_count: row.get(2)?,
+                last_played_at_ms: row.get(3)?,
+                updated_at_ms: row.get(4)?,
+            })
+        })?;
+        let track_stats = collect_rows(rows)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT track_id, played_at_ms FROM play_history ORDER BY id ASC")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ExportedPlay {
+                track_id: row.get(0)?,
+                played_at_ms: row.get(1)?,
+            })
+        })?;
+        let play_history = collect_rows(rows)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT track_id, remember, position_ms, updated_at_ms FROM track_resume ORDER BY track_id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ExportedResume {
+                track_id: row.get(0)?,
+                remember: row.get::<_, Option<i64>>(1)?.map(|v| v != 0),
+                position_ms: row.get(2)?,
+                updated_at_ms: row.get(3)?,
+            })
+        })?;
+        let resume = collect_rows(rows)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, track_id, position_ms, label, created_at_ms FROM track_bookmarks
+             ORDER BY track_id ASC, position_ms ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ExportedBookmark {
+                id: row.get(0)?,
+                track_id: row.get(1)?,
+                position_ms: row.get(2)?,
+                label: row.get(3)?,
+                created_at_ms: row.get(4)?,
+            })
+        })?;
+        let bookmarks = collect_rows(rows)?;
+
+        Ok(LibraryExport {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            schema_version: self.schema_version()?,
+            exported_at_ms: now_ms(),
+            settings,
+            roots,
+            tracks,
+            playlists,
+            track_stats,
+            play_history,
+            resume,
+            bookmarks,
+        })
+    }
+
+    /// Merges an export into this library in one transaction. Rows from the
+    /// backup win: a local root, track or playlist with the same path or name
+    /// but a different id is replaced, so the backup's ids survive.
+    pub fn import_library(
+        &mut self,
+        export: &LibraryExport,
+    ) -> Result<LibraryImportSummary, DbError> {
+        let mut summary = LibraryImportSummary::default();
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        for setting in &export.settings {
+            tx.execute(
+                "INSERT INTO app_settings (key, value_json, updated_at_ms)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key) DO UPDATE SET value_json = excluded.value_json, updated_at_ms = excluded.updated_at_ms",
+                params![setting.key, serde_json::to_string(&setting.value)?, now_ms()],
+            )?;
+            summary.settings += 1;
+        }
+
+        for root in &export.roots {
+            tx.execute(
+                "DELETE FROM library_roots WHERE path = ?1 AND id <> ?2",
+                params![root.path, root.id],
+            )?;
+            tx.execute(
+                "INSERT INTO library_roots (id, path, watched, recursive, include_extensions, exclude_extensions, auto_artwork, paused, created_at_ms, updated_at_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT(id) DO UPDATE SET
+                    path = excluded.path, watched = excluded.watched, recursive = excluded.recursive,
+                    include_extensions = excluded.include_extensions, exclude_extensions = excluded.exclude_extensions,
+                    auto_artwork = excluded.auto_artwork, paused = excluded.paused, updated_at_ms = excluded.updated_at_ms",
+                params![
+                    root.id,
+                    root.path,
+                    bool_to_i64(root.watched),
+                    bool_to_i64(root.recursive),
+                    RootOptions::parse_extensions(&root.include_extensions).join(","),
+                    RootOptions::parse_extensions(&root.exclude_extensions).join(","),
+                    bool_to_i64(root.auto_artwork),
+                    bool_to_i64(root.paused),
+                    root.created_at_ms,
+                    root.updated_at_ms,
+                ],
+            )?;
+            summary.roots += 1;
+        }
+
+        for track in &export.tracks {
+            Uuid::parse_str(&track.id)?;
+            tx.execute(
+                "DELETE FROM tracks WHERE path = ?1 AND id <> ?2",
+                params![track.path, track.id],
+            )?;
+            tx.execute(
+                "INSERT INTO tracks (id, path, title, artist, album, duration_ms, sample_rate, channels, bit_depth, file_mtime_ms, added_at_ms, updated_at_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                 ON CONFLICT(id) DO UPDATE SET
+                    path = excluded.path, title = excluded.title, artist = excluded.artist, album = excluded.album,
+                    duration_ms = excluded.duration_ms, sample_rate = excluded.sample_rate, channels = excluded.channels,
+                    bit_depth = excluded.bit_depth, file_mtime_ms = excluded.file_mtime_ms, updated_at_ms = excluded.updated_at_ms",
+                params![
+                    track.id,
+                    track.path,
+                    track.title,
+                    track.artist,
+                    track.album,
+                    track.duration_ms,
+                    track.sample_rate,
+                    track.channels,
+                    track.bit_depth,
+                    track.file_mtime_ms,
+                    track.added_at_ms,
+                    track.updated_at_ms,
+                ],
+            )?;
+            summary.tracks += 1;
+        }
+
+        for playlist in &export.playlists {
+            tx.execute(
+                "DELETE FROM playlists WHERE name = ?1 COLLATE NOCASE AND id <> ?2",
+                params![playlist.name, playlist.id],
+            )?;
+            tx.execute(
+                "INSERT INTO playlists (id, name, created_at_ms, updated_at_ms)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(id) DO UPDATE SET name = excluded.name, updated_at_ms = excluded.updated_at_ms",
+                params![playlist.id, playlist.name, playlist.created_at_ms, playlist.updated_at_ms],
+            )?;
+            tx.execute(
+                "DELETE FROM playlist_entries WHERE playlist_id = ?1",
+                params![playlist.id],
+            )?;
+            let mut position = 0i64;
+            for entry in &playlist.entries {
+                let inserted = tx.execute(
+                    "INSERT INTO playlist_entries (playlist_id, track_id, position, added_at_ms)
+                     SELECT ?1, id, ?3, ?4 FROM tracks WHERE id = ?2",
+                    params![playlist.id, entry.track_id, position, entry.added_at_ms],
+                )?;
+                if inserted == 0 {
+                    summary.skipped_entries += 1;
+                } else {
+                    position += 1;
+                    summary.playlist_entries += 1;
+                }
+            }
+            summary.playlists += 1;
+        }
+
+        // Stats and plays of tracks in neither the backup nor the library
+        // are dropped; a play already recorded at the same time is not
+        // counted twice.
+        for stats in &export.track_stats {
+            summary.track_stats += tx.execute(
+                "INSERT INTO track_stats (track_id, rating, play_count, last_played_at_ms, updated_at_ms)
+                 SELECT id, ?2, ?3, ?4, ?5 FROM tracks WHERE id = ?1
+                 ON CONFLICT(track_id) DO UPDATE SET
+                    rating = excluded.rating, play_count = excluded.play_count,
+                    last_played_at_ms = excluded.last_played_at_ms, updated_at_ms = excluded.updated_at_ms",
+                params![
+                    stats.track_id,
+                    stats.rating,
+                    stats.play_count,
+                    stats.last_played_at_ms,
+                    stats.updated_at_ms,
+                ],
+            )?;
+        }
+        for play in &export.play_history {
+            summary.plays += tx.execute(
+                "INSERT INTO play_history (track_id, played_at_ms)
+                 SELECT id, ?2 FROM tracks WHERE id = ?1 AND NOT EXISTS (
+                    SELECT 1 FROM play_history WHERE track_id = ?1 AND played_at_ms = ?2
+                 )",
+                params![play.track_id, play.played_at_ms],
+            )?;
+        }
+        for resume in &export.resume {
+            summary.resume_positions += tx.execute(
+                "INSERT INTO track_resume (track_id, remember, position_ms, updated_at_ms)
+                 SELECT id, ?2, ?3, ?4 FROM tracks WHERE id = ?1
+                 ON CONFLICT(track_id) DO UPDATE SET
+                    remember = excluded.remember, position_ms = excluded.position_ms,
+                    updated_at_ms = excluded.updated_at_ms",
+                params![
+                    resume.track_id,
+                    resume.remember.map(bool_to_i64),
+                    resume.position_ms,
+                    resume.updated_at_ms,
+                ],
+            )?;
+        }
+        for bookmark in &export.bookmarks {
+            summary.bookmarks += tx.execute(
+                "INSERT INTO track_bookmarks (id, track_id, position_ms, label, created_at_ms)
+                 SELECT ?1, id, ?3, ?4, ?5 FROM tracks WHERE id = ?2
+                 ON CONFLICT(id) DO UPDATE SET
+                    track_id = excluded.track_id, position_ms = excluded.position_ms, label = excluded.label",
+                params![
+                    bookmark.id,
+                    bookmark.track_id,
+                    bookmark.position_ms,
+                    bookmark.label,
+                    bookmark.created_at_ms,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    pub fn stats(&self) -> Result<DatabaseStats, DbError> {
+        let settings_count = count_table(&self.conn, StatsTable::AppSettings)?;
+        let library_root_count = count_table(&self.conn, StatsTable::LibraryRoots)?;
+        let track_count = count_table(&self.conn, StatsTable::Tracks)?;
+        let track_duration_ms: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(duration_ms), 0) FROM tracks",
+            [],
+            |row| row.get(0),
+        )?;
+        let artwork_asset_count = count_table(&self.conn, StatsTable::ArtworkAssets)?;
+        let track_artwork_count = count_table(&self.conn, StatsTable::TrackArtwork)?;
+        let playlist_count = count_table(&self.conn, StatsTable::Playlists)?;
+        let playlist_entry_count = count_table(&self.conn, StatsTable::PlaylistEntries)?;
+        let page_count: i64 = self
+            .conn
+            .query_row("PRAGMA page_count;", [], |row| row.get(0))?;
+        let page_size: i64 = self
+            .conn
+            .query_row("PRAGMA page_size;", [], |row| row.get(0))?;
+
+        Ok(DatabaseStats {
+            settings_count,
+            library_root_count,
+            track_count,
+            track_duration_ms,
+            artwork_asset_count,
+            track_artwork_count,
+            playlist_count,
+            playlist_entry_count,
+            page_count,
+            page_size,
+            db_size_bytes: page_count.saturating_mul(page_size),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StatsTable {
+    AppSettings,
+    LibraryRoots,
+    Tracks,
+    ArtworkAssets,
+    TrackArtwork,
+    Playlists,
+    PlaylistEntries,
+}
 
 impl StatsTable {
     fn as_sql(self) -> &'static str {
@@ -1111,6 +3878,82 @@ impl StatsTable {
     }
 }
 
+/// Brings the schema up to `target`. Each migration runs in its own
+/// transaction together with its `user_version` bump and history row, so a
+/// failure rolls that step back and leaves the database at the last version
+/// that applied cleanly.
+fn run_migrations(
+    conn: &mut Connection,
+    migrations: &[Migration],
+    target: i64,
+) -> Result<(), DbError> {
+    let current: i64 = conn.query_row("PRAGMA user_version;", [], |row| row.get(0))?;
+    if current > target {
+        return Err(DbError::UnsupportedSchemaVersion {
+            found: current,
+            max_supported: target,
+        });
+    }
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    tx.execute_batch(SCHEMA_VERSION_TABLE_SQL)?;
+    if current == 0 {
+        tx.execute_batch(SCHEMA_SQL)?;
+        tx.execute(
+            "INSERT OR IGNORE INTO schema_version (version, name, applied_at_ms) VALUES (?1, 'initial schema', ?2)",
+            params![target, now_ms()],
+        )?;
+        tx.execute_batch(&format!("PRAGMA user_version = {target};"))?;
+        tx.commit()?;
+        return Ok(());
+    }
+    tx.execute(
+        "INSERT INTO schema_version (version, name, applied_at_ms)
+         SELECT ?1, 'baseline', ?2 WHERE NOT EXISTS (SELECT 1 FROM schema_version)",
+        params![current, now_ms()],
+    )?;
+    tx.commit()?;
+
+    for migration in migrations {
+        if migration.version <= current || migration.version > target {
+            continue;
+        }
+        let failed = |source| DbError::Migration {
+            version: migration.version,
+            name: migration.name,
+            source,
+        };
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(failed)?;
+        tx.execute_batch(migration.sql).map_err(failed)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO schema_version (version, name, applied_at_ms) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.name, now_ms()],
+        )
+        .map_err(failed)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {};", migration.version))
+            .map_err(failed)?;
+        tx.commit().map_err(failed)?;
+        tracing::info!(
+            version = migration.version,
+            name = migration.name,
+            "applied schema migration"
+        );
+    }
+
+    Ok(())
+}
+
+/// Marks a playlist as changed after its entries were edited.
+fn touch_playlist(conn: &Connection, playlist_id: &str) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE playlists SET updated_at_ms = ?2 WHERE id = ?1",
+        params![playlist_id, now_ms()],
+    )?;
+    Ok(())
+}
+
 fn count_table(conn: &Connection, table: StatsTable) -> Result<i64, DbError> {
     Ok(conn.query_row(table.as_sql(), [], |row| row.get(0))?)
 }
@@ -1120,11 +3963,104 @@ fn read_library_root(row: &Row<'_>) -> rusqlite::Result<LibraryRootRow> {
         id: row.get(0)?,
         path: row.get(1)?,
         watched: row.get::<_, i64>(2)? != 0,
+        options: RootOptions {
+            recursive: row.get::<_, i64>(5)? != 0,
+            include_extensions: RootOptions::parse_extensions(&row.get::<_, String>(6)?),
+            exclude_extensions: RootOptions::parse_extensions(&row.get::<_, String>(7)?),
+            auto_artwork: row.get::<_, i64>(8)? != 0,
+        },
+        paused: row.get::<_, i64>(9)? != 0,
+        offline_since_ms: row.get(10)?,
         created_at_ms: row.get(3)?,
         updated_at_ms: row.get(4)?,
     })
 }
 
+/// The query behind a built-in playlist and the lower bound it takes as
+/// `?1`; `?2` is the row limit.
+fn smart_playlist_query(smart: SmartPlaylist) -> (String, i64) {
+    const COLUMNS: &str = "t.id, t.path, t.title, t.artist, t.album, t.duration_ms, t.sample_rate,
+        t.channels, t.bit_depth, t.file_mtime_ms, t.added_at_ms, t.updated_at_ms";
+    match smart {
+        SmartPlaylist::AddedThisWeek => (
+            format!(
+                "SELECT {COLUMNS} FROM tracks t
+                 WHERE t.added_at_ms >= ?1
+                 ORDER BY t.added_at_ms DESC, t.path ASC LIMIT ?2"
+            ),
+            now_ms() - 7 * 24 * 60 * 60 * 1000,
+        ),
+        // SQLite knows the local timezone, so midnight is computed there.
+        SmartPlaylist::PlayedToday => (
+            format!(
+                "SELECT {COLUMNS} FROM tracks t
+                 JOIN (SELECT track_id, MAX(played_at_ms) AS played_at_ms FROM play_history
+                       WHERE played_at_ms >= MAX(?1, CAST(strftime('%s', 'now', 'localtime', 'start of day', 'utc') AS INTEGER) * 1000)
+                       GROUP BY track_id) h ON h.track_id = t.id
+                 ORDER BY h.played_at_ms DESC LIMIT ?2"
+            ),
+            0,
+        ),
+        SmartPlaylist::MostPlayed => (
+            format!(
+                "SELECT {COLUMNS} FROM tracks t
+                 JOIN track_stats s ON s.track_id = t.id
+                 WHERE s.play_count > 0 AND COALESCE(s.last_played_at_ms, 0) >= ?1
+                 ORDER BY s.play_count DESC, s.last_played_at_ms DESC LIMIT MIN(?2, 25)"
+            ),
+            0,
+        ),
+    }
+}
+
+/// The tracks [`Database::tracks_needing_analysis`] picks, as a `FROM`
+/// clause over `tracks t`.
+const NEEDS_ANALYSIS_SQL: &str = "FROM tracks t
+     LEFT JOIN track_analysis a ON a.track_id = t.id
+     WHERE a.track_id IS NULL OR a.file_mtime_ms IS NOT t.file_mtime_ms";
+
+/// The tracks [`Database::tracks_needing_loudness`] picks, as a `FROM`
+/// clause over `tracks t`.
+const NEEDS_LOUDNESS_SQL: &str = "FROM tracks t
+     LEFT JOIN track_loudness l ON l.track_id = t.id
+     WHERE l.track_id IS NULL OR l.file_mtime_ms IS NOT t.file_mtime_ms";
+
+/// Tracks with what the health check found in their files, ignoring results
+/// from before a file last changed.
+/// Keeps the plays of `play_history h` between the local dates `?2` and
+/// `?3`, or every play when they are `NULL`. `?1` is left to the query.
+const PLAYED_DURING_SQL: &str = "(?2 IS NULL
+     OR (h.played_at_ms >= CAST(strftime('%s', ?2, 'utc') AS INTEGER) * 1000
+         AND h.played_at_ms < CAST(strftime('%s', ?3, '+1 day', 'utc') AS INTEGER) * 1000))";
+
+fn date_range_params(during: Option<&DateRange>) -> (Option<&str>, Option<&str>) {
+    match during {
+        Some(range) => (Some(range.from.as_str()), Some(range.to.as_str())),
+        None => (None, None),
+    }
+}
+
+const HEALTH_SQL: &str = "FROM tracks t
+     LEFT JOIN track_health h ON h.track_id = t.id AND h.file_mtime_ms IS t.file_mtime_ms";
+
+/// The tracks [`Database::tracks_needing_silence`] picks, as a `FROM`
+/// clause over `tracks t` with the threshold as `?1`.
+const NEEDS_SILENCE_SQL: &str = "FROM tracks t
+     LEFT JOIN track_silence s ON s.track_id = t.id
+     WHERE s.track_id IS NULL OR s.file_mtime_ms IS NOT t.file_mtime_ms OR s.threshold_db IS NOT ?1";
+
+const LOUDNESS_COLUMNS: &str =
+    "integrated_lufs, true_peak_db, replaygain_track_db, replaygain_album_db";
+
+fn read_track_loudness(row: &Row<'_>) -> rusqlite::Result<TrackLoudness> {
+    Ok(TrackLoudness {
+        integrated_lufs: row.get::<_, f64>(0)? as f32,
+        true_peak_db: row.get::<_, f64>(1)? as f32,
+        replaygain_track_db: row.get::<_, Option<f64>>(2)?.map(|gain| gain as f32),
+        replaygain_album_db: row.get::<_, Option<f64>>(3)?.map(|gain| gain as f32),
+    })
+}
+
 fn read_track_row(row: &Row<'_>) -> rusqlite::Result<TrackRow> {
     let id_text: String = row.get(0)?;
     let id = parse_track_id_for_row(&id_text)?;
@@ -1144,6 +4080,23 @@ fn read_track_row(row: &Row<'_>) -> rusqlite::Result<TrackRow> {
     })
 }
 
+const PODCAST_EPISODE_COLUMNS: &str = "id, podcast_id, guid, title, audio_url, published_at_ms, duration_ms, local_path, position_ms, played";
+
+fn read_podcast_episode_row(row: &Row<'_>) -> rusqlite::Result<PodcastEpisodeRow> {
+    Ok(PodcastEpisodeRow {
+        id: row.get(0)?,
+        podcast_id: row.get(1)?,
+        guid: row.get(2)?,
+        title: row.get(3)?,
+        audio_url: row.get(4)?,
+        published_at_ms: row.get(5)?,
+        duration_ms: row.get(6)?,
+        local_path: row.get(7)?,
+        position_ms: row.get(8)?,
+        played: row.get::<_, i64>(9)? != 0,
+    })
+}
+
 fn read_track_artwork_row(row: &Row<'_>) -> rusqlite::Result<TrackArtworkRow> {
     let id_text: String = row.get(0)?;
     let track_id = parse_track_id_for_row(&id_text)?;
@@ -1255,6 +4208,31 @@ mod tests {
         assert_eq!(db.get_setting_json("missing").unwrap(), None);
     }
 
+    #[test]
+    fn search_matches_tags_and_reports_list_positions() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        for (path, title) in [
+            ("/m/a.flac", "Intro"),
+            ("/m/b.flac", "100% Pure"),
+            ("/m/c.flac", "Purely"),
+        ] {
+            db.upsert_track(&TrackRecord {
+                title: Some(title.to_string()),
+                ..sample_track(path)
+            })
+            .unwrap();
+        }
+        let found = db.search_tracks("pure", 10).unwrap();
+        let positions: Vec<_> = found
+            .iter()
+            .map(|(pos, row)| (*pos, row.path.as_str()))
+            .collect();
+        assert_eq!(positions, [(1, "/m/b.flac"), (2, "/m/c.flac")]);
+        // LIKE wildcards in the query match literally.
+        assert_eq!(db.search_tracks("0%", 10).unwrap().len(), 1);
+        assert_eq!(db.search_tracks("artist", 2).unwrap().len(), 2);
+    }
+
     #[test]
     fn library_root_upsert_is_idempotent_by_path() {
         let db = Database::open_in_memory_for_tests().unwrap();
@@ -1275,6 +4253,74 @@ mod tests {
         assert_eq!(db.list_library_roots().unwrap().len(), 1);
     }
 
+    #[test]
+    fn library_root_options_round_trip_and_survive_upsert() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        let root = LibraryRoot {
+            path: "/music".into(),
+            watched: true,
+        };
+        assert_eq!(
+            db.upsert_library_root(&root).unwrap().options,
+            RootOptions::default()
+        );
+
+        let options = RootOptions {
+            recursive: false,
+            include_extensions: vec!["flac".into(), "wav".into()],
+            exclude_extensions: vec!["mp3".into()],
+            auto_artwork: true,
+        };
+        assert_eq!(
+            db.set_library_root_options("/music", &options)
+                .unwrap()
+                .options,
+            options
+        );
+        assert_eq!(db.upsert_library_root(&root).unwrap().options, options);
+        assert!(matches!(
+            db.set_library_root_options("/elsewhere", &options),
+            Err(DbError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn library_roots_can_be_paused_and_marked_offline() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        db.upsert_library_root(&LibraryRoot {
+            path: "/mnt/nas".into(),
+            watched: true,
+        })
+        .unwrap();
+        assert!(db.set_library_root_paused("/mnt/nas", true).unwrap().paused);
+
+        assert!(db.set_library_root_offline("/mnt/nas", true).unwrap());
+        let since = db
+            .get_library_root_by_path("/mnt/nas")
+            .unwrap()
+            .unwrap()
+            .offline_since_ms;
+        assert!(since.is_some());
+        assert!(!db.set_library_root_offline("/mnt/nas", true).unwrap());
+        assert_eq!(
+            db.get_library_root_by_path("/mnt/nas")
+                .unwrap()
+                .unwrap()
+                .offline_since_ms,
+            since
+        );
+        assert!(db.set_library_root_offline("/mnt/nas", false).unwrap());
+        assert!(!db.set_library_root_offline("/not/a/root", true).unwrap());
+
+        let root = db.get_library_root_by_path("/mnt/nas").unwrap().unwrap();
+        assert!(root.paused && root.offline_since_ms.is_none());
+        assert!(
+            !db.set_library_root_paused("/mnt/nas", false)
+                .unwrap()
+                .paused
+        );
+    }
+
     #[test]
     fn playlist_crud_and_entries_work() {
         let db = Database::open_in_memory_for_tests().unwrap();
@@ -1286,16 +4332,140 @@ mod tests {
         db.upsert_track(&t1).unwrap();
         db.upsert_track(&t2).unwrap();
 
-        assert_eq!(db.append_track_to_playlist(&playlist_id, t1.id).unwrap(), 0);
-        assert_eq!(db.append_track_to_playlist(&playlist_id, t2.id).unwrap(), 1);
-        assert_eq!(db.playlist_track_count(&playlist_id).unwrap(), 2);
+        assert_eq!(db.append_track_to_playlist(&playlist_id, t1.id).unwrap(), 0);
+        assert_eq!(db.append_track_to_playlist(&playlist_id, t2.id).unwrap(), 1);
+        assert_eq!(db.playlist_track_count(&playlist_id).unwrap(), 2);
+
+        let playlists = db.list_playlists().unwrap();
+        assert_eq!(playlists.len(), 1);
+        assert_eq!(playlists[0].name, "Faves");
+
+        let t3 = sample_track("/music/c.flac");
+        db.upsert_track(&t3).unwrap();
+        db.append_track_to_playlist(&playlist_id, t3.id).unwrap();
+        let order = |db: &Database| {
+            db.list_playlist_tracks(&playlist_id, 10)
+                .unwrap()
+                .into_iter()
+                .map(|row| row.track.path)
+                .collect::<Vec<_>>()
+        };
+        db.move_playlist_track(&playlist_id, 0, 2).unwrap();
+        assert_eq!(
+            order(&db),
+            ["/music/b.flac", "/music/c.flac", "/music/a.flac"]
+        );
+        db.move_playlist_track(&playlist_id, 2, 0).unwrap();
+        assert_eq!(
+            order(&db),
+            ["/music/a.flac", "/music/b.flac", "/music/c.flac"]
+        );
+        assert!(db.move_playlist_track(&playlist_id, 0, 3).is_err());
+
+        db.delete_playlist(&playlist_id).unwrap();
+        assert!(db.list_playlists().unwrap().is_empty());
+    }
+
+    #[test]
+    fn playlist_summaries_total_tracks_and_follow_edits() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        let full = db.create_playlist("Full").unwrap();
+        let empty = db.create_playlist("Empty").unwrap();
+        let mut t1 = sample_track("/music/a.flac");
+        t1.duration_ms = Some(180_000);
+        let t2 = sample_track("/music/b.flac");
+        db.upsert_track(&t1).unwrap();
+        db.upsert_track(&t2).unwrap();
+        db.append_track_to_playlist(&full, t1.id).unwrap();
+        db.append_track_to_playlist(&full, t2.id).unwrap();
+
+        let summaries = db.list_playlist_summaries().unwrap();
+        let totals = summaries
+            .iter()
+            .map(|s| (s.playlist.name.as_str(), s.track_count, s.duration_ms))
+            .collect::<Vec<_>>();
+        assert_eq!(totals, [("Empty", 0, 0), ("Full", 2, 180_000)]);
+
+        db.conn
+            .execute(
+                "UPDATE playlists SET updated_at_ms = 0 WHERE id = ?1",
+                params![full],
+            )
+            .unwrap();
+        db.remove_playlist_track_at(&full, 1).unwrap();
+        let touched = db.list_playlists().unwrap();
+        assert!(touched.iter().find(|p| p.id == full).unwrap().updated_at_ms > 0);
+        assert!(touched.iter().any(|p| p.id == empty));
+    }
+
+    #[test]
+    fn recently_played_lists_each_track_once_newest_first() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        let tracks: Vec<_> = ["/music/a.flac", "/music/b.flac", "/music/c.flac"]
+            .into_iter()
+            .map(sample_track)
+            .collect();
+        for track in &tracks {
+            db.upsert_track(track).unwrap();
+        }
+        for index in [0, 1, 0, 2] {
+            db.record_track_play(&tracks[index].id.0.to_string())
+                .unwrap();
+        }
+        let recent = |limit| {
+            db.list_recently_played(limit)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.path)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            recent(10),
+            ["/music/c.flac", "/music/a.flac", "/music/b.flac"]
+        );
+        assert_eq!(recent(1), ["/music/c.flac"]);
+    }
 
-        let playlists = db.list_playlists().unwrap();
-        assert_eq!(playlists.len(), 1);
-        assert_eq!(playlists[0].name, "Faves");
+    #[test]
+    fn taken_playlists_come_back_without_tracks_that_left() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let playlist_id = db.create_playlist("Road Trip").unwrap();
+        let tracks: Vec<_> = ["/music/a.flac", "/music/b.flac", "/music/c.flac"]
+            .into_iter()
+            .map(sample_track)
+            .collect();
+        for track in &tracks {
+            db.upsert_track(track).unwrap();
+            db.append_track_to_playlist(&playlist_id, track.id).unwrap();
+        }
 
-        db.delete_playlist(&playlist_id).unwrap();
+        let deleted = db.take_playlist(&playlist_id).unwrap();
+        assert_eq!(deleted.playlist.name, "Road Trip");
+        assert_eq!(deleted.entries.len(), 3);
         assert!(db.list_playlists().unwrap().is_empty());
+        assert!(matches!(
+            db.take_playlist(&playlist_id),
+            Err(DbError::NotFound(_))
+        ));
+
+        db.delete_tracks_by_paths(&["/music/b.flac".to_string()])
+            .unwrap();
+        assert_eq!(db.restore_playlist(&deleted).unwrap(), 2);
+        let paths: Vec<_> = db
+            .list_playlist_tracks(&playlist_id, 10)
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.position, row.track.path))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (0, "/music/a.flac".to_string()),
+                (1, "/music/c.flac".to_string())
+            ]
+        );
+        // The name is taken again, so a second restore fails.
+        assert!(db.restore_playlist(&deleted).is_err());
     }
 
     #[test]
@@ -1351,6 +4521,45 @@ mod tests {
         assert_eq!(db.count_tracks().unwrap(), 1);
     }
 
+    #[test]
+    fn renaming_keeps_track_ids_and_playlist_entries() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let moved = sample_track("/music/old/a.flac");
+        let single = sample_track("/music/b.flac");
+        let stale = sample_track("/music/new/a.flac");
+        for track in [&moved, &single, &stale] {
+            db.upsert_track(track).unwrap();
+        }
+        let playlist = db.create_playlist("Mix").unwrap();
+        db.append_track_to_playlist(&playlist, moved.id).unwrap();
+
+        assert_eq!(
+            db.rename_track_paths("/music/old", "/music/new").unwrap(),
+            1
+        );
+        assert_eq!(
+            db.rename_track_paths("/music/b.flac", "/music/c.flac")
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.rename_track_paths("/music/gone", "/music/elsewhere")
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(db.count_tracks().unwrap(), 2);
+        let renamed = db.get_track_by_path("/music/new/a.flac").unwrap().unwrap();
+        assert_eq!(renamed.id, moved.id);
+        assert_eq!(
+            db.get_track_by_path("/music/c.flac").unwrap().unwrap().id,
+            single.id
+        );
+        let entries = db.list_playlist_tracks(&playlist, 10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].track.path, "/music/new/a.flac");
+    }
+
     #[test]
     fn artwork_cache_dedupes_and_purges_orphans() {
         let mut db = Database::open_in_memory_for_tests().unwrap();
@@ -1389,6 +4598,15 @@ mod tests {
         let row = db.get_track_artwork_by_path(&t1.path).unwrap().unwrap();
         assert_eq!(row.mime_type.as_deref(), Some("image/jpeg"));
         assert_eq!(row.byte_len, 4);
+        let album = t1.album.clone().unwrap();
+        assert_eq!(
+            db.get_artwork_data_for_album(&album).unwrap(),
+            Some(vec![1, 2, 3, 4])
+        );
+        assert_eq!(
+            db.get_artwork_data_for_album("No Such Album").unwrap(),
+            None
+        );
 
         db.delete_tracks_by_paths(std::slice::from_ref(&t1.path))
             .unwrap();
@@ -1406,6 +4624,14 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         conn.execute_batch(
             r#"
+            CREATE TABLE library_roots (
+                id TEXT PRIMARY KEY,
+                path TEXT NOT NULL UNIQUE,
+                watched INTEGER NOT NULL CHECK (watched IN (0, 1)),
+                created_at_ms INTEGER NOT NULL,
+                updated_at_ms INTEGER NOT NULL
+            );
+            INSERT INTO library_roots VALUES ('r1', '/music', 1, 0, 0);
             CREATE TABLE tracks (
                 id TEXT PRIMARY KEY,
                 path TEXT NOT NULL UNIQUE,
@@ -1431,8 +4657,832 @@ mod tests {
             ..DatabaseOptions::default()
         };
         let db = Database::from_connection(conn, &options, None).unwrap();
-        assert_eq!(db.schema_version().unwrap(), 2);
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
         assert_eq!(db.count_artwork_assets().unwrap(), 0);
         assert_eq!(db.count_track_artwork_links().unwrap(), 0);
+        let roots = db.list_library_roots().unwrap();
+        assert_eq!(roots[0].options, RootOptions::default());
+        let history: Vec<(i64, String)> = db
+            .applied_migrations()
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.version, m.name))
+            .collect();
+        assert_eq!(history[0], (1, "baseline".to_string()));
+        assert_eq!(history.last().unwrap().0, SCHEMA_VERSION);
+        assert_eq!(history.len(), MIGRATIONS.len() + 1);
+    }
+
+    #[test]
+    fn failed_migration_rolls_back_to_last_good_version() {
+        assert_eq!(MIGRATIONS.last().map(|m| m.version), Some(SCHEMA_VERSION));
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        assert_eq!(db.applied_migrations().unwrap()[0].name, "initial schema");
+
+        let next = SCHEMA_VERSION + 1;
+        let migrations = [
+            Migration {
+                version: next,
+                name: "good",
+                sql: "CREATE TABLE good_table (x INTEGER);",
+            },
+            Migration {
+                version: next + 1,
+                name: "broken",
+                sql: "CREATE TABLE half_done (x INTEGER); SELECT * FROM no_such_table;",
+            },
+        ];
+        let err = run_migrations(&mut db.conn, &migrations, next + 1).unwrap_err();
+        assert!(matches!(err, DbError::Migration { name: "broken", .. }));
+        assert_eq!(db.schema_version().unwrap(), next);
+        assert_eq!(
+            db.applied_migrations().unwrap().last().unwrap().version,
+            next
+        );
+        let half_done: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'half_done'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(half_done, 0);
+
+        assert!(matches!(
+            run_migrations(&mut db.conn, MIGRATIONS, SCHEMA_VERSION),
+            Err(DbError::UnsupportedSchemaVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn maintenance_removes_orphans_and_renumbers_playlists() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let tracks: Vec<TrackRecord> = ["/m/a.flac", "/m/b.flac", "/m/c.flac"]
+            .into_iter()
+            .map(sample_track)
+            .collect();
+        db.upsert_tracks_batch(&tracks).unwrap();
+        let playlist = db.create_playlist("Mix").unwrap();
+        for track in &tracks {
+            db.append_track_to_playlist(&playlist, track.id).unwrap();
+        }
+        db.upsert_track_artwork_batch(&[TrackArtworkUpsert {
+            track_path: "/m/a.flac".to_string(),
+            source_kind: "embedded".to_string(),
+            source: "tag".to_string(),
+            mime_type: None,
+            picture_type: None,
+            bytes: vec![1, 2, 3],
+        }])
+        .unwrap();
+
+        db.conn.execute_batch("PRAGMA foreign_keys = OFF;").unwrap();
+        db.conn
+            .execute(
+                "DELETE FROM tracks WHERE path IN ('/m/a.flac', '/m/b.flac')",
+                [],
+            )
+            .unwrap();
+        db.conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+
+        assert!(db.integrity_check().unwrap().is_empty());
+        let cleanup = db.remove_orphan_rows().unwrap();
+        assert_eq!(
+            cleanup,
+            OrphanCleanup {
+                playlist_entries: 2,
+                artwork_links: 1,
+                artwork_assets: 1,
+            }
+        );
+        let remaining = db.list_playlist_tracks(&playlist, 10).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].position, 0);
+        assert_eq!(remaining[0].track.id, tracks[2].id);
+        assert_eq!(db.remove_orphan_rows().unwrap().total(), 0);
+        db.vacuum().unwrap();
+    }
+
+    #[test]
+    fn library_export_restores_with_ids_preserved() {
+        let mut source = Database::open_in_memory_for_tests().unwrap();
+        source
+            .upsert_library_root(&LibraryRoot {
+                path: "/music".to_string(),
+                watched: true,
+            })
+            .unwrap();
+        let a = sample_track("/music/a.flac");
+        let b = sample_track("/music/b.flac");
+        source.upsert_tracks_batch(&[a.clone(), b.clone()]).unwrap();
+        let playlist = source.create_playlist("Road Trip").unwrap();
+        source.append_track_to_playlist(&playlist, b.id).unwrap();
+        source.append_track_to_playlist(&playlist, a.id).unwrap();
+        source.set_setting_json("ui.theme", &json!("nord")).unwrap();
+        let (a_id, b_id) = (a.id.0.to_string(), b.id.0.to_string());
+        source.record_track_play(&b_id).unwrap();
+        source
+            .conn
+            .execute_batch(&format!(
+                "INSERT INTO play_history (track_id, played_at_ms) VALUES ('{b_id}', 1000);
+                 UPDATE track_stats SET rating = 4, play_count = 2 WHERE track_id = '{b_id}';"
+            ))
+            .unwrap();
+        source.set_track_remember(&a_id, Some(true)).unwrap();
+        source.set_track_resume_position(&a_id, 90_000).unwrap();
+        let bookmark = source.add_track_bookmark(&a_id, 30_000, "Chorus").unwrap();
+
+        let mut export = source.export_library().unwrap();
+        let json = serde_json::to_string(&export).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LibraryExport>(&json).unwrap(),
+            export
+        );
+        assert_eq!(export.remap_paths("/music", "/mnt/nas/music"), 3);
+        assert_eq!(export.tracks[0].path, "/mnt/nas/music/a.flac");
+
+        let mut target = Database::open_in_memory_for_tests().unwrap();
+        let stale = sample_track("/mnt/nas/music/a.flac");
+        target.upsert_track(&stale).unwrap();
+        target.create_playlist("road trip").unwrap();
+
+        let summary = target.import_library(&export).unwrap();
+        assert_eq!(summary.tracks, 2);
+        assert_eq!(summary.playlist_entries, 2);
+        assert_eq!(summary.skipped_entries, 0);
+        assert_eq!((summary.track_stats, summary.plays), (1, 2));
+        assert_eq!((summary.resume_positions, summary.bookmarks), (1, 1));
+        assert_eq!(target.count_tracks().unwrap(), 2);
+        assert!(target.get_track_by_id(stale.id).unwrap().is_none());
+        assert_eq!(
+            target
+                .get_track_by_path("/mnt/nas/music/a.flac")
+                .unwrap()
+                .unwrap()
+                .id,
+            a.id
+        );
+        let playlists = target.list_playlists().unwrap();
+        assert_eq!(playlists.len(), 1);
+        assert_eq!(playlists[0].id, playlist);
+        let entries: Vec<TrackId> = target
+            .list_playlist_tracks(&playlist, 10)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.track.id)
+            .collect();
+        assert_eq!(entries, [b.id, a.id]);
+        assert_eq!(
+            target.get_setting_json("ui.theme").unwrap(),
+            Some(json!("nord"))
+        );
+        assert_eq!(
+            target.list_library_roots().unwrap()[0].path,
+            "/mnt/nas/music"
+        );
+        let stats = target.get_track_stats(&b_id).unwrap().unwrap();
+        assert_eq!((stats.rating, stats.play_count), (Some(4), 2));
+        assert!(target.get_track_stats(&a_id).unwrap().is_none());
+        let history: i64 = target
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM play_history WHERE track_id = ?1",
+                params![b_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(history, 2);
+        let played_today: Vec<String> = target
+            .list_smart_playlist_tracks(SmartPlaylist::PlayedToday, 10)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.path)
+            .collect();
+        assert_eq!(played_today, ["/mnt/nas/music/b.flac"]);
+        let resume = target.get_track_resume(&a_id).unwrap().unwrap();
+        assert_eq!((resume.remember, resume.position_ms), (Some(true), 90_000));
+        assert_eq!(target.list_track_bookmarks(&a_id).unwrap(), [bookmark]);
+
+        // Restoring again adds no plays, and a version 1 export without
+        // stats or history still reads.
+        assert_eq!(target.import_library(&export).unwrap().plays, 0);
+        let mut old: JsonValue = serde_json::to_value(&export).unwrap();
+        old.as_object_mut().unwrap().remove("track_stats");
+        for section in ["play_history", "resume", "bookmarks"] {
+            old.as_object_mut().unwrap().remove(section);
+        }
+        old["version"] = json!(1);
+        let old: LibraryExport = serde_json::from_value(old).unwrap();
+        assert!(old.track_stats.is_empty() && old.play_history.is_empty());
+        assert!(old.resume.is_empty() && old.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn library_backups_read_back_in_both_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&DatabaseOptions {
+            path: dir.path().join("auric.db"),
+            ..DatabaseOptions::default()
+        })
+        .unwrap();
+        db.upsert_track(&sample_track("/music/a.flac")).unwrap();
+        db.create_playlist("Mix").unwrap();
+
+        for name in ["backup.db", "backup.json"] {
+            let dest = dir.path().join(name);
+            db.write_library_backup(&dest).unwrap();
+            assert!(db.write_library_backup(&dest).is_err());
+            let export = read_library_backup(&dest).unwrap();
+            assert_eq!(export.tracks.len(), 1);
+            assert_eq!(export.playlists[0].name, "Mix");
+        }
+
+        fs::write(dir.path().join("other.json"), r#"{"format":"x","version":1,"schema_version":4,"exported_at_ms":0,"settings":[],"roots":[],"tracks":[],"playlists":[]}"#).unwrap();
+        assert!(matches!(
+            read_library_backup(&dir.path().join("other.json")),
+            Err(DbError::InvalidBackup(_))
+        ));
+    }
+
+    #[test]
+    fn podcast_refresh_adds_new_episodes_and_keeps_progress() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let feed = "https://example.com/feed.xml";
+        let podcast = db.subscribe_podcast(feed, feed).unwrap();
+        assert_eq!(db.subscribe_podcast(feed, "again").unwrap().id, podcast.id);
+
+        let episode = |guid: &str, published: i64| PodcastEpisodeUpsert {
+            guid: guid.to_string(),
+            title: format!("Episode {guid}"),
+            audio_url: format!("https://example.com/{guid}.mp3"),
+            published_at_ms: Some(published),
+            duration_ms: Some(60_000),
+        };
+        let added = db
+            .update_podcast_feed(
+                &podcast.id,
+                "Show",
+                &[episode("1", 1_000), episode("2", 2_000)],
+            )
+            .unwrap();
+        assert_eq!(added, 2);
+        let episodes = db.list_podcast_episodes(&podcast.id, 10).unwrap();
+        assert_eq!(episodes[0].guid, "2");
+        assert!(db
+            .set_podcast_episode_progress(&episodes[1].id, 30_000, false)
+            .unwrap());
+        db.set_podcast_episode_local_path(&episodes[1].id, Some("/tmp/1.mp3"))
+            .unwrap();
+        assert!(!db
+            .set_podcast_episode_progress("not-an-episode", 1, true)
+            .unwrap());
+
+        let mut renamed = episode("1", 1_000);
+        renamed.title = "Pilot".to_string();
+        let added = db
+            .update_podcast_feed(&podcast.id, "Show", &[renamed, episode("3", 3_000)])
+            .unwrap();
+        assert_eq!(added, 1);
+        let first = db.get_podcast_episode(&episodes[1].id).unwrap().unwrap();
+        assert_eq!(first.title, "Pilot");
+        assert_eq!(first.position_ms, 30_000);
+        assert_eq!(first.local_path.as_deref(), Some("/tmp/1.mp3"));
+
+        let listed = db.list_podcasts().unwrap();
+        assert_eq!(listed[0].title, "Show");
+        assert_eq!(listed[0].episode_count, 3);
+        assert!(listed[0].refreshed_at_ms.is_some());
+        db.delete_podcast(&podcast.id).unwrap();
+        assert!(db.get_podcast_episode(&first.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn remote_playlists_follow_the_server_and_leave_local_ones_alone() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let tracks = [sample_track("subsonic://1"), sample_track("subsonic://2")];
+        db.upsert_tracks_batch(&tracks).unwrap();
+        let local = db.create_playlist("Mine").unwrap();
+        let remote = |id: &str, name: &str, tracks: &[&TrackRecord]| RemotePlaylist {
+            remote_id: id.to_string(),
+            name: name.to_string(),
+            track_ids: tracks.iter().map(|t| t.id).collect(),
+        };
+
+        let synced = db
+            .sync_remote_playlists(
+                "subsonic",
+                &[
+                    remote("a", "Road Trip", &[&tracks[1], &tracks[0]]),
+                    remote("b", "Gone Soon", &[&tracks[0]]),
+                ],
+            )
+            .unwrap();
+        assert_eq!(synced, 2);
+        let road_trip = db
+            .list_playlists()
+            .unwrap()
+            .into_iter()
+            .find(|p| p.name == "Road Trip")
+            .unwrap();
+        let entries = db.list_playlist_tracks(&road_trip.id, 10).unwrap();
+        assert_eq!(entries[0].track.path, "subsonic://2");
+
+        db.sync_remote_playlists("subsonic", &[remote("a", "Road Trip 2", &[&tracks[0]])])
+            .unwrap();
+        let names: Vec<String> = db
+            .list_playlists()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"Road Trip 2".to_string()));
+        assert_eq!(db.playlist_track_count(&road_trip.id).unwrap(), 1);
+        assert_eq!(db.playlist_track_count(&local).unwrap(), 0);
+    }
+
+    #[test]
+    fn track_resume_and_bookmarks_follow_the_track() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let track = sample_track("/books/novel.m4b");
+        db.upsert_track(&track).unwrap();
+        let id = track.id.0.to_string();
+
+        assert!(db.get_track_resume(&id).unwrap().is_none());
+        assert!(db.set_track_resume_position(&id, 90_000).unwrap());
+        assert!(!db.set_track_resume_position("episode-id", 1).unwrap());
+        db.set_track_remember(&id, Some(true)).unwrap();
+        let resume = db.get_track_resume(&id).unwrap().unwrap();
+        assert_eq!((resume.remember, resume.position_ms), (Some(true), 90_000));
+        db.set_track_remember(&id, None).unwrap();
+        assert_eq!(db.get_track_resume(&id).unwrap().unwrap().remember, None);
+        assert!(db.set_track_remember("missing", Some(false)).is_err());
+
+        let later = db.add_track_bookmark(&id, 600_000, "Chapter 2").unwrap();
+        db.add_track_bookmark(&id, 5_000, "Prologue").unwrap();
+        let labels: Vec<String> = db
+            .list_track_bookmarks(&id)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.label)
+            .collect();
+        assert_eq!(labels, ["Prologue", "Chapter 2"]);
+        db.delete_track_bookmark(&later.id).unwrap();
+        assert!(db.delete_track_bookmark(&later.id).is_err());
+
+        db.delete_tracks_by_paths(std::slice::from_ref(&track.path))
+            .unwrap();
+        assert!(db.get_track_resume(&id).unwrap().is_none());
+        assert!(db.list_track_bookmarks(&id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn track_volume_offsets_are_stored_and_cleared() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let track = sample_track("/music/live.flac");
+        db.upsert_track(&track).unwrap();
+        let id = track.id.0.to_string();
+
+        assert_eq!(db.track_volume_offset(&id).unwrap(), 0.0);
+        db.set_track_volume_offset(&id, 4.5).unwrap();
+        assert_eq!(db.track_volume_offset(&id).unwrap(), 4.5);
+        db.set_track_volume_offset(&id, 0.0).unwrap();
+        assert_eq!(db.track_volume_offset(&id).unwrap(), 0.0);
+        assert!(db.set_track_volume_offset("missing", -3.0).is_err());
+
+        db.set_track_volume_offset(&id, -2.0).unwrap();
+        db.delete_tracks_by_paths(std::slice::from_ref(&track.path))
+            .unwrap();
+        assert_eq!(db.track_volume_offset(&id).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn track_bpms_are_stored_until_the_file_changes() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let fast = sample_track("/music/fast.flac");
+        let noise = sample_track("/music/noise.flac");
+        db.upsert_track(&fast).unwrap();
+        db.upsert_track(&noise).unwrap();
+        let id = fast.id.0.to_string();
+        assert_eq!(db.tracks_needing_analysis(10).unwrap().len(), 2);
+
+        db.set_track_bpm(&id, Some(128.0)).unwrap();
+        assert_eq!(db.count_tracks_needing_analysis().unwrap(), 1);
+        db.set_track_bpm(&noise.id.0.to_string(), None).unwrap();
+        assert!(db.tracks_needing_analysis(10).unwrap().is_empty());
+        assert_eq!(db.track_bpms().unwrap(), [(id.clone(), 128.0)]);
+        assert!(db.set_track_bpm("missing", Some(90.0)).is_err());
+
+        db.upsert_track(&TrackRecord {
+            file_mtime_ms: Some(1),
+            ..fast.clone()
+        })
+        .unwrap();
+        let stale = db.tracks_needing_analysis(10).unwrap();
+        assert_eq!(
+            stale.iter().map(|t| t.path.as_str()).collect::<Vec<_>>(),
+            [fast.path.as_str()]
+        );
+
+        db.delete_tracks_by_paths(std::slice::from_ref(&fast.path))
+            .unwrap();
+        assert!(db.track_bpms().unwrap().is_empty());
+    }
+
+    #[test]
+    fn track_loudness_is_kept_per_track_and_listed_by_album() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let first = TrackRecord {
+            duration_ms: Some(200_000),
+            ..sample_track("/music/album/01.flac")
+        };
+        let second = sample_track("/music/album/02.flac");
+        db.upsert_track(&first).unwrap();
+        db.upsert_track(&second).unwrap();
+        assert_eq!(db.count_tracks_needing_loudness().unwrap(), 2);
+
+        let measured = TrackLoudness {
+            integrated_lufs: -9.5,
+            true_peak_db: -0.3,
+            replaygain_track_db: Some(-8.5),
+            replaygain_album_db: None,
+        };
+        let id = first.id.0.to_string();
+        db.set_track_loudness(&id, Some(&measured)).unwrap();
+        db.set_track_loudness(&second.id.0.to_string(), None)
+            .unwrap();
+        assert_eq!(db.count_tracks_needing_loudness().unwrap(), 0);
+        assert!(db.tracks_needing_loudness(10).unwrap().is_empty());
+        assert_eq!(db.track_loudness(&id).unwrap(), Some(measured));
+        assert_eq!(db.track_loudness(&second.id.0.to_string()).unwrap(), None);
+        assert_eq!(
+            db.list_album_loudness("Album", "Artist").unwrap(),
+            [(measured, Some(200_000))]
+        );
+        assert!(db.set_track_loudness("missing", None).is_err());
+
+        db.upsert_track(&TrackRecord {
+            file_mtime_ms: Some(1),
+            ..second.clone()
+        })
+        .unwrap();
+        assert_eq!(db.tracks_needing_loudness(10).unwrap()[0].path, second.path);
+        db.delete_tracks_by_paths(std::slice::from_ref(&first.path))
+            .unwrap();
+        assert_eq!(db.track_loudness(&id).unwrap(), None);
+    }
+
+    #[test]
+    fn track_silence_is_kept_for_the_threshold_it_was_found_at() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let track = sample_track("/music/album/01.flac");
+        let broken = sample_track("/music/album/02.flac");
+        db.upsert_track(&track).unwrap();
+        db.upsert_track(&broken).unwrap();
+        assert_eq!(db.count_tracks_needing_silence(-60).unwrap(), 2);
+
+        let silence = TrackSilence {
+            leading_ms: 1_200,
+            trailing_ms: 4_000,
+            duration_ms: 180_000,
+        };
+        let id = track.id.0.to_string();
+        db.set_track_silence(&id, -60, Some(&silence)).unwrap();
+        db.set_track_silence(&broken.id.0.to_string(), -60, None)
+            .unwrap();
+        assert!(db.tracks_needing_silence(-60, 10).unwrap().is_empty());
+        assert_eq!(db.track_silence(&id, -60).unwrap(), Some(silence));
+        assert_eq!(
+            db.track_silence(&broken.id.0.to_string(), -60).unwrap(),
+            None
+        );
+        assert!(db.set_track_silence("missing", -60, None).is_err());
+
+        // Another threshold needs every track looked at again.
+        assert_eq!(db.track_silence(&id, -50).unwrap(), None);
+        assert_eq!(db.count_tracks_needing_silence(-50).unwrap(), 2);
+
+        db.upsert_track(&TrackRecord {
+            file_mtime_ms: Some(1),
+            ..track.clone()
+        })
+        .unwrap();
+        assert_eq!(
+            db.tracks_needing_silence(-60, 10).unwrap()[0].path,
+            track.path
+        );
+        db.delete_tracks_by_paths(std::slice::from_ref(&track.path))
+            .unwrap();
+        assert_eq!(db.track_silence(&id, -60).unwrap(), None);
+    }
+
+    #[test]
+    fn smart_playlists_follow_additions_and_plays() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        let old = sample_track("/music/old.flac");
+        let new = sample_track("/music/new.flac");
+        db.upsert_track(&old).unwrap();
+        db.upsert_track(&new).unwrap();
+        db.conn
+            .execute(
+                "UPDATE tracks SET added_at_ms = ?1 WHERE path = ?2",
+                params![now_ms() - 30 * 24 * 60 * 60 * 1000, old.path],
+            )
+            .unwrap();
+        let paths = |smart| -> Vec<String> {
+            db.list_smart_playlist_tracks(smart, 100)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.path)
+                .collect()
+        };
+        assert_eq!(paths(SmartPlaylist::AddedThisWeek), vec![new.path.clone()]);
+        assert_eq!(
+            db.count_smart_playlist_tracks(SmartPlaylist::AddedThisWeek, 100)
+                .unwrap(),
+            1
+        );
+        assert!(paths(SmartPlaylist::PlayedToday).is_empty());
+
+        for track in [&old, &new, &old] {
+            db.record_track_play(&track.id.0.to_string()).unwrap();
+        }
+        assert_eq!(
+            paths(SmartPlaylist::MostPlayed),
+            [old.path.clone(), new.path.clone()]
+        );
+        let mut today = paths(SmartPlaylist::PlayedToday);
+        today.sort();
+        assert_eq!(today, [new.path.clone(), old.path.clone()]);
+        assert_eq!(
+            SmartPlaylist::from_id(SmartPlaylist::MostPlayed.id()),
+            Some(SmartPlaylist::MostPlayed)
+        );
+    }
+
+    #[test]
+    fn health_issues_come_from_the_tracks_and_current_check_results() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        let good = TrackRecord {
+            duration_ms: Some(200_000),
+            ..sample_track("/music/good.flac")
+        };
+        let silent = TrackRecord {
+            duration_ms: Some(0),
+            ..sample_track("/music/zero.mp3")
+        };
+        let stream = TrackRecord {
+            duration_ms: Some(100_000),
+            ..sample_track("subsonic://7")
+        };
+        for track in [&good, &silent, &stream] {
+            db.upsert_track(track).unwrap();
+        }
+        let count = |issue| db.count_health_issue(issue).unwrap();
+        assert_eq!(count(HealthIssue::ZeroDuration), 1);
+        assert_eq!(
+            count(HealthIssue::MissingArtwork),
+            2,
+            "streams are left out"
+        );
+        assert_eq!(db.last_health_check_ms().unwrap(), None);
+        assert_eq!(count(HealthIssue::MissingTags), 0);
+
+        let health = TrackHealth {
+            readable: true,
+            missing_tags: vec!["artist".to_string(), "album".to_string()],
+            low_bitrate_kbps: Some(96),
+        };
+        db.set_track_health(&silent.id.0.to_string(), &health)
+            .unwrap();
+        db.set_track_health(
+            &good.id.0.to_string(),
+            &TrackHealth {
+                readable: false,
+                missing_tags: Vec::new(),
+                low_bitrate_kbps: None,
+            },
+        )
+        .unwrap();
+        assert!(db.last_health_check_ms().unwrap().is_some());
+        assert_eq!(count(HealthIssue::Unreadable), 1);
+        let low = db
+            .tracks_with_health_issue(HealthIssue::LowBitrate, 10)
+            .unwrap();
+        assert_eq!(low.len(), 1);
+        assert_eq!(low[0].0.path, "/music/zero.mp3");
+        assert_eq!(low[0].1.as_ref(), Some(&health));
+        assert!(db.set_track_health("missing", &health).is_err());
+
+        // A result from before the file changed no longer counts.
+        db.conn
+            .execute(
+                "UPDATE tracks SET file_mtime_ms = 99 WHERE path = '/music/zero.mp3'",
+                [],
+            )
+            .unwrap();
+        assert_eq!(count(HealthIssue::MissingTags), 0);
+        assert_eq!(count(HealthIssue::LowBitrate), 0);
+
+        db.conn
+            .execute(
+                "UPDATE tracks SET album = '' WHERE path = '/music/zero.mp3'",
+                [],
+            )
+            .unwrap();
+        db.fill_track_tags(
+            "/music/zero.mp3",
+            Some("New".into()),
+            None,
+            Some("Record".into()),
+        )
+        .unwrap();
+        let filled = db.get_track_by_path("/music/zero.mp3").unwrap().unwrap();
+        assert_eq!(filled.title.as_deref(), Some("Track"), "set tags are kept");
+        assert_eq!(filled.artist.as_deref(), Some("Artist"));
+        assert_eq!(filled.album.as_deref(), Some("Record"));
+        assert_eq!(
+            db.track_paths_after("/music/good.flac", 10)
+                .unwrap()
+                .iter()
+                .map(|(_, p)| p.as_str())
+                .collect::<Vec<_>>(),
+            ["/music/zero.mp3", "subsonic://7"]
+        );
+        assert_eq!(
+            HealthIssue::from_key("bitrate"),
+            Some(HealthIssue::LowBitrate)
+        );
+    }
+
+    #[test]
+    fn listening_stats_come_from_the_play_history() {
+        let db = Database::open_in_memory_for_tests().unwrap();
+        let song = TrackRecord {
+            duration_ms: Some(180_000),
+            ..sample_track("/music/a/song.flac")
+        };
+        let other = TrackRecord {
+            title: Some("Other".to_string()),
+            artist: Some("Band".to_string()),
+            album: None,
+            duration_ms: Some(120_000),
+            ..sample_track("/music/b.v2/other.MP3")
+        };
+        let stream = sample_track("subsonic://42");
+        for track in [&song, &other, &stream] {
+            db.upsert_track(track).unwrap();
+        }
+        let day_ms = 24 * 60 * 60 * 1000;
+        let now = now_ms();
+        let play = |track: &TrackRecord, at_ms: i64| {
+            db.conn
+                .execute(
+                    "INSERT INTO play_history (track_id, played_at_ms) VALUES (?1, ?2)",
+                    params![track.id.0.to_string(), at_ms],
+                )
+                .unwrap();
+        };
+        play(&song, now);
+        play(&song, now);
+        play(&other, now);
+        play(&other, now - 3 * day_ms);
+        play(&song, now - 60 * day_ms);
+
+        let days = db.listening_time(StatsPeriod::Day, 7, now).unwrap();
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[6].1, 480_000);
+        assert_eq!(days[3].1, 120_000);
+        assert_eq!(days.iter().map(|(_, ms)| ms).sum::<i64>(), 600_000);
+        assert!(days[0].0 < days[6].0);
+        let weeks = db.listening_time(StatsPeriod::Week, 12, now).unwrap();
+        assert_eq!(weeks.len(), 12);
+        assert_eq!(weeks.iter().map(|(_, ms)| ms).sum::<i64>(), 780_000);
+
+        let artists = db.top_played(PlayGroup::Artist, None, 10).unwrap();
+        assert_eq!(
+            artists
+                .iter()
+                .map(|t| (t.name.as_str(), t.plays))
+                .collect::<Vec<_>>(),
+            [("Artist", 3), ("Band", 2)]
+        );
+        let albums = db.top_played(PlayGroup::Album, None, 10).unwrap();
+        assert_eq!(albums.len(), 1);
+        assert_eq!(albums[0].detail.as_deref(), Some("Artist"));
+        let tracks = db.top_played(PlayGroup::Track, None, 1).unwrap();
+        assert_eq!((tracks[0].name.as_str(), tracks[0].plays), ("Track", 3));
+        assert_eq!(db.play_totals(None).unwrap(), (5, 780_000));
+
+        // A range takes in whole local days at either end.
+        let local_date = |ms: i64| -> String {
+            db.conn
+                .query_row(
+                    "SELECT date(?1 / 1000, 'unixepoch', 'localtime')",
+                    [ms],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        let recent = DateRange {
+            from: local_date(now - 3 * day_ms),
+            to: local_date(now),
+        };
+        assert_eq!(db.play_totals(Some(&recent)).unwrap(), (4, 600_000));
+        assert_eq!(db.play_variety(&recent).unwrap(), (2, 2));
+        let mut artists = db
+            .top_played(PlayGroup::Artist, Some(&recent), 10)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.name, t.plays))
+            .collect::<Vec<_>>();
+        artists.sort();
+        assert_eq!(
+            artists,
+            [("Artist".to_string(), 2), ("Band".to_string(), 2)]
+        );
+        assert_eq!(db.local_today().unwrap(), local_date(now_ms()));
+        let long_ago = DateRange {
+            from: local_date(now - 60 * day_ms),
+            to: local_date(now - 60 * day_ms),
+        };
+        assert_eq!(db.play_totals(Some(&long_ago)).unwrap(), (1, 180_000));
+        let months = db.listening_by_month(&long_ago).unwrap();
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].1, 180_000);
+        assert_eq!(Some(months[0].0.as_str()), long_ago.from.get(..7));
+
+        assert_eq!(
+            db.track_formats().unwrap(),
+            [
+                ("".to_string(), 1),
+                ("flac".to_string(), 1),
+                ("mp3".to_string(), 1)
+            ]
+        );
+        let months = db.tracks_added_by_month().unwrap();
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].1, 3);
+    }
+
+    #[test]
+    fn imported_stats_only_raise_counts_and_keep_existing_playlists() {
+        use crate::import::{ImportedPlaylist, ImportedTrack};
+
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let track = sample_track("/music/a.flac");
+        db.upsert_track(&track).unwrap();
+        let id = track.id.0.to_string();
+        assert!(db.record_track_play(&id).unwrap());
+        assert!(!db.record_track_play("episode-id").unwrap());
+        db.create_playlist("Gym").unwrap();
+
+        let library = ImportedLibrary {
+            tracks: vec![
+                ImportedTrack {
+                    path: "/music/a.flac".to_string(),
+                    rating: Some(4),
+                    play_count: Some(12),
+                    last_played_at_ms: Some(1_000),
+                },
+                ImportedTrack {
+                    path: "/elsewhere/b.flac".to_string(),
+                    ..ImportedTrack::default()
+                },
+            ],
+            playlists: vec![
+                ImportedPlaylist {
+                    name: "Mix".to_string(),
+                    paths: vec!["/music/a.flac".to_string(), "/elsewhere/b.flac".to_string()],
+                },
+                ImportedPlaylist {
+                    name: "gym".to_string(),
+                    paths: vec!["/music/a.flac".to_string()],
+                },
+            ],
+        };
+        let summary = db.import_track_stats(&library).unwrap();
+        assert_eq!(
+            summary,
+            StatsImportSummary {
+                tracks: 1,
+                unmatched_tracks: 1,
+                playlists: 1,
+                playlist_entries: 1,
+                skipped_playlists: 1,
+                unmatched_entries: 1,
+            }
+        );
+        let stats = db.get_track_stats(&id).unwrap().unwrap();
+        assert_eq!((stats.rating, stats.play_count), (Some(4), 12));
+        assert!(stats.last_played_at_ms.unwrap() > 1_000);
+
+        let mut again = library.clone();
+        again.tracks[0].rating = Some(1);
+        again.tracks[0].play_count = Some(2);
+        assert_eq!(db.import_track_stats(&again).unwrap().skipped_playlists, 2);
+        let stats = db.get_track_stats(&id).unwrap().unwrap();
+        assert_eq!((stats.rating, stats.play_count), (Some(4), 12));
     }
 }
@@ -0,0 +1,302 @@
+//! Writing the track table and playlists as CSV or JSON, for spreadsheets
+//! and other tools. Unlike a library backup this is one-way: nothing reads
+//! these files back.
+
+use crate::db::{PlaylistTrackRow, TrackWithStats};
+use crate::podcast::format_date;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// The format named by the file's extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::parse(path.extension()?.to_str()?)
+    }
+}
+
+/// A playlist and its entries in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistExport {
+    pub name: String,
+    pub entries: Vec<PlaylistTrackRow>,
+}
+
+const TRACK_COLUMNS: &[&str] = &[
+    "path",
+    "artist",
+    "album",
+    "title",
+    "duration_ms",
+    "sample_rate",
+    "channels",
+    "bit_depth",
+    "rating",
+    "play_count",
+    "last_played",
+    "added",
+];
+
+const PLAYLIST_COLUMNS: &[&str] = &[
+    "playlist",
+    "position",
+    "path",
+    "artist",
+    "album",
+    "title",
+    "duration_ms",
+];
+
+#[derive(Serialize)]
+struct TrackEntry<'a> {
+    path: &'a str,
+    artist: Option<&'a str>,
+    album: Option<&'a str>,
+    title: Option<&'a str>,
+    duration_ms: Option<i64>,
+    sample_rate: Option<i64>,
+    channels: Option<i64>,
+    bit_depth: Option<i64>,
+    rating: Option<u8>,
+    play_count: i64,
+    last_played_at_ms: Option<i64>,
+    added_at_ms: i64,
+}
+
+#[derive(Serialize)]
+struct PlaylistEntry<'a> {
+    name: &'a str,
+    tracks: Vec<PlaylistTrack<'a>>,
+}
+
+#[derive(Serialize)]
+struct PlaylistTrack<'a> {
+    path: &'a str,
+    artist: Option<&'a str>,
+    album: Option<&'a str>,
+    title: Option<&'a str>,
+    duration_ms: Option<i64>,
+}
+
+/// Writes one row or object per track. CSV dates are `YYYY-MM-DD` in UTC;
+/// JSON keeps Unix milliseconds.
+pub fn write_tracks(
+    out: &mut impl Write,
+    format: ExportFormat,
+    tracks: &[TrackWithStats],
+) -> io::Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            write_csv_row(out, TRACK_COLUMNS.iter().map(|c| c.to_string()))?;
+            for row in tracks {
+                let track = &row.track;
+                write_csv_row(
+                    out,
+                    [
+                        track.path.clone(),
+                        text(&track.artist),
+                        text(&track.album),
+                        text(&track.title),
+                        number(track.duration_ms),
+                        number(track.sample_rate),
+                        number(track.channels),
+                        number(track.bit_depth),
+                        number(row.rating),
+                        row.play_count.to_string(),
+                        row.last_played_at_ms.map(format_date).unwrap_or_default(),
+                        format_date(track.added_at_ms),
+                    ],
+                )?;
+            }
+            Ok(())
+        }
+        ExportFormat::Json => {
+            let entries: Vec<TrackEntry> = tracks
+                .iter()
+                .map(|row| TrackEntry {
+                    path: &row.track.path,
+                    artist: row.track.artist.as_deref(),
+                    album: row.track.album.as_deref(),
+                    title: row.track.title.as_deref(),
+                    duration_ms: row.track.duration_ms,
+                    sample_rate: row.track.sample_rate,
+                    channels: row.track.channels,
+                    bit_depth: row.track.bit_depth,
+                    rating: row.rating,
+                    play_count: row.play_count,
+                    last_played_at_ms: row.last_played_at_ms,
+                    added_at_ms: row.track.added_at_ms,
+                })
+                .collect();
+            write_json(out, &entries)
+        }
+    }
+}
+
+/// Writes playlists as one CSV row per entry, or as a JSON array of
+/// playlists each holding its tracks.
+pub fn write_playlists(
+    out: &mut impl Write,
+    format: ExportFormat,
+    playlists: &[PlaylistExport],
+) -> io::Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            write_csv_row(out, PLAYLIST_COLUMNS.iter().map(|c| c.to_string()))?;
+            for playlist in playlists {
+                for (position, entry) in playlist.entries.iter().enumerate() {
+                    let track = &entry.track;
+                    write_csv_row(
+                        out,
+                        [
+                            playlist.name.clone(),
+                            (position + 1).to_string(),
+                            track.path.clone(),
+                            text(&track.artist),
+                            text(&track.album),
+                            text(&track.title),
+                            number(track.duration_ms),
+                        ],
+                    )?;
+                }
+            }
+            Ok(())
+        }
+        ExportFormat::Json => {
+            let entries: Vec<PlaylistEntry> = playlists
+                .iter()
+                .map(|playlist| PlaylistEntry {
+                    name: &playlist.name,
+                    tracks: playlist
+                        .entries
+                        .iter()
+                        .map(|entry| PlaylistTrack {
+                            path: &entry.track.path,
+                            artist: entry.track.artist.as_deref(),
+                            album: entry.track.album.as_deref(),
+                            title: entry.track.title.as_deref(),
+                            duration_ms: entry.track.duration_ms,
+                        })
+                        .collect(),
+                })
+                .collect();
+            write_json(out, &entries)
+        }
+    }
+}
+
+fn write_json(out: &mut impl Write, value: &impl Serialize) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, value)?;
+    writeln!(out)
+}
+
+/// Writes one RFC 4180 row: fields holding a comma, quote or line break
+/// are quoted, with quotes doubled.
+fn write_csv_row(out: &mut impl Write, fields: impl IntoIterator<Item = String>) -> io::Result<()> {
+    let mut line = String::new();
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            line.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            line.push('"');
+            line.push_str(&field.replace('"', "\"\""));
+            line.push('"');
+        } else {
+            line.push_str(&field);
+        }
+    }
+    line.push_str("\r\n");
+    out.write_all(line.as_bytes())
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn number(value: Option<impl ToString>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::TrackRow;
+    use auric_core::TrackId;
+
+    fn track(path: &str, title: &str) -> TrackRow {
+        TrackRow {
+            id: TrackId(uuid::Uuid::new_v4()),
+            path: path.to_string(),
+            title: Some(title.to_string()),
+            artist: Some("Band".to_string()),
+            album: None,
+            duration_ms: Some(61_000),
+            sample_rate: Some(44_100),
+            channels: Some(2),
+            bit_depth: None,
+            file_mtime_ms: None,
+            added_at_ms: 951_782_400_000,
+            updated_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn csv_quotes_awkward_fields_and_json_keeps_numbers() {
+        let tracks = vec![TrackWithStats {
+            track: track("/music/a, b.flac", "Say \"hi\""),
+            rating: Some(4),
+            play_count: 3,
+            last_played_at_ms: None,
+        }];
+        let mut csv = Vec::new();
+        write_tracks(&mut csv, ExportFormat::Csv, &tracks).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("path,artist,album,title,"));
+        assert_eq!(
+            lines[1],
+            "\"/music/a, b.flac\",Band,,\"Say \"\"hi\"\"\",61000,44100,2,,4,3,,2000-02-29"
+        );
+
+        let mut json = Vec::new();
+        write_tracks(&mut json, ExportFormat::Json, &tracks).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value[0]["rating"], 4);
+        assert_eq!(value[0]["album"], serde_json::Value::Null);
+
+        let playlists = vec![PlaylistExport {
+            name: "Mix".to_string(),
+            entries: vec![PlaylistTrackRow {
+                playlist_id: "p".to_string(),
+                position: 0,
+                added_at_ms: 0,
+                track: track("/music/c.flac", "C"),
+            }],
+        }];
+        let mut csv = Vec::new();
+        write_playlists(&mut csv, ExportFormat::Csv, &playlists).unwrap();
+        assert!(String::from_utf8(csv)
+            .unwrap()
+            .contains("Mix,1,/music/c.flac,Band,,C,61000"));
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.JSON")),
+            Some(ExportFormat::Json)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("out.txt")), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! What the library health check looks for in each file: whether it can be
+//! read at all, which of its title, artist and album tags are empty, and
+//! whether a lossy file was encoded at a suspiciously low bitrate.
+//!
+//! Missing artwork and zero lengths are already known from the database and
+//! are reported from there; see [`crate::db::HealthIssue`].
+
+use crate::db::{Database, DbError, TrackHealth};
+use crate::scan::{infer_artist_album, infer_title};
+use lofty::config::WriteOptions;
+use lofty::file::{AudioFile, FileType, TaggedFile, TaggedFileExt};
+use lofty::probe::Probe;
+use lofty::tag::{Accessor, Tag};
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Lossy files below this average bitrate are reported.
+pub const LOW_BITRATE_KBPS: u32 = 128;
+/// Opus sounds good at half the bitrate the older codecs need.
+pub const LOW_BITRATE_OPUS_KBPS: u32 = 64;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HealthError {
+    #[error("failed to read or write tags: {0}")]
+    Tags(#[from] lofty::error::LoftyError),
+    #[error("db error: {0}")]
+    Db(#[from] DbError),
+}
+
+/// Reads the tags and properties of the file at `path`. A file that cannot
+/// be opened or parsed is reported as unreadable and nothing else.
+pub fn inspect_file(path: &Path) -> TrackHealth {
+    let tagged = match Probe::open(path).and_then(|probe| probe.read()) {
+        Ok(tagged) => tagged,
+        Err(err) => {
+            tracing::debug!(path = %path.display(), error = %err, "health check could not read file");
+            return TrackHealth {
+                readable: false,
+                missing_tags: Vec::new(),
+                low_bitrate_kbps: None,
+            };
+        }
+    };
+    let floor = match tagged.file_type() {
+        FileType::Opus => Some(LOW_BITRATE_OPUS_KBPS),
+        FileType::Aac
+        | FileType::Mpeg
+        | FileType::Mp4
+        | FileType::Mpc
+        | FileType::Vorbis
+        | FileType::Speex => Some(LOW_BITRATE_KBPS),
+        _ => None,
+    };
+    let low_bitrate_kbps = floor.and_then(|floor| {
+        tagged
+            .properties()
+            .audio_bitrate()
+            .filter(|kbps| *kbps > 0 && *kbps < floor)
+    });
+    TrackHealth {
+        readable: true,
+        missing_tags: missing_tags(&tagged)
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        low_bitrate_kbps,
+    }
+}
+
+/// Which of title, artist and album the file's main tag leaves empty.
+fn missing_tags(tagged: &TaggedFile) -> Vec<&'static str> {
+    let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
+    let blank = |value: Option<Cow<'_, str>>| value.is_none_or(|v| v.trim().is_empty());
+    [
+        ("title", blank(tag.and_then(|t| t.title()))),
+        ("artist", blank(tag.and_then(|t| t.artist()))),
+        ("album", blank(tag.and_then(|t| t.album()))),
+    ]
+    .into_iter()
+    .filter(|(_, blank)| *blank)
+    .map(|(field, _)| field)
+    .collect()
+}
+
+/// Writes the title, artist and album the scanner would guess from the
+/// file's name and folders into whichever of them the file's tags leave
+/// empty, then fills the same gaps in the library. Tags that are set are
+/// never changed. Returns the fields written.
+pub fn fill_missing_tags(db: &Database, path: &Path) -> Result<Vec<&'static str>, HealthError> {
+    let mut tagged = Probe::open(path)?.read()?;
+    let missing = missing_tags(&tagged);
+    if tagged.primary_tag().is_none() {
+        // The new main tag starts as a copy of the file's other tag, since a
+        // rescan reads only the main one.
+        let mut tag = Tag::new(tagged.primary_tag_type());
+        if let Some(existing) = tagged.first_tag() {
+            for item in existing.items() {
+                tag.insert(item.clone());
+            }
+            for picture in existing.pictures() {
+                tag.push_picture(picture.clone());
+            }
+        }
+        tagged.insert_tag(tag);
+    }
+    let Some(tag) = tagged.primary_tag_mut() else {
+        return Ok(Vec::new());
+    };
+    let (artist, album) = infer_artist_album(path);
+    let mut filled = Vec::new();
+    for field in missing {
+        let guess = match field {
+            "title" => infer_title(path),
+            "artist" => artist.clone(),
+            _ => album.clone(),
+        };
+        let Some(guess) = guess else { continue };
+        match field {
+            "title" => tag.set_title(guess),
+            "artist" => tag.set_artist(guess),
+            _ => tag.set_album(guess),
+        }
+        filled.push(field);
+    }
+    if filled.is_empty() {
+        return Ok(filled);
+    }
+    tagged.save_to_path(path, WriteOptions::default())?;
+
+    let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
+    let value =
+        |get: fn(&Tag) -> Option<Cow<'_, str>>| tag.and_then(get).map(|value| value.into_owned());
+    let path_string = path.to_string_lossy();
+    db.fill_track_tags(
+        &path_string,
+        value(Tag::title),
+        value(Tag::artist),
+        value(Tag::album),
+    )?;
+    if let Some(track) = db.get_track_by_path(&path_string)? {
+        db.set_track_health(&track.id.0.to_string(), &inspect_file(path))?;
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lofty::tag::{TagExt, TagType};
+
+    /// A second of 16-bit mono silence.
+    fn write_wav(path: &Path) {
+        let rate = 8_000u32;
+        let data_len = rate * 2;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&rate.to_le_bytes());
+        bytes.extend_from_slice(&(rate * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        bytes.resize(bytes.len() + data_len as usize, 0);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn inspects_readable_and_broken_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let wav = dir.path().join("untagged.wav");
+        write_wav(&wav);
+        let health = inspect_file(&wav);
+        assert!(health.readable);
+        assert_eq!(health.missing_tags, ["title", "artist", "album"]);
+        assert_eq!(health.low_bitrate_kbps, None);
+
+        let broken = dir.path().join("broken.flac");
+        std::fs::write(&broken, b"not audio").unwrap();
+        let health = inspect_file(&broken);
+        assert!(!health.readable);
+        assert!(health.missing_tags.is_empty());
+    }
+
+    #[test]
+    fn filling_keeps_what_another_tag_already_says() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Folder Artist/Folder Album/01_Intro.wav");
+        write_wav(&path);
+        // Only a RIFF INFO tag, not the ID3v2 tag a rescan reads.
+        let mut info = Tag::new(TagType::RiffInfo);
+        info.set_artist("Tagged Artist".to_string());
+        info.save_to_path(&path, WriteOptions::default()).unwrap();
+
+        let db = Database::open_in_memory_for_tests().unwrap();
+        let filled = fill_missing_tags(&db, &path).unwrap();
+        assert_eq!(filled, ["title", "album"]);
+
+        let tagged = Probe::open(&path).unwrap().read().unwrap();
+        let primary = tagged.primary_tag().unwrap();
+        assert_eq!(primary.tag_type(), TagType::Id3v2);
+        assert_eq!(primary.artist().as_deref(), Some("Tagged Artist"));
+        assert_eq!(primary.title().as_deref(), Some("01 Intro"));
+        assert_eq!(primary.album().as_deref(), Some("Folder Album"));
+        assert!(inspect_file(&path).missing_tags.is_empty());
+        assert!(fill_missing_tags(&db, &path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,666 @@
+//! Reading ratings, play counts and playlists from other players, for
+//! [`crate::db::Database::import_track_stats`].
+//!
+//! Each reader turns its source into an [`ImportedLibrary`] keyed by absolute
+//! file path; entries are matched to tracks by that path, so the music has to
+//! be scanned into auric first. Ratings are converted to 1–5 stars.
+
+use crate::podcast::{days_from_civil, decode_entities};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedLibrary {
+    pub tracks: Vec<ImportedTrack>,
+    pub playlists: Vec<ImportedPlaylist>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedTrack {
+    pub path: String,
+    /// 1–5 stars; `None` when unrated.
+    pub rating: Option<u8>,
+    pub play_count: Option<i64>,
+    pub last_played_at_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedPlaylist {
+    pub name: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("failed to read {path}: {source}")]
+    Sqlite {
+        path: String,
+        source: rusqlite::Error,
+    },
+    #[error("{0}")]
+    Format(String),
+}
+
+impl ImportedLibrary {
+    /// Rewrites paths under `from` to sit under `to`, for a library that was
+    /// on another machine or mount; returns how many paths changed.
+    pub fn remap_paths(&mut self, from: &str, to: &str) -> usize {
+        let from = Path::new(from);
+        let to = Path::new(to);
+        let remap = |path: &mut String| match Path::new(path.as_str()).strip_prefix(from) {
+            Ok(rest) => {
+                *path = to.join(rest).to_string_lossy().into_owned();
+                true
+            }
+            Err(_) => false,
+        };
+        let mut changed = 0;
+        for track in &mut self.tracks {
+            changed += usize::from(remap(&mut track.path));
+        }
+        for playlist in &mut self.playlists {
+            for path in &mut playlist.paths {
+                changed += usize::from(remap(path));
+            }
+        }
+        changed
+    }
+}
+
+/// Reads MPD's sticker database (`sticker_file`), whose song URIs are
+/// relative to `music_dir`. The `rating` (0–10), `playCount` and
+/// `lastPlayed` stickers set by clients such as myMPD and Cantata are used;
+/// `play_count` and `last_played` spellings are accepted too.
+pub fn read_mpd_stickers(
+    sticker_file: &Path,
+    music_dir: &Path,
+) -> Result<ImportedLibrary, ImportError> {
+    let conn = open_read_only(sticker_file)?;
+    let sqlite_err = |source| ImportError::Sqlite {
+        path: sticker_file.display().to_string(),
+        source,
+    };
+    let mut stmt = conn
+        .prepare("SELECT uri, name, value FROM sticker WHERE type = 'song' ORDER BY uri")
+        .map_err(sqlite_err)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(sqlite_err)?;
+
+    let mut tracks: Vec<ImportedTrack> = Vec::new();
+    for row in rows {
+        let (uri, name, value) = row.map_err(sqlite_err)?;
+        let path = music_dir.join(&uri).to_string_lossy().into_owned();
+        if tracks.last().map(|t| &t.path) != Some(&path) {
+            tracks.push(ImportedTrack {
+                path,
+                ..ImportedTrack::default()
+            });
+        }
+        let track = tracks.last_mut().expect("pushed above");
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "rating" => track.rating = value.parse::<f64>().ok().and_then(|r| stars(r / 10.0)),
+            "playcount" | "play_count" => track.play_count = value.parse().ok(),
+            "lastplayed" | "last_played" => {
+                track.last_played_at_ms = value.parse::<i64>().ok().map(|secs| secs * 1000)
+            }
+            _ => {}
+        }
+    }
+    Ok(ImportedLibrary {
+        tracks,
+        playlists: Vec::new(),
+    })
+}
+
+/// Reads MPD's stored playlists: the `.m3u` files in its
+/// `playlist_directory`, with entries relative to `music_dir`. Stream URLs
+/// are left out.
+pub fn read_mpd_playlists(
+    playlist_dir: &Path,
+    music_dir: &Path,
+) -> Result<Vec<ImportedPlaylist>, ImportError> {
+    let io_err = |path: &Path, source| ImportError::Io {
+        path: path.display().to_string(),
+        source,
+    };
+    let mut files: Vec<_> = fs::read_dir(playlist_dir)
+        .map_err(|err| io_err(playlist_dir, err))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("m3u"))
+        })
+        .collect();
+    files.sort();
+    let mut playlists = Vec::new();
+    for file in files {
+        let text = fs::read_to_string(&file).map_err(|err| io_err(&file, err))?;
+        let paths = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.contains("://"))
+            .map(|line| music_dir.join(line).to_string_lossy().into_owned())
+            .collect();
+        let name = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        playlists.push(ImportedPlaylist { name, paths });
+    }
+    Ok(playlists)
+}
+
+/// Reads a beets library database. Ratings and play counts are the flexible
+/// attributes the `mpdstats` plugin keeps (`rating` from 0 to 1,
+/// `play_count`, and `last_played` in Unix seconds); beets has no playlists
+/// of its own.
+pub fn read_beets(library_db: &Path) -> Result<ImportedLibrary, ImportError> {
+    let conn = open_read_only(library_db)?;
+    let sqlite_err = |source| ImportError::Sqlite {
+        path: library_db.display().to_string(),
+        source,
+    };
+    let has_attributes = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'item_attributes'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(sqlite_err)?
+        .is_some();
+    if !has_attributes {
+        return Err(ImportError::Format(format!(
+            "{} is not a beets library",
+            library_db.display()
+        )));
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT CAST(items.path AS BLOB),
+                    (SELECT CAST(value AS TEXT) FROM item_attributes WHERE entity_id = items.id AND key = 'rating'),
+                    (SELECT CAST(value AS TEXT) FROM item_attributes WHERE entity_id = items.id AND key = 'play_count'),
+                    (SELECT CAST(value AS TEXT) FROM item_attributes WHERE entity_id = items.id AND key = 'last_played')
+             FROM items ORDER BY items.id",
+        )
+        .map_err(sqlite_err)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, Vec<u8>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })
+        .map_err(sqlite_err)?;
+    let mut tracks = Vec::new();
+    for row in rows {
+        let (path, rating, play_count, last_played) = row.map_err(sqlite_err)?;
+        let number = |value: Option<String>| value.and_then(|v| v.trim().parse::<f64>().ok());
+        let track = ImportedTrack {
+            path: String::from_utf8_lossy(&path).into_owned(),
+            rating: number(rating).and_then(stars),
+            play_count: number(play_count).map(|count| count as i64),
+            last_played_at_ms: number(last_played).map(|secs| (secs * 1000.0) as i64),
+        };
+        if track.rating.is_some() || track.play_count.is_some() || track.last_played_at_ms.is_some()
+        {
+            tracks.push(track);
+        }
+    }
+    Ok(ImportedLibrary {
+        tracks,
+        playlists: Vec::new(),
+    })
+}
+
+/// Reads an iTunes or Music "Library.xml" export. Ratings the app derived
+/// from the album rating are skipped, as are the built-in playlists
+/// (Library, Music, Podcasts, ...) and playlist folders.
+pub fn read_itunes_xml(path: &Path) -> Result<ImportedLibrary, ImportError> {
+    let xml = fs::read_to_string(path).map_err(|source| ImportError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    parse_itunes_xml(&xml)
+}
+
+pub fn parse_itunes_xml(xml: &str) -> Result<ImportedLibrary, ImportError> {
+    let start = xml
+        .find("<plist")
+        .and_then(|at| xml[at..].find('>').map(|end| at + end + 1))
+        .ok_or_else(|| {
+            ImportError::Format("not an iTunes library: no <plist> element".to_string())
+        })?;
+    let mut parser = PlistParser { xml, pos: start };
+    let root = parser.value()?;
+
+    // Track ID to path, for looking up playlist items.
+    let mut locations: HashMap<i64, String> = HashMap::new();
+    let mut tracks = Vec::new();
+    for (_, track) in root.get("Tracks").map(Plist::entries).unwrap_or_default() {
+        let Some(path) = track
+            .get("Location")
+            .and_then(Plist::as_str)
+            .and_then(file_url_path)
+        else {
+            continue;
+        };
+        if let Some(id) = track.get("Track ID").and_then(Plist::as_int) {
+            locations.insert(id, path.clone());
+        }
+        let computed = track.get("Rating Computed").and_then(Plist::as_bool) == Some(true);
+        let rating = track
+            .get("Rating")
+            .and_then(Plist::as_int)
+            .filter(|_| !computed)
+            .and_then(|r| stars(r as f64 / 100.0));
+        let play_count = track.get("Play Count").and_then(Plist::as_int);
+        let last_played_at_ms = track
+            .get("Play Date UTC")
+            .and_then(Plist::as_str)
+            .and_then(parse_iso8601);
+        if rating.is_some() || play_count.is_some() || last_played_at_ms.is_some() {
+            tracks.push(ImportedTrack {
+                path,
+                rating,
+                play_count,
+                last_played_at_ms,
+            });
+        }
+    }
+
+    let mut playlists = Vec::new();
+    for playlist in root.get("Playlists").map(Plist::items).unwrap_or_default() {
+        let flag = |key: &str| playlist.get(key).and_then(Plist::as_bool) == Some(true);
+        if flag("Master") || flag("Folder") || playlist.get("Distinguished Kind").is_some() {
+            continue;
+        }
+        let Some(name) = playlist.get("Name").and_then(Plist::as_str) else {
+            continue;
+        };
+        let paths = playlist
+            .get("Playlist Items")
+            .map(Plist::items)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|item| item.get("Track ID").and_then(Plist::as_int))
+            .filter_map(|id| locations.get(&id).cloned())
+            .collect();
+        playlists.push(ImportedPlaylist {
+            name: name.to_string(),
+            paths,
+        });
+    }
+    Ok(ImportedLibrary { tracks, playlists })
+}
+
+fn open_read_only(path: &Path) -> Result<Connection, ImportError> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(|source| {
+        ImportError::Sqlite {
+            path: path.display().to_string(),
+            source,
+        }
+    })
+}
+
+/// A 0–1 rating as 1–5 stars, rounding to the nearest; zero is unrated.
+fn stars(fraction: f64) -> Option<u8> {
+    let stars = (fraction.clamp(0.0, 1.0) * 5.0).round() as u8;
+    (stars > 0).then_some(stars)
+}
+
+/// The local path of a `file://` URL, percent-decoded.
+fn file_url_path(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    let bytes = rest.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8_lossy(&out).into_owned();
+    // `file://localhost/C:/Music/...` on Windows.
+    match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => Some(path[1..].to_string()),
+        _ => Some(path),
+    }
+}
+
+/// Parses a plist `<date>` such as `2019-03-04T05:06:07Z` into Unix
+/// milliseconds.
+fn parse_iso8601(date: &str) -> Option<i64> {
+    let (day, time) = date.trim().trim_end_matches('Z').split_once('T')?;
+    let mut day = day.split('-').map(str::parse::<i64>);
+    let (year, month, day) = (day.next()?.ok()?, day.next()?.ok()?, day.next()?.ok()?);
+    let mut time = time.split(':').map(str::parse::<i64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    Some((days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second) * 1000)
+}
+
+/// The parts of a property list the importers read. Values they never look
+/// at (`<data>`, `<real>`) are kept as [`Plist::Other`].
+#[derive(Debug, Clone, PartialEq)]
+enum Plist {
+    Dict(Vec<(String, Plist)>),
+    Array(Vec<Plist>),
+    String(String),
+    Integer(i64),
+    Bool(bool),
+    Other,
+}
+
+impl Plist {
+    fn get(&self, key: &str) -> Option<&Plist> {
+        match self {
+            Self::Dict(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn entries(&self) -> Vec<(&str, &Plist)> {
+        match self {
+            Self::Dict(entries) => entries.iter().map(|(k, v)| (k.as_str(), v)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn items(&self) -> Vec<&Plist> {
+        match self {
+            Self::Array(items) => items.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A small recursive reader for the XML property-list format. Like the
+/// feed parser it reads by hand; a library export can run to tens of
+/// megabytes but only uses a handful of element types.
+struct PlistParser<'a> {
+    xml: &'a str,
+    pos: usize,
+}
+
+impl<'a> PlistParser<'a> {
+    fn error(&self, what: &str) -> ImportError {
+        ImportError::Format(format!("malformed plist near byte {}: {what}", self.pos))
+    }
+
+    /// The next tag's name (with a leading `/` for a close tag) and whether
+    /// it closes itself, skipping text, comments and declarations.
+    fn tag(&mut self) -> Result<(&'a str, bool), ImportError> {
+        loop {
+            let open = self.xml[self.pos..]
+                .find('<')
+                .ok_or_else(|| self.error("unexpected end"))?;
+            self.pos += open;
+            let rest: &'a str = &self.xml[self.pos..];
+            if rest.starts_with("<!--") {
+                let end = rest
+                    .find("-->")
+                    .ok_or_else(|| self.error("unclosed comment"))?;
+                self.pos += end + 3;
+                continue;
+            }
+            let end = rest.find('>').ok_or_else(|| self.error("unclosed tag"))?;
+            self.pos += end + 1;
+            if rest.starts_with("<?") || rest.starts_with("<!") {
+                continue;
+            }
+            let inner = &rest[1..end];
+            let self_closing = inner.ends_with('/');
+            let name = inner
+                .trim_end_matches('/')
+                .split_whitespace()
+                .next()
+                .unwrap_or("");
+            return Ok((name, self_closing));
+        }
+    }
+
+    /// The text up to `</name>`, entity-decoded.
+    fn text(&mut self, name: &str) -> Result<String, ImportError> {
+        let close = format!("</{name}>");
+        let end = self.xml[self.pos..]
+            .find(&close)
+            .ok_or_else(|| self.error("unclosed element"))?;
+        let text = decode_entities(&self.xml[self.pos..self.pos + end]);
+        self.pos += end + close.len();
+        Ok(text)
+    }
+
+    fn value(&mut self) -> Result<Plist, ImportError> {
+        let (name, self_closing) = self.tag()?;
+        self.value_for(name.to_string(), self_closing)
+    }
+
+    fn value_for(&mut self, name: String, self_closing: bool) -> Result<Plist, ImportError> {
+        if self_closing {
+            return Ok(match name.as_str() {
+                "true" => Plist::Bool(true),
+                "false" => Plist::Bool(false),
+                "dict" => Plist::Dict(Vec::new()),
+                "array" => Plist::Array(Vec::new()),
+                "string" => Plist::String(String::new()),
+                _ => Plist::Other,
+            });
+        }
+        match name.as_str() {
+            "dict" => {
+                let mut entries = Vec::new();
+                loop {
+                    let (tag, _) = self.tag()?;
+                    match tag {
+                        "/dict" => return Ok(Plist::Dict(entries)),
+                        "key" => {
+                            let key = self.text("key")?;
+                            entries.push((key, self.value()?));
+                        }
+                        _ => return Err(self.error("expected <key>")),
+                    }
+                }
+            }
+            "array" => {
+                let mut items = Vec::new();
+                loop {
+                    let (tag, self_closing) = self.tag()?;
+                    if tag == "/array" {
+                        return Ok(Plist::Array(items));
+                    }
+                    let tag = tag.to_string();
+                    items.push(self.value_for(tag, self_closing)?);
+                }
+            }
+            "string" | "date" => Ok(Plist::String(self.text(&name)?)),
+            "integer" => {
+                let text = self.text(&name)?;
+                text.trim()
+                    .parse()
+                    .map(Plist::Integer)
+                    .map_err(|_| self.error("bad <integer>"))
+            }
+            other if other.starts_with('/') => Err(self.error("unexpected close tag")),
+            other => {
+                self.text(other)?;
+                Ok(Plist::Other)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBRARY_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>Major Version</key><integer>1</integer>
+	<key>Music Folder</key><string>file:///Users/me/Music/</string>
+	<key>Tracks</key>
+	<dict>
+		<key>101</key>
+		<dict>
+			<key>Track ID</key><integer>101</integer>
+			<key>Name</key><string>Rock &amp; Roll</string>
+			<key>Play Count</key><integer>12</integer>
+			<key>Play Date UTC</key><date>2019-03-04T05:06:07Z</date>
+			<key>Rating</key><integer>80</integer>
+			<key>Compilation</key><true/>
+			<key>Location</key><string>file:///Users/me/Music/A%20B/01%20Rock.m4a</string>
+		</dict>
+		<key>102</key>
+		<dict>
+			<key>Track ID</key><integer>102</integer>
+			<key>Rating</key><integer>60</integer>
+			<key>Rating Computed</key><true/>
+			<key>Location</key><string>file://localhost/Users/me/Music/02.mp3</string>
+		</dict>
+	</dict>
+	<key>Playlists</key>
+	<array>
+		<dict>
+			<key>Name</key><string>Library</string>
+			<key>Master</key><true/>
+			<key>Playlist Items</key><array><dict><key>Track ID</key><integer>101</integer></dict></array>
+		</dict>
+		<dict>
+			<key>Name</key><string>Mix</string>
+			<key>Playlist Items</key>
+			<array>
+				<dict><key>Track ID</key><integer>102</integer></dict>
+				<dict><key>Track ID</key><integer>101</integer></dict>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>"#;
+
+    #[test]
+    fn itunes_libraries_yield_stats_and_user_playlists() {
+        let library = parse_itunes_xml(LIBRARY_XML).unwrap();
+        assert_eq!(
+            library.tracks,
+            vec![ImportedTrack {
+                path: "/Users/me/Music/A B/01 Rock.m4a".to_string(),
+                rating: Some(4),
+                play_count: Some(12),
+                last_played_at_ms: Some(1_551_675_967_000),
+            }]
+        );
+        assert_eq!(
+            library.playlists,
+            vec![ImportedPlaylist {
+                name: "Mix".to_string(),
+                paths: vec![
+                    "/Users/me/Music/02.mp3".to_string(),
+                    "/Users/me/Music/A B/01 Rock.m4a".to_string(),
+                ],
+            }]
+        );
+        assert!(parse_itunes_xml("<html></html>").is_err());
+    }
+
+    #[test]
+    fn mpd_stickers_and_beets_attributes_are_read_from_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let stickers = dir.path().join("sticker.sql");
+        Connection::open(&stickers)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE sticker (type VARCHAR, uri VARCHAR, name VARCHAR, value VARCHAR);
+                 INSERT INTO sticker VALUES ('song', 'Artist/a.flac', 'rating', '7');
+                 INSERT INTO sticker VALUES ('song', 'Artist/a.flac', 'playCount', '3');
+                 INSERT INTO sticker VALUES ('song', 'Artist/b.flac', 'lastPlayed', '1700000000');
+                 INSERT INTO sticker VALUES ('playlist', 'x', 'rating', '10');",
+            )
+            .unwrap();
+        let library = read_mpd_stickers(&stickers, Path::new("/music")).unwrap();
+        assert_eq!(library.tracks.len(), 2);
+        assert_eq!(library.tracks[0].path, "/music/Artist/a.flac");
+        assert_eq!(library.tracks[0].rating, Some(4));
+        assert_eq!(library.tracks[0].play_count, Some(3));
+        assert_eq!(library.tracks[1].last_played_at_ms, Some(1_700_000_000_000));
+
+        let playlists = dir.path().join("playlists");
+        fs::create_dir(&playlists).unwrap();
+        fs::write(
+            playlists.join("Road.m3u"),
+            "#EXTM3U\nArtist/b.flac\nhttp://radio/stream\n",
+        )
+        .unwrap();
+        let read = read_mpd_playlists(&playlists, Path::new("/music")).unwrap();
+        assert_eq!(read[0].name, "Road");
+        assert_eq!(read[0].paths, vec!["/music/Artist/b.flac".to_string()]);
+
+        let beets = dir.path().join("library.db");
+        Connection::open(&beets)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE items (id INTEGER PRIMARY KEY, path BLOB);
+                 CREATE TABLE item_attributes (id INTEGER PRIMARY KEY, entity_id INTEGER, key TEXT, value TEXT);
+                 INSERT INTO items VALUES (1, CAST('/music/Artist/a.flac' AS BLOB)), (2, CAST('/music/c.flac' AS BLOB));
+                 INSERT INTO item_attributes (entity_id, key, value) VALUES (1, 'rating', '0.5'), (1, 'play_count', '9');",
+            )
+            .unwrap();
+        let mut library = read_beets(&beets).unwrap();
+        assert_eq!(library.tracks.len(), 1);
+        assert_eq!(library.tracks[0].rating, Some(3));
+        assert_eq!(library.tracks[0].play_count, Some(9));
+        assert_eq!(library.remap_paths("/music", "/mnt/music"), 1);
+        assert_eq!(library.tracks[0].path, "/mnt/music/Artist/a.flac");
+        assert!(read_beets(&stickers).is_err());
+    }
+}
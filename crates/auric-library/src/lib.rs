@@ -1,8 +1,17 @@
 use auric_core::TrackId;
+use std::path::Path;
 
+pub mod cue;
 pub mod db;
+pub mod export;
+pub mod health;
+pub mod import;
+pub mod musicbrainz;
+pub mod podcast;
+pub mod review;
 pub mod scan;
 pub mod watch;
+pub mod writer;
 
 #[derive(Debug, Clone)]
 pub struct LibraryRoot {
@@ -10,6 +19,54 @@ pub struct LibraryRoot {
     pub watched: bool,
 }
 
+/// Per-folder scan settings stored alongside a library root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootOptions {
+    /// Descend into subfolders; when off only files directly in the root count.
+    pub recursive: bool,
+    /// Lowercase extensions without the dot; empty means every supported format.
+    pub include_extensions: Vec<String>,
+    pub exclude_extensions: Vec<String>,
+    /// Link a cover image from the track's folder when it has no embedded art.
+    pub auto_artwork: bool,
+}
+
+impl Default for RootOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            include_extensions: Vec::new(),
+            exclude_extensions: Vec::new(),
+            auto_artwork: false,
+        }
+    }
+}
+
+impl RootOptions {
+    /// Parses a list like `"FLAC, .mp3 wav"` into `["flac", "mp3", "wav"]`.
+    pub fn parse_extensions(raw: &str) -> Vec<String> {
+        let mut extensions = Vec::new();
+        for ext in raw.split(|c: char| c == ',' || c.is_whitespace()) {
+            let ext = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+            if !ext.is_empty() && !extensions.contains(&ext) {
+                extensions.push(ext);
+            }
+        }
+        extensions
+    }
+
+    /// Whether a file passes the include and exclude lists.
+    pub fn accepts(&self, path: &Path) -> bool {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        (self.include_extensions.is_empty() || self.include_extensions.contains(&ext))
+            && !self.exclude_extensions.contains(&ext)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TrackRecord {
     pub id: TrackId,
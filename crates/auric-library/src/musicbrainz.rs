@@ -0,0 +1,329 @@
+//! Shared access to the MusicBrainz web service.
+//!
+//! MusicBrainz allows one request per second from each client and answers
+//! `503` to anyone going faster. Every feature that looks something up there
+//! (tag lookup, cover art, similar artists) goes through one [`MusicBrainz`]
+//! handle, which keeps that budget for the whole process: callers queue for
+//! their turn, the most urgent feature first, and a `503` pushes every
+//! queued request back, not only the one that got it.
+//!
+//! Requests are made with `curl`, like the app's other network features.
+
+use std::collections::BTreeSet;
+use std::process::Command;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// The budget MusicBrainz asks clients to keep to.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tries per request before giving up on a busy server.
+pub const MAX_ATTEMPTS: u32 = 4;
+
+const API_ROOT: &str = "https://musicbrainz.org/ws/2/";
+
+/// MusicBrainz blocks anonymous clients, so requests name the app.
+const USER_AGENT: &str = concat!(
+    "auric/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/flntfnd/auric-tui )"
+);
+
+/// Who is asking. Waiting requests are served in this order, so a lookup
+/// the user is waiting on goes ahead of background work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    Metadata,
+    Artwork,
+    SimilarArtists,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Performs one GET. Swapped out in tests.
+pub trait Transport: Send + Sync {
+    fn get(&self, url: &str) -> std::io::Result<HttpResponse>;
+}
+
+#[derive(Debug, Default)]
+pub struct CurlTransport;
+
+impl Transport for CurlTransport {
+    fn get(&self, url: &str) -> std::io::Result<HttpResponse> {
+        let output = Command::new("curl")
+            .args([
+                "-sSL",
+                "--max-time",
+                "30",
+                "-H",
+                "Accept: application/json",
+                "-A",
+                USER_AGENT,
+            ])
+            .args(["-w", "\n%{http_code}", "--", url])
+            .output()?;
+        if !output.status.success() {
+            return Err(std::io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = text.rsplit_once('\n').unwrap_or(("", &text));
+        let status = status
+            .trim()
+            .parse()
+            .map_err(|_| std::io::Error::other(format!("curl gave no HTTP status for {url}")))?;
+        Ok(HttpResponse {
+            status,
+            body: body.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MusicBrainzError {
+    #[error("failed to reach MusicBrainz: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("MusicBrainz is busy; gave up after {0} attempts")]
+    Busy(u32),
+    #[error("MusicBrainz answered HTTP {0}")]
+    Status(u16),
+}
+
+/// A handle on the shared request budget. Clones share it.
+#[derive(Clone)]
+pub struct MusicBrainz {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    transport: Box<dyn Transport>,
+    interval: Duration,
+    gate: Mutex<Gate>,
+    turn: Condvar,
+}
+
+struct Gate {
+    /// When the next request may go out.
+    next_slot: Instant,
+    /// Requests waiting for their turn, by feature and then arrival.
+    waiting: BTreeSet<(Feature, u64)>,
+    arrivals: u64,
+}
+
+impl std::fmt::Debug for MusicBrainz {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MusicBrainz")
+            .field("interval", &self.shared.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for MusicBrainz {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MusicBrainz {
+    pub fn new() -> Self {
+        Self::with_transport(CurlTransport, MIN_INTERVAL)
+    }
+
+    pub fn with_transport(transport: impl Transport + 'static, interval: Duration) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                transport: Box::new(transport),
+                interval,
+                gate: Mutex::new(Gate {
+                    next_slot: Instant::now(),
+                    waiting: BTreeSet::new(),
+                    arrivals: 0,
+                }),
+                turn: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Fetches `path` under the web service root, such as
+    /// `recording?query=...&fmt=json`, once the budget allows. A `503` is
+    /// retried after a pause that doubles each time.
+    pub fn get(&self, feature: Feature, path: &str) -> Result<String, MusicBrainzError> {
+        let url = format!("{API_ROOT}{}", path.trim_start_matches('/'));
+        let mut backoff = self.shared.interval;
+        for attempt in 1..=MAX_ATTEMPTS {
+            self.wait_turn(feature);
+            let response = self.shared.transport.get(&url)?;
+            match response.status {
+                200..=299 => return Ok(response.body),
+                503 => {
+                    tracing::info!(
+                        ?feature,
+                        attempt,
+                        ?backoff,
+                        "MusicBrainz is busy; backing off"
+                    );
+                    self.hold_off(backoff);
+                    backoff *= 2;
+                }
+                status => return Err(MusicBrainzError::Status(status)),
+            }
+        }
+        Err(MusicBrainzError::Busy(MAX_ATTEMPTS))
+    }
+
+    /// Blocks until this request is first in line and its slot has come.
+    fn wait_turn(&self, feature: Feature) {
+        let mut gate = self
+            .shared
+            .gate
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let ticket = (feature, gate.arrivals);
+        gate.arrivals += 1;
+        gate.waiting.insert(ticket);
+        loop {
+            let now = Instant::now();
+            if gate.waiting.first() != Some(&ticket) {
+                gate = self
+                    .shared
+                    .turn
+                    .wait(gate)
+                    .unwrap_or_else(PoisonError::into_inner);
+            } else if now < gate.next_slot {
+                let wait = gate.next_slot - now;
+                gate = self
+                    .shared
+                    .turn
+                    .wait_timeout(gate, wait)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+            } else {
+                gate.waiting.remove(&ticket);
+                gate.next_slot = now + self.shared.interval;
+                self.shared.turn.notify_all();
+                return;
+            }
+        }
+    }
+
+    /// Keeps every request, queued or not, off the server for `pause`.
+    fn hold_off(&self, pause: Duration) {
+        let mut gate = self
+            .shared
+            .gate
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        gate.next_slot = gate.next_slot.max(Instant::now() + pause);
+        self.shared.turn.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers with the queued statuses in order, then 200, noting when and
+    /// what was asked.
+    #[derive(Clone, Default)]
+    struct Script {
+        statuses: Arc<Mutex<Vec<u16>>>,
+        calls: Arc<Mutex<Vec<(Instant, String)>>>,
+    }
+
+    impl Transport for Script {
+        fn get(&self, url: &str) -> std::io::Result<HttpResponse> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((Instant::now(), url.to_string()));
+            let status = {
+                let mut statuses = self.statuses.lock().unwrap();
+                if statuses.is_empty() {
+                    200
+                } else {
+                    statuses.remove(0)
+                }
+            };
+            Ok(HttpResponse {
+                status,
+                body: url.rsplit('/').next().unwrap_or_default().to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn requests_keep_the_interval_and_retry_when_busy() {
+        let interval = Duration::from_millis(40);
+        let script = Script::default();
+        script.statuses.lock().unwrap().extend([503, 503]);
+        let client = MusicBrainz::with_transport(script.clone(), interval);
+
+        assert_eq!(
+            client.get(Feature::Metadata, "/artist?query=a").unwrap(),
+            "artist?query=a"
+        );
+        let calls = script.calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 3);
+        // Backoff doubles: one interval, then two.
+        assert!(calls[1].0 - calls[0].0 >= interval);
+        assert!(calls[2].0 - calls[1].0 >= interval * 2);
+
+        client.get(Feature::Artwork, "release").unwrap();
+        let calls = script.calls.lock().unwrap().clone();
+        assert!(calls[3].0 - calls[2].0 >= interval);
+        assert_eq!(calls[3].1, format!("{API_ROOT}release"));
+
+        script
+            .statuses
+            .lock()
+            .unwrap()
+            .extend([503; MAX_ATTEMPTS as usize]);
+        assert!(matches!(
+            client.get(Feature::Metadata, "x"),
+            Err(MusicBrainzError::Busy(MAX_ATTEMPTS))
+        ));
+        script.statuses.lock().unwrap().push(404);
+        assert!(matches!(
+            client.get(Feature::Metadata, "x"),
+            Err(MusicBrainzError::Status(404))
+        ));
+    }
+
+    #[test]
+    fn waiting_requests_go_by_feature() {
+        let script = Script::default();
+        let client = MusicBrainz::with_transport(script.clone(), Duration::from_millis(150));
+        // Take the first slot so the rest have to queue.
+        client.get(Feature::SimilarArtists, "first").unwrap();
+
+        let mut threads = Vec::new();
+        for (feature, name) in [
+            (Feature::SimilarArtists, "similar"),
+            (Feature::Artwork, "artwork"),
+            (Feature::Metadata, "metadata"),
+        ] {
+            let client = client.clone();
+            threads.push(std::thread::spawn(move || {
+                client.get(feature, name).unwrap()
+            }));
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let order: Vec<String> = script
+            .calls
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, url)| url.trim_start_matches(API_ROOT).to_string())
+            .collect();
+        assert_eq!(order, ["first", "metadata", "artwork", "similar"]);
+    }
+}
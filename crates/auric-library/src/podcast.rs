@@ -0,0 +1,301 @@
+//! Reading podcast RSS feeds.
+//!
+//! Feeds are parsed by hand rather than with an XML library: only a handful
+//! of elements matter (`<channel><title>`, and per `<item>` the title, guid,
+//! enclosure, publish date and `itunes:duration`), and real-world feeds are
+//! often not well-formed enough for a strict parser anyway.
+
+use crate::db::PodcastEpisodeUpsert;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedFeed {
+    pub title: String,
+    /// Items with an audio enclosure, in feed order.
+    pub episodes: Vec<PodcastEpisodeUpsert>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FeedError {
+    #[error("not an RSS feed: no <channel> element")]
+    NoChannel,
+}
+
+pub fn parse_feed(xml: &str) -> Result<ParsedFeed, FeedError> {
+    let channel = element_body(xml, "channel").ok_or(FeedError::NoChannel)?;
+    let header = channel.split("<item").next().unwrap_or(channel);
+    let title = element_text(header, "title").unwrap_or_default();
+
+    let mut episodes = Vec::new();
+    let mut rest = channel;
+    while let Some(item) = element_body(rest, "item") {
+        let end = item.as_ptr() as usize - rest.as_ptr() as usize + item.len();
+        rest = &rest[end..];
+        let Some(audio_url) = enclosure_url(item) else {
+            continue;
+        };
+        let guid = element_text(item, "guid").unwrap_or_else(|| audio_url.clone());
+        episodes.push(PodcastEpisodeUpsert {
+            title: element_text(item, "title").unwrap_or_else(|| guid.clone()),
+            guid,
+            audio_url,
+            published_at_ms: element_text(item, "pubDate").and_then(|d| parse_rfc2822(&d)),
+            duration_ms: element_text(item, "itunes:duration").and_then(|d| parse_duration(&d)),
+        });
+    }
+    Ok(ParsedFeed { title, episodes })
+}
+
+/// Finds `<tag ...>` (not a longer name starting with `tag`) and returns the
+/// text up to the matching close tag. Self-closing tags have no body.
+fn element_body<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let (open_end, _) = find_open_tag(xml, tag)?;
+    let body = &xml[open_end..];
+    let close = format!("</{tag}>");
+    let end = body.find(&close).unwrap_or(body.len());
+    Some(&body[..end])
+}
+
+/// Position just past the `>` of the first `<tag` open tag, and the tag's
+/// attribute text.
+fn find_open_tag<'a>(xml: &'a str, tag: &str) -> Option<(usize, &'a str)> {
+    let needle = format!("<{tag}");
+    let mut from = 0;
+    while let Some(found) = xml[from..].find(&needle) {
+        let start = from + found + needle.len();
+        let next = xml[start..].chars().next()?;
+        if next == '>' || next == '/' || next.is_whitespace() {
+            let close = start + xml[start..].find('>')?;
+            if xml[..close].ends_with('/') {
+                from = close;
+                continue;
+            }
+            return Some((close + 1, &xml[start..close]));
+        }
+        from = start;
+    }
+    None
+}
+
+fn element_text(xml: &str, tag: &str) -> Option<String> {
+    let body = element_body(xml, tag)?.trim();
+    let text = match body.strip_prefix("<![CDATA[") {
+        Some(inner) => inner.split("]]>").next().unwrap_or(inner).to_string(),
+        None => decode_entities(body),
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn enclosure_url(item: &str) -> Option<String> {
+    let needle = "<enclosure";
+    let start = item.find(needle)? + needle.len();
+    let end = start + item[start..].find('>')?;
+    attribute(&item[start..end], "url")
+}
+
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    while let Some(found) = rest.find(name) {
+        let before = rest[..found].chars().next_back();
+        let after = rest[found + name.len()..].trim_start();
+        rest = &rest[found + name.len()..];
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &value[1..];
+        let end = value.find(quote)?;
+        return Some(decode_entities(&value[..end]));
+    }
+    None
+}
+
+/// Decodes the predefined XML entities and numeric character references.
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';').filter(|i| *i <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => {
+                out.push(ch);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parses an RSS date like `Tue, 10 Jun 2003 04:00:00 GMT` or
+/// `10 Jun 2003 04:00 +0200` into Unix milliseconds.
+pub fn parse_rfc2822(date: &str) -> Option<i64> {
+    let date = date.split_once(',').map(|(_, rest)| rest).unwrap_or(date);
+    let mut parts = date.split_whitespace();
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?.get(..3)?.to_ascii_lowercase();
+    let month = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ]
+    .iter()
+    .position(|m| *m == month)? as i64
+        + 1;
+    let mut year: i64 = parts.next()?.parse().ok()?;
+    if year < 100 {
+        year += if year < 50 { 2000 } else { 1900 };
+    }
+    let mut clock = parts.next()?.split(':');
+    let hour: i64 = clock.next()?.parse().ok()?;
+    let minute: i64 = clock.next()?.parse().ok()?;
+    let second: i64 = clock.next().map(|s| s.parse().ok()).unwrap_or(Some(0))?;
+    let offset_minutes = match parts.next() {
+        Some(zone) if zone.starts_with('+') || zone.starts_with('-') => {
+            let digits: i64 = zone[1..].parse().ok()?;
+            let minutes = digits / 100 * 60 + digits % 100;
+            if zone.starts_with('-') {
+                -minutes
+            } else {
+                minutes
+            }
+        }
+        Some("EDT") => -4 * 60,
+        Some("EST" | "CDT") => -5 * 60,
+        Some("CST" | "MDT") => -6 * 60,
+        Some("MST" | "PDT") => -7 * 60,
+        Some("PST") => -8 * 60,
+        _ => 0,
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second
+        - offset_minutes * 60;
+    Some(seconds * 1000)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Formats Unix milliseconds as a `YYYY-MM-DD` date in UTC.
+pub fn format_date(ms: i64) -> String {
+    let (year, month, day) = civil_from_ms(ms);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// The UTC year, month and day of Unix milliseconds; the inverse of
+/// [`days_from_civil`].
+pub fn civil_from_ms(ms: i64) -> (i64, i64, i64) {
+    let days = ms.div_euclid(86_400_000) + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parses `itunes:duration` in any of its forms: `3723`, `62:03` or
+/// `1:02:03`.
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let mut seconds: i64 = 0;
+    for part in text.trim().split(':') {
+        let value: f64 = part.trim().parse().ok()?;
+        seconds = seconds * 60 + value as i64;
+    }
+    Some(seconds * 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title>Tracks &amp; Tales</title>
+    <itunes:image href="https://example.com/cover.jpg"/>
+    <item>
+      <title><![CDATA[Episode 2: <Live>]]></title>
+      <guid isPermaLink="false">ep-2</guid>
+      <pubDate>Tue, 10 Jun 2003 04:00:00 GMT</pubDate>
+      <itunes:duration>1:02:03</itunes:duration>
+      <enclosure length="1" type="audio/mpeg" url="https://example.com/ep2.mp3?a=1&amp;b=2"/>
+    </item>
+    <item>
+      <title>Show notes only</title>
+    </item>
+    <item>
+      <title>Episode 1</title>
+      <pubDate>10 Jun 2003 06:00 +0200</pubDate>
+      <itunes:duration>95</itunes:duration>
+      <enclosure url='https://example.com/ep1.mp3' type="audio/mpeg" />
+    </item>
+  </channel>
+</rss>"#;
+
+    #[test]
+    fn feeds_yield_episodes_with_audio() {
+        let feed = parse_feed(FEED).unwrap();
+        assert_eq!(feed.title, "Tracks & Tales");
+        assert_eq!(feed.episodes.len(), 2);
+
+        let second = &feed.episodes[0];
+        assert_eq!(second.title, "Episode 2: <Live>");
+        assert_eq!(second.guid, "ep-2");
+        assert_eq!(second.audio_url, "https://example.com/ep2.mp3?a=1&b=2");
+        assert_eq!(second.published_at_ms, Some(1_055_217_600_000));
+        assert_eq!(second.duration_ms, Some(3_723_000));
+
+        let first = &feed.episodes[1];
+        assert_eq!(first.guid, "https://example.com/ep1.mp3");
+        assert_eq!(first.published_at_ms, second.published_at_ms);
+        assert_eq!(first.duration_ms, Some(95_000));
+        assert_eq!(format_date(second.published_at_ms.unwrap()), "2003-06-10");
+        assert_eq!(format_date(951_782_400_000), "2000-02-29");
+
+        assert!(matches!(
+            parse_feed("<html></html>"),
+            Err(FeedError::NoChannel)
+        ));
+    }
+}
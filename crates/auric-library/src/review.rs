@@ -0,0 +1,439 @@
+//! The year in review: what was played most over a range of dates, how long
+//! was listened and which month had the most, from the play history. It is
+//! shown in a dialog and written as text or JSON to keep or share.
+
+use crate::db::{Database, DateRange, DbError, PlayGroup, PlayTally};
+use crate::podcast::days_from_civil;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Span {
+    Year,
+    Month,
+    Custom,
+}
+
+/// The dates a review covers, as given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewRange {
+    /// `2025`, `2025-03` or `2025-01-01..2025-06-30`.
+    pub label: String,
+    pub dates: DateRange,
+    span: Span,
+}
+
+impl ReviewRange {
+    /// Parses a year, a `YYYY-MM` month, or `FROM..TO` where each end is a
+    /// year, a month or a `YYYY-MM-DD` date. A year or month covers all of it.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (from, to, span) = match text.split_once("..") {
+            Some((from, to)) => {
+                let (from, _, _) = parse_period(from)?;
+                let (_, to, _) = parse_period(to)?;
+                (from, to, Span::Custom)
+            }
+            None => parse_period(text)?,
+        };
+        (from <= to).then(|| Self {
+            label: text.to_string(),
+            dates: DateRange { from, to },
+            span,
+        })
+    }
+
+    /// The year or month `delta` before or after this one; `None` for a
+    /// range of dates.
+    pub fn step(&self, delta: i64) -> Option<Self> {
+        let year: i64 = self.label.get(..4)?.parse().ok()?;
+        match self.span {
+            Span::Year => Self::parse(&format!("{:04}", year + delta)),
+            Span::Month => {
+                let month: i64 = self.label.get(5..7)?.parse().ok()?;
+                let index = year * 12 + month - 1 + delta;
+                Self::parse(&format!(
+                    "{:04}-{:02}",
+                    index.div_euclid(12),
+                    index.rem_euclid(12) + 1
+                ))
+            }
+            Span::Custom => None,
+        }
+    }
+
+    /// `2025`, `March 2025` or `2025-01-01 to 2025-06-30`.
+    pub fn title(&self) -> String {
+        match self.span {
+            Span::Year => self.label.clone(),
+            Span::Month => month_name(&self.label),
+            Span::Custom => format!("{} to {}", self.dates.from, self.dates.to),
+        }
+    }
+}
+
+/// The first and last dates of a year, month or day, as `YYYY-MM-DD`.
+fn parse_period(text: &str) -> Option<(String, String, Span)> {
+    let text = text.trim();
+    if text.split('-').next()?.len() != 4 {
+        return None;
+    }
+    let parts = text
+        .split('-')
+        .map(|part| match part.bytes().all(|b| b.is_ascii_digit()) {
+            true => part.parse::<i64>().ok(),
+            false => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let date = |year: i64, month: i64, day: i64| format!("{year:04}-{month:02}-{day:02}");
+    match parts[..] {
+        [year] if year >= 1 => Some((date(year, 1, 1), date(year, 12, 31), Span::Year)),
+        [year, month] if year >= 1 && (1..=12).contains(&month) => Some((
+            date(year, month, 1),
+            date(year, month, days_in_month(year, month)),
+            Span::Month,
+        )),
+        [year, month, day]
+            if year >= 1
+                && (1..=12).contains(&month)
+                && (1..=days_in_month(year, month)).contains(&day) =>
+        {
+            Some((date(year, month, day), date(year, month, day), Span::Custom))
+        }
+        _ => None,
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    days_from_civil(next_year, next_month, 1) - days_from_civil(year, month, 1)
+}
+
+/// `2025-03` as `March 2025`; anything else as it is.
+pub fn month_name(month: &str) -> String {
+    let name = month
+        .get(5..7)
+        .and_then(|m| m.parse::<usize>().ok())
+        .and_then(|m| MONTH_NAMES.get(m.wrapping_sub(1)));
+    match (name, month.get(..4)) {
+        (Some(name), Some(year)) if month.len() == 7 => format!("{name} {year}"),
+        _ => month.to_string(),
+    }
+}
+
+/// Listening time, e.g. `83h 20m` or `45m`.
+pub fn listened_label(ms: i64) -> String {
+    let minutes = ms.max(0) / 60_000;
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h {:02}m", minutes % 60),
+    }
+}
+
+/// An artist, album or track and how often it was played. Albums and
+/// tracks carry their artist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Ranked {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    pub plays: i64,
+}
+
+impl Ranked {
+    /// `Teardrop — Massive Attack`, or just the name.
+    pub fn display(&self) -> String {
+        match self.artist.as_deref().filter(|artist| !artist.is_empty()) {
+            Some(artist) => format!("{} — {artist}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl From<PlayTally> for Ranked {
+    fn from(tally: PlayTally) -> Self {
+        Self {
+            name: tally.name,
+            artist: tally.detail,
+            plays: tally.plays,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonthTotal {
+    /// `YYYY-MM`.
+    pub month: String,
+    pub listened_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Review {
+    pub range: String,
+    pub from: String,
+    pub to: String,
+    pub plays: i64,
+    pub listened_ms: i64,
+    /// Different tracks and artists played.
+    pub tracks: i64,
+    pub artists: i64,
+    pub top_artists: Vec<Ranked>,
+    pub top_albums: Vec<Ranked>,
+    pub top_tracks: Vec<Ranked>,
+    /// Months with any listening, oldest first.
+    pub months: Vec<MonthTotal>,
+}
+
+impl Review {
+    /// Adds up the plays in `range`, keeping the `top` most played of each
+    /// kind.
+    pub fn build(db: &Database, range: &ReviewRange, top: usize) -> Result<Self, DbError> {
+        let during = Some(&range.dates);
+        let ranked = |group| -> Result<Vec<Ranked>, DbError> {
+            Ok(db
+                .top_played(group, during, top)?
+                .into_iter()
+                .map(Ranked::from)
+                .collect())
+        };
+        let (plays, listened_ms) = db.play_totals(during)?;
+        let (tracks, artists) = db.play_variety(&range.dates)?;
+        Ok(Self {
+            range: range.title(),
+            from: range.dates.from.clone(),
+            to: range.dates.to.clone(),
+            plays,
+            listened_ms,
+            tracks,
+            artists,
+            top_artists: ranked(PlayGroup::Artist)?,
+            top_albums: ranked(PlayGroup::Album)?,
+            top_tracks: ranked(PlayGroup::Track)?,
+            months: db
+                .listening_by_month(&range.dates)?
+                .into_iter()
+                .map(|(month, listened_ms)| MonthTotal { month, listened_ms })
+                .collect(),
+        })
+    }
+
+    /// The month listened to most; the earlier one on a tie.
+    pub fn top_month(&self) -> Option<&MonthTotal> {
+        self.months
+            .iter()
+            .fold(None, |best: Option<&MonthTotal>, month| match best {
+                Some(best) if best.listened_ms >= month.listened_ms => Some(best),
+                _ => Some(month),
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewFormat {
+    Text,
+    Json,
+}
+
+impl ReviewFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" | "txt" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// The format named by the file's extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::parse(path.extension()?.to_str()?)
+    }
+}
+
+pub fn write_review(out: &mut impl Write, format: ReviewFormat, review: &Review) -> io::Result<()> {
+    match format {
+        ReviewFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, review)?;
+            writeln!(out)
+        }
+        ReviewFormat::Text => write_text(out, review),
+    }
+}
+
+fn write_text(out: &mut impl Write, review: &Review) -> io::Result<()> {
+    writeln!(
+        out,
+        "Your {} in music ({} to {})",
+        review.range, review.from, review.to
+    )?;
+    writeln!(out)?;
+    if review.plays == 0 {
+        return writeln!(out, "Nothing was played.");
+    }
+    writeln!(
+        out,
+        "{} plays, {} listened",
+        review.plays,
+        listened_label(review.listened_ms)
+    )?;
+    writeln!(
+        out,
+        "{} different tracks by {} artists",
+        review.tracks, review.artists
+    )?;
+    if let Some(top) = review.top_month() {
+        writeln!(
+            out,
+            "Most listened month: {} ({})",
+            month_name(&top.month),
+            listened_label(top.listened_ms)
+        )?;
+    }
+    for (title, ranked) in [
+        ("Top artists", &review.top_artists),
+        ("Top albums", &review.top_albums),
+        ("Top tracks", &review.top_tracks),
+    ] {
+        if ranked.is_empty() {
+            continue;
+        }
+        writeln!(out)?;
+        writeln!(out, "{title}")?;
+        for (i, entry) in ranked.iter().enumerate() {
+            writeln!(
+                out,
+                "{:>3}. {} ({} plays)",
+                i + 1,
+                entry.display(),
+                entry.plays
+            )?;
+        }
+    }
+    writeln!(out)?;
+    writeln!(out, "By month")?;
+    for month in &review.months {
+        writeln!(
+            out,
+            "  {:<15} {}",
+            month_name(&month.month),
+            listened_label(month.listened_ms)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_cover_whole_years_months_and_days() {
+        let year = ReviewRange::parse("2024").unwrap();
+        assert_eq!(
+            (year.dates.from.as_str(), year.dates.to.as_str()),
+            ("2024-01-01", "2024-12-31")
+        );
+        assert_eq!(year.step(-1).unwrap().label, "2023");
+        let february = ReviewRange::parse("2024-02").unwrap();
+        assert_eq!(february.dates.to, "2024-02-29");
+        assert_eq!(february.title(), "February 2024");
+        assert_eq!(february.step(-2).unwrap().label, "2023-12");
+        assert_eq!(february.step(11).unwrap().label, "2025-01");
+        let custom = ReviewRange::parse("2024-06..2025-01-15").unwrap();
+        assert_eq!(
+            (custom.dates.from.as_str(), custom.dates.to.as_str()),
+            ("2024-06-01", "2025-01-15")
+        );
+        assert_eq!(custom.title(), "2024-06-01 to 2025-01-15");
+        assert!(custom.step(1).is_none());
+
+        for bad in [
+            "",
+            "24",
+            "2024-13",
+            "2023-02-29",
+            "2025..2024",
+            "2024-1x",
+            "-2024",
+        ] {
+            assert!(ReviewRange::parse(bad).is_none(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn text_summary_leads_with_totals_and_the_top_month() {
+        let review = Review {
+            range: "2025".to_string(),
+            from: "2025-01-01".to_string(),
+            to: "2025-12-31".to_string(),
+            plays: 1204,
+            listened_ms: 5_000 * 60_000,
+            tracks: 412,
+            artists: 87,
+            top_artists: vec![Ranked {
+                name: "Massive Attack".to_string(),
+                artist: None,
+                plays: 31,
+            }],
+            top_albums: Vec::new(),
+            top_tracks: vec![Ranked {
+                name: "Teardrop".to_string(),
+                artist: Some("Massive Attack".to_string()),
+                plays: 9,
+            }],
+            months: vec![
+                MonthTotal {
+                    month: "2025-02".to_string(),
+                    listened_ms: 45 * 60_000,
+                },
+                MonthTotal {
+                    month: "2025-03".to_string(),
+                    listened_ms: 725 * 60_000,
+                },
+                MonthTotal {
+                    month: "2025-04".to_string(),
+                    listened_ms: 725 * 60_000,
+                },
+            ],
+        };
+        let mut text = Vec::new();
+        write_review(&mut text, ReviewFormat::Text, &review).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with("Your 2025 in music (2025-01-01 to 2025-12-31)\n"));
+        assert!(text.contains("1204 plays, 83h 20m listened\n"));
+        assert!(text.contains("Most listened month: March 2025 (12h 05m)\n"));
+        assert!(text.contains("  1. Teardrop — Massive Attack (9 plays)\n"));
+        assert!(!text.contains("Top albums"));
+        assert!(text.contains("  February 2025   45m\n"));
+
+        let mut json = Vec::new();
+        write_review(&mut json, ReviewFormat::Json, &review).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["top_tracks"][0]["artist"], "Massive Attack");
+        assert!(value["top_artists"][0].get("artist").is_none());
+        assert_eq!(value["months"][1]["listened_ms"], 725 * 60_000);
+        assert_eq!(
+            ReviewFormat::from_path(Path::new("wrapped.TXT")),
+            Some(ReviewFormat::Text)
+        );
+    }
+}
@@ -1,9 +1,9 @@
 use crate::db::{Database, TrackArtworkUpsert};
-use crate::TrackRecord;
+use crate::{RootOptions, TrackRecord};
 use auric_core::TrackId;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use uuid::Uuid;
 use walkdir::WalkDir;
@@ -39,13 +39,26 @@ pub struct ScanSummary {
     pub embedded_artwork_inserted_assets: usize,
     pub embedded_artwork_reused_assets: usize,
     pub embedded_artwork_skipped_oversize: usize,
+    pub folder_artwork_candidates: usize,
     pub skipped_non_audio_files: usize,
+    pub skipped_filtered_files: usize,
     pub skipped_unreadable_entries: usize,
     pub pruned_missing_tracks: usize,
     pub purged_orphan_artwork_assets: usize,
     pub elapsed_ms: u128,
 }
 
+/// What a [`DirectoryScanner::reconcile_path`] run changed under one root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconcileSummary {
+    pub root_path: String,
+    pub added_tracks: usize,
+    pub updated_tracks: usize,
+    pub removed_tracks: usize,
+    pub unchanged_tracks: usize,
+    pub elapsed_ms: u128,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ScanError {
     #[error("invalid scan root: {0}")]
@@ -78,146 +91,236 @@ impl DirectoryScanner {
         root: impl AsRef<Path>,
     ) -> Result<ScanSummary, ScanError> {
         let root = root.as_ref();
-        let root_meta = fs::metadata(root).map_err(|err| {
-            if err.kind() == std::io::ErrorKind::NotFound {
-                ScanError::InvalidRoot(format!("{} (not found)", root.display()))
-            } else {
-                ScanError::Io(err)
-            }
-        })?;
-        if !root_meta.is_dir() {
-            return Err(ScanError::InvalidRoot(format!(
-                "{} (not a directory)",
-                root.display()
-            )));
-        }
+        check_root(root)?;
 
         let root_path = normalize_path(root)?;
         let start = Instant::now();
-        let mut discovered_audio_files = 0usize;
-        let mut imported_tracks = 0usize;
-        let mut embedded_artwork_candidates = 0usize;
-        let mut embedded_artwork_linked_tracks = 0usize;
-        let mut embedded_artwork_inserted_assets = 0usize;
-        let mut embedded_artwork_reused_assets = 0usize;
-        let mut embedded_artwork_skipped_oversize = 0usize;
-        let mut skipped_non_audio_files = 0usize;
-        let mut skipped_unreadable_entries = 0usize;
-        let mut batch = Vec::with_capacity(self.options.batch_size.max(1));
-        let mut artwork_batch = Vec::with_capacity(self.options.batch_size.max(1));
+        let mut tally = ImportTally::new(self.options.batch_size, root_options(db, &root_path)?);
         let mut seen_audio_paths = if self.options.prune_missing {
             Some(HashSet::new())
         } else {
             None
         };
 
-        let walker = WalkDir::new(&root_path).follow_links(self.options.follow_symlinks);
-        for entry in walker {
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(_) => {
-                    skipped_unreadable_entries += 1;
-                    continue;
-                }
-            };
+        let root = Path::new(&root_path);
+        self.import_tree(db, root, root, &mut tally, seen_audio_paths.as_mut())?;
+        tally.flush(db)?;
 
-            if !entry.file_type().is_file() {
+        let pruned_missing_tracks = if self.may_prune(root) {
+            self.prune_missing_under_root(
+                db,
+                &root_path,
+                &tally.root_options,
+                seen_audio_paths.as_ref(),
+            )?
+        } else {
+            0
+        };
+        self.finish(db, tally, root_path, pruned_missing_tracks, start)
+    }
+
+    /// Imports only `paths`, which changed under `root`: files are read and
+    /// written in batches, folders are walked, and paths that no longer exist
+    /// are pruned when `prune_missing` is set. Lets the watcher handle a burst
+    /// of changes without rescanning the whole root. Paths below the top level
+    /// of a non-recursive root are ignored. With `auto_artwork` on, a cover
+    /// image that arrives after its music is linked to the folder's tracks.
+    pub fn scan_paths(
+        &self,
+        db: &mut Database,
+        root: impl AsRef<Path>,
+        paths: &[PathBuf],
+    ) -> Result<ScanSummary, ScanError> {
+        let root_path = normalize_path(root.as_ref())?;
+        let root = Path::new(&root_path);
+        let start = Instant::now();
+        let mut tally = ImportTally::new(self.options.batch_size, root_options(db, &root_path)?);
+        let mut missing = Vec::new();
+        let mut cover_folders = BTreeSet::new();
+
+        for path in paths {
+            if !tally.root_options.recursive && path != root && path.parent() != Some(root) {
                 continue;
             }
+            match fs::metadata(path) {
+                Ok(meta) if meta.is_dir() => {
+                    if tally.root_options.recursive || path == root {
+                        self.import_tree(db, root, path, &mut tally, None)?;
+                    }
+                }
+                Ok(meta) if meta.is_file() => {
+                    if tally.root_options.auto_artwork && folder_artwork_kind(path).is_some() {
+                        cover_folders.extend(path.parent().map(Path::to_path_buf));
+                    }
+                    self.import_file(db, path, &mut tally)?;
+                }
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => missing.push(path),
+                Err(err) => {
+                    tracing::warn!(path = %path.display(), error = %err, "skipping unreadable entry");
+                    tally.skipped_unreadable_entries += 1;
+                }
+            }
+        }
+        for folder in &cover_folders {
+            tally.link_folder_cover(db, &self.options, folder)?;
+        }
+        tally.flush(db)?;
 
-            let path = entry.path();
-            if !is_supported_audio_file(path) {
-                skipped_non_audio_files += 1;
-                continue;
+        let mut pruned_missing_tracks = 0;
+        if self.may_prune(root) {
+            for path in missing {
+                let gone = db.list_track_paths_under_prefix(&normalize_path(path)?)?;
+                pruned_missing_tracks += db.delete_tracks_by_paths(&gone)?;
             }
+        }
+        self.finish(db, tally, root_path, pruned_missing_tracks, start)
+    }
 
+    /// Brings the library in line with the files under `root`: new files are
+    /// imported, files whose mtime changed are read again, and tracks whose
+    /// files are gone or no longer admitted by the root's options are removed.
+    /// Unchanged files are not opened, so this is cheap enough to run for
+    /// every watched folder at startup.
+    pub fn reconcile_path(
+        &self,
+        db: &mut Database,
+        root: impl AsRef<Path>,
+    ) -> Result<ReconcileSummary, ScanError> {
+        let root = root.as_ref();
+        check_root(root)?;
+        let root_path = normalize_path(root)?;
+        let root = Path::new(&root_path);
+        let start = Instant::now();
+        let mut tally = ImportTally::new(self.options.batch_size, root_options(db, &root_path)?);
+        let known = db
+            .list_track_mtimes_under_prefix(&root_path)?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let mut seen = HashSet::new();
+        let (mut added_tracks, mut updated_tracks, mut unchanged_tracks) = (0, 0, 0);
+
+        self.walk_tree(root, root, &mut tally, |tally, path| {
             let path_string = normalize_path(path)?;
-            if let Some(seen) = &mut seen_audio_paths {
-                seen.insert(path_string.clone());
+            match known.get(&path_string) {
+                Some(mtime) if *mtime == file_mtime_ms(path) => unchanged_tracks += 1,
+                known_track => {
+                    if known_track.is_some() {
+                        updated_tracks += 1;
+                    } else {
+                        added_tracks += 1;
+                    }
+                    tally.add_file(db, &self.options, path)?;
+                }
             }
+            seen.insert(path_string);
+            Ok(())
+        })?;
+        tally.flush(db)?;
 
-            let metadata = probe_embedded_metadata(
-                path,
-                self.options.read_embedded_artwork,
-                self.options.max_embedded_artwork_bytes,
-            );
-            let (artist, album) = metadata
-                .as_ref()
-                .map(|m| (m.artist.clone(), m.album.clone()))
-                .unwrap_or_else(|| infer_artist_album(path));
-            let title = metadata
-                .as_ref()
-                .and_then(|m| m.title.clone())
-                .or_else(|| infer_title(path));
-            let duration_ms = metadata.as_ref().and_then(|m| m.duration_ms);
-            let sample_rate = metadata.as_ref().and_then(|m| m.sample_rate);
-            let channels = metadata.as_ref().and_then(|m| m.channels);
-            let bit_depth = metadata.as_ref().and_then(|m| m.bit_depth);
-            let file_mtime_ms = file_mtime_ms(path);
-            let artwork = metadata.as_ref().and_then(|m| m.artwork.clone());
-            let artwork_oversize = metadata
-                .as_ref()
-                .and_then(|m| m.artwork_oversize_bytes)
-                .is_some();
-
-            batch.push(TrackRecord {
-                id: TrackId(Uuid::new_v4()),
-                path: path_string.clone(),
-                title,
-                artist,
-                album,
-                duration_ms,
-                sample_rate,
-                channels,
-                bit_depth,
-                file_mtime_ms,
-            });
-            discovered_audio_files += 1;
-            if artwork_oversize {
-                embedded_artwork_skipped_oversize += 1;
-            }
-            if let Some(artwork) = artwork {
-                embedded_artwork_candidates += 1;
-                artwork_batch.push(TrackArtworkUpsert {
-                    track_path: path_string,
-                    source_kind: "embedded".to_string(),
-                    source: "embedded".to_string(),
-                    mime_type: artwork.mime_type,
-                    picture_type: artwork.picture_type,
-                    bytes: artwork.bytes,
-                });
+        let removed_tracks = if root_looks_offline(root) {
+            tracing::warn!(root = %root.display(), "root looks offline; not removing missing tracks");
+            0
+        } else {
+            self.prune_missing_under_root(db, &root_path, &tally.root_options, Some(&seen))?
+        };
+        // New tracks cannot orphan artwork; re-read or removed ones can.
+        if removed_tracks > 0 || updated_tracks > 0 {
+            db.purge_orphan_artwork_assets()?;
+        }
+
+        Ok(ReconcileSummary {
+            root_path,
+            added_tracks,
+            updated_tracks,
+            removed_tracks,
+            unchanged_tracks,
+            elapsed_ms: start.elapsed().as_millis(),
+        })
+    }
+
+    fn import_tree(
+        &self,
+        db: &mut Database,
+        root: &Path,
+        dir: &Path,
+        tally: &mut ImportTally,
+        mut seen_audio_paths: Option<&mut HashSet<String>>,
+    ) -> Result<(), ScanError> {
+        self.walk_tree(root, dir, tally, |tally, path| {
+            let path_string = tally.add_file(db, &self.options, path)?;
+            if let Some(seen) = seen_audio_paths.as_deref_mut() {
+                seen.insert(path_string);
             }
+            Ok(())
+        })
+    }
 
-            if batch.len() >= self.options.batch_size.max(1) {
-                imported_tracks += db.upsert_tracks_batch(&batch)?;
-                if !artwork_batch.is_empty() {
-                    let art_summary = db.upsert_track_artwork_batch(&artwork_batch)?;
-                    embedded_artwork_linked_tracks += art_summary.linked_tracks;
-                    embedded_artwork_inserted_assets += art_summary.inserted_assets;
-                    embedded_artwork_reused_assets += art_summary.reused_assets;
-                    artwork_batch.clear();
+    /// Calls `visit` for every file under `dir` that the root admits,
+    /// counting the entries it skips. Only the top level of a non-recursive
+    /// root is walked.
+    fn walk_tree(
+        &self,
+        root: &Path,
+        dir: &Path,
+        tally: &mut ImportTally,
+        mut visit: impl FnMut(&mut ImportTally, &Path) -> Result<(), ScanError>,
+    ) -> Result<(), ScanError> {
+        let mut walker = WalkDir::new(dir).follow_links(self.options.follow_symlinks);
+        if !tally.root_options.recursive && dir == root {
+            walker = walker.max_depth(1);
+        }
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    tracing::warn!(error = %err, "skipping unreadable entry");
+                    tally.skipped_unreadable_entries += 1;
+                    continue;
                 }
-                batch.clear();
+            };
+
+            if entry.file_type().is_file() && tally.admits(entry.path()) {
+                visit(tally, entry.path())?;
             }
         }
+        Ok(())
+    }
 
-        if !batch.is_empty() {
-            imported_tracks += db.upsert_tracks_batch(&batch)?;
-            if !artwork_batch.is_empty() {
-                let art_summary = db.upsert_track_artwork_batch(&artwork_batch)?;
-                embedded_artwork_linked_tracks += art_summary.linked_tracks;
-                embedded_artwork_inserted_assets += art_summary.inserted_assets;
-                embedded_artwork_reused_assets += art_summary.reused_assets;
-                artwork_batch.clear();
-            }
+    /// Imports one file if it is audio the root's extension filters allow.
+    fn import_file(
+        &self,
+        db: &mut Database,
+        path: &Path,
+        tally: &mut ImportTally,
+    ) -> Result<(), ScanError> {
+        if tally.admits(path) {
+            tally.add_file(db, &self.options, path)?;
         }
+        Ok(())
+    }
 
-        let pruned_missing_tracks = if self.options.prune_missing {
-            self.prune_missing_under_root(db, &root_path, seen_audio_paths.as_ref())?
-        } else {
-            0
-        };
+    /// Pruning is on and the root is reachable. An unmounted drive or share
+    /// looks like every file was deleted, so nothing is pruned under it.
+    fn may_prune(&self, root: &Path) -> bool {
+        if !self.options.prune_missing {
+            return false;
+        }
+        let offline = root_looks_offline(root);
+        if offline {
+            tracing::warn!(root = %root.display(), "root looks offline; not pruning missing tracks");
+        }
+        !offline
+    }
 
+    fn finish(
+        &self,
+        db: &mut Database,
+        tally: ImportTally,
+        root_path: String,
+        pruned_missing_tracks: usize,
+        start: Instant,
+    ) -> Result<ScanSummary, ScanError> {
         let purge_orphans = self.options.read_embedded_artwork || self.options.prune_missing;
         let purged_orphan_artwork_assets = if purge_orphans {
             db.purge_orphan_artwork_assets()?
@@ -227,15 +330,17 @@ impl DirectoryScanner {
 
         Ok(ScanSummary {
             root_path,
-            discovered_audio_files,
-            imported_tracks,
-            embedded_artwork_candidates,
-            embedded_artwork_linked_tracks,
-            embedded_artwork_inserted_assets,
-            embedded_artwork_reused_assets,
-            embedded_artwork_skipped_oversize,
-            skipped_non_audio_files,
-            skipped_unreadable_entries,
+            discovered_audio_files: tally.discovered_audio_files,
+            imported_tracks: tally.imported_tracks,
+            embedded_artwork_candidates: tally.embedded_artwork_candidates,
+            embedded_artwork_linked_tracks: tally.embedded_artwork_linked_tracks,
+            embedded_artwork_inserted_assets: tally.embedded_artwork_inserted_assets,
+            embedded_artwork_reused_assets: tally.embedded_artwork_reused_assets,
+            embedded_artwork_skipped_oversize: tally.embedded_artwork_skipped_oversize,
+            folder_artwork_candidates: tally.folder_artwork_candidates,
+            skipped_non_audio_files: tally.skipped_non_audio_files,
+            skipped_filtered_files: tally.skipped_filtered_files,
+            skipped_unreadable_entries: tally.skipped_unreadable_entries,
             pruned_missing_tracks,
             purged_orphan_artwork_assets,
             elapsed_ms: start.elapsed().as_millis(),
@@ -251,19 +356,26 @@ impl DirectoryScanner {
         Ok(summaries)
     }
 
+    /// Removes tracks under the root that are gone from disk or that the
+    /// root's options no longer admit.
     fn prune_missing_under_root(
         &self,
         db: &mut Database,
         root_path: &str,
+        root_options: &RootOptions,
         seen_audio_paths: Option<&HashSet<String>>,
     ) -> Result<usize, ScanError> {
+        let root = Path::new(root_path);
         let mut delete_paths = Vec::new();
         for path in db.list_track_paths_under_prefix(root_path)? {
             let should_keep = seen_audio_paths.is_some_and(|seen| seen.contains(&path));
             if should_keep {
                 continue;
             }
-            if !Path::new(&path).exists() {
+            let track = Path::new(&path);
+            let admitted = root_options.accepts(track)
+                && (root_options.recursive || track.parent() == Some(root));
+            if !admitted || !track.exists() {
                 delete_paths.push(path);
             }
         }
@@ -271,34 +383,299 @@ impl DirectoryScanner {
     }
 }
 
-fn is_supported_audio_file(path: &Path) -> bool {
-    let ext = match path.extension().and_then(|e| e.to_str()) {
-        Some(ext) => ext,
-        None => return false,
-    };
+/// Tracks read so far and not yet written, plus the running counts for the
+/// summary. Writes happen a batch at a time.
+#[derive(Debug, Default)]
+struct ImportTally {
+    batch_size: usize,
+    root_options: RootOptions,
+    /// Cover image found in each folder, looked up once per folder.
+    folder_art: HashMap<PathBuf, Option<TrackArtworkUpsert>>,
+    batch: Vec<TrackRecord>,
+    artwork_batch: Vec<TrackArtworkUpsert>,
+    discovered_audio_files: usize,
+    imported_tracks: usize,
+    embedded_artwork_candidates: usize,
+    embedded_artwork_linked_tracks: usize,
+    embedded_artwork_inserted_assets: usize,
+    embedded_artwork_reused_assets: usize,
+    embedded_artwork_skipped_oversize: usize,
+    folder_artwork_candidates: usize,
+    skipped_non_audio_files: usize,
+    skipped_filtered_files: usize,
+    skipped_unreadable_entries: usize,
+}
+
+impl ImportTally {
+    fn new(batch_size: usize, root_options: RootOptions) -> Self {
+        let batch_size = batch_size.max(1);
+        Self {
+            batch_size,
+            root_options,
+            batch: Vec::with_capacity(batch_size),
+            artwork_batch: Vec::with_capacity(batch_size),
+            ..Self::default()
+        }
+    }
+
+    /// Reads one audio file and queues it, writing the batch once it is full.
+    /// Returns the normalized path.
+    fn add_file(
+        &mut self,
+        db: &mut Database,
+        options: &ScanOptions,
+        path: &Path,
+    ) -> Result<String, ScanError> {
+        let path_string = normalize_path(path)?;
+        let metadata = probe_embedded_metadata(
+            path,
+            options.read_embedded_artwork,
+            options.max_embedded_artwork_bytes,
+        );
+        let (artist, album) = metadata
+            .as_ref()
+            .map(|m| (m.artist.clone(), m.album.clone()))
+            .unwrap_or_else(|| infer_artist_album(path));
+        let title = metadata
+            .as_ref()
+            .and_then(|m| m.title.clone())
+            .or_else(|| infer_title(path));
+        let duration_ms = metadata.as_ref().and_then(|m| m.duration_ms);
+        let sample_rate = metadata.as_ref().and_then(|m| m.sample_rate);
+        let channels = metadata.as_ref().and_then(|m| m.channels);
+        let bit_depth = metadata.as_ref().and_then(|m| m.bit_depth);
+        let file_mtime_ms = file_mtime_ms(path);
+        let artwork = metadata.as_ref().and_then(|m| m.artwork.clone());
+        let artwork_oversize = metadata
+            .as_ref()
+            .and_then(|m| m.artwork_oversize_bytes)
+            .is_some();
 
-    matches!(
-        ext.to_ascii_lowercase().as_str(),
-        "flac"
-            | "wav"
-            | "wave"
-            | "aiff"
-            | "aif"
-            | "mp3"
-            | "m4a"
-            | "aac"
-            | "alac"
-            | "ogg"
-            | "opus"
-            | "wma"
-            | "ape"
-            | "wv"
-            | "dsf"
-            | "dff"
-    )
+        self.batch.push(TrackRecord {
+            id: TrackId(Uuid::new_v4()),
+            path: path_string.clone(),
+            title,
+            artist,
+            album,
+            duration_ms,
+            sample_rate,
+            channels,
+            bit_depth,
+            file_mtime_ms,
+        });
+        self.discovered_audio_files += 1;
+        if artwork_oversize {
+            self.embedded_artwork_skipped_oversize += 1;
+        }
+        if let Some(artwork) = artwork {
+            self.embedded_artwork_candidates += 1;
+            self.artwork_batch.push(TrackArtworkUpsert {
+                track_path: path_string.clone(),
+                source_kind: "embedded".to_string(),
+                source: "embedded".to_string(),
+                mime_type: artwork.mime_type,
+                picture_type: artwork.picture_type,
+                bytes: artwork.bytes,
+            });
+        } else if self.root_options.auto_artwork {
+            let folder = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            let max_bytes = options.max_embedded_artwork_bytes;
+            let cover = self
+                .folder_art
+                .entry(folder)
+                .or_insert_with_key(|folder| find_folder_artwork(folder, max_bytes));
+            if let Some(cover) = cover {
+                self.folder_artwork_candidates += 1;
+                self.artwork_batch.push(TrackArtworkUpsert {
+                    track_path: path_string.clone(),
+                    ..cover.clone()
+                });
+            }
+        }
+
+        if self.batch.len() >= self.batch_size {
+            self.flush(db)?;
+        }
+        Ok(path_string)
+    }
+
+    /// Whether `path` is audio the root's extension filters allow; counts the
+    /// files that are not.
+    fn admits(&mut self, path: &Path) -> bool {
+        if !auric_core::is_supported_audio_file(path) {
+            self.skipped_non_audio_files += 1;
+            false
+        } else if !self.root_options.accepts(path) {
+            self.skipped_filtered_files += 1;
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Links the cover image in `folder` to the tracks already imported
+    /// there, so a cover copied in after the music still shows. Tracks with
+    /// embedded artwork keep it.
+    fn link_folder_cover(
+        &mut self,
+        db: &mut Database,
+        options: &ScanOptions,
+        folder: &Path,
+    ) -> Result<(), ScanError> {
+        let Some(cover) = find_folder_artwork(folder, options.max_embedded_artwork_bytes) else {
+            return Ok(());
+        };
+        let folder_path = normalize_path(folder)?;
+        for track_path in db.list_track_paths_under_prefix(&folder_path)? {
+            if Path::new(&track_path).parent() != Some(Path::new(&folder_path))
+                || self
+                    .artwork_batch
+                    .iter()
+                    .any(|art| art.track_path == track_path)
+            {
+                continue;
+            }
+            let current = db.get_track_artwork_by_path(&track_path)?;
+            if current.is_some_and(|art| art.source == "embedded") {
+                continue;
+            }
+            self.folder_artwork_candidates += 1;
+            self.artwork_batch.push(TrackArtworkUpsert {
+                track_path,
+                ..cover.clone()
+            });
+        }
+        self.folder_art.insert(folder.to_path_buf(), Some(cover));
+        Ok(())
+    }
+
+    fn flush(&mut self, db: &mut Database) -> Result<(), ScanError> {
+        if self.batch.is_empty() && self.artwork_batch.is_empty() {
+            return Ok(());
+        }
+        if !self.batch.is_empty() {
+            self.imported_tracks += db.upsert_tracks_batch(&self.batch)?;
+        }
+        if !self.artwork_batch.is_empty() {
+            let art_summary = db.upsert_track_artwork_batch(&self.artwork_batch)?;
+            self.embedded_artwork_linked_tracks += art_summary.linked_tracks;
+            self.embedded_artwork_inserted_assets += art_summary.inserted_assets;
+            self.embedded_artwork_reused_assets += art_summary.reused_assets;
+            self.artwork_batch.clear();
+        }
+        self.batch.clear();
+        Ok(())
+    }
+}
+
+/// Fails unless `root` is an existing directory.
+fn check_root(root: &Path) -> Result<(), ScanError> {
+    let root_meta = fs::metadata(root).map_err(|err| {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            ScanError::InvalidRoot(format!("{} (not found)", root.display()))
+        } else {
+            ScanError::Io(err)
+        }
+    })?;
+    if !root_meta.is_dir() {
+        return Err(ScanError::InvalidRoot(format!(
+            "{} (not a directory)",
+            root.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Whether `root` looks like an unmounted drive or share: missing,
+/// unreadable, or an empty directory left behind as a mount point.
+pub fn root_looks_offline(root: &Path) -> bool {
+    match fs::read_dir(root) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => true,
+    }
+}
+
+/// Options saved for `root_path` when it is a library root, defaults otherwise.
+fn root_options(db: &Database, root_path: &str) -> Result<RootOptions, ScanError> {
+    Ok(db
+        .get_library_root_by_path(root_path)?
+        .map(|root| root.options)
+        .unwrap_or_default())
+}
+
+/// Links the cover image in each track's folder (`cover.jpg`, `Folder.png`,
+/// ...) to the tracks at `track_paths`, whether or not their root links
+/// folder covers on its own. Returns how many tracks were linked.
+pub fn link_folder_covers(
+    db: &mut Database,
+    track_paths: &[String],
+    max_bytes: usize,
+) -> Result<usize, ScanError> {
+    let mut covers: HashMap<PathBuf, Option<TrackArtworkUpsert>> = HashMap::new();
+    let batch = track_paths
+        .iter()
+        .filter_map(|track_path| {
+            let folder = Path::new(track_path).parent()?.to_path_buf();
+            let cover = covers
+                .entry(folder)
+                .or_insert_with_key(|folder| find_folder_artwork(folder, max_bytes));
+            Some(TrackArtworkUpsert {
+                track_path: track_path.clone(),
+                ..cover.clone()?
+            })
+        })
+        .collect::<Vec<_>>();
+    if batch.is_empty() {
+        return Ok(0);
+    }
+    Ok(db.upsert_track_artwork_batch(&batch)?.linked_tracks)
+}
+
+const FOLDER_ARTWORK_NAMES: &[&str] = &["cover", "folder", "front", "album"];
+
+/// Looks for a conventional cover image (`cover.jpg`, `Folder.png`, ...) in
+/// `dir`, preferring names in [`FOLDER_ARTWORK_NAMES`] order.
+fn find_folder_artwork(dir: &Path, max_bytes: usize) -> Option<TrackArtworkUpsert> {
+    let mut found = fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (rank, mime) = folder_artwork_kind(&path)?;
+            Some((rank, path, mime))
+        })
+        .collect::<Vec<_>>();
+    found.sort();
+    found.into_iter().find_map(|(_, path, mime)| {
+        let len = fs::metadata(&path).ok()?.len();
+        if len == 0 || len > max_bytes as u64 {
+            return None;
+        }
+        Some(TrackArtworkUpsert {
+            track_path: String::new(),
+            source_kind: "folder".to_string(),
+            source: path.to_string_lossy().to_string(),
+            mime_type: Some(mime.to_string()),
+            picture_type: Some("CoverFront".to_string()),
+            bytes: fs::read(&path).ok()?,
+        })
+    })
+}
+
+/// For a conventional cover image, its rank in [`FOLDER_ARTWORK_NAMES`] and
+/// its MIME type.
+fn folder_artwork_kind(path: &Path) -> Option<(usize, &'static str)> {
+    let stem = path.file_stem()?.to_str()?.to_ascii_lowercase();
+    let rank = FOLDER_ARTWORK_NAMES.iter().position(|name| *name == stem)?;
+    let mime = match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        _ => return None,
+    };
+    Some((rank, mime))
 }
 
-fn infer_title(path: &Path) -> Option<String> {
+pub(crate) fn infer_title(path: &Path) -> Option<String> {
     let stem = path.file_stem()?.to_string_lossy();
     let title = stem.replace('_', " ").trim().to_string();
     if title.is_empty() {
@@ -308,7 +685,7 @@ fn infer_title(path: &Path) -> Option<String> {
     }
 }
 
-fn infer_artist_album(path: &Path) -> (Option<String>, Option<String>) {
+pub(crate) fn infer_artist_album(path: &Path) -> (Option<String>, Option<String>) {
     let mut comps = path
         .parent()
         .into_iter()
@@ -357,7 +734,13 @@ fn probe_embedded_metadata(
     use lofty::probe::Probe;
     use lofty::tag::Accessor;
 
-    let tagged_file = Probe::open(path).ok()?.read().ok()?;
+    let tagged_file = match Probe::open(path).and_then(|probe| probe.read()) {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "failed to read tags");
+            return None;
+        }
+    };
     let props = tagged_file.properties();
     let tag = tagged_file
         .primary_tag()
@@ -491,4 +874,222 @@ mod tests {
         assert_eq!(summary.pruned_missing_tracks, 1);
         assert_eq!(db.count_tracks().unwrap(), 1);
     }
+
+    #[test]
+    fn scan_paths_imports_only_the_changed_paths() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("music");
+        let album = root.join("Artist").join("Album");
+        fs::create_dir_all(&album).unwrap();
+        fs::write(root.join("old.flac"), b"x").unwrap();
+        fs::write(root.join("untouched.flac"), b"x").unwrap();
+
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let scanner = DirectoryScanner::new(ScanOptions {
+            prune_missing: true,
+            ..ScanOptions::default()
+        });
+        scanner
+            .scan_paths(&mut db, &root, &[root.join("old.flac")])
+            .unwrap();
+        assert_eq!(db.count_tracks().unwrap(), 1);
+
+        fs::remove_file(root.join("old.flac")).unwrap();
+        fs::write(album.join("01.flac"), b"x").unwrap();
+        fs::write(album.join("02.flac"), b"x").unwrap();
+        fs::write(album.join("cover.jpg"), b"x").unwrap();
+        let summary = scanner
+            .scan_paths(&mut db, &root, &[root.join("old.flac"), album.clone()])
+            .unwrap();
+        assert_eq!(summary.imported_tracks, 2);
+        assert_eq!(summary.skipped_non_audio_files, 1);
+        assert_eq!(summary.pruned_missing_tracks, 1);
+        let paths = db
+            .list_tracks(10)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|p| p.contains("Album")));
+    }
+
+    #[test]
+    fn root_options_limit_depth_and_extensions_and_link_folder_art() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("music");
+        let nested = root.join("Artist").join("Album");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("top.flac"), b"x").unwrap();
+        fs::write(root.join("top.mp3"), b"x").unwrap();
+        fs::write(root.join("Cover.JPG"), b"jpeg bytes").unwrap();
+        fs::write(nested.join("deep.flac"), b"x").unwrap();
+
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let root_path = normalize_path(&root).unwrap();
+        db.upsert_library_root(&crate::LibraryRoot {
+            path: root_path.clone(),
+            watched: true,
+        })
+        .unwrap();
+        let scanner = DirectoryScanner::new(ScanOptions {
+            prune_missing: true,
+            ..ScanOptions::default()
+        });
+        assert_eq!(
+            scanner.scan_path(&mut db, &root).unwrap().imported_tracks,
+            3
+        );
+
+        let options = RootOptions {
+            recursive: false,
+            include_extensions: Vec::new(),
+            exclude_extensions: RootOptions::parse_extensions(".MP3"),
+            auto_artwork: true,
+        };
+        db.set_library_root_options(&root_path, &options).unwrap();
+        let summary = scanner.scan_path(&mut db, &root).unwrap();
+        assert_eq!(summary.imported_tracks, 1);
+        assert_eq!(summary.skipped_filtered_files, 1);
+        assert_eq!(summary.folder_artwork_candidates, 1);
+        assert_eq!(summary.pruned_missing_tracks, 2);
+        let tracks = db.list_tracks(10).unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert!(tracks[0].path.ends_with("top.flac"));
+        assert_eq!(db.count_track_artwork_links().unwrap(), 1);
+
+        let summary = scanner
+            .scan_paths(&mut db, &root, &[nested.join("deep.flac"), nested.clone()])
+            .unwrap();
+        assert_eq!(summary.discovered_audio_files, 0);
+        assert_eq!(db.count_tracks().unwrap(), 1);
+    }
+
+    #[test]
+    fn a_cover_arriving_after_its_music_is_linked_to_the_folder() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("music");
+        let album = root.join("Artist").join("Album");
+        fs::create_dir_all(&album).unwrap();
+        fs::write(album.join("01.flac"), b"x").unwrap();
+        fs::write(album.join("02.flac"), b"x").unwrap();
+        fs::write(root.join("loose.flac"), b"x").unwrap();
+
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let root_path = normalize_path(&root).unwrap();
+        db.upsert_library_root(&crate::LibraryRoot {
+            path: root_path.clone(),
+            watched: true,
+        })
+        .unwrap();
+        let options = RootOptions {
+            auto_artwork: true,
+            ..RootOptions::default()
+        };
+        db.set_library_root_options(&root_path, &options).unwrap();
+        let scanner = DirectoryScanner::new(ScanOptions::default());
+        scanner
+            .scan_paths(&mut db, &root, &[album.clone(), root.join("loose.flac")])
+            .unwrap();
+        assert_eq!(db.count_track_artwork_links().unwrap(), 0);
+
+        fs::write(album.join("folder.png"), b"png bytes").unwrap();
+        fs::write(album.join("notes.txt"), b"x").unwrap();
+        let summary = scanner
+            .scan_paths(
+                &mut db,
+                &root,
+                &[album.join("folder.png"), album.join("notes.txt")],
+            )
+            .unwrap();
+        assert_eq!(summary.folder_artwork_candidates, 2);
+        assert_eq!(summary.embedded_artwork_inserted_assets, 1);
+        assert_eq!(db.count_track_artwork_links().unwrap(), 2);
+        let art = db
+            .get_track_artwork_by_path(&normalize_path(&album.join("01.flac")).unwrap())
+            .unwrap()
+            .unwrap();
+        assert!(art.source.ends_with("folder.png"));
+
+        // A better-ranked cover replaces it.
+        fs::write(album.join("cover.jpg"), b"jpeg bytes").unwrap();
+        scanner
+            .scan_paths(&mut db, &root, &[album.join("cover.jpg")])
+            .unwrap();
+        let art = db
+            .get_track_artwork_by_path(&normalize_path(&album.join("02.flac")).unwrap())
+            .unwrap()
+            .unwrap();
+        assert!(art.source.ends_with("cover.jpg"));
+        assert_eq!(db.count_track_artwork_links().unwrap(), 2);
+    }
+
+    #[test]
+    fn prune_is_skipped_when_the_root_looks_unmounted() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("mnt");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.flac"), b"x").unwrap();
+        fs::write(root.join("b.flac"), b"x").unwrap();
+
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let scanner = DirectoryScanner::new(ScanOptions {
+            prune_missing: true,
+            ..ScanOptions::default()
+        });
+        scanner.scan_path(&mut db, &root).unwrap();
+
+        fs::remove_file(root.join("a.flac")).unwrap();
+        fs::remove_file(root.join("b.flac")).unwrap();
+        assert!(root_looks_offline(&root));
+        let summary = scanner.scan_path(&mut db, &root).unwrap();
+        assert_eq!(summary.pruned_missing_tracks, 0);
+        let summary = scanner
+            .scan_paths(&mut db, &root, &[root.join("a.flac")])
+            .unwrap();
+        assert_eq!(summary.pruned_missing_tracks, 0);
+        assert_eq!(db.count_tracks().unwrap(), 2);
+    }
+
+    #[test]
+    fn reconcile_adds_updates_and_removes_only_what_changed() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("music");
+        fs::create_dir_all(root.join("Album")).unwrap();
+        for name in ["keep.flac", "edit.flac", "gone.flac"] {
+            fs::write(root.join("Album").join(name), b"x").unwrap();
+        }
+
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let scanner = DirectoryScanner::new(ScanOptions::default());
+        let first = scanner.reconcile_path(&mut db, &root).unwrap();
+        assert_eq!(
+            (
+                first.added_tracks,
+                first.updated_tracks,
+                first.removed_tracks
+            ),
+            (3, 0, 0)
+        );
+
+        fs::remove_file(root.join("Album").join("gone.flac")).unwrap();
+        fs::write(root.join("Album").join("new.flac"), b"x").unwrap();
+        let edited = fs::File::options()
+            .write(true)
+            .open(root.join("Album").join("edit.flac"))
+            .unwrap();
+        edited
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+
+        let summary = scanner.reconcile_path(&mut db, &root).unwrap();
+        assert_eq!(summary.added_tracks, 1);
+        assert_eq!(summary.updated_tracks, 1);
+        assert_eq!(summary.removed_tracks, 1);
+        assert_eq!(summary.unchanged_tracks, 1);
+        assert_eq!(db.count_tracks().unwrap(), 3);
+        assert!(scanner
+            .reconcile_path(&mut db, root.join("missing"))
+            .is_err());
+    }
 }
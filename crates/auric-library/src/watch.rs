@@ -1,10 +1,11 @@
 use crate::db::Database;
-use crate::scan::{DirectoryScanner, ScanError, ScanOptions, ScanSummary};
-use notify::{recommended_watcher, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use crate::scan::{root_looks_offline, DirectoryScanner, ScanError, ScanOptions, ScanSummary};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Debug, Clone)]
 pub struct WatchOptions {
@@ -18,6 +19,9 @@ pub struct WatchOptions {
     pub max_embedded_artwork_bytes: usize,
     pub scan_on_start: bool,
     pub max_runtime: Option<Duration>,
+    /// How often roots are checked for going offline, coming back, or being
+    /// paused or resumed.
+    pub root_check_interval_ms: u64,
 }
 
 impl Default for WatchOptions {
@@ -33,6 +37,7 @@ impl Default for WatchOptions {
             max_embedded_artwork_bytes: 8 * 1024 * 1024,
             scan_on_start: false,
             max_runtime: None,
+            root_check_interval_ms: 2_000,
         }
     }
 }
@@ -57,6 +62,15 @@ pub struct WatchRescan {
     pub summary: ScanSummary,
 }
 
+/// A file or folder renamed or moved within the watched roots. Its tracks
+/// keep their ids; only the paths change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchRename {
+    pub from: String,
+    pub to: String,
+    pub moved_tracks: usize,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WatchSessionSummary {
     pub watched_root_count: usize,
@@ -64,6 +78,7 @@ pub struct WatchSessionSummary {
     pub observed_notify_events: usize,
     pub ignored_notify_events: usize,
     pub rescans: Vec<WatchRescan>,
+    pub renames: Vec<WatchRename>,
     pub elapsed_ms: u128,
 }
 
@@ -80,11 +95,22 @@ pub enum WatchError {
 #[derive(Debug, Clone)]
 pub struct WatchedFolderService {
     options: WatchOptions,
+    rename_tx: Option<mpsc::Sender<WatchRename>>,
 }
 
 impl WatchedFolderService {
     pub fn new(options: WatchOptions) -> Self {
-        Self { options }
+        Self {
+            options,
+            rename_tx: None,
+        }
+    }
+
+    /// Also reports each rename on `tx` as it happens, so a running player
+    /// can update the paths in its queue.
+    pub fn with_rename_sender(mut self, tx: mpsc::Sender<WatchRename>) -> Self {
+        self.rename_tx = Some(tx);
+        self
     }
 
     pub fn options(&self) -> &WatchOptions {
@@ -118,36 +144,26 @@ impl WatchedFolderService {
                 observed_notify_events: 0,
                 ignored_notify_events: 0,
                 rescans: Vec::new(),
+                renames: Vec::new(),
                 elapsed_ms: 0,
             });
         }
 
+        let scanner = DirectoryScanner::new(self.options.scan_options());
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = build_watcher(tx)?;
         let mut skipped_root_count = 0usize;
         let mut rescans = Vec::new();
-        let mut active_roots = roots
-            .into_iter()
-            .filter(|root| {
-                let watchable = root.path.is_dir();
-                if !watchable {
-                    skipped_root_count = skipped_root_count.saturating_add(1);
-                }
-                watchable
-            })
-            .collect::<Vec<_>>();
-        if active_roots.is_empty() {
-            return Ok(WatchSessionSummary {
-                watched_root_count: 0,
-                skipped_root_count,
-                observed_notify_events: 0,
-                ignored_notify_events: 0,
-                rescans,
-                elapsed_ms: started.elapsed().as_millis(),
-            });
-        }
-
-        let scanner = DirectoryScanner::new(self.options.scan_options());
-        if self.options.scan_on_start {
-            for root in &active_roots {
+        let mut roots_by_state = RootSet::default();
+        for root in roots {
+            let status = root_status(db, &root)?;
+            if status != RootStatus::Online {
+                note_detached(db, &root, status)?;
+                skipped_root_count = skipped_root_count.saturating_add(1);
+                roots_by_state.detached.push((root, status));
+                continue;
+            }
+            if self.options.scan_on_start {
                 let summary = scanner.scan_path(db, &root.path)?;
                 rescans.push(WatchRescan {
                     root_path: root.path_string.clone(),
@@ -156,38 +172,32 @@ impl WatchedFolderService {
                     summary,
                 });
             }
-        }
-
-        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
-        let mut watcher = build_watcher(tx)?;
-        let mut final_roots = Vec::with_capacity(active_roots.len());
-        for root in active_roots.drain(..) {
-            match watcher.watch(&root.path, RecursiveMode::Recursive) {
-                Ok(()) => final_roots.push(root),
-                Err(err) => {
-                    eprintln!(
-                        "warning: could not watch root '{}': {err}",
-                        root.path.display()
-                    );
-                    skipped_root_count = skipped_root_count.saturating_add(1);
-                }
+            if attach_root(db, &mut watcher, &root)? {
+                roots_by_state.attached.push(root);
+            } else {
+                skipped_root_count = skipped_root_count.saturating_add(1);
             }
         }
-        if final_roots.is_empty() {
+        if roots_by_state.attached.is_empty() && roots_by_state.detached.is_empty() {
             return Ok(WatchSessionSummary {
                 watched_root_count: 0,
                 skipped_root_count,
                 observed_notify_events: 0,
                 ignored_notify_events: 0,
                 rescans,
+                renames: Vec::new(),
                 elapsed_ms: started.elapsed().as_millis(),
             });
         }
 
         let mut observed_notify_events = 0usize;
         let mut ignored_notify_events = 0usize;
-        let mut pending = PendingRoots::new(self.options.debounce_ms);
+        let mut pending = PendingPaths::new(self.options.debounce_ms);
+        let mut renames = Vec::new();
         let poll_timeout = Duration::from_millis(self.options.poll_timeout_ms.max(10));
+        let root_check_interval =
+            Duration::from_millis(self.options.root_check_interval_ms.max(10));
+        let mut next_root_check = Instant::now() + root_check_interval;
 
         loop {
             let elapsed = started.elapsed();
@@ -204,19 +214,28 @@ impl WatchedFolderService {
             match rx.recv_timeout(timeout) {
                 Ok(Ok(event)) => {
                     observed_notify_events += 1;
+                    if let Some(rename) = self.apply_rename(db, &roots_by_state.attached, &event)? {
+                        // The separate from/to events that preceded this one
+                        // would otherwise prune and re-read the moved tracks.
+                        pending.forget(Path::new(&rename.from));
+                        pending.forget(Path::new(&rename.to));
+                        renames.push(rename);
+                        continue;
+                    }
                     let now_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
-                    let changed_roots = roots_for_event_paths(&final_roots, &event);
-                    if changed_roots.is_empty() {
+                    let changed = changed_paths(&roots_by_state.attached, &event);
+                    if changed.is_empty() {
                         ignored_notify_events += 1;
                     } else {
-                        for root in changed_roots {
-                            pending.mark(root, now_ms);
+                        for (root, path) in changed {
+                            pending.mark(root, path, now_ms, FileState::of(path));
                         }
                     }
                 }
-                Ok(Err(_notify_err)) => {
+                Ok(Err(notify_err)) => {
                     // Ignore per-event errors and continue; the session summary surfaces ignored counts
                     // and runtime returns only on setup/fatal scan/database failures.
+                    tracing::warn!(error = %notify_err, "watcher event error");
                     ignored_notify_events += 1;
                 }
                 Err(mpsc::RecvTimeoutError::Timeout) => {}
@@ -230,12 +249,24 @@ impl WatchedFolderService {
                 &mut pending,
                 u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
             )?;
+
+            if Instant::now() >= next_root_check {
+                check_roots(
+                    &scanner,
+                    db,
+                    &mut watcher,
+                    &mut roots_by_state,
+                    &mut pending,
+                    &mut rescans,
+                )?;
+                next_root_check = Instant::now() + root_check_interval;
+            }
         }
 
-        // Drain any remaining debounced roots before exit.
+        // Drain any remaining debounced paths before exit.
         let remaining = pending.drain_all();
-        for (root_path, event_count) in remaining {
-            let summary = scanner.scan_path(db, Path::new(&root_path))?;
+        for (root_path, paths, event_count) in remaining {
+            let summary = scanner.scan_paths(db, Path::new(&root_path), &paths)?;
             rescans.push(WatchRescan {
                 root_path,
                 reason: "shutdown-flush".to_string(),
@@ -245,14 +276,47 @@ impl WatchedFolderService {
         }
 
         Ok(WatchSessionSummary {
-            watched_root_count: final_roots.len(),
+            watched_root_count: roots_by_state.attached.len(),
             skipped_root_count,
             observed_notify_events,
             ignored_notify_events,
             rescans,
+            renames,
             elapsed_ms: started.elapsed().as_millis(),
         })
     }
+
+    /// Moves the tracks for a rename event whose old and new paths are both
+    /// under a watched root. Returns `None` for every other event, which is
+    /// then handled as separate changes to each path.
+    fn apply_rename(
+        &self,
+        db: &mut Database,
+        roots: &[WatchedRoot],
+        event: &Event,
+    ) -> Result<Option<WatchRename>, WatchError> {
+        let EventKind::Modify(ModifyKind::Name(RenameMode::Both)) = event.kind else {
+            return Ok(None);
+        };
+        let [from, to] = event.paths.as_slice() else {
+            return Ok(None);
+        };
+        if best_matching_root(roots, from).is_none() || best_matching_root(roots, to).is_none() {
+            return Ok(None);
+        }
+        let from = from.to_string_lossy().to_string();
+        let to = to.to_string_lossy().to_string();
+        let moved_tracks = db.rename_track_paths(&from, &to)?;
+        let rename = WatchRename {
+            from,
+            to,
+            moved_tracks,
+        };
+        if let Some(tx) = &self.rename_tx {
+            let _ = tx.send(rename.clone());
+        }
+        Ok(Some(rename))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -261,6 +325,133 @@ pub struct WatchedRoot {
     pub path: PathBuf,
 }
 
+/// Whether a root can be watched right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RootStatus {
+    Online,
+    Paused,
+    Offline,
+}
+
+fn root_status(db: &Database, root: &WatchedRoot) -> Result<RootStatus, WatchError> {
+    let paused = db
+        .get_library_root_by_path(&root.path_string)?
+        .is_some_and(|row| row.paused);
+    Ok(if paused {
+        RootStatus::Paused
+    } else if root_looks_offline(&root.path) {
+        RootStatus::Offline
+    } else {
+        RootStatus::Online
+    })
+}
+
+/// The session's roots, split by whether the watcher is attached to them.
+/// Detached roots remember why, so a returning root can be reported as
+/// resumed or re-attached.
+#[derive(Debug, Default)]
+struct RootSet {
+    attached: Vec<WatchedRoot>,
+    detached: Vec<(WatchedRoot, RootStatus)>,
+}
+
+/// Starts watching `root` with its saved recursion option and clears its
+/// offline mark. Returns `false` if the watch could not be set up.
+fn attach_root(
+    db: &Database,
+    watcher: &mut RecommendedWatcher,
+    root: &WatchedRoot,
+) -> Result<bool, WatchError> {
+    let recursive = db
+        .get_library_root_by_path(&root.path_string)?
+        .map(|row| row.options.recursive)
+        .unwrap_or(true);
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    match watcher.watch(&root.path, mode) {
+        Ok(()) => {
+            db.set_library_root_offline(&root.path_string, false)?;
+            Ok(true)
+        }
+        Err(err) => {
+            tracing::warn!(root = %root.path.display(), error = %err, "could not watch root");
+            eprintln!(
+                "warning: could not watch root '{}': {err}",
+                root.path.display()
+            );
+            Ok(false)
+        }
+    }
+}
+
+fn note_detached(db: &Database, root: &WatchedRoot, status: RootStatus) -> Result<(), WatchError> {
+    match status {
+        RootStatus::Offline => {
+            db.set_library_root_offline(&root.path_string, true)?;
+            tracing::warn!(root = %root.path.display(), "root is offline; keeping its tracks until it returns");
+        }
+        RootStatus::Paused => tracing::info!(root = %root.path.display(), "watching paused"),
+        RootStatus::Online => {}
+    }
+    Ok(())
+}
+
+/// Detaches roots that went offline or were paused, and re-attaches the ones
+/// that are back, rescanning each to reconcile what changed in the meantime.
+fn check_roots(
+    scanner: &DirectoryScanner,
+    db: &mut Database,
+    watcher: &mut RecommendedWatcher,
+    roots: &mut RootSet,
+    pending: &mut PendingPaths,
+    rescans: &mut Vec<WatchRescan>,
+) -> Result<(), WatchError> {
+    for root in std::mem::take(&mut roots.attached) {
+        let status = root_status(db, &root)?;
+        if status == RootStatus::Online {
+            roots.attached.push(root);
+            continue;
+        }
+        let _ = watcher.unwatch(&root.path);
+        pending.forget_root(&root.path_string);
+        note_detached(db, &root, status)?;
+        roots.detached.push((root, status));
+    }
+
+    for (root, previous) in std::mem::take(&mut roots.detached) {
+        let status = root_status(db, &root)?;
+        if status != RootStatus::Online {
+            if status != previous {
+                note_detached(db, &root, status)?;
+            }
+            roots.detached.push((root, status));
+            continue;
+        }
+        if !attach_root(db, watcher, &root)? {
+            roots.detached.push((root, previous));
+            continue;
+        }
+        let reason = if previous == RootStatus::Paused {
+            "resumed"
+        } else {
+            "reattached"
+        };
+        tracing::info!(root = %root.path.display(), reason, "watching root again");
+        let summary = scanner.scan_path(db, &root.path)?;
+        rescans.push(WatchRescan {
+            root_path: root.path_string.clone(),
+            reason: reason.to_string(),
+            event_count: 0,
+            summary,
+        });
+        roots.attached.push(root);
+    }
+    Ok(())
+}
+
 fn build_watcher(
     tx: mpsc::Sender<notify::Result<Event>>,
 ) -> Result<RecommendedWatcher, notify::Error> {
@@ -271,7 +462,7 @@ fn build_watcher(
 
 fn compute_poll_timeout(
     base_timeout: Duration,
-    pending: &PendingRoots,
+    pending: &PendingPaths,
     started: Instant,
     max_runtime: Option<Duration>,
 ) -> Duration {
@@ -295,11 +486,11 @@ fn drain_ready_rescans(
     scanner: &DirectoryScanner,
     db: &mut Database,
     rescans: &mut Vec<WatchRescan>,
-    pending: &mut PendingRoots,
+    pending: &mut PendingPaths,
     now_ms: u64,
 ) -> Result<(), WatchError> {
-    for (root_path, event_count) in pending.drain_ready(now_ms) {
-        let summary = scanner.scan_path(db, Path::new(&root_path))?;
+    for (root_path, paths, event_count) in pending.drain_ready(now_ms, FileState::of) {
+        let summary = scanner.scan_paths(db, Path::new(&root_path), &paths)?;
         rescans.push(WatchRescan {
             root_path,
             reason: "filesystem-change".to_string(),
@@ -310,16 +501,19 @@ fn drain_ready_rescans(
     Ok(())
 }
 
-fn roots_for_event_paths<'a>(roots: &'a [WatchedRoot], event: &Event) -> Vec<&'a str> {
-    let mut matched = Vec::new();
-    for path in &event.paths {
-        if let Some(root) = best_matching_root(roots, path) {
-            if !matched.contains(&root.path_string.as_str()) {
-                matched.push(root.path_string.as_str());
-            }
-        }
+/// The paths an event touched, each with the root it belongs to. Access
+/// events (files being read, including by our own scans) are not changes.
+fn changed_paths<'a, 'e>(roots: &'a [WatchedRoot], event: &'e Event) -> Vec<(&'a str, &'e Path)> {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return Vec::new();
     }
-    matched
+    event
+        .paths
+        .iter()
+        .filter_map(|path| {
+            best_matching_root(roots, path).map(|root| (root.path_string.as_str(), path.as_path()))
+        })
+        .collect()
 }
 
 fn best_matching_root<'a>(roots: &'a [WatchedRoot], path: &Path) -> Option<&'a WatchedRoot> {
@@ -329,91 +523,206 @@ fn best_matching_root<'a>(roots: &'a [WatchedRoot], path: &Path) -> Option<&'a W
         .max_by_key(|root| root.path.as_os_str().len())
 }
 
+/// What a path looked like when last checked. A file still being copied
+/// changes size or mtime between checks; one that has settled does not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileState {
+    Missing,
+    Present {
+        len: u64,
+        modified: Option<SystemTime>,
+    },
+}
+
+impl FileState {
+    fn of(path: &Path) -> Self {
+        match std::fs::metadata(path) {
+            Ok(meta) => Self::Present {
+                len: meta.len(),
+                modified: meta.modified().ok(),
+            },
+            Err(_) => Self::Missing,
+        }
+    }
+}
+
+/// Changed paths waiting for their events to settle. A path is ready once
+/// `debounce_ms` has passed since its last event and it looks the same as
+/// when that event arrived; otherwise it waits another round, so half-copied
+/// files are not imported.
 #[derive(Debug, Clone, Default)]
-struct PendingRoots {
+struct PendingPaths {
     debounce_ms: u64,
-    roots: BTreeMap<String, PendingRoot>,
+    paths: BTreeMap<PathBuf, PendingPath>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct PendingRoot {
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingPath {
+    root: String,
     last_event_ms: u64,
     event_count: usize,
+    state: FileState,
 }
 
-impl PendingRoots {
+/// A root with the settled paths to import under it and how many events
+/// they came from.
+type ReadyRoot = (String, Vec<PathBuf>, usize);
+
+impl PendingPaths {
     fn new(debounce_ms: u64) -> Self {
         Self {
             debounce_ms,
-            roots: BTreeMap::new(),
+            paths: BTreeMap::new(),
         }
     }
 
-    fn mark(&mut self, root_path: &str, now_ms: u64) {
+    /// Drops every pending path under a root that is no longer watched.
+    fn forget_root(&mut self, root_path: &str) {
+        self.paths.retain(|_, pending| pending.root != root_path);
+    }
+
+    fn mark(&mut self, root_path: &str, path: &Path, now_ms: u64, state: FileState) {
         let entry = self
-            .roots
-            .entry(root_path.to_string())
-            .or_insert(PendingRoot {
+            .paths
+            .entry(path.to_path_buf())
+            .or_insert_with(|| PendingPath {
+                root: root_path.to_string(),
                 last_event_ms: now_ms,
                 event_count: 0,
+                state,
             });
         entry.last_event_ms = now_ms;
         entry.event_count = entry.event_count.saturating_add(1);
+        entry.state = state;
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.paths.remove(path);
     }
 
     fn next_ready_at_ms(&self) -> Option<u64> {
-        self.roots
+        self.paths
             .values()
             .map(|pending| pending.last_event_ms.saturating_add(self.debounce_ms))
             .min()
     }
 
-    fn drain_ready(&mut self, now_ms: u64) -> Vec<(String, usize)> {
-        let mut ready_keys = Vec::new();
-        for (root, pending) in &self.roots {
-            if now_ms >= pending.last_event_ms.saturating_add(self.debounce_ms) {
-                ready_keys.push(root.clone());
+    /// Takes the paths that have settled, grouped by root. `state_of` reads a
+    /// path's current state; paths that changed since their last event are
+    /// kept and checked again after another debounce interval.
+    fn drain_ready(
+        &mut self,
+        now_ms: u64,
+        state_of: impl Fn(&Path) -> FileState,
+    ) -> Vec<ReadyRoot> {
+        let mut ready = BTreeMap::new();
+        for (path, pending) in &mut self.paths {
+            if now_ms < pending.last_event_ms.saturating_add(self.debounce_ms) {
+                continue;
             }
-        }
-        let mut ready = Vec::with_capacity(ready_keys.len());
-        for key in ready_keys {
-            if let Some(pending) = self.roots.remove(&key) {
-                ready.push((key, pending.event_count));
+            let state = state_of(path);
+            if state == pending.state {
+                ready.insert(path.clone(), pending.clone());
+            } else {
+                pending.state = state;
+                pending.last_event_ms = now_ms;
             }
         }
-        ready
+        for path in ready.keys() {
+            self.paths.remove(path);
+        }
+        group_by_root(ready)
     }
 
-    fn drain_all(&mut self) -> Vec<(String, usize)> {
-        std::mem::take(&mut self.roots)
-            .into_iter()
-            .map(|(root, pending)| (root, pending.event_count))
-            .collect()
+    fn drain_all(&mut self) -> Vec<ReadyRoot> {
+        group_by_root(std::mem::take(&mut self.paths))
     }
 }
 
+fn group_by_root(paths: BTreeMap<PathBuf, PendingPath>) -> Vec<ReadyRoot> {
+    let mut roots: BTreeMap<String, (Vec<PathBuf>, usize)> = BTreeMap::new();
+    for (path, pending) in paths {
+        let (paths, event_count) = roots.entry(pending.root).or_default();
+        paths.push(path);
+        *event_count = event_count.saturating_add(pending.event_count);
+    }
+    roots
+        .into_iter()
+        .map(|(root, (paths, event_count))| (root, paths, event_count))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn present(len: u64) -> FileState {
+        FileState::Present {
+            len,
+            modified: None,
+        }
+    }
+
     #[test]
-    fn pending_roots_debounces_multiple_events() {
-        let mut pending = PendingRoots::new(200);
-        pending.mark("/music/a", 10);
-        pending.mark("/music/a", 20);
-        pending.mark("/music/b", 40);
+    fn pending_paths_debounce_per_path_and_batch_by_root() {
+        let mut pending = PendingPaths::new(200);
+        pending.mark("/music", Path::new("/music/a/01.flac"), 10, present(1));
+        pending.mark("/music", Path::new("/music/a/01.flac"), 20, present(2));
+        pending.mark("/music", Path::new("/music/a/02.flac"), 30, present(2));
+        pending.mark("/live", Path::new("/live/set.flac"), 40, FileState::Missing);
 
         assert_eq!(pending.next_ready_at_ms(), Some(220));
-        assert_eq!(pending.drain_ready(219), Vec::<(String, usize)>::new());
+        let unchanged = |path: &Path| {
+            if path.starts_with("/live") {
+                FileState::Missing
+            } else {
+                present(2)
+            }
+        };
+        assert!(pending.drain_ready(219, unchanged).is_empty());
 
-        let ready = pending.drain_ready(240);
+        let ready = pending.drain_ready(240, unchanged);
         assert_eq!(
             ready,
-            vec![("/music/a".to_string(), 2), ("/music/b".to_string(), 1)]
+            vec![
+                (
+                    "/live".to_string(),
+                    vec![PathBuf::from("/live/set.flac")],
+                    1
+                ),
+                (
+                    "/music".to_string(),
+                    vec![
+                        PathBuf::from("/music/a/01.flac"),
+                        PathBuf::from("/music/a/02.flac")
+                    ],
+                    3
+                ),
+            ]
         );
         assert!(pending.drain_all().is_empty());
     }
 
+    #[test]
+    fn files_still_growing_wait_for_another_round() {
+        let mut pending = PendingPaths::new(200);
+        pending.mark("/music", Path::new("/music/big.flac"), 0, present(10));
+
+        assert!(pending.drain_ready(200, |_| present(50)).is_empty());
+        assert_eq!(pending.next_ready_at_ms(), Some(400));
+        assert!(pending.drain_ready(400, |_| present(90)).is_empty());
+
+        let ready = pending.drain_ready(600, |_| present(90));
+        assert_eq!(
+            ready,
+            vec![(
+                "/music".to_string(),
+                vec![PathBuf::from("/music/big.flac")],
+                1
+            )]
+        );
+    }
+
     #[test]
     fn chooses_most_specific_matching_root() {
         let roots = vec![
@@ -432,21 +741,144 @@ mod tests {
     }
 
     #[test]
-    fn roots_for_event_collects_unique_matches() {
+    fn event_paths_are_matched_to_roots_and_reads_ignored() {
         let roots = vec![WatchedRoot {
             path_string: "/music".to_string(),
             path: PathBuf::from("/music"),
         }];
-        let event = Event {
+        let mut event = Event {
             kind: notify::event::EventKind::Create(notify::event::CreateKind::File),
             paths: vec![
                 PathBuf::from("/music/a.flac"),
-                PathBuf::from("/music/sub/b.flac"),
+                PathBuf::from("/elsewhere/b.flac"),
             ],
             attrs: Default::default(),
         };
 
-        let matched = roots_for_event_paths(&roots, &event);
-        assert_eq!(matched, vec!["/music"]);
+        let changed = changed_paths(&roots, &event);
+        assert_eq!(changed, vec![("/music", Path::new("/music/a.flac"))]);
+
+        event.kind = EventKind::Access(notify::event::AccessKind::Read);
+        assert!(changed_paths(&roots, &event).is_empty());
+    }
+
+    #[test]
+    fn renames_within_roots_move_tracks_in_place() {
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        let track = crate::TrackRecord {
+            id: auric_core::TrackId(uuid::Uuid::new_v4()),
+            path: "/music/old/01.flac".to_string(),
+            title: None,
+            artist: None,
+            album: None,
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+            file_mtime_ms: None,
+        };
+        db.upsert_track(&track).unwrap();
+        let roots = vec![WatchedRoot {
+            path_string: "/music".to_string(),
+            path: PathBuf::from("/music"),
+        }];
+        let (tx, rx) = mpsc::channel();
+        let service = WatchedFolderService::new(WatchOptions::default()).with_rename_sender(tx);
+        let rename = |from: &str, to: &str| Event {
+            kind: EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            paths: vec![PathBuf::from(from), PathBuf::from(to)],
+            attrs: Default::default(),
+        };
+
+        let moved_out = rename("/music/old", "/elsewhere/old");
+        assert_eq!(
+            service.apply_rename(&mut db, &roots, &moved_out).unwrap(),
+            None
+        );
+
+        let renamed = service
+            .apply_rename(&mut db, &roots, &rename("/music/old", "/music/new"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(renamed.moved_tracks, 1);
+        assert_eq!(rx.try_recv().unwrap(), renamed);
+        let row = db.get_track_by_path("/music/new/01.flac").unwrap().unwrap();
+        assert_eq!(row.id, track.id);
+    }
+
+    #[test]
+    fn offline_and_paused_roots_are_detached_and_reconciled_on_return() {
+        let dir = tempfile::tempdir().unwrap();
+        let mount = dir.path().join("mnt");
+        std::fs::create_dir(&mount).unwrap();
+        let mount = mount.canonicalize().unwrap();
+        let path_string = mount.to_string_lossy().to_string();
+        std::fs::write(mount.join("a.flac"), b"x").unwrap();
+
+        let mut db = Database::open_in_memory_for_tests().unwrap();
+        db.upsert_library_root(&crate::LibraryRoot {
+            path: path_string.clone(),
+            watched: true,
+        })
+        .unwrap();
+        let scanner = DirectoryScanner::new(ScanOptions {
+            prune_missing: true,
+            ..ScanOptions::default()
+        });
+        scanner.scan_path(&mut db, &mount).unwrap();
+
+        let (tx, _rx) = mpsc::channel();
+        let mut watcher = build_watcher(tx).unwrap();
+        let root = WatchedRoot {
+            path_string: path_string.clone(),
+            path: mount.clone(),
+        };
+        let mut roots = RootSet::default();
+        assert!(attach_root(&db, &mut watcher, &root).unwrap());
+        roots.attached.push(root);
+        let mut pending = PendingPaths::new(0);
+        let mut rescans = Vec::new();
+        let mut check = |db: &mut Database, roots: &mut RootSet, pending: &mut PendingPaths| {
+            check_roots(&scanner, db, &mut watcher, roots, pending, &mut rescans).unwrap();
+        };
+
+        // Unmounted: the mount point is left empty.
+        std::fs::remove_file(mount.join("a.flac")).unwrap();
+        pending.mark(&path_string, &mount.join("a.flac"), 0, FileState::Missing);
+        check(&mut db, &mut roots, &mut pending);
+        assert!(roots.attached.is_empty());
+        assert_eq!(roots.detached[0].1, RootStatus::Offline);
+        assert!(pending.drain_all().is_empty());
+        let row = db.get_library_root_by_path(&path_string).unwrap().unwrap();
+        assert!(row.offline_since_ms.is_some());
+        assert_eq!(db.count_tracks().unwrap(), 1);
+
+        // Remounted with different contents.
+        std::fs::write(mount.join("b.flac"), b"x").unwrap();
+        check(&mut db, &mut roots, &mut pending);
+        assert_eq!(roots.attached.len(), 1);
+        let row = db.get_library_root_by_path(&path_string).unwrap().unwrap();
+        assert!(row.offline_since_ms.is_none());
+
+        db.set_library_root_paused(&path_string, true).unwrap();
+        check(&mut db, &mut roots, &mut pending);
+        assert_eq!(roots.detached[0].1, RootStatus::Paused);
+        db.set_library_root_paused(&path_string, false).unwrap();
+        check(&mut db, &mut roots, &mut pending);
+        assert_eq!(roots.attached.len(), 1);
+
+        let reasons = rescans
+            .iter()
+            .map(|r| r.reason.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(reasons, ["reattached", "resumed"]);
+        assert_eq!(rescans[0].summary.pruned_missing_tracks, 1);
+        let paths = db
+            .list_tracks(10)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [mount.join("b.flac").to_string_lossy().to_string()]);
     }
 }
@@ -0,0 +1,274 @@
+use crate::db::{Database, DatabaseOptions, DbError};
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+enum WriteJob {
+    Setting {
+        key: String,
+        value: JsonValue,
+    },
+    Position {
+        id: String,
+        position_ms: i64,
+        finished: bool,
+        remember: bool,
+    },
+    TrackPlayed {
+        track_id: String,
+    },
+    Flush(Sender<()>),
+}
+
+impl WriteJob {
+    /// Writes with the same key replace each other; `None` for writes that
+    /// all count.
+    fn key(&self) -> Option<(&'static str, &str)> {
+        match self {
+            WriteJob::Setting { key, .. } => Some(("setting", key)),
+            WriteJob::Position { id, .. } => Some(("position", id)),
+            WriteJob::TrackPlayed { .. } | WriteJob::Flush(_) => None,
+        }
+    }
+}
+
+/// Applies the writes made while music plays (settings, the playback
+/// session, playback positions and play counts) on a dedicated thread with
+/// its own connection, so a render loop never waits on disk or on a scan
+/// holding the write lock. Writes are applied in the order they were queued;
+/// a burst of writes to one setting or one track's position only stores the
+/// last value. Edits the user makes by hand, such as playlist changes, are not
+/// queued: the shell reads them back at once to show the result.
+pub struct DbWriter {
+    tx: Option<Sender<WriteJob>>,
+    handle: Option<JoinHandle<()>>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl DbWriter {
+    /// Opens the writer's connection before returning, so a database that
+    /// cannot be opened is reported here rather than on the first write.
+    pub fn spawn(options: &DatabaseOptions) -> Result<Self, DbError> {
+        let db = Database::open(options)?;
+        let (tx, rx) = mpsc::channel();
+        let last_error = Arc::new(Mutex::new(None));
+        let errors = Arc::clone(&last_error);
+        let handle = std::thread::Builder::new()
+            .name("auric-db-writer".to_string())
+            .spawn(move || run(db, rx, errors))?;
+        Ok(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+            last_error,
+        })
+    }
+
+    /// Queues a write and returns at once.
+    pub fn set_setting_json(&self, key: &str, value: JsonValue) {
+        self.send(WriteJob::Setting {
+            key: key.to_string(),
+            value,
+        });
+    }
+
+    /// Queues [`Database::save_playback_position`].
+    pub fn save_playback_position(
+        &self,
+        id: &str,
+        position_ms: i64,
+        finished: bool,
+        remember: bool,
+    ) {
+        self.send(WriteJob::Position {
+            id: id.to_string(),
+            position_ms,
+            finished,
+            remember,
+        });
+    }
+
+    /// Queues [`Database::record_track_play`].
+    pub fn record_track_play(&self, track_id: &str) {
+        self.send(WriteJob::TrackPlayed {
+            track_id: track_id.to_string(),
+        });
+    }
+
+    /// Waits until every write queued so far is on disk, returning the first
+    /// error since the previous flush.
+    pub fn flush(&self) -> Result<(), DbError> {
+        let (done_tx, done_rx) = mpsc::channel();
+        self.send(WriteJob::Flush(done_tx));
+        let _ = done_rx.recv();
+        match self.last_error.lock().ok().and_then(|mut err| err.take()) {
+            Some(message) => Err(DbError::Writer(message)),
+            None => Ok(()),
+        }
+    }
+
+    fn send(&self, job: WriteJob) {
+        let sent = self
+            .tx
+            .as_ref()
+            .map(|tx| tx.send(job).is_ok())
+            .unwrap_or(false);
+        if !sent {
+            self.record_error("database writer thread has stopped".to_string());
+        }
+    }
+
+    fn record_error(&self, message: String) {
+        if let Ok(mut slot) = self.last_error.lock() {
+            slot.get_or_insert(message);
+        }
+    }
+}
+
+impl Drop for DbWriter {
+    /// Closing the channel lets the thread finish the queued writes and exit.
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run(db: Database, rx: Receiver<WriteJob>, errors: Arc<Mutex<Option<String>>>) {
+    while let Ok(first) = rx.recv() {
+        let mut batch = vec![first];
+        batch.extend(rx.try_iter());
+
+        // Only the last write to each key before a flush matters, so walk
+        // backwards and skip keys already seen since the nearest flush.
+        let mut seen = HashSet::new();
+        let mut keep = vec![true; batch.len()];
+        for (i, job) in batch.iter().enumerate().rev() {
+            match job.key() {
+                Some(key) => keep[i] = seen.insert(key),
+                None if matches!(job, WriteJob::Flush(_)) => seen.clear(),
+                None => {}
+            }
+        }
+
+        let record = |what: String, err: DbError| {
+            tracing::warn!(error = %err, "failed to save {what}");
+            if let Ok(mut slot) = errors.lock() {
+                slot.get_or_insert(format!("failed to save {what}: {err}"));
+            }
+        };
+        for (job, keep) in batch.into_iter().zip(keep) {
+            match job {
+                _ if !keep => {}
+                WriteJob::Setting { key, value } => {
+                    if let Err(err) = db.set_setting_json(&key, &value) {
+                        record(key, err);
+                    }
+                }
+                WriteJob::Position {
+                    id,
+                    position_ms,
+                    finished,
+                    remember,
+                } => {
+                    if let Err(err) =
+                        db.save_playback_position(&id, position_ms, finished, remember)
+                    {
+                        record(format!("the playback position of {id}"), err);
+                    }
+                }
+                WriteJob::TrackPlayed { track_id } => {
+                    if let Err(err) = db.record_track_play(&track_id) {
+                        record(format!("the play of {track_id}"), err);
+                    }
+                }
+                WriteJob::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn queued_writes_land_in_order_after_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = DatabaseOptions {
+            path: dir.path().join("auric.db"),
+            ..DatabaseOptions::default()
+        };
+        let reader = Database::open(&options).unwrap();
+        let writer = DbWriter::spawn(&options).unwrap();
+        for i in 0..50 {
+            writer.set_setting_json("playback.state", json!(i));
+        }
+        writer.set_setting_json("ui.layout", json!("wide"));
+        writer.flush().unwrap();
+        assert_eq!(
+            reader.get_setting_json("playback.state").unwrap(),
+            Some(json!(49))
+        );
+        assert_eq!(
+            reader.get_setting_json("ui.layout").unwrap(),
+            Some(json!("wide"))
+        );
+
+        writer.set_setting_json("playback.state", json!("final"));
+        drop(writer);
+        assert_eq!(
+            reader.get_setting_json("playback.state").unwrap(),
+            Some(json!("final"))
+        );
+    }
+
+    #[test]
+    fn positions_keep_the_last_value_and_every_play_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = DatabaseOptions {
+            path: dir.path().join("auric.db"),
+            ..DatabaseOptions::default()
+        };
+        let reader = Database::open(&options).unwrap();
+        let track = crate::TrackRecord {
+            id: auric_core::TrackId(uuid::Uuid::new_v4()),
+            path: "/music/a.flac".to_string(),
+            title: None,
+            artist: None,
+            album: None,
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+            file_mtime_ms: None,
+        };
+        reader.upsert_track(&track).unwrap();
+        let id = track.id.0.to_string();
+
+        let writer = DbWriter::spawn(&options).unwrap();
+        for position_ms in [1_000, 2_000, 3_000] {
+            writer.save_playback_position(&id, position_ms, false, true);
+        }
+        writer.record_track_play(&id);
+        writer.record_track_play(&id);
+        writer.flush().unwrap();
+        assert_eq!(
+            reader.get_track_resume(&id).unwrap().unwrap().position_ms,
+            3_000
+        );
+        assert_eq!(reader.get_track_stats(&id).unwrap().unwrap().play_count, 2);
+
+        writer.save_playback_position(&id, 4_000, true, true);
+        writer.flush().unwrap();
+        assert_eq!(
+            reader.get_track_resume(&id).unwrap().unwrap().position_ms,
+            0
+        );
+    }
+}
@@ -1,12 +1,30 @@
+use crate::theme::blend;
 use ratatui::prelude::*;
 use ratatui_image::picker::Picker;
 use ratatui_image::protocol::StatefulProtocol;
 use ratatui_image::StatefulImage;
+use std::collections::HashMap;
+
+/// Albums whose thumbnails are kept decoded; the least recently shown go first.
+pub const THUMBNAIL_CAPACITY: usize = 256;
+
+/// Covers fetched per tick, so a long scroll does not stall a frame.
+pub const THUMBNAIL_FETCH_BATCH: usize = 8;
 
 pub struct ArtworkState {
     picker: Option<Picker>,
     pub current_image: Option<StatefulProtocol>,
     current_track_path: String,
+    /// What `current_image` was built from, so a frame showing the same
+    /// track and picture reuses it, including a picture that failed to decode.
+    current_key: Option<ImageKey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImageKey {
+    track_path: String,
+    data_len: Option<usize>,
+    pixel_art: Option<u16>,
 }
 
 impl std::fmt::Debug for ArtworkState {
@@ -14,6 +32,7 @@ impl std::fmt::Debug for ArtworkState {
         f.debug_struct("ArtworkState")
             .field("has_image", &self.current_image.is_some())
             .field("track", &self.current_track_path)
+            .field("key", &self.current_key)
             .finish()
     }
 }
@@ -24,6 +43,7 @@ impl Clone for ArtworkState {
             picker: self.picker.clone(),
             current_image: None,
             current_track_path: String::new(),
+            current_key: None,
         }
     }
 }
@@ -33,17 +53,21 @@ impl ArtworkState {
     /// support. Must be called BEFORE entering alternate screen mode.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        let picker = Picker::from_query_stdio().ok();
+        let picker = Picker::from_query_stdio()
+            .map_err(|err| tracing::info!(error = %err, "terminal graphics query failed; artwork disabled"))
+            .ok();
         Self {
             picker,
             current_image: None,
             current_track_path: String::new(),
+            current_key: None,
         }
     }
 
     /// Update the artwork for the given track. Pass image_data as the raw bytes
     /// (JPEG/PNG) from the database. If pixel_art is true, downscale aggressively
-    /// for a chunky look.
+    /// for a chunky look. The image is rebuilt only when the track, its
+    /// picture or the pixel-art setting changes.
     pub fn update(
         &mut self,
         track_path: &str,
@@ -51,10 +75,16 @@ impl ArtworkState {
         pixel_art: bool,
         pixel_cell_size: u16,
     ) {
-        if track_path == self.current_track_path && self.current_image.is_some() {
+        let key = ImageKey {
+            track_path: track_path.to_string(),
+            data_len: image_data.map(<[u8]>::len),
+            pixel_art: pixel_art.then_some(pixel_cell_size),
+        };
+        if self.current_key.as_ref() == Some(&key) {
             return;
         }
         self.current_track_path = track_path.to_string();
+        self.current_key = Some(key);
 
         let Some(picker) = &self.picker else {
             self.current_image = None;
@@ -62,7 +92,9 @@ impl ArtworkState {
         };
 
         self.current_image = image_data.and_then(|data| {
-            let mut img = image::load_from_memory(data).ok()?;
+            let mut img = image::load_from_memory(data)
+                .map_err(|err| tracing::warn!(track = track_path, error = %err, "failed to decode artwork"))
+                .ok()?;
             if pixel_art {
                 let cell = pixel_cell_size.max(1) as u32;
                 let target = cell * 8;
@@ -86,5 +118,286 @@ impl ArtworkState {
     pub fn clear(&mut self) {
         self.current_image = None;
         self.current_track_path.clear();
+        self.current_key = None;
+    }
+}
+
+/// An album cover shrunk to a square two cells wide, drawn with half blocks:
+/// each cell shows its top pixel as the `▀` and its bottom one behind it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// `[row][column]`.
+    pixels: [[Color; 2]; 2],
+}
+
+impl Thumbnail {
+    pub const WIDTH: u16 = 2;
+
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let img = image::load_from_memory(data)
+            .map_err(|err| tracing::debug!(error = %err, "failed to decode album thumbnail"))
+            .ok()?;
+        let small = img.thumbnail_exact(2, 2).to_rgb8();
+        let pixel = |x, y| {
+            let [r, g, b] = small.get_pixel(x, y).0;
+            Color::Rgb(r, g, b)
+        };
+        Some(Self {
+            pixels: [[pixel(0, 0), pixel(1, 0)], [pixel(0, 1), pixel(1, 1)]],
+        })
+    }
+
+    pub fn spans(&self) -> Vec<Span<'static>> {
+        (0..2)
+            .map(|x| {
+                Span::styled(
+                    "▀",
+                    Style::default().fg(self.pixels[0][x]).bg(self.pixels[1][x]),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Stand-in art for an album without a cover: a diagonal gradient and the
+/// album's initials, both derived from its name so the same album always
+/// looks the same.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    from: Color,
+    to: Color,
+    initials: String,
+}
+
+impl Placeholder {
+    pub fn new(artist: &str, album: &str) -> Self {
+        let hash = fnv1a(
+            format!(
+                "{}\u{1f}{}",
+                artist.trim().to_lowercase(),
+                album.trim().to_lowercase()
+            )
+            .as_bytes(),
+        );
+        let hue = (hash % 360) as f32;
+        let spread = 30.0 + (hash >> 16) as f32 % 60.0;
+        let named = if album.trim().is_empty() {
+            artist
+        } else {
+            album
+        };
+        Self {
+            from: hsl(hue, 0.55, 0.42),
+            to: hsl((hue + spread) % 360.0, 0.6, 0.28),
+            initials: initials(named),
+        }
+    }
+
+    pub fn initials(&self) -> &str {
+        &self.initials
+    }
+
+    /// The gradient alone, small enough for a list row.
+    pub fn thumbnail(&self) -> Thumbnail {
+        let mid = blend(self.from, self.to, 0.5);
+        Thumbnail {
+            pixels: [[self.from, mid], [mid, self.to]],
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let span = f32::from((area.width - 1).max(1)) + f32::from((area.height - 1).max(1));
+        for y in 0..area.height {
+            for x in 0..area.width {
+                let t = (f32::from(x) + f32::from(y)) / span;
+                if let Some(cell) = buf.cell_mut((area.x + x, area.y + y)) {
+                    cell.set_symbol(" ").set_bg(blend(self.from, self.to, t));
+                }
+            }
+        }
+        let width = crate::text::display_width(&self.initials) as u16;
+        if width > area.width {
+            return;
+        }
+        let x = area.x + (area.width - width) / 2;
+        let y = area.y + area.height / 2;
+        let style = Style::default()
+            .fg(Color::Rgb(240, 240, 240))
+            .add_modifier(Modifier::BOLD);
+        buf.set_string(x, y, &self.initials, style);
+    }
+}
+
+/// Up to two initials from the first words with a letter or digit; `♪`
+/// when there are none.
+fn initials(name: &str) -> String {
+    let initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+    if initials.is_empty() {
+        "♪".to_string()
+    } else {
+        initials
+    }
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is the same in every build.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::Rgb(channel(r), channel(g), channel(b))
+}
+
+/// Decoded album thumbnails for list rows. Albums asked for and not yet
+/// known are collected in [`Self::take_wanted`] for the host to fetch.
+#[derive(Debug, Clone, Default)]
+pub struct Thumbnails {
+    /// Album -> (last use, thumbnail or `None` without a cover).
+    entries: HashMap<String, (u64, Option<Thumbnail>)>,
+    uses: u64,
+    wanted: Vec<String>,
+}
+
+impl Thumbnails {
+    /// The thumbnail for `album`: `None` while it is being fetched,
+    /// `Some(None)` when the album has no cover.
+    pub fn get(&mut self, album: &str) -> Option<Option<Thumbnail>> {
+        self.uses += 1;
+        match self.entries.get_mut(album) {
+            Some((used, thumbnail)) => {
+                *used = self.uses;
+                Some(*thumbnail)
+            }
+            None => {
+                if !self.wanted.iter().any(|a| a == album) {
+                    self.wanted.push(album.to_string());
+                }
+                None
+            }
+        }
+    }
+
+    /// Up to `limit` albums to fetch covers for, oldest request first.
+    pub fn take_wanted(&mut self, limit: usize) -> Vec<String> {
+        let take = limit.min(self.wanted.len());
+        self.wanted.drain(..take).collect()
+    }
+
+    pub fn insert(&mut self, album: String, data: Option<&[u8]>) {
+        if self.entries.len() >= THUMBNAIL_CAPACITY && !self.entries.contains_key(&album) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(album, _)| album.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.uses += 1;
+        self.entries
+            .insert(album, (self.uses, data.and_then(Thumbnail::decode)));
+    }
+
+    /// Forgets every thumbnail, e.g. after a rescan may have changed covers.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.wanted.clear();
+    }
+
+    pub fn contains(&self, album: &str) -> bool {
+        self.entries.contains_key(album)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(color: [u8; 3]) -> Vec<u8> {
+        let img = image::RgbImage::from_pixel(8, 8, image::Rgb(color));
+        let mut out = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut out, image::ImageFormat::Png).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn placeholders_are_stable_per_album_and_show_initials() {
+        let abbey = Placeholder::new("The Beatles", "Abbey Road");
+        assert_eq!(abbey, Placeholder::new("the beatles ", "ABBEY ROAD"));
+        assert_ne!(
+            abbey.thumbnail(),
+            Placeholder::new("The Beatles", "Revolver").thumbnail()
+        );
+        assert_eq!(abbey.initials(), "AR");
+        assert_eq!(Placeholder::new("Björk", "").initials(), "B");
+        assert_eq!(Placeholder::new("", "...").initials(), "♪");
+
+        let area = Rect::new(0, 0, 8, 4);
+        let mut buf = Buffer::empty(area);
+        abbey.render(area, &mut buf);
+        assert_eq!(buf[(3, 2)].symbol(), "A");
+        assert_eq!(buf[(4, 2)].symbol(), "R");
+        assert_eq!(buf[(0, 0)].bg, abbey.from);
+        assert_eq!(buf[(7, 3)].bg, abbey.to);
+    }
+
+    #[test]
+    fn thumbnails_are_fetched_once_and_the_least_recent_are_dropped() {
+        let mut thumbs = Thumbnails::default();
+        assert_eq!(thumbs.get("Red"), None);
+        assert_eq!(thumbs.get("Red"), None);
+        assert_eq!(thumbs.get("Bare"), None);
+        assert_eq!(thumbs.take_wanted(1), ["Red"]);
+        assert_eq!(thumbs.take_wanted(8), ["Bare"]);
+
+        thumbs.insert("Red".into(), Some(&png([255, 0, 0])));
+        thumbs.insert("Bare".into(), None);
+        let red = thumbs.get("Red").unwrap().unwrap();
+        assert_eq!(red.spans()[0].style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(red.spans()[1].style.bg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(thumbs.get("Bare"), Some(None));
+        assert!(thumbs.take_wanted(8).is_empty());
+
+        thumbs.get("Red");
+        for i in 0..THUMBNAIL_CAPACITY {
+            thumbs.insert(format!("album {i}"), None);
+        }
+        assert_eq!(thumbs.len(), THUMBNAIL_CAPACITY);
+        // "Bare" was used longest ago and went first, then "Red".
+        thumbs.get("Bare");
+        thumbs.get("Red");
+        assert_eq!(thumbs.take_wanted(8), ["Bare", "Red"]);
     }
 }
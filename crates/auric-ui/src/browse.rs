@@ -3,6 +3,8 @@ pub enum BrowseMode {
     Songs,
     Artists,
     Albums,
+    /// The last tracks played; picking one plays it instead of filtering.
+    Recent,
 }
 
 impl BrowseMode {
@@ -11,11 +13,12 @@ impl BrowseMode {
             Self::Songs => "Songs",
             Self::Artists => "Artists",
             Self::Albums => "Albums",
+            Self::Recent => "Recent",
         }
     }
 
     pub fn all() -> &'static [Self] {
-        &[Self::Songs, Self::Artists, Self::Albums]
+        &[Self::Songs, Self::Artists, Self::Albums, Self::Recent]
     }
 }
 
@@ -74,8 +77,7 @@ impl BrowseState {
             return;
         }
         let new_idx = if delta < 0 {
-            self.mode_index
-                .saturating_sub(delta.unsigned_abs())
+            self.mode_index.saturating_sub(delta.unsigned_abs())
         } else {
             self.mode_index
                 .saturating_add(delta as usize)
@@ -91,8 +93,7 @@ impl BrowseState {
             return;
         }
         let new_idx = if delta < 0 {
-            self.item_index
-                .saturating_sub(delta.unsigned_abs())
+            self.item_index.saturating_sub(delta.unsigned_abs())
         } else {
             self.item_index
                 .saturating_add(delta as usize)
@@ -0,0 +1,115 @@
+use std::env;
+use std::io::Write;
+
+/// System clipboard access with a fallback for sessions that have none.
+///
+/// The native clipboard is opened lazily and kept alive for the lifetime of
+/// the shell, since on X11 the copied text disappears once its owner is
+/// dropped. When no native clipboard is available (typically over SSH),
+/// copies are sent to the terminal as an OSC 52 sequence instead.
+pub struct Clipboard {
+    native: Option<arboard::Clipboard>,
+    native_failed: bool,
+}
+
+/// How a copy request reached the user's clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMethod {
+    Native,
+    Terminal,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self {
+            native: None,
+            native_failed: false,
+        }
+    }
+
+    pub fn copy(&mut self, text: &str) -> Result<CopyMethod, String> {
+        if let Some(native) = self.native() {
+            if native.set_text(text.to_string()).is_ok() {
+                return Ok(CopyMethod::Native);
+            }
+        }
+        if is_remote_session() {
+            write_osc52(text).map_err(|e| format!("terminal clipboard write failed: {e}"))?;
+            return Ok(CopyMethod::Terminal);
+        }
+        Err("no clipboard available".to_string())
+    }
+
+    pub fn paste(&mut self) -> Result<String, String> {
+        let native = self.native().ok_or_else(|| {
+            "no clipboard available; use your terminal's paste instead".to_string()
+        })?;
+        native
+            .get_text()
+            .map_err(|e| format!("clipboard read failed: {e}"))
+    }
+
+    fn native(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.native.is_none() && !self.native_failed {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.native = Some(clipboard),
+                Err(_) => self.native_failed = true,
+            }
+        }
+        self.native.as_mut()
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_remote_session() -> bool {
+    env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some()
+}
+
+fn write_osc52(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))?;
+    stdout.flush()
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_matches_reference_vectors() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(
+            base64_encode("Björk – Jóga".as_bytes()),
+            "QmrDtnJrIOKAkyBKw7NnYQ=="
+        );
+    }
+}
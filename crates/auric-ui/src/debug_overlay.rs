@@ -0,0 +1,155 @@
+use crate::shell::ShellTrackItem;
+use std::mem::size_of;
+use std::time::Duration;
+
+/// Last, smoothed and worst duration of a step in the run loop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timing {
+    pub last: Duration,
+    pub avg: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    pub fn record(&mut self, sample: Duration) {
+        self.last = sample;
+        // Exponential moving average over roughly the last eight samples.
+        self.avg = if self.avg.is_zero() {
+            sample
+        } else {
+            self.avg - self.avg / 8 + sample / 8
+        };
+        self.max = self.max.max(sample);
+    }
+}
+
+/// Run-loop measurements shown by the F12 debug overlay.
+#[derive(Debug, Clone, Default)]
+pub struct DebugStats {
+    pub render: Timing,
+    pub tick: Timing,
+    pub player_poll: Timing,
+    pub refresh: Timing,
+    pub frames: u64,
+    /// The wait between frames the scheduler chose last.
+    pub frame_interval: Duration,
+    /// Messages drained from each queue on the last tick.
+    pub player_events: usize,
+    pub scan_messages: usize,
+    pub input_events: usize,
+}
+
+/// Rough heap and inline size of the track list in bytes.
+pub fn track_list_bytes(tracks: &[ShellTrackItem]) -> usize {
+    tracks
+        .iter()
+        .map(|track| {
+            size_of::<ShellTrackItem>()
+                + track.id.capacity()
+                + track.title.capacity()
+                + track.artist.capacity()
+                + track.album.capacity()
+                + track.path.capacity()
+        })
+        .sum()
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros >= 10_000 {
+        format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+    } else {
+        format!("{micros}µs")
+    }
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    const KIB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= KIB * KIB {
+        format!("{:.1} MiB", bytes / (KIB * KIB))
+    } else {
+        format!("{:.1} KiB", bytes / KIB)
+    }
+}
+
+fn timing_row(label: &str, timing: &Timing) -> (String, String) {
+    (
+        label.to_string(),
+        format!(
+            "{} avg {} max {}",
+            format_duration(timing.last),
+            format_duration(timing.avg),
+            format_duration(timing.max)
+        ),
+    )
+}
+
+/// Label/value rows for the overlay.
+pub fn debug_rows(
+    stats: &DebugStats,
+    tracks: &[ShellTrackItem],
+    fft: Duration,
+    db_timings: &[(String, Duration)],
+) -> Vec<(String, String)> {
+    let mut rows = vec![
+        timing_row("render", &stats.render),
+        timing_row("tick", &stats.tick),
+        timing_row("player poll", &stats.player_poll),
+        ("fft".to_string(), format_duration(fft)),
+        timing_row("refresh", &stats.refresh),
+        (
+            "queues".to_string(),
+            format!(
+                "player {}  scan {}  input {}",
+                stats.player_events, stats.scan_messages, stats.input_events
+            ),
+        ),
+        (
+            "tracks".to_string(),
+            format!(
+                "{} ≈ {}",
+                tracks.len(),
+                format_bytes(track_list_bytes(tracks))
+            ),
+        ),
+        (
+            "frames".to_string(),
+            match stats.frame_interval.as_secs_f64() {
+                0.0 => stats.frames.to_string(),
+                secs => format!("{} at {:.0} fps", stats.frames, 1.0 / secs),
+            },
+        ),
+    ];
+    rows.extend(
+        db_timings
+            .iter()
+            .map(|(query, elapsed)| (format!("db {query}"), format_duration(*elapsed))),
+    );
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timing_tracks_last_average_and_max() {
+        let mut timing = Timing::default();
+        timing.record(Duration::from_millis(8));
+        assert_eq!(timing.avg, Duration::from_millis(8));
+        timing.record(Duration::from_millis(16));
+        assert_eq!(timing.last, Duration::from_millis(16));
+        assert_eq!(timing.avg, Duration::from_millis(9));
+        timing.record(Duration::from_millis(1));
+        assert_eq!(timing.max, Duration::from_millis(16));
+    }
+
+    #[test]
+    fn durations_and_sizes_are_readable() {
+        assert_eq!(format_duration(Duration::from_micros(850)), "850µs");
+        assert_eq!(format_duration(Duration::from_micros(12_340)), "12.3ms");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entries kept before the oldest are dropped.
+const CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub time: SystemTime,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// Wall-clock time as `HH:MM:SS` UTC, matching the log file timestamps.
+    pub fn time_label(&self) -> String {
+        let secs = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+            % 86_400;
+        format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: VecDeque<LogEntry>,
+    /// Total pushed since start, including entries that have been dropped.
+    pushed: u64,
+}
+
+/// Recent warnings and errors shown in the log dialog. Clones share the same
+/// buffer, so the app's log subscriber and the shell can both hold one.
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, level: LogLevel, message: impl Into<String>) {
+        self.push_entry(LogEntry {
+            time: SystemTime::now(),
+            level,
+            message: message.into(),
+        });
+    }
+
+    pub fn push_entry(&self, entry: LogEntry) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        if inner.entries.len() == CAPACITY {
+            inner.entries.pop_front();
+        }
+        inner.entries.push_back(entry);
+        inner.pushed += 1;
+    }
+
+    /// Oldest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.inner
+            .lock()
+            .map(|inner| inner.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Counter that only grows; compare with a saved value to count new entries.
+    pub fn pushed(&self) -> u64 {
+        self.inner.lock().map(|inner| inner.pushed).unwrap_or(0)
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn oldest_entries_are_dropped_at_capacity() {
+        let log = EventLog::new();
+        let shared = log.clone();
+        for i in 0..CAPACITY + 3 {
+            shared.push(LogLevel::Warn, format!("entry {i}"));
+        }
+        let entries = log.entries();
+        assert_eq!(entries.len(), CAPACITY);
+        assert_eq!(entries[0].message, "entry 3");
+        assert_eq!(log.pushed(), CAPACITY as u64 + 3);
+
+        log.clear();
+        assert!(log.entries().is_empty());
+        assert_eq!(log.pushed(), CAPACITY as u64 + 3);
+    }
+
+    #[test]
+    fn time_label_is_utc_clock_time() {
+        let entry = LogEntry {
+            time: UNIX_EPOCH + Duration::from_secs(2 * 86_400 + 13 * 3600 + 5 * 60 + 9),
+            level: LogLevel::Error,
+            message: String::new(),
+        };
+        assert_eq!(entry.time_label(), "13:05:09");
+    }
+}
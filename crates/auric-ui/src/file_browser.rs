@@ -1,13 +1,85 @@
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+/// Directory entries a folder's audio count looks at before giving up and
+/// showing a lower bound, so one huge folder cannot stall the counts.
+const COUNT_BUDGET: usize = 20_000;
+
+/// Bookmark slots, jumped to with the number keys.
+pub const BOOKMARK_SLOTS: usize = 9;
+/// Recently added folders kept.
+const RECENT_LIMIT: usize = 10;
+
+/// Folders the browser can jump to: numbered bookmarks and the folders most
+/// recently added to the library, newest first. Persisted to the settings
+/// table whenever it changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserPlaces {
+    pub bookmarks: Vec<String>,
+    pub recent: Vec<String>,
+}
+
+impl BrowserPlaces {
+    /// Bookmarks `dir`, or removes its bookmark. Returns the slot it now
+    /// has (1-based), `Some(0)` when it was removed, or `None` when every
+    /// slot is taken.
+    pub fn toggle_bookmark(&mut self, dir: &str) -> Option<usize> {
+        if let Some(index) = self.bookmarks.iter().position(|b| b == dir) {
+            self.bookmarks.remove(index);
+            return Some(0);
+        }
+        if self.bookmarks.len() >= BOOKMARK_SLOTS {
+            return None;
+        }
+        self.bookmarks.push(dir.to_string());
+        Some(self.bookmarks.len())
+    }
+
+    /// Puts `dir` at the front of the recent folders.
+    pub fn remember(&mut self, dir: &str) {
+        self.recent.retain(|r| r != dir);
+        self.recent.insert(0, dir.to_string());
+        self.recent.truncate(RECENT_LIMIT);
+    }
+
+    /// The recent folder after `current`, or the newest when `current` is
+    /// not one of them.
+    pub fn next_recent(&self, current: &Path) -> Option<&str> {
+        let next = self
+            .recent
+            .iter()
+            .position(|r| Path::new(r) == current)
+            .map_or(0, |index| (index + 1) % self.recent.len());
+        self.recent.get(next).map(String::as_str)
+    }
+
+    /// Where the browser opens: next to the folder added last, so sibling
+    /// folders are one step away, or `None` to use the home folder.
+    pub fn start_dir(&self) -> Option<PathBuf> {
+        self.recent
+            .iter()
+            .filter_map(|r| Path::new(r).parent())
+            .find(|parent| parent.is_dir())
+            .map(Path::to_path_buf)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct FileBrowser {
     current_dir: PathBuf,
+    /// Every folder in `current_dir`; `entries` is the part matching `filter`.
+    listing: Vec<DirEntry>,
     entries: Vec<DirEntry>,
+    filter: String,
     pub selected: usize,
     pub scroll_offset: usize,
-    pub path_input: String,
+    pub path_input: crate::text_input::TextInput,
     pub input_focused: bool,
+    /// Whether folders starting with `.` are listed.
+    show_hidden: bool,
+    counts: CountFeed,
 }
 
 #[derive(Debug, Clone)]
@@ -15,17 +87,41 @@ pub struct DirEntry {
     pub name: String,
     pub path: PathBuf,
     pub is_dir: bool,
+    /// Supported audio files anywhere below, once counted.
+    pub audio_files: Option<AudioCount>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioCount {
+    pub files: usize,
+    /// False when the count stopped early and is only a lower bound.
+    pub complete: bool,
+}
+
+/// Audio counts arriving from the thread that walks the listed folders, by
+/// entry index. A cloned browser receives none; its counts stay unknown.
+#[derive(Debug, Default)]
+struct CountFeed(Option<Receiver<(usize, AudioCount)>>);
+
+impl Clone for CountFeed {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
 }
 
 impl FileBrowser {
     pub fn new(start_dir: &Path) -> Self {
         let mut browser = Self {
             current_dir: start_dir.to_path_buf(),
+            listing: Vec::new(),
             entries: Vec::new(),
+            filter: String::new(),
             selected: 0,
             scroll_offset: 0,
-            path_input: start_dir.display().to_string(),
+            path_input: crate::text_input::TextInput::with_value(start_dir.display().to_string()),
             input_focused: false,
+            show_hidden: false,
+            counts: CountFeed::default(),
         };
         browser.refresh_entries();
         browser
@@ -50,32 +146,134 @@ impl FileBrowser {
     }
 
     pub fn refresh_entries(&mut self) {
+        self.listing.clear();
         self.entries.clear();
-        if let Ok(read_dir) = std::fs::read_dir(&self.current_dir) {
-            let mut dirs: Vec<DirEntry> = read_dir
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    e.file_type().map(|ft| ft.is_dir()).unwrap_or(false)
-                        && !e.file_name().to_string_lossy().starts_with('.')
-                })
-                .map(|e| DirEntry {
-                    name: e.file_name().to_string_lossy().into_owned(),
-                    path: e.path(),
-                    is_dir: true,
-                })
-                .collect();
-            dirs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-            self.entries = dirs;
+        match std::fs::read_dir(&self.current_dir) {
+            Ok(read_dir) => {
+                let mut dirs: Vec<DirEntry> = read_dir
+                    .filter_map(|e| e.ok())
+                    .filter(|e| {
+                        e.file_type().map(|ft| ft.is_dir()).unwrap_or(false)
+                            && (self.show_hidden
+                                || !e.file_name().to_string_lossy().starts_with('.'))
+                    })
+                    .map(|e| DirEntry {
+                        name: e.file_name().to_string_lossy().into_owned(),
+                        path: e.path(),
+                        is_dir: true,
+                        audio_files: None,
+                    })
+                    .collect();
+                dirs.sort_by_key(|a| a.name.to_lowercase());
+                self.listing = dirs;
+                self.start_counting();
+            }
+            Err(err) => {
+                tracing::warn!(dir = %self.current_dir.display(), error = %err, "failed to list directory");
+            }
+        }
+        self.apply_filter();
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// Narrows the listing to folders whose name contains `filter`, ignoring
+    /// case. The selection stays on the same folder while it still matches.
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_string();
+        let selected = self.selected_entry().map(|e| e.name.clone());
+        self.apply_filter();
+        if let Some(index) =
+            selected.and_then(|name| self.entries.iter().position(|e| e.name == name))
+        {
+            self.selected = index;
         }
+    }
+
+    fn apply_filter(&mut self) {
+        let needle = self.filter.to_lowercase();
+        self.entries = self
+            .listing
+            .iter()
+            .filter(|e| e.name.to_lowercase().contains(&needle))
+            .cloned()
+            .collect();
         self.selected = 0;
         self.scroll_offset = 0;
     }
 
+    pub fn show_hidden(&self) -> bool {
+        self.show_hidden
+    }
+
+    /// Lists or hides folders starting with `.`, keeping the selection on
+    /// the same folder when it is still listed.
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        let selected = self.selected_entry().map(|e| e.name.clone());
+        self.refresh_entries();
+        if let Some(index) =
+            selected.and_then(|name| self.entries.iter().position(|e| e.name == name))
+        {
+            self.selected = index;
+        }
+    }
+
+    /// Counts the audio in each listed folder on a background thread; the
+    /// results land through [`Self::poll_counts`]. A thread whose listing
+    /// was replaced stops at its next result.
+    fn start_counting(&mut self) {
+        if self.listing.is_empty() {
+            self.counts = CountFeed::default();
+            return;
+        }
+        let dirs: Vec<PathBuf> = self.listing.iter().map(|e| e.path.clone()).collect();
+        let (tx, rx) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("auric-browse-count".into())
+            .spawn(move || {
+                for (index, dir) in dirs.iter().enumerate() {
+                    if tx
+                        .send((index, count_audio_files(dir, COUNT_BUDGET)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        self.counts = match spawned {
+            Ok(_) => CountFeed(Some(rx)),
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to start counting audio files");
+                CountFeed::default()
+            }
+        };
+    }
+
+    /// Takes in the audio counts finished since the last call.
+    pub fn poll_counts(&mut self) {
+        let Some(rx) = &self.counts.0 else {
+            return;
+        };
+        for (index, count) in rx.try_iter() {
+            let Some(listed) = self.listing.get_mut(index) else {
+                continue;
+            };
+            listed.audio_files = Some(count);
+            if let Some(entry) = self.entries.iter_mut().find(|e| e.path == listed.path) {
+                entry.audio_files = Some(count);
+            }
+        }
+    }
+
     pub fn enter_selected(&mut self) {
         if let Some(entry) = self.entries.get(self.selected) {
             if entry.is_dir {
                 self.current_dir = entry.path.clone();
-                self.path_input = self.current_dir.display().to_string();
+                self.path_input.set(self.current_dir.display().to_string());
+                self.filter.clear();
                 self.refresh_entries();
             }
         }
@@ -88,7 +286,8 @@ impl FileBrowser {
                 .file_name()
                 .map(|n| n.to_string_lossy().into_owned());
             self.current_dir = parent.to_path_buf();
-            self.path_input = self.current_dir.display().to_string();
+            self.path_input.set(self.current_dir.display().to_string());
+            self.filter.clear();
             self.refresh_entries();
             if let Some(name) = old_name {
                 if let Some(idx) = self.entries.iter().position(|e| e.name == name) {
@@ -111,7 +310,8 @@ impl FileBrowser {
 
         if resolved.is_dir() {
             self.current_dir = resolved;
-            self.path_input = self.current_dir.display().to_string();
+            self.path_input.set(self.current_dir.display().to_string());
+            self.filter.clear();
             self.refresh_entries();
         }
     }
@@ -124,29 +324,95 @@ impl FileBrowser {
         self.selected = (self.selected as isize + delta).clamp(0, max) as usize;
     }
 
+    /// Starts typing a path, from the current folder.
+    pub fn focus_path_input(&mut self) {
+        let mut dir = self.current_dir.display().to_string();
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+        self.path_input.set(dir);
+        self.input_focused = true;
+    }
+
     pub fn sync_path_input_to_selected(&mut self) {
-        self.path_input = self.selected_path().display().to_string();
+        let selected = self.selected_path().display().to_string();
+        self.path_input.set(selected);
     }
 
-    pub fn apply_path_input(&mut self) {
-        let expanded = if self.path_input.starts_with('~') {
-            if let Some(home) = home_dir() {
-                home.join(self.path_input.strip_prefix("~/").unwrap_or(
-                    self.path_input.strip_prefix('~').unwrap_or(&self.path_input),
-                ))
-                .display()
-                .to_string()
-            } else {
-                self.path_input.clone()
-            }
+    /// Opens the folder typed into the path input. Returns `false`, leaving
+    /// the browser where it was, when it names no folder.
+    pub fn apply_path_input(&mut self) -> bool {
+        let path = self.resolve_input(self.path_input.as_str());
+        if !path.is_dir() {
+            return false;
+        }
+        self.current_dir = path;
+        self.path_input.set(self.current_dir.display().to_string());
+        self.filter.clear();
+        self.refresh_entries();
+        true
+    }
+
+    /// Completes the last part of the typed path to the subfolders it could
+    /// name: fully, with a trailing `/`, when only one fits, otherwise as far
+    /// as they all agree. Returns the names that fit.
+    pub fn complete_path_input(&mut self) -> Vec<String> {
+        let input = self.path_input.as_str().to_string();
+        if input == "~" {
+            self.path_input.set("~/");
+            return Vec::new();
+        }
+        let (dir_part, prefix) = match input.rfind('/') {
+            Some(slash) => input.split_at(slash + 1),
+            None => ("", input.as_str()),
+        };
+        let dir = if dir_part.is_empty() {
+            self.current_dir.clone()
         } else {
-            self.path_input.clone()
+            self.resolve_input(dir_part)
         };
-        let path = PathBuf::from(&expanded);
-        if path.is_dir() {
-            self.current_dir = path;
-            self.refresh_entries();
+        let mut names: Vec<String> = match std::fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|name| {
+                    name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.'))
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        names.sort();
+        match names.as_slice() {
+            [] => {}
+            [only] => self.path_input.set(format!("{dir_part}{only}/")),
+            [first, rest @ ..] => {
+                let mut common = first.as_str();
+                for name in rest {
+                    let agreed = common
+                        .char_indices()
+                        .zip(name.chars())
+                        .find(|((_, a), b)| a != b)
+                        .map_or(common.len().min(name.len()), |((i, _), _)| i);
+                    common = &common[..agreed];
+                }
+                if common.len() > prefix.len() {
+                    self.path_input.set(format!("{dir_part}{common}"));
+                }
+            }
         }
+        names
+    }
+
+    /// A typed path with `~` expanded, relative to the current folder.
+    fn resolve_input(&self, input: &str) -> PathBuf {
+        let expanded = match (input.strip_prefix('~'), home_dir()) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+                home.join(rest.trim_start_matches('/'))
+            }
+            _ => PathBuf::from(input),
+        };
+        self.current_dir.join(expanded)
     }
 }
 
@@ -154,6 +420,37 @@ fn home_dir() -> Option<PathBuf> {
     std::env::var_os("HOME").map(PathBuf::from)
 }
 
+/// Supported audio files anywhere under `dir`, looking at no more than
+/// `budget` directory entries.
+fn count_audio_files(dir: &Path, budget: usize) -> AudioCount {
+    let mut pending = vec![dir.to_path_buf()];
+    let mut seen = 0usize;
+    let mut files = 0usize;
+    while let Some(dir) = pending.pop() {
+        let Ok(read_dir) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.filter_map(|e| e.ok()) {
+            seen += 1;
+            if seen > budget {
+                return AudioCount {
+                    files,
+                    complete: false,
+                };
+            }
+            match entry.file_type() {
+                Ok(ft) if ft.is_dir() => pending.push(entry.path()),
+                Ok(_) if auric_core::is_supported_audio_file(&entry.path()) => files += 1,
+                _ => {}
+            }
+        }
+    }
+    AudioCount {
+        files,
+        complete: true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,11 +475,35 @@ mod tests {
         assert!(!names.contains(&".hidden"));
     }
 
+    #[test]
+    fn filter_narrows_the_listing_until_the_folder_changes() {
+        let tmp = make_test_tree();
+        let mut browser = FileBrowser::new(tmp.path());
+        browser.set_filter("SIC");
+        let names: Vec<&str> = browser.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Music"]);
+
+        browser.set_filter("");
+        assert_eq!(browser.entries().len(), 2);
+        browser.set_filter("xyz");
+        assert!(browser.entries().is_empty());
+        assert_eq!(browser.selected_path(), tmp.path());
+
+        browser.set_filter("mu");
+        browser.enter_selected();
+        assert_eq!(browser.filter(), "");
+        assert_eq!(browser.entries().len(), 2);
+    }
+
     #[test]
     fn enter_descends_into_directory() {
         let tmp = make_test_tree();
         let mut browser = FileBrowser::new(tmp.path());
-        let music_idx = browser.entries().iter().position(|e| e.name == "Music").unwrap();
+        let music_idx = browser
+            .entries()
+            .iter()
+            .position(|e| e.name == "Music")
+            .unwrap();
         browser.selected = music_idx;
         browser.enter_selected();
         assert_eq!(browser.current_dir(), tmp.path().join("Music"));
@@ -195,7 +516,11 @@ mod tests {
     fn go_up_returns_to_parent_and_reselects() {
         let tmp = make_test_tree();
         let mut browser = FileBrowser::new(tmp.path());
-        let music_idx = browser.entries().iter().position(|e| e.name == "Music").unwrap();
+        let music_idx = browser
+            .entries()
+            .iter()
+            .position(|e| e.name == "Music")
+            .unwrap();
         browser.selected = music_idx;
         browser.enter_selected();
         browser.go_up();
@@ -213,6 +538,118 @@ mod tests {
         assert_eq!(browser.selected, browser.entries().len().saturating_sub(1));
     }
 
+    #[test]
+    fn places_keep_bookmark_slots_and_recent_folders() {
+        let tmp = make_test_tree();
+        let music = tmp.path().join("Music");
+        let mut places = BrowserPlaces::default();
+        assert_eq!(places.start_dir(), None);
+        for n in 0..BOOKMARK_SLOTS {
+            assert_eq!(places.toggle_bookmark(&format!("/b{n}")), Some(n + 1));
+        }
+        assert_eq!(places.toggle_bookmark("/full"), None);
+        assert_eq!(places.toggle_bookmark("/b0"), Some(0));
+        assert_eq!(places.bookmarks[0], "/b1");
+
+        places.remember(&music.join("Albums").display().to_string());
+        places.remember("/gone");
+        places.remember(&music.join("Albums").display().to_string());
+        assert_eq!(places.recent.len(), 2);
+        assert_eq!(places.start_dir(), Some(music.clone()));
+        assert_eq!(
+            places.next_recent(tmp.path()),
+            places.recent.first().map(String::as_str)
+        );
+        assert_eq!(places.next_recent(&music.join("Albums")), Some("/gone"));
+        assert_eq!(
+            places.next_recent(Path::new("/gone")),
+            places.recent.first().map(String::as_str)
+        );
+    }
+
+    #[test]
+    fn typed_paths_complete_to_folders() {
+        let tmp = make_test_tree();
+        std::fs::create_dir_all(tmp.path().join("Music/Alb")).unwrap();
+        let mut browser = FileBrowser::new(tmp.path());
+        let root = tmp.path().display().to_string();
+
+        browser.path_input.set(format!("{root}/Mu"));
+        assert_eq!(browser.complete_path_input(), ["Music"]);
+        assert_eq!(browser.path_input.as_str(), format!("{root}/Music/"));
+        assert_eq!(
+            browser.complete_path_input(),
+            ["Alb", "Albums", "Playlists"]
+        );
+        browser.path_input.set(format!("{root}/Music/A"));
+        assert_eq!(browser.complete_path_input().len(), 2);
+        assert_eq!(browser.path_input.as_str(), format!("{root}/Music/Alb"));
+        browser.path_input.set(format!("{root}/."));
+        assert_eq!(browser.complete_path_input(), [".hidden"]);
+        browser.path_input.set("Doc");
+        assert_eq!(browser.complete_path_input(), ["Documents"]);
+        assert_eq!(browser.path_input.as_str(), "Documents/");
+
+        assert!(browser.apply_path_input());
+        assert_eq!(browser.current_dir(), tmp.path().join("Documents"));
+        browser.path_input.set(format!("{root}/nope"));
+        assert!(!browser.apply_path_input());
+        assert_eq!(browser.current_dir(), tmp.path().join("Documents"));
+    }
+
+    #[test]
+    fn hidden_folders_toggle_and_audio_is_counted() {
+        let tmp = make_test_tree();
+        for file in [
+            "Music/Albums/a.flac",
+            "Music/Albums/b.MP3",
+            "Music/cover.jpg",
+            ".hidden/c.ogg",
+        ] {
+            std::fs::write(tmp.path().join(file), b"").unwrap();
+        }
+        let mut browser = FileBrowser::new(tmp.path());
+        let counted = |browser: &mut FileBrowser| {
+            for _ in 0..500 {
+                browser.poll_counts();
+                if browser.entries().iter().all(|e| e.audio_files.is_some()) {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(2));
+            }
+            browser
+                .entries()
+                .iter()
+                .map(|e| {
+                    (
+                        e.name.clone(),
+                        e.audio_files.map_or(usize::MAX, |c| c.files),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            counted(&mut browser),
+            [("Documents".to_string(), 0), ("Music".to_string(), 2)]
+        );
+
+        browser.selected = 1;
+        browser.toggle_hidden();
+        assert!(browser.show_hidden());
+        assert_eq!(browser.selected_entry().unwrap().name, "Music");
+        assert_eq!(counted(&mut browser)[0], (".hidden".to_string(), 1));
+        browser.toggle_hidden();
+        assert_eq!(browser.entries().len(), 2);
+
+        assert_eq!(
+            count_audio_files(tmp.path(), 3),
+            AudioCount {
+                files: 0,
+                complete: false
+            }
+        );
+    }
+
     #[test]
     fn navigate_to_valid_path() {
         let tmp = make_test_tree();
@@ -0,0 +1,246 @@
+//! The footer's two lines, drawn from the `footer_top` and `footer_bottom`
+//! templates in `[ui]`. A template is plain text with `{segment}`
+//! placeholders; `{=}` pushes the rest of the line to the right edge. A
+//! placeholder that is not a segment is shown as written.
+
+use ratatui::style::Style;
+use ratatui::text::Span;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::text::display_width;
+
+pub const DEFAULT_TOP: &str = "{title}  {tracks}{filter}{badges}{=}{log}{hints}";
+pub const DEFAULT_BOTTOM: &str = "{status}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    /// The app title.
+    Title,
+    /// `1234 tracks`.
+    Tracks,
+    /// `  filter: /query` while the track list is filtered.
+    Filter,
+    /// `  [radio]` and `  [scanning...]` while they apply.
+    Badges,
+    /// `L: 3 new in log  ` when warnings were logged since the log was opened.
+    Log,
+    /// As many key hints as fit in what is left of the line.
+    Hints,
+    /// The latest status message.
+    Status,
+    /// `playing: Artist - Title (01:23 / 04:56)`.
+    Playing,
+    /// `3/12 in queue`, or nothing with an empty queue.
+    Queue,
+    /// `vol 80%`.
+    Volume,
+    /// Local time as `HH:MM`.
+    Clock,
+}
+
+impl Segment {
+    pub const ALL: [Segment; 11] = [
+        Segment::Title,
+        Segment::Tracks,
+        Segment::Filter,
+        Segment::Badges,
+        Segment::Log,
+        Segment::Hints,
+        Segment::Status,
+        Segment::Playing,
+        Segment::Queue,
+        Segment::Volume,
+        Segment::Clock,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Segment::Title => "title",
+            Segment::Tracks => "tracks",
+            Segment::Filter => "filter",
+            Segment::Badges => "badges",
+            Segment::Log => "log",
+            Segment::Hints => "hints",
+            Segment::Status => "status",
+            Segment::Playing => "playing",
+            Segment::Queue => "queue",
+            Segment::Volume => "volume",
+            Segment::Clock => "clock",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        Self::ALL.into_iter().find(|segment| segment.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    Text(String),
+    Segment(Segment),
+    /// `{=}`: everything after it is right-aligned.
+    Right,
+}
+
+pub fn parse(template: &str) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            text.push_str(&rest[open..]);
+            rest = "";
+            break;
+        };
+        let name = &after[..close];
+        let part = match name.trim() {
+            "=" => Some(Part::Right),
+            name => Segment::from_name(name).map(Part::Segment),
+        };
+        match part {
+            Some(part) => {
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push(part);
+            }
+            None => text.push_str(&rest[open..open + close + 2]),
+        }
+        rest = &after[close + 1..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    parts
+}
+
+/// A drawn part of a footer line: a segment's spans, or `segment: None`
+/// for template text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece<'a> {
+    pub segment: Option<Segment>,
+    pub spans: Vec<Span<'a>>,
+}
+
+impl Piece<'_> {
+    pub fn width(&self) -> usize {
+        self.spans
+            .iter()
+            .map(|span| display_width(&span.content))
+            .sum()
+    }
+}
+
+/// The left- and right-aligned pieces of one footer line `width` columns
+/// wide. `segment` draws a segment given the columns left for it, which
+/// only hints make use of: they are drawn last, in whatever room the rest
+/// of the line leaves.
+pub fn layout<'a>(
+    parts: &[Part],
+    width: usize,
+    text_style: Style,
+    mut segment: impl FnMut(Segment, usize) -> Vec<Span<'a>>,
+) -> (Vec<Piece<'a>>, Vec<Piece<'a>>) {
+    let mut pieces: Vec<Option<Piece<'a>>> = parts
+        .iter()
+        .map(|part| match part {
+            Part::Text(text) => Some(Piece {
+                segment: None,
+                spans: vec![Span::styled(text.clone(), text_style)],
+            }),
+            Part::Segment(Segment::Hints) | Part::Right => None,
+            Part::Segment(other) => Some(Piece {
+                segment: Some(*other),
+                spans: segment(*other, 0),
+            }),
+        })
+        .collect();
+    let used: usize = pieces.iter().flatten().map(Piece::width).sum();
+    let room = width.saturating_sub(used + 2);
+    for (part, piece) in parts.iter().zip(pieces.iter_mut()) {
+        if *part == Part::Segment(Segment::Hints) {
+            *piece = Some(Piece {
+                segment: Some(Segment::Hints),
+                spans: segment(Segment::Hints, room),
+            });
+        }
+    }
+
+    let split = parts
+        .iter()
+        .position(|part| *part == Part::Right)
+        .unwrap_or(parts.len());
+    let right = pieces.split_off(split);
+    (
+        pieces.into_iter().flatten().collect(),
+        right.into_iter().flatten().collect(),
+    )
+}
+
+/// `now` as `HH:MM` at `utc_offset_minutes` from UTC.
+pub fn clock_label(now: SystemTime, utc_offset_minutes: i32) -> String {
+    let secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let minutes = (secs / 60 + i64::from(utc_offset_minutes)).rem_euclid(24 * 60);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_segments_alignment_and_unknown_placeholders() {
+        assert_eq!(
+            parse("{clock} | {queue}{=}{nope} {hints"),
+            vec![
+                Part::Segment(Segment::Clock),
+                Part::Text(" | ".to_string()),
+                Part::Segment(Segment::Queue),
+                Part::Right,
+                Part::Text("{nope} {hints".to_string()),
+            ]
+        );
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn hints_get_the_room_left_by_the_rest_of_the_line() {
+        let parts = parse("{title} {hints}{=}{clock}");
+        let mut rooms = Vec::new();
+        let (left, right) = layout(&parts, 30, Style::default(), |segment, room| {
+            rooms.push((segment, room));
+            vec![Span::raw(match segment {
+                Segment::Title => "Auric",
+                Segment::Clock => "12:30",
+                _ => "hints",
+            })]
+        });
+        assert_eq!(rooms.last(), Some(&(Segment::Hints, 30 - 11 - 2)));
+        let text = |pieces: &[Piece]| {
+            pieces
+                .iter()
+                .flat_map(|piece| &piece.spans)
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+        assert_eq!(text(&left), "Auric hints");
+        assert_eq!(left[2].segment, Some(Segment::Hints));
+        assert_eq!(text(&right), "12:30");
+    }
+
+    #[test]
+    fn clock_is_shown_at_the_local_offset() {
+        let noon =
+            UNIX_EPOCH + std::time::Duration::from_secs(19_000 * 86_400 + 12 * 3600 + 5 * 60 + 59);
+        assert_eq!(clock_label(noon, 0), "12:05");
+        assert_eq!(clock_label(noon, 150), "14:35");
+        assert_eq!(clock_label(noon, -13 * 60), "23:05");
+    }
+}
@@ -0,0 +1,257 @@
+//! Characters for the seek bar, volume slider and visualizer.
+//!
+//! Not every terminal font has braille or the heavy box-drawing lines, and
+//! the missing ones show as boxes. The `unicode_level` setting swaps in a
+//! set that fonts are more likely to have. A theme can name its own
+//! characters in a `[glyphs]` table; those are used at the full level only,
+//! so choosing `basic` or `ascii` always gets characters that render.
+//!
+//! At the ascii level the finished frame is also passed through [`asciify`],
+//! which swaps borders, arrows and other decoration for plain characters.
+
+use crate::Theme;
+use ratatui::buffer::Buffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnicodeLevel {
+    /// Braille visualizer and heavy lines.
+    #[default]
+    Full,
+    /// Block elements and light lines, without braille.
+    Basic,
+    Ascii,
+}
+
+impl UnicodeLevel {
+    pub fn from_config(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "basic" | "blocks" => Self::Basic,
+            "ascii" => Self::Ascii,
+            _ => Self::Full,
+        }
+    }
+
+    /// Like [`Self::from_config`], but `auto` picks ascii when the locale
+    /// is not UTF-8, where anything else would come out garbled.
+    pub fn resolve(raw: &str, utf8_locale: bool) -> Self {
+        if raw.trim().eq_ignore_ascii_case("auto") && !utf8_locale {
+            Self::Ascii
+        } else {
+            Self::from_config(raw)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyphs {
+    pub progress_fill: String,
+    /// Drawn at the end of the fill when it reaches past half a cell.
+    pub progress_edge: String,
+    pub progress_empty: String,
+    /// The scrubber handle at the playback position.
+    pub playhead: String,
+    /// Ticks on the seek bar where chapters start.
+    pub chapter_mark: String,
+    /// Visualizer cells; `None` draws braille dots.
+    pub cells: Option<CellGlyphs>,
+}
+
+/// Stand-ins for braille cells: `levels` for cells filled from the bottom
+/// (a quarter, half, three quarters, full) and `dot` for any other pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellGlyphs {
+    pub levels: [String; 4],
+    pub dot: String,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self::builtin(UnicodeLevel::Full)
+    }
+}
+
+impl Glyphs {
+    pub fn builtin(level: UnicodeLevel) -> Self {
+        let cells = |levels: [&str; 4], dot: &str| {
+            Some(CellGlyphs {
+                levels: levels.map(str::to_string),
+                dot: dot.to_string(),
+            })
+        };
+        let (fill, edge, empty, playhead, mark, cells) = match level {
+            UnicodeLevel::Full => ("━", "╸", "─", "●", "┃", None),
+            UnicodeLevel::Basic => ("█", "▌", "─", "█", "│", cells(["▂", "▄", "▆", "█"], "•")),
+            UnicodeLevel::Ascii => ("#", "#", "-", "o", "|", cells([".", "-", "=", "#"], "*")),
+        };
+        Self {
+            progress_fill: fill.to_string(),
+            progress_edge: edge.to_string(),
+            progress_empty: empty.to_string(),
+            playhead: playhead.to_string(),
+            chapter_mark: mark.to_string(),
+            cells,
+        }
+    }
+
+    /// The full set with the theme's `glyphs.*` overrides. Each override is
+    /// one character, except `glyphs.spectrum`: four bar levels, lowest
+    /// first, with an optional fifth for scattered dots.
+    pub fn from_theme(theme: &Theme) -> Self {
+        let mut glyphs = Self::default();
+        let single = |key: &str| {
+            theme
+                .tokens
+                .get(key)
+                .filter(|v| v.chars().count() == 1)
+                .cloned()
+        };
+        for (key, field) in [
+            ("glyphs.progress_fill", &mut glyphs.progress_fill),
+            ("glyphs.progress_edge", &mut glyphs.progress_edge),
+            ("glyphs.progress_empty", &mut glyphs.progress_empty),
+            ("glyphs.playhead", &mut glyphs.playhead),
+            ("glyphs.chapter_mark", &mut glyphs.chapter_mark),
+        ] {
+            if let Some(v) = single(key) {
+                *field = v;
+            }
+        }
+        if let Some(spectrum) = theme.tokens.get("glyphs.spectrum") {
+            let chars: Vec<String> = spectrum.chars().map(String::from).collect();
+            if let [a, b, c, d, rest @ ..] = chars.as_slice() {
+                glyphs.cells = Some(CellGlyphs {
+                    levels: [a.clone(), b.clone(), c.clone(), d.clone()],
+                    dot: rest.first().unwrap_or(d).clone(),
+                });
+            }
+        }
+        glyphs
+    }
+
+    /// These glyphs at `level`: unchanged at the full level, the built-in
+    /// safe set below it.
+    pub fn at_level(&self, level: UnicodeLevel) -> Self {
+        match level {
+            UnicodeLevel::Full => self.clone(),
+            _ => Self::builtin(level),
+        }
+    }
+
+    /// The character for a braille cell with the dots in `pattern` set
+    /// (bits as in U+2800).
+    pub fn dot_cell(&self, pattern: u8) -> String {
+        let Some(cells) = &self.cells else {
+            return char::from_u32(0x2800 + u32::from(pattern))
+                .unwrap_or(' ')
+                .to_string();
+        };
+        // Dot rows top to bottom: 1 4, 2 5, 3 6, 7 8.
+        let rows = [0x09, 0x12, 0x24, 0xc0].map(|mask| pattern & mask != 0);
+        let filled = rows.iter().rev().take_while(|set| **set).count();
+        if filled > 0 && rows.iter().filter(|set| **set).count() == filled {
+            cells.levels[filled - 1].clone()
+        } else {
+            cells.dot.clone()
+        }
+    }
+}
+
+/// Replaces the decorative characters in `buf` (box drawing, blocks,
+/// arrows, shapes, braille and Nerd Font icons) with ASCII. Other text, like
+/// track titles, is left as it is.
+pub fn asciify(buf: &mut Buffer) {
+    for cell in &mut buf.content {
+        let mut chars = cell.symbol().chars();
+        if let (Some(ch), None) = (chars.next(), chars.next()) {
+            if let Some(plain) = ascii_fallback(ch) {
+                cell.set_char(plain);
+            }
+        }
+    }
+}
+
+/// The ASCII stand-in for a decorative character, or `None` to keep it.
+pub fn ascii_fallback(ch: char) -> Option<char> {
+    let plain =
+        match ch {
+            '─' | '━' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '═' | '╴' | '╶' | '╸' | '╺' | '╼'
+            | '╾' => '-',
+            '│' | '┃' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '║' | '╵' | '╷' | '╹' | '╻' | '╽'
+            | '╿' => '|',
+            '\u{2500}'..='\u{257f}' => '+',
+            '█' | '▉' | '▊' | '▇' | '▆' => '#',
+            '▌' | '▐' | '▍' | '▎' | '▏' => '>',
+            '▁' | '▂' | '▃' => '_',
+            '▄' | '▅' => '=',
+            '\u{2580}'..='\u{259f}' => ' ',
+            '▲' | '↑' => '^',
+            '▼' | '↓' => 'v',
+            '←' | '◀' | '‹' => '<',
+            '→' | '▶' | '›' => '>',
+            '●' | '⬤' | '•' | '◆' => '*',
+            '◐' | '○' => 'o',
+            '✓' | '✔' => 'x',
+            '♪' | '♫' => '#',
+            '…' | '·' => '.',
+            '–' | '—' => '-',
+            '≈' => '~',
+            'µ' => 'u',
+            '\u{2800}' => ' ',
+            '\u{2801}'..='\u{28ff}' => '.',
+            // Nerd Font icons live in the private use areas.
+            '\u{e000}'..='\u{f8ff}' | '\u{f0000}'..='\u{10ffff}' => ' ',
+            _ => return None,
+        };
+    Some(plain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn levels_pick_safe_sets_and_themes_override_the_full_one() {
+        assert_eq!(UnicodeLevel::from_config(" ASCII "), UnicodeLevel::Ascii);
+        assert_eq!(UnicodeLevel::from_config("blocks"), UnicodeLevel::Basic);
+        assert_eq!(UnicodeLevel::from_config("whatever"), UnicodeLevel::Full);
+
+        let full = Glyphs::default();
+        assert_eq!(full.dot_cell(0xc0), "⣀");
+        let ascii = full.at_level(UnicodeLevel::Ascii);
+        assert_eq!(
+            (ascii.progress_fill.as_str(), ascii.progress_empty.as_str()),
+            ("#", "-")
+        );
+        assert_eq!(ascii.dot_cell(0xc0), ".");
+        assert_eq!(ascii.dot_cell(0xff), "#");
+        assert_eq!(ascii.dot_cell(0x01), "*");
+        assert_eq!(Glyphs::builtin(UnicodeLevel::Basic).dot_cell(0xe4), "▄");
+
+        let theme = Theme {
+            name: "t".into(),
+            tokens: BTreeMap::from([
+                ("glyphs.playhead".to_string(), "◆".to_string()),
+                ("glyphs.progress_fill".to_string(), "too long".to_string()),
+                ("glyphs.spectrum".to_string(), "_-=#".to_string()),
+            ]),
+        };
+        let themed = Glyphs::from_theme(&theme);
+        assert_eq!(themed.playhead, "◆");
+        assert_eq!(themed.progress_fill, "━");
+        assert_eq!(themed.dot_cell(0x24 | 0xc0), "-");
+        assert_eq!(themed.dot_cell(0x01), "#");
+        assert_eq!(themed.at_level(UnicodeLevel::Ascii).playhead, "o");
+    }
+
+    #[test]
+    fn auto_falls_back_to_ascii_outside_utf8_and_asciify_keeps_text() {
+        assert_eq!(UnicodeLevel::resolve("auto", true), UnicodeLevel::Full);
+        assert_eq!(UnicodeLevel::resolve("auto", false), UnicodeLevel::Ascii);
+        assert_eq!(UnicodeLevel::resolve("basic", false), UnicodeLevel::Basic);
+
+        let mut buf = Buffer::with_lines(["╭─ Björk ─╮", "│▌ 󰎆 ▲ … │"]);
+        asciify(&mut buf);
+        assert_eq!(buf, Buffer::with_lines(["+- Björk -+", "|>   ^ . |"]));
+    }
+}
@@ -0,0 +1,128 @@
+//! Selection state for the library health report (`H`): the kinds of
+//! problem on the left, the tracks with the selected one on the right.
+
+use crate::shell::{ShellHealthIssue, ShellHealthTrack};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HealthPanel {
+    pub issue: usize,
+    pub track: usize,
+    /// Keys move through the track list rather than the issues.
+    pub tracks_focused: bool,
+}
+
+impl HealthPanel {
+    pub fn selected_issue<'a>(
+        &self,
+        issues: &'a [ShellHealthIssue],
+    ) -> Option<&'a ShellHealthIssue> {
+        issues.get(self.issue)
+    }
+
+    pub fn selected_track<'a>(
+        &self,
+        issues: &'a [ShellHealthIssue],
+    ) -> Option<&'a ShellHealthTrack> {
+        self.selected_issue(issues)?.tracks.get(self.track)
+    }
+
+    pub fn move_selection(&mut self, delta: isize, issues: &[ShellHealthIssue]) {
+        if self.tracks_focused {
+            let len = self
+                .selected_issue(issues)
+                .map(|i| i.tracks.len())
+                .unwrap_or(0);
+            self.track = step(self.track, delta, len);
+        } else {
+            let before = self.issue;
+            self.issue = step(self.issue, delta, issues.len());
+            if self.issue != before {
+                self.track = 0;
+            }
+        }
+    }
+
+    /// Moves into the track list if the selected issue lists any.
+    pub fn focus_tracks(&mut self, issues: &[ShellHealthIssue]) {
+        self.tracks_focused = self
+            .selected_issue(issues)
+            .is_some_and(|i| !i.tracks.is_empty());
+    }
+
+    pub fn focus_issues(&mut self) {
+        self.tracks_focused = false;
+    }
+
+    /// Keeps the selection in range after the report changed underneath it.
+    pub fn clamp(&mut self, issues: &[ShellHealthIssue]) {
+        self.issue = self.issue.min(issues.len().saturating_sub(1));
+        let tracks = self
+            .selected_issue(issues)
+            .map(|i| i.tracks.len())
+            .unwrap_or(0);
+        self.track = self.track.min(tracks.saturating_sub(1));
+        if tracks == 0 {
+            self.tracks_focused = false;
+        }
+    }
+}
+
+fn step(index: usize, delta: isize, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    index.saturating_add_signed(delta).min(len - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(tracks: usize) -> ShellHealthIssue {
+        ShellHealthIssue {
+            label: "Missing tags".to_string(),
+            count: tracks,
+            needs_check: false,
+            fix: None,
+            tracks: (0..tracks)
+                .map(|i| ShellHealthTrack {
+                    path: format!("/music/{i}.flac"),
+                    title: format!("Track {i}"),
+                    detail: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn selection_stays_on_the_tracks_of_the_selected_issue() {
+        let issues = vec![issue(3), issue(0)];
+        let mut panel = HealthPanel::default();
+        panel.focus_tracks(&issues);
+        panel.move_selection(5, &issues);
+        assert_eq!((panel.issue, panel.track), (0, 2));
+
+        panel.focus_issues();
+        panel.move_selection(1, &issues);
+        assert_eq!((panel.issue, panel.track), (1, 0));
+        panel.focus_tracks(&issues);
+        assert!(!panel.tracks_focused);
+
+        let mut panel = HealthPanel {
+            issue: 0,
+            track: 2,
+            tracks_focused: true,
+        };
+        panel.clamp(&[issue(1)]);
+        assert_eq!(
+            panel,
+            HealthPanel {
+                issue: 0,
+                track: 0,
+                tracks_focused: true
+            }
+        );
+        panel.clamp(&[]);
+        assert_eq!(panel, HealthPanel::default());
+    }
+}
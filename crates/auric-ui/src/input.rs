@@ -0,0 +1,296 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::{Duration, Instant};
+
+/// How long a partially typed chord stays pending before it is discarded.
+pub const DEFAULT_CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// A single key within a chord sequence.
+///
+/// Shift is folded into the character for `KeyCode::Char` so that `G` matches
+/// regardless of whether the terminal reports the shift modifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChordKey {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl ChordKey {
+    pub const fn char(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    pub fn from_event(key: &KeyEvent) -> Self {
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        Self {
+            code: key.code,
+            modifiers,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let base = match self.code {
+            KeyCode::Char(' ') => "SPC".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            other => format!("{other:?}"),
+        };
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            format!("C-{base}")
+        } else {
+            base
+        }
+    }
+
+    /// The key a label such as `D`, `C-p`, `Enter` or `Up` names, as
+    /// written in the help dialog and footer hints.
+    pub fn parse(label: &str) -> Option<Self> {
+        if let Some(rest) = label.strip_prefix("C-").filter(|rest| !rest.is_empty()) {
+            let key = Self::parse(rest)?;
+            return Some(Self {
+                modifiers: key.modifiers | KeyModifiers::CONTROL,
+                ..key
+            });
+        }
+        let code = match label {
+            "SPC" => KeyCode::Char(' '),
+            "Enter" => KeyCode::Enter,
+            "Tab" => KeyCode::Tab,
+            "Esc" => KeyCode::Esc,
+            "Backspace" => KeyCode::Backspace,
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            _ => {
+                let mut chars = label.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    pub fn to_event(self) -> KeyEvent {
+        KeyEvent::new(self.code, self.modifiers)
+    }
+}
+
+/// Result of feeding a key into a [`ChordMatcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordResult<A> {
+    /// The key extends a known chord prefix; wait for more input.
+    Pending,
+    /// The key completed a chord.
+    Matched(A),
+    /// The key is not part of any chord and should be handled normally.
+    Passthrough,
+}
+
+/// Pending-key state machine for multi-key sequences such as `g g`.
+///
+/// Only sequences of two or more keys are registered here; single-key bindings
+/// stay in the regular key handler and receive every key the matcher passes
+/// through.
+#[derive(Debug, Clone)]
+pub struct ChordMatcher<A> {
+    bindings: Vec<(Vec<ChordKey>, A)>,
+    pending: Vec<ChordKey>,
+    last_key_at: Option<Instant>,
+    timeout: Duration,
+}
+
+impl<A: Copy> ChordMatcher<A> {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            bindings: Vec::new(),
+            pending: Vec::new(),
+            last_key_at: None,
+            timeout,
+        }
+    }
+
+    pub fn bind(mut self, keys: &[ChordKey], action: A) -> Self {
+        debug_assert!(keys.len() >= 2, "chords need at least two keys");
+        self.bindings.push((keys.to_vec(), action));
+        self
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (&[ChordKey], A)> {
+        self.bindings
+            .iter()
+            .map(|(keys, action)| (keys.as_slice(), *action))
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Space-separated labels of the keys typed so far, e.g. `"g"`.
+    pub fn pending_label(&self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        Some(
+            self.pending
+                .iter()
+                .map(ChordKey::label)
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+        self.last_key_at = None;
+    }
+
+    pub fn feed(&mut self, key: ChordKey, now: Instant) -> ChordResult<A> {
+        if self
+            .last_key_at
+            .is_some_and(|at| now.saturating_duration_since(at) > self.timeout)
+        {
+            self.cancel();
+        }
+
+        self.pending.push(key);
+        if let Some(result) = self.resolve(now) {
+            return result;
+        }
+
+        // The key broke the pending sequence; retry it as the start of a new one.
+        let had_prefix = self.pending.len() > 1;
+        self.cancel();
+        if had_prefix {
+            self.pending.push(key);
+            if let Some(result) = self.resolve(now) {
+                return result;
+            }
+            self.cancel();
+        }
+        ChordResult::Passthrough
+    }
+
+    fn resolve(&mut self, now: Instant) -> Option<ChordResult<A>> {
+        let mut is_prefix = false;
+        for (keys, action) in &self.bindings {
+            if !keys.starts_with(&self.pending) {
+                continue;
+            }
+            if keys.len() == self.pending.len() {
+                let action = *action;
+                self.cancel();
+                return Some(ChordResult::Matched(action));
+            }
+            is_prefix = true;
+        }
+        if is_prefix {
+            self.last_key_at = Some(now);
+            Some(ChordResult::Pending)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher() -> ChordMatcher<u8> {
+        ChordMatcher::new(DEFAULT_CHORD_TIMEOUT)
+            .bind(&[ChordKey::char('g'), ChordKey::char('g')], 1)
+            .bind(&[ChordKey::char('g'), ChordKey::char('t')], 2)
+            .bind(
+                &[
+                    ChordKey::char(' '),
+                    ChordKey::char('p'),
+                    ChordKey::char('n'),
+                ],
+                3,
+            )
+    }
+
+    #[test]
+    fn matches_two_and_three_key_sequences() {
+        let mut m = matcher();
+        let now = Instant::now();
+        assert_eq!(m.feed(ChordKey::char('g'), now), ChordResult::Pending);
+        assert_eq!(m.pending_label().as_deref(), Some("g"));
+        assert_eq!(m.feed(ChordKey::char('t'), now), ChordResult::Matched(2));
+        assert!(!m.is_pending());
+
+        assert_eq!(m.feed(ChordKey::char(' '), now), ChordResult::Pending);
+        assert_eq!(m.feed(ChordKey::char('p'), now), ChordResult::Pending);
+        assert_eq!(m.pending_label().as_deref(), Some("SPC p"));
+        assert_eq!(m.feed(ChordKey::char('n'), now), ChordResult::Matched(3));
+    }
+
+    #[test]
+    fn unrelated_keys_pass_through_and_break_prefixes() {
+        let mut m = matcher();
+        let now = Instant::now();
+        assert_eq!(m.feed(ChordKey::char('j'), now), ChordResult::Passthrough);
+        assert_eq!(m.feed(ChordKey::char('g'), now), ChordResult::Pending);
+        assert_eq!(m.feed(ChordKey::char('j'), now), ChordResult::Passthrough);
+        assert!(!m.is_pending());
+        // A broken prefix may itself start a new chord.
+        assert_eq!(m.feed(ChordKey::char(' '), now), ChordResult::Pending);
+        assert_eq!(m.feed(ChordKey::char('g'), now), ChordResult::Pending);
+        assert_eq!(m.feed(ChordKey::char('g'), now), ChordResult::Matched(1));
+    }
+
+    #[test]
+    fn pending_prefix_expires_after_timeout() {
+        let mut m = matcher();
+        let start = Instant::now();
+        assert_eq!(m.feed(ChordKey::char('g'), start), ChordResult::Pending);
+        let later = start + DEFAULT_CHORD_TIMEOUT + Duration::from_millis(1);
+        assert_eq!(m.feed(ChordKey::char('t'), later), ChordResult::Passthrough);
+    }
+
+    #[test]
+    fn labels_parse_back_into_keys() {
+        for key in [
+            ChordKey::char('D'),
+            ChordKey::char(' '),
+            ChordKey::ctrl('p'),
+            ChordKey {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+            },
+        ] {
+            assert_eq!(ChordKey::parse(&key.label()), Some(key));
+        }
+        assert_eq!(
+            ChordKey::parse("Esc").map(|key| key.code),
+            Some(KeyCode::Esc)
+        );
+        assert_eq!(ChordKey::parse("any"), None);
+        assert_eq!(ChordKey::parse(""), None);
+    }
+
+    #[test]
+    fn shift_is_folded_into_characters() {
+        let key = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(ChordKey::from_event(&key), ChordKey::char('G'));
+    }
+}
@@ -0,0 +1,472 @@
+use crate::input::{ChordKey, ChordMatcher};
+
+/// Groups shown as headings in the help dialog, in display order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyCategory {
+    Playback,
+    Navigation,
+    Library,
+    Layout,
+    Clipboard,
+    Mouse,
+    General,
+}
+
+impl KeyCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Playback => "Playback",
+            Self::Navigation => "Navigation",
+            Self::Library => "Library",
+            Self::Layout => "Layout",
+            Self::Clipboard => "Clipboard",
+            Self::Mouse => "Mouse",
+            Self::General => "General",
+        }
+    }
+}
+
+/// A single-key (or modifier) binding handled directly by the normal-mode
+/// key handler. Multi-key chords are described by the [`ChordMatcher`] they
+/// are registered in instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub keys: &'static str,
+    pub description: &'static str,
+    pub category: KeyCategory,
+}
+
+const fn bind(keys: &'static str, description: &'static str, category: KeyCategory) -> KeyBinding {
+    KeyBinding {
+        keys,
+        description,
+        category,
+    }
+}
+
+/// Normal-mode bindings. Keep in sync with `ShellState::handle_key`.
+pub const NORMAL_BINDINGS: &[KeyBinding] = &[
+    bind(
+        "Enter",
+        "play selected track or playlist (or name a new one)",
+        KeyCategory::Playback,
+    ),
+    bind("Space", "play / pause", KeyCategory::Playback),
+    bind(
+        "e / E",
+        "play / queue the selected track's whole album",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "w / W",
+        "play / queue everything by the selected track's artist",
+        KeyCategory::Playback,
+    ),
+    bind("n / N", "next / previous track", KeyCategory::Playback),
+    bind("+ / -", "volume up / down", KeyCategory::Playback),
+    bind("s", "toggle shuffle", KeyCategory::Playback),
+    bind("S", "toggle random album", KeyCategory::Playback),
+    bind(
+        "R",
+        "toggle radio (similar artists when the queue ends)",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "C",
+        "toggle consume (played tracks leave the queue)",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "[ / ]",
+        "seek back / ahead 5s (30s when resuming)",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "{ / }",
+        "seek back / ahead 30s (5min when resuming)",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "( / )",
+        "previous / next chapter from a CUE sheet (or C-[ / C-])",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "0 - 9",
+        "seek to 0% - 90% of the track",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "b / B",
+        "bookmark position / list bookmarks",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "Media keys",
+        "play / pause, next, previous, volume",
+        KeyCategory::Playback,
+    ),
+    bind("Tab / S-Tab", "switch pane focus", KeyCategory::Navigation),
+    bind(
+        "Esc",
+        "back to the previous pane or view",
+        KeyCategory::Navigation,
+    ),
+    bind(
+        "j / k",
+        "move selection (or arrows)",
+        KeyCategory::Navigation,
+    ),
+    bind("PgUp / PgDn", "move by a page", KeyCategory::Navigation),
+    bind("G", "jump to last", KeyCategory::Navigation),
+    bind(
+        "A-h / A-l",
+        "scroll a long title (or S-Left / S-Right)",
+        KeyCategory::Navigation,
+    ),
+    bind("A-1 - A-9", "switch to tab 1 - 9", KeyCategory::Navigation),
+    bind("/", "filter tracks", KeyCategory::Library),
+    bind("o", "cycle sort column", KeyCategory::Library),
+    bind(
+        "a",
+        "add music folder (queue the track in Recent)",
+        KeyCategory::Library,
+    ),
+    bind("r", "refresh library", KeyCategory::Library),
+    bind(
+        "i",
+        "track info (folder properties in roots)",
+        KeyCategory::Library,
+    ),
+    bind("P", "podcasts", KeyCategory::Library),
+    bind("T", "listening stats", KeyCategory::Library),
+    bind("H", "library health", KeyCategory::Library),
+    bind("u", "undo playlist delete", KeyCategory::Library),
+    bind(
+        "c",
+        "copy a folder or playlist into a new playlist",
+        KeyCategory::Library,
+    ),
+    bind(
+        "E (roots)",
+        "add the folder's tracks to the queue, in the list's sort order",
+        KeyCategory::Playback,
+    ),
+    bind(
+        "> / <",
+        "move track into / out of the split",
+        KeyCategory::Library,
+    ),
+    bind(
+        "h / l",
+        "browse back / open (queue or playlist in the split)",
+        KeyCategory::Navigation,
+    ),
+    bind(
+        "D",
+        "move track file to the trash (asks first)",
+        KeyCategory::Library,
+    ),
+    bind("C-Left / C-Right", "resize sidebar", KeyCategory::Layout),
+    bind("C-Up / C-Down", "resize now playing", KeyCategory::Layout),
+    bind("m", "toggle mini player", KeyCategory::Layout),
+    bind("v", "cycle visualizer style", KeyCategory::Layout),
+    bind("y", "copy track path", KeyCategory::Clipboard),
+    bind("Y", "copy \"Artist – Title\"", KeyCategory::Clipboard),
+    bind(
+        "C-v",
+        "paste into filter, palette or path",
+        KeyCategory::Clipboard,
+    ),
+    bind("Click", "focus pane and select row", KeyCategory::Mouse),
+    bind("Double-click", "play track", KeyCategory::Mouse),
+    bind(
+        "Wheel",
+        "scroll pane (volume over slider)",
+        KeyCategory::Mouse,
+    ),
+    bind("Click header", "sort by column", KeyCategory::Mouse),
+    bind(": / C-p", "command palette", KeyCategory::General),
+    bind(",", "settings", KeyCategory::General),
+    bind("?", "toggle this help", KeyCategory::General),
+    bind("L", "recent warnings and errors", KeyCategory::General),
+    bind("q / C-c", "quit", KeyCategory::General),
+];
+
+/// What the footer suggests keys for: the focused pane, or the prompt that
+/// takes keys in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintContext {
+    Sources,
+    Browse,
+    /// The browse pane's Recent list.
+    Recent,
+    Tracks,
+    Split,
+    Playlists,
+    TrackFilter,
+    CommandPalette,
+    NewPlaylist,
+    Confirm,
+    Party,
+}
+
+/// A footer hint: a binding's keys with a label short enough for the footer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    pub keys: &'static str,
+    pub label: &'static str,
+}
+
+const fn hint(keys: &'static str, label: &'static str) -> Hint {
+    Hint { keys, label }
+}
+
+/// The keys a click on a hint presses: the first of its alternatives, so
+/// `Enter / Esc` is Enter and `z q` is z then q. `None` for hints such as
+/// `any other key` that do not name keys.
+pub fn hint_keys(keys: &str) -> Option<Vec<ChordKey>> {
+    let first = keys.split(" / ").next()?;
+    let keys = first
+        .split_whitespace()
+        .map(ChordKey::parse)
+        .collect::<Option<Vec<_>>>()?;
+    (!keys.is_empty()).then_some(keys)
+}
+
+/// Footer hints for each context, most useful first. Pane hints name keys
+/// from [`NORMAL_BINDINGS`] or a chord; the prompts take keys of their own.
+const HINTS: &[(HintContext, &[Hint])] = &[
+    (
+        HintContext::Sources,
+        &[
+            hint("i", "folder properties"),
+            hint("a", "add folder"),
+            hint("c", "to playlist"),
+            hint("E", "queue folder"),
+            hint("r", "refresh"),
+        ],
+    ),
+    (
+        HintContext::Browse,
+        &[hint("h / l", "back / open"), hint("Esc", "back")],
+    ),
+    (
+        HintContext::Recent,
+        &[
+            hint("Enter", "play now"),
+            hint("a", "queue"),
+            hint("h", "back"),
+        ],
+    ),
+    (
+        HintContext::Tracks,
+        &[
+            hint("Enter", "play"),
+            hint("/", "filter"),
+            hint("o", "sort"),
+            hint("i", "info"),
+            hint("> / <", "to split"),
+            hint("y", "copy path"),
+            hint("D", "trash"),
+        ],
+    ),
+    (
+        HintContext::Split,
+        &[
+            hint("<", "remove"),
+            hint("h / l", "queue or playlist"),
+            hint("z q", "hide split"),
+        ],
+    ),
+    (
+        HintContext::Playlists,
+        &[
+            hint("Enter", "play"),
+            hint("u", "undo delete"),
+            hint("c", "copy"),
+            hint(": / C-p", "playlist commands"),
+        ],
+    ),
+    (
+        HintContext::TrackFilter,
+        &[
+            hint("Enter / Esc", "done"),
+            hint("Up / Down", "move"),
+            hint("C-v", "paste"),
+        ],
+    ),
+    (
+        HintContext::CommandPalette,
+        &[
+            hint("Enter", "run"),
+            hint("Esc", "cancel"),
+            hint("C-v", "paste"),
+        ],
+    ),
+    (
+        HintContext::NewPlaylist,
+        &[
+            hint("Enter", "create"),
+            hint("Esc", "cancel"),
+            hint("C-v", "paste"),
+        ],
+    ),
+    (
+        HintContext::Confirm,
+        &[hint("y", "confirm"), hint("any other key", "cancel")],
+    ),
+    (
+        HintContext::Party,
+        &[hint("Enter", "add to queue"), hint("q", "quit")],
+    ),
+];
+
+pub fn footer_hints(context: HintContext) -> &'static [Hint] {
+    HINTS
+        .iter()
+        .find(|(ctx, _)| *ctx == context)
+        .map_or(&[], |(_, hints)| hints)
+}
+
+/// One row of the help dialog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    pub keys: String,
+    pub description: String,
+    pub category: KeyCategory,
+}
+
+impl HelpEntry {
+    fn matches(&self, query: &str) -> bool {
+        self.keys.to_lowercase().contains(query)
+            || self.description.to_lowercase().contains(query)
+            || self.category.label().to_lowercase().contains(query)
+    }
+}
+
+/// Collects the single-key bindings and every chord registered in `chords`,
+/// sorted by category. `describe` names a chord action and picks its group.
+pub fn help_entries<A: Copy>(
+    chords: &ChordMatcher<A>,
+    describe: impl Fn(A) -> (&'static str, KeyCategory),
+) -> Vec<HelpEntry> {
+    let mut entries: Vec<HelpEntry> = NORMAL_BINDINGS
+        .iter()
+        .map(|binding| HelpEntry {
+            keys: binding.keys.to_string(),
+            description: binding.description.to_string(),
+            category: binding.category,
+        })
+        .collect();
+    entries.extend(chords.bindings().map(|(keys, action)| {
+        let (description, category) = describe(action);
+        HelpEntry {
+            keys: keys
+                .iter()
+                .map(ChordKey::label)
+                .collect::<Vec<_>>()
+                .join(" "),
+            description: description.to_string(),
+            category,
+        }
+    }));
+    // Stable sort keeps table order within each category.
+    entries.sort_by_key(|entry| entry.category);
+    entries
+}
+
+/// Entries whose keys, description or category contain `query`
+/// (case-insensitive). An empty query keeps everything.
+pub fn filter_entries<'a>(entries: &'a [HelpEntry], query: &str) -> Vec<&'a HelpEntry> {
+    let query = query.trim().to_lowercase();
+    entries
+        .iter()
+        .filter(|entry| query.is_empty() || entry.matches(&query))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn help_includes_chords_grouped_by_category() {
+        let chords = ChordMatcher::new(Duration::from_secs(1))
+            .bind(&[ChordKey::char('g'), ChordKey::char('g')], 1u8)
+            .bind(&[ChordKey::char('z'), ChordKey::char('s')], 2u8);
+        let entries = help_entries(&chords, |action| match action {
+            1 => ("jump to first", KeyCategory::Navigation),
+            _ => ("toggle sidebar", KeyCategory::Layout),
+        });
+
+        let gg = entries
+            .iter()
+            .find(|e| e.keys == "g g")
+            .expect("g g listed");
+        assert_eq!(gg.category, KeyCategory::Navigation);
+        assert!(entries.iter().any(|e| e.keys == "z s"));
+        assert!(entries.windows(2).all(|w| w[0].category <= w[1].category));
+        assert_eq!(entries.len(), NORMAL_BINDINGS.len() + 2);
+    }
+
+    #[test]
+    fn pane_hints_name_bound_keys() {
+        let chords = ["z q"];
+        for context in [
+            HintContext::Sources,
+            HintContext::Browse,
+            HintContext::Recent,
+            HintContext::Tracks,
+            HintContext::Split,
+            HintContext::Playlists,
+        ] {
+            let hints = footer_hints(context);
+            assert!(!hints.is_empty(), "{context:?}");
+            for hint in hints {
+                let bound = NORMAL_BINDINGS.iter().any(|binding| {
+                    binding.keys == hint.keys
+                        || binding.keys.split(" / ").any(|key| key == hint.keys)
+                }) || chords.contains(&hint.keys);
+                assert!(bound, "{} is not in the keymap", hint.keys);
+            }
+        }
+        assert_eq!(footer_hints(HintContext::Confirm)[0].keys, "y");
+    }
+
+    #[test]
+    fn clicked_hints_press_their_first_keys() {
+        assert_eq!(
+            hint_keys("z q"),
+            Some(vec![ChordKey::char('z'), ChordKey::char('q')])
+        );
+        assert_eq!(hint_keys(": / C-p"), Some(vec![ChordKey::char(':')]));
+        assert_eq!(hint_keys("> / <"), Some(vec![ChordKey::char('>')]));
+        assert_eq!(hint_keys("C-v"), Some(vec![ChordKey::ctrl('v')]));
+        assert_eq!(hint_keys("any other key"), None);
+        for (_, hints) in HINTS {
+            for hint in *hints {
+                assert!(
+                    hint_keys(hint.keys).is_some() || hint.keys == "any other key",
+                    "{}",
+                    hint.keys
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn filter_matches_keys_descriptions_and_categories() {
+        let entries = help_entries(&ChordMatcher::<u8>::new(Duration::from_secs(1)), |_| {
+            ("", KeyCategory::General)
+        });
+        let volume = filter_entries(&entries, "VOLUME");
+        assert!(volume.iter().all(|e| e.description.contains("volume")));
+        assert!(!volume.is_empty());
+        assert!(filter_entries(&entries, "clipboard")
+            .iter()
+            .all(|e| e.category == KeyCategory::Clipboard));
+        assert_eq!(filter_entries(&entries, "  ").len(), entries.len());
+    }
+}
@@ -0,0 +1,294 @@
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
+
+pub const SIDEBAR_PERCENT_MIN: u16 = 15;
+pub const SIDEBAR_PERCENT_MAX: u16 = 50;
+pub const NOW_PLAYING_HEIGHT_MIN: u16 = 4;
+pub const NOW_PLAYING_HEIGHT_MAX: u16 = 20;
+/// Rows used by the now-playing bar in mini player mode.
+pub const MINI_BAR_HEIGHT: u16 = 2;
+
+/// User-adjustable panel proportions and visibility.
+///
+/// Stored under `[ui.layout]` in the config and persisted to the settings
+/// table whenever it changes from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub sidebar_percent: u16,
+    pub now_playing_height: u16,
+    pub show_sidebar: bool,
+    pub show_now_playing: bool,
+    pub show_spectrum: bool,
+    /// The queue or a playlist beside the track list.
+    pub show_split: bool,
+    /// Compact mode: a now-playing bar above the track list and nothing else.
+    pub mini: bool,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            sidebar_percent: 27,
+            now_playing_height: 8,
+            show_sidebar: true,
+            show_now_playing: true,
+            show_spectrum: true,
+            show_split: false,
+            mini: false,
+        }
+    }
+}
+
+impl PanelLayout {
+    /// Clamps proportions loaded from config or the database into a usable range.
+    pub fn clamped(mut self) -> Self {
+        self.sidebar_percent = self
+            .sidebar_percent
+            .clamp(SIDEBAR_PERCENT_MIN, SIDEBAR_PERCENT_MAX);
+        self.now_playing_height = self
+            .now_playing_height
+            .clamp(NOW_PLAYING_HEIGHT_MIN, NOW_PLAYING_HEIGHT_MAX);
+        self
+    }
+
+    pub fn resize_sidebar(&mut self, delta: i16) {
+        self.show_sidebar = true;
+        self.sidebar_percent = self
+            .sidebar_percent
+            .saturating_add_signed(delta)
+            .clamp(SIDEBAR_PERCENT_MIN, SIDEBAR_PERCENT_MAX);
+    }
+
+    pub fn resize_now_playing(&mut self, delta: i16) {
+        self.show_now_playing = true;
+        self.now_playing_height = self
+            .now_playing_height
+            .saturating_add_signed(delta)
+            .clamp(NOW_PLAYING_HEIGHT_MIN, NOW_PLAYING_HEIGHT_MAX);
+    }
+
+    pub fn describe(&self) -> String {
+        if self.mini {
+            return "Layout: mini player".to_string();
+        }
+        let on_off = |visible: bool| if visible { "on" } else { "off" };
+        let sidebar = if self.show_sidebar {
+            format!("{}%", self.sidebar_percent)
+        } else {
+            "hidden".to_string()
+        };
+        let now_playing = if self.show_now_playing {
+            format!("{} rows", self.now_playing_height)
+        } else {
+            "hidden".to_string()
+        };
+        format!(
+            "Layout: sidebar {sidebar}  now playing {now_playing}  spectrum {}  split {}",
+            on_off(self.show_spectrum),
+            on_off(self.show_split)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SidebarAreas {
+    pub roots: Rect,
+    pub browse: Rect,
+    pub playlists: Rect,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LayoutVariant {
+    #[default]
+    Full,
+    Mini,
+}
+
+/// Screen regions for one frame, derived from the terminal size and the
+/// current [`PanelLayout`]. Hidden panels have no area.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppLayout {
+    pub variant: LayoutVariant,
+    pub sidebar: Option<SidebarAreas>,
+    pub separator: Option<Rect>,
+    pub now_playing: Option<Rect>,
+    pub library: Rect,
+    /// Right of `library` when the split is shown.
+    pub split: Option<Rect>,
+    pub footer: Rect,
+}
+
+impl AppLayout {
+    pub fn new(area: Rect, panels: &PanelLayout) -> Self {
+        if panels.mini {
+            return Self::mini(area);
+        }
+        let panels = panels.clamped();
+        let vertical = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(12), Constraint::Length(3)])
+            .split(area);
+        let main = vertical[0];
+        let footer = vertical[1];
+
+        let (sidebar, separator, right_col) = if panels.show_sidebar {
+            let cols = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(panels.sidebar_percent),
+                    Constraint::Length(1), // vertical separator
+                    Constraint::Percentage(100 - panels.sidebar_percent),
+                ])
+                .split(main);
+            let left_sections = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(7),
+                    Constraint::Length(1), // gap
+                    Constraint::Length(7),
+                    Constraint::Length(1), // gap
+                    Constraint::Min(8),
+                ])
+                .split(cols[0]);
+            let sidebar = SidebarAreas {
+                roots: left_sections[0],
+                browse: left_sections[2],
+                playlists: left_sections[4],
+            };
+            (Some(sidebar), Some(cols[1]), cols[2])
+        } else {
+            (None, None, main)
+        };
+
+        let (now_playing, library) = if panels.show_now_playing {
+            let right_sections = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(panels.now_playing_height),
+                    Constraint::Length(1), // gap
+                    Constraint::Min(12),
+                ])
+                .split(right_col);
+            (Some(right_sections[0]), right_sections[2])
+        } else {
+            (None, right_col)
+        };
+
+        let (library, split) = if panels.show_split {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(55),
+                    Constraint::Length(1), // gap
+                    Constraint::Percentage(45),
+                ])
+                .split(library);
+            (halves[0], Some(halves[2]))
+        } else {
+            (library, None)
+        };
+
+        Self {
+            variant: LayoutVariant::Full,
+            sidebar,
+            separator,
+            now_playing,
+            library,
+            split,
+            footer,
+        }
+    }
+
+    /// Now-playing bar, track list and a single status row.
+    fn mini(area: Rect) -> Self {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(MINI_BAR_HEIGHT),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(area);
+        Self {
+            variant: LayoutVariant::Mini,
+            sidebar: None,
+            separator: None,
+            now_playing: Some(rows[0]),
+            library: rows[1],
+            split: None,
+            footer: rows[2],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_splits_sidebar_and_now_playing() {
+        let layout = AppLayout::new(Rect::new(0, 0, 100, 40), &PanelLayout::default());
+        let sidebar = layout.sidebar.expect("sidebar visible");
+        assert!((25..=27).contains(&sidebar.roots.width));
+        assert_eq!(layout.now_playing.map(|r| r.height), Some(8));
+        assert_eq!(layout.footer.height, 3);
+        assert!(layout.library.x > sidebar.roots.x);
+    }
+
+    #[test]
+    fn hidden_panels_give_space_to_the_library() {
+        let panels = PanelLayout {
+            show_sidebar: false,
+            show_now_playing: false,
+            ..PanelLayout::default()
+        };
+        let layout = AppLayout::new(Rect::new(0, 0, 100, 40), &panels);
+        assert!(layout.sidebar.is_none());
+        assert!(layout.now_playing.is_none());
+        assert_eq!(layout.library, Rect::new(0, 0, 100, 37));
+    }
+
+    #[test]
+    fn split_takes_the_right_of_the_library() {
+        let panels = PanelLayout {
+            show_sidebar: false,
+            show_now_playing: false,
+            show_split: true,
+            ..PanelLayout::default()
+        };
+        let layout = AppLayout::new(Rect::new(0, 0, 100, 40), &panels);
+        assert_eq!(layout.library, Rect::new(0, 0, 54, 37));
+        assert_eq!(layout.split, Some(Rect::new(55, 0, 45, 37)));
+    }
+
+    #[test]
+    fn resizing_is_clamped_and_reveals_hidden_panels() {
+        let mut panels = PanelLayout {
+            show_sidebar: false,
+            ..PanelLayout::default()
+        };
+        panels.resize_sidebar(100);
+        assert!(panels.show_sidebar);
+        assert_eq!(panels.sidebar_percent, SIDEBAR_PERCENT_MAX);
+        panels.resize_now_playing(-100);
+        assert_eq!(panels.now_playing_height, NOW_PLAYING_HEIGHT_MIN);
+    }
+
+    #[test]
+    fn mini_layout_keeps_only_bar_tracks_and_status() {
+        let panels = PanelLayout {
+            mini: true,
+            ..PanelLayout::default()
+        };
+        let layout = AppLayout::new(Rect::new(0, 0, 60, 12), &panels);
+        assert_eq!(layout.variant, LayoutVariant::Mini);
+        assert!(layout.sidebar.is_none());
+        assert_eq!(
+            layout.now_playing,
+            Some(Rect::new(0, 0, 60, MINI_BAR_HEIGHT))
+        );
+        assert_eq!(layout.library, Rect::new(0, 2, 60, 9));
+        assert_eq!(layout.footer, Rect::new(0, 11, 60, 1));
+    }
+}
@@ -2,21 +2,41 @@ use std::collections::BTreeMap;
 
 pub mod artwork;
 pub mod browse;
+pub mod clipboard;
+pub mod debug_overlay;
+pub mod event_log;
 pub mod file_browser;
+pub mod footer;
+pub mod glyphs;
+pub mod health;
+pub mod input;
+pub mod keymap;
+pub mod layout;
 pub mod modal;
+pub mod podcasts;
+pub mod remote_inbox;
+pub mod scheduler;
 pub mod seekbar;
+pub mod settings;
 pub mod shell;
+pub mod stats;
 pub mod terminal_caps;
+pub mod terminal_guard;
+pub mod text;
+pub mod text_input;
 pub mod theme;
 pub mod visualizer;
 
+pub use layout::{AppLayout, LayoutVariant, PanelLayout};
 pub use shell::{
     render_once_to_text, run_interactive, run_interactive_full, run_interactive_with_handlers,
     run_interactive_with_refresh, run_interactive_with_scan, FocusPane, IconMode,
     PaletteCommandResult, PlaybackAction, PlayerEventUpdate, RunOptions, ScanProgress,
-    ShellListItem, ShellSnapshot, ShellState, ShellTrackItem,
+    ShellEpisode, ShellHealthIssue, ShellHealthReport, ShellHealthTrack, ShellListItem,
+    ShellListeningStats, ShellPodcast, ShellReview, ShellRootOptions, ShellSnapshot, ShellState,
+    ShellTrackItem, SortColumn,
 };
-pub use theme::{FsThemeStore, Palette};
+pub use theme::{ColorMode, FsThemeStore, Palette};
 
 #[derive(Debug, Clone)]
 pub struct Theme {
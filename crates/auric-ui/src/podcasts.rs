@@ -0,0 +1,110 @@
+//! Selection state for the podcasts dialog (`P`): subscriptions on the left,
+//! the selected subscription's episodes on the right.
+
+use crate::shell::{ShellEpisode, ShellPodcast};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PodcastPanel {
+    pub podcast: usize,
+    pub episode: usize,
+    /// Keys move through the episode list rather than the subscriptions.
+    pub episodes_focused: bool,
+}
+
+impl PodcastPanel {
+    pub fn selected_podcast<'a>(&self, podcasts: &'a [ShellPodcast]) -> Option<&'a ShellPodcast> {
+        podcasts.get(self.podcast)
+    }
+
+    pub fn selected_episode<'a>(&self, podcasts: &'a [ShellPodcast]) -> Option<&'a ShellEpisode> {
+        self.selected_podcast(podcasts)?.episodes.get(self.episode)
+    }
+
+    pub fn move_selection(&mut self, delta: isize, podcasts: &[ShellPodcast]) {
+        if self.episodes_focused {
+            let len = self
+                .selected_podcast(podcasts)
+                .map(|p| p.episodes.len())
+                .unwrap_or(0);
+            self.episode = step(self.episode, delta, len);
+        } else {
+            let before = self.podcast;
+            self.podcast = step(self.podcast, delta, podcasts.len());
+            if self.podcast != before {
+                self.episode = 0;
+            }
+        }
+    }
+
+    /// Moves into the episode list if the selected podcast has any.
+    pub fn focus_episodes(&mut self, podcasts: &[ShellPodcast]) {
+        self.episodes_focused = self
+            .selected_podcast(podcasts)
+            .is_some_and(|p| !p.episodes.is_empty());
+    }
+
+    pub fn focus_podcasts(&mut self) {
+        self.episodes_focused = false;
+    }
+
+    /// Keeps the selection in range after the list changed underneath it.
+    pub fn clamp(&mut self, podcasts: &[ShellPodcast]) {
+        self.podcast = self.podcast.min(podcasts.len().saturating_sub(1));
+        let episodes = self
+            .selected_podcast(podcasts)
+            .map(|p| p.episodes.len())
+            .unwrap_or(0);
+        self.episode = self.episode.min(episodes.saturating_sub(1));
+        if episodes == 0 {
+            self.episodes_focused = false;
+        }
+    }
+}
+
+fn step(index: usize, delta: isize, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    index.saturating_add_signed(delta).min(len - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn podcast(id: &str, episodes: usize) -> ShellPodcast {
+        ShellPodcast {
+            id: id.to_string(),
+            title: id.to_string(),
+            detail: String::new(),
+            episodes: (0..episodes)
+                .map(|i| ShellEpisode {
+                    id: format!("{id}-{i}"),
+                    title: format!("Episode {i}"),
+                    detail: String::new(),
+                    position_ms: 0,
+                    downloaded: false,
+                    played: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn selection_stays_within_the_focused_list() {
+        let podcasts = vec![podcast("a", 3), podcast("b", 0)];
+        let mut panel = PodcastPanel::default();
+        panel.focus_episodes(&podcasts);
+        panel.move_selection(10, &podcasts);
+        assert_eq!(panel.selected_episode(&podcasts).unwrap().id, "a-2");
+
+        panel.focus_podcasts();
+        panel.move_selection(1, &podcasts);
+        assert_eq!(panel.episode, 0);
+        panel.focus_episodes(&podcasts);
+        assert!(!panel.episodes_focused);
+
+        panel.clamp(&podcasts[..1]);
+        assert_eq!(panel.selected_podcast(&podcasts).unwrap().id, "a");
+    }
+}
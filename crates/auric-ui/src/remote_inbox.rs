@@ -0,0 +1,75 @@
+use crate::shell::KeyAction;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// Outcome of a remote request: the resulting status line, or the error.
+pub type RemoteResult = Result<String, String>;
+
+/// An action from outside the terminal, handled by the run loop as if it had
+/// come from the keyboard.
+#[derive(Debug)]
+pub struct RemoteRequest {
+    /// `KeyAction::Continue` asks for the playback status without changing it.
+    pub action: KeyAction,
+    pub reply: Sender<RemoteResult>,
+}
+
+/// Queue of remote requests shared between the run loop and the threads that
+/// accept them. Clones share the same queue.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteInbox {
+    queue: Arc<Mutex<VecDeque<RemoteRequest>>>,
+}
+
+impl RemoteInbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `action` and returns the receiver its outcome is sent to.
+    pub fn submit(&self, action: KeyAction) -> Receiver<RemoteResult> {
+        let (reply, outcome) = mpsc::channel();
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.push_back(RemoteRequest { action, reply });
+        }
+        outcome
+    }
+
+    /// Takes every queued request, oldest first.
+    pub fn drain(&self) -> Vec<RemoteRequest> {
+        self.queue
+            .lock()
+            .map(|mut queue| queue.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::PlaybackAction;
+
+    #[test]
+    fn requests_are_drained_in_order_and_answered() {
+        let inbox = RemoteInbox::new();
+        let first = inbox
+            .clone()
+            .submit(KeyAction::Playback(PlaybackAction::Next));
+        let _second = inbox.submit(KeyAction::Continue);
+
+        let requests = inbox.drain();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].action,
+            KeyAction::Playback(PlaybackAction::Next)
+        );
+        assert!(inbox.drain().is_empty());
+
+        requests[0]
+            .reply
+            .send(Ok("Playing: next".to_string()))
+            .unwrap();
+        assert_eq!(first.recv().unwrap(), Ok("Playing: next".to_string()));
+    }
+}
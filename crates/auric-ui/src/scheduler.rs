@@ -0,0 +1,131 @@
+//! How often the shell redraws when no input arrives. Input is handled as
+//! soon as it comes in, and player events, remote requests and background
+//! jobs every [`TICK`], at any rate; the rate only decides how often the
+//! screen is drawn. Moving pictures get the full rate, everything else (a
+//! paused player, a clock that ticks once a second) a slow one, and a
+//! terminal in the background the slowest.
+
+use std::time::Duration;
+
+/// Frames per second while the spectrum or a fade is moving.
+pub const DEFAULT_ACTIVE_FPS: u16 = 30;
+/// Frames per second otherwise: enough for the playback clock, status
+/// messages and background job progress.
+pub const IDLE_FPS: u16 = 2;
+/// Frames per second while the terminal does not have focus.
+pub const BACKGROUND_FPS: u16 = 1;
+/// How often the loop looks at the player, the remote inbox and background
+/// jobs, drawn or not: a finished track advances within this.
+pub const TICK: Duration = Duration::from_millis(100);
+
+/// What the next frame has to show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pace {
+    /// Something moves every frame.
+    Active,
+    Idle,
+    /// The terminal lost focus; nobody is looking.
+    Background,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameScheduler {
+    active: Duration,
+    idle: Duration,
+    background: Duration,
+}
+
+impl Default for FrameScheduler {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACTIVE_FPS)
+    }
+}
+
+impl FrameScheduler {
+    /// Runs at `active_fps` (at least [`IDLE_FPS`]) while active.
+    pub fn new(active_fps: u16) -> Self {
+        Self {
+            active: frame_interval(active_fps.max(IDLE_FPS)),
+            idle: frame_interval(IDLE_FPS),
+            background: frame_interval(BACKGROUND_FPS),
+        }
+    }
+
+    pub fn interval(&self, pace: Pace) -> Duration {
+        match pace {
+            Pace::Active => self.active,
+            Pace::Idle => self.idle,
+            Pace::Background => self.background,
+        }
+    }
+
+    /// How long to wait for input when the last frame was drawn `elapsed`
+    /// ago.
+    pub fn timeout(&self, pace: Pace, elapsed: Duration) -> Duration {
+        self.interval(pace).saturating_sub(elapsed)
+    }
+
+    /// Whether a frame drawn `elapsed` ago is due to be replaced.
+    pub fn frame_due(&self, pace: Pace, elapsed: Duration) -> bool {
+        elapsed >= self.interval(pace)
+    }
+
+    /// How long to wait for input when the last frame was drawn
+    /// `since_draw` ago and the current tick began `since_tick` ago: until
+    /// the next frame or the next tick, whichever comes first.
+    pub fn poll_timeout(&self, pace: Pace, since_draw: Duration, since_tick: Duration) -> Duration {
+        self.timeout(pace, since_draw)
+            .min(TICK.saturating_sub(since_tick))
+    }
+}
+
+fn frame_interval(fps: u16) -> Duration {
+    Duration::from_secs(1) / u32::from(fps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idles_slowly_and_never_below_the_idle_rate() {
+        let frames = FrameScheduler::default();
+        assert_eq!(
+            frames.interval(Pace::Active),
+            Duration::from_nanos(33_333_333)
+        );
+        assert_eq!(frames.interval(Pace::Idle), Duration::from_millis(500));
+        assert_eq!(frames.interval(Pace::Background), Duration::from_secs(1));
+        assert_eq!(
+            frames.timeout(Pace::Idle, Duration::from_millis(120)),
+            Duration::from_millis(380)
+        );
+        assert_eq!(
+            frames.timeout(Pace::Active, Duration::from_millis(50)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            FrameScheduler::new(0).interval(Pace::Active),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn slow_frames_still_wait_at_most_one_tick_for_input() {
+        let frames = FrameScheduler::default();
+        assert_eq!(
+            frames.poll_timeout(Pace::Background, Duration::ZERO, Duration::ZERO),
+            TICK
+        );
+        assert_eq!(
+            frames.poll_timeout(Pace::Idle, Duration::ZERO, Duration::from_millis(30)),
+            Duration::from_millis(70)
+        );
+        assert_eq!(
+            frames.poll_timeout(Pace::Active, Duration::from_millis(20), Duration::ZERO),
+            Duration::from_nanos(13_333_333)
+        );
+        assert!(!frames.frame_due(Pace::Background, TICK));
+        assert!(frames.frame_due(Pace::Idle, Duration::from_millis(500)));
+    }
+}
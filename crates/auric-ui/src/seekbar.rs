@@ -1,12 +1,16 @@
+use crate::glyphs::Glyphs;
+use crate::theme::Palette;
 use ratatui::prelude::*;
 use ratatui::widgets::Widget;
-use crate::theme::Palette;
 
 pub struct SeekBar<'a> {
     pub progress: f32,
+    /// Where chapters start, as fractions of the track.
+    pub marks: &'a [f32],
     pub elapsed: &'a str,
     pub remaining: &'a str,
     pub palette: &'a Palette,
+    pub glyphs: &'a Glyphs,
 }
 
 impl<'a> Widget for SeekBar<'a> {
@@ -22,7 +26,12 @@ impl<'a> Widget for SeekBar<'a> {
         let bar_width = bar_end.saturating_sub(bar_start);
 
         // Elapsed label
-        buf.set_string(area.x, area.y, self.elapsed, Style::default().fg(self.palette.text_muted));
+        buf.set_string(
+            area.x,
+            area.y,
+            self.elapsed,
+            Style::default().fg(self.palette.text_muted),
+        );
 
         // Progress bar with half-block precision
         let fill_exact = (bar_width as f32) * self.progress.clamp(0.0, 1.0);
@@ -34,12 +43,31 @@ impl<'a> Widget for SeekBar<'a> {
         for x in bar_start..bar_end {
             let offset = x.saturating_sub(bar_start);
             if offset < filled_full {
-                buf.set_string(x, area.y, "━", Style::default().fg(self.palette.progress_fill));
+                buf.set_string(
+                    x,
+                    area.y,
+                    &self.glyphs.progress_fill,
+                    Style::default().fg(self.palette.progress_fill),
+                );
             } else if offset == filled_full && fractional >= 0.5 && x < bar_end {
                 // Half-block transition at the fill edge
-                buf.set_string(x, area.y, "╸", Style::default().fg(self.palette.progress_fill));
+                buf.set_string(
+                    x,
+                    area.y,
+                    &self.glyphs.progress_edge,
+                    Style::default().fg(self.palette.progress_fill),
+                );
             } else {
-                buf.set_string(x, area.y, "─", dim_empty);
+                buf.set_string(x, area.y, &self.glyphs.progress_empty, dim_empty);
+            }
+        }
+
+        // Chapter ticks, under the playhead
+        let mark_style = Style::default().fg(self.palette.accent_2);
+        for mark in self.marks.iter().filter(|m| **m > 0.0 && **m < 1.0) {
+            let x = bar_start + ((bar_width as f32) * mark) as u16;
+            if x < bar_end {
+                buf.set_string(x, area.y, &self.glyphs.chapter_mark, mark_style);
             }
         }
 
@@ -49,7 +77,7 @@ impl<'a> Widget for SeekBar<'a> {
             buf.set_string(
                 playhead_pos,
                 area.y,
-                "●",
+                &self.glyphs.playhead,
                 Style::default().fg(self.palette.accent),
             );
         }
@@ -84,3 +112,50 @@ pub fn click_to_progress(
     }
     None
 }
+
+/// Like [`click_to_progress`], but columns left or right of the bar clamp to
+/// the start or the end, so a drag can overshoot.
+pub fn drag_to_progress(x: u16, bar_area: Rect, elapsed_width: u16, remaining_width: u16) -> f32 {
+    let bar_start = bar_area.x + elapsed_width + 1;
+    let bar_end = bar_area.x + bar_area.width.saturating_sub(remaining_width + 1);
+    let bar_width = bar_end.saturating_sub(bar_start);
+    if bar_width == 0 {
+        return 0.0;
+    }
+    (x.saturating_sub(bar_start).min(bar_width)) as f32 / bar_width as f32
+}
+
+/// Compact volume slider drawn in the Now Playing controls row.
+pub struct VolumeBar<'a> {
+    pub volume: f32,
+    pub palette: &'a Palette,
+    pub glyphs: &'a Glyphs,
+}
+
+impl<'a> Widget for VolumeBar<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let filled = (area.width as f32 * self.volume.clamp(0.0, 1.0)).round() as u16;
+        for offset in 0..area.width {
+            let (symbol, color) = if offset < filled {
+                (&self.glyphs.progress_fill, self.palette.progress_fill)
+            } else {
+                (&self.glyphs.progress_empty, self.palette.border_unfocused)
+            };
+            buf.set_string(area.x + offset, area.y, symbol, Style::default().fg(color));
+        }
+    }
+}
+
+/// Map a mouse x-coordinate to a volume (0.0-1.0) on a [`VolumeBar`].
+/// Columns left or right of the bar clamp to mute or full volume, so a drag
+/// can overshoot the ends.
+pub fn column_to_volume(x: u16, bar_area: Rect) -> f32 {
+    if bar_area.width == 0 || x < bar_area.x {
+        return 0.0;
+    }
+    let cells = (x - bar_area.x + 1).min(bar_area.width);
+    cells as f32 / bar_area.width as f32
+}
@@ -0,0 +1,448 @@
+use crate::shell::{ShellRootOptions, ShellSnapshot};
+
+/// How a setting is edited in the Settings dialog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingKind {
+    Toggle,
+    Choice(Vec<String>),
+    Number {
+        min: i64,
+        max: i64,
+        step: i64,
+    },
+    Text,
+    /// Comma-separated file extensions; may be empty.
+    Extensions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingItem {
+    /// Key sent to the app in `__setting_set <key> <value>`.
+    pub key: String,
+    pub label: String,
+    pub kind: SettingKind,
+    pub value: String,
+    pub note: Option<&'static str>,
+}
+
+impl SettingItem {
+    fn new(
+        key: impl Into<String>,
+        label: impl Into<String>,
+        kind: SettingKind,
+        value: impl Into<String>,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            label: label.into(),
+            kind,
+            value: value.into(),
+            note: None,
+        }
+    }
+
+    fn with_note(mut self, note: &'static str) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    /// Whether the value is typed into an editor rather than stepped through.
+    pub fn is_editable_text(&self) -> bool {
+        matches!(
+            self.kind,
+            SettingKind::Number { .. } | SettingKind::Text | SettingKind::Extensions
+        )
+    }
+
+    /// The value one step forward or back, or `None` for free text.
+    pub fn step(&self, forward: bool) -> Option<String> {
+        match &self.kind {
+            SettingKind::Toggle => Some((self.value != "true").to_string()),
+            SettingKind::Choice(options) if !options.is_empty() => {
+                let current = options.iter().position(|o| *o == self.value);
+                let next = match (current, forward) {
+                    (Some(i), true) => (i + 1) % options.len(),
+                    (Some(i), false) => (i + options.len() - 1) % options.len(),
+                    (None, _) => 0,
+                };
+                Some(options[next].clone())
+            }
+            SettingKind::Choice(_) => None,
+            SettingKind::Number { min, max, step } => {
+                let current = self.value.parse::<i64>().unwrap_or(*min);
+                let delta = if forward { *step } else { -step };
+                Some((current + delta).clamp(*min, *max).to_string())
+            }
+            SettingKind::Text | SettingKind::Extensions => None,
+        }
+    }
+
+    /// Checks a typed value before it is sent to the app.
+    pub fn validate(&self, raw: &str) -> Result<String, String> {
+        let raw = raw.trim();
+        match &self.kind {
+            SettingKind::Number { min, max, .. } => match raw.parse::<i64>() {
+                Ok(n) if (*min..=*max).contains(&n) => Ok(n.to_string()),
+                _ => Err(format!(
+                    "{} must be a number from {min} to {max}",
+                    self.label
+                )),
+            },
+            SettingKind::Text if raw.is_empty() => Err(format!("{} cannot be empty", self.label)),
+            SettingKind::Extensions => {
+                let mut extensions: Vec<String> = Vec::new();
+                for ext in raw.split(|c: char| c == ',' || c.is_whitespace()) {
+                    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+                    if ext.chars().any(|c| !c.is_ascii_alphanumeric()) {
+                        return Err(format!("{}: '{ext}' is not a file extension", self.label));
+                    }
+                    if !ext.is_empty() && !extensions.contains(&ext) {
+                        extensions.push(ext);
+                    }
+                }
+                Ok(extensions.join(","))
+            }
+            _ => Ok(raw.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsPage {
+    pub title: &'static str,
+    pub items: Vec<SettingItem>,
+}
+
+fn choices(options: &[&str]) -> SettingKind {
+    SettingKind::Choice(options.iter().map(|o| o.to_string()).collect())
+}
+
+/// The properties dialog for one library root. Keys are sent to the app in
+/// `__root_set <key> <value> <path>`.
+pub fn root_properties_page(options: &ShellRootOptions) -> SettingsPage {
+    SettingsPage {
+        title: "Scan Options",
+        items: vec![
+            SettingItem::new(
+                "recursive",
+                "Include Subfolders",
+                SettingKind::Toggle,
+                options.recursive.to_string(),
+            ),
+            SettingItem::new(
+                "include_extensions",
+                "Only Extensions",
+                SettingKind::Extensions,
+                &options.include_extensions,
+            )
+            .with_note("empty for all"),
+            SettingItem::new(
+                "exclude_extensions",
+                "Skip Extensions",
+                SettingKind::Extensions,
+                &options.exclude_extensions,
+            ),
+            SettingItem::new(
+                "auto_artwork",
+                "Folder Artwork",
+                SettingKind::Toggle,
+                options.auto_artwork.to_string(),
+            )
+            .with_note("cover.jpg, folder.png, ..."),
+            SettingItem::new(
+                "paused",
+                "Pause Watching",
+                SettingKind::Toggle,
+                options.paused.to_string(),
+            ),
+        ],
+    }
+}
+
+/// Settings dialog pages with the current values from the snapshot.
+pub fn settings_pages(snapshot: &ShellSnapshot) -> Vec<SettingsPage> {
+    const RESTART: &str = "restart to apply";
+    vec![
+        SettingsPage {
+            title: "Appearance",
+            items: vec![
+                SettingItem::new(
+                    "theme",
+                    "Theme",
+                    SettingKind::Choice(snapshot.available_themes.clone()),
+                    &snapshot.theme_name,
+                )
+                .with_note(RESTART),
+                SettingItem::new(
+                    "color_scheme",
+                    "Color Scheme",
+                    choices(&["dark", "light"]),
+                    &snapshot.setting_color_scheme,
+                )
+                .with_note(RESTART),
+                SettingItem::new(
+                    "color_mode",
+                    "Color Mode",
+                    choices(&["auto", "truecolor", "256", "16"]),
+                    &snapshot.setting_color_mode,
+                )
+                .with_note(RESTART),
+                SettingItem::new(
+                    "unicode_level",
+                    "Unicode Level",
+                    choices(&["auto", "full", "basic", "ascii"]),
+                    &snapshot.setting_unicode_level,
+                ),
+                SettingItem::new(
+                    "screen_reader",
+                    "Screen Reader Mode",
+                    choices(&["off", "on", "announce"]),
+                    &snapshot.setting_screen_reader,
+                ),
+                SettingItem::new(
+                    "use_theme_background",
+                    "Use Theme Background",
+                    SettingKind::Toggle,
+                    snapshot.setting_use_theme_bg.to_string(),
+                ),
+                SettingItem::new(
+                    "icon_pack",
+                    "Icon Pack",
+                    choices(&["nerd-font", "ascii"]),
+                    &snapshot.setting_icon_pack,
+                ),
+                SettingItem::new(
+                    "preferred_terminal_font",
+                    "Terminal Font",
+                    SettingKind::Text,
+                    &snapshot.preferred_terminal_font,
+                ),
+            ],
+        },
+        SettingsPage {
+            title: "Artwork",
+            items: vec![
+                SettingItem::new(
+                    "pixel_art_artwork",
+                    "Pixel Art Artwork",
+                    SettingKind::Toggle,
+                    snapshot.setting_pixel_art.to_string(),
+                ),
+                SettingItem::new(
+                    "pixel_art_cell_size",
+                    "Pixel Art Cell Size",
+                    SettingKind::Number {
+                        min: 1,
+                        max: 4,
+                        step: 1,
+                    },
+                    snapshot.setting_pixel_art_cell_size.to_string(),
+                ),
+            ],
+        },
+        SettingsPage {
+            title: "Playback",
+            items: vec![
+                SettingItem::new(
+                    "volume_step",
+                    "Volume Step (%)",
+                    SettingKind::Number {
+                        min: 1,
+                        max: 25,
+                        step: 1,
+                    },
+                    snapshot.setting_volume_step.to_string(),
+                ),
+                SettingItem::new(
+                    "resume_min_minutes",
+                    "Remember Position After (min)",
+                    SettingKind::Number {
+                        min: 0,
+                        max: 600,
+                        step: 5,
+                    },
+                    snapshot.setting_resume_min_minutes.to_string(),
+                )
+                .with_note("0 = only tracks set to remember"),
+                SettingItem::new(
+                    "shuffle_strategy",
+                    "Shuffle",
+                    choices(&["random", "weighted"]),
+                    &snapshot.setting_shuffle_strategy,
+                ),
+                SettingItem::new(
+                    "shuffle_rating_weight",
+                    "Favor High Ratings",
+                    SettingKind::Number {
+                        min: 0,
+                        max: 10,
+                        step: 1,
+                    },
+                    snapshot.setting_shuffle_rating_weight.to_string(),
+                )
+                .with_note("weighted shuffle"),
+                SettingItem::new(
+                    "shuffle_recency_weight",
+                    "Favor Not Played Lately",
+                    SettingKind::Number {
+                        min: 0,
+                        max: 10,
+                        step: 1,
+                    },
+                    snapshot.setting_shuffle_recency_weight.to_string(),
+                )
+                .with_note("weighted shuffle"),
+                SettingItem::new(
+                    "normalization",
+                    "Loudness Normalization",
+                    choices(&["off", "track", "album"]),
+                    &snapshot.setting_normalization,
+                )
+                .with_note("analyze loudness measures tracks"),
+                SettingItem::new(
+                    "normalization_target_lufs",
+                    "Normalization Target (LUFS)",
+                    SettingKind::Number {
+                        min: -30,
+                        max: -5,
+                        step: 1,
+                    },
+                    snapshot.setting_normalization_target_lufs.to_string(),
+                ),
+                SettingItem::new(
+                    "trim_silence",
+                    "Trim Silence",
+                    SettingKind::Toggle,
+                    snapshot.setting_trim_silence.to_string(),
+                )
+                .with_note("analyze silence finds it"),
+                SettingItem::new(
+                    "silence_threshold_db",
+                    "Silence Threshold (dB)",
+                    SettingKind::Number {
+                        min: -90,
+                        max: -20,
+                        step: 5,
+                    },
+                    snapshot.setting_silence_threshold_db.to_string(),
+                ),
+                SettingItem::new(
+                    "silence_min_ms",
+                    "Shortest Trimmed Silence (ms)",
+                    SettingKind::Number {
+                        min: 0,
+                        max: 10_000,
+                        step: 250,
+                    },
+                    snapshot.setting_silence_min_ms.to_string(),
+                ),
+                SettingItem::new(
+                    "crossfeed",
+                    "Headphone Crossfeed",
+                    SettingKind::Toggle,
+                    snapshot.setting_crossfeed.to_string(),
+                )
+                .with_note("for headphones"),
+                SettingItem::new(
+                    "max_volume",
+                    "Max Volume (%)",
+                    SettingKind::Number {
+                        min: 10,
+                        max: 100,
+                        step: 5,
+                    },
+                    snapshot.setting_max_volume.to_string(),
+                ),
+                SettingItem::new(
+                    "limiter",
+                    "Limiter",
+                    SettingKind::Toggle,
+                    snapshot.setting_limiter.to_string(),
+                )
+                .with_note("eases peaks instead of clipping"),
+            ],
+        },
+        SettingsPage {
+            title: "Library",
+            items: vec![SettingItem::new(
+                "folder_playlists",
+                "Folders as Playlists",
+                SettingKind::Toggle,
+                snapshot.setting_folder_playlists.to_string(),
+            )
+            .with_note("read-only; c copies one")],
+        },
+        SettingsPage {
+            title: "Features",
+            items: snapshot
+                .feature_summary
+                .iter()
+                .map(|(name, enabled)| {
+                    SettingItem::new(
+                        format!("feature.{name}"),
+                        name.replace('_', " "),
+                        SettingKind::Toggle,
+                        enabled.to_string(),
+                    )
+                })
+                .collect(),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(kind: SettingKind, value: &str) -> SettingItem {
+        SettingItem::new("k", "Value", kind, value)
+    }
+
+    #[test]
+    fn stepping_wraps_choices_and_clamps_numbers() {
+        let choice = item(choices(&["a", "b", "c"]), "c");
+        assert_eq!(choice.step(true).as_deref(), Some("a"));
+        assert_eq!(choice.step(false).as_deref(), Some("b"));
+
+        let number = item(
+            SettingKind::Number {
+                min: 1,
+                max: 4,
+                step: 1,
+            },
+            "4",
+        );
+        assert_eq!(number.step(true).as_deref(), Some("4"));
+        assert_eq!(number.step(false).as_deref(), Some("3"));
+
+        assert_eq!(
+            item(SettingKind::Toggle, "true").step(true).as_deref(),
+            Some("false")
+        );
+        assert_eq!(item(SettingKind::Text, "x").step(true), None);
+    }
+
+    #[test]
+    fn typed_values_are_validated() {
+        let number = item(
+            SettingKind::Number {
+                min: 1,
+                max: 25,
+                step: 1,
+            },
+            "5",
+        );
+        assert_eq!(number.validate(" 10 "), Ok("10".to_string()));
+        assert!(number.validate("30").is_err());
+        assert!(number.validate("ten").is_err());
+        assert!(item(SettingKind::Text, "x").validate("  ").is_err());
+
+        let extensions = item(SettingKind::Extensions, "");
+        assert_eq!(
+            extensions.validate(".FLAC, wav flac"),
+            Ok("flac,wav".to_string())
+        );
+        assert_eq!(extensions.validate("  "), Ok(String::new()));
+        assert!(extensions.validate("mp3;ogg").is_err());
+    }
+}
@@ -1,25 +1,36 @@
+use crate::event_log::{EventLog, LogLevel};
+use crate::footer;
+use crate::glyphs::{Glyphs, UnicodeLevel};
+use crate::layout::{AppLayout, LayoutVariant, PanelLayout};
+use crate::scheduler::{FrameScheduler, Pace};
+use crate::text::{
+    display_width, pad_to_width, scroll_to_width, truncate_start_to_width, truncate_to_width,
+};
+use crate::text_input::TextInput;
 use crate::theme::Palette;
 use crate::UiError;
 use crossterm::event::{
-    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
-    Event, KeyCode, KeyEvent, KeyEventKind,
-    KeyModifiers, MouseEvent, MouseEventKind,
+    self, EnableBracketedPaste, EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyEvent,
+    KeyEventKind, KeyModifiers, MediaKeyCode, MouseButton, MouseEvent, MouseEventKind,
 };
 use crossterm::execute;
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
+use crossterm::terminal::{enable_raw_mode, EnterAlternateScreen};
 use ratatui::backend::{CrosstermBackend, TestBackend};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph, Wrap,
+};
 use ratatui::{Frame, Terminal};
 use std::cmp::min;
 use std::io::{self, Stdout};
 use std::time::{Duration, Instant};
 use tachyonfx::{fx, EffectTimer, Interpolation};
 
+/// Maximum gap between two clicks on the same track row to count as a double-click.
+const DOUBLE_CLICK_WINDOW: Duration = Duration::from_millis(400);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IconMode {
     NerdFont,
@@ -35,11 +46,34 @@ impl IconMode {
     }
 }
 
+/// Output for terminal screen readers. Any mode but `Off` draws in ASCII
+/// and holds still: no visualizer, no fades, and a clock that moves in
+/// ten-second steps. `Announce` also writes track changes and pauses to a
+/// line of their own at the bottom of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenReaderMode {
+    Off,
+    On,
+    Announce,
+}
+
+impl ScreenReaderMode {
+    pub fn from_config(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "on" | "true" => Self::On,
+            "announce" => Self::Announce,
+            _ => Self::Off,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusPane {
     Sources,
     Browse,
     Tracks,
+    /// The queue or playlist beside the track list.
+    Split,
     Inspector,
 }
 
@@ -48,7 +82,8 @@ impl FocusPane {
         match self {
             Self::Sources => Self::Browse,
             Self::Browse => Self::Tracks,
-            Self::Tracks => Self::Inspector,
+            Self::Tracks => Self::Split,
+            Self::Split => Self::Inspector,
             Self::Inspector => Self::Sources,
         }
     }
@@ -58,8 +93,59 @@ impl FocusPane {
             Self::Sources => Self::Inspector,
             Self::Browse => Self::Sources,
             Self::Tracks => Self::Browse,
-            Self::Inspector => Self::Tracks,
+            Self::Split => Self::Tracks,
+            Self::Inspector => Self::Split,
+        }
+    }
+}
+
+/// Deepest navigation steps Escape can walk back through.
+const NAV_HISTORY: usize = 32;
+
+/// Tabs are switched with Alt-1 to Alt-9.
+const MAX_TABS: usize = 9;
+
+/// Where the user was: the focused pane, the browse view with its cursor,
+/// and the track cursor, so Escape can return there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NavEntry {
+    focus: FocusPane,
+    browse_mode_index: usize,
+    browse_show_items: bool,
+    browse_item_index: usize,
+    filter_artist: Option<String>,
+    filter_album: Option<String>,
+    selected_track: usize,
+}
+
+/// Everything a tab keeps of its own: the view, the track filter and sort,
+/// and the way back.
+#[derive(Debug, Clone)]
+struct ViewTab {
+    view: NavEntry,
+    filter: TextInput,
+    sort_column: SortColumn,
+    sort_ascending: bool,
+    history: Vec<NavEntry>,
+}
+
+impl ViewTab {
+    fn label(&self) -> String {
+        if let Some(name) = self
+            .view
+            .filter_album
+            .as_ref()
+            .or(self.view.filter_artist.as_ref())
+        {
+            return name.clone();
         }
+        if !self.filter.is_empty() {
+            return format!("/{}", self.filter);
+        }
+        crate::browse::BrowseMode::all()
+            .get(self.view.browse_mode_index)
+            .map_or("Songs", |mode| mode.label())
+            .to_string()
     }
 }
 
@@ -81,6 +167,117 @@ pub struct ShellTrackItem {
     pub sample_rate: Option<i64>,
     pub channels: Option<i64>,
     pub bit_depth: Option<i64>,
+    /// Estimated tempo, once the track has been analyzed.
+    pub bpm: Option<u16>,
+}
+
+/// A library root's scan options as shown in its properties dialog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellRootOptions {
+    pub recursive: bool,
+    /// Comma-separated, empty for none.
+    pub include_extensions: String,
+    pub exclude_extensions: String,
+    pub auto_artwork: bool,
+    pub paused: bool,
+}
+
+/// A podcast subscription and its newest episodes, for the podcasts dialog.
+#[derive(Debug, Clone)]
+pub struct ShellPodcast {
+    pub id: String,
+    pub title: String,
+    /// Episode counts, e.g. `12 episodes, 3 unplayed`.
+    pub detail: String,
+    pub episodes: Vec<ShellEpisode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShellEpisode {
+    pub id: String,
+    pub title: String,
+    /// Publish date and length.
+    pub detail: String,
+    /// Where playback will resume; zero when unstarted.
+    pub position_ms: u64,
+    pub downloaded: bool,
+    pub played: bool,
+}
+
+/// Listening history and library totals for the stats dashboard (`T`).
+#[derive(Debug, Clone, Default)]
+pub struct ShellListeningStats {
+    /// Time listened on each of the last days and weeks, oldest first, by
+    /// the `YYYY-MM-DD` date each starts on.
+    pub days: Vec<(String, u64)>,
+    pub weeks: Vec<(String, u64)>,
+    /// Names with how often they were played, most played first. Albums and
+    /// tracks carry their artist, e.g. `Blue Train — John Coltrane`.
+    pub top_artists: Vec<(String, u64)>,
+    pub top_albums: Vec<(String, u64)>,
+    pub top_tracks: Vec<(String, u64)>,
+    pub plays: u64,
+    pub listened_ms: u64,
+    /// Tracks per format, e.g. `FLAC`, most common first.
+    pub formats: Vec<(String, u64)>,
+    /// Tracks added in each `YYYY-MM` month that had any, oldest first.
+    pub added_by_month: Vec<(String, u64)>,
+}
+
+/// A year-in-review summary of the play history over a range of dates,
+/// from `review` in the palette or `y` in the stats dashboard.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellReview {
+    /// The range as `review` takes it, e.g. `2025` or `2025-03`.
+    pub range: String,
+    /// `2025`, `March 2025` or `2025-01-01 to 2025-06-30`.
+    pub title: String,
+    /// The ranges a year or month before and after, for stepping through.
+    pub previous: Option<String>,
+    pub next: Option<String>,
+    pub plays: u64,
+    pub listened_ms: u64,
+    /// Different tracks and artists played.
+    pub tracks: u64,
+    pub artists: u64,
+    /// The month listened to most, e.g. `March 2025`.
+    pub top_month: Option<(String, u64)>,
+    /// Most played first; albums and tracks carry their artist.
+    pub top_artists: Vec<(String, u64)>,
+    pub top_albums: Vec<(String, u64)>,
+    pub top_tracks: Vec<(String, u64)>,
+    /// Time listened in each `YYYY-MM` month that had any, oldest first.
+    pub months: Vec<(String, u64)>,
+}
+
+/// The library health report (`H`): each kind of problem with the tracks
+/// that have it.
+#[derive(Debug, Clone, Default)]
+pub struct ShellHealthReport {
+    pub issues: Vec<ShellHealthIssue>,
+    /// When the files were last checked, e.g. `2026-10-18`; empty if never.
+    pub checked: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShellHealthIssue {
+    pub label: String,
+    pub count: usize,
+    /// Only found by checking the files, so unknown until they have been.
+    pub needs_check: bool,
+    /// What the bulk fix does and the palette command that runs it.
+    pub fix: Option<(String, String)>,
+    /// The first tracks with the issue, by path.
+    pub tracks: Vec<ShellHealthTrack>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShellHealthTrack {
+    pub path: String,
+    /// `Artist — Title`, as the library has it.
+    pub title: String,
+    /// What the check found, e.g. `no album` or `96 kbps`; may be empty.
+    pub detail: String,
 }
 
 #[derive(Debug, Clone)]
@@ -93,11 +290,18 @@ pub struct ShellSnapshot {
     pub preferred_terminal_font: String,
     pub mouse_enabled: bool,
     pub artwork_filter: String,
+    /// Album rows show cover thumbnails.
+    pub show_artwork: bool,
     pub pixel_art_enabled: bool,
     pub pixel_art_cell_size: u16,
     pub roots: Vec<ShellListItem>,
+    /// Options for each entry of `roots`, in the same order.
+    pub root_options: Vec<ShellRootOptions>,
     pub playlists: Vec<ShellListItem>,
     pub tracks: Vec<ShellTrackItem>,
+    pub podcasts: Vec<ShellPodcast>,
+    pub listening_stats: ShellListeningStats,
+    pub health: ShellHealthReport,
     pub feature_summary: Vec<(String, bool)>,
     pub status_lines: Vec<String>,
     pub playback_status: String,
@@ -108,20 +312,73 @@ pub struct ShellSnapshot {
     pub now_playing_artwork: Option<Vec<u8>>,
     pub now_playing_duration_ms: u64,
     pub now_playing_position_ms: u64,
+    /// The playing track resumes where it stopped, and the seek keys take
+    /// audiobook-sized steps.
+    pub now_playing_remembers_position: bool,
     pub volume: f32,
+    /// The playing track's and the queue's volume offsets together, in dB.
+    pub volume_offset_db: f32,
     pub shuffle: bool,
+    /// A random album follows when the current one ends.
+    pub random_album: bool,
+    /// Similar artists are queued when the queue runs out.
+    pub radio: bool,
+    /// Tracks leave the queue once playback moves past them.
+    pub consume: bool,
+    /// Guests may only add to the queue; see [`ShellState::handle_party_key`].
+    pub party_mode: bool,
     pub repeat_mode: String,
     pub queue_length: usize,
     pub queue_position: usize,
+    /// The next tracks in play order; empty when shuffle picks as it goes.
+    pub up_next: Vec<ShellTrackItem>,
+    /// Chapter starts in the playing track and their titles, from a CUE
+    /// sheet beside it.
+    pub now_playing_chapters: Vec<(u64, String)>,
     pub artists: Vec<String>,
     pub albums: Vec<(String, String)>,
+    /// The last tracks played, newest first, for the browse pane's Recent list.
+    pub recent_tracks: Vec<ShellTrackItem>,
     pub total_track_count: usize,
+    /// Length of the whole library.
+    pub total_duration_ms: u64,
+    /// What the split view shows: `queue` or a playlist id.
+    pub split_source: String,
+    pub split_title: String,
+    pub split_tracks: Vec<ShellTrackItem>,
     pub setting_use_theme_bg: bool,
     pub setting_icon_pack: String,
     pub setting_pixel_art: bool,
     pub setting_pixel_art_cell_size: u16,
     pub setting_color_scheme: String,
+    pub setting_color_mode: String,
+    pub setting_unicode_level: String,
+    pub setting_screen_reader: String,
+    pub setting_volume_step: u16,
+    pub setting_folder_playlists: bool,
+    pub setting_resume_min_minutes: u16,
+    pub setting_shuffle_strategy: String,
+    pub setting_shuffle_rating_weight: u16,
+    pub setting_shuffle_recency_weight: u16,
+    pub setting_normalization: String,
+    pub setting_normalization_target_lufs: i16,
+    pub setting_trim_silence: bool,
+    pub setting_silence_threshold_db: i16,
+    pub setting_silence_min_ms: u16,
+    pub setting_crossfeed: bool,
+    pub setting_max_volume: u16,
+    pub setting_limiter: bool,
+    /// Footer templates; see [`crate::footer`].
+    pub setting_footer_top: String,
+    pub setting_footer_bottom: String,
+    /// The local time zone's offset from UTC, for the footer clock.
+    pub utc_offset_minutes: i32,
     pub available_themes: Vec<String>,
+    pub panel_layout: PanelLayout,
+    /// Bookmarked and recently added folders for the Add Music browser.
+    pub browser_places: crate::file_browser::BrowserPlaces,
+    /// How long each database query behind this snapshot took.
+    pub db_timings: Vec<(String, Duration)>,
 }
 
 #[derive(Debug, Clone)]
@@ -131,29 +388,89 @@ pub struct ShellState {
     pub selected_root: usize,
     pub selected_playlist: usize,
     pub selected_track: usize,
-    pub track_filter_query: String,
-    pub command_palette_input: String,
+    pub selected_split: usize,
+    pub track_filter_query: TextInput,
+    pub command_palette_input: TextInput,
+    /// The passphrase typed to quit during party mode; shown masked.
+    pub party_passphrase_input: TextInput,
+    /// The name typed after choosing "+ New Playlist".
+    pub new_playlist_input: TextInput,
+    /// A playlist just created from the sidebar, selected once it shows up
+    /// in a snapshot.
+    pending_playlist_select: Option<String>,
+    /// The palette command run when the question in the status line is
+    /// answered with `y`.
+    pending_confirm: Option<String>,
+    /// Kept here rather than read from each snapshot so changes show at
+    /// once; saved through `__places_save`.
+    browser_places: crate::file_browser::BrowserPlaces,
+    /// Cover thumbnails for the Albums list, fetched through `__album_art`.
+    pub album_thumbnails: crate::artwork::Thumbnails,
     pub status_message: Option<String>,
-    pub show_help: bool,
+    pub panel_layout: PanelLayout,
     roots_scroll: usize,
     playlists_scroll: usize,
     tracks_scroll: usize,
+    /// How far the selected track's title and album are scrolled, and which
+    /// track that is; moving the selection starts the next row unscrolled.
+    title_scroll: (usize, usize),
+    split_scroll: usize,
     input_mode: InputMode,
+    help_scroll: usize,
+    help_filter: TextInput,
+    help_filter_focused: bool,
+    /// Warnings and errors shown by the log dialog (`L`).
+    pub event_log: EventLog,
+    log_scroll: usize,
+    /// `EventLog::pushed` when the log dialog was last opened.
+    log_seen: u64,
+    /// Requests from the remote-control socket, handled every tick.
+    pub remote_inbox: crate::remote_inbox::RemoteInbox,
     filtered_track_indices: Vec<usize>,
     file_browser: Option<crate::file_browser::FileBrowser>,
     terminal_caps: crate::terminal_caps::TerminalCaps,
-    scanning_path: Option<String>,
+    /// Cleared while the terminal reports that it lost focus.
+    terminal_focused: bool,
+    /// A background job is running.
+    scanning: bool,
     sort_column: SortColumn,
-    last_click: Option<(Instant, u16, u16)>,
+    /// Time, row and item index of the last left click in the track list or
+    /// file browser, for double-click detection.
+    last_click: Option<(Instant, u16, usize)>,
     sort_ascending: bool,
     pub playback_position_ms: u64,
     pub playback_duration_ms: u64,
     pub playback_status: String,
     pub seek_bar_area: Rect,
+    pub volume_bar_area: Rect,
+    /// The queue line in Now Playing; a click opens the queue.
+    queue_line_area: Rect,
+    /// Where the mouse pointer was last seen, for hover highlighting.
+    mouse_hover: Option<(u16, u16)>,
+    /// Footer hints drawn in the last frame and the keys a click on each
+    /// presses.
+    hint_areas: Vec<(Rect, Vec<crate::input::ChordKey>)>,
+    /// Set while the left button is held after pressing on the volume bar.
+    volume_dragging: bool,
+    /// The seek bar column under the pointer and the time there, shown as
+    /// a tooltip while hovering or dragging.
+    seek_preview: Option<(u16, u64)>,
+    /// Set while the left button is held after pressing on the seek bar;
+    /// the seek happens on release.
+    seek_dragging: bool,
+    /// Set while the left button is held after pressing on a split row.
+    split_drag: Option<SplitDrag>,
     pub artwork: crate::artwork::ArtworkState,
     pub browse: crate::browse::BrowseState,
     browse_filter_artist: Option<String>,
     browse_filter_album: Option<String>,
+    /// Views left by changing focus or drilling into the browser, most
+    /// recent last.
+    nav_history: Vec<NavEntry>,
+    /// Open tabs, empty while there is only one. The active tab's entry is
+    /// stale; its state lives in the fields above until it is switched away.
+    tabs: Vec<ViewTab>,
+    active_tab: usize,
     pub spectrum_bands: Vec<f32>,
     pub viz_samples: Vec<f32>,
     pub viz_style: crate::visualizer::VisualizerStyle,
@@ -161,32 +478,72 @@ pub struct ShellState {
     pub viz_area: Rect,
     pub fire_history: Vec<Vec<f32>>,
     pub track_change_time: Option<Instant>,
+    /// The last state change written to the screen reader line, and the
+    /// (track, status) it describes.
+    announcement: Option<String>,
+    announced: (String, String),
+    /// Toggled with F12.
+    pub show_debug: bool,
+    pub debug_stats: crate::debug_overlay::DebugStats,
     last_track_path: String,
     track_info_artwork: Option<Vec<u8>>,
     track_info_art_state: crate::artwork::ArtworkState,
+    settings_page: usize,
     settings_index: usize,
+    /// Value being typed for a number or text setting.
+    settings_editor: Option<TextInput>,
+    /// Index into `snapshot.roots` when the dialog shows that folder's
+    /// properties instead of the app settings.
+    settings_root: Option<usize>,
+    podcast_panel: crate::podcasts::PodcastPanel,
+    health_panel: crate::health::HealthPanel,
+    /// What the year-in-review dialog shows.
+    review: Option<ShellReview>,
+    chords: crate::input::ChordMatcher<ChordAction>,
 }
 
 impl ShellState {
     pub fn new(snapshot: ShellSnapshot) -> Self {
+        let panel_layout = snapshot.panel_layout.clamped();
+        let browser_places = snapshot.browser_places.clone();
         let mut state = Self {
             snapshot,
             focus: FocusPane::Tracks,
             selected_root: 0,
             selected_playlist: 0,
             selected_track: 0,
-            track_filter_query: String::new(),
-            command_palette_input: String::new(),
-            status_message: Some(default_status_message().to_string()),
-            show_help: false,
+            selected_split: 0,
+            track_filter_query: TextInput::new(),
+            command_palette_input: TextInput::new(),
+            party_passphrase_input: TextInput::new(),
+            new_playlist_input: TextInput::new(),
+            pending_playlist_select: None,
+            pending_confirm: None,
+            browser_places,
+            album_thumbnails: crate::artwork::Thumbnails::default(),
+            status_message: None,
+            panel_layout,
             roots_scroll: 0,
             playlists_scroll: 0,
             tracks_scroll: 0,
+            title_scroll: (0, 0),
+            split_scroll: 0,
             input_mode: InputMode::Normal,
+            help_scroll: 0,
+            help_filter: TextInput::new(),
+            help_filter_focused: false,
+            event_log: EventLog::new(),
+            log_scroll: 0,
+            log_seen: 0,
+            remote_inbox: crate::remote_inbox::RemoteInbox::new(),
             filtered_track_indices: Vec::new(),
             file_browser: None,
             terminal_caps: crate::terminal_caps::TerminalCaps::detect(),
-            scanning_path: None,
+            terminal_focused: true,
+            scanning: false,
+            podcast_panel: crate::podcasts::PodcastPanel::default(),
+            health_panel: crate::health::HealthPanel::default(),
+            review: None,
             sort_column: SortColumn::Title,
             sort_ascending: true,
             last_click: None,
@@ -194,10 +551,21 @@ impl ShellState {
             playback_duration_ms: 0,
             playback_status: "stopped".to_string(),
             seek_bar_area: Rect::default(),
+            queue_line_area: Rect::default(),
+            mouse_hover: None,
+            hint_areas: Vec::new(),
+            volume_bar_area: Rect::default(),
+            volume_dragging: false,
+            seek_preview: None,
+            seek_dragging: false,
+            split_drag: None,
             artwork: crate::artwork::ArtworkState::new(),
             browse: crate::browse::BrowseState::new(),
             browse_filter_artist: None,
             browse_filter_album: None,
+            nav_history: Vec::new(),
+            tabs: Vec::new(),
+            active_tab: 0,
             spectrum_bands: vec![0.0; 32],
             viz_samples: Vec::new(),
             viz_style: crate::visualizer::VisualizerStyle::Spectrum,
@@ -205,10 +573,18 @@ impl ShellState {
             viz_area: Rect::default(),
             fire_history: Vec::new(),
             track_change_time: None,
+            announcement: None,
+            announced: (String::new(), String::new()),
+            show_debug: false,
+            debug_stats: crate::debug_overlay::DebugStats::default(),
             last_track_path: String::new(),
             track_info_artwork: None,
             track_info_art_state: crate::artwork::ArtworkState::new(),
+            settings_page: 0,
             settings_index: 0,
+            settings_editor: None,
+            settings_root: None,
+            chords: normal_mode_chords(),
         };
         state.rebuild_track_filter();
         // Auto-trigger welcome panel on empty library
@@ -228,9 +604,27 @@ impl ShellState {
         self.selected_root = self
             .selected_root
             .min(self.snapshot.roots.len().saturating_sub(1));
-        self.selected_playlist = self
-            .selected_playlist
-            .min(self.snapshot.playlists.len().saturating_sub(1));
+        self.selected_playlist = match self.pending_playlist_select.take() {
+            Some(name) => match self.snapshot.playlists.iter().position(|p| p.label == name) {
+                Some(index) => index,
+                None => {
+                    self.pending_playlist_select = Some(name);
+                    self.selected_playlist
+                }
+            },
+            None => self.selected_playlist,
+        }
+        .min(self.playlist_rows() - 1);
+        self.selected_split = self
+            .selected_split
+            .min(self.snapshot.split_tracks.len().saturating_sub(1));
+        self.podcast_panel.clamp(&self.snapshot.podcasts);
+        self.health_panel.clamp(&self.snapshot.health.issues);
+        if self.browse.mode == crate::browse::BrowseMode::Recent && self.browse.show_items {
+            let index = self.browse.item_index;
+            self.browse.items = self.recent_labels();
+            self.browse.item_index = index.min(self.browse.items.len().saturating_sub(1));
+        }
         self.rebuild_track_filter();
         // Trigger fade when a new track starts playing.
         if incoming_status == "playing"
@@ -262,9 +656,13 @@ impl ShellState {
                     delta,
                 );
             }
+            FocusPane::Split => {
+                self.selected_split =
+                    shift_index(self.selected_split, self.snapshot.split_tracks.len(), delta);
+            }
             FocusPane::Inspector => {
                 self.selected_playlist =
-                    shift_index(self.selected_playlist, self.snapshot.playlists.len(), delta);
+                    shift_index(self.selected_playlist, self.playlist_rows(), delta);
             }
         }
     }
@@ -281,6 +679,7 @@ impl ShellState {
                 }
             }
             FocusPane::Tracks => self.selected_track = 0,
+            FocusPane::Split => self.selected_split = 0,
             FocusPane::Inspector => self.selected_playlist = 0,
         }
     }
@@ -300,9 +699,10 @@ impl ShellState {
             FocusPane::Tracks => {
                 self.selected_track = self.filtered_track_indices.len().saturating_sub(1)
             }
-            FocusPane::Inspector => {
-                self.selected_playlist = self.snapshot.playlists.len().saturating_sub(1)
+            FocusPane::Split => {
+                self.selected_split = self.snapshot.split_tracks.len().saturating_sub(1)
             }
+            FocusPane::Inspector => self.selected_playlist = self.playlist_rows() - 1,
         }
     }
 
@@ -310,6 +710,13 @@ impl ShellState {
         if key.kind != KeyEventKind::Press {
             return KeyAction::Continue;
         }
+        if key.code == KeyCode::F(12) {
+            self.show_debug = !self.show_debug;
+            return KeyAction::Continue;
+        }
+        if let KeyCode::Media(media) = key.code {
+            return self.media_key_action(media);
+        }
 
         match self.input_mode {
             InputMode::TrackInfo => {
@@ -325,23 +732,109 @@ impl ShellState {
             InputMode::CommandPalette => return self.handle_command_palette_key(key),
             InputMode::AddMusic | InputMode::Welcome => return self.handle_add_music_key(key),
             InputMode::Settings => return self.handle_settings_key(key),
+            InputMode::Help => return self.handle_help_key(key),
+            InputMode::Log => return self.handle_log_key(key),
+            InputMode::Podcasts => return self.handle_podcasts_key(key),
+            InputMode::Stats => {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('T') | KeyCode::Char('q') => {
+                        self.input_mode = InputMode::Normal;
+                    }
+                    KeyCode::Char('y') => return KeyAction::CommandSubmitted("review".to_string()),
+                    _ => {}
+                }
+                return KeyAction::Continue;
+            }
+            InputMode::Review => return self.handle_review_key(key),
+            InputMode::Health => return self.handle_health_key(key),
+            InputMode::PartyUnlock => return self.handle_party_unlock_key(key),
+            InputMode::NewPlaylist => return self.handle_new_playlist_key(key),
+            InputMode::Confirm => return self.handle_confirm_key(key),
             InputMode::Normal => {}
         }
 
+        if self.chords.is_pending() && key.code == KeyCode::Esc {
+            self.chords.cancel();
+            self.status_message = None;
+            return KeyAction::Continue;
+        }
+        let was_pending = self.chords.is_pending();
+        match self
+            .chords
+            .feed(crate::input::ChordKey::from_event(&key), Instant::now())
+        {
+            crate::input::ChordResult::Pending => {
+                self.status_message = self.chords.pending_label().map(|keys| format!("{keys} …"));
+                return KeyAction::Continue;
+            }
+            crate::input::ChordResult::Matched(action) => {
+                self.status_message = None;
+                return self.apply_chord_action(action);
+            }
+            crate::input::ChordResult::Passthrough => {
+                if was_pending {
+                    self.status_message = None;
+                }
+            }
+        }
+
+        if self.snapshot.party_mode {
+            if let Some(action) = self.handle_party_key(&key) {
+                return action;
+            }
+        }
+
         match key.code {
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 return KeyAction::Quit;
             }
             KeyCode::Char('q') => return KeyAction::Quit,
-            KeyCode::Tab => self.focus = self.focus.next(),
-            KeyCode::BackTab => self.focus = self.focus.prev(),
-            KeyCode::Char('?') => self.show_help = !self.show_help,
-            KeyCode::Esc if self.show_help => self.show_help = false,
+            KeyCode::Esc => self.navigate_back(),
+            KeyCode::Tab => self.cycle_focus(true),
+            KeyCode::BackTab => self.cycle_focus(false),
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.panel_layout.resize_sidebar(-2);
+                return self.layout_changed();
+            }
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.panel_layout.resize_sidebar(2);
+                return self.layout_changed();
+            }
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.panel_layout.resize_now_playing(-1);
+                return self.layout_changed();
+            }
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.panel_layout.resize_now_playing(1);
+                return self.layout_changed();
+            }
+            KeyCode::Char('m') => {
+                self.panel_layout.mini = !self.panel_layout.mini;
+                return self.layout_changed();
+            }
+            KeyCode::Char('?') => {
+                self.help_scroll = 0;
+                self.help_filter.clear();
+                self.help_filter_focused = false;
+                self.input_mode = InputMode::Help;
+            }
+            KeyCode::Char('L') => self.open_log(),
+            KeyCode::Char('P') => {
+                self.input_mode = InputMode::Podcasts;
+                return KeyAction::RefreshRequested;
+            }
+            KeyCode::Char('T') => {
+                self.input_mode = InputMode::Stats;
+                return KeyAction::RefreshRequested;
+            }
+            KeyCode::Char('H') => {
+                self.input_mode = InputMode::Health;
+                return KeyAction::RefreshRequested;
+            }
             KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
             KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
             KeyCode::PageDown => self.move_selection(10),
             KeyCode::PageUp => self.move_selection(-10),
-            KeyCode::Char('g') => self.move_to_start(),
             KeyCode::Char('G') => self.move_to_end(),
             KeyCode::Char('/') if self.focus == FocusPane::Tracks => self.enter_track_filter_mode(),
             KeyCode::Char(':') => self.enter_command_palette_mode(),
@@ -349,10 +842,30 @@ impl ShellState {
                 self.enter_command_palette_mode()
             }
             KeyCode::Char('r') => return KeyAction::RefreshRequested,
+            KeyCode::Enter
+                if self.focus == FocusPane::Browse && self.selected_recent_track().is_some() =>
+            {
+                if let Some(track) = self.selected_recent_track() {
+                    return KeyAction::CommandSubmitted(format!("__play_now {}", track.path));
+                }
+            }
+            KeyCode::Char('a')
+                if self.focus == FocusPane::Browse && self.selected_recent_track().is_some() =>
+            {
+                if let Some(track) = self.selected_recent_track() {
+                    return KeyAction::Playback(PlaybackAction::Enqueue {
+                        path: track.path.clone(),
+                        requested_by: "keyboard".to_string(),
+                    });
+                }
+            }
             KeyCode::Char('a') => {
-                self.file_browser = Some(crate::file_browser::FileBrowser::new(
-                    &home_dir().unwrap_or_else(|| std::path::PathBuf::from("/")),
-                ));
+                let start = self
+                    .browser_places
+                    .start_dir()
+                    .or_else(home_dir)
+                    .unwrap_or_else(|| std::path::PathBuf::from("/"));
+                self.file_browser = Some(crate::file_browser::FileBrowser::new(&start));
                 self.input_mode = InputMode::AddMusic;
             }
             KeyCode::Enter | KeyCode::Char('l') if self.focus == FocusPane::Browse => {
@@ -366,6 +879,104 @@ impl ShellState {
                     track_index: self.selected_track,
                 });
             }
+            KeyCode::Char('h' | 'l') | KeyCode::Left | KeyCode::Right
+                if self.focus == FocusPane::Tracks
+                    && key
+                        .modifiers
+                        .intersects(KeyModifiers::ALT | KeyModifiers::SHIFT) =>
+            {
+                let forward = matches!(key.code, KeyCode::Char('l') | KeyCode::Right);
+                self.scroll_title(forward);
+            }
+            KeyCode::Enter if self.focus == FocusPane::Inspector => {
+                match self.snapshot.playlists.get(self.selected_playlist) {
+                    Some(playlist) => {
+                        return KeyAction::CommandSubmitted(format!(
+                            "playlist play {}",
+                            playlist.id
+                        ));
+                    }
+                    None => self.open_new_playlist_prompt(),
+                }
+            }
+            KeyCode::Char('c') if self.focus == FocusPane::Sources => {
+                match self.snapshot.roots.get(self.selected_root) {
+                    Some(root) => {
+                        return KeyAction::CommandSubmitted(format!(
+                            "playlist copy folder:{}",
+                            root.id
+                        ));
+                    }
+                    None => self.status_message = Some("No library root selected".to_string()),
+                }
+            }
+            KeyCode::Char('E') if self.focus == FocusPane::Sources => {
+                match self.snapshot.roots.get(self.selected_root) {
+                    Some(root) => {
+                        return KeyAction::CommandSubmitted(format!(
+                            "__queue_folder {} {} {}",
+                            root.id,
+                            self.sort_column.label(),
+                            if self.sort_ascending { "asc" } else { "desc" }
+                        ));
+                    }
+                    None => self.status_message = Some("No library root selected".to_string()),
+                }
+            }
+            KeyCode::Char('c') if self.focus == FocusPane::Inspector => {
+                if let Some(playlist) = self.snapshot.playlists.get(self.selected_playlist) {
+                    return KeyAction::CommandSubmitted(format!("playlist copy {}", playlist.id));
+                }
+            }
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::Char('l') | KeyCode::Right
+                if self.focus == FocusPane::Split =>
+            {
+                let forward = matches!(key.code, KeyCode::Char('l') | KeyCode::Right);
+                return KeyAction::CommandSubmitted(format!(
+                    "__split_source {}",
+                    self.next_split_source(forward)
+                ));
+            }
+            KeyCode::Char('>')
+                if self.focus == FocusPane::Tracks && self.pane_visible(FocusPane::Split) =>
+            {
+                match self.selected_track_item() {
+                    Some(track) => {
+                        return KeyAction::CommandSubmitted(format!("__split_add {}", track.path))
+                    }
+                    None => self.status_message = Some("No track selected".to_string()),
+                }
+            }
+            KeyCode::Char(key @ ('e' | 'E' | 'w' | 'W')) if self.focus == FocusPane::Tracks => {
+                let group = if key.eq_ignore_ascii_case(&'e') {
+                    "album"
+                } else {
+                    "artist"
+                };
+                let mode = if key.is_ascii_uppercase() {
+                    "queue"
+                } else {
+                    "play"
+                };
+                match self.selected_track_item() {
+                    Some(track) => {
+                        return KeyAction::CommandSubmitted(format!(
+                            "__whole {group} {mode} {}",
+                            track.path
+                        ));
+                    }
+                    None => self.status_message = Some("No track selected".to_string()),
+                }
+            }
+            KeyCode::Char('<')
+                if self.focus == FocusPane::Split
+                    && self.selected_split < self.snapshot.split_tracks.len() =>
+            {
+                return KeyAction::CommandSubmitted(format!(
+                    "__split_remove {}",
+                    self.selected_split
+                ));
+            }
             KeyCode::Char(' ') => {
                 return KeyAction::Playback(PlaybackAction::TogglePause);
             }
@@ -384,6 +995,55 @@ impl ShellState {
             KeyCode::Char('s') => {
                 return KeyAction::Playback(PlaybackAction::ToggleShuffle);
             }
+            KeyCode::Char('S') => {
+                return KeyAction::Playback(PlaybackAction::ToggleRandomAlbum);
+            }
+            KeyCode::Char('R') => {
+                return KeyAction::Playback(PlaybackAction::ToggleRadio);
+            }
+            KeyCode::Char('C') => {
+                return KeyAction::Playback(PlaybackAction::ToggleConsume);
+            }
+            KeyCode::Char('[') | KeyCode::Char(']')
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return self.jump_chapter(key.code == KeyCode::Char(']'));
+            }
+            KeyCode::Char('(') | KeyCode::Char(')') => {
+                return self.jump_chapter(key.code == KeyCode::Char(')'));
+            }
+            KeyCode::Char('[') | KeyCode::Char(']') | KeyCode::Char('{') | KeyCode::Char('}') => {
+                let forward = matches!(key.code, KeyCode::Char(']') | KeyCode::Char('}'));
+                let long = matches!(key.code, KeyCode::Char('{') | KeyCode::Char('}'));
+                return self.seek_step(forward, long);
+            }
+            KeyCode::Char(digit @ '1'..='9') if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.switch_tab(usize::from(digit as u8 - b'1'));
+            }
+            KeyCode::Char(digit @ '0'..='9') => {
+                return self.seek_to_tenth(u64::from(digit as u8 - b'0'));
+            }
+            KeyCode::Char('b') => {
+                return KeyAction::CommandSubmitted("bookmark add".to_string());
+            }
+            KeyCode::Char('B') => {
+                return KeyAction::CommandSubmitted("bookmark list".to_string());
+            }
+            KeyCode::Char('u') => {
+                return KeyAction::CommandSubmitted("undo".to_string());
+            }
+            KeyCode::Char('D') if self.focus == FocusPane::Tracks => {
+                match self
+                    .selected_track_item()
+                    .map(|t| (t.title.clone(), t.path.clone()))
+                {
+                    Some((title, path)) => self.ask_to_confirm(
+                        format!("Move \"{title}\" to the trash?"),
+                        format!("trash {path}"),
+                    ),
+                    None => self.status_message = Some("No track selected".to_string()),
+                }
+            }
             KeyCode::Char('o') => {
                 self.cycle_sort();
                 self.status_message = Some(format!(
@@ -394,10 +1054,7 @@ impl ShellState {
             }
             KeyCode::Char('v') => {
                 self.viz_style = self.viz_style.next();
-                self.status_message = Some(format!(
-                    "Visualizer: {}",
-                    self.viz_style.label()
-                ));
+                self.status_message = Some(format!("Visualizer: {}", self.viz_style.label()));
             }
             KeyCode::Char('i') if self.focus == FocusPane::Tracks => {
                 let path = self.selected_track_item().map(|t| t.path.clone());
@@ -408,2902 +1065,8183 @@ impl ShellState {
                     return KeyAction::CommandSubmitted(format!("__fetch_artwork {path}"));
                 }
             }
+            KeyCode::Char('i') if self.focus == FocusPane::Sources => {
+                if self.selected_root < self.snapshot.roots.len() {
+                    self.settings_index = 0;
+                    self.settings_editor = None;
+                    self.settings_root = Some(self.selected_root);
+                    self.input_mode = InputMode::Settings;
+                } else {
+                    self.status_message = Some("No library root selected".to_string());
+                }
+            }
             KeyCode::Char(',') => {
                 self.settings_index = 0;
+                self.settings_editor = None;
+                self.settings_root = None;
                 self.input_mode = InputMode::Settings;
             }
+            KeyCode::Char('y') => match self.selected_track_item() {
+                Some(track) => return KeyAction::CopyToClipboard(track.path.clone()),
+                None => self.status_message = Some("No track selected".to_string()),
+            },
+            KeyCode::Char('Y') => match self.selected_track_item() {
+                Some(track) => {
+                    return KeyAction::CopyToClipboard(format!(
+                        "{} – {}",
+                        track.artist, track.title
+                    ));
+                }
+                None => self.status_message = Some("No track selected".to_string()),
+            },
             _ => {}
         }
         KeyAction::Continue
     }
 
-    fn handle_mouse(&mut self, mouse: MouseEvent, areas: &RenderAreas) -> KeyAction {
-        match mouse.kind {
-            MouseEventKind::ScrollDown => {
-                self.set_focus_from_point(mouse.column, mouse.row, areas);
-                self.move_selection(3);
+    /// Whether keystrokes currently go to a text input rather than to
+    /// navigation bindings.
+    pub fn is_editing_text(&self) -> bool {
+        match self.input_mode {
+            InputMode::TrackFilter
+            | InputMode::CommandPalette
+            | InputMode::PartyUnlock
+            | InputMode::NewPlaylist => true,
+            InputMode::Help => self.help_filter_focused,
+            InputMode::Settings => self.settings_editor.is_some(),
+            InputMode::AddMusic | InputMode::Welcome => self
+                .file_browser
+                .as_ref()
+                .is_some_and(|browser| browser.input_focused),
+            _ => false,
+        }
+    }
+
+    /// Inserts pasted text at the cursor of the active text input. Only the
+    /// first line is used since every input is single-line.
+    pub fn paste_text(&mut self, text: &str) {
+        let line = text.lines().next().unwrap_or_default();
+        match self.input_mode {
+            InputMode::TrackFilter => {
+                self.track_filter_query.insert_str(line);
+                self.rebuild_track_filter();
+                self.status_message = Some(self.filter_status_line(true));
             }
-            MouseEventKind::ScrollUp => {
-                self.set_focus_from_point(mouse.column, mouse.row, areas);
-                self.move_selection(-3);
+            InputMode::CommandPalette => {
+                self.command_palette_input.insert_str(line);
+                self.status_message = Some(self.command_palette_status_line());
             }
-            MouseEventKind::Down(_) => {
-                let x = mouse.column;
-                let y = mouse.row;
-                // Click on visualizer cycles style
-                if self.viz_area != Rect::default() && self.viz_area.contains((x, y).into()) {
-                    self.viz_style = self.viz_style.next();
-                    self.status_message = Some(format!(
-                        "Visualizer: {}",
-                        self.viz_style.label()
-                    ));
-                    return KeyAction::Continue;
-                }
-                // Check if clicking on the seek bar
-                if self.seek_bar_area != Rect::default() && self.seek_bar_area.contains((x, y).into()) {
-                    let elapsed_width = 5u16; // "MM:SS" is 5 chars
-                    let remaining_width = 5u16;
-                    if let Some(progress) = crate::seekbar::click_to_progress(
-                        x, self.seek_bar_area, elapsed_width, remaining_width,
-                    ) {
-                        let position_ms = (progress as f64 * self.playback_duration_ms as f64) as u64;
-                        return KeyAction::Playback(PlaybackAction::Seek { position_ms });
+            InputMode::PartyUnlock => {
+                self.party_passphrase_input.insert_str(line);
+                self.status_message = Some(self.party_unlock_status_line());
+            }
+            InputMode::NewPlaylist => {
+                self.new_playlist_input.insert_str(line);
+                self.status_message = Some(self.new_playlist_status_line());
+            }
+            InputMode::AddMusic | InputMode::Welcome => {
+                if let Some(browser) = self.file_browser.as_mut() {
+                    if browser.input_focused {
+                        browser.path_input.insert_str(line);
                     }
                 }
-                // Check if clicking on track list header for sorting
-                if areas.track_header.contains((x, y).into()) {
-                    let co = &areas.track_col_offsets;
-                    let col = if x >= co.quality_start {
-                        Some(SortColumn::Quality)
-                    } else if x >= co.album_start {
-                        Some(SortColumn::Album)
-                    } else if x >= co.artist_start {
-                        Some(SortColumn::Artist)
-                    } else if x >= co.time_start {
-                        Some(SortColumn::Time)
-                    } else if x >= co.title_start {
-                        Some(SortColumn::Title)
-                    } else {
-                        None
-                    };
-                    if let Some(col) = col {
-                        self.set_sort_column(col);
-                        self.status_message = Some(format!(
-                            "Sort: {} {}",
-                            self.sort_column.label(),
-                            if self.sort_ascending { "▲" } else { "▼" }
-                        ));
-                    }
-                } else {
-                    // Double-click detection
-                    let is_double = self
-                        .last_click
-                        .map(|(t, lx, ly)| {
-                            t.elapsed() < Duration::from_millis(400) && lx == x && ly == y
-                        })
-                        .unwrap_or(false);
-
-                    self.set_focus_from_point(x, y, areas);
-                    self.select_from_mouse_click(x, y, areas);
-
-                    if is_double && self.focus == FocusPane::Tracks {
-                        self.last_click = None;
-                        return KeyAction::Playback(PlaybackAction::PlayTrack {
-                            track_index: self.selected_track,
-                        });
-                    }
-                    self.last_click = Some((Instant::now(), x, y));
+            }
+            InputMode::Settings => {
+                if let Some(editor) = self.settings_editor.as_mut() {
+                    editor.insert_str(line);
                 }
             }
+            InputMode::Help if self.help_filter_focused => {
+                self.help_filter.insert_str(line);
+                self.help_scroll = 0;
+            }
             _ => {}
         }
-        KeyAction::Continue
     }
 
-    fn handle_settings_key(&mut self, key: KeyEvent) -> KeyAction {
-        let num_settings = 6;
-        match key.code {
-            KeyCode::Esc | KeyCode::Char(',') | KeyCode::Char('q') => {
-                self.input_mode = InputMode::Normal;
+    fn apply_chord_action(&mut self, action: ChordAction) -> KeyAction {
+        match action {
+            ChordAction::MoveToStart => self.move_to_start(),
+            ChordAction::Focus(pane) => {
+                if !self.pane_visible(pane) {
+                    if pane == FocusPane::Split {
+                        self.panel_layout.show_split = true;
+                    } else {
+                        self.panel_layout.show_sidebar = true;
+                    }
+                    self.panel_layout.mini = false;
+                    self.remember_view();
+                    self.focus = pane;
+                    return self.layout_changed();
+                }
+                if pane != self.focus {
+                    self.remember_view();
+                }
+                self.focus = pane;
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                self.settings_index = (self.settings_index + 1).min(num_settings - 1);
+            ChordAction::ToggleSidebar => {
+                self.panel_layout.show_sidebar = !self.panel_layout.show_sidebar;
+                return self.layout_changed();
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                self.settings_index = self.settings_index.saturating_sub(1);
+            ChordAction::ToggleNowPlaying => {
+                self.panel_layout.show_now_playing = !self.panel_layout.show_now_playing;
+                return self.layout_changed();
             }
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                let command = match self.settings_index {
-                    0 => "__setting_cycle theme".to_string(),
-                    1 => "__setting_toggle use_theme_background".to_string(),
-                    2 => "__setting_cycle icon_pack".to_string(),
-                    3 => "__setting_toggle pixel_art_artwork".to_string(),
-                    4 => "__setting_cycle pixel_art_cell_size".to_string(),
-                    5 => "__setting_cycle color_scheme".to_string(),
-                    _ => return KeyAction::Continue,
-                };
-                return KeyAction::CommandSubmitted(command);
+            ChordAction::ToggleSpectrum => {
+                self.panel_layout.show_spectrum = !self.panel_layout.show_spectrum;
+                return self.layout_changed();
             }
-            _ => {}
+            ChordAction::ToggleSplit => {
+                self.panel_layout.show_split = !self.panel_layout.show_split;
+                return self.layout_changed();
+            }
+            ChordAction::OpenTab => self.open_tab(),
+            ChordAction::CloseTab => self.close_tab(),
+            ChordAction::NextTab => self.switch_tab((self.active_tab + 1) % self.tab_count()),
         }
         KeyAction::Continue
     }
 
-    fn handle_filter_key(&mut self, key: KeyEvent) -> KeyAction {
-        match key.code {
-            KeyCode::Esc | KeyCode::Enter => {
-                self.input_mode = InputMode::Normal;
-                self.status_message = Some(self.filter_status_line(false));
-            }
-            KeyCode::Backspace => {
-                self.track_filter_query.pop();
-                self.rebuild_track_filter();
-                self.status_message = Some(self.filter_status_line(true));
-            }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.track_filter_query.clear();
-                self.rebuild_track_filter();
-                self.status_message = Some(self.filter_status_line(true));
-            }
-            KeyCode::Down => self.move_selection(1),
-            KeyCode::Up => self.move_selection(-1),
-            KeyCode::PageDown => self.move_selection(10),
-            KeyCode::PageUp => self.move_selection(-10),
-            KeyCode::Home => self.move_to_start(),
-            KeyCode::End => self.move_to_end(),
-            KeyCode::Char(c)
-                if !key.modifiers.contains(KeyModifiers::CONTROL)
-                    && !key.modifiers.contains(KeyModifiers::ALT)
-                    && !c.is_control() =>
-            {
-                self.track_filter_query.push(c);
-                self.rebuild_track_filter();
-                self.status_message = Some(self.filter_status_line(true));
+    /// Shows the queue in the split view and focuses it.
+    fn open_queue_view(&mut self) -> KeyAction {
+        self.remember_view();
+        self.panel_layout.show_split = true;
+        self.panel_layout.mini = false;
+        self.focus = FocusPane::Split;
+        KeyAction::CommandSubmitted("__queue_view".to_string())
+    }
+
+    /// The source after (or before) the split's current one, going from
+    /// the queue through the playlists and back.
+    fn next_split_source(&self, forward: bool) -> String {
+        let sources: Vec<&str> = std::iter::once("queue")
+            .chain(self.snapshot.playlists.iter().map(|p| p.id.as_str()))
+            .collect();
+        let current = sources
+            .iter()
+            .position(|id| *id == self.snapshot.split_source)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % sources.len()
+        } else {
+            (current + sources.len() - 1) % sources.len()
+        };
+        sources[next].to_string()
+    }
+
+    fn pane_visible(&self, pane: FocusPane) -> bool {
+        match pane {
+            FocusPane::Tracks => true,
+            FocusPane::Split => self.panel_layout.show_split && !self.panel_layout.mini,
+            _ => self.panel_layout.show_sidebar && !self.panel_layout.mini,
+        }
+    }
+
+    fn cycle_focus(&mut self, forward: bool) {
+        self.remember_view();
+        loop {
+            self.focus = if forward {
+                self.focus.next()
+            } else {
+                self.focus.prev()
+            };
+            if self.pane_visible(self.focus) {
+                break;
             }
-            _ => {}
         }
-        KeyAction::Continue
     }
 
-    fn enter_track_filter_mode(&mut self) {
-        self.input_mode = InputMode::TrackFilter;
-        self.status_message = Some(self.filter_status_line(true));
+    /// Moves focus off hidden panes and asks the host to persist the layout.
+    fn layout_changed(&mut self) -> KeyAction {
+        if !self.pane_visible(self.focus) {
+            self.focus = FocusPane::Tracks;
+        }
+        self.status_message = Some(self.panel_layout.describe());
+        match serde_json::to_string(&self.panel_layout) {
+            Ok(json) => KeyAction::CommandSubmitted(format!("__layout_save {json}")),
+            Err(_) => KeyAction::Continue,
+        }
     }
 
-    fn handle_command_palette_key(&mut self, key: KeyEvent) -> KeyAction {
-        match key.code {
-            KeyCode::Esc => {
-                self.input_mode = InputMode::Normal;
-                self.command_palette_input.clear();
-                self.status_message = Some("Command palette canceled".to_string());
+    fn handle_mouse(&mut self, mouse: MouseEvent, areas: &RenderAreas) -> KeyAction {
+        self.mouse_hover = Some((mouse.column, mouse.row));
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left) && self.footer_hints_shown() {
+            let clicked = self
+                .hint_areas
+                .iter()
+                .find(|(area, _)| area.contains((mouse.column, mouse.row).into()))
+                .map(|(_, keys)| keys.clone());
+            if let Some(keys) = clicked {
+                return self.press_keys(&keys);
             }
-            KeyCode::Enter => {
-                self.input_mode = InputMode::Normal;
-                let command = self.command_palette_input.trim().to_string();
-                self.command_palette_input.clear();
-                if command.is_empty() {
-                    self.status_message = Some("Command palette canceled".to_string());
+        }
+        if matches!(self.input_mode, InputMode::AddMusic | InputMode::Welcome) {
+            return match areas.file_browser {
+                Some(dialog) => self.handle_file_browser_mouse(mouse, &dialog),
+                None => KeyAction::Continue,
+            };
+        }
+        if self.input_mode == InputMode::Podcasts {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self
+                    .podcast_panel
+                    .move_selection(1, &self.snapshot.podcasts),
+                MouseEventKind::ScrollUp => self
+                    .podcast_panel
+                    .move_selection(-1, &self.snapshot.podcasts),
+                _ => {}
+            }
+            return KeyAction::Continue;
+        }
+        if self.input_mode == InputMode::Health {
+            let issues = &self.snapshot.health.issues;
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self.health_panel.move_selection(1, issues),
+                MouseEventKind::ScrollUp => self.health_panel.move_selection(-1, issues),
+                _ => {}
+            }
+            return KeyAction::Continue;
+        }
+        if matches!(self.input_mode, InputMode::Stats | InputMode::Review) {
+            return KeyAction::Continue;
+        }
+        if self.input_mode == InputMode::Log {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self.log_scroll = self.log_scroll.saturating_add(3),
+                MouseEventKind::ScrollUp => self.log_scroll = self.log_scroll.saturating_sub(3),
+                _ => {}
+            }
+            return KeyAction::Continue;
+        }
+        let over_volume = self.volume_bar_area != Rect::default()
+            && self
+                .volume_bar_area
+                .contains((mouse.column, mouse.row).into());
+        let over_seek = self.seek_bar_area != Rect::default()
+            && self
+                .seek_bar_area
+                .contains((mouse.column, mouse.row).into());
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if over_seek => {
+                self.seek_preview = self.seek_target(mouse.column, false);
+                self.seek_dragging = self.seek_preview.is_some();
+                return KeyAction::Continue;
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.seek_dragging => {
+                self.seek_preview = self.seek_target(mouse.column, true);
+                return KeyAction::Continue;
+            }
+            MouseEventKind::Up(_) if self.seek_dragging => {
+                self.seek_dragging = false;
+                if let Some((_, position_ms)) = self.seek_preview.take() {
+                    self.playback_position_ms = position_ms;
+                    return KeyAction::Playback(PlaybackAction::Seek { position_ms });
+                }
+                return KeyAction::Continue;
+            }
+            MouseEventKind::Moved => {
+                self.seek_preview = if over_seek {
+                    self.seek_target(mouse.column, false)
                 } else {
-                    return KeyAction::CommandSubmitted(command);
+                    None
+                };
+                return KeyAction::Continue;
+            }
+            MouseEventKind::ScrollUp if over_volume => {
+                return KeyAction::Playback(PlaybackAction::VolumeUp);
+            }
+            MouseEventKind::ScrollDown if over_volume => {
+                return KeyAction::Playback(PlaybackAction::VolumeDown);
+            }
+            MouseEventKind::Down(MouseButton::Left) if over_volume => {
+                self.volume_dragging = true;
+                return self.set_volume_from_column(mouse.column);
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.volume_dragging => {
+                return self.set_volume_from_column(mouse.column);
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.split_drag.is_some() => {
+                self.drag_split_row(mouse.row, &areas.split);
+                return KeyAction::Continue;
+            }
+            MouseEventKind::Up(_) if self.split_drag.is_some() => {
+                let drag = self.split_drag.take().expect("checked above");
+                if drag.to == drag.from {
+                    return KeyAction::Continue;
                 }
+                self.selected_split = drag.to;
+                return KeyAction::CommandSubmitted(format!(
+                    "__split_move {} {}",
+                    drag.from, drag.to
+                ));
             }
-            KeyCode::Backspace => {
-                self.command_palette_input.pop();
-                self.status_message = Some(self.command_palette_status_line());
+            MouseEventKind::Up(_) => self.volume_dragging = false,
+            MouseEventKind::ScrollDown => {
+                self.set_focus_from_point(mouse.column, mouse.row, areas);
+                self.move_selection(3);
             }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.command_palette_input.clear();
-                self.status_message = Some(self.command_palette_status_line());
+            MouseEventKind::ScrollUp => {
+                self.set_focus_from_point(mouse.column, mouse.row, areas);
+                self.move_selection(-3);
             }
-            KeyCode::Char(c)
-                if !key.modifiers.contains(KeyModifiers::CONTROL)
-                    && !key.modifiers.contains(KeyModifiers::ALT)
-                    && !c.is_control() =>
-            {
-                self.command_palette_input.push(c);
-                self.status_message = Some(self.command_palette_status_line());
+            MouseEventKind::Down(button) => {
+                let x = mouse.column;
+                let y = mouse.row;
+                if self.queue_line_area.contains((x, y).into()) {
+                    return self.open_queue_view();
+                }
+                // Click on visualizer cycles style
+                if self.viz_area != Rect::default() && self.viz_area.contains((x, y).into()) {
+                    self.viz_style = self.viz_style.next();
+                    self.status_message = Some(format!("Visualizer: {}", self.viz_style.label()));
+                    return KeyAction::Continue;
+                }
+                // Check if clicking on track list header for sorting
+                if areas.track_header.contains((x, y).into()) {
+                    let co = &areas.track_col_offsets;
+                    let col = if x >= co.quality_start {
+                        Some(SortColumn::Quality)
+                    } else if x >= co.bpm_start {
+                        Some(SortColumn::Bpm)
+                    } else if x >= co.album_start {
+                        Some(SortColumn::Album)
+                    } else if x >= co.artist_start {
+                        Some(SortColumn::Artist)
+                    } else if x >= co.time_start {
+                        Some(SortColumn::Time)
+                    } else if x >= co.title_start {
+                        Some(SortColumn::Title)
+                    } else {
+                        None
+                    };
+                    if let Some(col) = col {
+                        self.set_sort_column(col);
+                        self.status_message = Some(format!(
+                            "Sort: {} {}",
+                            self.sort_column.label(),
+                            if self.sort_ascending { "▲" } else { "▼" }
+                        ));
+                    }
+                } else {
+                    self.set_focus_from_point(x, y, areas);
+                    self.select_from_mouse_click(x, y, areas);
+                    if button == MouseButton::Left && self.focus == FocusPane::Split {
+                        self.split_drag = areas
+                            .split
+                            .mouse_item_index(
+                                x,
+                                y,
+                                self.split_scroll,
+                                self.snapshot.split_tracks.len(),
+                            )
+                            .map(|from| SplitDrag { from, to: from });
+                    }
+
+                    if button != MouseButton::Left || self.focus != FocusPane::Tracks {
+                        self.last_click = None;
+                        return KeyAction::Continue;
+                    }
+                    // Only a second click on the same row within the window
+                    // activates; anything else just selects.
+                    let now = Instant::now();
+                    let is_double = self.last_click.is_some_and(|(t, row, track)| {
+                        now.duration_since(t) <= DOUBLE_CLICK_WINDOW
+                            && row == y
+                            && track == self.selected_track
+                    });
+                    if is_double {
+                        self.last_click = None;
+                        return KeyAction::Playback(PlaybackAction::PlayTrack {
+                            track_index: self.selected_track,
+                        });
+                    }
+                    self.last_click = Some((now, y, self.selected_track));
+                }
             }
             _ => {}
         }
         KeyAction::Continue
     }
 
-    fn enter_command_palette_mode(&mut self) {
-        self.input_mode = InputMode::CommandPalette;
-        self.command_palette_input.clear();
-        self.status_message = Some(self.command_palette_status_line());
+    /// The column and track time at `column` on the seek bar. Off the bar
+    /// it is `None`, or the nearest end when `clamp` is set so a drag can
+    /// overshoot.
+    fn seek_target(&self, column: u16, clamp: bool) -> Option<(u16, u64)> {
+        if self.playback_duration_ms == 0 || self.playback_status == "stopped" {
+            return None;
+        }
+        // "MM:SS" on either side of the bar.
+        let (elapsed_width, remaining_width) = (5u16, 5u16);
+        let progress = if clamp {
+            crate::seekbar::drag_to_progress(
+                column,
+                self.seek_bar_area,
+                elapsed_width,
+                remaining_width,
+            )
+        } else {
+            crate::seekbar::click_to_progress(
+                column,
+                self.seek_bar_area,
+                elapsed_width,
+                remaining_width,
+            )?
+        };
+        let position_ms = (progress as f64 * self.playback_duration_ms as f64) as u64;
+        Some((column, position_ms))
     }
 
-    fn handle_add_music_key(&mut self, key: KeyEvent) -> KeyAction {
-        let browser = match self.file_browser.as_mut() {
-            Some(b) => b,
-            None => {
-                self.input_mode = InputMode::Normal;
-                return KeyAction::Continue;
+    /// Updates the displayed volume right away so a drag feels immediate; the
+    /// playback handler applies it to the player without a snapshot refresh.
+    fn set_volume_from_column(&mut self, column: u16) -> KeyAction {
+        let volume = crate::seekbar::column_to_volume(column, self.volume_bar_area);
+        if (volume - self.snapshot.volume).abs() < f32::EPSILON {
+            return KeyAction::Continue;
+        }
+        self.snapshot.volume = volume;
+        KeyAction::Playback(PlaybackAction::SetVolume { volume })
+    }
+
+    /// Mouse input while the add-music dialog is open. Clicks outside the
+    /// dialog are ignored so the library underneath stays untouched.
+    fn handle_file_browser_mouse(
+        &mut self,
+        mouse: MouseEvent,
+        dialog: &FileBrowserAreas,
+    ) -> KeyAction {
+        let Some(browser) = self.file_browser.as_mut() else {
+            return KeyAction::Continue;
+        };
+        let point = (mouse.column, mouse.row).into();
+        match mouse.kind {
+            MouseEventKind::ScrollDown if dialog.dialog.contains(point) => {
+                browser.move_selection(3);
+                browser.sync_path_input_to_selected();
+            }
+            MouseEventKind::ScrollUp if dialog.dialog.contains(point) => {
+                browser.move_selection(-3);
+                browser.sync_path_input_to_selected();
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if dialog.load_button.contains(point) {
+                    return self.add_selected_folder();
+                }
+                if dialog.path_input.contains(point) {
+                    browser.input_focused = true;
+                    return KeyAction::Continue;
+                }
+                if !dialog.entries.contains(point) {
+                    return KeyAction::Continue;
+                }
+                let index = dialog.first_entry + usize::from(mouse.row - dialog.entries.y);
+                if index >= browser.entries().len() {
+                    return KeyAction::Continue;
+                }
+                browser.input_focused = false;
+                let now = Instant::now();
+                let is_double = self.last_click.is_some_and(|(t, row, item)| {
+                    now.duration_since(t) <= DOUBLE_CLICK_WINDOW
+                        && row == mouse.row
+                        && item == index
+                });
+                browser.selected = index;
+                if is_double {
+                    self.last_click = None;
+                    browser.enter_selected();
+                } else {
+                    self.last_click = Some((now, mouse.row, index));
+                    browser.sync_path_input_to_selected();
+                }
             }
+            _ => {}
+        }
+        KeyAction::Continue
+    }
+
+    fn add_selected_folder(&mut self) -> KeyAction {
+        let Some(browser) = self.file_browser.take() else {
+            return KeyAction::Continue;
         };
+        let path = browser.selected_path().to_string_lossy().into_owned();
+        self.input_mode = InputMode::Normal;
+        // The host records it too when handling `__add_root`.
+        self.browser_places.remember(&path);
+        KeyAction::CommandSubmitted(format!("__add_root {path}"))
+    }
 
-        if browser.input_focused {
+    fn handle_help_key(&mut self, key: KeyEvent) -> KeyAction {
+        if self.help_filter_focused {
             match key.code {
                 KeyCode::Esc => {
-                    browser.input_focused = false;
-                }
-                KeyCode::Tab => {
-                    browser.input_focused = false;
-                }
-                KeyCode::Enter => {
-                    browser.apply_path_input();
-                    browser.input_focused = false;
-                }
-                KeyCode::Backspace => {
-                    browser.path_input.pop();
+                    self.help_filter.clear();
+                    self.help_filter_focused = false;
+                    self.help_scroll = 0;
                 }
-                KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    browser.path_input.clear();
+                KeyCode::Enter => self.help_filter_focused = false,
+                KeyCode::Down => self.help_scroll = self.help_scroll.saturating_add(1),
+                KeyCode::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+                KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return KeyAction::PasteFromClipboard;
                 }
-                KeyCode::Char(c) => {
-                    browser.path_input.push(c);
+                _ => {
+                    if self.help_filter.handle_key(&key) {
+                        self.help_scroll = 0;
+                    }
                 }
-                _ => {}
             }
             return KeyAction::Continue;
         }
-
         match key.code {
-            KeyCode::Esc => {
-                self.file_browser = None;
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => {
                 self.input_mode = InputMode::Normal;
             }
-            KeyCode::Tab => {
-                browser.input_focused = true;
-            }
+            KeyCode::Char('/') => self.help_filter_focused = true,
             KeyCode::Char('j') | KeyCode::Down => {
-                browser.move_selection(1);
-                browser.sync_path_input_to_selected();
+                self.help_scroll = self.help_scroll.saturating_add(1);
             }
             KeyCode::Char('k') | KeyCode::Up => {
-                browser.move_selection(-1);
-                browser.sync_path_input_to_selected();
-            }
-            KeyCode::Enter => {
-                browser.enter_selected();
-            }
-            KeyCode::Backspace | KeyCode::Char('h') => {
-                browser.go_up();
+                self.help_scroll = self.help_scroll.saturating_sub(1);
             }
-            KeyCode::Char(' ') => {
-                let path = browser.selected_path().to_string_lossy().into_owned();
-                self.file_browser = None;
-                self.input_mode = InputMode::Normal;
-                return KeyAction::CommandSubmitted(format!("__add_root {path}"));
+            KeyCode::PageDown | KeyCode::Char(' ') => {
+                self.help_scroll = self.help_scroll.saturating_add(10);
             }
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(10),
+            KeyCode::Char('g') | KeyCode::Home => self.help_scroll = 0,
+            // Clamped to the last page when the dialog is drawn.
+            KeyCode::Char('G') | KeyCode::End => self.help_scroll = usize::MAX,
             _ => {}
         }
         KeyAction::Continue
     }
 
-    fn rebuild_track_filter(&mut self) {
-        self.filtered_track_indices.clear();
-        if self.track_filter_query.is_empty() {
-            self.filtered_track_indices
-                .extend(0..self.snapshot.tracks.len());
+    /// Opens the log dialog scrolled to the newest entry and marks
+    /// everything in it as seen.
+    fn open_log(&mut self) {
+        self.log_seen = self.event_log.pushed();
+        // Clamped to the last page when the dialog is drawn.
+        self.log_scroll = usize::MAX;
+        self.input_mode = InputMode::Log;
+    }
+
+    /// Scrolls the selected track's title and album by a few cells, for
+    /// names too long for their columns.
+    fn scroll_title(&mut self, forward: bool) {
+        const STEP: usize = 4;
+        let Some(longest) = self
+            .selected_track_item()
+            .map(|t| display_width(&t.title).max(display_width(&t.album)))
+        else {
+            return;
+        };
+        let offset = self.title_offset();
+        let offset = if forward {
+            (offset + STEP).min(longest)
         } else {
-            let query = self.track_filter_query.to_lowercase();
-            self.filtered_track_indices.extend(
-                self.snapshot
-                    .tracks
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, track)| track_matches_query(track, &query))
-                    .map(|(idx, _)| idx),
-            );
-        }
-        if let Some(ref artist) = self.browse_filter_artist {
-            self.filtered_track_indices.retain(|&idx| {
-                self.snapshot.tracks[idx]
-                    .artist
-                    .eq_ignore_ascii_case(artist)
-            });
-        }
-        if let Some(ref album) = self.browse_filter_album {
-            self.filtered_track_indices.retain(|&idx| {
-                self.snapshot.tracks[idx]
-                    .album
-                    .eq_ignore_ascii_case(album)
-            });
-        }
-        self.apply_sort();
-        self.selected_track = self
-            .selected_track
-            .min(self.filtered_track_indices.len().saturating_sub(1));
+            offset.saturating_sub(STEP)
+        };
+        self.title_scroll = (self.selected_track, offset);
     }
 
-    fn apply_sort(&mut self) {
-        let tracks = &self.snapshot.tracks;
-        let col = self.sort_column;
-        let asc = self.sort_ascending;
-        self.filtered_track_indices.sort_by(|&a, &b| {
-            let cmp = match col {
-                SortColumn::Title => tracks[a].title.to_ascii_lowercase().cmp(&tracks[b].title.to_ascii_lowercase()),
-                SortColumn::Artist => tracks[a].artist.to_ascii_lowercase().cmp(&tracks[b].artist.to_ascii_lowercase()),
-                SortColumn::Album => tracks[a].album.to_ascii_lowercase().cmp(&tracks[b].album.to_ascii_lowercase()),
-                SortColumn::Time => tracks[a].duration_ms.cmp(&tracks[b].duration_ms),
-                SortColumn::Quality => tracks[a].sample_rate.cmp(&tracks[b].sample_rate),
-            };
-            if asc { cmp } else { cmp.reverse() }
-        });
+    /// The scroll of the selected track's title; zero once the selection
+    /// has moved on.
+    fn title_offset(&self) -> usize {
+        match self.title_scroll {
+            (track, offset) if track == self.selected_track => offset,
+            _ => 0,
+        }
     }
 
-    fn set_sort_column(&mut self, col: SortColumn) {
-        if self.sort_column == col {
-            self.sort_ascending = !self.sort_ascending;
+    /// Why the track list shows what it does: the artist or album picked in
+    /// Browse, the counts, the search and the sort, e.g.
+    /// `Library › Radiohead (12/12,034) · /creep · Title ▲`.
+    fn library_breadcrumb(&self) -> String {
+        let mut crumbs = vec!["Library"];
+        crumbs.extend(self.browse_filter_artist.as_deref());
+        crumbs.extend(self.browse_filter_album.as_deref());
+        let filtered = self.filtered_track_count();
+        let total = self.snapshot.total_track_count;
+        let count = if filtered < total {
+            format!("{}/{}", thousands(filtered), thousands(total))
         } else {
-            self.sort_column = col;
-            self.sort_ascending = true;
+            thousands(filtered)
+        };
+        let mut title = format!("{} ({count})", crumbs.join(" › "));
+        if !self.track_filter_query.is_empty() {
+            title.push_str(&format!(" · /{}", self.track_filter_query));
         }
-        self.apply_sort();
-        self.selected_track = 0;
+        let arrow = if self.sort_ascending { "▲" } else { "▼" };
+        title.push_str(&format!(" · {} {arrow}", self.sort_column.label()));
+        title
     }
 
-    fn cycle_sort(&mut self) {
-        if self.sort_ascending {
-            self.sort_ascending = false;
-        } else {
-            self.sort_column = self.sort_column.next();
-            self.sort_ascending = true;
-        }
-        self.apply_sort();
-        self.selected_track = 0;
+    /// The footer's key hints for the open prompt or the focused pane.
+    fn footer_hints(&self) -> Vec<crate::keymap::Hint> {
+        use crate::keymap::{footer_hints, HintContext};
+        let context = match (self.input_mode, self.focus) {
+            (InputMode::Confirm, _) => HintContext::Confirm,
+            (InputMode::TrackFilter, _) => HintContext::TrackFilter,
+            (InputMode::CommandPalette, _) => HintContext::CommandPalette,
+            (InputMode::NewPlaylist, _) => HintContext::NewPlaylist,
+            _ if self.snapshot.party_mode => HintContext::Party,
+            (_, FocusPane::Sources) => HintContext::Sources,
+            (_, FocusPane::Browse) if self.selected_recent_track().is_some() => HintContext::Recent,
+            (_, FocusPane::Browse) => HintContext::Browse,
+            (_, FocusPane::Tracks) => HintContext::Tracks,
+            (_, FocusPane::Split) => HintContext::Split,
+            (_, FocusPane::Inspector) => HintContext::Playlists,
+        };
+        let split_shown = self.pane_visible(FocusPane::Split);
+        footer_hints(context)
+            .iter()
+            .filter(|hint| split_shown || hint.keys != "> / <")
+            .copied()
+            .collect()
     }
 
-    fn handle_browse_enter(&mut self) {
-        if self.browse.show_items && !self.browse.items.is_empty() {
-            self.browse.update_selected_item();
-            match self.browse.mode {
-                crate::browse::BrowseMode::Artists => {
-                    self.browse_filter_artist = self.browse.selected_item.clone();
-                    self.browse_filter_album = None;
-                }
-                crate::browse::BrowseMode::Albums => {
-                    self.browse_filter_album = self.browse.selected_item.clone();
-                    self.browse_filter_artist = None;
-                }
-                crate::browse::BrowseMode::Songs => {}
+    /// As many of [`Self::footer_hints`] as fit in `width` columns, each
+    /// as `keys: label` and two spaces, ending with the help key.
+    fn fitting_hints(&self, width: usize) -> Vec<String> {
+        let help = "?: help";
+        let (mut hints, mut used) = (Vec::new(), 0);
+        for hint in self.footer_hints() {
+            let next = format!("{}: {}  ", hint.keys, hint.label);
+            if used + display_width(&next) + help.len() > width {
+                break;
             }
-            self.rebuild_track_filter();
-        } else {
-            self.apply_browse_mode();
+            used += display_width(&next);
+            hints.push(next);
+        }
+        if used + help.len() <= width {
+            hints.push(help.to_string());
         }
+        hints
     }
 
-    fn handle_browse_back(&mut self) {
-        if self.browse.show_items {
-            self.browse.show_items = false;
-            self.browse.selected_item = None;
-            self.browse_filter_artist = None;
-            self.browse_filter_album = None;
-            self.rebuild_track_filter();
+    /// Whether the footer hints are for the keys that would be handled now,
+    /// rather than for the pane under a dialog.
+    fn footer_hints_shown(&self) -> bool {
+        matches!(
+            self.input_mode,
+            InputMode::Normal
+                | InputMode::Confirm
+                | InputMode::TrackFilter
+                | InputMode::CommandPalette
+                | InputMode::NewPlaylist
+        )
+    }
+
+    /// Presses `keys` as if they were typed, for a click on a footer hint.
+    fn press_keys(&mut self, keys: &[crate::input::ChordKey]) -> KeyAction {
+        let mut action = KeyAction::Continue;
+        for key in keys {
+            action = self.handle_key(key.to_event());
         }
+        action
     }
 
-    fn apply_browse_mode(&mut self) {
-        let mode = crate::browse::BrowseMode::all()[self.browse.mode_index];
-        self.browse.set_mode(mode);
-        match mode {
-            crate::browse::BrowseMode::Songs => {
-                self.browse.show_items = false;
-                self.browse.items.clear();
-                self.browse_filter_artist = None;
-                self.browse_filter_album = None;
-            }
-            crate::browse::BrowseMode::Artists => {
-                self.browse.show_items = true;
-                self.browse.items = self.snapshot.artists.clone();
-            }
-            crate::browse::BrowseMode::Albums => {
-                self.browse.show_items = true;
-                self.browse.items = self
-                    .snapshot
-                    .albums
-                    .iter()
-                    .map(|(a, _)| a.clone())
-                    .collect();
-            }
+    fn hovered(&self, area: Rect) -> bool {
+        self.mouse_hover
+            .is_some_and(|(x, y)| area.contains((x, y).into()))
+    }
+
+    /// Warnings and errors logged since the log dialog was last opened.
+    pub fn unseen_log_count(&self) -> u64 {
+        self.event_log.pushed().saturating_sub(self.log_seen)
+    }
+
+    /// How soon the next frame is due: the spectrum and track fades move on their own.
+    pub fn pace(&self) -> Pace {
+        if !self.terminal_focused {
+            return Pace::Background;
+        }
+        let spectrum = self.viz_area != Rect::default() && self.playback_status == "playing";
+        if spectrum || self.track_change_time.is_some() {
+            Pace::Active
+        } else {
+            Pace::Idle
         }
-        self.rebuild_track_filter();
     }
 
-    fn filter_status_line(&self, editing: bool) -> String {
-        let mode = if editing { "editing" } else { "applied" };
-        format!(
-            "Track filter ({mode}): \"{}\" [{}/{}]  Enter/Esc close  Ctrl-U clear",
-            self.track_filter_query,
-            self.filtered_track_indices.len(),
-            self.snapshot.tracks.len()
-        )
+    /// Whether the player should analyse what it plays for the spectrum:
+    /// only while the spectrum is shown and the terminal has focus.
+    pub fn wants_spectrum(&self) -> bool {
+        let layout = &self.panel_layout;
+        self.terminal_focused
+            && layout.show_spectrum
+            && layout.show_now_playing
+            && !layout.mini
+            && self.screen_reader() == ScreenReaderMode::Off
     }
 
-    fn command_palette_status_line(&self) -> String {
+    fn set_terminal_focus(&mut self, focused: bool) {
+        self.terminal_focused = focused;
+        if !focused {
+            self.mouse_hover = None;
+        }
+    }
+
+    /// One-line playback status, e.g. `playing: Artist - Title (01:23 / 04:56)`.
+    pub fn playback_summary(&self) -> String {
+        let snapshot = &self.snapshot;
+        if snapshot.now_playing_title.is_empty() {
+            return self.playback_status.clone();
+        }
+        let track = if snapshot.now_playing_artist.is_empty() {
+            snapshot.now_playing_title.clone()
+        } else {
+            format!(
+                "{} - {}",
+                snapshot.now_playing_artist, snapshot.now_playing_title
+            )
+        };
         format!(
-            "Command palette: :{}  (Enter run, Esc cancel, Ctrl-U clear)",
-            self.command_palette_input
+            "{}: {track} ({} / {})",
+            self.playback_status,
+            format_ms(self.playback_position_ms),
+            format_ms(self.playback_duration_ms)
         )
     }
 
-    fn selected_track_item(&self) -> Option<&ShellTrackItem> {
-        let track_index = *self.filtered_track_indices.get(self.selected_track)?;
-        self.snapshot.tracks.get(track_index)
+    /// Shows an error in the status line and keeps it in the log dialog so
+    /// it is still available after the status line changes.
+    fn report_error(&mut self, message: String) {
+        self.event_log.push(LogLevel::Error, message.clone());
+        self.status_message = Some(message);
     }
 
-    fn filtered_track_count(&self) -> usize {
-        self.filtered_track_indices.len()
+    fn handle_log_key(&mut self, key: KeyEvent) -> KeyAction {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('L') | KeyCode::Char('q') => {
+                self.log_seen = self.event_log.pushed();
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.log_scroll = self.log_scroll.saturating_add(1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.log_scroll = self.log_scroll.saturating_sub(1);
+            }
+            KeyCode::PageDown | KeyCode::Char(' ') => {
+                self.log_scroll = self.log_scroll.saturating_add(10);
+            }
+            KeyCode::PageUp => self.log_scroll = self.log_scroll.saturating_sub(10),
+            KeyCode::Char('g') | KeyCode::Home => self.log_scroll = 0,
+            KeyCode::Char('G') | KeyCode::End => self.log_scroll = usize::MAX,
+            KeyCode::Char('c') => {
+                self.event_log.clear();
+                self.log_scroll = 0;
+            }
+            _ => {}
+        }
+        KeyAction::Continue
     }
 
-    fn filtered_track_iter(&self) -> impl Iterator<Item = &ShellTrackItem> {
-        self.filtered_track_indices
-            .iter()
-            .filter_map(|idx| self.snapshot.tracks.get(*idx))
+    fn handle_podcasts_key(&mut self, key: KeyEvent) -> KeyAction {
+        let podcasts = &self.snapshot.podcasts;
+        let panel = &mut self.podcast_panel;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('P') | KeyCode::Char('q') => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => panel.move_selection(1, podcasts),
+            KeyCode::Char('k') | KeyCode::Up => panel.move_selection(-1, podcasts),
+            KeyCode::PageDown => panel.move_selection(10, podcasts),
+            KeyCode::PageUp => panel.move_selection(-10, podcasts),
+            KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => panel.focus_episodes(podcasts),
+            KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left | KeyCode::Backspace => {
+                panel.focus_podcasts()
+            }
+            KeyCode::Enter if !panel.episodes_focused => panel.focus_episodes(podcasts),
+            KeyCode::Enter => {
+                if let Some(episode) = panel.selected_episode(podcasts) {
+                    let command = format!("podcast play {}", episode.id);
+                    self.input_mode = InputMode::Normal;
+                    return KeyAction::CommandSubmitted(command);
+                }
+            }
+            KeyCode::Char('d') => {
+                let episode = panel.selected_episode(podcasts);
+                if let Some(episode) = episode.filter(|_| panel.episodes_focused) {
+                    return KeyAction::CommandSubmitted(format!("podcast download {}", episode.id));
+                }
+            }
+            KeyCode::Char('r') => {
+                return KeyAction::CommandSubmitted("podcast refresh".to_string())
+            }
+            KeyCode::Char('a') => {
+                self.enter_command_palette_mode();
+                self.command_palette_input.set("podcast subscribe ");
+            }
+            _ => {}
+        }
+        KeyAction::Continue
     }
 
-    fn sync_scroll_offsets(&mut self, areas: &RenderAreas) {
-        self.roots_scroll = normalize_scroll(
-            self.roots_scroll,
-            self.selected_root,
-            self.snapshot.roots.len(),
-            areas.roots.visible_items,
-        );
-        self.playlists_scroll = normalize_scroll(
-            self.playlists_scroll,
-            self.selected_playlist,
-            self.snapshot.playlists.len(),
-            areas.playlists.visible_items,
-        );
-        self.tracks_scroll = normalize_scroll(
-            self.tracks_scroll,
-            self.selected_track,
-            self.filtered_track_indices.len(),
-            areas.tracks.visible_items,
-        );
-        if self.browse.show_items {
-            let items_visible = if let Some(ref bi) = areas.browse_items {
-                bi.visible_items
-            } else {
-                let browse_inner = borderless_content_area(areas.browse);
-                let modes_height = crate::browse::BrowseMode::all().len() as u16 + 1;
-                browse_inner.height.saturating_sub(modes_height) as usize
-            };
-            self.browse.item_scroll = normalize_scroll(
-                self.browse.item_scroll,
-                self.browse.item_index,
-                self.browse.items.len(),
-                items_visible,
-            );
+    fn handle_health_key(&mut self, key: KeyEvent) -> KeyAction {
+        let issues = &self.snapshot.health.issues;
+        let panel = &mut self.health_panel;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('H') | KeyCode::Char('q') => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => panel.move_selection(1, issues),
+            KeyCode::Char('k') | KeyCode::Up => panel.move_selection(-1, issues),
+            KeyCode::PageDown => panel.move_selection(10, issues),
+            KeyCode::PageUp => panel.move_selection(-10, issues),
+            KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => panel.focus_tracks(issues),
+            KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left | KeyCode::Backspace => {
+                panel.focus_issues()
+            }
+            KeyCode::Enter if !panel.tracks_focused => panel.focus_tracks(issues),
+            KeyCode::Enter => {
+                if let Some(path) = panel.selected_track(issues).map(|t| t.path.clone()) {
+                    self.input_mode = InputMode::Normal;
+                    self.show_track(&path);
+                }
+            }
+            KeyCode::Char('r') => return KeyAction::CommandSubmitted("health check".to_string()),
+            KeyCode::Char('f') => match panel.selected_issue(issues) {
+                Some(ShellHealthIssue {
+                    fix: Some((what, command)),
+                    count,
+                    ..
+                }) if *count > 0 => {
+                    let (question, command) = (format!("{what}?"), command.clone());
+                    self.ask_to_confirm(question, command);
+                }
+                Some(issue) => {
+                    self.status_message =
+                        Some(format!("Nothing to fix for {}", issue.label.to_lowercase()));
+                }
+                None => {}
+            },
+            _ => {}
         }
+        KeyAction::Continue
     }
 
-    fn set_focus_from_point(&mut self, x: u16, y: u16, areas: &RenderAreas) {
-        let point = (x, y).into();
-        if areas.roots.outer.contains(point) {
-            self.focus = FocusPane::Sources;
-        } else if areas.browse.contains(point) {
-            self.focus = FocusPane::Browse;
-        } else if let Some(ref bi) = areas.browse_items {
-            if bi.outer.contains(point) {
-                self.focus = FocusPane::Browse;
-            } else if areas.playlists.outer.contains(point) {
-                self.focus = FocusPane::Inspector;
-            } else if areas.tracks.outer.contains(point) {
-                self.focus = FocusPane::Tracks;
+    /// Shows `review` in the year-in-review dialog.
+    pub fn open_review(&mut self, review: ShellReview) {
+        self.review = Some(review);
+        self.input_mode = InputMode::Review;
+    }
+
+    fn handle_review_key(&mut self, key: KeyEvent) -> KeyAction {
+        let Some(review) = &self.review else {
+            self.input_mode = InputMode::Normal;
+            return KeyAction::Continue;
+        };
+        let step = match key.code {
+            KeyCode::Esc | KeyCode::Char('y') | KeyCode::Char('q') => {
+                self.input_mode = InputMode::Normal;
+                return KeyAction::Continue;
             }
-        } else if areas.playlists.outer.contains(point) {
-            self.focus = FocusPane::Inspector;
-        } else if areas.tracks.outer.contains(point) {
-            self.focus = FocusPane::Tracks;
+            KeyCode::Left | KeyCode::Char('h') => &review.previous,
+            KeyCode::Right | KeyCode::Char('l') => &review.next,
+            KeyCode::Char('e') => {
+                let range = review.range.clone();
+                self.enter_command_palette_mode();
+                self.command_palette_input.set(format!(
+                    "review export {range} auric-review-{}.txt",
+                    range.replace("..", "_")
+                ));
+                return KeyAction::Continue;
+            }
+            _ => return KeyAction::Continue,
+        };
+        match step {
+            Some(range) => KeyAction::CommandSubmitted(format!("review {range}")),
+            None => KeyAction::Continue,
         }
     }
 
-    fn select_from_mouse_click(&mut self, x: u16, y: u16, areas: &RenderAreas) {
-        if let Some(index) =
-            areas
-                .roots
-                .mouse_item_index(x, y, self.roots_scroll, self.snapshot.roots.len())
-        {
-            self.selected_root = index;
+    /// Selects the track at `path` in the full track list, as a step Esc
+    /// can go back from.
+    fn show_track(&mut self, path: &str) {
+        let Some(index) = self.snapshot.tracks.iter().position(|t| t.path == path) else {
+            self.status_message =
+                Some("That track is not in the loaded list; filter for it with /".to_string());
             return;
+        };
+        self.remember_view();
+        self.track_filter_query.clear();
+        self.browse.selected_item = None;
+        self.browse_filter_artist = None;
+        self.browse_filter_album = None;
+        self.rebuild_track_filter();
+        self.selected_track = self
+            .filtered_track_indices
+            .iter()
+            .position(|&i| i == index)
+            .unwrap_or(0);
+        self.focus = FocusPane::Tracks;
+    }
+
+    /// Hardware media keys, as terminals with the kitty keyboard protocol
+    /// report them. They work whatever is open, like on any other player.
+    fn media_key_action(&mut self, media: MediaKeyCode) -> KeyAction {
+        let action = match media {
+            MediaKeyCode::Play => PlaybackAction::Resume,
+            MediaKeyCode::Pause => PlaybackAction::Pause,
+            MediaKeyCode::PlayPause => PlaybackAction::TogglePause,
+            MediaKeyCode::Stop => PlaybackAction::Stop,
+            MediaKeyCode::TrackNext => PlaybackAction::Next,
+            MediaKeyCode::TrackPrevious => PlaybackAction::Previous,
+            MediaKeyCode::RaiseVolume => PlaybackAction::VolumeUp,
+            MediaKeyCode::LowerVolume => PlaybackAction::VolumeDown,
+            MediaKeyCode::MuteVolume => PlaybackAction::SetVolume { volume: 0.0 },
+            MediaKeyCode::FastForward => return self.seek_step(true, false),
+            MediaKeyCode::Rewind => return self.seek_step(false, false),
+            MediaKeyCode::Reverse | MediaKeyCode::Record => return KeyAction::Continue,
+        };
+        KeyAction::Playback(action)
+    }
+
+    /// The playback position the seek bar shows: where a drag would seek to
+    /// while one is under way.
+    fn shown_position_ms(&self) -> u64 {
+        match self.seek_preview {
+            Some((_, position_ms)) if self.seek_dragging => position_ms,
+            _ if self.screen_reader() != ScreenReaderMode::Off => {
+                self.playback_position_ms / 10_000 * 10_000
+            }
+            _ => self.playback_position_ms,
         }
-        if let Some(index) = areas.playlists.mouse_item_index(
-            x,
-            y,
-            self.playlists_scroll,
-            self.snapshot.playlists.len(),
-        ) {
-            self.selected_playlist = index;
+    }
+
+    fn screen_reader(&self) -> ScreenReaderMode {
+        ScreenReaderMode::from_config(&self.snapshot.setting_screen_reader)
+    }
+
+    /// Notes a track change or a pause for the screen reader line.
+    fn update_announcement(&mut self) {
+        let track = self.snapshot.now_playing_path.clone();
+        let status = self.playback_status.clone();
+        if (&track, &status) == (&self.announced.0, &self.announced.1) {
             return;
         }
-        if let Some(ref bi) = areas.browse_items {
-            if let Some(index) = bi.mouse_item_index(
-                x,
-                y,
-                self.browse.item_scroll,
-                self.browse.items.len(),
-            ) {
-                self.browse.item_index = index;
-                return;
-            }
-        }
-        if let Some(index) = areas.tracks.mouse_item_index(
-            x,
-            y,
-            self.tracks_scroll,
-            self.filtered_track_indices.len(),
-        ) {
-            self.selected_track = index;
+        let track_changed = track != self.announced.0;
+        self.announcement = match status.as_str() {
+            _ if track.is_empty() => None,
+            "playing" if track_changed => Some(format!(
+                "Now playing: {} by {}",
+                self.snapshot.now_playing_title, self.snapshot.now_playing_artist
+            )),
+            "playing" => Some("Playing".to_string()),
+            "paused" => Some("Paused".to_string()),
+            "stopped" => Some("Stopped".to_string()),
+            _ => self.announcement.take(),
+        };
+        self.announced = (track, status);
+    }
+
+    /// The configured unicode level, with `auto` decided by the locale.
+    /// Screen reader mode is always ascii.
+    fn unicode_level(&self) -> UnicodeLevel {
+        if self.screen_reader() != ScreenReaderMode::Off {
+            return UnicodeLevel::Ascii;
         }
+        UnicodeLevel::resolve(
+            &self.snapshot.setting_unicode_level,
+            self.terminal_caps.utf8_locale,
+        )
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SortColumn {
-    Title,
-    Time,
-    Artist,
-    Album,
-    Quality,
-}
+    /// The theme's bar and visualizer characters at the unicode level.
+    fn glyphs(&self, palette: &Palette) -> Glyphs {
+        palette.glyphs.at_level(self.unicode_level())
+    }
 
-impl SortColumn {
-    fn next(self) -> Self {
-        match self {
-            Self::Title => Self::Artist,
-            Self::Artist => Self::Album,
-            Self::Album => Self::Time,
-            Self::Time => Self::Quality,
-            Self::Quality => Self::Title,
+    /// The icon and a space before a sidebar row; nothing in ascii mode.
+    fn icon_prefix(&self, token: IconToken) -> String {
+        if self.unicode_level() == UnicodeLevel::Ascii {
+            String::new()
+        } else {
+            format!("{} ", icon_glyph(self.snapshot.icon_mode, token))
         }
     }
 
-    fn label(self) -> &'static str {
-        match self {
-            Self::Title => "Title",
-            Self::Artist => "Artist",
-            Self::Album => "Album",
-            Self::Time => "Time",
-            Self::Quality => "Quality",
+    /// Seeks from the current position: 5s or 30s, or 30s and 5min when the
+    /// track remembers its position, which is how audiobooks and mixes are
+    /// played.
+    fn seek_step(&mut self, forward: bool, long: bool) -> KeyAction {
+        if self.playback_status == "stopped" {
+            return KeyAction::Continue;
         }
+        let step_ms = match (self.snapshot.now_playing_remembers_position, long) {
+            (false, false) => 5_000,
+            (false, true) | (true, false) => 30_000,
+            (true, true) => 300_000,
+        };
+        let position_ms = if forward {
+            let ahead = self.playback_position_ms.saturating_add(step_ms);
+            match self.playback_duration_ms {
+                0 => ahead,
+                duration => ahead.min(duration.saturating_sub(1_000)),
+            }
+        } else {
+            self.playback_position_ms.saturating_sub(step_ms)
+        };
+        // Repeated presses add up before the player reports the new position.
+        self.playback_position_ms = position_ms;
+        KeyAction::Playback(PlaybackAction::Seek { position_ms })
     }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum InputMode {
-    Normal,
-    TrackFilter,
-    CommandPalette,
-    AddMusic,
-    Welcome,
-    TrackInfo,
-    Settings,
-}
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum KeyAction {
-    Continue,
-    Quit,
-    RefreshRequested,
-    CommandSubmitted(String),
-    Playback(PlaybackAction),
-}
+    /// Seeks to the start of the next or previous chapter. Going back from
+    /// more than a few seconds into a chapter restarts it, as previous-track
+    /// does.
+    fn jump_chapter(&mut self, forward: bool) -> KeyAction {
+        if self.playback_status == "stopped" {
+            return KeyAction::Continue;
+        }
+        let chapters = &self.snapshot.now_playing_chapters;
+        if chapters.is_empty() {
+            self.status_message = Some("No chapters in this track".to_string());
+            return KeyAction::Continue;
+        }
+        let position = self.playback_position_ms;
+        let target = if forward {
+            chapters.iter().position(|(start, _)| *start > position)
+        } else {
+            chapters
+                .iter()
+                .rposition(|(start, _)| start.saturating_add(3_000) < position)
+        };
+        let Some(index) = target else {
+            self.status_message = Some(
+                if forward {
+                    "Last chapter"
+                } else {
+                    "First chapter"
+                }
+                .to_string(),
+            );
+            return KeyAction::Continue;
+        };
+        let (position_ms, title) = chapters[index].clone();
+        self.status_message = Some(if title.is_empty() {
+            format!("Chapter {}", index + 1)
+        } else {
+            format!("Chapter {}: {title}", index + 1)
+        });
+        self.playback_position_ms = position_ms;
+        KeyAction::Playback(PlaybackAction::Seek { position_ms })
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum PlaybackAction {
-    PlayTrack { track_index: usize },
-    TogglePause,
-    Stop,
-    Next,
-    Previous,
-    VolumeUp,
-    VolumeDown,
-    ToggleShuffle,
-    Seek { position_ms: u64 },
-}
+    /// Where chapters start, as fractions of the playing track.
+    fn chapter_marks(&self) -> Vec<f32> {
+        match self.playback_duration_ms {
+            0 => Vec::new(),
+            duration => self
+                .snapshot
+                .now_playing_chapters
+                .iter()
+                .map(|(start, _)| *start as f32 / duration as f32)
+                .collect(),
+        }
+    }
 
-#[derive(Debug, Clone, Copy)]
-pub struct RunOptions {
-    pub tick_rate: Duration,
-    pub mouse: bool,
-}
+    /// Seeks to `tenths` of the way through the track, as the number keys do
+    /// in mpv. The progress bar moves straight away.
+    fn seek_to_tenth(&mut self, tenths: u64) -> KeyAction {
+        if self.playback_status == "stopped" {
+            return KeyAction::Continue;
+        }
+        if self.playback_duration_ms == 0 {
+            self.status_message = Some("Track length unknown; cannot seek by percent".to_string());
+            return KeyAction::Continue;
+        }
+        let position_ms = self.playback_duration_ms * tenths / 10;
+        self.playback_position_ms = position_ms;
+        KeyAction::Playback(PlaybackAction::Seek { position_ms })
+    }
 
-impl Default for RunOptions {
-    fn default() -> Self {
-        Self {
-            tick_rate: Duration::from_millis(100),
-            mouse: true,
+    /// Pages the Settings dialog shows: the app settings, or the properties
+    /// of one library root.
+    fn settings_pages(&self) -> Vec<crate::settings::SettingsPage> {
+        match self
+            .settings_root
+            .and_then(|i| self.snapshot.root_options.get(i))
+        {
+            Some(options) => vec![crate::settings::root_properties_page(options)],
+            None => crate::settings::settings_pages(&self.snapshot),
         }
     }
-}
 
-type RefreshSnapshotFn<'a> = dyn FnMut() -> Result<ShellSnapshot, UiError> + 'a;
-type CommandPaletteFn<'a> = dyn FnMut(&str) -> Result<PaletteCommandResult, UiError> + 'a;
-type BackgroundScanFn<'a> =
-    dyn FnMut(String) -> std::sync::mpsc::Receiver<ScanProgress> + 'a;
+    /// Settings are applied and persisted by the app as soon as they change.
+    fn setting_command(&self, item: &crate::settings::SettingItem, value: &str) -> KeyAction {
+        let root = self.settings_root.and_then(|i| self.snapshot.roots.get(i));
+        match root {
+            Some(root) => KeyAction::CommandSubmitted(format!(
+                "__root_set {} {value} {}",
+                item.key, root.label
+            )),
+            None => KeyAction::CommandSubmitted(format!("__setting_set {} {value}", item.key)),
+        }
+    }
 
-/// Progress messages sent from a background scan thread.
-#[derive(Debug, Clone)]
-pub enum ScanProgress {
-    /// Periodic progress update: discovered N files so far.
-    Progress { discovered: usize, path: String },
-    /// Scan completed successfully.
-    Done { message: String },
-    /// Scan failed.
-    Error { message: String },
-}
+    fn handle_settings_key(&mut self, key: KeyEvent) -> KeyAction {
+        let pages = self.settings_pages();
+        self.settings_page = self.settings_page.min(pages.len().saturating_sub(1));
+        let Some(page) = pages.get(self.settings_page) else {
+            self.input_mode = InputMode::Normal;
+            return KeyAction::Continue;
+        };
+        self.settings_index = self.settings_index.min(page.items.len().saturating_sub(1));
+        let item = page.items.get(self.settings_index);
 
-#[derive(Debug, Clone)]
-pub struct PlayerEventUpdate {
-    pub position_ms: u64,
-    pub duration_ms: u64,
-    pub status: String,
-    pub track_finished: bool,
-    pub spectrum_bands: Vec<f32>,
-    pub raw_samples: Vec<f32>,
-}
+        if let Some(editor) = self.settings_editor.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.settings_editor = None,
+                KeyCode::Enter => {
+                    let raw = editor.take();
+                    self.settings_editor = None;
+                    let Some(item) = item else {
+                        return KeyAction::Continue;
+                    };
+                    match item.validate(&raw) {
+                        Ok(value) => return self.setting_command(item, &value),
+                        Err(err) => self.status_message = Some(err),
+                    }
+                }
+                KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return KeyAction::PasteFromClipboard;
+                }
+                _ => {
+                    editor.handle_key(&key);
+                }
+            }
+            return KeyAction::Continue;
+        }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct PaletteCommandResult {
-    pub status_message: String,
-    pub refresh_requested: bool,
-    /// If set, the event loop should spawn a background scan for this path.
-    pub background_scan_path: Option<String>,
-    /// Artwork data returned by __fetch_artwork command.
-    pub artwork_data: Option<Vec<u8>>,
-}
+        match key.code {
+            KeyCode::Esc | KeyCode::Char(',') | KeyCode::Char('q') => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.settings_index =
+                    (self.settings_index + 1).min(page.items.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.settings_index = self.settings_index.saturating_sub(1);
+            }
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => {
+                self.settings_page = (self.settings_page + 1) % pages.len();
+                self.settings_index = 0;
+            }
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => {
+                self.settings_page = (self.settings_page + pages.len() - 1) % pages.len();
+                self.settings_index = 0;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let Some(item) = item else {
+                    return KeyAction::Continue;
+                };
+                if item.is_editable_text() {
+                    self.settings_editor = Some(TextInput::with_value(item.value.clone()));
+                } else if let Some(value) = item.step(true) {
+                    return self.setting_command(item, &value);
+                }
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Char('-') => {
+                let forward = key.code != KeyCode::Char('-');
+                if let Some((item, value)) = item.and_then(|i| i.step(forward).map(|v| (i, v))) {
+                    return self.setting_command(item, &value);
+                }
+            }
+            _ => {}
+        }
+        KeyAction::Continue
+    }
 
-impl PaletteCommandResult {
-    pub fn new(status_message: impl Into<String>, refresh_requested: bool) -> Self {
-        Self {
-            status_message: status_message.into(),
-            refresh_requested,
-            background_scan_path: None,
-            artwork_data: None,
+    fn handle_filter_key(&mut self, key: KeyEvent) -> KeyAction {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => {
+                self.input_mode = InputMode::Normal;
+                self.status_message = Some(self.filter_status_line(false));
+            }
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return KeyAction::PasteFromClipboard;
+            }
+            _ => {
+                if self.track_filter_query.handle_key(&key) {
+                    self.rebuild_track_filter();
+                    self.status_message = Some(self.filter_status_line(true));
+                }
+            }
         }
+        KeyAction::Continue
     }
 
-    pub fn with_background_scan(
-        status_message: impl Into<String>,
-        scan_path: String,
-    ) -> Self {
-        Self {
-            status_message: status_message.into(),
-            refresh_requested: false,
-            background_scan_path: Some(scan_path),
-            artwork_data: None,
+    fn enter_track_filter_mode(&mut self) {
+        self.input_mode = InputMode::TrackFilter;
+        self.status_message = Some(self.filter_status_line(true));
+    }
+
+    fn handle_command_palette_key(&mut self, key: KeyEvent) -> KeyAction {
+        match key.code {
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.command_palette_input.clear();
+                self.status_message = Some("Command palette canceled".to_string());
+            }
+            KeyCode::Enter => {
+                self.input_mode = InputMode::Normal;
+                let command = self.command_palette_input.take().trim().to_string();
+                if command.is_empty() {
+                    self.status_message = Some("Command palette canceled".to_string());
+                } else {
+                    return KeyAction::CommandSubmitted(command);
+                }
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return KeyAction::PasteFromClipboard;
+            }
+            _ => {
+                if self.command_palette_input.handle_key(&key) {
+                    self.status_message = Some(self.command_palette_status_line());
+                }
+            }
         }
+        KeyAction::Continue
     }
 
-    pub fn with_artwork(status_message: impl Into<String>, data: Option<Vec<u8>>) -> Self {
-        Self {
-            status_message: status_message.into(),
-            refresh_requested: false,
-            background_scan_path: None,
-            artwork_data: data,
+    /// Normal-mode keys while party mode is on. Enter on a track adds it to
+    /// the queue, quitting asks for the passphrase, and keys that control
+    /// playback or change the library are refused; `None` lets navigation,
+    /// filtering, help and the palette through.
+    fn handle_party_key(&mut self, key: &KeyEvent) -> Option<KeyAction> {
+        let quit = key.code == KeyCode::Char('q')
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+        if quit {
+            self.party_passphrase_input.clear();
+            self.input_mode = InputMode::PartyUnlock;
+            self.status_message = Some(self.party_unlock_status_line());
+            return Some(KeyAction::Continue);
+        }
+        match key.code {
+            KeyCode::Enter if self.focus == FocusPane::Tracks => {
+                Some(match self.selected_track_item() {
+                    Some(track) => KeyAction::Playback(PlaybackAction::Enqueue {
+                        path: track.path.clone(),
+                        requested_by: "keyboard".to_string(),
+                    }),
+                    None => KeyAction::Continue,
+                })
+            }
+            KeyCode::Enter if self.focus == FocusPane::Inspector => Some(self.party_refusal()),
+            KeyCode::Char(
+                ' ' | 'n' | 'N' | '+' | '=' | '-' | 's' | 'S' | 'R' | '[' | ']' | '{' | '}',
+            )
+            | KeyCode::Char('b' | 'B' | 'a' | ',' | 'P' | 'i' | 'u' | 'c' | 'D' | '0'..='9') => {
+                Some(self.party_refusal())
+            }
+            KeyCode::Char('e' | 'E' | 'w' | 'W' | 'C') => Some(self.party_refusal()),
+            _ => None,
         }
     }
-}
 
-#[derive(Debug, Clone, Copy, Default)]
-struct PaneArea {
-    outer: Rect,
-    inner: Rect,
-    visible_items: usize,
-    item_height: u16,
-}
+    fn party_refusal(&mut self) -> KeyAction {
+        self.status_message =
+            Some("Party mode: Enter adds the selected track to the queue".to_string());
+        KeyAction::Continue
+    }
 
-impl PaneArea {
-    #[cfg_attr(not(test), allow(dead_code))]
-    fn bordered(area: Rect, item_height: u16) -> Self {
-        Self::from_list_area(area, inner_rect(area), item_height)
+    fn handle_party_unlock_key(&mut self, key: KeyEvent) -> KeyAction {
+        match key.code {
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.party_passphrase_input.clear();
+                self.status_message = Some("Still partying".to_string());
+            }
+            KeyCode::Enter => {
+                self.input_mode = InputMode::Normal;
+                let passphrase = self.party_passphrase_input.take();
+                self.status_message = None;
+                return KeyAction::CommandSubmitted(format!("__party_quit {passphrase}"));
+            }
+            _ => {
+                if self.party_passphrase_input.handle_key(&key) {
+                    self.status_message = Some(self.party_unlock_status_line());
+                }
+            }
+        }
+        KeyAction::Continue
     }
 
-    /// For borderless sections: header takes 1 row, content has 1-cell left pad.
-    fn borderless(area: Rect, item_height: u16) -> Self {
-        let content = borderless_content_area(area);
-        Self::from_list_area(area, content, item_height)
+    /// The Playlists pane lists the playlists and then "+ New Playlist".
+    fn playlist_rows(&self) -> usize {
+        self.snapshot.playlists.len() + 1
     }
 
-    fn from_list_area(outer: Rect, list_area: Rect, item_height: u16) -> Self {
-        let inner = list_area;
-        let inner_height = inner.height as usize;
-        let item_height_usize = usize::from(item_height.max(1));
-        let visible_items = if inner_height == 0 {
-            0
-        } else {
-            inner_height.div_ceil(item_height_usize)
-        };
-        Self {
-            outer,
-            inner,
-            visible_items,
-            item_height: item_height.max(1),
-        }
+    fn open_new_playlist_prompt(&mut self) {
+        self.new_playlist_input.clear();
+        self.input_mode = InputMode::NewPlaylist;
+        self.status_message = Some(self.new_playlist_status_line());
     }
 
-    fn mouse_item_index(&self, x: u16, y: u16, scroll: usize, len: usize) -> Option<usize> {
-        if len == 0 || !self.inner.contains((x, y).into()) {
-            return None;
+    fn handle_new_playlist_key(&mut self, key: KeyEvent) -> KeyAction {
+        match key.code {
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.new_playlist_input.clear();
+                self.status_message = Some("Cancelled".to_string());
+            }
+            KeyCode::Enter => {
+                let name = self.new_playlist_input.as_str().trim().to_string();
+                if name.is_empty() {
+                    return KeyAction::Continue;
+                }
+                self.input_mode = InputMode::Normal;
+                self.new_playlist_input.clear();
+                self.status_message = None;
+                self.pending_playlist_select = Some(name.clone());
+                return KeyAction::CommandSubmitted(format!("playlist create {name}"));
+            }
+            _ => {
+                if self.new_playlist_input.handle_key(&key) {
+                    self.status_message = Some(self.new_playlist_status_line());
+                }
+            }
         }
-        let local_row = usize::from(y.saturating_sub(self.inner.y));
-        let item_height = usize::from(self.item_height.max(1));
-        let index = scroll.saturating_add(local_row / item_height);
-        (index < len).then_some(index)
+        KeyAction::Continue
     }
-}
 
-#[derive(Debug, Clone, Copy, Default)]
-struct RenderAreas {
-    roots: PaneArea,
-    browse: Rect,
-    browse_items: Option<PaneArea>,
-    playlists: PaneArea,
-    tracks: PaneArea,
-    track_header: Rect,
-    track_col_offsets: TrackColumnOffsets,
-}
-
-#[derive(Debug, Clone, Copy, Default)]
-struct TrackColumnOffsets {
-    title_start: u16,
-    time_start: u16,
-    artist_start: u16,
-    album_start: u16,
-    quality_start: u16,
-}
-
-pub fn run_interactive(
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-) -> Result<(), UiError> {
-    run_interactive_with_optional_handlers(state, palette, options, None, None, None)
-}
-
-pub fn run_interactive_with_refresh<F>(
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-    mut refresh: F,
-) -> Result<(), UiError>
-where
-    F: FnMut() -> Result<ShellSnapshot, UiError>,
-{
-    run_interactive_with_optional_handlers(state, palette, options, Some(&mut refresh), None, None)
-}
-
-pub fn run_interactive_with_handlers<FRefresh, FCommand>(
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-    mut refresh: FRefresh,
-    mut command_handler: FCommand,
-) -> Result<(), UiError>
-where
-    FRefresh: FnMut() -> Result<ShellSnapshot, UiError>,
-    FCommand: FnMut(&str) -> Result<PaletteCommandResult, UiError>,
-{
-    run_interactive_with_optional_handlers(
-        state,
-        palette,
-        options,
-        Some(&mut refresh),
-        Some(&mut command_handler),
-        None,
-    )
-}
-
-pub fn run_interactive_with_scan<FRefresh, FCommand, FScan>(
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-    mut refresh: FRefresh,
-    mut command_handler: FCommand,
-    mut scan_handler: FScan,
-) -> Result<(), UiError>
-where
-    FRefresh: FnMut() -> Result<ShellSnapshot, UiError>,
-    FCommand: FnMut(&str) -> Result<PaletteCommandResult, UiError>,
-    FScan: FnMut(String) -> std::sync::mpsc::Receiver<ScanProgress>,
-{
-    run_interactive_with_optional_handlers(
-        state,
-        palette,
-        options,
-        Some(&mut refresh),
-        Some(&mut command_handler),
-        Some(&mut scan_handler),
-    )
-}
-
-type PlaybackActionFn<'a> = dyn FnMut(PlaybackAction) -> Result<PaletteCommandResult, UiError> + 'a;
-type PlayerPollFn<'a> = dyn FnMut() -> Vec<PlayerEventUpdate> + 'a;
-
-fn run_interactive_with_optional_handlers(
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-    refresh: Option<&mut RefreshSnapshotFn<'_>>,
-    command_handler: Option<&mut CommandPaletteFn<'_>>,
-    scan_handler: Option<&mut BackgroundScanFn<'_>>,
-) -> Result<(), UiError> {
-    run_interactive_full_inner(
-        state,
-        palette,
-        options,
-        refresh,
-        command_handler,
-        scan_handler,
-        None,
-        None,
-    )
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn run_interactive_full<FRefresh, FCommand, FScan, FPlayback, FPlayerPoll>(
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-    mut refresh: FRefresh,
-    mut command_handler: FCommand,
-    mut scan_handler: FScan,
-    mut playback_handler: FPlayback,
-    mut player_poll: FPlayerPoll,
-) -> Result<(), UiError>
-where
-    FRefresh: FnMut() -> Result<ShellSnapshot, UiError>,
-    FCommand: FnMut(&str) -> Result<PaletteCommandResult, UiError>,
-    FScan: FnMut(String) -> std::sync::mpsc::Receiver<ScanProgress>,
-    FPlayback: FnMut(PlaybackAction) -> Result<PaletteCommandResult, UiError>,
-    FPlayerPoll: FnMut() -> Vec<PlayerEventUpdate>,
-{
-    run_interactive_full_inner(
-        state,
-        palette,
-        options,
-        Some(&mut refresh),
-        Some(&mut command_handler),
-        Some(&mut scan_handler),
-        Some(&mut playback_handler),
-        Some(&mut player_poll),
-    )
-}
-
-#[allow(clippy::too_many_arguments)]
-fn run_interactive_full_inner(
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-    refresh: Option<&mut RefreshSnapshotFn<'_>>,
-    command_handler: Option<&mut CommandPaletteFn<'_>>,
-    scan_handler: Option<&mut BackgroundScanFn<'_>>,
-    playback_handler: Option<&mut PlaybackActionFn<'_>>,
-    player_poll: Option<&mut PlayerPollFn<'_>>,
-) -> Result<(), UiError> {
-    enable_raw_mode().map_err(|e| UiError::Terminal(format!("enable_raw_mode failed: {e}")))?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)
-        .map_err(|e| UiError::Terminal(format!("enter alt screen failed: {e}")))?;
-    if options.mouse {
-        execute!(stdout, EnableMouseCapture)
-            .map_err(|e| UiError::Terminal(format!("enable mouse capture failed: {e}")))?;
+    fn new_playlist_status_line(&self) -> String {
+        format!(
+            "New playlist name: {}  (Enter create, Esc cancel)",
+            self.new_playlist_input
+        )
     }
-    execute!(stdout, EnableBracketedPaste)
-        .map_err(|e| UiError::Terminal(format!("enable bracketed paste failed: {e}")))?;
-
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)
-        .map_err(|e| UiError::Terminal(format!("terminal init failed: {e}")))?;
-
-    let result = run_loop(
-        &mut terminal,
-        state,
-        palette,
-        options,
-        refresh,
-        command_handler,
-        scan_handler,
-        playback_handler,
-        player_poll,
-    );
 
-    let _ = execute!(terminal.backend_mut(), DisableBracketedPaste);
-    if options.mouse {
-        let _ = execute!(terminal.backend_mut(), DisableMouseCapture);
+    /// Asks `question` in the status line; `y` runs `command`, any other
+    /// key cancels.
+    fn ask_to_confirm(&mut self, question: String, command: String) {
+        self.status_message = Some(format!("{question}  (y confirm, any other key cancels)"));
+        self.pending_confirm = Some(command);
+        self.input_mode = InputMode::Confirm;
     }
-    let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
-    drop(terminal);
-    let _ = disable_raw_mode();
 
-    result
-}
+    fn handle_confirm_key(&mut self, key: KeyEvent) -> KeyAction {
+        self.input_mode = InputMode::Normal;
+        let command = self.pending_confirm.take();
+        match (key.code, command) {
+            (KeyCode::Char('y' | 'Y'), Some(command)) => {
+                self.status_message = None;
+                KeyAction::CommandSubmitted(command)
+            }
+            _ => {
+                self.status_message = Some("Cancelled".to_string());
+                KeyAction::Continue
+            }
+        }
+    }
 
-#[allow(clippy::too_many_arguments)]
-fn run_loop(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    state: &mut ShellState,
-    palette: &Palette,
-    options: RunOptions,
-    mut refresh: Option<&mut RefreshSnapshotFn<'_>>,
-    mut command_handler: Option<&mut CommandPaletteFn<'_>>,
-    mut scan_handler: Option<&mut BackgroundScanFn<'_>>,
-    mut playback_handler: Option<&mut PlaybackActionFn<'_>>,
-    mut player_poll: Option<&mut PlayerPollFn<'_>>,
-) -> Result<(), UiError> {
-    use std::sync::mpsc;
+    fn party_unlock_status_line(&self) -> String {
+        format!(
+            "Passphrase to quit: {}  (Enter confirm, Esc cancel)",
+            "*".repeat(self.party_passphrase_input.as_str().chars().count())
+        )
+    }
 
-    let mut last_draw = Instant::now();
-    let mut last_areas = RenderAreas::default();
-    let mut scan_rx: Option<mpsc::Receiver<ScanProgress>> = None;
-    let mut last_scan_refresh = Instant::now();
+    fn enter_command_palette_mode(&mut self) {
+        self.input_mode = InputMode::CommandPalette;
+        self.command_palette_input.clear();
+        self.status_message = Some(self.command_palette_status_line());
+    }
 
-    // Helper closure: handle a PaletteCommandResult, optionally starting a background scan.
-    let handle_command_result = |state: &mut ShellState,
-                                 result: PaletteCommandResult,
-                                 refresh: &mut Option<&mut RefreshSnapshotFn<'_>>,
-                                 scan_handler: &mut Option<&mut BackgroundScanFn<'_>>,
-                                 scan_rx: &mut Option<mpsc::Receiver<ScanProgress>>| {
-        state.status_message = Some(result.status_message);
-        if result.refresh_requested {
-            try_refresh_snapshot(state, refresh);
-        }
-        if let Some(scan_path) = result.background_scan_path {
-            if let Some(handler) = scan_handler.as_mut() {
-                state.scanning_path = Some(scan_path.clone());
-                state.status_message = Some(format!("Scanning {}...", scan_path));
-                *scan_rx = Some((*handler)(scan_path));
+    fn handle_add_music_key(&mut self, key: KeyEvent) -> KeyAction {
+        let browser = match self.file_browser.as_mut() {
+            Some(b) => b,
+            None => {
+                self.input_mode = InputMode::Normal;
+                return KeyAction::Continue;
             }
-        }
-        if result.artwork_data.is_some() {
-            state.track_info_artwork = result.artwork_data;
-        }
-    };
+        };
 
-    loop {
-        // Poll background scan progress (non-blocking)
-        if let Some(rx) = &scan_rx {
-            loop {
-                match rx.try_recv() {
-                    Ok(ScanProgress::Progress { discovered, path }) => {
-                        state.status_message =
-                            Some(format!("Scanning {path}... ({discovered} tracks imported)"));
-                        // Refresh snapshot frequently to show track count updates
-                        if last_scan_refresh.elapsed() >= Duration::from_millis(750) {
-                            try_refresh_snapshot(state, &mut refresh);
-                            last_scan_refresh = Instant::now();
-                        }
-                    }
-                    Ok(ScanProgress::Done { message }) => {
-                        state.scanning_path = None;
-                        state.status_message = Some(message);
-                        try_refresh_snapshot(state, &mut refresh);
-                        break;
-                    }
-                    Ok(ScanProgress::Error { message }) => {
-                        state.scanning_path = None;
-                        state.status_message = Some(format!("Scan failed: {message}"));
-                        break;
-                    }
-                    Err(mpsc::TryRecvError::Empty) => break,
-                    Err(mpsc::TryRecvError::Disconnected) => {
-                        state.scanning_path = None;
-                        state.status_message = Some("Scan finished".to_string());
-                        try_refresh_snapshot(state, &mut refresh);
-                        break;
+        if browser.input_focused {
+            match key.code {
+                KeyCode::Esc => {
+                    browser.input_focused = false;
+                }
+                KeyCode::Tab => {
+                    let names = browser.complete_path_input();
+                    self.status_message = match names.len() {
+                        0 => Some("No folder matches".to_string()),
+                        1 => None,
+                        _ => Some(truncate_to_width(&names.join("  "), 200)),
+                    };
+                }
+                KeyCode::Enter => {
+                    if browser.apply_path_input() {
+                        browser.input_focused = false;
+                    } else {
+                        self.status_message =
+                            Some(format!("Not a folder: {}", browser.path_input.as_str()));
                     }
                 }
+                KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return KeyAction::PasteFromClipboard;
+                }
+                _ => {
+                    browser.path_input.handle_key(&key);
+                }
             }
-            if state.scanning_path.is_none() {
-                scan_rx = None;
-            }
+            return KeyAction::Continue;
         }
 
-        // Poll player events
-        if let Some(poll_fn) = player_poll.as_mut() {
-            for update in (*poll_fn)() {
-                if !update.status.is_empty() {
-                    state.playback_status = update.status;
-                }
-                if update.position_ms > 0 || update.duration_ms > 0 {
-                    state.playback_position_ms = update.position_ms;
-                    state.playback_duration_ms = update.duration_ms;
-                }
-                if !update.spectrum_bands.is_empty() {
-                    state.spectrum_bands = crate::visualizer::smooth_bands(
-                        &state.spectrum_bands,
-                        &update.spectrum_bands,
-                        0.6,
-                        0.15,
-                    );
-                    // Update fire history (scrolling spectrogram)
-                    state.fire_history.insert(0, state.spectrum_bands.clone());
-                    if state.fire_history.len() > 40 {
-                        state.fire_history.truncate(40);
-                    }
+        // While a filter is typed every character extends it; the arrows and
+        // Enter still move and open.
+        if !browser.filter().is_empty() {
+            let mut filter = browser.filter().to_string();
+            let edited = match key.code {
+                KeyCode::Esc => {
+                    filter.clear();
+                    true
                 }
-                if !update.raw_samples.is_empty() {
-                    state.viz_samples = update.raw_samples;
+                KeyCode::Backspace => {
+                    filter.pop();
+                    true
                 }
-                state.viz_frame = state.viz_frame.wrapping_add(1);
-                if update.track_finished {
-                    // Auto-advance to next track
-                    if let Some(handler) = playback_handler.as_mut() {
-                        if let Ok(result) = (*handler)(PlaybackAction::Next) {
-                            state.status_message = Some(result.status_message);
-                            if result.refresh_requested {
-                                try_refresh_snapshot(state, &mut refresh);
-                            }
-                        }
-                    }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    filter.push(c);
+                    true
                 }
+                _ => false,
+            };
+            if edited {
+                browser.set_filter(&filter);
+                browser.sync_path_input_to_selected();
+                return KeyAction::Continue;
             }
         }
 
-        terminal
-            .draw(|f| {
-                last_areas = draw_shell(f, state, palette);
-            })
-            .map_err(|e| UiError::Terminal(format!("draw failed: {e}")))?;
-
-        let elapsed = last_draw.elapsed();
-        let timeout = options.tick_rate.saturating_sub(elapsed);
-        if event::poll(timeout).map_err(|e| UiError::Terminal(format!("poll failed: {e}")))? {
-            match event::read().map_err(|e| UiError::Terminal(format!("read event failed: {e}")))? {
-                Event::Key(key) => match state.handle_key(key) {
-                    KeyAction::Quit => return Ok(()),
-                    KeyAction::Continue => {}
-                    KeyAction::RefreshRequested => {
-                        try_refresh_snapshot(state, &mut refresh);
-                    }
-                    KeyAction::CommandSubmitted(command) => {
-                        if let Some(handler) = command_handler.as_mut() {
-                            match (*handler)(&command) {
-                                Ok(result) => {
-                                    handle_command_result(
-                                        state,
-                                        result,
-                                        &mut refresh,
-                                        &mut scan_handler,
-                                        &mut scan_rx,
-                                    );
-                                }
-                                Err(err) => {
-                                    state.status_message = Some(format!("Command failed: {err}"));
-                                }
-                            }
-                        } else {
-                            state.status_message = Some(format!(
-                                "Command palette unavailable in this shell mode: {command}"
-                            ));
-                        }
+        match key.code {
+            KeyCode::Esc => {
+                self.file_browser = None;
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                browser.focus_path_input();
+            }
+            KeyCode::Tab | KeyCode::Char('/') => {
+                browser.focus_path_input();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                browser.move_selection(1);
+                browser.sync_path_input_to_selected();
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                browser.move_selection(-1);
+                browser.sync_path_input_to_selected();
+            }
+            KeyCode::Enter => {
+                browser.enter_selected();
+            }
+            KeyCode::Backspace | KeyCode::Char('h') => {
+                browser.go_up();
+            }
+            KeyCode::Char(' ') => return self.add_selected_folder(),
+            KeyCode::Char('.') => {
+                browser.toggle_hidden();
+                self.status_message = Some(if browser.show_hidden() {
+                    "Showing hidden folders".to_string()
+                } else {
+                    "Hiding hidden folders".to_string()
+                });
+            }
+            KeyCode::Char('b') => {
+                let dir = browser.current_dir().display().to_string();
+                if self.browser_places.toggle_bookmark(&dir).is_none() {
+                    self.status_message = Some(format!(
+                        "All {} bookmark slots are taken; press b in one to free it",
+                        crate::file_browser::BOOKMARK_SLOTS
+                    ));
+                    return KeyAction::Continue;
+                }
+                return self.places_changed();
+            }
+            KeyCode::Char(digit @ '1'..='9') => {
+                let slot = usize::from(digit as u8 - b'1');
+                match self.browser_places.bookmarks.get(slot) {
+                    Some(dir) if std::path::Path::new(dir).is_dir() => {
+                        browser.navigate_to(std::path::Path::new(dir))
                     }
-                    KeyAction::Playback(action) => {
-                        if let Some(handler) = playback_handler.as_mut() {
-                            match (*handler)(action) {
-                                Ok(result) => {
-                                    state.status_message = Some(result.status_message);
-                                    if result.refresh_requested {
-                                        try_refresh_snapshot(state, &mut refresh);
-                                    }
-                                }
-                                Err(err) => {
-                                    state.status_message =
-                                        Some(format!("Playback error: {err}"));
-                                }
-                            }
-                        }
+                    Some(dir) => {
+                        self.status_message =
+                            Some(format!("Bookmark {digit} is not reachable: {dir}"))
                     }
-                },
-                Event::Mouse(mouse) => {
-                    if options.mouse {
-                        let mouse_action = state.handle_mouse(mouse, &last_areas);
-                        if let KeyAction::Playback(action) = mouse_action {
-                            if let Some(handler) = playback_handler.as_mut() {
-                                match (*handler)(action) {
-                                    Ok(result) => {
-                                        state.status_message = Some(result.status_message);
-                                        if result.refresh_requested {
-                                            try_refresh_snapshot(state, &mut refresh);
-                                        }
-                                    }
-                                    Err(err) => {
-                                        state.status_message =
-                                            Some(format!("Playback error: {err}"));
-                                    }
-                                }
-                            }
-                        }
+                    None => {
+                        self.status_message =
+                            Some(format!("No bookmark {digit}; b bookmarks this folder"))
                     }
                 }
-                Event::Resize(_, _) => {}
-                Event::Paste(content) => {
-                    let paths: Vec<String> = content
-                        .lines()
-                        .map(|l| l.trim().to_string())
-                        .filter(|l| !l.is_empty())
-                        .collect();
+            }
+            KeyCode::Char('r') => match self.browser_places.next_recent(browser.current_dir()) {
+                Some(dir) => browser.navigate_to(std::path::Path::new(dir)),
+                None => self.status_message = Some("No recently added folders yet".to_string()),
+            },
+            // Any other character starts narrowing the listing.
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                browser.set_filter(&c.to_string());
+                browser.sync_path_input_to_selected();
+            }
+            _ => {}
+        }
+        KeyAction::Continue
+    }
 
-                    for path_str in paths {
-                        let path = std::path::Path::new(&path_str);
-                        if path.is_dir() {
-                            match state.input_mode {
-                                InputMode::AddMusic | InputMode::Welcome => {
-                                    if let Some(browser) = state.file_browser.as_mut() {
-                                        browser.navigate_to(path);
-                                    }
-                                }
-                                InputMode::Normal => {
-                                    if let Some(handler) = command_handler.as_mut() {
-                                        match (*handler)(&format!("__add_root {path_str}")) {
-                                            Ok(result) => {
-                                                handle_command_result(
-                                                    state,
-                                                    result,
-                                                    &mut refresh,
-                                                    &mut scan_handler,
-                                                    &mut scan_rx,
-                                                );
-                                            }
-                                            Err(err) => {
-                                                state.status_message =
-                                                    Some(format!("Drop failed: {err}"));
-                                            }
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
-                        } else {
-                            state.status_message =
-                                Some(format!("Not a directory: {path_str}"));
-                        }
-                    }
+    /// Asks the host to persist bookmarks and recent folders.
+    fn places_changed(&self) -> KeyAction {
+        match serde_json::to_string(&self.browser_places) {
+            Ok(json) => KeyAction::CommandSubmitted(format!("__places_save {json}")),
+            Err(_) => KeyAction::Continue,
+        }
+    }
+
+    fn rebuild_track_filter(&mut self) {
+        self.filtered_track_indices.clear();
+        if self.track_filter_query.is_empty() {
+            self.filtered_track_indices
+                .extend(0..self.snapshot.tracks.len());
+        } else {
+            let (query, bpm_range) =
+                split_bpm_range(&self.track_filter_query.as_str().to_lowercase());
+            self.filtered_track_indices.extend(
+                self.snapshot
+                    .tracks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, track)| query.is_empty() || track_matches_query(track, &query))
+                    .filter(|(_, track)| {
+                        bpm_range
+                            .as_ref()
+                            .is_none_or(|range| track.bpm.is_some_and(|bpm| range.contains(&bpm)))
+                    })
+                    .map(|(idx, _)| idx),
+            );
+        }
+        if let Some(ref artist) = self.browse_filter_artist {
+            self.filtered_track_indices.retain(|&idx| {
+                self.snapshot.tracks[idx]
+                    .artist
+                    .eq_ignore_ascii_case(artist)
+            });
+        }
+        if let Some(ref album) = self.browse_filter_album {
+            self.filtered_track_indices
+                .retain(|&idx| self.snapshot.tracks[idx].album.eq_ignore_ascii_case(album));
+        }
+        self.apply_sort();
+        self.selected_track = self
+            .selected_track
+            .min(self.filtered_track_indices.len().saturating_sub(1));
+    }
+
+    fn apply_sort(&mut self) {
+        let tracks = &self.snapshot.tracks;
+        let col = self.sort_column;
+        let asc = self.sort_ascending;
+        self.filtered_track_indices.sort_by(|&a, &b| {
+            let cmp = col.compare(&tracks[a], &tracks[b]);
+            if asc {
+                cmp
+            } else {
+                cmp.reverse()
+            }
+        });
+    }
+
+    fn set_sort_column(&mut self, col: SortColumn) {
+        if self.sort_column == col {
+            self.sort_ascending = !self.sort_ascending;
+        } else {
+            self.sort_column = col;
+            self.sort_ascending = true;
+        }
+        self.apply_sort();
+        self.selected_track = 0;
+    }
+
+    fn cycle_sort(&mut self) {
+        if self.sort_ascending {
+            self.sort_ascending = false;
+        } else {
+            self.sort_column = self.sort_column.next();
+            self.sort_ascending = true;
+        }
+        self.apply_sort();
+        self.selected_track = 0;
+    }
+
+    fn handle_browse_enter(&mut self) {
+        self.remember_view();
+        if self.browse.show_items && !self.browse.items.is_empty() {
+            self.browse.update_selected_item();
+            match self.browse.mode {
+                crate::browse::BrowseMode::Artists => {
+                    self.browse_filter_artist = self.browse.selected_item.clone();
+                    self.browse_filter_album = None;
+                }
+                crate::browse::BrowseMode::Albums => {
+                    self.browse_filter_album = self.browse.selected_item.clone();
+                    self.browse_filter_artist = None;
                 }
-                Event::FocusGained | Event::FocusLost => {}
+                // Recent tracks play from the key handler instead.
+                crate::browse::BrowseMode::Songs | crate::browse::BrowseMode::Recent => {}
             }
+            self.rebuild_track_filter();
+        } else {
+            self.apply_browse_mode();
         }
-        last_draw = Instant::now();
     }
-}
 
-pub fn render_once_to_text(
-    state: &mut ShellState,
-    palette: &Palette,
-    width: u16,
-    height: u16,
-) -> Result<String, UiError> {
-    let backend = TestBackend::new(width, height);
-    let mut terminal = Terminal::new(backend)
-        .map_err(|e| UiError::Terminal(format!("test terminal init failed: {e}")))?;
-    terminal
-        .draw(|f| {
-            let _ = draw_shell(f, state, palette);
-        })
-        .map_err(|e| UiError::Terminal(format!("test draw failed: {e}")))?;
+    fn handle_browse_back(&mut self) {
+        if self.browse.show_items {
+            self.browse.show_items = false;
+            self.browse.selected_item = None;
+            self.browse_filter_artist = None;
+            self.browse_filter_album = None;
+            self.rebuild_track_filter();
+        }
+    }
 
-    let buffer = terminal.backend().buffer().clone();
-    let mut lines = Vec::with_capacity(height as usize);
-    for y in 0..height {
-        let mut line = String::new();
-        for x in 0..width {
-            let symbol = buffer[(x, y)].symbol();
-            line.push_str(symbol);
+    fn current_view(&self) -> NavEntry {
+        NavEntry {
+            focus: self.focus,
+            browse_mode_index: self.browse.mode_index,
+            browse_show_items: self.browse.show_items,
+            browse_item_index: self.browse.item_index,
+            filter_artist: self.browse_filter_artist.clone(),
+            filter_album: self.browse_filter_album.clone(),
+            selected_track: self.selected_track,
         }
-        while line.ends_with(' ') {
-            line.pop();
+    }
+
+    /// Records the current view before navigating away from it.
+    fn remember_view(&mut self) {
+        let view = self.current_view();
+        if self.nav_history.last() == Some(&view) {
+            return;
         }
-        lines.push(line);
+        if self.nav_history.len() == NAV_HISTORY {
+            self.nav_history.remove(0);
+        }
+        self.nav_history.push(view);
     }
-    Ok(lines.join("\n"))
-}
 
-fn draw_shell(frame: &mut Frame, state: &mut ShellState, palette: &Palette) -> RenderAreas {
-    let root = frame.area();
-    frame.render_widget(
-        Block::default().style(Style::default().bg(palette.bg_root())),
-        root,
-    );
+    /// Escape: returns to the view before the last focus change or browse
+    /// drill-down, skipping steps that changed nothing visible. With no
+    /// history left it clears the browse filter.
+    fn navigate_back(&mut self) {
+        let current = self.current_view();
+        while let Some(view) = self.nav_history.pop() {
+            if view != current {
+                self.restore_view(view);
+                return;
+            }
+        }
+        self.handle_browse_back();
+    }
 
-    let vertical = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(12), Constraint::Length(2)])
-        .split(root);
-    let main = vertical[0];
-    let footer = vertical[1];
+    fn save_tab(&self) -> ViewTab {
+        ViewTab {
+            view: self.current_view(),
+            filter: self.track_filter_query.clone(),
+            sort_column: self.sort_column,
+            sort_ascending: self.sort_ascending,
+            history: self.nav_history.clone(),
+        }
+    }
 
-    let cols = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(27),
-            Constraint::Length(1), // vertical separator
-            Constraint::Percentage(73),
-        ])
-        .split(main);
-    let left_col = cols[0];
-    let separator_col = cols[1];
-    let right_col = cols[2];
+    fn load_tab(&mut self, tab: ViewTab) {
+        self.track_filter_query = tab.filter;
+        self.sort_column = tab.sort_column;
+        self.sort_ascending = tab.sort_ascending;
+        self.nav_history = tab.history;
+        self.restore_view(tab.view);
+    }
 
-    render_vertical_separator(frame, separator_col, palette);
+    fn tab_count(&self) -> usize {
+        self.tabs.len().max(1)
+    }
 
-    let left_sections = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(7),
-            Constraint::Length(1),  // gap
-            Constraint::Length(7),
-            Constraint::Length(1),  // gap
-            Constraint::Min(8),
-        ])
-        .split(left_col);
+    /// Opens a tab after the current one showing the same view.
+    fn open_tab(&mut self) {
+        if self.tab_count() == MAX_TABS {
+            self.status_message = Some(format!("All {MAX_TABS} tabs are open; t x closes one"));
+            return;
+        }
+        let current = self.save_tab();
+        if self.tabs.is_empty() {
+            self.tabs.push(current.clone());
+        }
+        self.active_tab += 1;
+        self.tabs.insert(self.active_tab, current);
+        self.status_message = Some(format!("Tab {} opened", self.active_tab + 1));
+    }
 
-    let right_sections = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(8),
-            Constraint::Length(1),  // gap
-            Constraint::Min(12),
-        ])
-        .split(right_col);
+    fn switch_tab(&mut self, index: usize) {
+        if index >= self.tab_count() {
+            self.status_message = Some(format!("No tab {}; t n opens one", index + 1));
+            return;
+        }
+        if index == self.active_tab {
+            return;
+        }
+        self.tabs[self.active_tab] = self.save_tab();
+        self.active_tab = index;
+        self.load_tab(self.tabs[index].clone());
+    }
 
-    let library_area = right_sections[2];
-    let has_browse_split = state.browse.show_items && !state.browse.items.is_empty();
+    fn close_tab(&mut self) {
+        if self.tabs.len() < 2 {
+            self.status_message = Some("Only one tab is open".to_string());
+            return;
+        }
+        self.tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        self.load_tab(self.tabs[self.active_tab].clone());
+        if self.tabs.len() == 1 {
+            self.tabs.clear();
+        }
+    }
 
-    let (tracks_area, browse_items_area) = if has_browse_split {
-        let lib_cols = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(30),
-                Constraint::Length(1),
-                Constraint::Percentage(70),
-            ])
-            .split(library_area);
-        (lib_cols[2], Some(lib_cols[0]))
-    } else {
-        (library_area, None)
-    };
+    fn restore_view(&mut self, view: NavEntry) {
+        let modes = crate::browse::BrowseMode::all();
+        self.browse.mode_index = view.browse_mode_index.min(modes.len() - 1);
+        if view.browse_show_items {
+            self.apply_browse_mode();
+            self.browse.item_index = view
+                .browse_item_index
+                .min(self.browse.items.len().saturating_sub(1));
+        } else {
+            self.browse.mode = modes[self.browse.mode_index];
+            self.browse.show_items = false;
+        }
+        self.browse.selected_item = view.filter_artist.clone().or(view.filter_album.clone());
+        self.browse_filter_artist = view.filter_artist;
+        self.browse_filter_album = view.filter_album;
+        self.rebuild_track_filter();
+        self.selected_track = view
+            .selected_track
+            .min(self.filtered_track_indices.len().saturating_sub(1));
+        self.focus = if self.pane_visible(view.focus) {
+            view.focus
+        } else {
+            FocusPane::Tracks
+        };
+    }
 
-    let (header_area, library_rows_area) = library_panel_inner_areas(tracks_area);
-    let areas = RenderAreas {
-        roots: PaneArea::borderless(left_sections[0], 1),
-        browse: left_sections[2],
-        browse_items: browse_items_area.map(|a| PaneArea::bordered(a, 1)),
-        playlists: PaneArea::borderless(left_sections[4], 1),
-        tracks: PaneArea::from_list_area(tracks_area, library_rows_area, 1),
-        track_header: header_area,
-        track_col_offsets: TrackColumnOffsets::default(),
-    };
-    state.sync_scroll_offsets(&areas);
+    fn apply_browse_mode(&mut self) {
+        let mode = crate::browse::BrowseMode::all()[self.browse.mode_index];
+        self.browse.set_mode(mode);
+        match mode {
+            crate::browse::BrowseMode::Songs => {
+                self.browse.show_items = false;
+                self.browse.items.clear();
+                self.browse_filter_artist = None;
+                self.browse_filter_album = None;
+            }
+            crate::browse::BrowseMode::Artists => {
+                self.browse.show_items = true;
+                self.browse.items = self.snapshot.artists.clone();
+            }
+            crate::browse::BrowseMode::Albums => {
+                self.browse.show_items = true;
+                self.browse.items = self
+                    .snapshot
+                    .albums
+                    .iter()
+                    .map(|(a, _)| a.clone())
+                    .collect();
+            }
+            crate::browse::BrowseMode::Recent => {
+                self.browse.show_items = true;
+                self.browse.items = self.recent_labels();
+                self.browse_filter_artist = None;
+                self.browse_filter_album = None;
+                if self.browse.items.is_empty() {
+                    self.status_message = Some("Nothing played yet".to_string());
+                }
+            }
+        }
+        self.rebuild_track_filter();
+    }
 
-    render_roots(frame, left_sections[0], state, palette);
-    render_browse_modes(frame, left_sections[2], state, palette);
-    render_playlists(frame, left_sections[4], state, palette);
-    render_now_playing(frame, right_sections[0], state, palette);
+    /// `Title · Artist` for each recently played track.
+    fn recent_labels(&self) -> Vec<String> {
+        self.snapshot
+            .recent_tracks
+            .iter()
+            .map(|track| format!("{} · {}", track.title, track.artist))
+            .collect()
+    }
 
-    if has_browse_split {
-        let lib_cols = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(30),
-                Constraint::Length(1),
-                Constraint::Percentage(70),
-            ])
-            .split(library_area);
-        render_browse_items(frame, lib_cols[0], state, palette);
-        render_vertical_separator(frame, lib_cols[1], palette);
+    /// The track under the cursor while the Recent list is open.
+    fn selected_recent_track(&self) -> Option<&ShellTrackItem> {
+        if self.browse.mode != crate::browse::BrowseMode::Recent || !self.browse.show_items {
+            return None;
+        }
+        self.snapshot.recent_tracks.get(self.browse.item_index)
     }
 
-    let col_offsets = render_tracks(frame, tracks_area, state, palette);
-    let areas = RenderAreas { track_col_offsets: col_offsets, ..areas };
-    render_status(frame, footer, state, palette);
+    fn filter_status_line(&self, editing: bool) -> String {
+        let mode = if editing { "editing" } else { "applied" };
+        format!(
+            "Track filter ({mode}): \"{}\" [{}/{}]  Enter/Esc close  Ctrl-U clear",
+            self.track_filter_query,
+            self.filtered_track_indices.len(),
+            self.snapshot.tracks.len()
+        )
+    }
 
-    if state.show_help {
-        render_help_overlay(frame, palette);
+    fn command_palette_status_line(&self) -> String {
+        format!(
+            "Command palette: :{}  (Enter run, Esc cancel, Ctrl-U clear)",
+            self.command_palette_input
+        )
     }
-    if state.input_mode == InputMode::CommandPalette {
-        render_command_palette_overlay(frame, state, palette);
+
+    fn selected_track_item(&self) -> Option<&ShellTrackItem> {
+        let track_index = *self.filtered_track_indices.get(self.selected_track)?;
+        self.snapshot.tracks.get(track_index)
     }
-    if state.input_mode == InputMode::AddMusic {
-        render_add_music_overlay(frame, state, palette, false);
+
+    fn filtered_track_count(&self) -> usize {
+        self.filtered_track_indices.len()
     }
-    if state.input_mode == InputMode::Welcome {
-        render_add_music_overlay(frame, state, palette, true);
+
+    fn filtered_track_iter(&self) -> impl Iterator<Item = &ShellTrackItem> {
+        self.filtered_track_indices
+            .iter()
+            .filter_map(|idx| self.snapshot.tracks.get(*idx))
     }
-    if state.input_mode == InputMode::TrackInfo {
-        render_track_info_overlay(frame, state, palette);
+
+    fn sync_scroll_offsets(&mut self, areas: &RenderAreas) {
+        self.roots_scroll = normalize_scroll(
+            self.roots_scroll,
+            self.selected_root,
+            self.snapshot.roots.len(),
+            areas.roots.visible_items,
+        );
+        self.playlists_scroll = normalize_scroll(
+            self.playlists_scroll,
+            self.selected_playlist,
+            self.playlist_rows(),
+            areas.playlists.visible_items,
+        );
+        self.tracks_scroll = normalize_scroll(
+            self.tracks_scroll,
+            self.selected_track,
+            self.filtered_track_indices.len(),
+            areas.tracks.visible_items,
+        );
+        self.split_scroll = normalize_scroll(
+            self.split_scroll,
+            self.selected_split,
+            self.snapshot.split_tracks.len(),
+            areas.split.visible_items,
+        );
+        if self.browse.show_items {
+            let items_visible = if let Some(ref bi) = areas.browse_items {
+                bi.visible_items
+            } else {
+                let browse_inner = borderless_content_area(areas.browse);
+                let modes_height = crate::browse::BrowseMode::all().len() as u16 + 1;
+                browse_inner.height.saturating_sub(modes_height) as usize
+            };
+            self.browse.item_scroll = normalize_scroll(
+                self.browse.item_scroll,
+                self.browse.item_index,
+                self.browse.items.len(),
+                items_visible,
+            );
+        }
     }
-    if state.input_mode == InputMode::Settings {
-        render_settings_overlay(frame, state, palette);
+
+    /// Lays the panes out again for a terminal resized to `area`, so scroll
+    /// offsets keep the selection in view and clicks land on the rows they
+    /// will be drawn at, before the next frame is drawn. Targets only known
+    /// once drawn, such as the seek bar, are dropped until then.
+    fn relayout(&mut self, area: Rect) -> RenderAreas {
+        let (_, areas) = pane_areas(panes_area(area, self), self);
+        self.sync_scroll_offsets(&areas);
+        self.seek_bar_area = Rect::default();
+        self.volume_bar_area = Rect::default();
+        self.queue_line_area = Rect::default();
+        self.viz_area = Rect::default();
+        self.hint_areas.clear();
+        self.mouse_hover = None;
+        areas
     }
 
-    // Fade-in effect on the Now Playing panel when a new track starts.
-    const FADE_DURATION_MS: u128 = 350;
-    if let Some(started) = state.track_change_time {
-        let elapsed = started.elapsed();
-        if elapsed.as_millis() < FADE_DURATION_MS {
-            let mut effect = fx::fade_from_fg(
-                palette.text_muted,
-                EffectTimer::from_ms(FADE_DURATION_MS as u32, Interpolation::QuadOut),
-            );
-            effect.process(elapsed.into(), frame.buffer_mut(), right_sections[0]);
+    fn set_focus_from_point(&mut self, x: u16, y: u16, areas: &RenderAreas) {
+        let point = (x, y).into();
+        let pane = if areas.roots.outer.contains(point) {
+            FocusPane::Sources
+        } else if areas.browse.contains(point)
+            || areas
+                .browse_items
+                .as_ref()
+                .is_some_and(|bi| bi.outer.contains(point))
+        {
+            FocusPane::Browse
+        } else if areas.playlists.outer.contains(point) {
+            FocusPane::Inspector
+        } else if areas.tracks.outer.contains(point) {
+            FocusPane::Tracks
+        } else if areas.split.outer.contains(point) {
+            FocusPane::Split
         } else {
-            state.track_change_time = None;
+            return;
+        };
+        if pane != self.focus {
+            self.remember_view();
+            self.focus = pane;
         }
     }
 
-    areas
+    fn select_from_mouse_click(&mut self, x: u16, y: u16, areas: &RenderAreas) {
+        if let Some(index) =
+            areas
+                .roots
+                .mouse_item_index(x, y, self.roots_scroll, self.snapshot.roots.len())
+        {
+            self.selected_root = index;
+            return;
+        }
+        if let Some(index) =
+            areas
+                .playlists
+                .mouse_item_index(x, y, self.playlists_scroll, self.playlist_rows())
+        {
+            self.selected_playlist = index;
+            return;
+        }
+        if let Some(ref bi) = areas.browse_items {
+            if let Some(index) =
+                bi.mouse_item_index(x, y, self.browse.item_scroll, self.browse.items.len())
+            {
+                self.browse.item_index = index;
+                return;
+            }
+        }
+        if let Some(index) = areas.tracks.mouse_item_index(
+            x,
+            y,
+            self.tracks_scroll,
+            self.filtered_track_indices.len(),
+        ) {
+            self.selected_track = index;
+            return;
+        }
+        if let Some(index) =
+            areas
+                .split
+                .mouse_item_index(x, y, self.split_scroll, self.snapshot.split_tracks.len())
+        {
+            self.selected_split = index;
+        }
+    }
+
+    /// Moves the dragged split row's landing place to the row under line
+    /// `y`, one row past the edge when the pointer leaves the list so the
+    /// list scrolls along.
+    fn drag_split_row(&mut self, y: u16, area: &PaneArea) {
+        let Some(drag) = self.split_drag else {
+            return;
+        };
+        let last = self.snapshot.split_tracks.len().saturating_sub(1);
+        let to = if y < area.inner.y {
+            drag.to.saturating_sub(1)
+        } else if y >= area.inner.bottom() {
+            drag.to + 1
+        } else {
+            // Rows below the insertion line are drawn one lower.
+            let row = self.split_scroll + usize::from(y - area.inner.y);
+            match drag.line_row() {
+                Some(line) if row == line => drag.to,
+                Some(line) if row > line => row - 1,
+                _ => row,
+            }
+        };
+        let to = min(to, last);
+        self.split_drag = Some(SplitDrag { to, ..drag });
+        self.selected_split = to;
+        // Keep both the landing row and the insertion line in view.
+        let visible = area.visible_items.max(2);
+        self.split_scroll = self
+            .split_scroll
+            .min(to)
+            .max((to + 2).saturating_sub(visible));
+    }
 }
 
-fn render_roots(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
-    let focused = state.focus == FocusPane::Sources;
-    let base_style = if focused {
-        Style::default().fg(palette.text)
-    } else {
-        Style::default().fg(palette.text).add_modifier(Modifier::DIM)
-    };
+/// A split row being dragged to a new place: `from` is its index, `to`
+/// where it lands on release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SplitDrag {
+    from: usize,
+    to: usize,
+}
 
-    render_section_header(frame, area, "Library Roots", focused, palette);
-    let content_area = borderless_content_area(area);
-    if content_area.width == 0 || content_area.height == 0 {
-        return;
+impl SplitDrag {
+    /// Where the insertion line goes among the drawn rows: above the
+    /// landing row when moving up, below it when moving down.
+    fn line_row(self) -> Option<usize> {
+        match self.to.cmp(&self.from) {
+            std::cmp::Ordering::Less => Some(self.to),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(self.to + 1),
+        }
     }
+}
 
-    let items: Vec<ListItem> = if state.snapshot.roots.is_empty() {
-        vec![
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(Span::styled(
-                "No library roots",
-                Style::default().fg(palette.text_muted),
-            ))),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(Span::styled(
-                "  Press a to add a music folder",
-                Style::default().fg(palette.text_muted),
-            ))),
-        ]
-    } else {
-        state
-            .snapshot
-            .roots
-            .iter()
-            .map(|r| {
-                let icon = icon_glyph(state.snapshot.icon_mode, IconToken::Folder);
-                let detail = r.detail.as_deref().unwrap_or("");
-                ListItem::new(Line::from(vec![
-                    Span::styled(format!("{icon} "), base_style.fg(palette.accent)),
-                    Span::styled(&r.label, base_style),
-                    Span::styled(
-                        if detail.is_empty() {
-                            String::new()
-                        } else {
-                            format!("  {detail}")
-                        },
-                        base_style.fg(palette.text_muted),
-                    ),
-                ]))
-            })
-            .collect()
-    };
-
-    let list = List::new(items)
-        .highlight_symbol("▌ ")
-        .highlight_style(
-            Style::default()
-                .bg(palette.selection_bg)
-                .fg(palette.text),
-        );
-    let mut list_state = ListState::default().with_selected(Some(min(
-        state.selected_root,
-        state.snapshot.roots.len().saturating_sub(1),
-    )));
-    list_state = list_state.with_offset(state.roots_scroll);
-    frame.render_stateful_widget(list, content_area, &mut list_state);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Title,
+    Time,
+    Artist,
+    Album,
+    Bpm,
+    Quality,
 }
 
-fn render_browse_modes(frame: &mut Frame, area: Rect, state: &ShellState, palette: &Palette) {
-    let focused = state.focus == FocusPane::Browse;
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            Self::Title => Self::Artist,
+            Self::Artist => Self::Album,
+            Self::Album => Self::Time,
+            Self::Time => Self::Bpm,
+            Self::Bpm => Self::Quality,
+            Self::Quality => Self::Title,
+        }
+    }
 
-    render_section_header(frame, area, "Browse Library", focused, palette);
-    let content_area = borderless_content_area(area);
-    if content_area.width == 0 || content_area.height == 0 {
-        return;
+    fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Artist => "Artist",
+            Self::Album => "Album",
+            Self::Time => "Time",
+            Self::Bpm => "BPM",
+            Self::Quality => "Quality",
+        }
     }
-    let dim = if focused { Modifier::empty() } else { Modifier::DIM };
 
-    let modes = crate::browse::BrowseMode::all();
-    let mode_icons = [IconToken::Track, IconToken::Folder, IconToken::Playlist];
-    let mut lines = Vec::new();
+    /// The column named by `label`, ignoring case; how the host reads the
+    /// sort order a command was sent with.
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            Self::Title,
+            Self::Artist,
+            Self::Album,
+            Self::Time,
+            Self::Bpm,
+            Self::Quality,
+        ]
+        .into_iter()
+        .find(|col| col.label().eq_ignore_ascii_case(label))
+    }
 
-    for (idx, mode) in modes.iter().enumerate() {
-        let is_current = idx == state.browse.mode_index;
-        let highlight = focused && !state.browse.show_items && is_current;
-        let icon = mode_icons.get(idx).copied().unwrap_or(IconToken::Folder);
-        let mut spans = vec![
-            Span::styled(
-                format!("{} ", icon_glyph(state.snapshot.icon_mode, icon)),
-                Style::default().fg(if is_current {
-                    palette.focus
-                } else {
-                    palette.text_muted
-                }).add_modifier(dim),
-            ),
-            Span::styled(
-                mode.label(),
-                Style::default()
-                    .fg(if is_current {
-                        palette.text
-                    } else {
-                        palette.text_muted
-                    })
-                    .add_modifier(if is_current {
-                        Modifier::BOLD | dim
-                    } else {
-                        dim
-                    }),
-            ),
-        ];
-        if highlight {
-            for span in &mut spans {
-                span.style = span.style.add_modifier(Modifier::REVERSED);
-            }
+    /// Orders two tracks by this column, ascending.
+    pub fn compare(self, a: &ShellTrackItem, b: &ShellTrackItem) -> std::cmp::Ordering {
+        match self {
+            Self::Title => a
+                .title
+                .to_ascii_lowercase()
+                .cmp(&b.title.to_ascii_lowercase()),
+            Self::Artist => a
+                .artist
+                .to_ascii_lowercase()
+                .cmp(&b.artist.to_ascii_lowercase()),
+            Self::Album => a
+                .album
+                .to_ascii_lowercase()
+                .cmp(&b.album.to_ascii_lowercase()),
+            Self::Time => a.duration_ms.cmp(&b.duration_ms),
+            Self::Bpm => a.bpm.cmp(&b.bpm),
+            Self::Quality => a.sample_rate.cmp(&b.sample_rate),
         }
-        lines.push(Line::from(spans));
     }
-
-    let paragraph = Paragraph::new(lines);
-    frame.render_widget(paragraph, content_area);
 }
 
-fn render_browse_items(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
-    let title = match state.browse.mode {
-        crate::browse::BrowseMode::Artists => "Artists",
-        crate::browse::BrowseMode::Albums => "Albums",
-        crate::browse::BrowseMode::Songs => return,
-    };
-
-    let focused = state.focus == FocusPane::Browse;
-    let block = pane_block(title, focused, palette);
-    let content_area = block.inner(area);
-    frame.render_widget(block, area);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChordAction {
+    MoveToStart,
+    Focus(FocusPane),
+    ToggleSidebar,
+    ToggleNowPlaying,
+    ToggleSpectrum,
+    ToggleSplit,
+    OpenTab,
+    CloseTab,
+    NextTab,
+}
 
-    if content_area.width == 0 || content_area.height == 0 {
-        return;
+impl ChordAction {
+    fn describe(self) -> (&'static str, crate::keymap::KeyCategory) {
+        use crate::keymap::KeyCategory;
+        match self {
+            Self::MoveToStart => ("jump to first", KeyCategory::Navigation),
+            Self::Focus(FocusPane::Sources) => ("focus sources", KeyCategory::Navigation),
+            Self::Focus(FocusPane::Browse) => ("focus browse", KeyCategory::Navigation),
+            Self::Focus(FocusPane::Tracks) => ("focus tracks", KeyCategory::Navigation),
+            Self::Focus(FocusPane::Split) => ("focus split", KeyCategory::Navigation),
+            Self::Focus(FocusPane::Inspector) => ("focus playlists", KeyCategory::Navigation),
+            Self::ToggleSidebar => ("show / hide sidebar", KeyCategory::Layout),
+            Self::ToggleNowPlaying => ("show / hide now playing", KeyCategory::Layout),
+            Self::ToggleSpectrum => ("show / hide spectrum", KeyCategory::Layout),
+            Self::ToggleSplit => ("show / hide split view", KeyCategory::Layout),
+            Self::OpenTab => ("open a tab with this view", KeyCategory::Navigation),
+            Self::CloseTab => ("close this tab", KeyCategory::Navigation),
+            Self::NextTab => ("next tab", KeyCategory::Navigation),
+        }
     }
+}
 
-    let base_style = if focused {
-        Style::default().fg(palette.text)
-    } else {
-        Style::default().fg(palette.text).add_modifier(Modifier::DIM)
-    };
+fn normal_mode_chords() -> crate::input::ChordMatcher<ChordAction> {
+    use crate::input::{ChordKey, ChordMatcher, DEFAULT_CHORD_TIMEOUT};
+    let g = ChordKey::char('g');
+    let z = ChordKey::char('z');
+    let t = ChordKey::char('t');
+    ChordMatcher::new(DEFAULT_CHORD_TIMEOUT)
+        .bind(&[g, g], ChordAction::MoveToStart)
+        .bind(
+            &[g, ChordKey::char('s')],
+            ChordAction::Focus(FocusPane::Sources),
+        )
+        .bind(
+            &[g, ChordKey::char('b')],
+            ChordAction::Focus(FocusPane::Browse),
+        )
+        .bind(
+            &[g, ChordKey::char('t')],
+            ChordAction::Focus(FocusPane::Tracks),
+        )
+        .bind(
+            &[g, ChordKey::char('p')],
+            ChordAction::Focus(FocusPane::Inspector),
+        )
+        .bind(
+            &[g, ChordKey::char('v')],
+            ChordAction::Focus(FocusPane::Split),
+        )
+        .bind(&[z, ChordKey::char('s')], ChordAction::ToggleSidebar)
+        .bind(&[z, ChordKey::char('n')], ChordAction::ToggleNowPlaying)
+        .bind(&[z, ChordKey::char('v')], ChordAction::ToggleSpectrum)
+        .bind(&[z, ChordKey::char('q')], ChordAction::ToggleSplit)
+        .bind(&[t, ChordKey::char('n')], ChordAction::OpenTab)
+        .bind(&[t, ChordKey::char('x')], ChordAction::CloseTab)
+        .bind(&[t, t], ChordAction::NextTab)
+}
 
-    let items: Vec<ListItem> = state
-        .browse
-        .items
-        .iter()
-        .map(|item| {
-            let is_active = state.browse.selected_item.as_deref() == Some(item.as_str());
-            let style = if is_active {
-                base_style.fg(palette.focus).add_modifier(Modifier::BOLD)
-            } else {
-                base_style
-            };
-            ListItem::new(Line::from(Span::styled(item.as_str(), style)))
-        })
-        .collect();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputMode {
+    Normal,
+    TrackFilter,
+    CommandPalette,
+    AddMusic,
+    Welcome,
+    TrackInfo,
+    Settings,
+    Help,
+    Log,
+    Podcasts,
+    Stats,
+    Health,
+    Review,
+    PartyUnlock,
+    /// Naming a playlist made from the sidebar's "+ New Playlist" row.
+    NewPlaylist,
+    /// A yes/no question in the status line; see [`ShellState::ask_to_confirm`].
+    Confirm,
+}
 
-    let list = List::new(items)
-        .highlight_symbol("▌ ")
-        .highlight_style(
-            Style::default()
-                .bg(palette.selection_bg)
-                .fg(palette.text)
-                .add_modifier(Modifier::BOLD),
-        );
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyAction {
+    Continue,
+    Quit,
+    RefreshRequested,
+    CommandSubmitted(String),
+    Playback(PlaybackAction),
+    CopyToClipboard(String),
+    PasteFromClipboard,
+}
 
-    let mut list_state = ListState::default().with_selected(Some(
-        state
-            .browse
-            .item_index
-            .min(state.browse.items.len().saturating_sub(1)),
-    ));
-    list_state = list_state.with_offset(state.browse.item_scroll);
-    frame.render_stateful_widget(list, content_area, &mut list_state);
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlaybackAction {
+    PlayTrack {
+        track_index: usize,
+    },
+    TogglePause,
+    /// Pause only if playing; used by remote control.
+    Pause,
+    /// Resume or start the current track unless already playing.
+    Resume,
+    Stop,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    SetVolume {
+        volume: f32,
+    },
+    ToggleShuffle,
+    /// Queue a random album whenever the current one finishes.
+    ToggleRandomAlbum,
+    /// Keep the queue going with similar artists when it runs out.
+    ToggleRadio,
+    /// Take tracks off the queue once they have played.
+    ToggleConsume,
+    Seek {
+        position_ms: u64,
+    },
+    /// Add a library track to the end of the queue, starting it if nothing
+    /// plays. `requested_by` names the guest for party mode's cooldown.
+    Enqueue {
+        path: String,
+        requested_by: String,
+    },
+    /// Move on after the current track finished. Unlike `Next` it is not a
+    /// guest's choice, so party mode allows it.
+    Advance,
 }
 
-fn render_playlists(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
-    let focused = state.focus == FocusPane::Inspector;
-    let base_style = if focused {
-        Style::default().fg(palette.text)
-    } else {
-        Style::default().fg(palette.text).add_modifier(Modifier::DIM)
-    };
+#[derive(Debug, Clone, Copy)]
+pub struct RunOptions {
+    pub frames: FrameScheduler,
+    pub mouse: bool,
+}
 
-    render_section_header(frame, area, "Playlists", focused, palette);
-    let content_area = borderless_content_area(area);
-    if content_area.width == 0 || content_area.height == 0 {
-        return;
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            frames: FrameScheduler::default(),
+            mouse: true,
+        }
     }
+}
 
-    let items: Vec<ListItem> = if state.snapshot.playlists.is_empty() {
-        vec![ListItem::new(Line::from(Span::styled(
-            "No playlists",
-            base_style.fg(palette.text_muted),
-        )))]
-    } else {
-        state
-            .snapshot
-            .playlists
-            .iter()
-            .map(|p| {
-                let icon = icon_glyph(state.snapshot.icon_mode, IconToken::Playlist);
-                ListItem::new(Line::from(vec![
-                    Span::styled(format!("{icon} "), base_style.fg(palette.accent_2)),
-                    Span::styled(&p.label, base_style),
-                ]))
-            })
-            .collect()
-    };
+type RefreshSnapshotFn<'a> = dyn FnMut() -> Result<ShellSnapshot, UiError> + 'a;
+type CommandPaletteFn<'a> = dyn FnMut(&str) -> Result<PaletteCommandResult, UiError> + 'a;
+/// Starts the next queued background job, if any, returning the status
+/// line to show while it runs and the channel it reports on.
+type BackgroundScanFn<'a> =
+    dyn FnMut() -> Option<(String, std::sync::mpsc::Receiver<ScanProgress>)> + 'a;
 
-    let list = List::new(items)
-        .highlight_symbol("▌ ")
-        .highlight_style(
-            Style::default()
-                .bg(palette.selection_bg)
-                .fg(palette.text),
-        );
-    let mut list_state = ListState::default().with_selected(Some(min(
-        state.selected_playlist,
-        state.snapshot.playlists.len().saturating_sub(1),
-    )));
-    list_state = list_state.with_offset(state.playlists_scroll);
-    frame.render_stateful_widget(list, content_area, &mut list_state);
+/// Progress messages sent from a background scan thread.
+#[derive(Debug, Clone)]
+pub enum ScanProgress {
+    /// Periodic progress update: discovered N files so far.
+    Progress { discovered: usize, path: String },
+    /// Progress of a background job that is not a scan, shown as is.
+    Status { message: String },
+    /// Scan completed successfully.
+    Done { message: String },
+    /// Scan failed.
+    Error { message: String },
 }
 
-fn render_tracks(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) -> TrackColumnOffsets {
-    let title = {
-        let filtered = state.filtered_track_count();
-        let total = state.snapshot.total_track_count;
-        let base = if let Some(ref artist) = state.browse_filter_artist {
-            format!("{artist} ({filtered})")
-        } else if let Some(ref album) = state.browse_filter_album {
-            format!("{album} ({filtered})")
-        } else if !state.track_filter_query.is_empty() {
-            format!(
-                "Library ({}/{}) /{}",
-                filtered, total, state.track_filter_query
-            )
-        } else if filtered < total {
-            format!("Library ({}/{})", filtered, total)
-        } else {
-            format!("Library ({})", filtered)
-        };
-        base
-    };
-    let outer_block = pane_block(&title, state.focus == FocusPane::Tracks, palette);
-    let inner = outer_block.inner(area);
-    frame.render_widget(outer_block, area);
+#[derive(Debug, Clone)]
+pub struct PlayerEventUpdate {
+    pub position_ms: u64,
+    pub duration_ms: u64,
+    pub status: String,
+    pub track_finished: bool,
+    /// Shown in the status line, such as the output device going away.
+    pub message: Option<String>,
+    pub spectrum_bands: Vec<f32>,
+    pub raw_samples: Vec<f32>,
+}
 
-    if inner.width == 0 || inner.height == 0 {
-        return TrackColumnOffsets::default();
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteCommandResult {
+    pub status_message: String,
+    pub refresh_requested: bool,
+    /// Artwork data returned by __fetch_artwork command.
+    pub artwork_data: Option<Vec<u8>>,
+    /// Covers returned by `__album_art`, by album name.
+    pub album_art: Vec<(String, Option<Vec<u8>>)>,
+    /// The shell should exit, e.g. after the party-mode passphrase.
+    pub quit_requested: bool,
+    /// A summary to show in the year-in-review dialog.
+    pub review: Option<ShellReview>,
+}
+
+impl PaletteCommandResult {
+    pub fn new(status_message: impl Into<String>, refresh_requested: bool) -> Self {
+        Self {
+            status_message: status_message.into(),
+            refresh_requested,
+            artwork_data: None,
+            album_art: Vec::new(),
+            quit_requested: false,
+            review: None,
+        }
     }
 
-    let (header_area, rows_area) = library_panel_inner_areas(area);
+    /// Shows `status_message` and ends the shell.
+    pub fn quit(status_message: impl Into<String>) -> Self {
+        Self {
+            quit_requested: true,
+            ..Self::new(status_message, false)
+        }
+    }
 
-    // Calculate column widths proportionally
-    let total_w = inner.width as usize;
-    let col_time = 7usize;
-    let col_quality = 14;
-    let fixed = col_time + col_quality;
-    let flexible = total_w.saturating_sub(fixed);
-    let col_title = flexible * 30 / 100;
-    let col_artist = flexible * 25 / 100;
-    let col_album = flexible.saturating_sub(col_title + col_artist);
-
-    let header_x = inner.x;
-    let offsets = TrackColumnOffsets {
-        title_start: header_x,
-        time_start: header_x + col_title as u16,
-        artist_start: header_x + (col_title + col_time) as u16,
-        album_start: header_x + (col_title + col_time + col_artist) as u16,
-        quality_start: header_x + (col_title + col_time + col_artist + col_album) as u16,
-    };
-
-    let sort_indicator = |col: SortColumn| -> &str {
-        if state.sort_column == col {
-            if state.sort_ascending { " ▲" } else { " ▼" }
-        } else {
-            ""
-        }
-    };
-    let sort_style = |col: SortColumn| -> Style {
-        if state.sort_column == col {
-            Style::default().fg(palette.accent)
-        } else {
-            Style::default().fg(palette.text_muted)
+    pub fn with_artwork(status_message: impl Into<String>, data: Option<Vec<u8>>) -> Self {
+        Self {
+            status_message: status_message.into(),
+            refresh_requested: false,
+            artwork_data: data,
+            album_art: Vec::new(),
+            quit_requested: false,
+            review: None,
         }
-    };
-
-    let header = Line::from(vec![
-        Span::styled(
-            pad_cell(&format!("Title{}", sort_indicator(SortColumn::Title)), col_title),
-            sort_style(SortColumn::Title),
-        ),
-        Span::styled(
-            pad_cell(&format!("Time{}", sort_indicator(SortColumn::Time)), col_time),
-            sort_style(SortColumn::Time),
-        ),
-        Span::styled(
-            pad_cell(&format!("Artist{}", sort_indicator(SortColumn::Artist)), col_artist),
-            sort_style(SortColumn::Artist),
-        ),
-        Span::styled(
-            pad_cell(&format!("Album{}", sort_indicator(SortColumn::Album)), col_album),
-            sort_style(SortColumn::Album),
-        ),
-        Span::styled(
-            format!("Quality{}", sort_indicator(SortColumn::Quality)),
-            sort_style(SortColumn::Quality),
-        ),
-    ]);
-    frame.render_widget(Paragraph::new(header), header_area);
+    }
 
-    let items: Vec<ListItem> = if state.filtered_track_count() == 0 {
-        if state.snapshot.tracks.is_empty() && state.snapshot.roots.is_empty() {
-            vec![
-                ListItem::new(Line::from("")),
-                ListItem::new(Line::from(Span::styled(
-                    "No tracks in library",
-                    Style::default().fg(palette.text_muted),
-                ))),
-                ListItem::new(Line::from("")),
-                ListItem::new(Line::from(Span::styled(
-                    "  Add a music folder to get started",
-                    Style::default().fg(palette.text_muted),
-                ))),
-                ListItem::new(Line::from(Span::styled(
-                    "  Press a or : then root add /path",
-                    Style::default().fg(palette.text_muted),
-                ))),
-            ]
-        } else if state.snapshot.tracks.is_empty() {
-            vec![
-                ListItem::new(Line::from("")),
-                ListItem::new(Line::from(Span::styled(
-                    "No tracks in library",
-                    Style::default().fg(palette.text_muted),
-                ))),
-                ListItem::new(Line::from("")),
-                ListItem::new(Line::from(Span::styled(
-                    "  Press : then scan roots to import",
-                    Style::default().fg(palette.text_muted),
-                ))),
-            ]
-        } else {
-            vec![ListItem::new(Line::from(Span::styled(
-                "No tracks match current filter",
-                Style::default().fg(palette.text_muted),
-            )))]
+    /// Opens the year-in-review dialog on `review`.
+    pub fn with_review(review: ShellReview) -> Self {
+        Self {
+            review: Some(review),
+            ..Self::new("", false)
         }
-    } else {
-        let use_alt_bg = !palette.use_terminal_bg;
-        state
-            .filtered_track_iter()
-            .enumerate()
-            .map(|(idx, t)| {
-                let row = format!(
-                    "{}{}{}{}{}",
-                    pad_cell(&truncate_text(&t.title, col_title.saturating_sub(1)), col_title),
-                    pad_cell(&format_duration_short(t.duration_ms), col_time),
-                    pad_cell(&truncate_text(&t.artist, col_artist.saturating_sub(1)), col_artist),
-                    pad_cell(&truncate_text(&t.album, col_album.saturating_sub(1)), col_album),
-                    format_tech_compact(t.sample_rate, t.bit_depth, t.channels)
-                );
-                let row_style = if use_alt_bg && idx % 2 == 1 {
-                    Style::default().fg(palette.text).bg(palette.surface_2)
-                } else {
-                    Style::default().fg(palette.text)
-                };
-                ListItem::new(Line::from(Span::styled(row, row_style)))
-            })
-            .collect()
-    };
+    }
 
-    let focused = state.focus == FocusPane::Tracks;
-    let list = List::new(items)
-        .highlight_style(
-            Style::default()
-                .bg(palette.selection_bg)
-                .fg(palette.text),
-        )
-        .highlight_symbol("▌ ")
-        .repeat_highlight_symbol(true)
-        .style(if focused {
-            Style::default()
-        } else {
-            Style::default().add_modifier(Modifier::DIM)
-        });
-    let selected = if state.filtered_track_count() == 0 {
-        None
-    } else {
-        Some(min(
-            state.selected_track,
-            state.filtered_track_count().saturating_sub(1),
-        ))
-    };
-    let mut list_state = ListState::default().with_selected(selected);
-    list_state = list_state.with_offset(state.tracks_scroll);
-    frame.render_stateful_widget(list, rows_area, &mut list_state);
-    offsets
+    /// Answers `__album_art` without touching the status line.
+    pub fn with_album_art(album_art: Vec<(String, Option<Vec<u8>>)>) -> Self {
+        Self {
+            album_art,
+            ..Self::new("", false)
+        }
+    }
 }
 
-fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
-    let block = pane_block("Now Playing", false, palette);
-    let content_area = padded_inner(area);
-    frame.render_widget(block, area);
+#[derive(Debug, Clone, Copy, Default)]
+struct PaneArea {
+    outer: Rect,
+    inner: Rect,
+    visible_items: usize,
+    item_height: u16,
+}
 
-    let is_playing = state.playback_status == "playing";
-    let is_paused = state.playback_status == "paused";
-    let has_track = !state.snapshot.now_playing_title.is_empty();
+impl PaneArea {
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn bordered(area: Rect, item_height: u16) -> Self {
+        Self::from_list_area(area, inner_rect(area), item_height)
+    }
 
-    if has_track {
-        // Update artwork state when track changes
-        state.artwork.update(
-            &state.snapshot.now_playing_path,
-            state.snapshot.now_playing_artwork.as_deref(),
-            state.snapshot.pixel_art_enabled,
-            state.snapshot.pixel_art_cell_size,
-        );
+    /// For borderless sections: header takes 1 row, content has 1-cell left pad.
+    fn borderless(area: Rect, item_height: u16) -> Self {
+        let content = borderless_content_area(area);
+        Self::from_list_area(area, content, item_height)
+    }
 
-        // Split content area: artwork on left (square), text on right
-        let show_art = state.artwork.has_image() && content_area.height >= 3;
-        let art_width = if show_art {
-            content_area.height.saturating_mul(2).min(content_area.width / 3)
-        } else {
+    fn from_list_area(outer: Rect, list_area: Rect, item_height: u16) -> Self {
+        let inner = list_area;
+        let inner_height = inner.height as usize;
+        let item_height_usize = usize::from(item_height.max(1));
+        let visible_items = if inner_height == 0 {
             0
-        };
-        let text_area = Rect {
-            x: content_area.x + art_width,
-            y: content_area.y,
-            width: content_area.width.saturating_sub(art_width),
-            height: content_area.height,
-        };
-
-        let status_icon = if is_playing {
-            ">"
-        } else if is_paused {
-            "||"
         } else {
-            "[]"
+            inner_height.div_ceil(item_height_usize)
         };
+        Self {
+            outer,
+            inner,
+            visible_items,
+            item_height: item_height.max(1),
+        }
+    }
 
-        // Row 0: status icon + title + artist/album
-        let title_line = Line::from(vec![
-            Span::styled(
-                format!("{status_icon} "),
-                Style::default().fg(if is_playing {
-                    palette.progress_fill
-                } else {
-                    palette.text_muted
-                }),
-            ),
-            Span::styled(
-                state.snapshot.now_playing_title.as_str(),
-                Style::default()
-                    .fg(palette.text)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(
-                format!(
-                    "  {}  {}",
-                    state.snapshot.now_playing_artist, state.snapshot.now_playing_album
-                ),
-                Style::default().fg(palette.text_muted),
-            ),
-        ]);
-        let title_area = Rect {
-            x: text_area.x,
-            y: text_area.y,
-            width: text_area.width,
-            height: 1,
-        };
-        frame.render_widget(Paragraph::new(title_line), title_area);
+    fn mouse_item_index(&self, x: u16, y: u16, scroll: usize, len: usize) -> Option<usize> {
+        if len == 0 || !self.inner.contains((x, y).into()) {
+            return None;
+        }
+        let local_row = usize::from(y.saturating_sub(self.inner.y));
+        let item_height = usize::from(self.item_height.max(1));
+        let index = scroll.saturating_add(local_row / item_height);
+        (index < len).then_some(index)
+    }
+}
 
-        // Row 1: seek bar
-        let position = state.playback_position_ms;
-        let duration = state.playback_duration_ms;
-        let progress = if duration > 0 {
-            position as f32 / duration as f32
-        } else {
-            0.0
-        };
-        let elapsed_str = format_ms(position);
-        let remaining_ms = duration.saturating_sub(position);
-        let remaining_str = format_ms(remaining_ms);
+#[derive(Debug, Clone, Copy, Default)]
+struct RenderAreas {
+    roots: PaneArea,
+    browse: Rect,
+    browse_items: Option<PaneArea>,
+    playlists: PaneArea,
+    tracks: PaneArea,
+    split: PaneArea,
+    track_header: Rect,
+    track_col_offsets: TrackColumnOffsets,
+    file_browser: Option<FileBrowserAreas>,
+}
 
-        let seek_bar_rect = Rect {
-            x: text_area.x,
-            y: text_area.y + 1,
+/// Hit-test regions of the add-music dialog from the last frame.
+#[derive(Debug, Clone, Copy, Default)]
+struct FileBrowserAreas {
+    dialog: Rect,
+    path_input: Rect,
+    /// Rows of the visible directory entries, one entry per row.
+    entries: Rect,
+    /// Index of the entry drawn on the first row of `entries`.
+    first_entry: usize,
+    load_button: Rect,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TrackColumnOffsets {
+    title_start: u16,
+    time_start: u16,
+    artist_start: u16,
+    album_start: u16,
+    bpm_start: u16,
+    quality_start: u16,
+}
+
+pub fn run_interactive(
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+) -> Result<(), UiError> {
+    run_interactive_with_optional_handlers(state, palette, options, None, None, None)
+}
+
+pub fn run_interactive_with_refresh<F>(
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+    mut refresh: F,
+) -> Result<(), UiError>
+where
+    F: FnMut() -> Result<ShellSnapshot, UiError>,
+{
+    run_interactive_with_optional_handlers(state, palette, options, Some(&mut refresh), None, None)
+}
+
+pub fn run_interactive_with_handlers<FRefresh, FCommand>(
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+    mut refresh: FRefresh,
+    mut command_handler: FCommand,
+) -> Result<(), UiError>
+where
+    FRefresh: FnMut() -> Result<ShellSnapshot, UiError>,
+    FCommand: FnMut(&str) -> Result<PaletteCommandResult, UiError>,
+{
+    run_interactive_with_optional_handlers(
+        state,
+        palette,
+        options,
+        Some(&mut refresh),
+        Some(&mut command_handler),
+        None,
+    )
+}
+
+pub fn run_interactive_with_scan<FRefresh, FCommand, FScan>(
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+    mut refresh: FRefresh,
+    mut command_handler: FCommand,
+    mut scan_handler: FScan,
+) -> Result<(), UiError>
+where
+    FRefresh: FnMut() -> Result<ShellSnapshot, UiError>,
+    FCommand: FnMut(&str) -> Result<PaletteCommandResult, UiError>,
+    FScan: FnMut() -> Option<(String, std::sync::mpsc::Receiver<ScanProgress>)>,
+{
+    run_interactive_with_optional_handlers(
+        state,
+        palette,
+        options,
+        Some(&mut refresh),
+        Some(&mut command_handler),
+        Some(&mut scan_handler),
+    )
+}
+
+type PlaybackActionFn<'a> = dyn FnMut(PlaybackAction) -> Result<PaletteCommandResult, UiError> + 'a;
+/// Polls the player; the flag says whether to analyse samples for the
+/// spectrum.
+type PlayerPollFn<'a> = dyn FnMut(bool) -> Vec<PlayerEventUpdate> + 'a;
+
+fn run_interactive_with_optional_handlers(
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+    refresh: Option<&mut RefreshSnapshotFn<'_>>,
+    command_handler: Option<&mut CommandPaletteFn<'_>>,
+    scan_handler: Option<&mut BackgroundScanFn<'_>>,
+) -> Result<(), UiError> {
+    run_interactive_full_inner(
+        state,
+        palette,
+        options,
+        refresh,
+        command_handler,
+        scan_handler,
+        None,
+        None,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_interactive_full<FRefresh, FCommand, FScan, FPlayback, FPlayerPoll>(
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+    mut refresh: FRefresh,
+    mut command_handler: FCommand,
+    mut scan_handler: FScan,
+    mut playback_handler: FPlayback,
+    mut player_poll: FPlayerPoll,
+) -> Result<(), UiError>
+where
+    FRefresh: FnMut() -> Result<ShellSnapshot, UiError>,
+    FCommand: FnMut(&str) -> Result<PaletteCommandResult, UiError>,
+    FScan: FnMut() -> Option<(String, std::sync::mpsc::Receiver<ScanProgress>)>,
+    FPlayback: FnMut(PlaybackAction) -> Result<PaletteCommandResult, UiError>,
+    FPlayerPoll: FnMut(bool) -> Vec<PlayerEventUpdate>,
+{
+    run_interactive_full_inner(
+        state,
+        palette,
+        options,
+        Some(&mut refresh),
+        Some(&mut command_handler),
+        Some(&mut scan_handler),
+        Some(&mut playback_handler),
+        Some(&mut player_poll),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_interactive_full_inner(
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+    refresh: Option<&mut RefreshSnapshotFn<'_>>,
+    command_handler: Option<&mut CommandPaletteFn<'_>>,
+    scan_handler: Option<&mut BackgroundScanFn<'_>>,
+    playback_handler: Option<&mut PlaybackActionFn<'_>>,
+    player_poll: Option<&mut PlayerPollFn<'_>>,
+) -> Result<(), UiError> {
+    crate::terminal_guard::install_panic_hook();
+    crate::terminal_guard::install_signal_handlers()
+        .map_err(|e| UiError::Terminal(format!("signal handler setup failed: {e}")))?;
+    enable_raw_mode().map_err(|e| UiError::Terminal(format!("enable_raw_mode failed: {e}")))?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)
+        .map_err(|e| UiError::Terminal(format!("enter alt screen failed: {e}")))?;
+    if options.mouse {
+        execute!(stdout, EnableMouseCapture)
+            .map_err(|e| UiError::Terminal(format!("enable mouse capture failed: {e}")))?;
+    }
+    execute!(stdout, EnableBracketedPaste, EnableFocusChange)
+        .map_err(|e| UiError::Terminal(format!("enable bracketed paste failed: {e}")))?;
+    crate::terminal_guard::enable_keyboard_enhancement();
+
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)
+        .map_err(|e| UiError::Terminal(format!("terminal init failed: {e}")))?;
+
+    let result = run_loop(
+        &mut terminal,
+        state,
+        palette,
+        options,
+        refresh,
+        command_handler,
+        scan_handler,
+        playback_handler,
+        player_poll,
+    );
+
+    drop(terminal);
+    crate::terminal_guard::restore_terminal(options.mouse);
+
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    state: &mut ShellState,
+    palette: &Palette,
+    options: RunOptions,
+    mut refresh: Option<&mut RefreshSnapshotFn<'_>>,
+    mut command_handler: Option<&mut CommandPaletteFn<'_>>,
+    mut scan_handler: Option<&mut BackgroundScanFn<'_>>,
+    mut playback_handler: Option<&mut PlaybackActionFn<'_>>,
+    mut player_poll: Option<&mut PlayerPollFn<'_>>,
+) -> Result<(), UiError> {
+    use std::sync::mpsc;
+
+    let mut last_draw = Instant::now();
+    // Set when input or a player event changed what is on screen, so the
+    // next tick draws without waiting for the frame to be due.
+    let mut redraw = true;
+    let mut last_areas = RenderAreas::default();
+    let mut scan_rx: Option<mpsc::Receiver<ScanProgress>> = None;
+    let mut last_scan_refresh = Instant::now();
+    let mut clipboard = crate::clipboard::Clipboard::new();
+
+    // Helper closure: handle a PaletteCommandResult.
+    let handle_command_result =
+        |state: &mut ShellState,
+         result: PaletteCommandResult,
+         refresh: &mut Option<&mut RefreshSnapshotFn<'_>>| {
+            state.status_message = Some(result.status_message);
+            if result.refresh_requested {
+                try_refresh_snapshot(state, refresh);
+            }
+            if result.artwork_data.is_some() {
+                state.track_info_artwork = result.artwork_data;
+            }
+            if let Some(review) = result.review {
+                state.open_review(review);
+            }
+        };
+
+    loop {
+        if crate::terminal_guard::shutdown_requested() {
+            return Ok(());
+        }
+        let tick_start = Instant::now();
+        state.debug_stats.scan_messages = 0;
+        state.debug_stats.player_events = 0;
+
+        // Poll background scan progress (non-blocking)
+        if let Some(rx) = &scan_rx {
+            loop {
+                let message = rx.try_recv();
+                if message.is_ok() {
+                    state.debug_stats.scan_messages += 1;
+                }
+                match message {
+                    Ok(ScanProgress::Progress { discovered, path }) => {
+                        state.status_message =
+                            Some(format!("Scanning {path}... ({discovered} tracks imported)"));
+                        // Refresh snapshot frequently to show track count updates
+                        if last_scan_refresh.elapsed() >= Duration::from_millis(750) {
+                            try_refresh_snapshot(state, &mut refresh);
+                            last_scan_refresh = Instant::now();
+                        }
+                    }
+                    Ok(ScanProgress::Status { message }) => {
+                        state.status_message = Some(message);
+                    }
+                    Ok(ScanProgress::Done { message }) => {
+                        state.scanning = false;
+                        state.status_message = Some(message);
+                        state.album_thumbnails.clear();
+                        try_refresh_snapshot(state, &mut refresh);
+                        break;
+                    }
+                    Ok(ScanProgress::Error { message }) => {
+                        state.scanning = false;
+                        state.report_error(format!("Scan failed: {message}"));
+                        break;
+                    }
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        state.scanning = false;
+                        state.status_message = Some("Scan finished".to_string());
+                        try_refresh_snapshot(state, &mut refresh);
+                        break;
+                    }
+                }
+            }
+            if !state.scanning {
+                scan_rx = None;
+            }
+        }
+        // One background job at a time; the next queued one starts when the
+        // running one finishes.
+        if scan_rx.is_none() {
+            if let Some((message, rx)) = scan_handler.as_mut().and_then(|handler| handler()) {
+                state.scanning = true;
+                state.status_message = Some(message);
+                scan_rx = Some(rx);
+            }
+        }
+
+        // Poll player events
+        if let Some(poll_fn) = player_poll.as_mut() {
+            let poll_start = Instant::now();
+            let updates = (*poll_fn)(state.wants_spectrum());
+            state.debug_stats.player_poll.record(poll_start.elapsed());
+            state.debug_stats.player_events = updates.len();
+            redraw |= apply_player_updates(state, updates, &mut playback_handler, &mut refresh);
+        }
+
+        // Everything above runs every tick; drawing only when the frame
+        // is due at the current pace or something just changed.
+        let pace = state.pace();
+        state.debug_stats.frame_interval = options.frames.interval(pace);
+        if redraw || options.frames.frame_due(pace, last_draw.elapsed()) {
+            let render_start = Instant::now();
+            terminal
+                .draw(|f| {
+                    last_areas = draw_shell(f, state, palette);
+                })
+                .map_err(|e| UiError::Terminal(format!("draw failed: {e}")))?;
+            state.debug_stats.render.record(render_start.elapsed());
+            state.debug_stats.tick.record(tick_start.elapsed());
+            state.debug_stats.frames += 1;
+            last_draw = Instant::now();
+            redraw = false;
+
+            // Covers for album rows drawn without one; they show next frame.
+            let wanted = state
+                .album_thumbnails
+                .take_wanted(crate::artwork::THUMBNAIL_FETCH_BATCH);
+            if let Some(handler) = command_handler.as_mut().filter(|_| !wanted.is_empty()) {
+                let albums = serde_json::to_string(&wanted).unwrap_or_default();
+                match (*handler)(&format!("__album_art {albums}")) {
+                    Ok(result) => {
+                        for (album, data) in result.album_art {
+                            state.album_thumbnails.insert(album, data.as_deref());
+                        }
+                    }
+                    Err(err) => tracing::warn!(error = %err, "failed to fetch album covers"),
+                }
+                // Unanswered albums count as coverless rather than being asked for every frame.
+                for album in wanted {
+                    if !state.album_thumbnails.contains(&album) {
+                        state.album_thumbnails.insert(album, None);
+                    }
+                }
+            }
+        }
+
+        let timeout = options
+            .frames
+            .poll_timeout(pace, last_draw.elapsed(), tick_start.elapsed());
+        let input_ready =
+            event::poll(timeout).map_err(|e| UiError::Terminal(format!("poll failed: {e}")))?;
+        state.debug_stats.input_events = usize::from(input_ready);
+        let mut actions = Vec::new();
+        if input_ready {
+            let action = match event::read()
+                .map_err(|e| UiError::Terminal(format!("read event failed: {e}")))?
+            {
+                Event::Key(key) => state.handle_key(key),
+                Event::Mouse(mouse) if options.mouse => state.handle_mouse(mouse, &last_areas),
+                Event::FocusGained => {
+                    state.set_terminal_focus(true);
+                    KeyAction::Continue
+                }
+                Event::FocusLost => {
+                    state.set_terminal_focus(false);
+                    KeyAction::Continue
+                }
+                Event::Resize(width, height) => {
+                    last_areas = state.relayout(Rect::new(0, 0, width, height));
+                    KeyAction::Continue
+                }
+                Event::Mouse(_) => KeyAction::Continue,
+                Event::Paste(content) if state.is_editing_text() => {
+                    state.paste_text(&content);
+                    KeyAction::Continue
+                }
+                Event::Paste(content) => {
+                    let paths: Vec<String> = content
+                        .lines()
+                        .map(|l| l.trim().to_string())
+                        .filter(|l| !l.is_empty())
+                        .collect();
+
+                    for path_str in paths {
+                        let path = std::path::Path::new(&path_str);
+                        if path.is_dir() {
+                            match state.input_mode {
+                                InputMode::AddMusic | InputMode::Welcome => {
+                                    if let Some(browser) = state.file_browser.as_mut() {
+                                        browser.navigate_to(path);
+                                    }
+                                }
+                                InputMode::Normal => {
+                                    if let Some(handler) = command_handler.as_mut() {
+                                        match (*handler)(&format!("__add_root {path_str}")) {
+                                            Ok(result) => {
+                                                handle_command_result(state, result, &mut refresh);
+                                            }
+                                            Err(err) => {
+                                                state.report_error(format!("Drop failed: {err}"));
+                                            }
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                        } else {
+                            state.status_message = Some(format!("Not a directory: {path_str}"));
+                        }
+                    }
+                    KeyAction::Continue
+                }
+            };
+            actions.push((action, None));
+        }
+        actions.extend(
+            state
+                .remote_inbox
+                .drain()
+                .into_iter()
+                .map(|request| (request.action, Some(request.reply))),
+        );
+        redraw |= !actions.is_empty();
+        for (action, reply) in actions {
+            // A remote `Continue` is a status query.
+            let query = action == KeyAction::Continue;
+            let mut failed = false;
+            match action {
+                KeyAction::Quit => return Ok(()),
+                KeyAction::Continue => {}
+                KeyAction::RefreshRequested => {
+                    try_refresh_snapshot(state, &mut refresh);
+                }
+                KeyAction::CommandSubmitted(command) => {
+                    if let Some(handler) = command_handler.as_mut() {
+                        match (*handler)(&command) {
+                            Ok(result) if result.quit_requested => return Ok(()),
+                            Ok(result) => {
+                                handle_command_result(state, result, &mut refresh);
+                            }
+                            Err(err) => {
+                                state.report_error(format!("Command failed: {err}"));
+                                failed = true;
+                            }
+                        }
+                    } else {
+                        state.status_message = Some(format!(
+                            "Command palette unavailable in this shell mode: {command}"
+                        ));
+                        failed = true;
+                    }
+                }
+                KeyAction::Playback(action) => {
+                    if let Some(handler) = playback_handler.as_mut() {
+                        match (*handler)(action) {
+                            Ok(result) => {
+                                state.status_message = Some(result.status_message);
+                                if result.refresh_requested {
+                                    try_refresh_snapshot(state, &mut refresh);
+                                }
+                            }
+                            Err(err) => {
+                                state.report_error(format!("Playback error: {err}"));
+                                failed = true;
+                            }
+                        }
+                    }
+                }
+                KeyAction::CopyToClipboard(text) => match clipboard.copy(&text) {
+                    Ok(crate::clipboard::CopyMethod::Native) => {
+                        state.status_message = Some(format!("Copied: {text}"));
+                    }
+                    Ok(crate::clipboard::CopyMethod::Terminal) => {
+                        state.status_message = Some(format!("Copied via terminal: {text}"));
+                    }
+                    Err(err) => state.report_error(format!("Copy failed: {err}")),
+                },
+                KeyAction::PasteFromClipboard => match clipboard.paste() {
+                    Ok(text) => state.paste_text(&text),
+                    Err(err) => state.report_error(format!("Paste failed: {err}")),
+                },
+            }
+            if let Some(reply) = reply {
+                let message = if query {
+                    state.playback_summary()
+                } else {
+                    state.status_message.clone().unwrap_or_default()
+                };
+                let _ = reply.send(if failed { Err(message) } else { Ok(message) });
+            }
+        }
+    }
+}
+
+/// Applies what the player reported since the last tick, advancing to the
+/// next track when one finished. Returns whether the change should be
+/// drawn right away rather than when the next frame is due.
+fn apply_player_updates(
+    state: &mut ShellState,
+    updates: Vec<PlayerEventUpdate>,
+    playback_handler: &mut Option<&mut PlaybackActionFn<'_>>,
+    refresh: &mut Option<&mut RefreshSnapshotFn<'_>>,
+) -> bool {
+    let mut redraw = false;
+    for update in updates {
+        if !update.status.is_empty() {
+            redraw |= state.playback_status != update.status;
+            state.playback_status = update.status;
+        }
+        if let Some(message) = update.message {
+            state.status_message = Some(message);
+            redraw = true;
+        }
+        if update.position_ms > 0 || update.duration_ms > 0 {
+            state.playback_position_ms = update.position_ms;
+            state.playback_duration_ms = update.duration_ms;
+        }
+        if !update.spectrum_bands.is_empty() {
+            state.spectrum_bands = crate::visualizer::smooth_bands(
+                &state.spectrum_bands,
+                &update.spectrum_bands,
+                0.6,
+                0.15,
+            );
+            // Update fire history (scrolling spectrogram)
+            state.fire_history.insert(0, state.spectrum_bands.clone());
+            if state.fire_history.len() > 40 {
+                state.fire_history.truncate(40);
+            }
+        }
+        if !update.raw_samples.is_empty() {
+            state.viz_samples = update.raw_samples;
+        }
+        state.viz_frame = state.viz_frame.wrapping_add(1);
+        if update.track_finished {
+            redraw = true;
+            // Auto-advance to next track
+            if let Some(handler) = playback_handler.as_mut() {
+                if let Ok(result) = (*handler)(PlaybackAction::Advance) {
+                    state.status_message = Some(result.status_message);
+                    if result.refresh_requested {
+                        try_refresh_snapshot(state, refresh);
+                    }
+                }
+            }
+        }
+    }
+    redraw
+}
+
+pub fn render_once_to_text(
+    state: &mut ShellState,
+    palette: &Palette,
+    width: u16,
+    height: u16,
+) -> Result<String, UiError> {
+    let backend = TestBackend::new(width, height);
+    let mut terminal = Terminal::new(backend)
+        .map_err(|e| UiError::Terminal(format!("test terminal init failed: {e}")))?;
+    terminal
+        .draw(|f| {
+            let _ = draw_shell(f, state, palette);
+        })
+        .map_err(|e| UiError::Terminal(format!("test draw failed: {e}")))?;
+
+    let buffer = terminal.backend().buffer().clone();
+    let mut lines = Vec::with_capacity(height as usize);
+    for y in 0..height {
+        let mut line = String::new();
+        for x in 0..width {
+            let symbol = buffer[(x, y)].symbol();
+            line.push_str(symbol);
+        }
+        while line.ends_with(' ') {
+            line.pop();
+        }
+        lines.push(line);
+    }
+    Ok(lines.join("\n"))
+}
+
+/// What is left of `area` for the panes: the tab bar takes the top row and
+/// the screen reader announcements the bottom one when they are shown.
+fn panes_area(mut area: Rect, state: &ShellState) -> Rect {
+    if state.screen_reader() == ScreenReaderMode::Announce && area.height > 1 {
+        area.height -= 1;
+    }
+    if state.tab_count() > 1 && area.height > 1 {
+        area.y += 1;
+        area.height -= 1;
+    }
+    area
+}
+
+/// The panes laid out in `root` and where clicks land in them.
+fn pane_areas(root: Rect, state: &ShellState) -> (AppLayout, RenderAreas) {
+    let layout = AppLayout::new(root, &state.panel_layout);
+    let library_area = layout.library;
+    let has_browse_split = state.browse.show_items && !state.browse.items.is_empty();
+
+    let (tracks_area, browse_items_area) = if has_browse_split {
+        let lib_cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(30),
+                Constraint::Length(1),
+                Constraint::Percentage(70),
+            ])
+            .split(library_area);
+        (lib_cols[2], Some(lib_cols[0]))
+    } else {
+        (library_area, None)
+    };
+
+    let (header_area, library_rows_area) = library_panel_inner_areas(tracks_area);
+    let areas = RenderAreas {
+        roots: layout
+            .sidebar
+            .map(|s| PaneArea::borderless(s.roots, 1))
+            .unwrap_or_default(),
+        browse: layout.sidebar.map(|s| s.browse).unwrap_or_default(),
+        browse_items: browse_items_area.map(|a| PaneArea::bordered(a, 1)),
+        playlists: layout
+            .sidebar
+            .map(|s| PaneArea::borderless(s.playlists, 1))
+            .unwrap_or_default(),
+        tracks: PaneArea::from_list_area(tracks_area, library_rows_area, 1),
+        split: layout
+            .split
+            .map(|s| PaneArea::borderless(s, 1))
+            .unwrap_or_default(),
+        track_header: header_area,
+        track_col_offsets: TrackColumnOffsets::default(),
+        file_browser: None,
+    };
+    (layout, areas)
+}
+
+fn draw_shell(frame: &mut Frame, state: &mut ShellState, palette: &Palette) -> RenderAreas {
+    let full = frame.area();
+    frame.render_widget(
+        Block::default().style(Style::default().bg(palette.bg_root())),
+        full,
+    );
+    let root = panes_area(full, state);
+    if root.bottom() < full.bottom() {
+        state.update_announcement();
+        let line = Rect {
+            y: root.bottom(),
+            height: 1,
+            ..full
+        };
+        let text = state.announcement.as_deref().unwrap_or("");
+        frame.render_widget(
+            Paragraph::new(text).style(Style::default().fg(palette.text)),
+            line,
+        );
+    }
+    if root.y > full.y {
+        render_tab_bar(frame, Rect { height: 1, ..full }, state, palette);
+    }
+
+    let (layout, areas) = pane_areas(root, state);
+    let footer = layout.footer;
+    if let Some(separator) = layout.separator {
+        render_vertical_separator(frame, separator, palette);
+    }
+    let library_area = layout.library;
+    let tracks_area = areas.tracks.outer;
+    state.sync_scroll_offsets(&areas);
+
+    if let Some(sidebar) = layout.sidebar {
+        render_roots(frame, sidebar.roots, state, palette);
+        render_browse_modes(frame, sidebar.browse, state, palette);
+        render_playlists(frame, sidebar.playlists, state, palette);
+    }
+    state.volume_bar_area = Rect::default();
+    state.queue_line_area = Rect::default();
+    match (layout.variant, layout.now_playing) {
+        (LayoutVariant::Full, Some(area)) => render_now_playing(frame, area, state, palette),
+        (LayoutVariant::Mini, Some(area)) => {
+            state.viz_area = Rect::default();
+            render_now_playing_bar(frame, area, state, palette);
+        }
+        (_, None) => {
+            state.seek_bar_area = Rect::default();
+            state.viz_area = Rect::default();
+        }
+    }
+
+    if areas.browse_items.is_some() {
+        let lib_cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(30),
+                Constraint::Length(1),
+                Constraint::Percentage(70),
+            ])
+            .split(library_area);
+        render_browse_items(frame, lib_cols[0], state, palette);
+        render_vertical_separator(frame, lib_cols[1], palette);
+    }
+
+    let col_offsets = render_tracks(frame, tracks_area, state, palette);
+    if let Some(split) = layout.split {
+        render_vertical_separator(
+            frame,
+            Rect {
+                x: split.x - 1,
+                width: 1,
+                ..split
+            },
+            palette,
+        );
+        render_split(frame, split, state, palette);
+    }
+    let mut areas = RenderAreas {
+        track_col_offsets: col_offsets,
+        ..areas
+    };
+    match layout.variant {
+        LayoutVariant::Full => render_status(frame, footer, state, palette),
+        LayoutVariant::Mini => render_mini_status(frame, footer, state, palette),
+    }
+    if state.input_mode == InputMode::Normal {
+        render_row_hover(frame, state, &areas, palette);
+    }
+    render_seek_preview(frame, state, palette);
+
+    if state.input_mode == InputMode::Help {
+        render_help_overlay(frame, state, palette);
+    }
+    if state.input_mode == InputMode::Log {
+        render_log_overlay(frame, state, palette);
+    }
+    if state.input_mode == InputMode::Podcasts {
+        render_podcasts_overlay(frame, state, palette);
+    }
+    if state.input_mode == InputMode::Stats {
+        render_stats_overlay(frame, state, palette);
+    }
+    if state.input_mode == InputMode::Health {
+        render_health_overlay(frame, state, palette);
+    }
+    if state.input_mode == InputMode::Review {
+        render_review_overlay(frame, state, palette);
+    }
+    if state.show_debug {
+        render_debug_overlay(frame, state, palette);
+    }
+    if state.input_mode == InputMode::CommandPalette {
+        render_command_palette_overlay(frame, state, palette);
+    }
+    if let Some(browser) = state.file_browser.as_mut() {
+        browser.poll_counts();
+    }
+    if state.input_mode == InputMode::AddMusic {
+        areas.file_browser = render_add_music_overlay(frame, state, palette, false);
+    }
+    if state.input_mode == InputMode::Welcome {
+        areas.file_browser = render_add_music_overlay(frame, state, palette, true);
+    }
+    if let (Some(dialog), Some((_, y))) = (areas.file_browser, state.mouse_hover) {
+        if state.hovered(dialog.entries) {
+            shade_hovered_row(
+                frame,
+                Rect {
+                    y,
+                    height: 1,
+                    ..dialog.entries
+                },
+                palette,
+            );
+        }
+    }
+    if state.input_mode == InputMode::TrackInfo {
+        render_track_info_overlay(frame, state, palette);
+    }
+    if state.input_mode == InputMode::Settings {
+        render_settings_overlay(frame, state, palette);
+    }
+
+    // Fade-in effect on the Now Playing panel when a new track starts.
+    const FADE_DURATION_MS: u128 = 350;
+    if state.screen_reader() != ScreenReaderMode::Off {
+        state.track_change_time = None;
+    }
+    if let Some(started) = state.track_change_time {
+        let elapsed = started.elapsed();
+        if elapsed.as_millis() < FADE_DURATION_MS {
+            let mut effect = fx::fade_from_fg(
+                palette.text_muted,
+                EffectTimer::from_ms(FADE_DURATION_MS as u32, Interpolation::QuadOut),
+            );
+            let fade_area = layout.now_playing.unwrap_or_default();
+            effect.process(elapsed.into(), frame.buffer_mut(), fade_area);
+        } else {
+            state.track_change_time = None;
+        }
+    }
+    if state.unicode_level() == UnicodeLevel::Ascii {
+        crate::glyphs::asciify(frame.buffer_mut());
+    }
+
+    areas
+}
+
+/// One row naming the open tabs, the active one highlighted.
+fn render_tab_bar(frame: &mut Frame, area: Rect, state: &ShellState, palette: &Palette) {
+    let mut spans = Vec::new();
+    for (index, tab) in state.tabs.iter().enumerate() {
+        let active = index == state.active_tab;
+        let label = if active {
+            state.save_tab().label()
+        } else {
+            tab.label()
+        };
+        let style = if active {
+            Style::default()
+                .fg(palette.focus)
+                .bg(palette.selection_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(palette.text_muted)
+        };
+        spans.push(Span::styled(
+            format!(" {} {} ", index + 1, truncate_to_width(&label, 24)),
+            style,
+        ));
+        spans.push(Span::raw(" "));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn render_roots(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
+    let focused = state.focus == FocusPane::Sources;
+    let base_style = if focused {
+        Style::default().fg(palette.text)
+    } else {
+        Style::default()
+            .fg(palette.text)
+            .add_modifier(Modifier::DIM)
+    };
+
+    render_section_header(frame, area, "Library Roots", focused, palette);
+    let content_area = borderless_content_area(area);
+    if content_area.width == 0 || content_area.height == 0 {
+        return;
+    }
+
+    let items: Vec<ListItem> = if state.snapshot.roots.is_empty() {
+        vec![
+            ListItem::new(Line::from("")),
+            ListItem::new(Line::from(Span::styled(
+                "No library roots",
+                Style::default().fg(palette.text_muted),
+            ))),
+            ListItem::new(Line::from("")),
+            ListItem::new(Line::from(Span::styled(
+                "  Press a to add a music folder",
+                Style::default().fg(palette.text_muted),
+            ))),
+        ]
+    } else {
+        state
+            .snapshot
+            .roots
+            .iter()
+            .map(|r| {
+                let detail = r.detail.as_deref().unwrap_or("");
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        state.icon_prefix(IconToken::Folder),
+                        base_style.fg(palette.accent),
+                    ),
+                    Span::styled(&r.label, base_style),
+                    Span::styled(
+                        if detail.is_empty() {
+                            String::new()
+                        } else {
+                            format!("  {detail}")
+                        },
+                        base_style.fg(palette.text_muted),
+                    ),
+                ]))
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
+        .highlight_symbol("▌ ")
+        .highlight_style(Style::default().bg(palette.selection_bg).fg(palette.text));
+    let mut list_state = ListState::default().with_selected(Some(min(
+        state.selected_root,
+        state.snapshot.roots.len().saturating_sub(1),
+    )));
+    list_state = list_state.with_offset(state.roots_scroll);
+    frame.render_stateful_widget(list, content_area, &mut list_state);
+}
+
+fn render_browse_modes(frame: &mut Frame, area: Rect, state: &ShellState, palette: &Palette) {
+    let focused = state.focus == FocusPane::Browse;
+
+    render_section_header(frame, area, "Browse Library", focused, palette);
+    let content_area = borderless_content_area(area);
+    if content_area.width == 0 || content_area.height == 0 {
+        return;
+    }
+    let dim = if focused {
+        Modifier::empty()
+    } else {
+        Modifier::DIM
+    };
+
+    let modes = crate::browse::BrowseMode::all();
+    let mode_icons = [
+        IconToken::Track,
+        IconToken::Folder,
+        IconToken::Playlist,
+        IconToken::Track,
+    ];
+    let mut lines = Vec::new();
+
+    for (idx, mode) in modes.iter().enumerate() {
+        let is_current = idx == state.browse.mode_index;
+        let highlight = focused && !state.browse.show_items && is_current;
+        let icon = mode_icons.get(idx).copied().unwrap_or(IconToken::Folder);
+        let mut spans = vec![
+            Span::styled(
+                state.icon_prefix(icon),
+                Style::default()
+                    .fg(if is_current {
+                        palette.focus
+                    } else {
+                        palette.text_muted
+                    })
+                    .add_modifier(dim),
+            ),
+            Span::styled(
+                mode.label(),
+                Style::default()
+                    .fg(if is_current {
+                        palette.text
+                    } else {
+                        palette.text_muted
+                    })
+                    .add_modifier(if is_current {
+                        Modifier::BOLD | dim
+                    } else {
+                        dim
+                    }),
+            ),
+        ];
+        if highlight {
+            for span in &mut spans {
+                span.style = span.style.add_modifier(Modifier::REVERSED);
+            }
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let paragraph = Paragraph::new(lines);
+    frame.render_widget(paragraph, content_area);
+}
+
+fn render_browse_items(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
+    let title = match state.browse.mode {
+        crate::browse::BrowseMode::Artists => "Artists",
+        crate::browse::BrowseMode::Albums => "Albums",
+        crate::browse::BrowseMode::Recent => "Recently Played",
+        crate::browse::BrowseMode::Songs => return,
+    };
+
+    let focused = state.focus == FocusPane::Browse;
+    let block = pane_block(title, focused, palette);
+    let content_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    if content_area.width == 0 || content_area.height == 0 {
+        return;
+    }
+
+    let base_style = if focused {
+        Style::default().fg(palette.text)
+    } else {
+        Style::default()
+            .fg(palette.text)
+            .add_modifier(Modifier::DIM)
+    };
+
+    // Only rows on screen ask for a thumbnail; the rest keep the column blank.
+    // Thumbnails are half-block art, which ascii mode has no characters for.
+    let thumbnails = state.browse.mode == crate::browse::BrowseMode::Albums
+        && state.snapshot.show_artwork
+        && state.unicode_level() != UnicodeLevel::Ascii;
+    let visible =
+        state.browse.item_scroll..state.browse.item_scroll + usize::from(content_area.height);
+    let items: Vec<ListItem> = state
+        .browse
+        .items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let is_active = state.browse.selected_item.as_deref() == Some(item.as_str());
+            let style = if is_active {
+                base_style.fg(palette.focus).add_modifier(Modifier::BOLD)
+            } else {
+                base_style
+            };
+            let mut spans = Vec::new();
+            if thumbnails {
+                let thumbnail = if visible.contains(&index) {
+                    state.album_thumbnails.get(item)
+                } else {
+                    None
+                };
+                match thumbnail {
+                    Some(Some(thumbnail)) => spans.extend(thumbnail.spans()),
+                    Some(None) => {
+                        let artist = state
+                            .snapshot
+                            .albums
+                            .iter()
+                            .find(|(album, _)| album == item)
+                            .map_or("", |(_, artist)| artist.as_str());
+                        spans.extend(
+                            crate::artwork::Placeholder::new(artist, item)
+                                .thumbnail()
+                                .spans(),
+                        );
+                    }
+                    None => spans.push(Span::raw(
+                        " ".repeat(usize::from(crate::artwork::Thumbnail::WIDTH)),
+                    )),
+                }
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::styled(item.as_str(), style));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items).highlight_symbol("▌ ").highlight_style(
+        Style::default()
+            .bg(palette.selection_bg)
+            .fg(palette.text)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let mut list_state = ListState::default().with_selected(Some(
+        state
+            .browse
+            .item_index
+            .min(state.browse.items.len().saturating_sub(1)),
+    ));
+    list_state = list_state.with_offset(state.browse.item_scroll);
+    frame.render_stateful_widget(list, content_area, &mut list_state);
+}
+
+fn render_playlists(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
+    let focused = state.focus == FocusPane::Inspector;
+    let base_style = if focused {
+        Style::default().fg(palette.text)
+    } else {
+        Style::default()
+            .fg(palette.text)
+            .add_modifier(Modifier::DIM)
+    };
+
+    render_section_header(frame, area, "Playlists", focused, palette);
+    let content_area = borderless_content_area(area);
+    if content_area.width == 0 || content_area.height == 0 {
+        return;
+    }
+
+    let mut items: Vec<ListItem> = state
+        .snapshot
+        .playlists
+        .iter()
+        .map(|p| {
+            let mut spans = vec![
+                Span::styled(
+                    state.icon_prefix(IconToken::Playlist),
+                    base_style.fg(palette.accent_2),
+                ),
+                Span::styled(&p.label, base_style),
+            ];
+            if let Some(detail) = &p.detail {
+                spans.push(Span::styled(
+                    format!(" {detail}"),
+                    base_style.fg(palette.text_muted),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    items.push(ListItem::new(Line::from(Span::styled(
+        "+ New Playlist",
+        base_style.fg(palette.accent),
+    ))));
+
+    let list = List::new(items)
+        .highlight_symbol("▌ ")
+        .highlight_style(Style::default().bg(palette.selection_bg).fg(palette.text));
+    let mut list_state = ListState::default().with_selected(Some(min(
+        state.selected_playlist,
+        state.playlist_rows() - 1,
+    )));
+    list_state = list_state.with_offset(state.playlists_scroll);
+    frame.render_stateful_widget(list, content_area, &mut list_state);
+}
+
+/// The queue or a playlist beside the track list. In the queue the playing
+/// entry is highlighted.
+fn render_split(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
+    let focused = state.focus == FocusPane::Split;
+    let base_style = if focused {
+        Style::default().fg(palette.text)
+    } else {
+        Style::default()
+            .fg(palette.text)
+            .add_modifier(Modifier::DIM)
+    };
+    let snapshot = &state.snapshot;
+    let title = format!("{} ({})", snapshot.split_title, snapshot.split_tracks.len());
+    render_section_header(frame, area, &title, focused, palette);
+    let content_area = borderless_content_area(area);
+    if content_area.width == 0 || content_area.height == 0 {
+        return;
+    }
+
+    // `queue_position` counts from one; zero means nothing is playing.
+    let playing = (snapshot.split_source == "queue")
+        .then(|| snapshot.queue_position.checked_sub(1))
+        .flatten();
+    let mut items: Vec<ListItem> = if snapshot.split_tracks.is_empty() {
+        vec![ListItem::new(Line::from(Span::styled(
+            "Empty — press > on a track to add it",
+            base_style.fg(palette.text_muted),
+        )))]
+    } else {
+        snapshot
+            .split_tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let style = if playing == Some(index) {
+                    base_style.fg(palette.accent).add_modifier(Modifier::BOLD)
+                } else {
+                    base_style
+                };
+                let item = ListItem::new(Line::from(vec![
+                    Span::styled(track.title.as_str(), style),
+                    Span::styled(
+                        format!("  {}", track.artist),
+                        base_style.fg(palette.text_muted),
+                    ),
+                ]));
+                if state.split_drag.is_some_and(|drag| drag.from == index) {
+                    item.style(Style::default().bg(palette.selection_bg))
+                } else {
+                    item
+                }
+            })
+            .collect()
+    };
+    // While dragging, the dragged row keeps its shading and a line shows
+    // where it lands; the list is not scrolled to a selection meanwhile.
+    if let Some(row) = state.split_drag.and_then(SplitDrag::line_row) {
+        let line = "─".repeat(usize::from(content_area.width));
+        items.insert(
+            row,
+            ListItem::new(Line::from(Span::styled(
+                line,
+                Style::default().fg(palette.accent),
+            ))),
+        );
+    }
+
+    let spacing = if state.split_drag.is_some() {
+        HighlightSpacing::Always
+    } else {
+        HighlightSpacing::WhenSelected
+    };
+    let list = List::new(items)
+        .highlight_symbol("▌ ")
+        .highlight_spacing(spacing)
+        .highlight_style(Style::default().bg(palette.selection_bg).fg(palette.text));
+    let selected = (focused && state.split_drag.is_none()).then(|| {
+        min(
+            state.selected_split,
+            snapshot.split_tracks.len().saturating_sub(1),
+        )
+    });
+    let mut list_state = ListState::default()
+        .with_selected(selected)
+        .with_offset(state.split_scroll);
+    frame.render_stateful_widget(list, content_area, &mut list_state);
+}
+
+fn render_tracks(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut ShellState,
+    palette: &Palette,
+) -> TrackColumnOffsets {
+    let title = state.library_breadcrumb();
+    let summary = library_summary(&state.snapshot);
+    let outer_block = pane_block(&title, state.focus == FocusPane::Tracks, palette).title(
+        Line::from(Span::styled(
+            format!(" {summary} "),
+            Style::default().fg(palette.text_muted),
+        ))
+        .right_aligned(),
+    );
+    let inner = outer_block.inner(area);
+    frame.render_widget(outer_block, area);
+
+    if inner.width == 0 || inner.height == 0 {
+        return TrackColumnOffsets::default();
+    }
+
+    let (header_area, rows_area) = library_panel_inner_areas(area);
+
+    // Calculate column widths proportionally
+    let total_w = inner.width as usize;
+    let col_time = 7usize;
+    let col_bpm = 6;
+    let col_quality = 14;
+    let fixed = col_time + col_bpm + col_quality;
+    let flexible = total_w.saturating_sub(fixed);
+    let col_title = flexible * 30 / 100;
+    let col_artist = flexible * 25 / 100;
+    let col_album = flexible.saturating_sub(col_title + col_artist);
+
+    let header_x = inner.x;
+    let offsets = TrackColumnOffsets {
+        title_start: header_x,
+        time_start: header_x + col_title as u16,
+        artist_start: header_x + (col_title + col_time) as u16,
+        album_start: header_x + (col_title + col_time + col_artist) as u16,
+        bpm_start: header_x + (col_title + col_time + col_artist + col_album) as u16,
+        quality_start: header_x + (col_title + col_time + col_artist + col_album + col_bpm) as u16,
+    };
+
+    let sort_indicator = |col: SortColumn| -> &str {
+        if state.sort_column == col {
+            if state.sort_ascending {
+                " ▲"
+            } else {
+                " ▼"
+            }
+        } else {
+            ""
+        }
+    };
+    let sort_style = |col: SortColumn| -> Style {
+        if state.sort_column == col {
+            Style::default().fg(palette.accent)
+        } else {
+            Style::default().fg(palette.text_muted)
+        }
+    };
+
+    let header = Line::from(vec![
+        Span::styled(
+            pad_cell(
+                &format!("Title{}", sort_indicator(SortColumn::Title)),
+                col_title,
+            ),
+            sort_style(SortColumn::Title),
+        ),
+        Span::styled(
+            pad_cell(
+                &format!("Time{}", sort_indicator(SortColumn::Time)),
+                col_time,
+            ),
+            sort_style(SortColumn::Time),
+        ),
+        Span::styled(
+            pad_cell(
+                &format!("Artist{}", sort_indicator(SortColumn::Artist)),
+                col_artist,
+            ),
+            sort_style(SortColumn::Artist),
+        ),
+        Span::styled(
+            pad_cell(
+                &format!("Album{}", sort_indicator(SortColumn::Album)),
+                col_album,
+            ),
+            sort_style(SortColumn::Album),
+        ),
+        Span::styled(
+            pad_cell(&format!("BPM{}", sort_indicator(SortColumn::Bpm)), col_bpm),
+            sort_style(SortColumn::Bpm),
+        ),
+        Span::styled(
+            format!("Quality{}", sort_indicator(SortColumn::Quality)),
+            sort_style(SortColumn::Quality),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(header), header_area);
+
+    let items: Vec<ListItem> = if state.filtered_track_count() == 0 {
+        if state.snapshot.tracks.is_empty() && state.snapshot.roots.is_empty() {
+            vec![
+                ListItem::new(Line::from("")),
+                ListItem::new(Line::from(Span::styled(
+                    "No tracks in library",
+                    Style::default().fg(palette.text_muted),
+                ))),
+                ListItem::new(Line::from("")),
+                ListItem::new(Line::from(Span::styled(
+                    "  Add a music folder to get started",
+                    Style::default().fg(palette.text_muted),
+                ))),
+                ListItem::new(Line::from(Span::styled(
+                    "  Press a or : then root add /path",
+                    Style::default().fg(palette.text_muted),
+                ))),
+            ]
+        } else if state.snapshot.tracks.is_empty() {
+            vec![
+                ListItem::new(Line::from("")),
+                ListItem::new(Line::from(Span::styled(
+                    "No tracks in library",
+                    Style::default().fg(palette.text_muted),
+                ))),
+                ListItem::new(Line::from("")),
+                ListItem::new(Line::from(Span::styled(
+                    "  Press : then scan roots to import",
+                    Style::default().fg(palette.text_muted),
+                ))),
+            ]
+        } else {
+            vec![ListItem::new(Line::from(Span::styled(
+                "No tracks match current filter",
+                Style::default().fg(palette.text_muted),
+            )))]
+        }
+    } else {
+        let use_alt_bg = !palette.use_terminal_bg;
+        let title_offset = state.title_offset();
+        let playing_path = state.snapshot.now_playing_path.as_str();
+        state
+            .filtered_track_iter()
+            .enumerate()
+            .map(|(idx, t)| {
+                let offset = if idx == state.selected_track {
+                    title_offset
+                } else {
+                    0
+                };
+                let scrolled = |text: &str, width: usize| {
+                    pad_to_width(
+                        &scroll_to_width(text, offset, width.saturating_sub(1)),
+                        width,
+                    )
+                };
+                let row = format!(
+                    "{}{}{}{}{}{}",
+                    scrolled(&t.title, col_title),
+                    pad_cell(&format_duration_short(t.duration_ms), col_time),
+                    pad_cell(&t.artist, col_artist),
+                    scrolled(&t.album, col_album),
+                    pad_cell(
+                        &t.bpm.map(|bpm| bpm.to_string()).unwrap_or_default(),
+                        col_bpm
+                    ),
+                    format_tech_compact(t.sample_rate, t.bit_depth, t.channels)
+                );
+                let row_style = if !playing_path.is_empty() && t.path == playing_path {
+                    Style::default().fg(palette.text).bg(palette.playing_row_bg)
+                } else if use_alt_bg && idx % 2 == 1 {
+                    Style::default().fg(palette.text).bg(palette.row_alt_bg)
+                } else {
+                    Style::default().fg(palette.text)
+                };
+                // Styling the item rather than the text fills the whole row.
+                ListItem::new(Line::from(row)).style(row_style)
+            })
+            .collect()
+    };
+
+    let focused = state.focus == FocusPane::Tracks;
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(palette.selection_bg).fg(palette.text))
+        .highlight_symbol("▌ ")
+        .repeat_highlight_symbol(true)
+        .style(if focused {
+            Style::default()
+        } else {
+            Style::default().add_modifier(Modifier::DIM)
+        });
+    let selected = if state.filtered_track_count() == 0 {
+        None
+    } else {
+        Some(min(
+            state.selected_track,
+            state.filtered_track_count().saturating_sub(1),
+        ))
+    };
+    let mut list_state = ListState::default().with_selected(selected);
+    list_state = list_state.with_offset(state.tracks_scroll);
+    frame.render_stateful_widget(list, rows_area, &mut list_state);
+    offsets
+}
+
+/// `Track 7/43 in queue · Next: Title – Artist, then Title – Artist`.
+fn queue_line<'a>(snapshot: &'a ShellSnapshot, palette: &Palette) -> Line<'a> {
+    let muted = Style::default().fg(palette.text_muted);
+    let mut spans = vec![Span::styled(
+        format!(
+            "Track {}/{} in queue",
+            snapshot.queue_position, snapshot.queue_length
+        ),
+        muted,
+    )];
+    let mut next = snapshot.up_next.iter();
+    if let Some(track) = next.next() {
+        spans.push(Span::styled("  ·  Next: ", muted));
+        spans.push(Span::styled(
+            format!("{} – {}", track.title, track.artist),
+            Style::default().fg(palette.text),
+        ));
+    } else if snapshot.shuffle || snapshot.random_album {
+        spans.push(Span::styled("  ·  Next: shuffled", muted));
+    }
+    if let Some(track) = next.next() {
+        spans.push(Span::styled(
+            format!(", then {} – {}", track.title, track.artist),
+            muted,
+        ));
+    }
+    Line::from(spans)
+}
+
+fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
+    // Set again below if the spectrum is drawn this frame.
+    state.viz_area = Rect::default();
+    let glyphs = state.glyphs(palette);
+    let block = pane_block("Now Playing", false, palette);
+    let content_area = padded_inner(area);
+    frame.render_widget(block, area);
+
+    let is_playing = state.playback_status == "playing";
+    let is_paused = state.playback_status == "paused";
+    let has_track = !state.snapshot.now_playing_title.is_empty();
+
+    if has_track {
+        // Update artwork state when track changes
+        state.artwork.update(
+            &state.snapshot.now_playing_path,
+            state.snapshot.now_playing_artwork.as_deref(),
+            state.snapshot.pixel_art_enabled,
+            state.snapshot.pixel_art_cell_size,
+        );
+
+        // Split content area: artwork on left (square), text on right. A
+        // track without a cover gets a placeholder in the same spot.
+        let show_art =
+            (state.artwork.has_image() || state.snapshot.show_artwork) && content_area.height >= 3;
+        let art_width = if show_art {
+            content_area
+                .height
+                .saturating_mul(2)
+                .min(content_area.width / 3)
+        } else {
+            0
+        };
+        let text_area = Rect {
+            x: content_area.x + art_width,
+            y: content_area.y,
+            width: content_area.width.saturating_sub(art_width),
+            height: content_area.height,
+        };
+
+        let status_icon = if is_playing {
+            ">"
+        } else if is_paused {
+            "||"
+        } else {
+            "[]"
+        };
+
+        // Row 0: status icon + title + artist/album
+        let title_line = Line::from(vec![
+            Span::styled(
+                format!("{status_icon} "),
+                Style::default().fg(if is_playing {
+                    palette.progress_fill
+                } else {
+                    palette.text_muted
+                }),
+            ),
+            Span::styled(
+                state.snapshot.now_playing_title.as_str(),
+                Style::default()
+                    .fg(palette.text)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(
+                    "  {}  {}",
+                    state.snapshot.now_playing_artist, state.snapshot.now_playing_album
+                ),
+                Style::default().fg(palette.text_muted),
+            ),
+        ]);
+        let title_area = Rect {
+            x: text_area.x,
+            y: text_area.y,
+            width: text_area.width,
+            height: 1,
+        };
+        frame.render_widget(Paragraph::new(title_line), title_area);
+
+        // Row 1: seek bar
+        let position = state.shown_position_ms();
+        let duration = state.playback_duration_ms;
+        let progress = if duration > 0 {
+            position as f32 / duration as f32
+        } else {
+            0.0
+        };
+        let elapsed_str = format_ms(position);
+        let remaining_ms = duration.saturating_sub(position);
+        let remaining_str = format_ms(remaining_ms);
+
+        let seek_bar_rect = Rect {
+            x: text_area.x,
+            y: text_area.y + 1,
             width: text_area.width,
             height: 1,
         };
-        state.seek_bar_area = seek_bar_rect;
-        frame.render_widget(
-            crate::seekbar::SeekBar {
-                progress,
-                elapsed: &elapsed_str,
-                remaining: &remaining_str,
-                palette,
-            },
-            seek_bar_rect,
-        );
+        state.seek_bar_area = seek_bar_rect;
+        let marks = state.chapter_marks();
+        frame.render_widget(
+            crate::seekbar::SeekBar {
+                progress,
+                marks: &marks,
+                elapsed: &elapsed_str,
+                remaining: &remaining_str,
+                palette,
+                glyphs: &glyphs,
+            },
+            seek_bar_rect,
+        );
+
+        // Row 3, when there is room: queue position and what plays next.
+        let queue_row = (text_area.height >= 4).then_some(text_area.y + 3);
+
+        // Row 2: volume slider + transport info
+        const VOLUME_LABEL: &str = "vol ";
+        const VOLUME_BAR_WIDTH: u16 = 10;
+        let info_row = text_area.y + 2;
+        let show_volume_bar = text_area.width >= VOLUME_LABEL.len() as u16 + VOLUME_BAR_WIDTH + 6;
+        let info_x = if show_volume_bar {
+            frame.render_widget(
+                Paragraph::new(Span::styled(
+                    VOLUME_LABEL,
+                    Style::default().fg(palette.text_muted),
+                )),
+                Rect::new(text_area.x, info_row, VOLUME_LABEL.len() as u16, 1),
+            );
+            let bar = Rect::new(
+                text_area.x + VOLUME_LABEL.len() as u16,
+                info_row,
+                VOLUME_BAR_WIDTH,
+                1,
+            );
+            frame.render_widget(
+                crate::seekbar::VolumeBar {
+                    volume: state.snapshot.volume,
+                    palette,
+                    glyphs: &glyphs,
+                },
+                bar,
+            );
+            state.volume_bar_area = bar;
+            bar.right() + 1
+        } else {
+            text_area.x
+        };
+        let info_line = Line::from(vec![Span::styled(
+            format!(
+                "{}{}%{}  {}  {}  {}  {}  {}",
+                if show_volume_bar { "" } else { VOLUME_LABEL },
+                (state.snapshot.volume * 100.0).round() as u32,
+                if state.snapshot.volume_offset_db == 0.0 {
+                    String::new()
+                } else {
+                    format!(" {:+.1}dB", state.snapshot.volume_offset_db)
+                },
+                if state.snapshot.shuffle {
+                    "shuffle"
+                } else {
+                    ""
+                },
+                if state.snapshot.random_album {
+                    "random:album"
+                } else {
+                    ""
+                },
+                if state.snapshot.consume {
+                    "consume"
+                } else {
+                    ""
+                },
+                match state.snapshot.repeat_mode.as_str() {
+                    "one" => "repeat:1",
+                    "all" => "repeat:all",
+                    _ => "",
+                },
+                if queue_row.is_some() {
+                    String::new()
+                } else {
+                    format!(
+                        "{}/{}",
+                        state.snapshot.queue_position, state.snapshot.queue_length
+                    )
+                },
+            ),
+            Style::default().fg(palette.text_muted),
+        )]);
+        let info_area = Rect {
+            x: info_x,
+            y: info_row,
+            width: text_area.right().saturating_sub(info_x),
+            height: 1,
+        };
+        frame.render_widget(Paragraph::new(info_line), info_area);
+
+        if let Some(row) = queue_row {
+            let area = Rect {
+                y: row,
+                height: 1,
+                ..text_area
+            };
+            frame.render_widget(Paragraph::new(queue_line(&state.snapshot, palette)), area);
+            state.queue_line_area = area;
+        }
+
+        // Spectrum visualizer: fills remaining height below the fixed rows
+        let viz_top = queue_row.map_or(text_area.y + 3, |row| row + 1);
+        let viz_bottom = text_area.y + text_area.height;
+        if is_playing
+            && state.panel_layout.show_spectrum
+            && state.screen_reader() == ScreenReaderMode::Off
+            && !state.spectrum_bands.is_empty()
+            && viz_bottom > viz_top
+            && text_area.width >= 4
+        {
+            let viz_area = Rect {
+                x: text_area.x,
+                y: viz_top,
+                width: text_area.width,
+                height: viz_bottom - viz_top,
+            };
+            frame.render_widget(
+                crate::visualizer::VisualizerWidget {
+                    style: state.viz_style,
+                    bands: &state.spectrum_bands,
+                    samples: &state.viz_samples,
+                    palette,
+                    glyphs: &glyphs,
+                    frame_count: state.viz_frame,
+                    fire_history: &state.fire_history,
+                },
+                viz_area,
+            );
+            state.viz_area = viz_area;
+        }
+
+        // Render album artwork on the left
+        if show_art {
+            let art_area = Rect {
+                x: content_area.x,
+                y: content_area.y,
+                width: art_width,
+                height: content_area.height,
+            };
+            if let Some(protocol) = &mut state.artwork.current_image {
+                frame.render_stateful_widget(
+                    ratatui_image::StatefulImage::default(),
+                    art_area,
+                    protocol,
+                );
+            } else {
+                crate::artwork::Placeholder::new(
+                    &state.snapshot.now_playing_artist,
+                    &state.snapshot.now_playing_album,
+                )
+                .render(art_area, frame.buffer_mut());
+            }
+        }
+    } else {
+        // Reset seek bar area when no track is playing
+        state.seek_bar_area = Rect::default();
+
+        let mut lines = Vec::new();
+        if let Some(track) = state.selected_track_item() {
+            lines.push(Line::from(Span::styled(
+                format!("{} - {}", track.title, track.artist),
+                Style::default().fg(palette.text_muted),
+            )));
+            lines.push(Line::from(Span::styled(
+                "Press Enter to play",
+                Style::default().fg(palette.text_muted),
+            )));
+        } else {
+            lines.push(Line::from(Span::styled(
+                "No track selected",
+                Style::default().fg(palette.text_muted),
+            )));
+        }
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: true });
+        frame.render_widget(paragraph, content_area);
+    }
+}
+
+/// Compact now-playing for mini player mode: title row and seek bar.
+fn render_now_playing_bar(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut ShellState,
+    palette: &Palette,
+) {
+    let glyphs = state.glyphs(palette);
+    let area = Rect {
+        x: area.x + 1,
+        width: area.width.saturating_sub(2),
+        ..area
+    };
+    if area.width == 0 || area.height == 0 {
+        state.seek_bar_area = Rect::default();
+        return;
+    }
+
+    if state.snapshot.now_playing_title.is_empty() {
+        state.seek_bar_area = Rect::default();
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                "Nothing playing",
+                Style::default().fg(palette.text_muted),
+            )),
+            area,
+        );
+        return;
+    }
+
+    let is_playing = state.playback_status == "playing";
+    let status_icon = match state.playback_status.as_str() {
+        "playing" => ">",
+        "paused" => "||",
+        _ => "[]",
+    };
+    let volume = format!("vol {}%", (state.snapshot.volume * 100.0).round() as u32);
+    let title_line = Line::from(vec![
+        Span::styled(
+            format!("{status_icon} "),
+            Style::default().fg(if is_playing {
+                palette.progress_fill
+            } else {
+                palette.text_muted
+            }),
+        ),
+        Span::styled(
+            state.snapshot.now_playing_title.as_str(),
+            Style::default()
+                .fg(palette.text)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("  {}", state.snapshot.now_playing_artist),
+            Style::default().fg(palette.text_muted),
+        ),
+    ]);
+    let volume_width = volume.len() as u16;
+    let title_area = Rect {
+        height: 1,
+        width: area.width.saturating_sub(volume_width + 1),
+        ..area
+    };
+    frame.render_widget(Paragraph::new(title_line), title_area);
+    if area.width > volume_width + 1 {
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                volume,
+                Style::default().fg(palette.text_muted),
+            )),
+            Rect {
+                x: area.x + area.width - volume_width,
+                y: area.y,
+                width: volume_width,
+                height: 1,
+            },
+        );
+    }
+
+    if area.height < 2 {
+        state.seek_bar_area = Rect::default();
+        return;
+    }
+    let position = state.shown_position_ms();
+    let duration = state.playback_duration_ms;
+    let progress = if duration > 0 {
+        position as f32 / duration as f32
+    } else {
+        0.0
+    };
+    let elapsed_str = format_ms(position);
+    let remaining_str = format_ms(duration.saturating_sub(position));
+    let seek_bar_rect = Rect {
+        y: area.y + 1,
+        height: 1,
+        ..area
+    };
+    state.seek_bar_area = seek_bar_rect;
+    let marks = state.chapter_marks();
+    frame.render_widget(
+        crate::seekbar::SeekBar {
+            progress,
+            marks: &marks,
+            elapsed: &elapsed_str,
+            remaining: &remaining_str,
+            palette,
+            glyphs: &glyphs,
+        },
+        seek_bar_rect,
+    );
+}
+
+/// The time under the pointer, just above it on the row over the seek bar.
+/// Shades the list row under the mouse pointer.
+fn render_row_hover(frame: &mut Frame, state: &ShellState, areas: &RenderAreas, palette: &Palette) {
+    let Some((x, y)) = state.mouse_hover else {
+        return;
+    };
+    let browse_items = areas
+        .browse_items
+        .map(|pane| (pane, state.browse.item_scroll, state.browse.items.len()));
+    let panes = [
+        (areas.roots, state.roots_scroll, state.snapshot.roots.len()),
+        (
+            areas.playlists,
+            state.playlists_scroll,
+            state.playlist_rows(),
+        ),
+        (
+            areas.tracks,
+            state.tracks_scroll,
+            state.filtered_track_indices.len(),
+        ),
+        (
+            areas.split,
+            state.split_scroll,
+            state.snapshot.split_tracks.len(),
+        ),
+    ];
+    for (pane, scroll, len) in panes.into_iter().chain(browse_items) {
+        if pane.mouse_item_index(x, y, scroll, len).is_some() {
+            let top = y - (y - pane.inner.y) % pane.item_height;
+            let row = Rect {
+                y: top,
+                height: pane.item_height,
+                ..pane.inner
+            }
+            .intersection(pane.inner);
+            shade_hovered_row(frame, row, palette);
+            return;
+        }
+    }
+}
+
+/// Puts the hover background behind `row`, except where it is the
+/// selected or playing row.
+fn shade_hovered_row(frame: &mut Frame, row: Rect, palette: &Palette) {
+    let row = row.intersection(frame.area());
+    let buffer = frame.buffer_mut();
+    for y in row.top()..row.bottom() {
+        for x in row.left()..row.right() {
+            let cell = &mut buffer[(x, y)];
+            if cell.bg != palette.selection_bg && cell.bg != palette.playing_row_bg {
+                cell.set_bg(palette.hover_bg);
+            }
+        }
+    }
+}
+
+fn render_seek_preview(frame: &mut Frame, state: &mut ShellState, palette: &Palette) {
+    let bar = state.seek_bar_area;
+    if bar == Rect::default() {
+        state.seek_preview = None;
+        state.seek_dragging = false;
+        return;
+    }
+    let Some((column, position_ms)) = state.seek_preview else {
+        return;
+    };
+    if bar.y == 0 {
+        return;
+    }
+    let label = format!(" {} ", format_ms(position_ms));
+    let width = (label.len() as u16).min(bar.width);
+    let x = column
+        .saturating_sub(width / 2)
+        .clamp(bar.x, bar.right().saturating_sub(width));
+    frame.render_widget(
+        Paragraph::new(label).style(
+            Style::default()
+                .fg(palette.bg_root())
+                .bg(palette.accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Rect::new(x, bar.y - 1, width, 1),
+    );
+}
+
+fn format_ms(ms: u64) -> String {
+    let total_secs = ms / 1000;
+    let minutes = total_secs / 60;
+    let seconds = total_secs % 60;
+    format!("{minutes:02}:{seconds:02}")
+}
+
+fn render_status(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
+    state.hint_areas.clear();
+    if area.height == 0 || area.width == 0 {
+        return;
+    }
+
+    // Dim top separator line
+    let rule: String = "─".repeat(area.width as usize);
+    frame.buffer_mut().set_string(
+        area.x,
+        area.y,
+        &rule,
+        Style::default().fg(palette.border_unfocused),
+    );
+
+    let content_area = Rect {
+        x: area.x + 1,
+        y: area.y + 1,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(1),
+    };
+    if content_area.width == 0 || content_area.height == 0 {
+        return;
+    }
+
+    let width = usize::from(content_area.width);
+    let templates = [
+        state.snapshot.setting_footer_top.clone(),
+        state.snapshot.setting_footer_bottom.clone(),
+    ];
+    for (row, template) in templates
+        .iter()
+        .enumerate()
+        .take(usize::from(content_area.height))
+    {
+        let parts = footer::parse(template);
+        let (mut left, mut right) = footer::layout(
+            &parts,
+            width,
+            Style::default().fg(palette.text_muted),
+            |segment, room| footer_segment(state, palette, segment, room),
+        );
+        let line_area = Rect {
+            x: content_area.x,
+            y: content_area.y + row as u16,
+            width: content_area.width,
+            height: 1,
+        };
+        place_hints(state, &mut left, line_area, palette);
+        frame.render_widget(Paragraph::new(footer_line(left)), line_area);
+
+        let right_width = right.iter().map(footer::Piece::width).sum::<usize>() as u16;
+        if right_width > 0 && content_area.width > right_width + 2 {
+            let right_area = Rect {
+                x: content_area.x + content_area.width - right_width,
+                width: right_width,
+                ..line_area
+            };
+            place_hints(state, &mut right, right_area, palette);
+            frame.render_widget(Paragraph::new(footer_line(right)), right_area);
+        }
+    }
+}
+
+fn footer_line(pieces: Vec<footer::Piece<'_>>) -> Line<'_> {
+    Line::from(
+        pieces
+            .into_iter()
+            .flat_map(|piece| piece.spans)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Records where the hints among `pieces` are drawn in `area`, for clicks,
+/// and underlines the one under the mouse pointer.
+fn place_hints(
+    state: &mut ShellState,
+    pieces: &mut [footer::Piece],
+    area: Rect,
+    palette: &Palette,
+) {
+    let mut x = area.x;
+    for piece in pieces {
+        if piece.segment != Some(footer::Segment::Hints) {
+            x = x.saturating_add(piece.width() as u16);
+            continue;
+        }
+        for span in &mut piece.spans {
+            let keys = span
+                .content
+                .split_once(": ")
+                .and_then(|(keys, _)| crate::keymap::hint_keys(keys));
+            let hint_area = Rect::new(x, area.y, display_width(span.content.trim_end()) as u16, 1);
+            x = x.saturating_add(display_width(&span.content) as u16);
+            let Some(keys) = keys.filter(|_| hint_area.right() <= area.right()) else {
+                continue;
+            };
+            if state.hovered(hint_area) {
+                span.style = span
+                    .style
+                    .fg(palette.text)
+                    .add_modifier(Modifier::UNDERLINED);
+            }
+            state.hint_areas.push((hint_area, keys));
+        }
+    }
+}
+
+/// One segment of a footer line; see [`footer::Segment`]. `room` is the
+/// width left for hints.
+fn footer_segment(
+    state: &ShellState,
+    palette: &Palette,
+    segment: footer::Segment,
+    room: usize,
+) -> Vec<Span<'static>> {
+    use footer::Segment;
+    let muted = Style::default().fg(palette.text_muted);
+    let snapshot = &state.snapshot;
+    match segment {
+        Segment::Title => vec![Span::styled(
+            snapshot.app_title.clone(),
+            Style::default()
+                .fg(palette.text)
+                .add_modifier(Modifier::BOLD),
+        )],
+        Segment::Tracks => vec![Span::styled(
+            format!("{} tracks", snapshot.total_track_count),
+            muted,
+        )],
+        Segment::Filter if state.track_filter_query.is_empty() => Vec::new(),
+        Segment::Filter => vec![Span::styled(
+            format!("  filter: /{}", state.track_filter_query),
+            muted,
+        )],
+        Segment::Badges => {
+            let mut badges = Vec::new();
+            if snapshot.radio {
+                badges.push(Span::styled(
+                    "  [radio]",
+                    Style::default()
+                        .fg(palette.accent_2)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if state.scanning {
+                badges.push(Span::styled(
+                    "  [scanning...]",
+                    Style::default()
+                        .fg(palette.warning)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            badges
+        }
+        Segment::Log => match state.unseen_log_count() {
+            0 => Vec::new(),
+            unseen => vec![Span::styled(
+                format!("L: {unseen} new in log  "),
+                Style::default()
+                    .fg(palette.warning)
+                    .add_modifier(Modifier::BOLD),
+            )],
+        },
+        Segment::Hints => state
+            .fitting_hints(room)
+            .into_iter()
+            .map(|hint| Span::styled(hint, muted))
+            .collect(),
+        Segment::Status => vec![Span::styled(
+            state.status_message.clone().unwrap_or_default(),
+            Style::default().fg(if state.scanning {
+                palette.accent
+            } else {
+                palette.text_muted
+            }),
+        )],
+        Segment::Playing => vec![Span::styled(state.playback_summary(), muted)],
+        Segment::Queue if snapshot.queue_length == 0 => Vec::new(),
+        Segment::Queue => vec![Span::styled(
+            format!(
+                "{}/{} in queue",
+                snapshot.queue_position, snapshot.queue_length
+            ),
+            muted,
+        )],
+        Segment::Volume => vec![Span::styled(
+            format!("vol {}%", (snapshot.volume * 100.0).round() as u32),
+            muted,
+        )],
+        Segment::Clock => vec![Span::styled(
+            footer::clock_label(std::time::SystemTime::now(), snapshot.utc_offset_minutes),
+            muted,
+        )],
+    }
+}
+
+fn render_mini_status(frame: &mut Frame, area: Rect, state: &mut ShellState, palette: &Palette) {
+    state.hint_areas.clear();
+    let area = Rect {
+        x: area.x + 1,
+        width: area.width.saturating_sub(2),
+        ..area
+    };
+    let muted = Style::default().fg(palette.text_muted);
+    let mut pieces = vec![match state.status_message.as_deref() {
+        Some(message) => footer::Piece {
+            segment: None,
+            spans: vec![Span::styled(message.to_string(), muted)],
+        },
+        None => footer::Piece {
+            segment: Some(footer::Segment::Hints),
+            spans: footer_segment(
+                state,
+                palette,
+                footer::Segment::Hints,
+                usize::from(area.width),
+            ),
+        },
+    }];
+    place_hints(state, &mut pieces, area, palette);
+    frame.render_widget(Paragraph::new(footer_line(pieces)), area);
+}
+
+fn render_track_info_overlay(frame: &mut Frame, state: &mut ShellState, palette: &Palette) {
+    let track = match state.selected_track_item() {
+        Some(t) => t.clone(),
+        None => return,
+    };
+
+    let frame_area = frame.area();
+    let width = (frame_area.width * 70 / 100)
+        .max(50)
+        .min(frame_area.width.saturating_sub(4));
+    let height = (frame_area.height * 70 / 100)
+        .max(16)
+        .min(frame_area.height.saturating_sub(4));
+    let x = frame_area.x + (frame_area.width.saturating_sub(width)) / 2;
+    let y = frame_area.y + (frame_area.height.saturating_sub(height)) / 2;
+    let area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(
+            " Track Info ",
+            Style::default()
+                .fg(palette.border_focused)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .border_style(Style::default().fg(palette.border_focused))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let content = Rect {
+        x: inner.x.saturating_add(1),
+        y: inner.y,
+        width: inner.width.saturating_sub(2),
+        height: inner.height,
+    };
+
+    if content.height < 4 || content.width < 20 {
+        return;
+    }
+
+    // Split: artwork on left, metadata on right
+    let has_art = state.track_info_artwork.is_some();
+    let art_width = if has_art {
+        content.height.saturating_mul(2).min(content.width / 3)
+    } else {
+        0
+    };
+    let art_area = Rect {
+        x: content.x,
+        y: content.y,
+        width: art_width,
+        height: content.height.saturating_sub(2), // leave room for footer
+    };
+    let meta_area = Rect {
+        x: content.x + art_width + if has_art { 1 } else { 0 },
+        y: content.y,
+        width: content
+            .width
+            .saturating_sub(art_width + if has_art { 1 } else { 0 }),
+        height: content.height,
+    };
+
+    // Render artwork
+    if has_art {
+        state.track_info_art_state.update(
+            &track.path,
+            state.track_info_artwork.as_deref(),
+            false,
+            2,
+        );
+        if let Some(protocol) = &mut state.track_info_art_state.current_image {
+            frame.render_stateful_widget(
+                ratatui_image::StatefulImage::default(),
+                art_area,
+                protocol,
+            );
+        }
+    }
+
+    // Render metadata
+    let duration = track
+        .duration_ms
+        .map(|ms| {
+            let secs = ms / 1000;
+            format!("{}:{:02}", secs / 60, secs % 60)
+        })
+        .unwrap_or_else(|| "--:--".to_string());
+
+    let sample_rate = track
+        .sample_rate
+        .map(|sr| {
+            let khz = sr / 1000;
+            let rem = (sr % 1000) / 100;
+            if rem > 0 {
+                format!("{khz}.{rem} kHz")
+            } else {
+                format!("{khz} kHz")
+            }
+        })
+        .unwrap_or_else(|| "-".to_string());
+    let channels = track
+        .channels
+        .map(|ch| match ch {
+            1 => "Mono".to_string(),
+            2 => "Stereo".to_string(),
+            n => format!("{n}ch"),
+        })
+        .unwrap_or_else(|| "-".to_string());
+    let bit_depth = track
+        .bit_depth
+        .filter(|&bd| bd > 0)
+        .map(|bd| format!("{bd}-bit"))
+        .unwrap_or_else(|| "Lossy".to_string());
+
+    let label_style = Style::default().fg(palette.text_muted);
+    let value_style = Style::default().fg(palette.text);
+    let title_style = Style::default()
+        .fg(palette.text)
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(&track.title, title_style)),
+        Line::from(Span::styled(
+            &track.artist,
+            Style::default().fg(palette.accent),
+        )),
+        Line::from(Span::styled(&track.album, value_style)),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Duration     ", label_style),
+            Span::styled(duration, value_style),
+        ]),
+        Line::from(vec![
+            Span::styled("Sample Rate  ", label_style),
+            Span::styled(sample_rate, value_style),
+        ]),
+        Line::from(vec![
+            Span::styled("Channels     ", label_style),
+            Span::styled(channels, value_style),
+        ]),
+        Line::from(vec![
+            Span::styled("Bit Depth    ", label_style),
+            Span::styled(bit_depth, value_style),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Path  ", label_style),
+            Span::styled(&track.path, Style::default().fg(palette.text_muted)),
+        ]),
+    ];
+
+    // Pad and add footer
+    let used = lines.len() as u16;
+    let remaining = meta_area.height.saturating_sub(used + 1);
+    for _ in 0..remaining {
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "Esc or i to close",
+        Style::default().fg(palette.text_muted),
+    )));
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, meta_area);
+}
+
+fn render_settings_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
+    let pages = state.settings_pages();
+    let page_index = state.settings_page.min(pages.len().saturating_sub(1));
+    let mut lines: Vec<Line> = Vec::new();
+
+    let mut tabs = vec![Span::raw(" ")];
+    for (i, page) in pages.iter().enumerate() {
+        let style = if i == page_index {
+            Style::default()
+                .fg(palette.accent)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+        } else {
+            Style::default().fg(palette.text_muted)
+        };
+        tabs.push(Span::styled(format!(" {} ", page.title), style));
+    }
+    lines.push(Line::from(tabs));
+    if let Some(root) = state
+        .settings_root
+        .and_then(|i| state.snapshot.roots.get(i))
+    {
+        lines.push(Line::from(Span::styled(
+            format!("  {}", root.label),
+            Style::default().fg(palette.text_muted),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let items = pages
+        .get(page_index)
+        .map(|p| p.items.as_slice())
+        .unwrap_or_default();
+    let label_width = items
+        .iter()
+        .map(|item| display_width(&item.label))
+        .max()
+        .unwrap_or(0)
+        + 3;
+    for (i, item) in items.iter().enumerate() {
+        let is_selected = i == state.settings_index;
+        let marker = if is_selected { " > " } else { "   " };
+        let style = if is_selected {
+            Style::default()
+                .fg(palette.text)
+                .bg(palette.selection_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(palette.text)
+        };
+        let value_style = if is_selected {
+            Style::default()
+                .fg(palette.accent)
+                .bg(palette.selection_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(palette.accent)
+        };
+
+        let mut spans = vec![
+            Span::styled(marker, style),
+            Span::styled(pad_to_width(&item.label, label_width), style),
+        ];
+        match state.settings_editor.as_ref().filter(|_| is_selected) {
+            Some(editor) => spans.extend(editor.spans(value_style, true)),
+            None => spans.push(Span::styled(item.value.as_str(), value_style)),
+        }
+        if let Some(note) = item.note {
+            spans.push(Span::styled(
+                format!("  ({note})"),
+                Style::default().fg(palette.text_muted),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    let hint = if state.settings_editor.is_some() {
+        "   Enter: save   Esc: cancel"
+    } else if state.settings_root.is_some() {
+        "   Enter/Space: change   Esc: close"
+    } else {
+        "   ←/→: page   Enter/Space: change   +/-: adjust   Esc: close"
+    };
+    lines.push(Line::from(Span::styled(
+        hint,
+        Style::default().fg(palette.text_muted),
+    )));
+
+    let title = if state.settings_root.is_some() {
+        "Folder Properties"
+    } else {
+        "Settings"
+    };
+    crate::modal::render_modal(frame, title, lines, 60, 55, palette);
+}
+
+fn render_help_overlay(frame: &mut Frame, state: &mut ShellState, palette: &Palette) {
+    let area = centered_rect(70, 80, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Help ")
+        .border_style(Style::default().fg(palette.focus))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 3 || inner.width < 10 {
+        return;
+    }
+    let content = Rect {
+        x: inner.x.saturating_add(1),
+        width: inner.width.saturating_sub(2),
+        ..inner
+    };
+
+    let entries = crate::keymap::help_entries(&state.chords, ChordAction::describe);
+    let matches = crate::keymap::filter_entries(&entries, state.help_filter.as_str());
+    let key_width = matches
+        .iter()
+        .map(|entry| display_width(&entry.keys))
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let mut lines: Vec<Line> = Vec::new();
+    let mut category = None;
+    for entry in &matches {
+        if category != Some(entry.category) {
+            if category.is_some() {
+                lines.push(Line::from(""));
+            }
+            category = Some(entry.category);
+            lines.push(Line::from(Span::styled(
+                entry.category.label(),
+                Style::default()
+                    .fg(palette.accent)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {}", pad_to_width(&entry.keys, key_width)),
+                Style::default()
+                    .fg(palette.text)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                entry.description.as_str(),
+                Style::default().fg(palette.text_muted),
+            ),
+        ]));
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No matching bindings",
+            Style::default().fg(palette.text_muted),
+        )));
+    }
+
+    let list_height = content.height.saturating_sub(2) as usize;
+    state.help_scroll = state
+        .help_scroll
+        .min(lines.len().saturating_sub(list_height));
+    let filter_style = if state.help_filter_focused {
+        Style::default().fg(palette.text)
+    } else {
+        Style::default().fg(palette.text_muted)
+    };
+    let filter_line = Line::from(
+        std::iter::once(Span::styled(
+            "Filter: ",
+            Style::default().fg(palette.text_muted),
+        ))
+        .chain(
+            state
+                .help_filter
+                .spans(filter_style, state.help_filter_focused),
+        )
+        .collect::<Vec<_>>(),
+    );
+    frame.render_widget(
+        Paragraph::new(filter_line),
+        Rect {
+            height: 1,
+            ..content
+        },
+    );
+    let visible: Vec<Line> = lines
+        .iter()
+        .skip(state.help_scroll)
+        .take(list_height)
+        .cloned()
+        .collect();
+    frame.render_widget(
+        Paragraph::new(visible),
+        Rect {
+            y: content.y + 1,
+            height: list_height as u16,
+            ..content
+        },
+    );
+    let position = if lines.len() > list_height {
+        format!(
+            "  {}-{} of {}",
+            state.help_scroll + 1,
+            (state.help_scroll + list_height).min(lines.len()),
+            lines.len()
+        )
+    } else {
+        String::new()
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            format!("j/k scroll  PgDn/PgUp page  / filter  esc close{position}"),
+            Style::default().fg(palette.text_muted),
+        )),
+        Rect {
+            y: content.y + content.height - 1,
+            height: 1,
+            ..content
+        },
+    );
+}
+
+fn render_podcasts_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
+    let area = centered_rect(80, 70, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Podcasts ")
+        .border_style(Style::default().fg(palette.focus))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 3 || inner.width < 30 {
+        return;
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(35),
+            Constraint::Length(1),
+            Constraint::Min(10),
+        ])
+        .split(rows[0]);
+    let podcasts = &state.snapshot.podcasts;
+    let panel = &state.podcast_panel;
+    // Two lines per entry.
+    let visible = (cols[0].height as usize / 2).max(1);
+    let muted = Style::default().fg(palette.text_muted);
+    let row_style = |selected: bool, focused: bool| match (selected, focused) {
+        (true, true) => Style::default()
+            .fg(palette.text)
+            .bg(palette.selection_bg)
+            .add_modifier(Modifier::BOLD),
+        (true, false) => Style::default().fg(palette.accent),
+        _ => Style::default().fg(palette.text),
+    };
+
+    let mut left: Vec<Line> = Vec::new();
+    if podcasts.is_empty() {
+        left.push(Line::from(Span::styled(" No podcasts yet.", muted)));
+        left.push(Line::from(Span::styled(" Press a to subscribe", muted)));
+        left.push(Line::from(Span::styled(" to a feed URL.", muted)));
+    }
+    let first = panel.podcast.saturating_sub(visible - 1);
+    let width = cols[0].width as usize;
+    for (i, podcast) in podcasts.iter().enumerate().skip(first).take(visible) {
+        let style = row_style(i == panel.podcast, !panel.episodes_focused);
+        left.push(Line::from(Span::styled(
+            pad_to_width(
+                &format!(
+                    " {}",
+                    truncate_to_width(&podcast.title, width.saturating_sub(2))
+                ),
+                width,
+            ),
+            style,
+        )));
+        left.push(Line::from(Span::styled(
+            format!("   {}", podcast.detail),
+            muted,
+        )));
+    }
+    frame.render_widget(Paragraph::new(left), cols[0]);
+    render_vertical_separator(frame, cols[1], palette);
+
+    let mut right: Vec<Line> = Vec::new();
+    let episodes = panel
+        .selected_podcast(podcasts)
+        .map(|p| p.episodes.as_slice())
+        .unwrap_or_default();
+    if episodes.is_empty() && !podcasts.is_empty() {
+        right.push(Line::from(Span::styled(
+            " No episodes; press r to refresh",
+            muted,
+        )));
+    }
+    let width = cols[2].width as usize;
+    let first = panel.episode.saturating_sub(visible - 1);
+    for (i, episode) in episodes.iter().enumerate().skip(first).take(visible) {
+        let mark = if episode.played {
+            "✓"
+        } else if episode.position_ms > 0 {
+            "◐"
+        } else {
+            "●"
+        };
+        let style = row_style(i == panel.episode, panel.episodes_focused);
+        let saved = if episode.downloaded { "  ↓" } else { "" };
+        right.push(Line::from(Span::styled(
+            pad_to_width(
+                &format!(
+                    " {mark} {}",
+                    truncate_to_width(&episode.title, width.saturating_sub(6))
+                ),
+                width,
+            ),
+            style,
+        )));
+        let resume = if episode.position_ms > 0 && !episode.played {
+            format!("  resume at {}", format_ms(episode.position_ms))
+        } else {
+            String::new()
+        };
+        right.push(Line::from(Span::styled(
+            format!("     {}{resume}{saved}", episode.detail),
+            muted,
+        )));
+    }
+    frame.render_widget(Paragraph::new(right), cols[2]);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(
+            " Enter: play   d: download   r: refresh   a: subscribe   Tab: switch list   Esc: close",
+            muted,
+        ))),
+        rows[1],
+    );
+}
+
+fn render_stats_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
+    let area = centered_rect(86, 84, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Listening Stats ")
+        .border_style(Style::default().fg(palette.focus))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 3 || inner.width < 20 {
+        return;
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    frame.render_widget(
+        crate::stats::StatsView {
+            stats: &state.snapshot.listening_stats,
+            total_tracks: state.snapshot.total_track_count,
+            palette,
+        },
+        rows[0],
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            " y: year in review   Esc: close",
+            Style::default().fg(palette.text_muted),
+        )),
+        rows[1],
+    );
+}
+
+fn render_review_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
+    let Some(review) = &state.review else { return };
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Year in Review ")
+        .border_style(Style::default().fg(palette.focus))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 3 || inner.width < 20 {
+        return;
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    frame.render_widget(crate::stats::ReviewView { review, palette }, rows[0]);
+    let mut keys = String::new();
+    if let Some(previous) = &review.previous {
+        keys.push_str(&format!(" ←: {previous}  "));
+    }
+    if let Some(next) = &review.next {
+        keys.push_str(&format!(" →: {next}  "));
+    }
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            format!("{keys} e: export   Esc: close"),
+            Style::default().fg(palette.text_muted),
+        )),
+        rows[1],
+    );
+}
+
+fn render_health_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
+    let area = centered_rect(80, 70, frame.area());
+    frame.render_widget(Clear, area);
+    let report = &state.snapshot.health;
+    let checked = match report.checked.as_str() {
+        "" => " Library Health · files never checked ".to_string(),
+        date => format!(" Library Health · files checked {date} "),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(checked)
+        .border_style(Style::default().fg(palette.focus))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 3 || inner.width < 30 {
+        return;
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(26),
+            Constraint::Length(1),
+            Constraint::Min(10),
+        ])
+        .split(rows[0]);
+    let issues = &report.issues;
+    let panel = &state.health_panel;
+    let muted = Style::default().fg(palette.text_muted);
+    let row_style = |selected: bool, focused: bool| match (selected, focused) {
+        (true, true) => Style::default()
+            .fg(palette.text)
+            .bg(palette.selection_bg)
+            .add_modifier(Modifier::BOLD),
+        (true, false) => Style::default().fg(palette.accent),
+        _ => Style::default().fg(palette.text),
+    };
+
+    let width = cols[0].width as usize;
+    let left = issues
+        .iter()
+        .enumerate()
+        .map(|(i, issue)| {
+            let count = match (issue.count, issue.needs_check && report.checked.is_empty()) {
+                (_, true) => "?".to_string(),
+                (count, false) => thousands(count),
+            };
+            let label = truncate_to_width(&issue.label, width.saturating_sub(count.len() + 3));
+            let gap = width.saturating_sub(label.chars().count() + count.len() + 2);
+            Line::from(Span::styled(
+                format!(" {label}{}{count} ", " ".repeat(gap)),
+                row_style(i == panel.issue, !panel.tracks_focused),
+            ))
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(Paragraph::new(left), cols[0]);
+    render_vertical_separator(frame, cols[1], palette);
+
+    // Two lines per track.
+    let visible = (cols[2].height as usize / 2).max(1);
+    let mut right: Vec<Line> = Vec::new();
+    let selected = panel.selected_issue(issues);
+    match selected {
+        Some(issue) if issue.needs_check && report.checked.is_empty() => {
+            right.push(Line::from(Span::styled(
+                " Press r to check the files",
+                muted,
+            )));
+        }
+        Some(issue) if issue.tracks.is_empty() => {
+            right.push(Line::from(Span::styled(" Nothing to report", muted)));
+        }
+        _ => {}
+    }
+    let tracks = selected.map(|i| i.tracks.as_slice()).unwrap_or_default();
+    let width = cols[2].width as usize;
+    let first = panel.track.saturating_sub(visible - 1);
+    for (i, track) in tracks.iter().enumerate().skip(first).take(visible) {
+        right.push(Line::from(Span::styled(
+            pad_to_width(
+                &format!(
+                    " {}",
+                    truncate_to_width(&track.title, width.saturating_sub(2))
+                ),
+                width,
+            ),
+            row_style(i == panel.track, panel.tracks_focused),
+        )));
+        let detail = match track.detail.as_str() {
+            "" => track.path.clone(),
+            detail => format!("{detail} · {}", track.path),
+        };
+        right.push(Line::from(Span::styled(
+            format!("   {}", truncate_to_width(&detail, width.saturating_sub(4))),
+            muted,
+        )));
+    }
+    if let Some(issue) = selected.filter(|i| i.count > i.tracks.len() && !i.tracks.is_empty()) {
+        if tracks.len() <= visible + first {
+            right.push(Line::from(Span::styled(
+                format!(
+                    "   … and {} more",
+                    thousands(issue.count - issue.tracks.len())
+                ),
+                muted,
+            )));
+        }
+    }
+    frame.render_widget(Paragraph::new(right), cols[2]);
+
+    let fix = match selected.and_then(|i| i.fix.as_ref()) {
+        Some((what, _)) => format!("   f: {}", what.to_lowercase()),
+        None => String::new(),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(
+            format!(" Enter: show track   r: check files{fix}   Esc: close"),
+            muted,
+        ))),
+        rows[1],
+    );
+}
+
+fn render_log_overlay(frame: &mut Frame, state: &mut ShellState, palette: &Palette) {
+    let area = centered_rect(80, 70, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Log (UTC) ")
+        .border_style(Style::default().fg(palette.focus))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 2 || inner.width < 20 {
+        return;
+    }
+    let content = Rect {
+        x: inner.x.saturating_add(1),
+        width: inner.width.saturating_sub(2),
+        ..inner
+    };
+
+    let entries = state.event_log.entries();
+    let list_height = content.height.saturating_sub(1) as usize;
+    state.log_scroll = state
+        .log_scroll
+        .min(entries.len().saturating_sub(list_height));
+    let message_width = (content.width as usize).saturating_sub(16);
+    let mut lines: Vec<Line> = entries
+        .iter()
+        .skip(state.log_scroll)
+        .take(list_height)
+        .map(|entry| {
+            let level_color = match entry.level {
+                LogLevel::Warn => palette.warning,
+                LogLevel::Error => palette.danger,
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", entry.time_label()),
+                    Style::default().fg(palette.text_muted),
+                ),
+                Span::styled(
+                    format!("{:<6} ", entry.level.label()),
+                    Style::default()
+                        .fg(level_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    truncate_to_width(&entry.message, message_width),
+                    Style::default().fg(palette.text),
+                ),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No warnings or errors",
+            Style::default().fg(palette.text_muted),
+        )));
+    }
+    frame.render_widget(
+        Paragraph::new(lines),
+        Rect {
+            height: list_height as u16,
+            ..content
+        },
+    );
+    let position = if entries.len() > list_height {
+        format!(
+            "  {}-{} of {}",
+            state.log_scroll + 1,
+            (state.log_scroll + list_height).min(entries.len()),
+            entries.len()
+        )
+    } else {
+        String::new()
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            format!("j/k scroll  g/G top/bottom  c clear  esc close{position}"),
+            Style::default().fg(palette.text_muted),
+        )),
+        Rect {
+            y: content.y + content.height - 1,
+            height: 1,
+            ..content
+        },
+    );
+}
+
+/// Small panel in the top-right corner; drawn over everything else.
+fn render_debug_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
+    let rows = crate::debug_overlay::debug_rows(
+        &state.debug_stats,
+        &state.snapshot.tracks,
+        crate::visualizer::last_analysis_time(),
+        &state.snapshot.db_timings,
+    );
+    let label_width = rows
+        .iter()
+        .map(|(label, _)| display_width(label))
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let content_width = rows
+        .iter()
+        .map(|(_, value)| label_width + display_width(value))
+        .max()
+        .unwrap_or(0);
+    let screen = frame.area();
+    let width = (content_width as u16 + 4).min(screen.width);
+    let height = (rows.len() as u16 + 2).min(screen.height);
+    let area = Rect {
+        x: screen.x + screen.width - width,
+        y: screen.y,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Debug ")
+        .border_style(Style::default().fg(palette.warning))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {}", pad_to_width(&label, label_width)),
+                    Style::default().fg(palette.text_muted),
+                ),
+                Span::styled(value, Style::default().fg(palette.text)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_command_palette_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
+    let frame_area = frame.area();
+    let width = frame_area.width.saturating_sub(8).clamp(24, 88);
+    let x = frame_area.x + frame_area.width.saturating_sub(width) / 2;
+    let height = 5u16;
+    let y = frame_area
+        .y
+        .saturating_add(frame_area.height.saturating_sub(height + 2));
+    let area = Rect::new(x, y, width, height);
+    frame.render_widget(Clear, area);
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(":", Style::default().fg(palette.focus).add_modifier(Modifier::BOLD)),
+        ]
+        .into_iter()
+        .chain(
+            state
+                .command_palette_input
+                .spans(Style::default().fg(palette.text), true),
+        )
+        .collect::<Vec<_>>()),
+        Line::from(Span::styled(
+            "Examples: help | refresh | scan roots | feature enable visualizer | root add /path --watched",
+            Style::default().fg(palette.text_muted),
+        )),
+        Line::from(Span::styled(
+            "playlist create <name> | playlist delete <id> | scan path <dir> --prune",
+            Style::default().fg(palette.text_muted),
+        )),
+    ];
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Command Palette")
+                .border_style(Style::default().fg(palette.focus))
+                .style(Style::default().bg(palette.bg_panel()).fg(palette.text)),
+        )
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_add_music_overlay(
+    frame: &mut Frame,
+    state: &ShellState,
+    palette: &Palette,
+    is_welcome: bool,
+) -> Option<FileBrowserAreas> {
+    let frame_area = frame.area();
+    let width = (frame_area.width * 60 / 100)
+        .max(40)
+        .min(frame_area.width.saturating_sub(4));
+    let height = (frame_area.height * 70 / 100)
+        .max(16)
+        .min(frame_area.height.saturating_sub(4));
+    let x = frame_area.x + (frame_area.width.saturating_sub(width)) / 2;
+    let y = frame_area.y + (frame_area.height.saturating_sub(height)) / 2;
+    let area = Rect::new(x, y, width, height);
+    frame.render_widget(Clear, area);
+
+    let title = if is_welcome {
+        " Welcome to auric "
+    } else {
+        " Add Music "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(palette.focus))
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let content = Rect {
+        x: inner.x.saturating_add(1),
+        y: inner.y,
+        width: inner.width.saturating_sub(2),
+        height: inner.height,
+    };
+
+    if content.height < 4 || content.width < 10 {
+        return None;
+    }
+
+    let browser = state.file_browser.as_ref()?;
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    if is_welcome {
+        lines.push(Line::from(Span::styled(
+            "Add a folder to get started.",
+            Style::default().fg(palette.text_muted),
+        )));
+        lines.push(Line::from(""));
+    }
 
-        // Row 2: transport info
-        let info_line = Line::from(vec![
+    let path_input_row = content.y + lines.len() as u16;
+    let input_style = if browser.input_focused {
+        Style::default()
+            .fg(palette.text)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(palette.text_muted)
+    };
+    lines.push(Line::from(
+        vec![Span::styled(
+            "Path: ",
+            Style::default().fg(palette.text_muted),
+        )]
+        .into_iter()
+        .chain(browser.path_input.spans(input_style, browser.input_focused))
+        .collect::<Vec<_>>(),
+    ));
+    let places = &state.browser_places;
+    if !places.bookmarks.is_empty() {
+        let marks = places
+            .bookmarks
+            .iter()
+            .enumerate()
+            .map(|(i, dir)| format!("{} {}", i + 1, tilde_path(dir)))
+            .collect::<Vec<_>>()
+            .join("  ");
+        lines.push(Line::from(vec![
+            Span::styled("Bookmarks: ", Style::default().fg(palette.text_muted)),
             Span::styled(
-                format!(
-                    "vol: {}%  {}  {}  {}/{}",
-                    (state.snapshot.volume * 100.0).round() as u32,
-                    if state.snapshot.shuffle { "shuffle" } else { "" },
-                    match state.snapshot.repeat_mode.as_str() {
-                        "one" => "repeat:1",
-                        "all" => "repeat:all",
-                        _ => "",
-                    },
-                    state.snapshot.queue_position,
-                    state.snapshot.queue_length,
+                truncate_to_width(&marks, (content.width as usize).saturating_sub(11)),
+                Style::default().fg(palette.text),
+            ),
+        ]));
+    }
+    if let Some(next) = places.next_recent(browser.current_dir()) {
+        lines.push(Line::from(vec![
+            Span::styled("Recent (r): ", Style::default().fg(palette.text_muted)),
+            Span::styled(
+                truncate_start_to_width(
+                    &tilde_path(next),
+                    (content.width as usize).saturating_sub(12),
                 ),
-                Style::default().fg(palette.text_muted),
+                Style::default().fg(palette.text),
             ),
-        ]);
-        let info_area = Rect {
-            x: text_area.x,
-            y: text_area.y + 2,
-            width: text_area.width,
-            height: 1,
+        ]));
+    }
+    lines.push(Line::from(""));
+
+    let current = browser.current_dir().display().to_string();
+    let slot = places.bookmarks.iter().position(|dir| *dir == current);
+    let dir_display = match slot {
+        Some(slot) => format!("{}/  [{}]", tilde_path(&current), slot + 1),
+        None => format!("{}/", tilde_path(&current)),
+    };
+    let filter_display = if browser.filter().is_empty() {
+        String::new()
+    } else {
+        format!("  filter: {}", browser.filter())
+    };
+    lines.push(Line::from(vec![
+        Span::styled(
+            truncate_start_to_width(
+                &dir_display,
+                (content.width as usize).saturating_sub(display_width(&filter_display)),
+            ),
+            Style::default()
+                .fg(palette.accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(filter_display, Style::default().fg(palette.text)),
+    ]));
+
+    let entries = browser.entries();
+    let header_lines = lines.len() as u16;
+    let footer_lines = if state.terminal_caps.supports_drag_drop {
+        2u16
+    } else {
+        1u16
+    };
+    let max_visible = content
+        .height
+        .saturating_sub(header_lines + footer_lines + 1) as usize;
+    let start = if max_visible > 0 && browser.selected >= max_visible {
+        browser.selected - max_visible + 1
+    } else {
+        0
+    };
+    let end = (start + max_visible).min(entries.len());
+
+    if entries.is_empty() {
+        let empty = if browser.filter().is_empty() {
+            "  (empty directory)"
+        } else {
+            "  (no folders match; esc clears the filter)"
         };
-        frame.render_widget(Paragraph::new(info_line), info_area);
+        lines.push(Line::from(Span::styled(
+            empty,
+            Style::default().fg(palette.text_muted),
+        )));
+    } else {
+        for (i, entry) in entries[start..end].iter().enumerate() {
+            let actual_idx = start + i;
+            let is_selected = actual_idx == browser.selected;
+            let marker = if is_selected { ">" } else { " " };
+            let icon = if entry.is_dir { "/" } else { "" };
+            let style = if is_selected {
+                Style::default()
+                    .fg(palette.text)
+                    .bg(palette.selection_bg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(palette.text)
+            };
+            let count = match entry.audio_files {
+                None => "…".to_string(),
+                Some(count) if count.files == 0 && count.complete => "no audio".to_string(),
+                Some(count) => {
+                    let more = if count.complete { "" } else { "+" };
+                    format!("{}{more} audio", count.files)
+                }
+            };
+            // The count sits at the right edge, at least a space from the name.
+            let count_width = display_width(&count);
+            let name_width =
+                (content.width as usize).saturating_sub(5 + display_width(icon) + count_width);
+            let name = format!(
+                "  {marker} {}{icon}",
+                truncate_to_width(&entry.name, name_width)
+            );
+            let gap = (content.width as usize).saturating_sub(display_width(&name) + count_width);
+            let count_style = if is_selected {
+                style.remove_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(palette.text_muted)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{name}{}", " ".repeat(gap)), style),
+                Span::styled(count, count_style),
+            ]));
+        }
+    }
+
+    let used = lines.len() as u16;
+    let target = content.height.saturating_sub(footer_lines);
+    if used < target {
+        for _ in 0..(target - used) {
+            lines.push(Line::from(""));
+        }
+    }
+
+    let esc_label = if is_welcome { "esc skip" } else { "esc cancel" };
+    let footer_row = content.y + lines.len() as u16;
+    const LOAD_BUTTON: &str = "[ Load ]";
+    let load_button = if footer_row < content.bottom() {
+        Rect::new(content.x + 2, footer_row, LOAD_BUTTON.len() as u16, 1)
+    } else {
+        Rect::default()
+    };
+    let mut load_style = Style::default()
+        .fg(palette.accent)
+        .add_modifier(Modifier::BOLD);
+    if state.hovered(load_button) {
+        load_style = load_style.add_modifier(Modifier::REVERSED);
+    }
+    lines.push(Line::from(vec![
+        Span::raw("  "),
+        Span::styled(LOAD_BUTTON, load_style),
+        Span::styled(
+            format!("  space add  enter open  backspace up  type to filter  . hidden  b bookmark  1-9 jump  r recent  / type a path  {esc_label}"),
+            Style::default().fg(palette.text_muted),
+        ),
+    ]));
+
+    if state.terminal_caps.supports_drag_drop {
+        lines.push(Line::from(Span::styled(
+            "  drag folders here to add",
+            Style::default().fg(palette.text_muted),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines);
+    frame.render_widget(paragraph, content);
+
+    Some(FileBrowserAreas {
+        dialog: area,
+        path_input: Rect::new(content.x, path_input_row, content.width, 1),
+        entries: Rect::new(
+            content.x,
+            content.y + header_lines,
+            content.width,
+            (end - start) as u16,
+        ),
+        first_entry: start,
+        load_button,
+    })
+}
+
+fn home_dir() -> Option<std::path::PathBuf> {
+    std::env::var_os("HOME").map(std::path::PathBuf::from)
+}
+
+/// `path` with the home folder shortened to `~`.
+fn tilde_path(path: &str) -> String {
+    match home_dir().map(|home| home.display().to_string()) {
+        Some(home) if !home.is_empty() && path.starts_with(&home) => {
+            format!("~{}", &path[home.len()..])
+        }
+        _ => path.to_string(),
+    }
+}
+
+fn pane_block<'a>(title: &'a str, focused: bool, palette: &Palette) -> Block<'a> {
+    let border_style = if focused {
+        Style::default().fg(palette.border_focused)
+    } else {
+        Style::default().fg(palette.border_unfocused)
+    };
+    let title_style = if focused {
+        Style::default()
+            .fg(palette.border_focused)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(palette.text_muted)
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(format!(" {title} "), title_style))
+        .border_style(border_style)
+        .style(Style::default().bg(palette.bg_panel()).fg(palette.text))
+}
+
+/// Render a borderless section header: bold title in accent color + dim rule line.
+fn render_section_header(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    focused: bool,
+    palette: &Palette,
+) {
+    if area.width == 0 || area.height == 0 {
+        return;
+    }
+    let title_style = if focused {
+        Style::default()
+            .fg(palette.accent)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+            .fg(palette.accent)
+            .add_modifier(Modifier::BOLD)
+            .add_modifier(Modifier::DIM)
+    };
+    let title_len = display_width(title) as u16;
+    let rule_width = area.width.saturating_sub(title_len + 1);
+    let rule: String = "─".repeat(rule_width as usize);
+    let line = Line::from(vec![
+        Span::styled(title, title_style),
+        Span::raw(" "),
+        Span::styled(rule, Style::default().fg(palette.border_unfocused)),
+    ]);
+    let header_area = Rect {
+        x: area.x,
+        y: area.y,
+        width: area.width,
+        height: 1,
+    };
+    frame.render_widget(Paragraph::new(line), header_area);
+}
+
+/// Get the content area below a section header, with 1-cell left padding.
+fn borderless_content_area(area: Rect) -> Rect {
+    if area.height <= 1 || area.width <= 1 {
+        return Rect::new(area.x, area.y, 0, 0);
+    }
+    Rect {
+        x: area.x + 1,
+        y: area.y + 1,
+        width: area.width.saturating_sub(1),
+        height: area.height.saturating_sub(1),
+    }
+}
+
+/// Draw a dim vertical separator line filling the given column.
+fn render_vertical_separator(frame: &mut Frame, area: Rect, palette: &Palette) {
+    for row in area.y..area.y + area.height {
+        frame.buffer_mut().set_string(
+            area.x,
+            row,
+            "│",
+            Style::default().fg(palette.border_unfocused),
+        );
+    }
+}
+
+fn library_panel_inner_areas(area: Rect) -> (Rect, Rect) {
+    let inner = inner_rect(area);
+    if inner.width == 0 || inner.height == 0 {
+        return (
+            Rect::new(inner.x, inner.y, 0, 0),
+            Rect::new(inner.x, inner.y, 0, 0),
+        );
+    }
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+    (sections[0], sections[1])
+}
+
+/// Fits `text` into a column `width` cells wide, leaving one cell of gutter.
+fn pad_cell(text: &str, width: usize) -> String {
+    let truncated = truncate_to_width(text, width.saturating_sub(1));
+    pad_to_width(&truncated, width)
+}
+
+/// Library totals for the track list's border, e.g.
+/// `3 folders · 12,034 tracks · 41 days`.
+fn library_summary(snapshot: &ShellSnapshot) -> String {
+    let plural = |n: usize, one: &str, many: &str| {
+        format!("{} {}", thousands(n), if n == 1 { one } else { many })
+    };
+    let minutes = snapshot.total_duration_ms / 60_000;
+    let length = match (minutes / (24 * 60), minutes / 60) {
+        (0, 0) => format!("{minutes} min"),
+        (0, hours) => plural(hours as usize, "hour", "hours"),
+        (days, _) => plural(days as usize, "day", "days"),
+    };
+    format!(
+        "{} · {} · {length}",
+        plural(snapshot.roots.len(), "folder", "folders"),
+        plural(snapshot.total_track_count, "track", "tracks"),
+    )
+}
+
+/// `12034` as `12,034`.
+pub(crate) fn thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn format_duration_short(duration_ms: Option<i64>) -> String {
+    let Some(ms) = duration_ms else {
+        return "--:--".to_string();
+    };
+    if ms <= 0 {
+        return "--:--".to_string();
+    }
+    let total_secs = ms / 1000;
+    let minutes = total_secs / 60;
+    let seconds = total_secs % 60;
+    format!("{minutes:02}:{seconds:02}")
+}
+
+fn format_tech_compact(
+    sample_rate: Option<i64>,
+    bit_depth: Option<i64>,
+    channels: Option<i64>,
+) -> String {
+    let sr = sample_rate.unwrap_or_default();
+    let bd = bit_depth.unwrap_or_default();
+    let ch = channels.unwrap_or_default();
+    let sr_khz = sr / 1000;
+    let sr_rem = (sr % 1000) / 100;
+    let sr_str = if sr_khz > 0 && sr_rem > 0 {
+        format!("{sr_khz}.{sr_rem}k")
+    } else if sr_khz > 0 {
+        format!("{sr_khz}k")
+    } else {
+        format!("{sr}Hz")
+    };
+    if bd > 0 {
+        format!("{bd}b/{sr_str} {ch}ch")
+    } else {
+        format!("{sr_str} {ch}ch")
+    }
+}
 
-        // Spectrum visualizer: fills remaining height below the three fixed rows
-        let viz_top = text_area.y + 3;
-        let viz_bottom = text_area.y + text_area.height;
-        if is_playing
-            && !state.spectrum_bands.is_empty()
-            && viz_bottom > viz_top
-            && text_area.width >= 4
-        {
-            let viz_area = Rect {
-                x: text_area.x,
-                y: viz_top,
-                width: text_area.width,
-                height: viz_bottom - viz_top,
+fn track_matches_query(track: &ShellTrackItem, query: &str) -> bool {
+    track.title.to_lowercase().contains(query)
+        || track.artist.to_lowercase().contains(query)
+        || track.album.to_lowercase().contains(query)
+        || track.path.to_lowercase().contains(query)
+}
+
+/// Splits a `bpm:120-130` term out of a track filter, returning the rest of
+/// the query and the range. `bpm:120` matches that tempo, and either end of a
+/// range may be left open (`bpm:-90`, `bpm:140-`).
+fn split_bpm_range(query: &str) -> (String, Option<std::ops::RangeInclusive<u16>>) {
+    let mut range = None;
+    let mut rest = Vec::new();
+    for word in query.split_whitespace() {
+        let parsed = word.strip_prefix("bpm:").and_then(|spec| {
+            let (low, high) = spec.split_once('-').unwrap_or((spec, spec));
+            let bound = |text: &str, open: u16| {
+                if text.is_empty() {
+                    Some(open)
+                } else {
+                    text.parse::<u16>().ok()
+                }
             };
-            frame.render_widget(
-                crate::visualizer::VisualizerWidget {
-                    style: state.viz_style,
-                    bands: &state.spectrum_bands,
-                    samples: &state.viz_samples,
-                    palette,
-                    frame_count: state.viz_frame,
-                    fire_history: &state.fire_history,
-                },
-                viz_area,
-            );
-            state.viz_area = viz_area;
+            Some(bound(low, 0)?..=bound(high, u16::MAX)?)
+        });
+        match parsed {
+            Some(parsed) => range = Some(parsed),
+            None => rest.push(word),
         }
+    }
+    match range {
+        Some(range) => (rest.join(" "), Some(range)),
+        None => (query.to_string(), None),
+    }
+}
 
-        // Render album artwork on the left
-        if show_art {
-            let art_area = Rect {
-                x: content_area.x,
-                y: content_area.y,
-                width: art_width,
-                height: content_area.height,
-            };
-            if let Some(protocol) = &mut state.artwork.current_image {
-                frame.render_stateful_widget(
-                    ratatui_image::StatefulImage::default(),
-                    art_area,
-                    protocol,
-                );
+fn normalize_scroll(offset: usize, selected: usize, len: usize, visible_items: usize) -> usize {
+    if len == 0 || visible_items == 0 {
+        return 0;
+    }
+    let max_offset = len.saturating_sub(visible_items);
+    let mut offset = offset.min(max_offset);
+    let selected = selected.min(len.saturating_sub(1));
+    if selected < offset {
+        offset = selected;
+    } else if selected >= offset.saturating_add(visible_items) {
+        offset = selected.saturating_add(1).saturating_sub(visible_items);
+    }
+    offset.min(max_offset)
+}
+
+fn try_refresh_snapshot(state: &mut ShellState, refresh: &mut Option<&mut RefreshSnapshotFn<'_>>) {
+    if let Some(refresh_fn) = refresh.as_mut() {
+        let started = Instant::now();
+        let refreshed = (*refresh_fn)();
+        state.debug_stats.refresh.record(started.elapsed());
+        match refreshed {
+            Ok(snapshot) => {
+                let total_tracks = snapshot.total_track_count;
+                state.replace_snapshot(snapshot);
+                state.status_message = Some(format!("Library refreshed ({total_tracks} tracks)"));
             }
+            Err(err) => state.report_error(format!("Refresh failed: {err}")),
         }
     } else {
-        // Reset seek bar area when no track is playing
-        state.seek_bar_area = Rect::default();
+        state.status_message = Some("Refresh not available in this shell mode".to_string());
+    }
+}
 
-        let mut lines = Vec::new();
-        if let Some(track) = state.selected_track_item() {
-            lines.push(Line::from(Span::styled(
-                format!("{} - {}", track.title, track.artist),
-                Style::default().fg(palette.text_muted),
-            )));
-            lines.push(Line::from(Span::styled(
-                "Press Enter to play",
-                Style::default().fg(palette.text_muted),
-            )));
-        } else {
-            lines.push(Line::from(Span::styled(
-                "No track selected",
-                Style::default().fg(palette.text_muted),
-            )));
-        }
-        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: true });
-        frame.render_widget(paragraph, content_area);
+fn shift_index(current: usize, len: usize, delta: isize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let current = current.min(len - 1) as isize;
+    let max = (len - 1) as isize;
+    (current + delta).clamp(0, max) as usize
+}
+
+fn inner_rect(area: Rect) -> Rect {
+    if area.width <= 2 || area.height <= 2 {
+        return Rect::new(area.x, area.y, 0, 0);
+    }
+    Rect::new(area.x + 1, area.y + 1, area.width - 2, area.height - 2)
+}
+
+fn padded_inner(area: Rect) -> Rect {
+    let inner = inner_rect(area);
+    Rect {
+        x: inner.x.saturating_add(1),
+        y: inner.y,
+        width: inner.width.saturating_sub(2),
+        height: inner.height,
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+    let horizontal = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1]);
+    horizontal[1]
+}
+
+#[derive(Debug, Clone, Copy)]
+enum IconToken {
+    Folder,
+    Playlist,
+    Track,
+}
+
+fn icon_glyph(mode: IconMode, token: IconToken) -> &'static str {
+    match (mode, token) {
+        (IconMode::NerdFont, IconToken::Folder) => "󰉋",
+        (IconMode::NerdFont, IconToken::Playlist) => "󰲹",
+        (IconMode::NerdFont, IconToken::Track) => "󰎆",
+        (IconMode::Ascii, IconToken::Folder) => "[D]",
+        (IconMode::Ascii, IconToken::Playlist) => "[P]",
+        (IconMode::Ascii, IconToken::Track) => "[*]",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Palette;
+
+    fn sample_state() -> ShellState {
+        ShellState::new(ShellSnapshot {
+            app_title: "Auric".into(),
+            theme_name: "auric-dark".into(),
+            color_scheme: "dark".into(),
+            icon_mode: IconMode::Ascii,
+            icon_fallback: "ascii".into(),
+            preferred_terminal_font: "FiraCode Nerd Font Mono".into(),
+            mouse_enabled: true,
+            artwork_filter: "none".into(),
+            show_artwork: true,
+            pixel_art_enabled: false,
+            pixel_art_cell_size: 2,
+            roots: vec![ShellListItem {
+                id: "r1".into(),
+                label: "/music".into(),
+                detail: Some("watched".into()),
+            }],
+            root_options: vec![ShellRootOptions {
+                recursive: true,
+                include_extensions: String::new(),
+                exclude_extensions: String::new(),
+                auto_artwork: false,
+                paused: false,
+            }],
+            playlists: vec![ShellListItem {
+                id: "p1".into(),
+                label: "Favorites".into(),
+                detail: None,
+            }],
+            tracks: vec![ShellTrackItem {
+                id: "t1".into(),
+                title: "Track One".into(),
+                artist: "Artist".into(),
+                album: "Album".into(),
+                path: "/music/Artist/Album/01.flac".into(),
+                duration_ms: Some(123_000),
+                sample_rate: Some(48_000),
+                channels: Some(2),
+                bit_depth: Some(24),
+                bpm: None,
+            }],
+            podcasts: vec![ShellPodcast {
+                id: "pc1".into(),
+                title: "Tracks & Tales".into(),
+                detail: "2 episodes, 1 unplayed".into(),
+                episodes: vec![
+                    ShellEpisode {
+                        id: "e2".into(),
+                        title: "Episode 2".into(),
+                        detail: "2026-10-01  1:02:03".into(),
+                        position_ms: 65_000,
+                        downloaded: true,
+                        played: false,
+                    },
+                    ShellEpisode {
+                        id: "e1".into(),
+                        title: "Episode 1".into(),
+                        detail: "2026-09-24  58:10".into(),
+                        position_ms: 0,
+                        downloaded: false,
+                        played: true,
+                    },
+                ],
+            }],
+            listening_stats: ShellListeningStats {
+                days: vec![
+                    ("2026-10-17".into(), 95 * 60_000),
+                    ("2026-10-18".into(), 42 * 60_000),
+                ],
+                weeks: vec![("2026-10-12".into(), 137 * 60_000)],
+                top_artists: vec![("Massive Attack".into(), 31)],
+                top_albums: vec![("Mezzanine — Massive Attack".into(), 18)],
+                top_tracks: vec![("Teardrop — Massive Attack".into(), 9)],
+                plays: 1_204,
+                listened_ms: 200 * 60_000,
+                formats: vec![("FLAC".into(), 2)],
+                added_by_month: vec![("2026-09".into(), 1), ("2026-10".into(), 1)],
+            },
+            health: ShellHealthReport {
+                issues: vec![
+                    ShellHealthIssue {
+                        label: "Missing tags".into(),
+                        count: 1,
+                        needs_check: true,
+                        fix: Some((
+                            "Fill missing tags from file names".into(),
+                            "health fix tags".into(),
+                        )),
+                        tracks: vec![ShellHealthTrack {
+                            path: "/music/Artist/Album/01.flac".into(),
+                            title: "Artist — Track One".into(),
+                            detail: "no artist, album".into(),
+                        }],
+                    },
+                    ShellHealthIssue {
+                        label: "Zero duration".into(),
+                        count: 0,
+                        needs_check: false,
+                        fix: None,
+                        tracks: Vec::new(),
+                    },
+                ],
+                checked: "2026-10-18".into(),
+            },
+            feature_summary: vec![
+                ("metadata".into(), true),
+                ("visualizer".into(), false),
+                ("mouse".into(), true),
+            ],
+            status_lines: vec!["ready".into()],
+            playback_status: "stopped".to_string(),
+            now_playing_path: String::new(),
+            now_playing_title: String::new(),
+            now_playing_artist: String::new(),
+            now_playing_album: String::new(),
+            now_playing_artwork: None,
+            now_playing_duration_ms: 0,
+            now_playing_position_ms: 0,
+            now_playing_remembers_position: false,
+            volume: 1.0,
+            volume_offset_db: 0.0,
+            shuffle: false,
+            random_album: false,
+            radio: false,
+            consume: false,
+            party_mode: false,
+            repeat_mode: "off".to_string(),
+            queue_length: 0,
+            up_next: Vec::new(),
+            now_playing_chapters: Vec::new(),
+            queue_position: 0,
+            artists: vec!["Artist".to_string()],
+            albums: vec![("Album".to_string(), "Artist".to_string())],
+            recent_tracks: Vec::new(),
+            total_track_count: 1,
+            total_duration_ms: 0,
+            split_source: "queue".to_string(),
+            split_title: "Queue".to_string(),
+            split_tracks: Vec::new(),
+            setting_use_theme_bg: false,
+            setting_icon_pack: "nerd-font".to_string(),
+            setting_pixel_art: false,
+            setting_pixel_art_cell_size: 2,
+            setting_color_scheme: "dark".to_string(),
+            setting_color_mode: "auto".to_string(),
+            setting_unicode_level: "full".to_string(),
+            setting_screen_reader: "off".to_string(),
+            setting_volume_step: 5,
+            setting_folder_playlists: false,
+            setting_resume_min_minutes: 20,
+            setting_shuffle_strategy: "random".to_string(),
+            setting_shuffle_rating_weight: 5,
+            setting_shuffle_recency_weight: 5,
+            setting_normalization: "track".to_string(),
+            setting_normalization_target_lufs: -18,
+            setting_trim_silence: true,
+            setting_silence_threshold_db: -60,
+            setting_silence_min_ms: 1_000,
+            setting_crossfeed: false,
+            setting_max_volume: 100,
+            setting_limiter: true,
+            setting_footer_top: footer::DEFAULT_TOP.to_string(),
+            setting_footer_bottom: footer::DEFAULT_BOTTOM.to_string(),
+            utc_offset_minutes: 0,
+            db_timings: Vec::new(),
+            available_themes: vec!["auric-dark".to_string()],
+            panel_layout: PanelLayout::default(),
+            browser_places: Default::default(),
+        })
+    }
+
+    #[test]
+    fn renders_shell_snapshot_to_text() {
+        let mut state = sample_state();
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("Library Roots"));
+        assert!(text.contains("Library"));
+        assert!(text.contains("Favorites"));
+        assert!(text.contains("Track One"));
+    }
+
+    #[test]
+    fn key_navigation_moves_selection() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        state.snapshot.tracks.push(ShellTrackItem {
+            id: "t2".into(),
+            title: "Track Two".into(),
+            artist: "Artist".into(),
+            album: "Album".into(),
+            path: "x".into(),
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+            bpm: None,
+        });
+        state.rebuild_track_filter();
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(state.selected_track, 1);
     }
-}
 
-fn format_ms(ms: u64) -> String {
-    let total_secs = ms / 1000;
-    let minutes = total_secs / 60;
-    let seconds = total_secs % 60;
-    format!("{minutes:02}:{seconds:02}")
-}
+    #[test]
+    fn chords_jump_to_start_and_switch_focus() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        state.snapshot.tracks.push(ShellTrackItem {
+            id: "t2".into(),
+            title: "Track Two".into(),
+            artist: "Artist".into(),
+            album: "Album".into(),
+            path: "x".into(),
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+            bpm: None,
+        });
+        state.rebuild_track_filter();
+        state.selected_track = 1;
+
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE));
+        assert_eq!(state.selected_track, 1);
+        assert_eq!(state.status_message.as_deref(), Some("g …"));
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE));
+        assert_eq!(state.selected_track, 0);
 
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE));
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        assert_eq!(state.focus, FocusPane::Inspector);
 
-fn render_status(frame: &mut Frame, area: Rect, state: &ShellState, palette: &Palette) {
-    if area.height == 0 || area.width == 0 {
-        return;
+        // A broken chord falls through to the single-key binding.
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE));
+        let action = state.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+        assert!(matches!(action, KeyAction::Quit));
     }
 
-    // Dim top separator line
-    let rule: String = "─".repeat(area.width as usize);
-    frame.buffer_mut().set_string(
-        area.x,
-        area.y,
-        &rule,
-        Style::default().fg(palette.border_unfocused),
-    );
+    #[test]
+    fn playing_row_is_highlighted_across_the_whole_row() {
+        let mut state = sample_state();
+        let mut second = state.snapshot.tracks[0].clone();
+        second.title = "Track Two".into();
+        second.path = "/music/two.flac".into();
+        state.snapshot.tracks.push(second);
+        state.snapshot.now_playing_path = "/music/two.flac".into();
+        state.rebuild_track_filter();
+        let palette = Palette {
+            use_terminal_bg: false,
+            ..Palette::default()
+        };
 
-    let content_area = Rect {
-        x: area.x + 1,
-        y: area.y + 1,
-        width: area.width.saturating_sub(2),
-        height: area.height.saturating_sub(1),
-    };
-    if content_area.width == 0 || content_area.height == 0 {
-        return;
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+        let mut areas = RenderAreas::default();
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        let buffer = terminal.backend().buffer();
+        let rows = areas.tracks.inner;
+        let row_of = |title: &str| {
+            (rows.y..rows.bottom())
+                .find(|&y| {
+                    (rows.x..rows.right())
+                        .map(|x| buffer[(x, y)].symbol())
+                        .collect::<String>()
+                        .contains(title)
+                })
+                .expect("row drawn")
+        };
+        let playing = row_of("Track Two");
+        assert_eq!(
+            buffer[(rows.right() - 1, playing)].bg,
+            palette.playing_row_bg
+        );
+        assert_ne!(
+            buffer[(rows.right() - 1, row_of("Track One"))].bg,
+            palette.playing_row_bg
+        );
     }
 
-    // Line 1: playback status + track info + badges
-    let filter_info = if state.track_filter_query.is_empty() {
-        String::new()
-    } else {
-        format!("  filter: /{}", state.track_filter_query)
-    };
+    #[test]
+    fn long_titles_scroll_on_the_selected_row_only() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        state.snapshot.tracks[0].title =
+            "Symphony No. 9 in D minor, Op. 125: IV. Presto – Allegro assai".into();
+        state.rebuild_track_filter();
+        let alt = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::ALT))
+        };
+        for _ in 0..3 {
+            alt(&mut state, 'l');
+        }
+        assert_eq!(state.title_offset(), 12);
+        let text = render_once_to_text(&mut state, &Palette::default(), 110, 30).unwrap();
+        assert!(text.contains("… 9 in D mino"), "{text}");
+        alt(&mut state, 'h');
+        assert_eq!(state.title_offset(), 8);
+
+        state.selected_track = 1;
+        assert_eq!(state.title_offset(), 0);
+    }
 
-    let mut line1_spans = vec![
-        Span::styled(
-            state.snapshot.app_title.as_str(),
-            Style::default().fg(palette.text).add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            format!("  {} tracks{filter_info}", state.snapshot.total_track_count),
-            Style::default().fg(palette.text_muted),
-        ),
-    ];
-    if state.scanning_path.is_some() {
-        line1_spans.push(Span::styled(
-            "  [scanning...]",
-            Style::default().fg(palette.warning).add_modifier(Modifier::BOLD),
-        ));
+    #[test]
+    fn track_list_title_shows_the_view_search_sort_and_totals() {
+        let mut state = sample_state();
+        state.snapshot.total_track_count = 12_034;
+        state.snapshot.total_duration_ms = 41 * 24 * 3_600_000 + 5_000_000;
+        state.browse_filter_artist = Some("Artist".to_string());
+        state.track_filter_query.insert_str("one");
+        state.rebuild_track_filter();
+        state.sort_ascending = false;
+        assert_eq!(
+            state.library_breadcrumb(),
+            "Library › Artist (1/12,034) · /one · Title ▼"
+        );
+        assert_eq!(
+            library_summary(&state.snapshot),
+            "1 folder · 12,034 tracks · 41 days"
+        );
+        let text = render_once_to_text(&mut state, &Palette::default(), 140, 30).unwrap();
+        assert!(
+            text.contains("1 folder · 12,034 tracks · 41 days"),
+            "{text}"
+        );
+
+        state.snapshot.total_duration_ms = 90 * 60_000;
+        assert!(library_summary(&state.snapshot).ends_with(" · 1 hour"));
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1_000_000), "1,000,000");
     }
 
-    // Badges on the right side of line 1
-    let hint = "?: help  ,: settings";
-    let hint_width = hint.len() as u16;
+    #[test]
+    fn footer_hints_follow_the_focused_pane_and_prompt() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        assert_eq!(
+            state.fitting_hints(200).concat(),
+            "Enter: play  /: filter  o: sort  i: info  y: copy path  D: trash  ?: help"
+        );
+        assert_eq!(
+            state.fitting_hints(31).concat(),
+            "Enter: play  /: filter  ?: help"
+        );
 
-    let line1_area = Rect {
-        x: content_area.x,
-        y: content_area.y,
-        width: content_area.width,
-        height: 1,
-    };
-    frame.render_widget(Paragraph::new(Line::from(line1_spans)), line1_area);
+        state.focus = FocusPane::Inspector;
+        let text = render_once_to_text(&mut state, &Palette::default(), 140, 30).unwrap();
+        assert!(text.contains("Enter: play  u: undo delete"), "{text}");
 
-    if content_area.width > hint_width + 2 {
-        let hint_area = Rect {
-            x: content_area.x + content_area.width - hint_width,
-            y: content_area.y,
-            width: hint_width,
-            height: 1,
+        state.ask_to_confirm("Delete?".to_string(), "trash x".to_string());
+        assert!(state
+            .fitting_hints(80)
+            .concat()
+            .starts_with("y: confirm  any other key: cancel"));
+        state.snapshot.party_mode = true;
+        state.input_mode = InputMode::Normal;
+        assert_eq!(
+            state.fitting_hints(80).concat(),
+            "Enter: add to queue  q: quit  ?: help"
+        );
+    }
+
+    #[test]
+    fn split_view_takes_focus_and_sends_tracks_across() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        let key = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
         };
-        frame.render_widget(
-            Paragraph::new(Span::styled(hint, Style::default().fg(palette.text_muted))),
-            hint_area,
+        assert_eq!(key(&mut state, KeyCode::Char('>')), KeyAction::Continue);
+
+        key(&mut state, KeyCode::Char('z'));
+        key(&mut state, KeyCode::Char('q'));
+        assert!(state.panel_layout.show_split);
+        assert_eq!(
+            key(&mut state, KeyCode::Char('>')),
+            KeyAction::CommandSubmitted("__split_add /music/Artist/Album/01.flac".to_string())
+        );
+
+        let mut snapshot = state.snapshot.clone();
+        snapshot.split_tracks = snapshot.tracks.clone();
+        snapshot.queue_position = 1;
+        state.replace_snapshot(snapshot);
+        key(&mut state, KeyCode::Tab);
+        assert_eq!(state.focus, FocusPane::Split);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("Queue (1)"), "{text}");
+        assert_eq!(
+            key(&mut state, KeyCode::Char('<')),
+            KeyAction::CommandSubmitted("__split_remove 0".to_string())
+        );
+        assert_eq!(
+            key(&mut state, KeyCode::Char('l')),
+            KeyAction::CommandSubmitted("__split_source p1".to_string())
+        );
+        assert_eq!(
+            key(&mut state, KeyCode::Char('h')),
+            KeyAction::CommandSubmitted("__split_source p1".to_string())
         );
     }
 
-    // Line 2: contextual help hints
-    if content_area.height > 1 {
-        let status_msg = state.status_message.as_deref().unwrap_or(default_status_message());
-        let line2_area = Rect {
-            x: content_area.x,
-            y: content_area.y + 1,
-            width: content_area.width,
-            height: 1,
+    #[test]
+    fn dragging_a_split_row_shows_where_it_lands_and_moves_it_on_release() {
+        let mut state = sample_state();
+        state.panel_layout.show_split = true;
+        let mut snapshot = state.snapshot.clone();
+        snapshot.split_tracks = ["One", "Two", "Three"]
+            .into_iter()
+            .map(|title| ShellTrackItem {
+                title: title.into(),
+                ..snapshot.tracks[0].clone()
+            })
+            .collect();
+        state.replace_snapshot(snapshot);
+        let palette = Palette::default();
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
         };
-        frame.render_widget(
-            Paragraph::new(Span::styled(
-                status_msg,
-                Style::default().fg(if state.scanning_path.is_some() {
-                    palette.accent
-                } else {
-                    palette.text_muted
-                }),
-            )),
-            line2_area,
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).expect("terminal");
+        let mut areas = RenderAreas::default();
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        let rows = areas.split.inner;
+        let x = rows.x + 3;
+        let row_text = |terminal: &Terminal<TestBackend>, y: u16| {
+            let buffer = terminal.backend().buffer();
+            (rows.x..rows.right())
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        };
+
+        state.handle_mouse(
+            mouse(MouseEventKind::Down(MouseButton::Left), x, rows.y),
+            &areas,
+        );
+        assert_eq!(state.focus, FocusPane::Split);
+        state.handle_mouse(
+            mouse(MouseEventKind::Drag(MouseButton::Left), x, rows.y + 2),
+            &areas,
+        );
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        assert!(row_text(&terminal, rows.y).contains("One"));
+        assert!(row_text(&terminal, rows.y + 2).contains("Three"));
+        assert!(row_text(&terminal, rows.y + 3)
+            .trim_start()
+            .starts_with("───"));
+
+        // Over the insertion line itself the landing row stays put.
+        state.handle_mouse(
+            mouse(MouseEventKind::Drag(MouseButton::Left), x, rows.y + 3),
+            &areas,
+        );
+        assert_eq!(
+            state.handle_mouse(
+                mouse(MouseEventKind::Up(MouseButton::Left), x, rows.y + 3),
+                &areas
+            ),
+            KeyAction::CommandSubmitted("__split_move 0 2".to_string())
+        );
+        assert_eq!(state.selected_split, 2);
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        assert!(!row_text(&terminal, rows.y + 3).contains('─'));
+
+        // Dropping a row back where it was moves nothing; with the line
+        // drawn above the first row, the dragged row shows one lower.
+        state.handle_mouse(
+            mouse(MouseEventKind::Down(MouseButton::Left), x, rows.y + 1),
+            &areas,
+        );
+        state.handle_mouse(
+            mouse(MouseEventKind::Drag(MouseButton::Left), x, rows.y),
+            &areas,
+        );
+        assert_eq!(state.selected_split, 0);
+        state.handle_mouse(
+            mouse(MouseEventKind::Drag(MouseButton::Left), x, rows.y + 2),
+            &areas,
+        );
+        assert_eq!(
+            state.handle_mouse(
+                mouse(MouseEventKind::Up(MouseButton::Left), x, rows.y + 2),
+                &areas
+            ),
+            KeyAction::Continue
         );
     }
-}
 
-fn render_track_info_overlay(frame: &mut Frame, state: &mut ShellState, palette: &Palette) {
-    let track = match state.selected_track_item() {
-        Some(t) => t.clone(),
-        None => return,
-    };
+    #[test]
+    fn tabs_keep_their_own_filter_sort_and_selection() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        state.snapshot.tracks.push(ShellTrackItem {
+            id: "t2".into(),
+            title: "Track Two".into(),
+            artist: "Other".into(),
+            album: "Second".into(),
+            path: "x".into(),
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+            bpm: None,
+        });
+        state.rebuild_track_filter();
+        let key = |state: &mut ShellState, ch, modifiers| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), modifiers))
+        };
 
-    let frame_area = frame.area();
-    let width = (frame_area.width * 70 / 100).max(50).min(frame_area.width.saturating_sub(4));
-    let height = (frame_area.height * 70 / 100).max(16).min(frame_area.height.saturating_sub(4));
-    let x = frame_area.x + (frame_area.width.saturating_sub(width)) / 2;
-    let y = frame_area.y + (frame_area.height.saturating_sub(height)) / 2;
-    let area = Rect::new(x, y, width, height);
+        // A lone tab draws no bar and Alt-2 has nowhere to go.
+        key(&mut state, '2', KeyModifiers::ALT);
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("No tab 2; t n opens one")
+        );
+        key(&mut state, 't', KeyModifiers::NONE);
+        key(&mut state, 'n', KeyModifiers::NONE);
+        assert_eq!((state.tab_count(), state.active_tab), (2, 1));
 
-    frame.render_widget(Clear, area);
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .title(Span::styled(
-            " Track Info ",
-            Style::default().fg(palette.border_focused).add_modifier(Modifier::BOLD),
-        ))
-        .border_style(Style::default().fg(palette.border_focused))
-        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
-    let inner = block.inner(area);
-    frame.render_widget(block, area);
+        state.track_filter_query.insert_str("two");
+        state.rebuild_track_filter();
+        key(&mut state, 'o', KeyModifiers::NONE);
+        assert_eq!(state.filtered_track_indices, [1]);
 
-    let content = Rect {
-        x: inner.x.saturating_add(1),
-        y: inner.y,
-        width: inner.width.saturating_sub(2),
-        height: inner.height,
-    };
+        key(&mut state, '1', KeyModifiers::ALT);
+        assert_eq!(state.active_tab, 0);
+        assert!(state.track_filter_query.is_empty());
+        assert_eq!(
+            (state.sort_column, state.sort_ascending),
+            (SortColumn::Title, true)
+        );
+        assert_eq!(state.filtered_track_indices.len(), 2);
+        state.selected_track = 1;
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.starts_with(" 1 Songs   2 /two\n"), "{text}");
+
+        key(&mut state, 't', KeyModifiers::NONE);
+        key(&mut state, 't', KeyModifiers::NONE);
+        assert_eq!(state.track_filter_query.as_str(), "two");
+        assert!(!state.sort_ascending);
+        key(&mut state, 't', KeyModifiers::NONE);
+        key(&mut state, 'x', KeyModifiers::NONE);
+        assert_eq!(
+            (state.tab_count(), state.active_tab, state.selected_track),
+            (1, 0, 1)
+        );
+        assert!(state.track_filter_query.is_empty());
+    }
 
-    if content.height < 4 || content.width < 20 {
-        return;
+    #[test]
+    fn bpm_filter_and_column_use_the_analyzed_tempo() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        state.snapshot.tracks[0].bpm = Some(124);
+        for (id, title, bpm) in [("t2", "Slow Dance", Some(92)), ("t3", "Unanalyzed", None)] {
+            state.snapshot.tracks.push(ShellTrackItem {
+                id: id.into(),
+                title: title.into(),
+                bpm,
+                ..state.snapshot.tracks[0].clone()
+            });
+        }
+        let titles = |state: &ShellState| {
+            state
+                .filtered_track_iter()
+                .map(|t| t.title.clone())
+                .collect::<Vec<_>>()
+        };
+
+        state.track_filter_query.insert_str("bpm:120-130");
+        state.rebuild_track_filter();
+        assert_eq!(titles(&state), ["Track One"]);
+        state.track_filter_query = TextInput::new();
+        state.track_filter_query.insert_str("dance bpm:-100");
+        state.rebuild_track_filter();
+        assert_eq!(titles(&state), ["Slow Dance"]);
+        state.track_filter_query = TextInput::new();
+        state.rebuild_track_filter();
+
+        state.set_sort_column(SortColumn::Bpm);
+        assert_eq!(titles(&state), ["Unanalyzed", "Slow Dance", "Track One"]);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("BPM ▲") && text.contains("92"), "{text}");
     }
 
-    // Split: artwork on left, metadata on right
-    let has_art = state.track_info_artwork.is_some();
-    let art_width = if has_art {
-        content.height.saturating_mul(2).min(content.width / 3)
-    } else {
-        0
-    };
-    let art_area = Rect {
-        x: content.x,
-        y: content.y,
-        width: art_width,
-        height: content.height.saturating_sub(2), // leave room for footer
-    };
-    let meta_area = Rect {
-        x: content.x + art_width + if has_art { 1 } else { 0 },
-        y: content.y,
-        width: content.width.saturating_sub(art_width + if has_art { 1 } else { 0 }),
-        height: content.height,
-    };
+    #[test]
+    fn track_filter_mode_filters_tracks() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        state.snapshot.tracks.push(ShellTrackItem {
+            id: "t2".into(),
+            title: "Night Drive".into(),
+            artist: "Auric".into(),
+            album: "Nocturne".into(),
+            path: "/music/Auric/Nocturne/02.flac".into(),
+            duration_ms: None,
+            sample_rate: None,
+            channels: None,
+            bit_depth: None,
+            bpm: None,
+        });
+        state.rebuild_track_filter();
+
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE)),
+            KeyAction::Continue
+        );
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE));
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE));
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE));
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
 
-    // Render artwork
-    if has_art {
-        state.track_info_art_state.update(
-            &track.path,
-            state.track_info_artwork.as_deref(),
-            false,
-            2,
+        assert_eq!(state.filtered_track_count(), 1);
+        assert_eq!(
+            state.selected_track_item().map(|t| t.title.as_str()),
+            Some("Night Drive")
         );
-        if let Some(protocol) = &mut state.track_info_art_state.current_image {
-            frame.render_stateful_widget(
-                ratatui_image::StatefulImage::default(),
-                art_area,
-                protocol,
-            );
-        }
+        assert!(state
+            .status_message
+            .as_deref()
+            .unwrap_or_default()
+            .contains("Track filter"));
     }
 
-    // Render metadata
-    let duration = track.duration_ms.map(|ms| {
-        let secs = ms / 1000;
-        format!("{}:{:02}", secs / 60, secs % 60)
-    }).unwrap_or_else(|| "--:--".to_string());
-
-    let sample_rate = track.sample_rate.map(|sr| {
-        let khz = sr / 1000;
-        let rem = (sr % 1000) / 100;
-        if rem > 0 { format!("{khz}.{rem} kHz") } else { format!("{khz} kHz") }
-    }).unwrap_or_else(|| "-".to_string());
-    let channels = track.channels.map(|ch| {
-        match ch { 1 => "Mono".to_string(), 2 => "Stereo".to_string(), n => format!("{n}ch") }
-    }).unwrap_or_else(|| "-".to_string());
-    let bit_depth = track.bit_depth.filter(|&bd| bd > 0).map(|bd| format!("{bd}-bit")).unwrap_or_else(|| "Lossy".to_string());
-
-    let label_style = Style::default().fg(palette.text_muted);
-    let value_style = Style::default().fg(palette.text);
-    let title_style = Style::default().fg(palette.text).add_modifier(Modifier::BOLD);
-
-    let mut lines = vec![
-        Line::from(""),
-        Line::from(Span::styled(&track.title, title_style)),
-        Line::from(Span::styled(&track.artist, Style::default().fg(palette.accent))),
-        Line::from(Span::styled(&track.album, value_style)),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("Duration     ", label_style),
-            Span::styled(duration, value_style),
-        ]),
-        Line::from(vec![
-            Span::styled("Sample Rate  ", label_style),
-            Span::styled(sample_rate, value_style),
-        ]),
-        Line::from(vec![
-            Span::styled("Channels     ", label_style),
-            Span::styled(channels, value_style),
-        ]),
-        Line::from(vec![
-            Span::styled("Bit Depth    ", label_style),
-            Span::styled(bit_depth, value_style),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("Path  ", label_style),
-            Span::styled(&track.path, Style::default().fg(palette.text_muted)),
-        ]),
-    ];
+    #[test]
+    fn mouse_click_selects_track_row_with_scroll() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        for i in 0..8 {
+            state.snapshot.tracks.push(ShellTrackItem {
+                id: format!("t{}", i + 2),
+                title: format!("Track {}", i + 2),
+                artist: "Artist".into(),
+                album: "Album".into(),
+                path: format!("/music/{i}.flac"),
+                duration_ms: None,
+                sample_rate: None,
+                channels: None,
+                bit_depth: None,
+                bpm: None,
+            });
+        }
+        state.rebuild_track_filter();
+        state.selected_track = 5;
+        let areas = RenderAreas {
+            roots: PaneArea::bordered(Rect::new(0, 0, 20, 8), 1),
+            browse: Rect::new(0, 16, 20, 8),
+            browse_items: None,
+            playlists: PaneArea::bordered(Rect::new(0, 8, 20, 8), 1),
+            tracks: PaneArea::bordered(Rect::new(20, 0, 40, 8), 1),
+            split: PaneArea::default(),
+            track_header: Rect::new(20, 0, 40, 1),
+            track_col_offsets: TrackColumnOffsets::default(),
+            file_browser: None,
+        };
+        state.sync_scroll_offsets(&areas);
 
-    // Pad and add footer
-    let used = lines.len() as u16;
-    let remaining = meta_area.height.saturating_sub(used + 1);
-    for _ in 0..remaining {
-        lines.push(Line::from(""));
-    }
-    lines.push(Line::from(Span::styled(
-        "Esc or i to close",
-        Style::default().fg(palette.text_muted),
-    )));
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(crossterm::event::MouseButton::Left),
+            column: areas.tracks.inner.x + 1,
+            row: areas.tracks.inner.y,
+            modifiers: KeyModifiers::NONE,
+        };
+        state.handle_mouse(click, &areas);
 
-    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: true });
-    frame.render_widget(paragraph, meta_area);
-}
+        assert_eq!(state.focus, FocusPane::Tracks);
+        assert_eq!(state.selected_track, state.tracks_scroll);
 
-fn render_settings_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
-    let settings: Vec<(&str, String)> = vec![
-        ("Theme", state.snapshot.theme_name.clone()),
-        ("Use Theme Background", format!("{}", state.snapshot.setting_use_theme_bg)),
-        ("Icon Pack", state.snapshot.setting_icon_pack.clone()),
-        ("Pixel Art Artwork", format!("{}", state.snapshot.setting_pixel_art)),
-        ("Pixel Art Cell Size", format!("{}", state.snapshot.setting_pixel_art_cell_size)),
-        ("Color Scheme", state.snapshot.setting_color_scheme.clone()),
-    ];
+        // A quick second click on the same row plays it, even at another column.
+        let again = MouseEvent {
+            column: click.column + 5,
+            ..click
+        };
+        assert_eq!(
+            state.handle_mouse(again, &areas),
+            KeyAction::Playback(PlaybackAction::PlayTrack {
+                track_index: state.selected_track
+            })
+        );
 
-    let mut lines: Vec<Line> = Vec::new();
-    lines.push(Line::from(""));
+        // A click long after the previous one only selects.
+        state.handle_mouse(click, &areas);
+        let stale = Instant::now().checked_sub(Duration::from_secs(5));
+        if let (Some(stale), Some(last)) = (stale, state.last_click.as_mut()) {
+            last.0 = stale;
+            assert_eq!(state.handle_mouse(click, &areas), KeyAction::Continue);
+        }
 
-    for (i, (label, value)) in settings.iter().enumerate() {
-        let is_selected = i == state.settings_index;
-        let marker = if is_selected { " > " } else { "   " };
-        let style = if is_selected {
-            Style::default().fg(palette.text).bg(palette.selection_bg).add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(palette.text)
-        };
-        let value_style = if is_selected {
-            Style::default().fg(palette.accent).bg(palette.selection_bg).add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(palette.accent)
+        // Clicking a different row is not a double-click either.
+        let below = MouseEvent {
+            row: click.row + 1,
+            ..click
         };
-
-        let restart_note = if i == 0 || i == 5 { "  (restart to apply)" } else { "" };
-        lines.push(Line::from(vec![
-            Span::styled(marker, style),
-            Span::styled(format!("{label:<25}"), style),
-            Span::styled(value.as_str(), value_style),
-            Span::styled(restart_note, Style::default().fg(palette.text_muted)),
-        ]));
+        assert_eq!(state.handle_mouse(below, &areas), KeyAction::Continue);
     }
 
-    lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(
-        "   Enter/Space: change   Esc: close",
-        Style::default().fg(palette.text_muted),
-    )));
-
-    crate::modal::render_modal(frame, "Settings", lines, 55, 45, palette);
-}
+    #[test]
+    fn file_browser_bookmarks_and_recent_folders_jump_back() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let nas = dir.path().join("nas");
+        std::fs::create_dir_all(nas.join("flac").join("jazz")).expect("mkdir");
+        let mut state = sample_state();
+        state.file_browser = Some(crate::file_browser::FileBrowser::new(&nas));
+        state.input_mode = InputMode::AddMusic;
+        let press = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
+        };
+        let current = |state: &ShellState| {
+            state
+                .file_browser
+                .as_ref()
+                .expect("browser")
+                .current_dir()
+                .to_path_buf()
+        };
 
-fn render_help_overlay(frame: &mut Frame, palette: &Palette) {
-    let area = centered_rect(65, 60, frame.area());
-    frame.render_widget(Clear, area);
-    let lines = vec![
-        Line::from(Span::styled(
-            "Auric Keyboard Shortcuts",
-            Style::default().add_modifier(Modifier::BOLD),
-        )),
-        Line::from("Tab / Shift-Tab: switch pane focus"),
-        Line::from("Enter: play selected track"),
-        Line::from("Space: play/pause"),
-        Line::from("n / N: next / previous track"),
-        Line::from("+ / -: volume up / down"),
-        Line::from("s: toggle shuffle"),
-        Line::from("o: cycle sort column (click header to sort)"),
-        Line::from("a: add music folder"),
-        Line::from("j/k or arrows: move selection"),
-        Line::from("PgUp/PgDn: page movement"),
-        Line::from("g / G: first / last"),
-        Line::from("/: track filter mode (type to filter, Enter/Esc close)"),
-        Line::from(": or Ctrl-P: command palette"),
-        Line::from("Mouse click: focus pane + select row"),
-        Line::from("Mouse wheel: scroll selected pane"),
-        Line::from("q or Ctrl-C: quit"),
-        Line::from("r: refresh library"),
-        Line::from("i: track info"),
-        Line::from("v: cycle visualizer style (or click visualizer)"),
-        Line::from(",: settings"),
-        Line::from("?: toggle this help"),
-    ];
-    let paragraph = Paragraph::new(lines)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Help")
-                .border_style(Style::default().fg(palette.focus))
-                .style(Style::default().bg(palette.bg_panel()).fg(palette.text)),
-        )
-        .wrap(Wrap { trim: true });
-    frame.render_widget(paragraph, area);
-}
+        let KeyAction::CommandSubmitted(save) = press(&mut state, 'b') else {
+            panic!("bookmarking saves the places");
+        };
+        assert!(save.starts_with("__places_save {"));
+        assert!(save.contains(&nas.display().to_string()));
+        state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(current(&state), nas.join("flac"));
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("Bookmarks: 1 "));
+        press(&mut state, '1');
+        assert_eq!(current(&state), nas);
+        press(&mut state, '2');
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("No bookmark 2; b bookmarks this folder")
+        );
 
-fn render_command_palette_overlay(frame: &mut Frame, state: &ShellState, palette: &Palette) {
-    let frame_area = frame.area();
-    let width = frame_area.width.saturating_sub(8).clamp(24, 88);
-    let x = frame_area.x + frame_area.width.saturating_sub(width) / 2;
-    let height = 5u16;
-    let y = frame_area
-        .y
-        .saturating_add(frame_area.height.saturating_sub(height + 2));
-    let area = Rect::new(x, y, width, height);
-    frame.render_widget(Clear, area);
+        // Adding "flac" makes it a recent folder, and the next browser opens
+        // beside it.
+        assert_eq!(
+            press(&mut state, ' '),
+            KeyAction::CommandSubmitted(format!("__add_root {}", nas.join("flac").display()))
+        );
+        state.focus = FocusPane::Tracks;
+        press(&mut state, 'a');
+        assert_eq!(current(&state), nas);
+        state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(current(&state), nas.join("flac").join("jazz"));
+        press(&mut state, 'r');
+        assert_eq!(current(&state), nas.join("flac"));
+    }
 
-    let lines = vec![
-        Line::from(vec![
-            Span::styled(":", Style::default().fg(palette.focus).add_modifier(Modifier::BOLD)),
-            Span::styled(
-                state.command_palette_input.as_str(),
-                Style::default().fg(palette.text),
-            ),
-        ]),
-        Line::from(Span::styled(
-            "Examples: help | refresh | scan roots | feature enable visualizer | root add /path --watched",
-            Style::default().fg(palette.text_muted),
-        )),
-        Line::from(Span::styled(
-            "playlist create <name> | playlist delete <id> | scan path <dir> --prune",
-            Style::default().fg(palette.text_muted),
-        )),
-    ];
-    let paragraph = Paragraph::new(lines)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Command Palette")
-                .border_style(Style::default().fg(palette.focus))
-                .style(Style::default().bg(palette.bg_panel()).fg(palette.text)),
-        )
-        .wrap(Wrap { trim: true });
-    frame.render_widget(paragraph, area);
-}
+    #[test]
+    fn file_browser_takes_typed_paths_with_completion() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("mnt").join("incoming")).expect("mkdir");
+        let mut state = sample_state();
+        state.file_browser = Some(crate::file_browser::FileBrowser::new(dir.path()));
+        state.input_mode = InputMode::AddMusic;
+        let key = |state: &mut ShellState, code, modifiers| {
+            state.handle_key(KeyEvent::new(code, modifiers))
+        };
 
-fn render_add_music_overlay(
-    frame: &mut Frame,
-    state: &ShellState,
-    palette: &Palette,
-    is_welcome: bool,
-) {
-    let frame_area = frame.area();
-    let width = (frame_area.width * 60 / 100).max(40).min(frame_area.width.saturating_sub(4));
-    let height = (frame_area.height * 70 / 100).max(16).min(frame_area.height.saturating_sub(4));
-    let x = frame_area.x + (frame_area.width.saturating_sub(width)) / 2;
-    let y = frame_area.y + (frame_area.height.saturating_sub(height)) / 2;
-    let area = Rect::new(x, y, width, height);
-    frame.render_widget(Clear, area);
+        key(&mut state, KeyCode::Char('l'), KeyModifiers::CONTROL);
+        assert!(state.is_editing_text());
+        for ch in "m".chars() {
+            key(&mut state, KeyCode::Char(ch), KeyModifiers::NONE);
+        }
+        key(&mut state, KeyCode::Tab, KeyModifiers::NONE);
+        key(&mut state, KeyCode::Char('x'), KeyModifiers::NONE);
+        key(&mut state, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(state
+            .status_message
+            .as_deref()
+            .is_some_and(|m| m.starts_with("Not a folder")));
+        key(&mut state, KeyCode::Backspace, KeyModifiers::NONE);
+        key(&mut state, KeyCode::Tab, KeyModifiers::NONE);
+        key(&mut state, KeyCode::Enter, KeyModifiers::NONE);
+        let browser = state.file_browser.as_ref().expect("browser");
+        assert_eq!(
+            browser.current_dir(),
+            dir.path().join("mnt").join("incoming")
+        );
+        assert!(!state.is_editing_text());
+        key(&mut state, KeyCode::Char('/'), KeyModifiers::NONE);
+        assert!(state.is_editing_text());
+    }
 
-    let title = if is_welcome { " Welcome to auric " } else { " Add Music " };
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title(title)
-        .border_style(Style::default().fg(palette.focus))
-        .style(Style::default().bg(palette.bg_panel()).fg(palette.text));
-    let inner = block.inner(area);
-    frame.render_widget(block, area);
+    #[test]
+    fn file_browser_shows_audio_counts_and_hidden_folders() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("rips").join("cd1")).expect("mkdir");
+        std::fs::create_dir(dir.path().join(".stash")).expect("mkdir");
+        for file in ["rips/cd1/01.flac", "rips/cd1/02.flac", "rips/notes.txt"] {
+            std::fs::write(dir.path().join(file), b"").expect("write");
+        }
+        let mut state = sample_state();
+        state.file_browser = Some(crate::file_browser::FileBrowser::new(dir.path()));
+        state.input_mode = InputMode::AddMusic;
+
+        let mut text = String::new();
+        for _ in 0..500 {
+            text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+            if text.contains("2 audio") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert!(text.contains("rips/") && text.contains("2 audio"), "{text}");
+        assert!(!text.contains(".stash"));
+        state.handle_key(KeyEvent::new(KeyCode::Char('.'), KeyModifiers::NONE));
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains(".stash/"));
+    }
 
-    let content = Rect {
-        x: inner.x.saturating_add(1),
-        y: inner.y,
-        width: inner.width.saturating_sub(2),
-        height: inner.height,
-    };
+    #[test]
+    fn typing_in_the_file_browser_filters_folders() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        for name in ["Beatles", "Bjork", "Cake"] {
+            std::fs::create_dir(dir.path().join(name)).expect("mkdir");
+        }
+        let mut state = sample_state();
+        state.file_browser = Some(crate::file_browser::FileBrowser::new(dir.path()));
+        state.input_mode = InputMode::AddMusic;
+        let names = |state: &ShellState| -> Vec<String> {
+            let browser = state.file_browser.as_ref().unwrap();
+            browser.entries().iter().map(|e| e.name.clone()).collect()
+        };
 
-    if content.height < 4 || content.width < 10 {
-        return;
+        // `b` bookmarks, so the filter starts from the capital.
+        state.handle_key(KeyEvent::new(KeyCode::Char('B'), KeyModifiers::SHIFT));
+        assert_eq!(names(&state), ["Beatles", "Bjork"]);
+        state.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+        assert_eq!(names(&state), ["Bjork"]);
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("filter: Bj"), "{text}");
+
+        state.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        assert_eq!(names(&state), ["Beatles", "Bjork"]);
+        state.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(names(&state).len(), 3);
+        assert_eq!(state.input_mode, InputMode::AddMusic);
+        state.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(state.file_browser.is_none());
     }
 
-    let browser = match &state.file_browser {
-        Some(b) => b,
-        None => return,
-    };
+    #[test]
+    fn album_rows_ask_for_covers_and_show_thumbnails() {
+        let mut state = sample_state();
+        state.browse.mode_index = crate::browse::BrowseMode::all()
+            .iter()
+            .position(|mode| *mode == crate::browse::BrowseMode::Albums)
+            .unwrap();
+        state.apply_browse_mode();
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(!text.contains('▀'));
+        assert_eq!(state.album_thumbnails.take_wanted(8), ["Album"]);
+
+        let cover = image::RgbImage::from_pixel(4, 4, image::Rgb([200, 40, 40]));
+        let mut png = std::io::Cursor::new(Vec::new());
+        cover.write_to(&mut png, image::ImageFormat::Png).unwrap();
+        state
+            .album_thumbnails
+            .insert("Album".into(), Some(png.get_ref()));
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("▀▀ Album"), "{text}");
 
-    let mut lines: Vec<Line> = Vec::new();
+        // A coverless album gets its generated placeholder colours.
+        state.album_thumbnails.insert("Album".into(), None);
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("▀▀ Album"), "{text}");
 
-    if is_welcome {
-        lines.push(Line::from(Span::styled(
-            "Add a folder to get started.",
-            Style::default().fg(palette.text_muted),
-        )));
-        lines.push(Line::from(""));
+        state.snapshot.show_artwork = false;
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(!text.contains('▀'));
     }
 
-    let input_style = if browser.input_focused {
-        Style::default().fg(palette.text).add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(palette.text_muted)
-    };
-    lines.push(Line::from(vec![
-        Span::styled("Path: ", Style::default().fg(palette.text_muted)),
-        Span::styled(&browser.path_input, input_style),
-        if browser.input_focused {
-            Span::styled("_", Style::default().fg(palette.focus).add_modifier(Modifier::SLOW_BLINK))
-        } else {
-            Span::raw("")
-        },
-    ]));
-    lines.push(Line::from(""));
+    #[test]
+    fn file_browser_overlay_handles_clicks_and_load_button() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("alpha")).expect("mkdir");
+        std::fs::create_dir(dir.path().join("beta")).expect("mkdir");
+        std::fs::create_dir(dir.path().join("beta").join("inner")).expect("mkdir");
 
-    let dir_display = browser
-        .current_dir()
-        .display()
-        .to_string()
-        .replace(
-            &home_dir()
-                .map(|h| h.display().to_string())
-                .unwrap_or_default(),
-            "~",
-        );
-    lines.push(Line::from(Span::styled(
-        format!("{dir_display}/"),
-        Style::default().fg(palette.accent).add_modifier(Modifier::BOLD),
-    )));
+        let mut state = sample_state();
+        state.file_browser = Some(crate::file_browser::FileBrowser::new(dir.path()));
+        state.input_mode = InputMode::AddMusic;
+
+        let draw = |state: &mut ShellState| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+            let mut areas = RenderAreas::default();
+            terminal
+                .draw(|f| areas = draw_shell(f, state, &Palette::default()))
+                .expect("draw");
+            areas
+        };
+        let click = |column: u16, row: u16| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
 
-    let entries = browser.entries();
-    let header_lines = lines.len() as u16;
-    let footer_lines = if state.terminal_caps.supports_drag_drop { 2u16 } else { 1u16 };
-    let max_visible = content.height.saturating_sub(header_lines + footer_lines + 1) as usize;
-    let start = if max_visible > 0 && browser.selected >= max_visible {
-        browser.selected - max_visible + 1
-    } else {
-        0
-    };
-    let end = (start + max_visible).min(entries.len());
+        let areas = draw(&mut state);
+        let dialog = areas.file_browser.expect("dialog areas");
+        assert_eq!(dialog.entries.height, 2);
+
+        // Single click on "beta" selects it; a second quick click enters it.
+        let beta = click(dialog.entries.x + 4, dialog.entries.y + 1);
+        assert_eq!(state.handle_mouse(beta, &areas), KeyAction::Continue);
+        let browser = state.file_browser.as_ref().expect("browser");
+        assert_eq!(browser.selected, 1);
+        assert_eq!(browser.current_dir(), dir.path());
+        state.handle_mouse(beta, &areas);
+        let browser = state.file_browser.as_ref().expect("browser");
+        assert_eq!(browser.current_dir(), dir.path().join("beta"));
+
+        let areas = draw(&mut state);
+        let dialog = areas.file_browser.expect("dialog areas");
+        let load = click(dialog.load_button.x, dialog.load_button.y);
+        let expected = dir.path().join("beta").join("inner");
+        assert_eq!(
+            state.handle_mouse(load, &areas),
+            KeyAction::CommandSubmitted(format!("__add_root {}", expected.display()))
+        );
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert!(state.file_browser.is_none());
+    }
 
-    if entries.is_empty() {
-        lines.push(Line::from(Span::styled(
-            "  (empty directory)",
-            Style::default().fg(palette.text_muted),
-        )));
-    } else {
-        for (i, entry) in entries[start..end].iter().enumerate() {
-            let actual_idx = start + i;
-            let is_selected = actual_idx == browser.selected;
-            let marker = if is_selected { ">" } else { " " };
-            let icon = if entry.is_dir { "/" } else { "" };
-            let style = if is_selected {
-                Style::default().fg(palette.text).bg(palette.selection_bg).add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(palette.text)
-            };
-            lines.push(Line::from(Span::styled(
-                format!("  {marker} {}{icon}", entry.name),
-                style,
-            )));
+    #[test]
+    fn command_palette_submits_command() {
+        let mut state = sample_state();
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE)),
+            KeyAction::Continue
+        );
+        for ch in ['h', 'e', 'l', 'p'] {
+            let _ = state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
         }
+        let action = state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(action, KeyAction::CommandSubmitted("help".to_string()));
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert!(state.command_palette_input.is_empty());
     }
 
-    let used = lines.len() as u16;
-    let target = content.height.saturating_sub(footer_lines);
-    if used < target {
-        for _ in 0..(target - used) {
-            lines.push(Line::from(""));
+    #[test]
+    fn recent_list_replays_and_requeues_tracks() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Browse;
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        for _ in 0..3 {
+            press(&mut state, KeyCode::Down);
         }
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.status_message.as_deref(), Some("Nothing played yet"));
+
+        let mut snapshot = state.snapshot.clone();
+        snapshot.recent_tracks = snapshot.tracks.clone();
+        state.replace_snapshot(snapshot);
+        assert_eq!(state.browse.items, ["Track One · Artist"]);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("Recently Played"), "{text}");
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("__play_now /music/Artist/Album/01.flac".into())
+        );
+        assert_eq!(
+            press(&mut state, KeyCode::Char('a')),
+            KeyAction::Playback(PlaybackAction::Enqueue {
+                path: "/music/Artist/Album/01.flac".into(),
+                requested_by: "keyboard".into(),
+            })
+        );
+        assert!(state
+            .fitting_hints(80)
+            .concat()
+            .starts_with("Enter: play now  a: queue"));
     }
 
-    let esc_label = if is_welcome { "esc skip" } else { "esc cancel" };
-    lines.push(Line::from(Span::styled(
-        format!("  space add  enter open  backspace up  tab path input  {esc_label}"),
-        Style::default().fg(palette.text_muted),
-    )));
+    #[test]
+    fn whole_album_and_artist_keys_send_the_selected_track() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        let key = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
+        };
+        assert_eq!(
+            key(&mut state, 'e'),
+            KeyAction::CommandSubmitted("__whole album play /music/Artist/Album/01.flac".into())
+        );
+        assert_eq!(
+            key(&mut state, 'W'),
+            KeyAction::CommandSubmitted("__whole artist queue /music/Artist/Album/01.flac".into())
+        );
+        state.snapshot.party_mode = true;
+        assert_eq!(key(&mut state, 'E'), KeyAction::Continue);
+    }
 
-    if state.terminal_caps.supports_drag_drop {
-        lines.push(Line::from(Span::styled(
-            "  drag folders here to add",
-            Style::default().fg(palette.text_muted),
-        )));
+    #[test]
+    fn party_mode_enqueues_on_enter_and_asks_before_quitting() {
+        let mut state = sample_state();
+        state.snapshot.party_mode = true;
+        state.focus = FocusPane::Tracks;
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            KeyAction::Playback(PlaybackAction::Enqueue {
+                path: "/music/Artist/Album/01.flac".to_string(),
+                requested_by: "keyboard".to_string(),
+            })
+        );
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)),
+            KeyAction::Continue
+        );
+
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)),
+            KeyAction::Continue
+        );
+        assert_eq!(state.input_mode, InputMode::PartyUnlock);
+        for ch in ['o', 'k'] {
+            let _ = state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        assert!(!state.status_message.as_deref().unwrap_or("").contains("ok"));
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            KeyAction::CommandSubmitted("__party_quit ok".to_string())
+        );
+        assert_eq!(state.input_mode, InputMode::Normal);
     }
 
-    let paragraph = Paragraph::new(lines);
-    frame.render_widget(paragraph, content);
-}
+    #[test]
+    fn media_keys_control_playback_from_any_mode() {
+        let mut state = sample_state();
+        let media = |code| KeyEvent::new(KeyCode::Media(code), KeyModifiers::NONE);
+        assert_eq!(
+            state.handle_key(media(MediaKeyCode::PlayPause)),
+            KeyAction::Playback(PlaybackAction::TogglePause)
+        );
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE));
+        assert_eq!(state.input_mode, InputMode::CommandPalette);
+        assert_eq!(
+            state.handle_key(media(MediaKeyCode::TrackNext)),
+            KeyAction::Playback(PlaybackAction::Next)
+        );
+        assert_eq!(state.input_mode, InputMode::CommandPalette);
+        assert_eq!(
+            state.handle_key(media(MediaKeyCode::TrackPrevious)),
+            KeyAction::Playback(PlaybackAction::Previous)
+        );
+        assert_eq!(
+            state.handle_key(media(MediaKeyCode::Record)),
+            KeyAction::Continue
+        );
+    }
 
-fn home_dir() -> Option<std::path::PathBuf> {
-    std::env::var_os("HOME").map(std::path::PathBuf::from)
-}
+    #[test]
+    fn number_keys_seek_to_a_tenth_of_the_track() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
+        };
+        state.playback_status = "stopped".to_string();
+        assert_eq!(press(&mut state, '5'), KeyAction::Continue);
 
-fn pane_block<'a>(title: &'a str, focused: bool, palette: &Palette) -> Block<'a> {
-    let border_style = if focused {
-        Style::default().fg(palette.border_focused)
-    } else {
-        Style::default().fg(palette.border_unfocused)
-    };
-    let title_style = if focused {
-        Style::default().fg(palette.border_focused).add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(palette.text_muted)
-    };
-    Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .title(Span::styled(format!(" {title} "), title_style))
-        .border_style(border_style)
-        .style(Style::default().bg(palette.bg_panel()).fg(palette.text))
-}
+        state.playback_status = "playing".to_string();
+        state.playback_duration_ms = 200_000;
+        state.playback_position_ms = 10_000;
+        assert_eq!(
+            press(&mut state, '3'),
+            KeyAction::Playback(PlaybackAction::Seek {
+                position_ms: 60_000
+            })
+        );
+        assert_eq!(state.playback_position_ms, 60_000);
+        assert_eq!(
+            press(&mut state, '0'),
+            KeyAction::Playback(PlaybackAction::Seek { position_ms: 0 })
+        );
 
-/// Render a borderless section header: bold title in accent color + dim rule line.
-fn render_section_header(
-    frame: &mut Frame,
-    area: Rect,
-    title: &str,
-    focused: bool,
-    palette: &Palette,
-) {
-    if area.width == 0 || area.height == 0 {
-        return;
+        state.playback_duration_ms = 0;
+        assert_eq!(press(&mut state, '9'), KeyAction::Continue);
+        assert!(state
+            .status_message
+            .as_deref()
+            .unwrap_or("")
+            .contains("length unknown"));
     }
-    let title_style = if focused {
-        Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)
-    } else {
-        Style::default()
-            .fg(palette.accent)
-            .add_modifier(Modifier::BOLD)
-            .add_modifier(Modifier::DIM)
-    };
-    let title_len = title.chars().count() as u16;
-    let rule_width = area.width.saturating_sub(title_len + 1);
-    let rule: String = "─".repeat(rule_width as usize);
-    let line = Line::from(vec![
-        Span::styled(title, title_style),
-        Span::raw(" "),
-        Span::styled(rule, Style::default().fg(palette.border_unfocused)),
-    ]);
-    let header_area = Rect {
-        x: area.x,
-        y: area.y,
-        width: area.width,
-        height: 1,
-    };
-    frame.render_widget(Paragraph::new(line), header_area);
-}
 
-/// Get the content area below a section header, with 1-cell left padding.
-fn borderless_content_area(area: Rect) -> Rect {
-    if area.height <= 1 || area.width <= 1 {
-        return Rect::new(area.x, area.y, 0, 0);
-    }
-    Rect {
-        x: area.x + 1,
-        y: area.y + 1,
-        width: area.width.saturating_sub(1),
-        height: area.height.saturating_sub(1),
-    }
-}
+    #[test]
+    fn chapter_keys_jump_between_cue_marks() {
+        let mut state = sample_state();
+        let seek = |position_ms| KeyAction::Playback(PlaybackAction::Seek { position_ms });
+        let press = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
+        };
+        state.playback_status = "playing".to_string();
+        assert_eq!(press(&mut state, ')'), KeyAction::Continue);
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("No chapters in this track")
+        );
 
-/// Draw a dim vertical separator line filling the given column.
-fn render_vertical_separator(frame: &mut Frame, area: Rect, palette: &Palette) {
-    for row in area.y..area.y + area.height {
-        frame.buffer_mut().set_string(
-            area.x,
-            row,
-            "│",
-            Style::default().fg(palette.border_unfocused),
+        state.snapshot.now_playing_chapters = vec![
+            (0, "Intro".into()),
+            (60_000, String::new()),
+            (120_000, "Finale".into()),
+        ];
+        state.playback_duration_ms = 180_000;
+        state.playback_position_ms = 61_000;
+        assert_eq!(press(&mut state, ')'), seek(120_000));
+        assert_eq!(state.status_message.as_deref(), Some("Chapter 3: Finale"));
+        assert_eq!(press(&mut state, ')'), KeyAction::Continue);
+        assert_eq!(state.status_message.as_deref(), Some("Last chapter"));
+        // Well into a chapter, back restarts it; near its start, back goes
+        // to the one before.
+        state.playback_position_ms = 90_000;
+        assert_eq!(press(&mut state, '('), seek(60_000));
+        assert_eq!(state.status_message.as_deref(), Some("Chapter 2"));
+        assert_eq!(press(&mut state, '('), seek(0));
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char(']'), KeyModifiers::CONTROL)),
+            seek(60_000)
         );
+
+        state.snapshot.now_playing_title = "Mix".into();
+        state.playback_position_ms = 30_000;
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).expect("render");
+        let bar = state.seek_bar_area;
+        let row = text.lines().nth(bar.y as usize).unwrap_or_default();
+        assert_eq!(row.matches('┃').count(), 2, "{row}");
     }
-}
 
-fn library_panel_inner_areas(area: Rect) -> (Rect, Rect) {
-    let inner = inner_rect(area);
-    if inner.width == 0 || inner.height == 0 {
-        return (
-            Rect::new(inner.x, inner.y, 0, 0),
-            Rect::new(inner.x, inner.y, 0, 0),
+    #[test]
+    fn new_playlist_row_names_and_selects_a_playlist() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        state.focus = FocusPane::Inspector;
+        state.move_to_end();
+        assert_eq!(state.selected_playlist, 1);
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).expect("render");
+        assert!(text.contains("+ New Playlist"));
+
+        assert_eq!(press(&mut state, KeyCode::Enter), KeyAction::Continue);
+        assert_eq!(state.input_mode, InputMode::NewPlaylist);
+        assert_eq!(press(&mut state, KeyCode::Enter), KeyAction::Continue);
+        for ch in "Road Trip".chars() {
+            press(&mut state, KeyCode::Char(ch));
+        }
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("New playlist name: Road Trip  (Enter create, Esc cancel)")
         );
-    }
-    let sections = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(1), Constraint::Min(0)])
-        .split(inner);
-    (sections[0], sections[1])
-}
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("playlist create Road Trip".into())
+        );
+        assert_eq!(state.input_mode, InputMode::Normal);
 
-fn pad_cell(text: &str, width: usize) -> String {
-    let truncated = truncate_text(text, width.saturating_sub(1));
-    format!("{truncated:<width$}")
-}
+        let mut snapshot = state.snapshot.clone();
+        snapshot.playlists.insert(
+            0,
+            ShellListItem {
+                id: "p2".into(),
+                label: "Road Trip".into(),
+                detail: None,
+            },
+        );
+        state.replace_snapshot(snapshot);
+        assert_eq!(state.selected_playlist, 0);
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("playlist play p2".into())
+        );
 
-fn truncate_text(text: &str, max_chars: usize) -> String {
-    if max_chars == 0 {
-        return String::new();
-    }
-    let count = text.chars().count();
-    if count <= max_chars {
-        return text.to_string();
-    }
-    if max_chars == 1 {
-        return "…".to_string();
+        state.move_to_end();
+        press(&mut state, KeyCode::Enter);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert_eq!(state.status_message.as_deref(), Some("Cancelled"));
     }
-    let mut out = text.chars().take(max_chars - 1).collect::<String>();
-    out.push('…');
-    out
-}
 
-fn format_duration_short(duration_ms: Option<i64>) -> String {
-    let Some(ms) = duration_ms else {
-        return "--:--".to_string();
-    };
-    if ms <= 0 {
-        return "--:--".to_string();
+    #[test]
+    fn copy_keys_emit_path_and_artist_title() {
+        let mut state = sample_state();
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE)),
+            KeyAction::CopyToClipboard("/music/Artist/Album/01.flac".to_string())
+        );
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char('Y'), KeyModifiers::SHIFT)),
+            KeyAction::CopyToClipboard("Artist – Track One".to_string())
+        );
     }
-    let total_secs = ms / 1000;
-    let minutes = total_secs / 60;
-    let seconds = total_secs % 60;
-    format!("{minutes:02}:{seconds:02}")
-}
 
-fn format_tech_compact(
-    sample_rate: Option<i64>,
-    bit_depth: Option<i64>,
-    channels: Option<i64>,
-) -> String {
-    let sr = sample_rate.unwrap_or_default();
-    let bd = bit_depth.unwrap_or_default();
-    let ch = channels.unwrap_or_default();
-    let sr_khz = sr / 1000;
-    let sr_rem = (sr % 1000) / 100;
-    let sr_str = if sr_khz > 0 && sr_rem > 0 {
-        format!("{sr_khz}.{sr_rem}k")
-    } else if sr_khz > 0 {
-        format!("{sr_khz}k")
-    } else {
-        format!("{sr}Hz")
-    };
-    if bd > 0 {
-        format!("{bd}b/{sr_str} {ch}ch")
-    } else {
-        format!("{sr_str} {ch}ch")
+    #[test]
+    fn paste_inserts_first_line_into_active_input() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE));
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL)),
+            KeyAction::PasteFromClipboard
+        );
+        assert!(state.is_editing_text());
+        state.paste_text("track one\nignored");
+        assert_eq!(state.track_filter_query.as_str(), "track one");
+        assert_eq!(state.filtered_track_count(), 1);
     }
-}
 
-fn default_status_message() -> &'static str {
-    "Enter: play  Space: pause  n/N: next/prev  +/-: volume  a: add music  ?: help"
-}
+    #[test]
+    fn layout_keys_resize_hide_panels_and_request_save() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Sources;
+        let action = state.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL));
+        assert_eq!(state.panel_layout.sidebar_percent, 29);
+        assert!(
+            matches!(action, KeyAction::CommandSubmitted(ref c) if c.starts_with("__layout_save {"))
+        );
 
-fn track_matches_query(track: &ShellTrackItem, query: &str) -> bool {
-    track.title.to_lowercase().contains(query)
-        || track.artist.to_lowercase().contains(query)
-        || track.album.to_lowercase().contains(query)
-        || track.path.to_lowercase().contains(query)
-}
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE));
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE));
+        assert!(!state.panel_layout.show_sidebar);
+        assert_eq!(state.focus, FocusPane::Tracks);
+        let _ = state.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(state.focus, FocusPane::Tracks);
 
-fn normalize_scroll(offset: usize, selected: usize, len: usize, visible_items: usize) -> usize {
-    if len == 0 || visible_items == 0 {
-        return 0;
-    }
-    let max_offset = len.saturating_sub(visible_items);
-    let mut offset = offset.min(max_offset);
-    let selected = selected.min(len.saturating_sub(1));
-    if selected < offset {
-        offset = selected;
-    } else if selected >= offset.saturating_add(visible_items) {
-        offset = selected.saturating_add(1).saturating_sub(visible_items);
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(!text.contains("Library Roots"));
+        assert!(text.contains("Track One"));
     }
-    offset.min(max_offset)
-}
 
-fn try_refresh_snapshot(state: &mut ShellState, refresh: &mut Option<&mut RefreshSnapshotFn<'_>>) {
-    if let Some(refresh_fn) = refresh.as_mut() {
-        match (*refresh_fn)() {
-            Ok(snapshot) => {
-                let total_tracks = snapshot.total_track_count;
-                state.replace_snapshot(snapshot);
-                state.status_message = Some(format!(
-                    "Library refreshed ({total_tracks} tracks)"
-                ));
-            }
-            Err(err) => {
-                state.status_message = Some(format!("Refresh failed: {err}"));
-            }
-        }
-    } else {
-        state.status_message = Some("Refresh not available in this shell mode".to_string());
+    #[test]
+    fn enter_on_a_playlist_plays_it_and_built_in_ones_show_their_count() {
+        let mut state = sample_state();
+        state.snapshot.playlists.insert(
+            0,
+            ShellListItem {
+                id: "smart:played-today".into(),
+                label: "Played today".into(),
+                detail: Some("7".into()),
+            },
+        );
+        state.focus = FocusPane::Inspector;
+        state.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+        let action = state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(matches!(action, KeyAction::CommandSubmitted(ref c) if c == "playlist play p1"));
+
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("Played today 7"));
     }
-}
 
+    #[test]
+    fn now_playing_shows_the_queue_and_opens_it_on_click() {
+        let mut state = sample_state();
+        state.snapshot.now_playing_title = "Track One".into();
+        state.snapshot.queue_position = 7;
+        state.snapshot.queue_length = 43;
+        let mut next = state.snapshot.tracks[0].clone();
+        next.title = "Coming Up".into();
+        state.snapshot.up_next = vec![
+            next.clone(),
+            ShellTrackItem {
+                title: "After That".into(),
+                ..next
+            },
+        ];
+        let text = render_once_to_text(&mut state, &Palette::default(), 140, 30).unwrap();
+        assert!(
+            text.contains(
+                "Track 7/43 in queue  ·  Next: Coming Up – Artist, then After That – Artist"
+            ),
+            "{text}"
+        );
 
-fn shift_index(current: usize, len: usize, delta: isize) -> usize {
-    if len == 0 {
-        return 0;
+        let area = state.queue_line_area;
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: area.x + 2,
+            row: area.y,
+            modifiers: KeyModifiers::NONE,
+        };
+        let action = state.handle_mouse(click, &RenderAreas::default());
+        assert_eq!(
+            action,
+            KeyAction::CommandSubmitted("__queue_view".to_string())
+        );
+        assert!(state.panel_layout.show_split);
+        assert_eq!(state.focus, FocusPane::Split);
     }
-    let current = current.min(len - 1) as isize;
-    let max = (len - 1) as isize;
-    (current + delta).clamp(0, max) as usize
-}
 
-fn inner_rect(area: Rect) -> Rect {
-    if area.width <= 2 || area.height <= 2 {
-        return Rect::new(area.x, area.y, 0, 0);
+    #[test]
+    fn mini_mode_renders_bar_and_tracks_only() {
+        let mut state = sample_state();
+        state.snapshot.now_playing_title = "Track One".into();
+        state.snapshot.now_playing_artist = "Artist".into();
+        state.playback_status = "playing".into();
+        state.playback_duration_ms = 123_000;
+        state.focus = FocusPane::Inspector;
+        let action = state.handle_key(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE));
+        assert!(matches!(action, KeyAction::CommandSubmitted(_)));
+        assert!(state.panel_layout.mini);
+        assert_eq!(state.focus, FocusPane::Tracks);
+
+        let text = render_once_to_text(&mut state, &Palette::default(), 60, 12).unwrap();
+        assert!(!text.contains("Library Roots"));
+        assert!(text.contains("vol 100%"));
+        assert_eq!(state.seek_bar_area.y, 1);
     }
-    Rect::new(area.x + 1, area.y + 1, area.width - 2, area.height - 2)
-}
 
-fn padded_inner(area: Rect) -> Rect {
-    let inner = inner_rect(area);
-    Rect {
-        x: inner.x.saturating_add(1),
-        y: inner.y,
-        width: inner.width.saturating_sub(2),
-        height: inner.height,
+    #[test]
+    fn ascii_unicode_level_draws_the_seek_bar_in_plain_characters() {
+        let mut state = sample_state();
+        state.snapshot.now_playing_title = "Track One".into();
+        state.playback_status = "playing".into();
+        state.playback_position_ms = 60_000;
+        state.playback_duration_ms = 120_000;
+        state.panel_layout.mini = true;
+
+        let text = render_once_to_text(&mut state, &Palette::default(), 60, 12).unwrap();
+        assert!(text.contains("━") && text.contains("●"));
+
+        state.snapshot.setting_unicode_level = "ascii".into();
+        let text = render_once_to_text(&mut state, &Palette::default(), 60, 12).unwrap();
+        assert!(!text.contains("━") && !text.contains("●"));
+        assert!(text.contains("####o---"));
     }
-}
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let vertical = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-    let horizontal = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(vertical[1]);
-    horizontal[1]
-}
+    #[test]
+    fn screen_reader_mode_announces_changes_on_the_bottom_line() {
+        let mut state = sample_state();
+        state.snapshot.setting_screen_reader = "announce".into();
+        state.snapshot.now_playing_path = "/music/one.flac".into();
+        state.snapshot.now_playing_title = "Track One".into();
+        state.snapshot.now_playing_artist = "Artist".into();
+        state.playback_status = "playing".into();
+        state.playback_position_ms = 65_000;
+        state.playback_duration_ms = 120_000;
+        state.spectrum_bands = vec![0.5; 16];
 
-#[derive(Debug, Clone, Copy)]
-enum IconToken {
-    Folder,
-    Playlist,
-    Track,
-}
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.is_ascii());
+        assert_eq!(
+            text.lines().last(),
+            Some("Now playing: Track One by Artist")
+        );
+        assert!(text.contains("1:00"));
+        assert_eq!(state.viz_area, Rect::default());
 
-fn icon_glyph(mode: IconMode, token: IconToken) -> &'static str {
-    match (mode, token) {
-        (IconMode::NerdFont, IconToken::Folder) => "󰉋",
-        (IconMode::NerdFont, IconToken::Playlist) => "󰲹",
-        (IconMode::NerdFont, IconToken::Track) => "󰎆",
-        (IconMode::Ascii, IconToken::Folder) => "[D]",
-        (IconMode::Ascii, IconToken::Playlist) => "[P]",
-        (IconMode::Ascii, IconToken::Track) => "[*]",
+        state.playback_status = "paused".into();
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert_eq!(text.lines().last(), Some("Paused"));
+
+        state.snapshot.setting_screen_reader = "on".into();
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(!text.contains("Paused"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::theme::Palette;
+    #[test]
+    fn frames_come_fast_only_while_the_spectrum_plays() {
+        let mut state = sample_state();
+        state.snapshot.now_playing_title = "Track One".into();
+        state.playback_status = "playing".into();
+        state.playback_duration_ms = 120_000;
+        state.spectrum_bands = vec![0.5; 16];
+        render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert_eq!(state.pace(), Pace::Active);
+
+        state.playback_status = "paused".into();
+        render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert_eq!(state.pace(), Pace::Idle);
+
+        state.playback_status = "playing".into();
+        state.panel_layout.show_spectrum = false;
+        render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert_eq!(state.pace(), Pace::Idle);
+
+        state.track_change_time = Some(Instant::now());
+        assert_eq!(state.pace(), Pace::Active);
+    }
 
-    fn sample_state() -> ShellState {
-        ShellState::new(ShellSnapshot {
-            app_title: "Auric".into(),
-            theme_name: "auric-dark".into(),
-            color_scheme: "dark".into(),
-            icon_mode: IconMode::Ascii,
-            icon_fallback: "ascii".into(),
-            preferred_terminal_font: "FiraCode Nerd Font Mono".into(),
-            mouse_enabled: true,
-            artwork_filter: "none".into(),
-            pixel_art_enabled: false,
-            pixel_art_cell_size: 2,
-            roots: vec![ShellListItem {
-                id: "r1".into(),
-                label: "/music".into(),
-                detail: Some("watched".into()),
-            }],
-            playlists: vec![ShellListItem {
-                id: "p1".into(),
-                label: "Favorites".into(),
-                detail: None,
-            }],
-            tracks: vec![ShellTrackItem {
-                id: "t1".into(),
-                title: "Track One".into(),
-                artist: "Artist".into(),
-                album: "Album".into(),
-                path: "/music/Artist/Album/01.flac".into(),
-                duration_ms: Some(123_000),
-                sample_rate: Some(48_000),
-                channels: Some(2),
-                bit_depth: Some(24),
-            }],
-            feature_summary: vec![
-                ("metadata".into(), true),
-                ("visualizer".into(), false),
-                ("mouse".into(), true),
-            ],
-            status_lines: vec!["ready".into()],
-            playback_status: "stopped".to_string(),
-            now_playing_path: String::new(),
-            now_playing_title: String::new(),
-            now_playing_artist: String::new(),
-            now_playing_album: String::new(),
-            now_playing_artwork: None,
-            now_playing_duration_ms: 0,
-            now_playing_position_ms: 0,
-            volume: 1.0,
-            shuffle: false,
-            repeat_mode: "off".to_string(),
-            queue_length: 0,
-            queue_position: 0,
-            artists: vec!["Artist".to_string()],
-            albums: vec![("Album".to_string(), "Artist".to_string())],
-            total_track_count: 1,
-            setting_use_theme_bg: false,
-            setting_icon_pack: "nerd-font".to_string(),
-            setting_pixel_art: false,
-            setting_pixel_art_cell_size: 2,
-            setting_color_scheme: "dark".to_string(),
-            available_themes: vec!["auric-dark".to_string()],
-        })
+    #[test]
+    fn losing_focus_stops_the_spectrum_and_slows_frames() {
+        let mut state = sample_state();
+        state.playback_status = "playing".into();
+        state.track_change_time = Some(Instant::now());
+        state.mouse_hover = Some((3, 4));
+        assert!(state.wants_spectrum());
+
+        state.set_terminal_focus(false);
+        assert_eq!(state.pace(), Pace::Background);
+        assert!(!state.wants_spectrum());
+        assert_eq!(state.mouse_hover, None);
+
+        state.set_terminal_focus(true);
+        assert_eq!(state.pace(), Pace::Active);
+        state.panel_layout.show_spectrum = false;
+        assert!(!state.wants_spectrum());
+    }
+
+    #[test]
+    fn a_track_finishing_in_the_background_advances_within_one_tick() {
+        let mut state = sample_state();
+        state.playback_status = "playing".into();
+        state.set_terminal_focus(false);
+        let frames = FrameScheduler::default();
+        let wait = frames.poll_timeout(state.pace(), Duration::ZERO, Duration::ZERO);
+        assert!(wait <= crate::scheduler::TICK, "{wait:?}");
+        assert!(!frames.frame_due(state.pace(), wait));
+
+        let mut actions = Vec::new();
+        let mut advance = |action| {
+            actions.push(action);
+            Ok(PaletteCommandResult::new("Playing: Track Two", false))
+        };
+        let finished = PlayerEventUpdate {
+            position_ms: 0,
+            duration_ms: 0,
+            status: "stopped".into(),
+            track_finished: true,
+            message: None,
+            spectrum_bands: Vec::new(),
+            raw_samples: Vec::new(),
+        };
+        let redraw = apply_player_updates(
+            &mut state,
+            vec![finished],
+            &mut Some(&mut advance as &mut PlaybackActionFn<'_>),
+            &mut None,
+        );
+        assert!(redraw);
+        assert_eq!(actions, [PlaybackAction::Advance]);
+        assert_eq!(state.status_message.as_deref(), Some("Playing: Track Two"));
+    }
+
+    #[test]
+    fn ascii_mode_draws_nothing_outside_ascii() {
+        let mut state = sample_state();
+        state.snapshot.icon_mode = IconMode::NerdFont;
+        state.snapshot.setting_unicode_level = "auto".into();
+        state.terminal_caps.utf8_locale = false;
+        state.snapshot.now_playing_title = "Track One".into();
+        state.playback_status = "paused".into();
+        state.playback_duration_ms = 120_000;
+        state.browse_filter_artist = Some("Artist".into());
+
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.is_ascii(), "{text}");
+        assert!(text.contains("+-"));
+        assert!(text.contains("Library > Artist"), "{text}");
+
+        state.terminal_caps.utf8_locale = true;
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains('╭') && text.contains("󰉋"));
     }
 
     #[test]
-    fn renders_shell_snapshot_to_text() {
+    fn f12_toggles_the_debug_overlay_in_any_mode() {
         let mut state = sample_state();
-        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
-        assert!(text.contains("Library Roots"));
-        assert!(text.contains("Library"));
-        assert!(text.contains("Favorites"));
-        assert!(text.contains("Track One"));
+        state.snapshot.db_timings = vec![("tracks".to_string(), Duration::from_micros(420))];
+        state.debug_stats.render.record(Duration::from_micros(900));
+        state.handle_key(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        state.handle_key(KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE));
+        assert!(state.show_debug);
+        assert_eq!(state.input_mode, InputMode::Help);
+
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("Debug"));
+        assert!(text.contains("render      900µs avg 900µs"));
+        assert!(text.contains("db tracks   420µs"));
+
+        state.handle_key(KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE));
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(!text.contains("db tracks"));
     }
 
     #[test]
-    fn key_navigation_moves_selection() {
+    fn log_dialog_keeps_errors_after_the_status_line_changes() {
         let mut state = sample_state();
-        state.focus = FocusPane::Tracks;
-        state.snapshot.tracks.push(ShellTrackItem {
-            id: "t2".into(),
-            title: "Track Two".into(),
-            artist: "Artist".into(),
-            album: "Album".into(),
-            path: "x".into(),
-            duration_ms: None,
-            sample_rate: None,
-            channels: None,
-            bit_depth: None,
+        state
+            .event_log
+            .push(LogLevel::Warn, "failed to read tags path=a.flac");
+        state.report_error("Scan failed: permission denied".to_string());
+        state.status_message = Some("Library refreshed".to_string());
+        assert_eq!(state.unseen_log_count(), 2);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("L: 2 new in log"));
+
+        state.handle_key(KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT));
+        assert_eq!(state.input_mode, InputMode::Log);
+        assert_eq!(state.unseen_log_count(), 0);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("WARN   failed to read tags path=a.flac"));
+        assert!(text.contains("ERROR  Scan failed: permission denied"));
+
+        state.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE));
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("No warnings or errors"));
+        state.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(state.input_mode, InputMode::Normal);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(!text.contains("new in log"));
+    }
+
+    #[test]
+    fn footer_lines_follow_the_configured_templates() {
+        let mut state = sample_state();
+        state.snapshot.setting_footer_top = "{tracks} | {queue}{=}{volume} {bogus}".to_string();
+        state.snapshot.setting_footer_bottom = "{=}{status} · {clock}".to_string();
+        state.snapshot.queue_position = 3;
+        state.snapshot.queue_length = 12;
+        state.snapshot.volume = 0.8;
+        state.status_message = Some("Library refreshed".to_string());
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        let lines = text.lines().collect::<Vec<_>>();
+        let (top, bottom) = (lines[lines.len() - 2], lines[lines.len() - 1]);
+        assert!(top.trim_start().starts_with(&format!(
+            "{} tracks | 3/12 in queue",
+            state.snapshot.total_track_count
+        )));
+        assert!(top.trim_end().ends_with("vol 80% {bogus}"));
+        assert!(!top.contains("?: help"));
+        let clock = footer::clock_label(std::time::SystemTime::now(), 0);
+        assert!(bottom
+            .trim_end()
+            .ends_with(&format!("Library refreshed · {clock}")));
+    }
+
+    #[test]
+    fn seek_keys_take_longer_steps_for_tracks_that_resume() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
+        };
+        let seek = |position_ms| KeyAction::Playback(PlaybackAction::Seek { position_ms });
+        assert_eq!(press(&mut state, ']'), KeyAction::Continue);
+
+        state.playback_status = "playing".into();
+        state.playback_position_ms = 60_000;
+        state.playback_duration_ms = 600_000;
+        assert_eq!(press(&mut state, ']'), seek(65_000));
+        assert_eq!(press(&mut state, '{'), seek(35_000));
+
+        state.snapshot.now_playing_remembers_position = true;
+        assert_eq!(press(&mut state, '['), seek(5_000));
+        assert_eq!(press(&mut state, '}'), seek(305_000));
+        assert_eq!(press(&mut state, '}'), seek(599_000));
+        assert_eq!(
+            press(&mut state, 'b'),
+            KeyAction::CommandSubmitted("bookmark add".into())
+        );
+        assert_eq!(
+            press(&mut state, 'u'),
+            KeyAction::CommandSubmitted("undo".into())
+        );
+    }
+
+    #[test]
+    fn trashing_a_track_asks_first() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
+        };
+        assert_eq!(press(&mut state, 'D'), KeyAction::Continue);
+        assert_eq!(state.input_mode, InputMode::Confirm);
+        assert!(state
+            .status_message
+            .as_deref()
+            .is_some_and(|message| message.starts_with("Move \"Track One\" to the trash?")));
+        assert_eq!(press(&mut state, 'n'), KeyAction::Continue);
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert_eq!(state.status_message.as_deref(), Some("Cancelled"));
+
+        press(&mut state, 'D');
+        assert_eq!(
+            press(&mut state, 'y'),
+            KeyAction::CommandSubmitted("trash /music/Artist/Album/01.flac".into())
+        );
+        assert_eq!(state.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn podcasts_dialog_plays_and_downloads_the_selected_episode() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        assert_eq!(
+            press(&mut state, KeyCode::Char('P')),
+            KeyAction::RefreshRequested
+        );
+        assert_eq!(state.input_mode, InputMode::Podcasts);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 30).unwrap();
+        assert!(text.contains("Tracks & Tales"));
+        assert!(text.contains("resume at 01:05"));
+
+        assert_eq!(press(&mut state, KeyCode::Char('d')), KeyAction::Continue);
+        press(&mut state, KeyCode::Enter);
+        press(&mut state, KeyCode::Char('j'));
+        assert_eq!(
+            press(&mut state, KeyCode::Char('d')),
+            KeyAction::CommandSubmitted("podcast download e1".into())
+        );
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("podcast play e1".into())
+        );
+        assert_eq!(state.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn stats_dashboard_charts_listening_and_the_library() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        assert_eq!(
+            press(&mut state, KeyCode::Char('T')),
+            KeyAction::RefreshRequested
+        );
+        assert_eq!(state.input_mode, InputMode::Stats);
+        let text = render_once_to_text(&mut state, &Palette::default(), 140, 44).unwrap();
+        assert!(text.contains("1,204 plays · 3h 20m listened · 2h 17m this week"));
+        assert!(text.contains("Massive Attack 31"));
+        assert!(text.contains("Mezzanine — Massive Attack 18"));
+        assert!(text.contains("Last 14 days (minutes)"));
+        assert!(text.contains("FLAC"));
+        assert!(text.contains("2026-09"));
+
+        assert_eq!(press(&mut state, KeyCode::Char('j')), KeyAction::Continue);
+        assert_eq!(state.input_mode, InputMode::Stats);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn year_in_review_opens_from_stats_and_steps_between_years() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        press(&mut state, KeyCode::Char('T'));
+        assert_eq!(
+            press(&mut state, KeyCode::Char('y')),
+            KeyAction::CommandSubmitted("review".into())
+        );
+
+        state.open_review(ShellReview {
+            range: "2025".into(),
+            title: "2025".into(),
+            previous: Some("2024".into()),
+            next: None,
+            plays: 1_204,
+            listened_ms: 5_000 * 60_000,
+            tracks: 412,
+            artists: 87,
+            top_month: Some(("March 2025".into(), 725 * 60_000)),
+            top_artists: vec![("Massive Attack".into(), 31)],
+            top_albums: Vec::new(),
+            top_tracks: vec![("Teardrop — Massive Attack".into(), 9)],
+            months: vec![
+                ("2025-02".into(), 45 * 60_000),
+                ("2025-03".into(), 725 * 60_000),
+            ],
         });
+        assert_eq!(state.input_mode, InputMode::Review);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 40).unwrap();
+        assert!(text.contains("Your 2025 in music"));
+        assert!(text.contains("83h 20m listened over 1,204 plays"));
+        assert!(text.contains("412 different tracks by 87 artists"));
+        assert!(text.contains("Most listened month: March 2025 (12h 05m)"));
+        assert!(text.contains("Teardrop — Massive Attack 9"));
+        assert!(text.contains("←: 2024"));
+
+        assert_eq!(
+            press(&mut state, KeyCode::Left),
+            KeyAction::CommandSubmitted("review 2024".into())
+        );
+        assert_eq!(press(&mut state, KeyCode::Right), KeyAction::Continue);
+        press(&mut state, KeyCode::Char('e'));
+        assert_eq!(state.input_mode, InputMode::CommandPalette);
+        assert_eq!(
+            state.command_palette_input.as_str(),
+            "review export 2025 auric-review-2025.txt"
+        );
+    }
+
+    #[test]
+    fn health_report_jumps_to_tracks_and_confirms_fixes() {
+        let mut state = sample_state();
+        let mut other = state.snapshot.tracks[0].clone();
+        other.title = "Another".into();
+        other.path = "/music/Artist/Album/00.flac".into();
+        state.snapshot.tracks.insert(0, other);
+        state.browse_filter_artist = Some("Nobody".into());
         state.rebuild_track_filter();
-        let _ = state.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
-        assert_eq!(state.selected_track, 1);
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        assert_eq!(
+            press(&mut state, KeyCode::Char('H')),
+            KeyAction::RefreshRequested
+        );
+        assert_eq!(state.input_mode, InputMode::Health);
+        let text = render_once_to_text(&mut state, &Palette::default(), 120, 36).unwrap();
+        assert!(text.contains("files checked 2026-10-18"));
+        assert!(text.contains("Artist — Track One"));
+        assert!(text.contains("no artist, album · /music/Artist/Album/01.flac"));
+        assert!(text.contains("f: fill missing tags from file names"));
+
+        assert_eq!(
+            press(&mut state, KeyCode::Char('r')),
+            KeyAction::CommandSubmitted("health check".into())
+        );
+        press(&mut state, KeyCode::Char('f'));
+        assert_eq!(state.input_mode, InputMode::Confirm);
+        assert_eq!(
+            press(&mut state, KeyCode::Char('y')),
+            KeyAction::CommandSubmitted("health fix tags".into())
+        );
+
+        press(&mut state, KeyCode::Char('H'));
+        press(&mut state, KeyCode::Char('j'));
+        press(&mut state, KeyCode::Char('f'));
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("Nothing to fix for zero duration")
+        );
+        press(&mut state, KeyCode::Char('k'));
+        press(&mut state, KeyCode::Enter);
+        assert!(state.health_panel.tracks_focused);
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert_eq!(state.focus, FocusPane::Tracks);
+        assert_eq!(state.browse_filter_artist, None);
+        assert_eq!(
+            state.selected_track_item().unwrap().path,
+            "/music/Artist/Album/01.flac"
+        );
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.browse_filter_artist.as_deref(), Some("Nobody"));
     }
 
     #[test]
-    fn track_filter_mode_filters_tracks() {
+    fn escape_walks_back_through_focus_and_browse_steps() {
         let mut state = sample_state();
-        state.focus = FocusPane::Tracks;
-        state.snapshot.tracks.push(ShellTrackItem {
-            id: "t2".into(),
-            title: "Night Drive".into(),
-            artist: "Auric".into(),
-            album: "Nocturne".into(),
-            path: "/music/Auric/Nocturne/02.flac".into(),
-            duration_ms: None,
-            sample_rate: None,
-            channels: None,
-            bit_depth: None,
-        });
+        let mut other = state.snapshot.tracks[0].clone();
+        other.id = "t2".into();
+        other.artist = "Other".into();
+        state.snapshot.tracks.push(other);
+        state.snapshot.artists = vec!["Artist".into(), "Other".into()];
         state.rebuild_track_filter();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+
+        // Tracks -> Inspector -> Sources -> Browse, then Artists -> "Other".
+        press(&mut state, KeyCode::Char('j'));
+        press(&mut state, KeyCode::Tab);
+        press(&mut state, KeyCode::Tab);
+        press(&mut state, KeyCode::Tab);
+        assert_eq!(state.focus, FocusPane::Browse);
+        press(&mut state, KeyCode::Char('j'));
+        press(&mut state, KeyCode::Enter);
+        press(&mut state, KeyCode::Char('j'));
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.browse_filter_artist.as_deref(), Some("Other"));
+        assert_eq!(state.filtered_track_indices, [1]);
+        press(&mut state, KeyCode::Tab);
+        assert_eq!(state.focus, FocusPane::Tracks);
 
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.focus, FocusPane::Browse);
+        assert_eq!(state.browse_filter_artist.as_deref(), Some("Other"));
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.browse_filter_artist, None);
+        assert!(state.browse.show_items);
         assert_eq!(
-            state.handle_key(KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE)),
-            KeyAction::Continue
+            state.browse.item_index, 1,
+            "the artist cursor is remembered"
         );
-        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
-        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE));
-        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE));
-        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE));
-        let _ = state.handle_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
+        press(&mut state, KeyCode::Esc);
+        assert!(!state.browse.show_items);
+        assert_eq!(state.browse.mode, crate::browse::BrowseMode::Artists);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.focus, FocusPane::Sources);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.focus, FocusPane::Inspector);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.focus, FocusPane::Tracks);
+        assert_eq!(state.selected_track, 1, "the track cursor is remembered");
+        // Nothing left to go back to.
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.focus, FocusPane::Tracks);
+    }
 
-        assert_eq!(state.filtered_track_count(), 1);
+    #[test]
+    fn help_dialog_lists_chords_scrolls_and_filters() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        press(&mut state, KeyCode::Char('?'));
+        assert_eq!(state.input_mode, InputMode::Help);
+
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 20).unwrap();
+        assert!(text.contains("Playback"));
+        assert!(text.contains("play / pause"));
+
+        press(&mut state, KeyCode::Char('G'));
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 20).unwrap();
+        assert!(state.help_scroll > 0 && state.help_scroll < usize::MAX);
+        assert!(text.contains("quit"));
+        assert!(!text.contains("play / pause"));
+
+        press(&mut state, KeyCode::Char('/'));
+        for c in "sidebar".chars() {
+            press(&mut state, KeyCode::Char(c));
+        }
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 20).unwrap();
+        assert_eq!(state.help_scroll, 0);
+        assert!(text.contains("z s"));
+        assert!(text.contains("resize sidebar"));
+        assert!(!text.contains("quit"));
+
+        press(&mut state, KeyCode::Esc);
+        assert!(state.help_filter.is_empty());
+        assert_eq!(state.input_mode, InputMode::Help);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn settings_pages_submit_changes_immediately() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        press(&mut state, KeyCode::Char(','));
+        assert_eq!(state.input_mode, InputMode::Settings);
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("Appearance") && text.contains("Features"));
+
+        press(&mut state, KeyCode::Right);
         assert_eq!(
-            state.selected_track_item().map(|t| t.title.as_str()),
-            Some("Night Drive")
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("__setting_set pixel_art_artwork true".into())
         );
+
+        press(&mut state, KeyCode::Char('j'));
+        press(&mut state, KeyCode::Enter);
+        assert!(state.is_editing_text());
+        press(&mut state, KeyCode::Backspace);
+        press(&mut state, KeyCode::Char('9'));
+        assert_eq!(press(&mut state, KeyCode::Enter), KeyAction::Continue);
         assert!(state
             .status_message
             .as_deref()
-            .unwrap_or_default()
-            .contains("Track filter"));
+            .unwrap_or("")
+            .contains("from 1 to 4"));
+
+        press(&mut state, KeyCode::Enter);
+        press(&mut state, KeyCode::Backspace);
+        press(&mut state, KeyCode::Char('3'));
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("__setting_set pixel_art_cell_size 3".into())
+        );
+
+        press(&mut state, KeyCode::Left);
+        press(&mut state, KeyCode::Left);
+        assert_eq!(state.settings_page, 4);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.input_mode, InputMode::Normal);
     }
 
     #[test]
-    fn mouse_click_selects_track_row_with_scroll() {
+    fn queueing_a_root_sends_the_track_list_sort() {
         let mut state = sample_state();
-        state.focus = FocusPane::Tracks;
-        for i in 0..8 {
-            state.snapshot.tracks.push(ShellTrackItem {
-                id: format!("t{}", i + 2),
-                title: format!("Track {}", i + 2),
-                artist: "Artist".into(),
-                album: "Album".into(),
-                path: format!("/music/{i}.flac"),
-                duration_ms: None,
-                sample_rate: None,
-                channels: None,
-                bit_depth: None,
-            });
+        state.focus = FocusPane::Sources;
+        let press = |state: &mut ShellState, ch| {
+            state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
+        };
+        assert_eq!(
+            press(&mut state, 'E'),
+            KeyAction::CommandSubmitted("__queue_folder r1 Title asc".into())
+        );
+        state.cycle_sort();
+        state.cycle_sort();
+        assert_eq!(
+            press(&mut state, 'E'),
+            KeyAction::CommandSubmitted("__queue_folder r1 Artist asc".into())
+        );
+        assert_eq!(SortColumn::from_label("artist"), Some(SortColumn::Artist));
+    }
+
+    #[test]
+    fn folder_properties_edit_the_selected_root() {
+        let mut state = sample_state();
+        let press = |state: &mut ShellState, code| {
+            state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        };
+        state.focus = FocusPane::Sources;
+        press(&mut state, KeyCode::Char('i'));
+        assert_eq!(state.input_mode, InputMode::Settings);
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("Folder Properties") && text.contains("/music"));
+
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("__root_set recursive false /music".into())
+        );
+        press(&mut state, KeyCode::Char('j'));
+        press(&mut state, KeyCode::Enter);
+        for c in "FLAC, .wav".chars() {
+            press(&mut state, KeyCode::Char(c));
         }
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            KeyAction::CommandSubmitted("__root_set include_extensions flac,wav /music".into())
+        );
+
+        press(&mut state, KeyCode::Esc);
+        press(&mut state, KeyCode::Char(','));
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(text.contains("Appearance"));
+    }
+
+    #[test]
+    fn hovered_rows_are_shaded_and_footer_hints_are_clickable() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        let mut second = state.snapshot.tracks[0].clone();
+        second.title = "Track Two".into();
+        state.snapshot.tracks.push(second);
         state.rebuild_track_filter();
-        state.selected_track = 5;
-        let areas = RenderAreas {
-            roots: PaneArea::bordered(Rect::new(0, 0, 20, 8), 1),
-            browse: Rect::new(0, 16, 20, 8),
-            browse_items: None,
-            playlists: PaneArea::bordered(Rect::new(0, 8, 20, 8), 1),
-            tracks: PaneArea::bordered(Rect::new(20, 0, 40, 8), 1),
-            track_header: Rect::new(20, 0, 40, 1),
-            track_col_offsets: TrackColumnOffsets::default(),
+        let palette = Palette::default();
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
         };
-        state.sync_scroll_offsets(&areas);
 
-        let click = MouseEvent {
-            kind: MouseEventKind::Down(crossterm::event::MouseButton::Left),
-            column: areas.tracks.inner.x + 1,
-            row: areas.tracks.inner.y,
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).expect("terminal");
+        let mut areas = RenderAreas::default();
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        let rows = areas.tracks.inner;
+        let (x, second_row) = (rows.x + 2, rows.y + 1);
+        state.handle_mouse(mouse(MouseEventKind::Moved, x, second_row), &areas);
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(x, second_row)].bg, palette.hover_bg);
+        assert_ne!(buffer[(x, rows.y)].bg, palette.hover_bg);
+        assert_eq!(state.selected_track, 0);
+
+        let (filter, _) = state
+            .hint_areas
+            .iter()
+            .find(|(_, keys)| keys == &[crate::input::ChordKey::char('/')])
+            .cloned()
+            .expect("filter hint drawn");
+        state.handle_mouse(mouse(MouseEventKind::Moved, filter.x, filter.y), &areas);
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        let buffer = terminal.backend().buffer();
+        assert!(buffer[(filter.x, filter.y)]
+            .modifier
+            .contains(Modifier::UNDERLINED));
+        assert_ne!(buffer[(x, second_row)].bg, palette.hover_bg);
+
+        state.handle_mouse(
+            mouse(
+                MouseEventKind::Down(MouseButton::Left),
+                filter.x + 1,
+                filter.y,
+            ),
+            &areas,
+        );
+        assert_eq!(state.input_mode, InputMode::TrackFilter);
+        terminal
+            .draw(|f| areas = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        let (done, _) = state.hint_areas[0].clone();
+        state.handle_mouse(
+            mouse(MouseEventKind::Down(MouseButton::Left), done.x, done.y),
+            &areas,
+        );
+        assert_eq!(state.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn resizing_keeps_the_selection_in_view_and_clicks_on_the_new_rows() {
+        let mut state = sample_state();
+        state.focus = FocusPane::Tracks;
+        let first = state.snapshot.tracks[0].clone();
+        state.snapshot.tracks = (0..60)
+            .map(|i| ShellTrackItem {
+                id: format!("t{i}"),
+                title: format!("Track {i}"),
+                ..first.clone()
+            })
+            .collect();
+        state.rebuild_track_filter();
+        state.selected_track = 40;
+        let palette = Palette::default();
+        let mut terminal = Terminal::new(TestBackend::new(120, 70)).expect("terminal");
+        terminal
+            .draw(|f| {
+                draw_shell(f, &mut state, &palette);
+            })
+            .expect("draw");
+        assert_eq!(state.tracks_scroll, 0);
+
+        let areas = state.relayout(Rect::new(0, 0, 120, 24));
+        let visible = areas.tracks.visible_items;
+        assert!(state.tracks_scroll <= 40 && 40 < state.tracks_scroll + visible);
+        assert_eq!(state.seek_bar_area, Rect::default());
+
+        let mut small = Terminal::new(TestBackend::new(120, 24)).expect("terminal");
+        let mut drawn = RenderAreas::default();
+        small
+            .draw(|f| drawn = draw_shell(f, &mut state, &palette))
+            .expect("draw");
+        assert_eq!(drawn.tracks.inner, areas.tracks.inner);
+
+        let scroll = state.tracks_scroll;
+        let row = areas.tracks.inner;
+        state.handle_mouse(
+            MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: row.x + 2,
+                row: row.y,
+                modifiers: KeyModifiers::NONE,
+            },
+            &areas,
+        );
+        assert_eq!(state.selected_track, scroll);
+    }
+
+    #[test]
+    fn seek_bar_previews_the_time_and_seeks_on_release() {
+        let mut state = sample_state();
+        state.snapshot.now_playing_title = "Track One".into();
+        state.playback_status = "playing".into();
+        state.playback_duration_ms = 600_000;
+        state.playback_position_ms = 60_000;
+        render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        let bar = state.seek_bar_area;
+        let (start, end) = (bar.x + 6, bar.right() - 6);
+
+        let areas = RenderAreas::default();
+        let mouse = |kind, column| MouseEvent {
+            kind,
+            column,
+            row: bar.y,
             modifiers: KeyModifiers::NONE,
         };
-        state.handle_mouse(click, &areas);
+        let middle = start + (end - start) / 2;
+        state.handle_mouse(mouse(MouseEventKind::Moved, middle), &areas);
+        let (_, hovered_ms) = state
+            .seek_preview
+            .expect("hovering the bar previews a time");
+        assert!((240_000..=360_000).contains(&hovered_ms));
+        let text = render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        assert!(
+            text.contains(&format!(" {} ", format_ms(hovered_ms))),
+            "{text}"
+        );
 
-        assert_eq!(state.focus, FocusPane::Tracks);
-        assert_eq!(state.selected_track, state.tracks_scroll);
+        let down = mouse(MouseEventKind::Down(MouseButton::Left), middle);
+        assert_eq!(state.handle_mouse(down, &areas), KeyAction::Continue);
+        // Dragging past the end previews the very end but does not seek yet.
+        let drag = mouse(MouseEventKind::Drag(MouseButton::Left), bar.right() + 4);
+        assert_eq!(state.handle_mouse(drag, &areas), KeyAction::Continue);
+        assert_eq!(state.playback_position_ms, 60_000);
+        assert_eq!(state.shown_position_ms(), 600_000);
+        let drag = mouse(MouseEventKind::Drag(MouseButton::Left), start);
+        state.handle_mouse(drag, &areas);
+        let up = mouse(MouseEventKind::Up(MouseButton::Left), start);
+        assert_eq!(
+            state.handle_mouse(up, &areas),
+            KeyAction::Playback(PlaybackAction::Seek { position_ms: 0 })
+        );
+        assert_eq!(state.playback_position_ms, 0);
+
+        state.handle_mouse(mouse(MouseEventKind::Moved, bar.x + 1), &areas);
+        assert!(state.seek_preview.is_none());
     }
 
     #[test]
-    fn command_palette_submits_command() {
+    fn volume_bar_supports_click_drag_and_scroll() {
         let mut state = sample_state();
+        state.snapshot.now_playing_title = "Track One".into();
+        state.playback_status = "paused".into();
+        state.snapshot.volume = 0.5;
+        render_once_to_text(&mut state, &Palette::default(), 100, 30).unwrap();
+        let bar = state.volume_bar_area;
+        assert_eq!(bar.width, 10);
+
+        let areas = RenderAreas::default();
+        let mouse = |kind, column| MouseEvent {
+            kind,
+            column,
+            row: bar.y,
+            modifiers: KeyModifiers::NONE,
+        };
+        let down = mouse(MouseEventKind::Down(MouseButton::Left), bar.x + 1);
         assert_eq!(
-            state.handle_key(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE)),
-            KeyAction::Continue
+            state.handle_mouse(down, &areas),
+            KeyAction::Playback(PlaybackAction::SetVolume { volume: 0.2 })
+        );
+        // Dragging past the end of the bar pins the volume at 100%.
+        let drag = mouse(MouseEventKind::Drag(MouseButton::Left), bar.right() + 3);
+        assert_eq!(
+            state.handle_mouse(drag, &areas),
+            KeyAction::Playback(PlaybackAction::SetVolume { volume: 1.0 })
+        );
+        assert_eq!(state.snapshot.volume, 1.0);
+        let up = mouse(MouseEventKind::Up(MouseButton::Left), bar.x);
+        state.handle_mouse(up, &areas);
+        let drag = mouse(MouseEventKind::Drag(MouseButton::Left), bar.x);
+        assert_eq!(state.handle_mouse(drag, &areas), KeyAction::Continue);
+
+        let scroll = mouse(MouseEventKind::ScrollDown, bar.x + 4);
+        assert_eq!(
+            state.handle_mouse(scroll, &areas),
+            KeyAction::Playback(PlaybackAction::VolumeDown)
         );
-        for ch in ['h', 'e', 'l', 'p'] {
-            let _ = state.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
-        }
-        let action = state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
-        assert_eq!(action, KeyAction::CommandSubmitted("help".to_string()));
-        assert_eq!(state.input_mode, InputMode::Normal);
-        assert!(state.command_palette_input.is_empty());
     }
 }
@@ -0,0 +1,457 @@
+//! The stats dashboard (`T`): listening time by day and week, the most
+//! played artists, albums and tracks, the library's formats, and how it grew.
+//! Also the year in review (`y` there), the same for a range of dates.
+
+use crate::shell::{thousands, ShellListeningStats, ShellReview};
+use crate::text::truncate_to_width;
+use crate::theme::Palette;
+use ratatui::prelude::*;
+use ratatui::symbols::Marker;
+use ratatui::widgets::{
+    Axis, Bar, BarChart, Block, Borders, Chart, Dataset, GraphType, Paragraph, Widget,
+};
+
+/// How many entries each of the most played lists shows.
+pub const TOP_COUNT: usize = 5;
+
+pub struct StatsView<'a> {
+    pub stats: &'a ShellListeningStats,
+    pub total_tracks: usize,
+    pub palette: &'a Palette,
+}
+
+impl Widget for StatsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < 40 || area.height < 12 {
+            Paragraph::new(Span::styled(" Too small for stats", self.muted())).render(area, buf);
+            return;
+        }
+        let rows = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Percentage(40),
+            Constraint::Length(TOP_COUNT as u16 + 2),
+            Constraint::Min(6),
+        ])
+        .split(area);
+        self.render_headline(rows[0], buf);
+
+        let listening =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(rows[1]);
+        self.render_listening(
+            " Last 14 days (minutes) ",
+            &self.stats.days,
+            day_label,
+            listening[0],
+            buf,
+        );
+        self.render_listening(
+            " Last 12 weeks (minutes) ",
+            &self.stats.weeks,
+            week_label,
+            listening[1],
+            buf,
+        );
+
+        let tops = Layout::horizontal([Constraint::Ratio(1, 3); 3]).split(rows[2]);
+        render_top(
+            self.palette,
+            " Top artists ",
+            &self.stats.top_artists,
+            tops[0],
+            buf,
+        );
+        render_top(
+            self.palette,
+            " Top albums ",
+            &self.stats.top_albums,
+            tops[1],
+            buf,
+        );
+        render_top(
+            self.palette,
+            " Top tracks ",
+            &self.stats.top_tracks,
+            tops[2],
+            buf,
+        );
+
+        let library = Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+            .split(rows[3]);
+        self.render_formats(library[0], buf);
+        self.render_growth(library[1], buf);
+    }
+}
+
+impl StatsView<'_> {
+    fn muted(&self) -> Style {
+        Style::default().fg(self.palette.text_muted)
+    }
+
+    fn section(&self, title: &'static str) -> Block<'static> {
+        section(self.palette, title)
+    }
+
+    fn render_headline(&self, area: Rect, buf: &mut Buffer) {
+        let stats = self.stats;
+        let this_week = stats.weeks.last().map(|(_, ms)| *ms).unwrap_or(0);
+        let line = Line::from(vec![
+            Span::styled(
+                format!(" {} plays", thousands(stats.plays as usize)),
+                Style::default()
+                    .fg(self.palette.text)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" · {} listened", listened_label(stats.listened_ms)),
+                self.muted(),
+            ),
+            Span::styled(
+                format!(" · {} this week", listened_label(this_week)),
+                self.muted(),
+            ),
+            Span::styled(
+                format!(" · {} tracks in library", thousands(self.total_tracks)),
+                self.muted(),
+            ),
+        ]);
+        Paragraph::new(line).render(area, buf);
+    }
+
+    /// Listening time as bars, in minutes.
+    fn render_listening(
+        &self,
+        title: &'static str,
+        periods: &[(String, u64)],
+        label: fn(&str) -> String,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let block = self.section(title);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if periods.is_empty() {
+            return;
+        }
+        let bars = periods
+            .iter()
+            .map(|(start, ms)| {
+                let value = ms / 60_000;
+                Bar::default()
+                    .value(value)
+                    .label(Line::from(label(start)))
+                    .text_value(if value == 0 {
+                        String::new()
+                    } else {
+                        value.to_string()
+                    })
+            })
+            .collect::<Vec<_>>();
+        let count = bars.len() as u16;
+        let width = (inner.width.saturating_sub(count) / count.max(1)).clamp(1, 6);
+        BarChart::vertical(bars)
+            .bar_width(width)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(self.palette.progress_fill))
+            .value_style(
+                Style::default()
+                    .fg(self.palette.bg_panel())
+                    .bg(self.palette.progress_fill),
+            )
+            .label_style(self.muted())
+            .render(inner, buf);
+    }
+
+    fn render_formats(&self, area: Rect, buf: &mut Buffer) {
+        let block = self.section(" Formats ");
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let bars = self
+            .stats
+            .formats
+            .iter()
+            .take(inner.height as usize)
+            .map(|(format, tracks)| {
+                Bar::default()
+                    .value(*tracks)
+                    .label(Line::from(format!("{format:<5}")))
+                    .text_value(thousands(*tracks as usize))
+            })
+            .collect::<Vec<_>>();
+        BarChart::horizontal(bars)
+            .bar_width(1)
+            .bar_gap(0)
+            .bar_style(Style::default().fg(self.palette.accent))
+            .value_style(Style::default().fg(self.palette.text))
+            .label_style(self.muted())
+            .render(inner, buf);
+    }
+
+    /// The library's size at the end of each month, as a line.
+    fn render_growth(&self, area: Rect, buf: &mut Buffer) {
+        let months = &self.stats.added_by_month;
+        let block = self.section(" Library growth (tracks) ");
+        let (Some((first, _)), Some((last, _))) = (months.first(), months.last()) else {
+            block.render(area, buf);
+            return;
+        };
+        let points = growth_points(months);
+        let top = points
+            .last()
+            .map(|(_, total)| *total)
+            .unwrap_or(0.0)
+            .max(1.0);
+        let dataset = Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(self.palette.progress_fill))
+            .data(&points);
+        Chart::new(vec![dataset])
+            .block(block)
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, (points.len() - 1).max(1) as f64])
+                    .labels([first.as_str(), last.as_str()])
+                    .style(self.muted()),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, top])
+                    .labels(["0".to_string(), thousands(top as usize)])
+                    .style(self.muted()),
+            )
+            .render(area, buf);
+    }
+}
+
+/// The year-in-review dialog: totals and the top month, the most played
+/// lists, and time listened month by month.
+pub struct ReviewView<'a> {
+    pub review: &'a ShellReview,
+    pub palette: &'a Palette,
+}
+
+impl Widget for ReviewView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let review = self.review;
+        let muted = Style::default().fg(self.palette.text_muted);
+        let heading = Line::from(Span::styled(
+            format!(" Your {} in music", review.title),
+            Style::default()
+                .fg(self.palette.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+        if review.plays == 0 {
+            Paragraph::new(vec![
+                heading,
+                Line::default(),
+                Line::from(Span::styled(" Nothing was played", muted)),
+            ])
+            .render(area, buf);
+            return;
+        }
+        let rows = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(TOP_COUNT as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .split(area);
+        let bold = Style::default()
+            .fg(self.palette.text)
+            .add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            heading,
+            Line::from(vec![
+                Span::styled(format!(" {}", listened_label(review.listened_ms)), bold),
+                Span::styled(" listened over ", muted),
+                Span::styled(thousands(review.plays as usize), bold),
+                Span::styled(" plays", muted),
+            ]),
+            Line::from(vec![
+                Span::styled(format!(" {}", thousands(review.tracks as usize)), bold),
+                Span::styled(" different tracks by ", muted),
+                Span::styled(thousands(review.artists as usize), bold),
+                Span::styled(" artists", muted),
+            ]),
+        ];
+        if let Some((month, ms)) = &review.top_month {
+            lines.push(Line::from(vec![
+                Span::styled(" Most listened month: ", muted),
+                Span::styled(month.clone(), bold),
+                Span::styled(format!(" ({})", listened_label(*ms)), muted),
+            ]));
+        }
+        Paragraph::new(lines).render(rows[0], buf);
+
+        let tops = Layout::horizontal([Constraint::Ratio(1, 3); 3]).split(rows[1]);
+        render_top(
+            self.palette,
+            " Top artists ",
+            &review.top_artists,
+            tops[0],
+            buf,
+        );
+        render_top(
+            self.palette,
+            " Top albums ",
+            &review.top_albums,
+            tops[1],
+            buf,
+        );
+        render_top(
+            self.palette,
+            " Top tracks ",
+            &review.top_tracks,
+            tops[2],
+            buf,
+        );
+
+        let block = section(self.palette, " By month (hours) ");
+        let inner = block.inner(rows[2]);
+        block.render(rows[2], buf);
+        let years = review.months.first().zip(review.months.last());
+        let with_year =
+            years.is_some_and(|((first, _), (last, _))| first.get(..4) != last.get(..4));
+        let bars = review
+            .months
+            .iter()
+            .map(|(month, ms)| {
+                let hours = ms / 3_600_000;
+                Bar::default()
+                    .value(*ms / 60_000)
+                    .label(Line::from(month_label(month, with_year)))
+                    .text_value(if hours == 0 {
+                        String::new()
+                    } else {
+                        hours.to_string()
+                    })
+            })
+            .collect::<Vec<_>>();
+        let count = bars.len().max(1) as u16;
+        let width = (inner.width.saturating_sub(count) / count).clamp(1, 5);
+        BarChart::vertical(bars)
+            .bar_width(width)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(self.palette.progress_fill))
+            .value_style(
+                Style::default()
+                    .fg(self.palette.bg_panel())
+                    .bg(self.palette.progress_fill),
+            )
+            .label_style(muted)
+            .render(inner, buf);
+    }
+}
+
+fn section(palette: &Palette, title: &'static str) -> Block<'static> {
+    Block::default()
+        .borders(Borders::TOP)
+        .title(title)
+        .border_style(Style::default().fg(palette.border_unfocused))
+        .title_style(Style::default().fg(palette.accent))
+}
+
+fn render_top(
+    palette: &Palette,
+    title: &'static str,
+    entries: &[(String, u64)],
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let muted = Style::default().fg(palette.text_muted);
+    let block = section(palette, title);
+    let inner = block.inner(area);
+    block.render(area, buf);
+    if entries.is_empty() {
+        Paragraph::new(Span::styled(" Nothing played yet", muted)).render(inner, buf);
+        return;
+    }
+    let width = inner.width as usize;
+    let lines = entries
+        .iter()
+        .take(TOP_COUNT)
+        .enumerate()
+        .map(|(i, (name, plays))| {
+            let count = format!(" {plays}");
+            let name_width = width.saturating_sub(count.len() + 4);
+            Line::from(vec![
+                Span::styled(format!(" {}. ", i + 1), muted),
+                Span::styled(
+                    truncate_to_width(name, name_width),
+                    Style::default().fg(palette.text),
+                ),
+                Span::styled(count, Style::default().fg(palette.accent_2)),
+            ])
+        })
+        .collect::<Vec<_>>();
+    Paragraph::new(lines).render(inner, buf);
+}
+
+/// Listening time for the headline, e.g. `3h 20m` or `45m`.
+pub fn listened_label(ms: u64) -> String {
+    let minutes = ms / 60_000;
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h {:02}m", minutes % 60),
+    }
+}
+
+/// The day of the month under a day's bar.
+fn day_label(start: &str) -> String {
+    start.get(8..).unwrap_or(start).to_string()
+}
+
+/// The month under a month's bar, e.g. `03`, with its year when the range
+/// runs over more than one: `24-03`.
+fn month_label(month: &str, with_year: bool) -> String {
+    let from = if with_year { 2 } else { 5 };
+    month.get(from..).unwrap_or(month).to_string()
+}
+
+/// `MM-DD` under a week's bar.
+fn week_label(start: &str) -> String {
+    start.get(5..).unwrap_or(start).to_string()
+}
+
+/// How many tracks there were at the end of each month, as chart points.
+pub fn growth_points(added_by_month: &[(String, u64)]) -> Vec<(f64, f64)> {
+    let mut total = 0;
+    added_by_month
+        .iter()
+        .enumerate()
+        .map(|(i, (_, added))| {
+            total += added;
+            (i as f64, total as f64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_listening_time_in_hours_and_minutes() {
+        assert_eq!(listened_label(0), "0m");
+        assert_eq!(listened_label(45 * 60_000 + 59_000), "45m");
+        assert_eq!(listened_label(200 * 60_000), "3h 20m");
+        assert_eq!(day_label("2026-10-18"), "18");
+        assert_eq!(week_label("2026-10-12"), "10-12");
+        assert_eq!(month_label("2026-03", false), "03");
+        assert_eq!(month_label("2026-03", true), "26-03");
+    }
+
+    #[test]
+    fn library_growth_adds_up_month_by_month() {
+        let months = [
+            ("2026-08".to_string(), 120),
+            ("2026-09".to_string(), 0),
+            ("2026-10".to_string(), 30),
+        ];
+        assert_eq!(
+            growth_points(&months),
+            [(0.0, 120.0), (1.0, 120.0), (2.0, 150.0)]
+        );
+        assert!(growth_points(&[]).is_empty());
+    }
+}
@@ -4,6 +4,8 @@ use std::env;
 pub struct TerminalCaps {
     pub supports_drag_drop: bool,
     pub terminal_name: String,
+    /// Whether the locale's character encoding is UTF-8.
+    pub utf8_locale: bool,
 }
 
 impl TerminalCaps {
@@ -17,10 +19,24 @@ impl TerminalCaps {
         Self {
             supports_drag_drop,
             terminal_name: term_program,
+            utf8_locale: locale_is_utf8(|name| env::var(name).ok()),
         }
     }
 }
 
+/// Reads the locale the way C programs do: the first of `LC_ALL`,
+/// `LC_CTYPE` and `LANG` that is set decides. No locale at all counts as
+/// UTF-8, since that is what terminals default to today.
+pub fn locale_is_utf8(var: impl Fn(&str) -> Option<String>) -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|value| !value.is_empty()))
+        .is_none_or(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,7 +46,30 @@ mod tests {
         let caps = TerminalCaps {
             supports_drag_drop: true,
             terminal_name: "ghostty".to_string(),
+            utf8_locale: true,
         };
         assert!(caps.supports_drag_drop);
     }
+
+    #[test]
+    fn locale_encoding_comes_from_the_first_variable_set() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert!(locale_is_utf8(env(&[])));
+        assert!(locale_is_utf8(env(&[("LANG", "en_US.UTF-8")])));
+        assert!(!locale_is_utf8(env(&[
+            ("LANG", "en_US.UTF-8"),
+            ("LC_ALL", "C")
+        ])));
+        assert!(locale_is_utf8(env(&[
+            ("LC_ALL", ""),
+            ("LC_CTYPE", "de_DE.utf8")
+        ])));
+        assert!(!locale_is_utf8(env(&[("LANG", "POSIX")])));
+    }
 }
@@ -0,0 +1,115 @@
+use crossterm::cursor::Show;
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, LeaveAlternateScreen};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once, OnceLock};
+
+/// Set by SIGTERM/SIGHUP/SIGINT. The run loop checks it every tick and returns
+/// normally, so the terminal is restored and the caller can flush state.
+static SHUTDOWN_REQUESTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+fn shutdown_flag() -> &'static Arc<AtomicBool> {
+    SHUTDOWN_REQUESTED.get_or_init(|| Arc::new(AtomicBool::new(false)))
+}
+
+/// Whether a termination signal has been received.
+pub fn shutdown_requested() -> bool {
+    shutdown_flag().load(Ordering::Relaxed)
+}
+
+/// Set while the terminal reports keys through the kitty keyboard protocol.
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+/// Asks terminals that speak the kitty keyboard protocol to report keys they
+/// otherwise keep to themselves, such as the media keys. Other terminals are
+/// left as they are.
+pub fn enable_keyboard_enhancement() {
+    if !matches!(
+        crossterm::terminal::supports_keyboard_enhancement(),
+        Ok(true)
+    ) {
+        return;
+    }
+    let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+    if execute!(io::stdout(), PushKeyboardEnhancementFlags(flags)).is_ok() {
+        KEYBOARD_ENHANCED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Leaves raw mode, the alternate screen, mouse capture, bracketed paste,
+/// focus reporting and the keyboard protocol.
+/// Errors are ignored: this runs on exit paths where there is nothing better
+/// to do, and disabling a mode that was never enabled is harmless.
+pub fn restore_terminal(mouse: bool) {
+    let mut stdout = io::stdout();
+    if KEYBOARD_ENHANCED.swap(false, Ordering::Relaxed) {
+        let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+    }
+    let _ = execute!(stdout, DisableBracketedPaste, DisableFocusChange);
+    if mouse {
+        let _ = execute!(stdout, DisableMouseCapture);
+    }
+    let _ = execute!(stdout, LeaveAlternateScreen, Show);
+    let _ = disable_raw_mode();
+}
+
+/// Chains a panic hook that restores the terminal before the default hook
+/// prints the message, so the panic is readable and the shell usable.
+/// Installing more than once is a no-op.
+pub fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            tracing::error!("panic: {info}");
+            restore_terminal(true);
+            previous(info);
+        }));
+    });
+}
+
+/// Routes SIGTERM, SIGHUP and SIGINT to [`shutdown_requested`]. A second
+/// signal while the first is still being handled terminates the process
+/// immediately. In raw mode Ctrl-C arrives as a key, so SIGINT only matters
+/// when running without the TUI.
+#[cfg(unix)]
+pub fn install_signal_handlers() -> io::Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::flag;
+
+    static INSTALLED: Once = Once::new();
+    let mut result = Ok(());
+    INSTALLED.call_once(|| {
+        result = (|| {
+            for signal in [SIGTERM, SIGHUP, SIGINT] {
+                flag::register_conditional_shutdown(signal, 1, Arc::clone(shutdown_flag()))?;
+                flag::register(signal, Arc::clone(shutdown_flag()))?;
+            }
+            Ok(())
+        })();
+    });
+    result
+}
+
+#[cfg(not(unix))]
+pub fn install_signal_handlers() -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sigterm_requests_shutdown() {
+        install_signal_handlers().unwrap();
+        assert!(!shutdown_requested());
+        signal_hook::low_level::raise(signal_hook::consts::SIGTERM).unwrap();
+        assert!(shutdown_requested());
+    }
+}
@@ -0,0 +1,117 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Number of terminal cells `text` occupies. CJK and most emoji take two.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Cuts `text` to at most `max_width` cells, ending with `…` when shortened.
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let budget = max_width - 1;
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    out.push('…');
+    out
+}
+
+/// Like [`truncate_to_width`] but keeps the end of the text, for paths where
+/// the last components matter most.
+pub fn truncate_start_to_width(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let budget = max_width - 1;
+    let mut used = 0;
+    let mut start = text.len();
+    for (idx, c) in text.char_indices().rev() {
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        start = idx;
+    }
+    format!("…{}", &text[start..])
+}
+
+/// A `max_width`-cell window into `text` starting `offset` cells in, for
+/// reading text too long for its column. Scrolling stops once the end is in
+/// view, and cut-off ends show as `…`.
+pub fn scroll_to_width(text: &str, offset: usize, max_width: usize) -> String {
+    let overflow = display_width(text).saturating_sub(max_width);
+    if offset == 0 || overflow == 0 || max_width == 0 {
+        return truncate_to_width(text, max_width);
+    }
+    let offset = offset.min(overflow + 1);
+    let mut skipped = 0;
+    let mut start = text.len();
+    for (idx, c) in text.char_indices() {
+        if skipped >= offset {
+            start = idx;
+            break;
+        }
+        skipped += c.width().unwrap_or(0);
+    }
+    format!("…{}", truncate_to_width(&text[start..], max_width - 1))
+}
+
+/// Pads `text` with spaces to exactly `width` cells. Text that is already
+/// wider is returned unchanged.
+pub fn pad_to_width(text: &str, width: usize) -> String {
+    let pad = width.saturating_sub(display_width(text));
+    format!("{text}{}", " ".repeat(pad))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_wide_glyphs_by_cell_count() {
+        assert_eq!(truncate_to_width("Hello", 5), "Hello");
+        assert_eq!(truncate_to_width("Hello", 4), "Hel…");
+        // Each CJK glyph is two cells; a half-cell remainder is left empty.
+        assert_eq!(truncate_to_width("坂本龍一", 6), "坂本…");
+        assert_eq!(truncate_to_width("坂本龍一", 5), "坂本…");
+        assert_eq!(display_width(&truncate_to_width("🎵🎶 Mix", 4)), 3);
+        assert_eq!(truncate_to_width("abc", 0), "");
+    }
+
+    #[test]
+    fn keeps_path_tails() {
+        assert_eq!(truncate_start_to_width("/home/me/music", 8), "…e/music");
+        assert_eq!(truncate_start_to_width("/music/ＡＢ/c", 6), "…Ｂ/c");
+    }
+
+    #[test]
+    fn scrolls_a_window_through_long_text() {
+        assert_eq!(scroll_to_width("Symphony No. 9", 0, 8), "Symphon…");
+        assert_eq!(scroll_to_width("Symphony No. 9", 4, 8), "…hony N…");
+        assert_eq!(scroll_to_width("Symphony No. 9", 40, 8), "…y No. 9");
+        assert_eq!(scroll_to_width("Short", 3, 8), "Short");
+    }
+
+    #[test]
+    fn pads_to_cell_width() {
+        assert_eq!(pad_to_width("東京", 6), "東京  ");
+        assert_eq!(display_width(&pad_to_width("abc", 6)), 6);
+        assert_eq!(pad_to_width("toolong", 3), "toolong");
+    }
+}
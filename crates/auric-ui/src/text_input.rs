@@ -0,0 +1,264 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Span;
+
+/// Single-line editable text buffer with a movable cursor.
+///
+/// The cursor is a byte offset that always sits on a char boundary, so
+/// multi-byte input (accented names, CJK titles) edits cleanly.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_value(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let cursor = value.len();
+        Self { value, cursor }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Cursor position as a char index (for rendering).
+    pub fn cursor_chars(&self) -> usize {
+        self.value[..self.cursor].chars().count()
+    }
+
+    /// Replaces the contents and moves the cursor to the end.
+    pub fn set(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+    }
+
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.value)
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.value.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        self.value.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(prev) = self.prev_boundary(self.cursor) {
+            self.value.replace_range(prev..self.cursor, "");
+            self.cursor = prev;
+        }
+    }
+
+    pub fn delete_forward(&mut self) {
+        if let Some(next) = self.next_boundary(self.cursor) {
+            self.value.replace_range(self.cursor..next, "");
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(prev) = self.prev_boundary(self.cursor) {
+            self.cursor = prev;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(next) = self.next_boundary(self.cursor) {
+            self.cursor = next;
+        }
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.len();
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start_before(self.cursor);
+    }
+
+    pub fn move_word_right(&mut self) {
+        let rest = &self.value[self.cursor..];
+        let skipped_space = rest.len() - rest.trim_start().len();
+        let word_len = rest[skipped_space..]
+            .find(char::is_whitespace)
+            .unwrap_or(rest.len() - skipped_space);
+        self.cursor += skipped_space + word_len;
+    }
+
+    pub fn delete_word_back(&mut self) {
+        let start = self.word_start_before(self.cursor);
+        self.value.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.value.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    pub fn delete_to_end(&mut self) {
+        self.value.truncate(self.cursor);
+    }
+
+    /// Applies a standard editing key. Returns `false` if the key is not an
+    /// editing key, so the caller can handle it (Enter, Esc, Up/Down, ...).
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Left if ctrl || alt => self.move_word_left(),
+            KeyCode::Right if ctrl || alt => self.move_word_right(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.move_home(),
+            KeyCode::End => self.move_end(),
+            KeyCode::Backspace if ctrl || alt => self.delete_word_back(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete_forward(),
+            KeyCode::Char('a') if ctrl => self.move_home(),
+            KeyCode::Char('e') if ctrl => self.move_end(),
+            KeyCode::Char('b') if ctrl => self.move_left(),
+            KeyCode::Char('f') if ctrl => self.move_right(),
+            KeyCode::Char('b') if alt => self.move_word_left(),
+            KeyCode::Char('f') if alt => self.move_word_right(),
+            KeyCode::Char('d') if ctrl => self.delete_forward(),
+            KeyCode::Char('w') if ctrl => self.delete_word_back(),
+            KeyCode::Char('u') if ctrl => self.delete_to_start(),
+            KeyCode::Char('k') if ctrl => self.delete_to_end(),
+            KeyCode::Char(c) if !ctrl && !alt && !c.is_control() => self.insert_char(c),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Renders the text with a block cursor. When `focused` is false the
+    /// cursor cell is omitted.
+    pub fn spans(&self, style: Style, focused: bool) -> Vec<Span<'_>> {
+        if !focused {
+            return vec![Span::styled(self.value.as_str(), style)];
+        }
+        let cursor_style = style.add_modifier(Modifier::REVERSED);
+        let (before, rest) = self.value.split_at(self.cursor);
+        let mut spans = vec![Span::styled(before, style)];
+        match rest.chars().next() {
+            Some(c) => {
+                let (at, after) = rest.split_at(c.len_utf8());
+                spans.push(Span::styled(at, cursor_style));
+                spans.push(Span::styled(after, style));
+            }
+            None => spans.push(Span::styled(" ", cursor_style)),
+        }
+        spans
+    }
+
+    fn prev_boundary(&self, at: usize) -> Option<usize> {
+        self.value[..at].char_indices().next_back().map(|(i, _)| i)
+    }
+
+    fn next_boundary(&self, at: usize) -> Option<usize> {
+        self.value[at..].chars().next().map(|c| at + c.len_utf8())
+    }
+
+    fn word_start_before(&self, at: usize) -> usize {
+        let head = self.value[..at].trim_end();
+        head.rfind(char::is_whitespace)
+            .map(|i| i + head[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0)
+    }
+}
+
+impl std::fmt::Display for TextInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn inserts_in_the_middle_and_deletes_forward() {
+        let mut input = TextInput::with_value("acd");
+        input.move_left();
+        input.move_left();
+        input.insert_char('b');
+        assert_eq!(input.as_str(), "abcd");
+        assert_eq!(input.cursor_chars(), 2);
+        input.delete_forward();
+        assert_eq!(input.as_str(), "abd");
+        input.move_end();
+        input.delete_forward();
+        assert_eq!(input.as_str(), "abd");
+    }
+
+    #[test]
+    fn word_movement_and_deletion() {
+        let mut input = TextInput::with_value("scan path  /music");
+        input.move_word_left();
+        assert_eq!(input.cursor_chars(), 11);
+        input.move_word_left();
+        assert_eq!(input.cursor_chars(), 5);
+        input.move_word_right();
+        assert_eq!(input.cursor_chars(), 9);
+        input.move_end();
+        input.delete_word_back();
+        assert_eq!(input.as_str(), "scan path  ");
+        input.delete_word_back();
+        assert_eq!(input.as_str(), "scan ");
+    }
+
+    #[test]
+    fn handles_multibyte_characters() {
+        let mut input = TextInput::with_value("Björk");
+        input.move_left();
+        input.move_left();
+        input.backspace();
+        assert_eq!(input.as_str(), "Bjrk");
+        input.insert_char('ö');
+        input.move_home();
+        input.delete_forward();
+        assert_eq!(input.as_str(), "jörk");
+    }
+
+    #[test]
+    fn key_bindings_edit_and_report_unhandled_keys() {
+        let mut input = TextInput::new();
+        for c in "hello world".chars() {
+            assert!(input.handle_key(&key(KeyCode::Char(c), KeyModifiers::NONE)));
+        }
+        assert!(input.handle_key(&key(KeyCode::Home, KeyModifiers::NONE)));
+        assert!(input.handle_key(&key(KeyCode::Char('k'), KeyModifiers::CONTROL)));
+        assert!(input.is_empty());
+        assert!(!input.handle_key(&key(KeyCode::Enter, KeyModifiers::NONE)));
+        assert!(!input.handle_key(&key(KeyCode::Up, KeyModifiers::NONE)));
+    }
+}
@@ -1,9 +1,46 @@
+use crate::glyphs::Glyphs;
 use crate::{Theme, ThemeStore, UiError};
 use ratatui::style::Color;
 use std::collections::BTreeMap;
+use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// How many colors the terminal can show. Themes are authored in RGB and
+/// quantized down for terminals without truecolor support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorMode {
+    pub fn from_config(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "16" | "ansi" | "ansi16" => Self::Ansi16,
+            "256" | "ansi256" => Self::Ansi256,
+            "truecolor" | "24bit" => Self::TrueColor,
+            _ => Self::detect(|name| env::var(name).ok()),
+        }
+    }
+
+    /// What the terminal says it supports: truecolor when `COLORTERM` is
+    /// `truecolor` or `24bit`, 256 colors for a `*-256color` `TERM`, and
+    /// the 16 ANSI colors otherwise.
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> Self {
+        let colorterm = var("COLORTERM").unwrap_or_default().to_ascii_lowercase();
+        if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+            return Self::TrueColor;
+        }
+        if var("TERM").is_some_and(|term| term.ends_with("-256color")) {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Palette {
     pub use_terminal_bg: bool,
@@ -22,10 +59,18 @@ pub struct Palette {
     pub border_focused: Color,
     pub border_unfocused: Color,
     pub selection_bg: Color,
+    /// Every other track row, when the theme background is in use.
+    pub row_alt_bg: Color,
+    /// The row of the playing track.
+    pub playing_row_bg: Color,
+    /// The row under the mouse pointer.
+    pub hover_bg: Color,
     pub progress_fill: Color,
     pub visualizer_low: Color,
     pub visualizer_mid: Color,
     pub visualizer_high: Color,
+    /// The theme's characters for the full unicode level.
+    pub glyphs: Glyphs,
 }
 
 impl Default for Palette {
@@ -47,10 +92,14 @@ impl Default for Palette {
             border_focused: color_from_hex("#90cdf4").unwrap_or(Color::Blue),
             border_unfocused: color_from_hex("#1e2736").unwrap_or(Color::DarkGray),
             selection_bg: color_from_hex("#2a3446").unwrap_or(Color::DarkGray),
+            row_alt_bg: color_from_hex("#202532").unwrap_or(Color::DarkGray),
+            playing_row_bg: color_from_hex("#213b3f").unwrap_or(Color::DarkGray),
+            hover_bg: color_from_hex("#212733").unwrap_or(Color::DarkGray),
             progress_fill: color_from_hex("#4fd1c5").unwrap_or(Color::Cyan),
             visualizer_low: color_from_hex("#63b3ed").unwrap_or(Color::Blue),
             visualizer_mid: color_from_hex("#4fd1c5").unwrap_or(Color::Cyan),
             visualizer_high: color_from_hex("#f6ad55").unwrap_or(Color::Yellow),
+            glyphs: Glyphs::default(),
         }
     }
 }
@@ -87,16 +136,78 @@ impl Palette {
                 *field = v;
             }
         }
+        // Optional: by default a step up from the panel, a tint of the
+        // accent for the playing row, and halfway to the selection on hover.
+        palette.row_alt_bg = get("colors.row_alt_bg").unwrap_or(palette.surface_2);
+        palette.playing_row_bg = get("colors.playing_row_bg")
+            .unwrap_or_else(|| blend(palette.surface_1, palette.accent, 0.18));
+        palette.hover_bg = get("colors.hover_bg")
+            .unwrap_or_else(|| blend(palette.surface_1, palette.selection_bg, 0.5));
+        palette.glyphs = Glyphs::from_theme(theme);
 
         palette
     }
 
+    /// Converts every RGB color to the nearest color the terminal supports.
+    pub fn with_color_mode(mut self, mode: ColorMode) -> Self {
+        let convert: fn(Color) -> Color = match mode {
+            ColorMode::TrueColor => return self,
+            ColorMode::Ansi256 => to_ansi256,
+            ColorMode::Ansi16 => to_ansi16,
+        };
+        let original_selection = self.selection_bg;
+        for field in [
+            &mut self.surface_0,
+            &mut self.surface_1,
+            &mut self.surface_2,
+            &mut self.text,
+            &mut self.text_muted,
+            &mut self.accent,
+            &mut self.accent_2,
+            &mut self.danger,
+            &mut self.warning,
+            &mut self.success,
+            &mut self.border,
+            &mut self.focus,
+            &mut self.border_focused,
+            &mut self.border_unfocused,
+            &mut self.selection_bg,
+            &mut self.row_alt_bg,
+            &mut self.playing_row_bg,
+            &mut self.hover_bg,
+            &mut self.progress_fill,
+            &mut self.visualizer_low,
+            &mut self.visualizer_mid,
+            &mut self.visualizer_high,
+        ] {
+            *field = convert(*field);
+        }
+        // Subtle selection tints often collapse onto the panel color; keep the
+        // selected row visible.
+        if self.selection_bg == self.surface_1 && original_selection != self.surface_1 {
+            self.selection_bg = if self.surface_1 == Color::Black {
+                Color::DarkGray
+            } else {
+                Color::Gray
+            };
+        }
+        self
+    }
+
     pub fn bg_root(&self) -> Color {
-        if self.use_terminal_bg { Color::Reset } else { self.surface_0 }
+        if self.use_terminal_bg {
+            Color::Reset
+        } else {
+            self.surface_0
+        }
     }
 
     pub fn bg_panel(&self) -> Color {
-        if self.use_terminal_bg { Color::Reset } else { self.surface_1 }
+        if self.use_terminal_bg {
+            Color::Reset
+        } else {
+            self.surface_1
+        }
     }
 }
 
@@ -122,11 +233,7 @@ impl FsThemeStore {
     }
 
     fn path_for(&self, name: &str) -> Result<PathBuf, UiError> {
-        if name.is_empty()
-            || name.contains('/')
-            || name.contains('\\')
-            || name.contains("..")
-        {
+        if name.is_empty() || name.contains('/') || name.contains('\\') || name.contains("..") {
             return Err(UiError::Theme(format!("invalid theme name: {name}")));
         }
         Ok(self.base_dir.join(format!("{name}.toml")))
@@ -175,6 +282,20 @@ impl ThemeStore for FsThemeStore {
     }
 }
 
+/// Mixes two RGB colors, `t` of the way from `from` to `to`. Any other
+/// color is returned as `from`.
+pub fn blend(from: Color, to: Color, t: f32) -> Color {
+    match (from, to) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+            let mix = |a: u8, b: u8| {
+                (f32::from(a) + (f32::from(b) - f32::from(a)) * t.clamp(0.0, 1.0)).round() as u8
+            };
+            Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+        }
+        _ => from,
+    }
+}
+
 fn flatten_toml(prefix: &str, value: &toml::Value, out: &mut BTreeMap<String, String>) {
     match value {
         toml::Value::Table(table) => {
@@ -220,6 +341,87 @@ fn color_from_hex(input: &str) -> Option<Color> {
     Some(Color::Rgb(r, g, b))
 }
 
+fn to_ansi256(color: Color) -> Color {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let nearest_level = |c: u8| {
+        (0..LEVELS.len())
+            .min_by_key(|&i| LEVELS[i].abs_diff(c))
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    let avg = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_step = (avg.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_step;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+    if distance((gray, gray, gray)) < distance(cube) {
+        Color::Indexed(232 + gray_step)
+    } else {
+        Color::Indexed(cube_index as u8)
+    }
+}
+
+/// Maps an RGB color onto the 16 named ANSI colors by hue rather than raw
+/// distance, so pastel accents keep their hue instead of washing out to gray.
+fn to_ansi16(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let (r, g, b) = (
+        f32::from(r) / 255.0,
+        f32::from(g) / 255.0,
+        f32::from(b) / 255.0,
+    );
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let value = max;
+    let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+
+    if value < 0.3 {
+        return Color::Black;
+    }
+    if saturation < 0.25 {
+        return match value {
+            v if v < 0.6 => Color::DarkGray,
+            v if v < 0.85 => Color::Gray,
+            _ => Color::White,
+        };
+    }
+
+    let delta = max - min;
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let bright = value > 0.8;
+    match ((hue + 30.0) / 60.0) as u32 % 6 {
+        0 if bright => Color::LightRed,
+        0 => Color::Red,
+        1 if bright => Color::LightYellow,
+        1 => Color::Yellow,
+        2 if bright => Color::LightGreen,
+        2 => Color::Green,
+        3 if bright => Color::LightCyan,
+        3 => Color::Cyan,
+        4 if bright => Color::LightBlue,
+        4 => Color::Blue,
+        _ if bright => Color::LightMagenta,
+        _ => Color::Magenta,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,4 +458,126 @@ mod tests {
         assert!(store.load("foo/bar").is_err());
         assert!(store.load("").is_err());
     }
+
+    #[test]
+    fn bundled_themes_define_every_color() {
+        let store = FsThemeStore::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../themes"));
+        let names = store.list().unwrap();
+        for preset in [
+            "nord",
+            "catppuccin-mocha",
+            "solarized-dark",
+            "tokyo-night",
+            "one-dark",
+        ] {
+            assert!(names.iter().any(|n| n == preset), "missing theme {preset}");
+        }
+        let keys = [
+            "surface_0",
+            "surface_1",
+            "surface_2",
+            "text",
+            "text_muted",
+            "accent",
+            "accent_2",
+            "danger",
+            "warning",
+            "success",
+            "border",
+            "focus",
+            "border_focused",
+            "border_unfocused",
+            "selection_bg",
+            "progress_fill",
+            "visualizer_low",
+            "visualizer_mid",
+            "visualizer_high",
+        ];
+        for name in names {
+            let theme = store.load(&name).unwrap();
+            assert_eq!(theme.name, name);
+            for key in keys {
+                let value = theme.tokens.get(&format!("colors.{key}"));
+                assert!(
+                    value.and_then(|v| color_from_hex(v)).is_some(),
+                    "{name}: colors.{key} missing or invalid"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn row_backgrounds_default_from_the_theme_and_can_be_set() {
+        let mut theme = Theme {
+            name: "t".into(),
+            tokens: BTreeMap::from([
+                ("colors.surface_1".to_string(), "#000000".to_string()),
+                ("colors.surface_2".to_string(), "#101010".to_string()),
+                ("colors.accent".to_string(), "#ff0000".to_string()),
+            ]),
+        };
+        let palette = Palette::from_theme(&theme);
+        assert_eq!(palette.row_alt_bg, Color::Rgb(0x10, 0x10, 0x10));
+        assert_eq!(palette.playing_row_bg, Color::Rgb(46, 0, 0));
+        assert_eq!(palette.hover_bg, Color::Rgb(0x15, 0x1a, 0x23));
+
+        theme
+            .tokens
+            .insert("colors.playing_row_bg".into(), "#123456".into());
+        assert_eq!(
+            Palette::from_theme(&theme).playing_row_bg,
+            Color::Rgb(0x12, 0x34, 0x56)
+        );
+    }
+
+    #[test]
+    fn ansi16_mode_keeps_hues_and_removes_rgb() {
+        let palette = Palette::default().with_color_mode(ColorMode::Ansi16);
+        assert_eq!(palette.surface_0, Color::Black);
+        assert_eq!(palette.accent, Color::LightCyan);
+        assert_eq!(palette.danger, Color::LightRed);
+        assert_eq!(palette.accent_2, Color::LightYellow);
+        assert_ne!(palette.selection_bg, palette.surface_1);
+        assert!(!matches!(palette.text, Color::Rgb(..)));
+    }
+
+    #[test]
+    fn ansi256_mode_uses_cube_and_gray_ramp() {
+        assert_eq!(to_ansi256(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(
+            to_ansi256(Color::Rgb(0x30, 0x30, 0x30)),
+            Color::Indexed(236)
+        );
+        assert_eq!(to_ansi256(Color::Reset), Color::Reset);
+        assert_eq!(ColorMode::from_config("truecolor"), ColorMode::TrueColor);
+        assert_eq!(ColorMode::from_config("16"), ColorMode::Ansi16);
+    }
+
+    #[test]
+    fn auto_color_mode_follows_colorterm_then_term() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            ColorMode::detect(env(&[("COLORTERM", "truecolor"), ("TERM", "xterm")])),
+            ColorMode::TrueColor
+        );
+        assert_eq!(
+            ColorMode::detect(env(&[("COLORTERM", "24bit")])),
+            ColorMode::TrueColor
+        );
+        assert_eq!(
+            ColorMode::detect(env(&[("COLORTERM", ""), ("TERM", "tmux-256color")])),
+            ColorMode::Ansi256
+        );
+        assert_eq!(
+            ColorMode::detect(env(&[("TERM", "xterm")])),
+            ColorMode::Ansi16
+        );
+        assert_eq!(ColorMode::detect(env(&[])), ColorMode::Ansi16);
+    }
 }
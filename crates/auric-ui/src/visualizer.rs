@@ -1,13 +1,12 @@
-use crate::theme::Palette;
+use crate::glyphs::Glyphs;
+use crate::theme::{blend, Palette};
 use ratatui::prelude::*;
 use ratatui::widgets::Widget;
 use rustfft::{num_complex::Complex, FftPlanner};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
-const BRAILLE_BASE: u32 = 0x2800;
-const DOT_MAP: [[u8; 4]; 2] = [
-    [0x01, 0x02, 0x04, 0x40],
-    [0x08, 0x10, 0x20, 0x80],
-];
+const DOT_MAP: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VisualizerStyle {
@@ -48,6 +47,8 @@ pub struct VisualizerWidget<'a> {
     pub bands: &'a [f32],
     pub samples: &'a [f32],
     pub palette: &'a Palette,
+    /// Characters for the current unicode level.
+    pub glyphs: &'a Glyphs,
     pub frame_count: u64,
     pub fire_history: &'a [Vec<f32>],
 }
@@ -58,19 +59,33 @@ impl<'a> Widget for VisualizerWidget<'a> {
             return;
         }
         match self.style {
-            VisualizerStyle::Spectrum => render_spectrum(area, buf, self.bands, self.palette),
-            VisualizerStyle::Oscilloscope => {
-                render_oscilloscope(area, buf, self.samples, self.palette)
+            VisualizerStyle::Spectrum => {
+                render_spectrum(area, buf, self.bands, self.palette, self.glyphs)
             }
-            VisualizerStyle::Mirror => render_mirror(area, buf, self.bands, self.palette),
-            VisualizerStyle::Scatter => {
-                render_scatter(area, buf, self.bands, self.palette, self.frame_count)
+            VisualizerStyle::Oscilloscope => {
+                render_oscilloscope(area, buf, self.samples, self.palette, self.glyphs)
             }
-            VisualizerStyle::Wave => {
-                render_wave(area, buf, self.bands, self.palette, self.frame_count)
+            VisualizerStyle::Mirror => {
+                render_mirror(area, buf, self.bands, self.palette, self.glyphs)
             }
+            VisualizerStyle::Scatter => render_scatter(
+                area,
+                buf,
+                self.bands,
+                self.palette,
+                self.glyphs,
+                self.frame_count,
+            ),
+            VisualizerStyle::Wave => render_wave(
+                area,
+                buf,
+                self.bands,
+                self.palette,
+                self.glyphs,
+                self.frame_count,
+            ),
             VisualizerStyle::Fire => {
-                render_fire(area, buf, self.fire_history, self.palette)
+                render_fire(area, buf, self.fire_history, self.palette, self.glyphs)
             }
         }
     }
@@ -93,6 +108,7 @@ fn flush_dots(
     dots: &[u8],
     area: Rect,
     buf: &mut Buffer,
+    glyphs: &Glyphs,
     color_fn: &dyn Fn(u16, u16) -> Color,
 ) {
     let cols = area.width as usize;
@@ -101,11 +117,11 @@ fn flush_dots(
             let idx = cy * cols + cx;
             let pattern = if idx < dots.len() { dots[idx] } else { 0 };
             if pattern != 0 {
-                let ch = char::from_u32(BRAILLE_BASE + pattern as u32).unwrap_or(' ');
+                let symbol = glyphs.dot_cell(pattern);
                 let x = area.x + cx as u16;
                 let y = area.y + cy as u16;
                 if let Some(cell) = buf.cell_mut((x, y)) {
-                    cell.set_char(ch);
+                    cell.set_symbol(&symbol);
                     cell.set_fg(color_fn(x - area.x, y - area.y));
                 }
             }
@@ -113,8 +129,22 @@ fn flush_dots(
     }
 }
 
-// Helper: get frequency band color
+// Helper: get frequency band color, a smooth low-mid-high gradient when the
+// theme colors are RGB and three steps otherwise
 fn band_color(band_idx: usize, num_bands: usize, palette: &Palette) -> Color {
+    let (low, mid, high) = (
+        palette.visualizer_low,
+        palette.visualizer_mid,
+        palette.visualizer_high,
+    );
+    if let (Color::Rgb(..), Color::Rgb(..), Color::Rgb(..)) = (low, mid, high) {
+        let t = band_idx as f32 / num_bands.saturating_sub(1).max(1) as f32;
+        return if t < 0.5 {
+            blend(low, mid, t * 2.0)
+        } else {
+            blend(mid, high, t * 2.0 - 1.0)
+        };
+    }
     if band_idx < num_bands / 3 {
         palette.visualizer_low
     } else if band_idx < 2 * num_bands / 3 {
@@ -125,7 +155,13 @@ fn band_color(band_idx: usize, num_bands: usize, palette: &Palette) -> Color {
 }
 
 // --- Style: Spectrum (bars with gaps) ---
-fn render_spectrum(area: Rect, buf: &mut Buffer, bands: &[f32], palette: &Palette) {
+fn render_spectrum(
+    area: Rect,
+    buf: &mut Buffer,
+    bands: &[f32],
+    palette: &Palette,
+    glyphs: &Glyphs,
+) {
     if bands.is_empty() {
         return;
     }
@@ -150,14 +186,20 @@ fn render_spectrum(area: Rect, buf: &mut Buffer, bands: &[f32], palette: &Palett
             }
         }
     }
-    flush_dots(&dots, area, buf, &|x, _| {
+    flush_dots(&dots, area, buf, glyphs, &|x, _| {
         let bi = x as usize * 2 * num_bands / dot_cols.max(1);
         band_color(bi, num_bands, palette)
     });
 }
 
 // --- Style: Oscilloscope (waveform trace) ---
-fn render_oscilloscope(area: Rect, buf: &mut Buffer, samples: &[f32], palette: &Palette) {
+fn render_oscilloscope(
+    area: Rect,
+    buf: &mut Buffer,
+    samples: &[f32],
+    palette: &Palette,
+    glyphs: &Glyphs,
+) {
     if samples.is_empty() {
         return;
     }
@@ -172,7 +214,8 @@ fn render_oscilloscope(area: Rect, buf: &mut Buffer, samples: &[f32], palette: &
     for px in 0..dot_cols {
         let si = px * n / dot_cols;
         let s = samples[si.min(n - 1)];
-        let py = (center as f32 - s * center as f32 * 0.9).clamp(0.0, (dot_rows - 1) as f32) as usize;
+        let py =
+            (center as f32 - s * center as f32 * 0.9).clamp(0.0, (dot_rows - 1) as f32) as usize;
 
         // Bresenham-style line from previous point
         if let Some(prev) = prev_y {
@@ -186,11 +229,11 @@ fn render_oscilloscope(area: Rect, buf: &mut Buffer, samples: &[f32], palette: &
         prev_y = Some(py);
     }
 
-    flush_dots(&dots, area, buf, &|_, _| palette.accent);
+    flush_dots(&dots, area, buf, glyphs, &|_, _| palette.accent);
 }
 
 // --- Style: Mirror (symmetric spectrum from center) ---
-fn render_mirror(area: Rect, buf: &mut Buffer, bands: &[f32], palette: &Palette) {
+fn render_mirror(area: Rect, buf: &mut Buffer, bands: &[f32], palette: &Palette, glyphs: &Glyphs) {
     if bands.is_empty() {
         return;
     }
@@ -225,7 +268,7 @@ fn render_mirror(area: Rect, buf: &mut Buffer, bands: &[f32], palette: &Palette)
             }
         }
     }
-    flush_dots(&dots, area, buf, &|x, _| {
+    flush_dots(&dots, area, buf, glyphs, &|x, _| {
         let bi = x as usize * 2 * num_bands / dot_cols.max(1);
         band_color(bi, num_bands, palette)
     });
@@ -237,6 +280,7 @@ fn render_scatter(
     buf: &mut Buffer,
     bands: &[f32],
     palette: &Palette,
+    glyphs: &Glyphs,
     frame: u64,
 ) {
     if bands.is_empty() {
@@ -256,7 +300,10 @@ fn render_scatter(
 
         for d in 0..num_dots {
             // Use frame + band + dot index for pseudo-random positioning
-            let seed = (frame.wrapping_mul(31).wrapping_add(i as u64 * 97).wrapping_add(d as u64 * 53)) as usize;
+            let seed = (frame
+                .wrapping_mul(31)
+                .wrapping_add(i as u64 * 97)
+                .wrapping_add(d as u64 * 53)) as usize;
             let px = band_x + (seed % spread);
             let height = (mag * dot_rows as f32 * 0.8) as usize;
             let py = dot_rows - 1 - (seed / 3 % height.max(1));
@@ -265,7 +312,7 @@ fn render_scatter(
             }
         }
     }
-    flush_dots(&dots, area, buf, &|x, _| {
+    flush_dots(&dots, area, buf, glyphs, &|x, _| {
         let bi = x as usize * 2 * num_bands / dot_cols.max(1);
         band_color(bi, num_bands, palette)
     });
@@ -277,6 +324,7 @@ fn render_wave(
     buf: &mut Buffer,
     bands: &[f32],
     palette: &Palette,
+    glyphs: &Glyphs,
     frame: u64,
 ) {
     if bands.is_empty() {
@@ -302,8 +350,8 @@ fn render_wave(
 
         let amplitude = (0.2 + local_mag * 0.7) * center as f32;
         let phase = t * std::f32::consts::PI * 4.0 + time;
-        let py = (center as f32 + amplitude * phase.sin())
-            .clamp(0.0, (dot_rows - 1) as f32) as usize;
+        let py =
+            (center as f32 + amplitude * phase.sin()).clamp(0.0, (dot_rows - 1) as f32) as usize;
 
         if let Some(prev) = prev_y {
             let (y0, y1) = if prev < py { (prev, py) } else { (py, prev) };
@@ -315,7 +363,7 @@ fn render_wave(
         }
         prev_y = Some(py);
     }
-    flush_dots(&dots, area, buf, &|x, _| {
+    flush_dots(&dots, area, buf, glyphs, &|x, _| {
         let bi = x as usize * 2 * num_bands / dot_cols.max(1);
         band_color(bi, num_bands, palette)
     });
@@ -327,6 +375,7 @@ fn render_fire(
     buf: &mut Buffer,
     history: &[Vec<f32>],
     palette: &Palette,
+    glyphs: &Glyphs,
 ) {
     let dot_cols = area.width as usize * 2;
     let dot_rows = area.height as usize * 4;
@@ -360,7 +409,7 @@ fn render_fire(
         }
     }
 
-    flush_dots(&dots, area, buf, &|_, y| {
+    flush_dots(&dots, area, buf, glyphs, &|_, y| {
         // Color gradient: bottom (hot) to top (cool)
         let t = y as f32 / area.height as f32;
         if t > 0.7 {
@@ -375,11 +424,25 @@ fn render_fire(
 
 // --- FFT Analysis ---
 
+/// Duration of the most recent [`analyze_spectrum`] call, for the debug overlay.
+static LAST_ANALYSIS_NANOS: AtomicU64 = AtomicU64::new(0);
+
+pub fn last_analysis_time() -> Duration {
+    Duration::from_nanos(LAST_ANALYSIS_NANOS.load(Ordering::Relaxed))
+}
+
 pub fn analyze_spectrum(samples: &[f32], num_bands: usize) -> Vec<f32> {
     if samples.is_empty() || num_bands == 0 {
         return vec![0.0; num_bands];
     }
+    let started = Instant::now();
+    let bands = compute_bands(samples, num_bands);
+    let nanos = u64::try_from(started.elapsed().as_nanos()).unwrap_or(u64::MAX);
+    LAST_ANALYSIS_NANOS.store(nanos, Ordering::Relaxed);
+    bands
+}
 
+fn compute_bands(samples: &[f32], num_bands: usize) -> Vec<f32> {
     let fft_size = 1024;
 
     let mut buffer: Vec<Complex<f32>> = (0..fft_size)
@@ -402,10 +465,8 @@ pub fn analyze_spectrum(samples: &[f32], num_bands: usize) -> Vec<f32> {
 
     let mut bands = vec![0.0f32; num_bands];
     for (band_idx, band_val) in bands.iter_mut().enumerate() {
-        let freq_lo =
-            20.0 * (16000.0f32 / 20.0).powf(band_idx as f32 / num_bands as f32);
-        let freq_hi =
-            20.0 * (16000.0f32 / 20.0).powf((band_idx + 1) as f32 / num_bands as f32);
+        let freq_lo = 20.0 * (16000.0f32 / 20.0).powf(band_idx as f32 / num_bands as f32);
+        let freq_hi = 20.0 * (16000.0f32 / 20.0).powf((band_idx + 1) as f32 / num_bands as f32);
         let bin_lo = (freq_lo * fft_size as f32 / 44100.0) as usize;
         let bin_hi = ((freq_hi * fft_size as f32 / 44100.0) as usize)
             .min(magnitudes.len())